@@ -60,6 +60,18 @@ impl HexColor {
         }
     }
 
+    /// Convert to X11 Color with the color channels premultiplied by alpha (RENDER fills)
+    pub fn to_premultiplied_x11_color(self) -> Color {
+        let color = self.to_x11_color();
+        let premultiply = |v: u16| (v as u32 * color.alpha as u32 / 0xFFFF) as u16;
+        Color {
+            red: premultiply(color.red),
+            green: premultiply(color.green),
+            blue: premultiply(color.blue),
+            alpha: color.alpha,
+        }
+    }
+
 }
 
 /// Opacity as percentage (0-100)
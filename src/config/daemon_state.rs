@@ -9,12 +9,12 @@ use anyhow::{Context, Result};
 // keep serde usages local to the config/profile module
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
 use tracing::{error, info};
 use x11rb::protocol::render::Color;
 
+use super::format::{self, ConfigFormat};
 use crate::color::{HexColor, Opacity};
-use crate::types::{CharacterSettings, Position, TextOffset};
+use crate::types::{CharacterSettings, Position, TextOffset, TitlePattern};
 
 
 // ==============================================================================
@@ -28,10 +28,16 @@ use crate::types::{CharacterSettings, Position, TextOffset};
 /// Per-character dimensions are stored in CharacterSettings, not here
 pub struct DisplayConfig {
     pub opacity: u32,
+    pub opacity_mode: crate::config::profile::OpacityMode,
+    pub monitor_colors: Vec<crate::config::profile::MonitorColor>,
     pub border_size: u16,
     pub border_color: Color,
+    pub scale_filter: crate::config::profile::ScaleFilter,
     pub text_offset: TextOffset,
     pub text_color: u32,
+    /// Label background box color (premultiplied), None = no box
+    pub text_background: Option<Color>,
+    pub text_background_padding: u16,
     pub hide_when_no_focus: bool,
 }
 
@@ -50,13 +56,6 @@ pub struct PersistentState {
 }
 
 impl PersistentState {
-    fn config_path() -> PathBuf {
-        let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-        path.push(crate::constants::config::APP_DIR);
-        path.push(crate::constants::config::FILENAME);
-        path
-    }
-
     /// Create empty state (will be populated via IPC SetProfile message)
     /// This is used by the preview daemon when starting - it waits for GUI to send config
     pub fn empty() -> Self {
@@ -69,6 +68,24 @@ impl PersistentState {
         }
     }
 
+    /// Per-character settings as configured in the GUI (includes client options)
+    /// Runtime positions live in `character_positions`, options come from the profile
+    pub fn configured_character(&self, character_name: &str) -> Option<&CharacterSettings> {
+        self.profile.character_positions.get(character_name)
+    }
+
+    /// Replace a character's configured options (live edit from the GUI)
+    /// Runtime position is kept; new dimensions (non-zero) also apply to the runtime entry
+    pub fn set_character_settings(&mut self, character_name: &str, settings: CharacterSettings) {
+        let dimensions = settings.dimensions;
+        if dimensions.width > 0 && dimensions.height > 0
+            && let Some(runtime) = self.character_positions.get_mut(character_name)
+        {
+            runtime.set_geometry(runtime.x, runtime.y, dimensions.width, dimensions.height);
+        }
+        self.profile.character_positions.insert(character_name.to_string(), settings);
+    }
+
     /// Get default thumbnail dimensions for screen size
     pub fn default_thumbnail_size(&self, _screen_width: u16, _screen_height: u16) -> (u16, u16) {
         // Use configured default dimensions from global settings
@@ -97,22 +114,44 @@ impl PersistentState {
                 HexColor::from_argb32(0xFF_FF_FF_FF).argb32()
             });
         
+        let text_background = match self.profile.text_background.trim() {
+            "" => None,
+            hex => HexColor::parse(hex).map(HexColor::to_premultiplied_x11_color).or_else(|| {
+                error!(text_background = %hex, "Invalid text_background hex, drawing no label box");
+                None
+            }),
+        };
+        
         let opacity = Opacity::from_percent(self.profile.opacity_percent).to_argb32();
         
         DisplayConfig {
             opacity,
+            opacity_mode: self.global.opacity_mode,
+            monitor_colors: self.global.monitor_colors.clone(),
             border_size: self.profile.border_size,
             border_color,
+            scale_filter: self.profile.scale_filter,
             text_offset: TextOffset::from_border_edge(self.profile.text_x, self.profile.text_y),
             text_color,
+            text_background,
+            text_background_padding: self.profile.text_background_padding,
             hide_when_no_focus: self.global.hide_when_no_focus,
         }
     }
+
+    /// Client title pattern compiled from the global settings (the default one if invalid)
+    pub fn title_pattern(&self) -> TitlePattern {
+        TitlePattern::new(&self.global.title_pattern)
+            .unwrap_or_else(|e| {
+                error!(title_pattern = %self.global.title_pattern, error = %e, "Invalid title_pattern, using default");
+                TitlePattern::default()
+            })
+    }
     pub fn load() -> Self {
         // Load new profile-based config format
-        let config_path = Self::config_path();
+        let config_path = crate::config::profile::Config::path();
         if let Ok(contents) = fs::read_to_string(&config_path) {
-            match serde_json::from_str::<crate::config::profile::Config>(&contents) {
+            match format::parse(&contents, ConfigFormat::detect(&config_path, &contents)) {
                 Ok(profile_config) => {
                     info!("Loading daemon config from profile-based format");
                     return Self::from_profile_config(profile_config);
@@ -145,11 +184,13 @@ impl PersistentState {
         info!(profile = %profile.name, "Using profile for daemon settings");
         
         // Just clone the structs - no conversion!
-        PersistentState {
+        let mut state = PersistentState {
             profile: profile.clone(),
             global: config.global.clone(),
             character_positions: profile.character_positions.clone(),
-        }
+        };
+        super::overrides::apply(&mut state.profile, &mut state.global);
+        state
     }
 
     /// Load config with screen size for smart defaults
@@ -165,9 +206,9 @@ impl PersistentState {
     /// NOTE: This method is now deprecated - GUI owns all config writes via IPC
     pub fn save(&self) -> Result<()> {
         // Load the profile-based config
-        let config_path = Self::config_path();
+        let config_path = crate::config::profile::Config::path();
         let mut profile_config = if let Ok(contents) = fs::read_to_string(&config_path) {
-            serde_json::from_str::<crate::config::profile::Config>(&contents)
+            format::parse(&contents, ConfigFormat::detect(&config_path, &contents))
                 .context("Failed to parse profile config for save")?
         } else {
             // No config exists, create default
@@ -185,7 +226,14 @@ impl PersistentState {
         // Merge character positions: keep existing positions, add/update only those we have
         let profile_positions = &mut profile_config.profiles[profile_idx].character_positions;
         for (char_name, char_settings) in &self.character_positions {
-            profile_positions.insert(char_name.clone(), char_settings.clone());
+            profile_positions.entry(char_name.clone())
+                .and_modify(|existing| existing.set_geometry(
+                    char_settings.x,
+                    char_settings.y,
+                    char_settings.dimensions.width,
+                    char_settings.dimensions.height,
+                ))
+                .or_insert(char_settings.clone());
         }
         
         // Save the updated profile config
@@ -0,0 +1,262 @@
+//! Config file formats: JSON (default) and TOML
+//!
+//! Both hold the same `Config`. TOML is read by converting the parsed document
+//! to a JSON value and deserializing that, so serde defaults and renames apply
+//! exactly as for JSON. It is written by a small emitter below: plain values
+//! first, then tables, then arrays of tables (`[[profiles]]`). Nulls have no
+//! TOML form and are left out; every optional field reads a missing key as
+//! unset. Comments aren't kept when the manager rewrites a TOML file.
+
+use anyhow::{bail, Context, Result};
+use serde_json::{Map, Number, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item};
+
+use super::{inheritance, layouts};
+use super::profile::Config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// Format of a config file: by extension, else by its contents (JSON starts with `{`)
+    pub fn detect(path: &Path, contents: &str) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
+            Some("json") => ConfigFormat::Json,
+            _ => Self::sniff(contents),
+        }
+    }
+
+    /// Format guessed from the contents alone
+    pub fn sniff(contents: &str) -> Self {
+        if contents.trim_start().starts_with('{') {
+            ConfigFormat::Json
+        } else {
+            ConfigFormat::Toml
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "JSON",
+            ConfigFormat::Toml => "TOML",
+        }
+    }
+
+    /// "toml" / "json" as given on the command line
+    pub fn parse_name(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "toml" => Ok(ConfigFormat::Toml),
+            "json" => Ok(ConfigFormat::Json),
+            _ => Err(format!("'{}' is not a config format (use toml or json)", value)),
+        }
+    }
+
+    /// Config filename for this format
+    fn filename(self) -> &'static str {
+        match self {
+            ConfigFormat::Json => crate::constants::config::FILENAME,
+            ConfigFormat::Toml => crate::constants::config::TOML_FILENAME,
+        }
+    }
+}
+
+/// Rewrite the config file in another format (`eve-l-preview convert-config`)
+/// The old file is kept as `<name>.bak`; returns the new file's path
+pub fn convert(to: ConfigFormat) -> Result<PathBuf> {
+    let from_path = Config::path();
+    let contents = fs::read_to_string(&from_path)
+        .with_context(|| format!("Failed to read config from {:?}", from_path))?;
+    let from = ConfigFormat::detect(&from_path, &contents);
+    if from == to {
+        bail!("{:?} is already {}", from_path, to.name());
+    }
+    let config = parse(&contents, from)
+        .with_context(|| format!("Failed to parse {} from {:?}", from.name(), from_path))?;
+
+    let to_path = from_path.with_file_name(to.filename());
+    crate::permissions::write_private(&to_path, to_string(&config, to)?.as_bytes())
+        .with_context(|| format!("Failed to write config to {:?}", to_path))?;
+    let backup = from_path.with_file_name(format!("{}.bak", from.filename()));
+    fs::rename(&from_path, &backup)
+        .with_context(|| format!("Failed to move {:?} aside to {:?}", from_path, backup))?;
+    Ok(to_path)
+}
+
+/// Parse a config file's contents
+pub fn parse(contents: &str, format: ConfigFormat) -> Result<Config> {
+    let mut value = match format {
+        ConfigFormat::Json => serde_json::from_str(contents).context("Failed to parse JSON config")?,
+        ConfigFormat::Toml => {
+            let document: DocumentMut = contents.parse().context("Failed to parse TOML config")?;
+            table_to_json(document.as_table())?
+        }
+    };
+    let own_fields = inheritance::resolve(&mut value);
+    let mut config: Config = serde_json::from_value(value)
+        .context(format!("Invalid value in {} config", format.name()))?;
+    for (profile, own) in config.profiles.iter_mut().zip(own_fields) {
+        profile.own_fields = own;
+    }
+    layouts::resolve(&mut config);
+    Ok(config)
+}
+
+/// Serialize a config for writing to disk
+pub fn to_string(config: &Config, format: ConfigFormat) -> Result<String> {
+    let mut value = serde_json::to_value(config).context("Failed to serialize config")?;
+    inheritance::strip(config, &mut value);
+    layouts::strip(config, &mut value);
+    match format {
+        ConfigFormat::Json => serde_json::to_string_pretty(&value).context("Failed to serialize config to JSON"),
+        ConfigFormat::Toml => {
+            let Value::Object(root) = value else {
+                bail!("Config did not serialize to a table");
+            };
+            let mut out = String::new();
+            write_table(&mut out, &[], &root, false);
+            Ok(out.trim_start().to_string())
+        }
+    }
+}
+
+fn table_to_json(table: &toml_edit::Table) -> Result<Value> {
+    let mut map = Map::new();
+    for (key, item) in table.iter() {
+        if let Some(value) = item_to_json(item)? {
+            map.insert(key.to_string(), value);
+        }
+    }
+    Ok(Value::Object(map))
+}
+
+fn item_to_json(item: &Item) -> Result<Option<Value>> {
+    Ok(match item {
+        Item::None => None,
+        Item::Value(value) => Some(value_to_json(value)?),
+        Item::Table(table) => Some(table_to_json(table)?),
+        Item::ArrayOfTables(tables) => Some(Value::Array(tables.iter().map(table_to_json).collect::<Result<_>>()?)),
+    })
+}
+
+fn value_to_json(value: &toml_edit::Value) -> Result<Value> {
+    use toml_edit::Value as Toml;
+    Ok(match value {
+        Toml::String(s) => Value::String(s.value().clone()),
+        Toml::Integer(i) => Value::from(*i.value()),
+        Toml::Float(f) => Number::from_f64(*f.value())
+            .map(Value::Number)
+            .context(format!("Unsupported float {} in TOML config", f.value()))?,
+        Toml::Boolean(b) => Value::Bool(*b.value()),
+        Toml::Datetime(d) => Value::String(d.value().to_string()),
+        Toml::Array(array) => Value::Array(array.iter().map(value_to_json).collect::<Result<_>>()?),
+        Toml::InlineTable(table) => {
+            let mut map = Map::new();
+            for (key, value) in table.iter() {
+                map.insert(key.to_string(), value_to_json(value)?);
+            }
+            Value::Object(map)
+        }
+    })
+}
+
+/// Bare key when possible, quoted otherwise (character names have spaces)
+fn key(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        name.to_string()
+    } else {
+        // JSON string escapes are valid in TOML basic strings
+        Value::String(name.to_string()).to_string()
+    }
+}
+
+fn header(path: &[String]) -> String {
+    path.iter().map(|name| key(name)).collect::<Vec<_>>().join(".")
+}
+
+/// Arrays written as [[tables]]: non-empty and made of tables only
+fn is_table_array(value: &Value) -> bool {
+    value.as_array().is_some_and(|items| !items.is_empty() && items.iter().all(Value::is_object))
+}
+
+/// Value written inline (`key = ...`)
+fn inline(value: &Value) -> String {
+    match value {
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().filter(|item| !item.is_null()).map(inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(map) => {
+            let fields: Vec<String> = map.iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(name, value)| format!("{} = {}", key(name), inline(value)))
+                .collect();
+            if fields.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{ {} }}", fields.join(", "))
+            }
+        }
+        // serde_json prints floats with a fraction ("1.0"), as TOML wants
+        other => other.to_string(),
+    }
+}
+
+/// Write one table's keys, then its sub-tables and arrays of tables
+fn write_table(out: &mut String, path: &[String], table: &Map<String, Value>, array_item: bool) {
+    let plain: Vec<(&String, &Value)> = table.iter()
+        .filter(|(_, value)| !value.is_null() && !value.is_object() && !is_table_array(value))
+        .collect();
+    if array_item {
+        out.push_str(&format!("\n[[{}]]\n", header(path)));
+    } else if !path.is_empty() && (!plain.is_empty() || !table.values().any(Value::is_object)) {
+        // Tables holding only other tables are implied by their headers
+        out.push_str(&format!("\n[{}]\n", header(path)));
+    }
+    for (name, value) in plain {
+        out.push_str(&format!("{} = {}\n", key(name), inline(value)));
+    }
+    for (name, value) in table {
+        let child = [path, std::slice::from_ref(name)].concat();
+        if let Value::Object(sub) = value {
+            write_table(out, &child, sub, false);
+        } else if is_table_array(value) {
+            for item in value.as_array().into_iter().flatten().filter_map(Value::as_object) {
+                write_table(out, &child, item, true);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CharacterSettings;
+
+    #[test]
+    fn test_toml_round_trip() {
+        let mut config = Config::default();
+        config.global.window_x = None;
+        config.global.window_y = Some(-20);
+        config.profiles[0].character_positions.insert("Some Pilot".to_string(), CharacterSettings::new(10, 20, 480, 270));
+        let toml = to_string(&config, ConfigFormat::Toml).unwrap();
+        assert!(toml.contains("[[profiles]]"));
+        assert!(toml.contains("[profiles.characters.\"Some Pilot\"]"));
+
+        let parsed = parse(&toml, ConfigFormat::Toml).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), serde_json::to_value(&config).unwrap());
+    }
+
+    #[test]
+    fn test_format_detection() {
+        assert_eq!(ConfigFormat::detect(Path::new("a/eve-l-preview.toml"), "{"), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::detect(Path::new("a/eve-l-preview.json"), ""), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::detect(Path::new("a/config"), "  {\"global\": {}}"), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::detect(Path::new("a/config"), "# mine\n[global]\n"), ConfigFormat::Toml);
+    }
+}
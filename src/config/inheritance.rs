@@ -0,0 +1,268 @@
+//! Profile inheritance (`inherits = "base"`)
+//!
+//! A profile naming a base profile only keeps the settings it changes; the
+//! rest is filled in from the base (itself possibly inheriting) when the file
+//! is read, so the rest of the program only sees complete profiles. Name,
+//! description, characters, their layout and the per-monitor-setup positions
+//! are never inherited. When saving, an inheriting profile drops every value
+//! equal to its base's, except the fields it set itself
+//! (`Profile::own_fields`). A base that doesn't exist or a loop of profiles
+//! inheriting from each other turns inheritance off for the profiles
+//! involved, with a warning.
+
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use tracing::warn;
+
+use super::profile::{Config, Profile};
+
+/// Profile fields that always belong to the profile itself
+const NOT_INHERITED: [&str; 6] = ["name", "description", "inherits", "characters", "layout", "monitor_positions"];
+
+/// Base profile index of each profile, None without one or when its chain is broken
+fn bases(links: &[(String, Option<String>)]) -> Vec<Option<usize>> {
+    let index = |name: &str| links.iter().position(|(n, _)| n == name);
+    (0..links.len())
+        .map(|idx| {
+            let base = index(links[idx].1.as_deref()?);
+            // The whole chain has to end in a profile without a base
+            let mut seen = vec![idx];
+            let mut current = idx;
+            while let Some(name) = &links[current].1 {
+                current = index(name)?;
+                if seen.contains(&current) {
+                    return None;
+                }
+                seen.push(current);
+            }
+            base
+        })
+        .collect()
+}
+
+fn links_of_json(profiles: &[Value]) -> Vec<(String, Option<String>)> {
+    profiles.iter()
+        .map(|p| (
+            p["name"].as_str().unwrap_or_default().to_string(),
+            p["inherits"].as_str().map(str::to_string),
+        ))
+        .collect()
+}
+
+fn links_of(profiles: &[Profile]) -> Vec<(String, Option<String>)> {
+    profiles.iter().map(|p| (p.name.clone(), p.inherits.clone())).collect()
+}
+
+/// Fill in inherited settings in a config file's contents
+/// Returns the fields each profile sets itself (empty for profiles without a base)
+pub fn resolve(config: &mut Value) -> Vec<BTreeSet<String>> {
+    let Some(profiles) = config.get_mut("profiles").and_then(Value::as_array_mut) else {
+        return Vec::new();
+    };
+    let links = links_of_json(profiles);
+    let bases = bases(&links);
+    for (idx, (name, inherits)) in links.iter().enumerate() {
+        if let Some(base) = inherits
+            && bases[idx].is_none()
+        {
+            warn!(profile = %name, base = %base, "Profile inherits from a missing profile or a loop of profiles, inheritance ignored");
+        }
+    }
+
+    let own: Vec<BTreeSet<String>> = profiles.iter().zip(&bases)
+        .map(|(profile, base)| match (profile.as_object(), base) {
+            (Some(object), Some(_)) => object.keys()
+                .filter(|key| !NOT_INHERITED.contains(&key.as_str()))
+                .cloned()
+                .collect(),
+            _ => BTreeSet::new(),
+        })
+        .collect();
+
+    // Bases first: keep going until every profile with a base has been merged
+    let mut done: Vec<bool> = bases.iter().map(Option::is_none).collect();
+    while done.iter().any(|d| !d) {
+        for idx in 0..profiles.len() {
+            let Some(base) = bases[idx].filter(|&base| !done[idx] && done[base]) else {
+                continue;
+            };
+            let mut merged: Map<String, Value> = profiles[base].as_object().cloned().unwrap_or_default();
+            merged.retain(|key, _| !NOT_INHERITED.contains(&key.as_str()));
+            if let Some(object) = profiles[idx].as_object() {
+                merged.extend(object.clone());
+            }
+            profiles[idx] = Value::Object(merged);
+            done[idx] = true;
+        }
+    }
+    own
+}
+
+/// Drop inherited values from a serialized config before it is written
+pub fn strip(config: &Config, value: &mut Value) {
+    let Some(profiles) = value.get_mut("profiles").and_then(Value::as_array_mut) else {
+        return;
+    };
+    let bases = bases(&links_of(&config.profiles));
+    for (idx, base) in bases.iter().enumerate() {
+        let (Some(base), Some(object)) = (base, profiles.get_mut(idx).and_then(Value::as_object_mut)) else {
+            continue;
+        };
+        let Ok(Value::Object(base_values)) = serde_json::to_value(&config.profiles[*base]) else {
+            continue;
+        };
+        let own = &config.profiles[idx].own_fields;
+        object.retain(|key, value| {
+            NOT_INHERITED.contains(&key.as_str()) || own.contains(key) || base_values.get(key) != Some(value)
+        });
+    }
+}
+
+impl Config {
+    /// Follow up on a change to the profile at `idx` in the manager: values now
+    /// differing from its base become its own, and profiles inheriting from it
+    /// pick up its new values
+    pub fn profile_edited(&mut self, idx: usize) {
+        let bases = bases(&links_of(&self.profiles));
+        if let Some(base) = bases.get(idx).copied().flatten() {
+            let (Ok(Value::Object(values)), Ok(Value::Object(base_values))) =
+                (serde_json::to_value(&self.profiles[idx]), serde_json::to_value(&self.profiles[base]))
+            else {
+                return;
+            };
+            let changed: Vec<String> = values.into_iter()
+                .filter(|(key, value)| !NOT_INHERITED.contains(&key.as_str()) && base_values.get(key) != Some(value))
+                .map(|(key, _)| key)
+                .collect();
+            self.profiles[idx].own_fields.extend(changed);
+        }
+
+        // Profiles below `idx`, nearest first so each copies from an updated base
+        let depth_below = |mut j: usize| {
+            let mut depth = 0;
+            while let Some(base) = bases[j] {
+                depth += 1;
+                if base == idx {
+                    return Some(depth);
+                }
+                j = base;
+            }
+            None
+        };
+        let mut below: Vec<(usize, usize)> = (0..self.profiles.len())
+            .filter_map(|j| depth_below(j).map(|depth| (depth, j)))
+            .collect();
+        below.sort();
+        for (_, j) in below {
+            let Some(base) = bases[j] else {
+                continue;
+            };
+            let (Ok(Value::Object(mut values)), Ok(Value::Object(base_values))) =
+                (serde_json::to_value(&self.profiles[j]), serde_json::to_value(&self.profiles[base]))
+            else {
+                continue;
+            };
+            let own = self.profiles[j].own_fields.clone();
+            for (key, value) in base_values {
+                if !NOT_INHERITED.contains(&key.as_str()) && !own.contains(&key) {
+                    values.insert(key, value);
+                }
+            }
+            if let Ok(mut updated) = serde_json::from_value::<Profile>(Value::Object(values)) {
+                updated.own_fields = own;
+                self.profiles[j] = updated;
+            }
+        }
+    }
+
+    /// Profiles that can be the base of the profile at `idx` (not itself, nor one inheriting from it)
+    pub fn possible_bases(&self, idx: usize) -> Vec<String> {
+        let links = links_of(&self.profiles);
+        (0..self.profiles.len())
+            .filter(|&j| {
+                let mut current = Some(j);
+                let mut steps = 0;
+                while let Some(k) = current {
+                    if k == idx || steps > links.len() {
+                        return false;
+                    }
+                    current = links[k].1.as_deref().and_then(|name| links.iter().position(|(n, _)| n == name));
+                    steps += 1;
+                }
+                true
+            })
+            .map(|j| self.profiles[j].name.clone())
+            .collect()
+    }
+
+    /// Point profiles inheriting from `old` at `new` (None: the base is gone, they keep their current values)
+    pub fn rename_base(&mut self, old: &str, new: Option<&str>) {
+        for profile in &mut self.profiles {
+            if profile.inherits.as_deref() == Some(old) {
+                profile.inherits = new.map(str::to_string);
+                if new.is_none() {
+                    profile.own_fields.clear();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolve_fills_in_chain_and_strip_undoes_it() {
+        let mut value = json!({ "profiles": [
+            { "name": "pvp", "inherits": "base", "cycle_group": ["FC"] },
+            { "name": "base", "opacity_percent": 60, "border_size": 2, "border_color": "#FF0000",
+              "text_size": 18, "text_x": 5, "text_y": 5, "text_color": "#FFFFFF" },
+            { "name": "pvp-small", "inherits": "pvp", "text_size": 12 },
+        ]});
+        let own = resolve(&mut value);
+        let mut config: Config = serde_json::from_value(value).unwrap();
+        for (profile, own) in config.profiles.iter_mut().zip(own) {
+            profile.own_fields = own;
+        }
+        let small = &config.profiles[2];
+        assert_eq!((small.opacity_percent, small.text_size), (60, 12));
+        assert_eq!(small.cycle_group, vec!["FC".to_string()]);
+
+        let mut saved = serde_json::to_value(&config).unwrap();
+        strip(&config, &mut saved);
+        let pvp = saved["profiles"][0].as_object().unwrap();
+        assert_eq!(pvp.keys().cloned().collect::<Vec<_>>(), vec!["characters", "cycle_group", "description", "inherits", "name"]);
+        assert_eq!(saved["profiles"][1]["opacity_percent"], 60);
+    }
+
+    #[test]
+    fn test_edits_propagate_to_inheriting_profiles() {
+        let mut config = Config::default();
+        let mut child = Profile::default_with_name("child".to_string(), String::new());
+        child.inherits = Some(config.profiles[0].name.clone());
+        config.profiles.push(child);
+
+        config.profiles[1].text_size = 30;
+        config.profile_edited(1);
+        config.profiles[0].opacity_percent = 20;
+        config.profiles[0].text_size = 10;
+        config.profile_edited(0);
+        assert_eq!(config.profiles[1].opacity_percent, 20);
+        assert_eq!(config.profiles[1].text_size, 30);
+        assert_eq!(config.possible_bases(0), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_broken_chains_are_ignored() {
+        let links = vec![
+            ("a".to_string(), Some("b".to_string())),
+            ("b".to_string(), Some("a".to_string())),
+            ("c".to_string(), Some("missing".to_string())),
+            ("d".to_string(), Some("e".to_string())),
+            ("e".to_string(), None),
+        ];
+        assert_eq!(bases(&links), vec![None, None, None, Some(4), None]);
+    }
+}
@@ -0,0 +1,159 @@
+//! Shared position layouts (`layout = "desk"`)
+//!
+//! Profiles naming the same layout share one set of characters (positions,
+//! sizes and the other per-character settings) kept in the file's top-level
+//! `layouts` table, so switching between them doesn't move any thumbnail. In
+//! memory every such profile holds its own copy in `character_positions`, as
+//! any other profile does: copies are filled in when the file is read and
+//! written back to the layout when it is saved, the selected profile's copy
+//! winning (it is the one the daemon and the manager update).
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::profile::Config;
+use crate::types::CharacterSettings;
+
+/// Profile whose characters are the current state of each layout
+fn sources(config: &Config) -> HashMap<&str, usize> {
+    let mut sources = HashMap::new();
+    for (idx, profile) in config.profiles.iter().enumerate() {
+        let Some(layout) = profile.layout.as_deref() else {
+            continue;
+        };
+        let selected = profile.name == config.global.selected_profile;
+        sources.entry(layout)
+            .and_modify(|source| if selected { *source = idx })
+            .or_insert(idx);
+    }
+    sources
+}
+
+/// Characters a profile gets when it joins a layout: the layout's, plus its own ones the layout lacks
+fn joined(own: &HashMap<String, CharacterSettings>, layout: &HashMap<String, CharacterSettings>) -> HashMap<String, CharacterSettings> {
+    let mut characters = own.clone();
+    characters.extend(layout.iter().map(|(name, settings)| (name.clone(), settings.clone())));
+    characters
+}
+
+/// Give each profile in a layout the layout's characters, after the file was read
+pub fn resolve(config: &mut Config) {
+    let Config { profiles, layouts, .. } = config;
+    for profile in profiles.iter_mut() {
+        let Some(name) = &profile.layout else {
+            continue;
+        };
+        match layouts.get(name) {
+            Some(layout) => {
+                profile.character_positions = joined(&profile.character_positions, layout);
+            }
+            // First profile naming a new layout starts it with its characters
+            None => {
+                layouts.insert(name.clone(), profile.character_positions.clone());
+            }
+        }
+    }
+}
+
+/// Move the characters of profiles in a layout to `layouts` in a serialized config
+pub fn strip(config: &Config, value: &mut Value) {
+    let mut layouts = serde_json::Map::new();
+    for (name, characters) in &config.layouts {
+        layouts.insert(name.clone(), serde_json::to_value(characters).unwrap_or_default());
+    }
+    for (name, idx) in sources(config) {
+        let characters = &config.profiles[idx].character_positions;
+        layouts.insert(name.to_string(), serde_json::to_value(characters).unwrap_or_default());
+    }
+    if let Some(profiles) = value.get_mut("profiles").and_then(Value::as_array_mut) {
+        for (profile, object) in config.profiles.iter().zip(profiles) {
+            if profile.layout.is_some()
+                && let Some(object) = object.as_object_mut()
+            {
+                object.remove("characters");
+            }
+        }
+    }
+    if let Some(root) = value.as_object_mut() {
+        if layouts.is_empty() {
+            root.remove("layouts");
+        } else {
+            root.insert("layouts".to_string(), Value::Object(layouts));
+        }
+    }
+}
+
+impl Config {
+    /// Bring every profile in a layout in line with the layout's current state
+    /// Call before the selected profile changes, its copy is the up to date one
+    pub fn share_layouts(&mut self) {
+        let sources: Vec<(String, usize)> = sources(self).into_iter()
+            .map(|(name, idx)| (name.to_string(), idx))
+            .collect();
+        for (name, source) in sources {
+            let characters = self.profiles[source].character_positions.clone();
+            for profile in &mut self.profiles {
+                if profile.layout.as_deref() == Some(name.as_str()) {
+                    profile.character_positions = characters.clone();
+                }
+            }
+            self.layouts.insert(name, characters);
+        }
+    }
+
+    /// Move the profile at `idx` into a layout (None: back to characters of its own, starting from a copy)
+    pub fn set_layout(&mut self, idx: usize, layout: Option<String>) {
+        self.share_layouts();
+        if let Some(name) = &layout {
+            let characters = match self.layouts.get(name) {
+                Some(shared) => joined(&self.profiles[idx].character_positions, shared),
+                None => self.profiles[idx].character_positions.clone(),
+            };
+            self.profiles[idx].layout = layout.clone();
+            for profile in &mut self.profiles {
+                if profile.layout == layout {
+                    profile.character_positions = characters.clone();
+                }
+            }
+            self.layouts.insert(name.clone(), characters);
+        } else {
+            self.profiles[idx].layout = None;
+        }
+    }
+
+    /// Names of the layouts in the config
+    pub fn layout_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.layouts.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::profile::Profile;
+
+    #[test]
+    fn test_profiles_in_a_layout_share_characters() {
+        let mut config = Config::default();
+        config.global.selected_profile = "pvp".to_string();
+        config.profiles[0].character_positions.insert("Main".to_string(), CharacterSettings::new(1, 2, 480, 270));
+        config.profiles.push(Profile::default_with_name("pvp".to_string(), String::new()));
+        config.set_layout(0, Some("desk".to_string()));
+        config.set_layout(1, Some("desk".to_string()));
+        assert_eq!(config.profiles[1].character_positions["Main"].x, 1);
+
+        // The selected profile's copy is saved to the layout
+        config.profiles[1].character_positions.get_mut("Main").unwrap().x = 50;
+        let mut value = serde_json::to_value(&config).unwrap();
+        strip(&config, &mut value);
+        assert!(value["profiles"][0].get("characters").is_none());
+        assert_eq!(value["layouts"]["desk"]["Main"]["x"], 50);
+
+        let mut reread: Config = serde_json::from_value(value).unwrap();
+        resolve(&mut reread);
+        assert_eq!(reread.profiles[0].character_positions["Main"].x, 50);
+        assert_eq!(reread.profiles[0].layout.as_deref(), Some("desk"));
+    }
+}
@@ -4,6 +4,8 @@
 //!
 //! This module manages application configuration with a unified JSON-based system
 //! supporting multiple visual profiles and global daemon behavior settings.
+//! The same structure can be kept in TOML instead (`format`): eve-l-preview.toml
+//! is used when it exists, and files are saved in the format they were read in.
 //!
 //! ### Config Flow
 //!
@@ -11,19 +13,61 @@
 //! JSON File (~/.config/eve-l-preview/eve-l-preview.json)
 //!     ├── global: GlobalSettings (daemon behavior + GUI window state)
 //!     │   ├── selected_profile (which profile is active)
-//!     │   ├── window_width, window_height (GUI manager window)
-//!     │   ├── hide_when_no_focus
-//!     │   ├── snap_threshold
+//!     │   ├── window_width, window_height, window_x, window_y, manager_tab (GUI manager window)
+//!     │   ├── start_minimized_to_tray (manager starts hidden behind its tray icon)
+//!     │   ├── hide_when_no_focus, prevent_minimize
+//!     │   ├── snap_threshold, show_drag_guides, drag_max_fps, drag_ghost
+//!     │   ├── screen_margins, overlap_avoidance
+//!     │   ├── raise_thumbnail_on_hover, raise_focused_thumbnail
+//!     │   ├── hover_tooltip (client details next to the hovered thumbnail)
 //!     │   ├── hotkey_require_eve_focus
-//!     │   ├── minimize_clients_on_switch
+//!     │   ├── cycle_wrap
+//!     │   ├── hotkey_backend (auto/evdev/portal)
+//!     │   ├── privacy_hotkey (toggle pixelated previews)
+//!     │   ├── focus_last_hotkey (flip to the previously focused client)
+//!     │   ├── focus_back_hotkey, focus_forward_hotkey (browser-style focus history)
+//!     │   ├── mouse_cycle_forward, mouse_cycle_backward (side mouse buttons cycling characters)
+//!     │   ├── chord_leader, chord_timeout_ms (leader combo, then 1-9 picks a character)
+//!     │   ├── cycle_strip (modifier+Tab shows the characters, releasing switches)
+//!     │   ├── switch_osd, switch_osd_ms (character name mid-screen after a hotkey switch)
+//!     │   ├── minimize_clients_on_switch, client_switch_action
 //!     │   ├── preserve_thumbnail_position_on_swap
-//!     │   └── default_thumbnail_width, default_thumbnail_height
+//!     │   ├── monitor_position_sets (positions kept per monitor setup)
+//!     │   ├── default_thumbnail_width, default_thumbnail_height
+//!     │   ├── mqtt (enabled, broker_url, username, password, topic_prefix, client_id)
+//!     │   ├── window_rules (match character/title → actions on map/focus)
+//!     │   ├── monitor_colors (per-monitor saturation/brightness of thumbnails)
+//!     │   ├── night_mode (enabled, follow_gamma, start_hour, end_hour, tint_percent, brightness_percent)
+//!     │   ├── thumbnail_max_fps (damage-driven redraw cap per thumbnail)
+//!     │   ├── low_bandwidth (auto/on/off: smaller, slower previews on remote displays)
+//!     │   ├── render_backend (xrender/gl)
+//!     │   ├── opacity_mode (auto/compositor/render)
+//!     │   ├── panel (enabled, layout, columns, grid/row/column: all previews in one window)
+//!     │   ├── show_process_stats (client CPU/RAM readout on thumbnails)
+//!     │   ├── stall_warning_secs (mark clients that stopped drawing)
+//!     │   ├── autosave_interval_secs (cap on unsaved position updates)
+//!     │   ├── live_source_refresh (redraw during client moves/resizes)
+//!     │   ├── usage_stats (local history of session counters)
+//!     │   ├── large_controls (bigger hit targets in the manager)
+//!     │   ├── wm_preset (auto/generic/i3/awesome/kwin/mutter)
+//!     │   ├── redirect_fullscreen_clients (guard against compositor unredirection)
+//!     │   └── title_pattern (regex finding EVE clients and character names in titles)
 //!     └── profiles: Vec<Profile> (visual appearance per profile)
 //!         ├── name, description
+//!         ├── inherits (base profile supplying every setting not given here)
+//!         ├── layout (shared layout holding the characters instead of the profile)
 //!         ├── opacity_percent, border_size, border_color
 //!         ├── text_size, text_x, text_y, text_color
+//!         ├── label_template ({character}, {name}, {index}, {status})
+//!         ├── text_background, text_background_padding (box behind the name label)
+//!         ├── scale_filter (nearest/bilinear/best XRender filter for downscaling)
 //!         ├── cycle_group (hotkey Tab/Shift+Tab order)
-//!         └── character_positions (x, y, width, height per character)
+//!         ├── cycle_sort (manual list, or alphabetical/login/position order of running characters)
+//!         ├── privacy_hotkey (overrides the global one while this profile is active)
+//!         ├── stacking_rules, stacking_default (previews above/below the active application by WM_CLASS)
+//!         ├── character_positions (x, y, width, height per character)
+//!         └── monitor_positions (x, y, width, height per character for each monitor setup)
+//! layouts: characters of each shared layout, by layout name
 //! ```
 //!
 //! ### Two Config Systems (Different Purposes)
@@ -32,6 +76,8 @@
 //! - **Used by**: GUI manager application
 //! - **Purpose**: Full configuration with profile management
 //! - **Operations**: Load, save, create/edit/delete profiles
+//! - **Resets**: `Config::reset(ResetScope, profile_idx)` puts one section back to
+//!   defaults and returns a `ResetUndo`; `undo_reset` restores only that section
 //! - **Save strategy**: Preserves character_positions (daemon owns this data)
 //!
 //! #### 2. `daemon_state::PersistentState` - Daemon Runtime
@@ -69,18 +115,62 @@
 //! **Visual Settings** (per-profile, in `Profile`):
 //! - opacity_percent, border_size, border_color
 //! - text_size, text_x, text_y, text_foreground
+//! - label_template (expanded per thumbnail each loop pass, redrawn only when the text changes)
+//! - text_background, text_background_padding (RENDER fill under the name label, kept inside the border)
+//! - scale_filter (set on each source picture; the GL backend always filters linearly)
 //! - cycle_group (hotkey order for this profile)
+//! - cycle_sort (non-manual orders are recomputed on every cycle hotkey)
+//! - privacy_hotkey (per-profile keymap: overrides the global privacy key; character hotkeys live in character_positions)
+//! - stacking_rules, stacking_default (followed through _NET_ACTIVE_WINDOW; "below" stacks previews under the active window's frame)
 //! - character_positions (window positions/dimensions)
 //!
 //! **Behavior Settings** (global, in `GlobalSettings`):
 //! - selected_profile (which profile is active)
-//! - window_width, window_height (GUI manager window dimensions)
+//! - window_width, window_height, window_x, window_y, manager_tab (manager window geometry and tab, saved on exit)
+//! - start_minimized_to_tray (tray-only manager startup)
 //! - hide_when_no_focus (show/hide thumbnails)
+//! - prevent_minimize (restore + lower minimized clients so they keep rendering)
 //! - snap_threshold (edge snapping distance)
+//! - show_drag_guides (snap target/work-area lines while dragging)
+//! - drag_max_fps (drag motion coalescing rate)
+//! - drag_ghost (drag an outline, move the thumbnail on release)
+//! - screen_margins (per-edge clear space respected by snapping and placement)
+//! - overlap_avoidance (nudge dropped thumbnail or push covered ones aside)
+//! - raise_thumbnail_on_hover, raise_focused_thumbnail (thumbnail stacking)
+//! - hover_tooltip (full name, geometry, FPS and last update on hover)
 //! - hotkey_require_eve_focus (restrict hotkeys to EVE focus)
+//! - cycle_wrap (Tab/Shift+Tab stop at the ends of the cycle order when off)
+//! - hotkey_backend (evdev devices or the GlobalShortcuts portal)
+//! - privacy_hotkey (pixelate all previews for streaming)
+//! - focus_last_hotkey (the daemon remembers focus from every source: hotkeys, clicks, the WM)
+//! - focus_back_hotkey, focus_forward_hotkey (last 10 focused characters; focusing something new drops the forward part)
+//! - mouse_cycle_forward, mouse_cycle_backward (button grabs on the clients, or root when hotkeys are global; Shift reverses them)
+//! - chord_leader, chord_timeout_ms (two-step hotkeys with an on-screen hint, keyboard grabbed for the follow-up)
+//! - cycle_strip (alt-tab style; the keyboard is grabbed while the strip is up, Escape cancels)
+//! - switch_osd, switch_osd_ms (hotkey switches only; the OSD uses the label font at a larger size)
 //! - minimize_clients_on_switch (minimize other clients on focus)
+//! - client_switch_action (minimize or lower those other clients)
 //! - preserve_thumbnail_position_on_swap (keep position on character change)
+//! - monitor_position_sets (move thumbnails to the positions saved for the RandR monitor setup)
 //! - default_thumbnail_width, default_thumbnail_height (new thumbnail defaults)
+//! - mqtt (optional broker for focus/character event publishing)
+//! - window_rules (client window move/resize/pin rules)
+//! - monitor_colors (approximate colour correction for wide-gamut monitors)
+//! - night_mode (warm, dimmed thumbnails by the clock or redshift/gammastep)
+//! - thumbnail_max_fps (coalesce damage into at most N redraws per second per thumbnail)
+//! - low_bandwidth (cap preview size and redraw rate over ssh -X)
+//! - render_backend (XRender or OpenGL thumbnail scaling)
+//! - opacity_mode (compositor opacity or XRender blending over the wallpaper)
+//! - panel (single-window panel mode and its grid/row/column layouts)
+//! - show_process_stats (CPU/RAM of each client from /proc)
+//! - stall_warning_secs (greyed-out thumbnail for hung clients)
+//! - autosave_interval_secs (save positions that keep changing)
+//! - live_source_refresh (thumbnail updates while a client is dragged/resized)
+//! - usage_stats (opt-in, local-only daily stats for the Session tab)
+//! - large_controls (larger buttons/sliders/checkboxes in the manager window)
+//! - wm_preset (window manager compatibility: thumbnail window type, raising on switch)
+//! - redirect_fullscreen_clients (previews of fullscreen clients under picom's unredir-if-possible)
+//! - title_pattern (client title regex with a `character` group)
 //!
 //! ### No Conversion, Just Extraction
 //!
@@ -99,9 +189,49 @@
 //! ```
 //!
 //! This ensures one source of truth with no synchronization issues.
+//!
+//! ### Inheritance
+//!
+//! A profile with `inherits = "<name>"` takes every setting it doesn't give
+//! from that profile (name, description and characters excepted).
+//! `inheritance::resolve` fills them in while the file is parsed, so both
+//! config systems above only ever see complete profiles; `Profile::own_fields`
+//! remembers what the profile set itself and `inheritance::strip` leaves the
+//! rest out again when saving. In the manager, `Config::profile_edited` keeps
+//! inheriting profiles in step with their base.
+//!
+//! ### Layouts
+//!
+//! Profiles with the same `layout` share their characters through the
+//! top-level `layouts` table. `layouts::resolve` copies a layout into each of
+//! its profiles' `character_positions` on load and `layouts::strip` writes the
+//! selected (else first) profile's copy back on save; the manager calls
+//! `Config::share_layouts` before switching profiles so the copies agree.
+//!
+//! ### Overrides
+//!
+//! `overrides` lets `EVELP_<FIELD>` variables (`__` between nested levels) and
+//! `--set field=value` arguments (`.` between levels) replace profile and
+//! global values in the daemon, --set last. They are applied to every
+//! SetProfile the daemon receives and never reach the saved file.
+//!
+//! ### Schema
+//!
+//! `schema::config_schema()` (`--dump-config-schema`) describes every field
+//! for editors and tools. It is derived from these structs (`JsonSchema`) and
+//! the default config, so a new field shows up there without further work; its
+//! doc comment becomes the description and a `#[schemars(range(..))]` attribute
+//! its bounds. The manager's "More Settings" groups
+//! (gui/components/schema_form.rs) render fields no hand-written widget edits
+//! from the same schema.
 
 pub mod daemon_state;
+pub mod format;
+pub mod inheritance;
+pub mod layouts;
+pub mod overrides;
 pub mod profile;
+pub mod schema;
 
 // Re-export commonly used types
 pub use daemon_state::{DisplayConfig, PersistentState};
@@ -0,0 +1,226 @@
+//! Setting overrides from the environment (EVELP_*) and the command line (--set)
+//!
+//! Every profile and global field can be overridden for one launch without
+//! touching the config file: `EVELP_` plus the field name in upper case
+//! (`EVELP_OPACITY_PERCENT=60`), with `__` between the levels of nested
+//! settings (`EVELP_NIGHT_MODE__ENABLED=true`), or `--set opacity_percent=60`
+//! / `--set night_mode.enabled=true`. Values are read as JSON, so numbers,
+//! booleans and lists work as written; anything else is taken as a string.
+//! Profile fields are looked up before global ones (only privacy_hotkey exists
+//! in both). The daemon applies them, environment first and --set last, each
+//! time it receives the settings, so they win over the file while the manager
+//! keeps showing and saving the file's values.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+use super::profile::{GlobalSettings, Profile};
+use super::schema;
+
+pub const PREFIX: &str = "EVELP_";
+
+/// Nesting separator inside a variable name
+const SEPARATOR: &str = "__";
+
+/// One setting to override
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Override {
+    /// Where it came from, for logs ("EVELP_OPACITY_PERCENT", "--set opacity_percent")
+    source: String,
+    /// Field names from the top of the profile/global table
+    path: Vec<String>,
+    raw: String,
+}
+
+static CLI: OnceLock<Vec<Override>> = OnceLock::new();
+
+/// Keep the --set overrides of this process (once, at startup)
+pub fn set_cli(overrides: Vec<Override>) {
+    let _ = CLI.set(overrides);
+}
+
+/// Arguments that pass the --set overrides on to a spawned preview daemon
+pub fn daemon_args() -> Vec<String> {
+    CLI.get().into_iter().flatten()
+        .flat_map(|o| ["--set".to_string(), format!("{}={}", o.path.join("."), o.raw)])
+        .collect()
+}
+
+/// Parse a `--set key=value` argument; unknown keys and values the field can't take are rejected
+pub fn parse_cli(arg: &str) -> Result<Override, String> {
+    let Some((key, raw)) = arg.split_once('=') else {
+        return Err(format!("'{}' is not key=value (e.g. opacity_percent=40)", arg));
+    };
+    let key = key.trim();
+    let candidate = Override {
+        source: format!("--set {}", key),
+        path: key.to_ascii_lowercase().split('.').map(str::to_string).collect(),
+        raw: raw.to_string(),
+    };
+    let mut profile = Profile::default_with_name(String::new(), String::new());
+    let mut global = GlobalSettings::default();
+    match apply_one(&mut profile, &mut global, &candidate) {
+        Ok(()) => Ok(candidate),
+        Err(ApplyError::Unknown) => Err(format!("'{}' is not a setting (see --dump-config-schema)", key)),
+        Err(ApplyError::Invalid) => Err(format!("'{}' is not a valid value for {}", raw, key)),
+    }
+}
+
+/// Overrides among environment variables
+fn from_env(vars: impl IntoIterator<Item = (String, String)>) -> Vec<Override> {
+    vars.into_iter()
+        .filter_map(|(name, raw)| {
+            let path = name.strip_prefix(PREFIX)?.to_ascii_lowercase().split(SEPARATOR).map(str::to_string).collect();
+            Some(Override { source: name, path, raw })
+        })
+        .collect()
+}
+
+/// Whether `path` names a field in the schema `properties`
+fn has_field(properties: &Value, path: &[String]) -> bool {
+    let Some((first, rest)) = path.split_first() else {
+        return false;
+    };
+    match properties.get(first) {
+        Some(field) if rest.is_empty() => !field.is_null(),
+        Some(field) => has_field(&field["properties"], rest),
+        None => false,
+    }
+}
+
+/// Set `path` in `settings` to `value`; false (settings untouched) if the result doesn't deserialize
+fn set<T: Serialize + DeserializeOwned>(settings: &mut T, path: &[String], value: Value) -> bool {
+    let Ok(mut json) = serde_json::to_value(&*settings) else {
+        return false;
+    };
+    let mut target = &mut json;
+    for name in &path[..path.len() - 1] {
+        let Some(object) = target.as_object_mut() else {
+            return false;
+        };
+        target = object.entry(name.clone()).or_insert_with(|| Value::Object(Default::default()));
+    }
+    let Some(object) = target.as_object_mut() else {
+        return false;
+    };
+    object.insert(path[path.len() - 1].clone(), value);
+    match serde_json::from_value(json) {
+        Ok(updated) => {
+            *settings = updated;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+enum ApplyError {
+    Unknown,
+    Invalid,
+}
+
+fn apply_one(profile: &mut Profile, global: &mut GlobalSettings, o: &Override) -> Result<(), ApplyError> {
+    static SCHEMA: OnceLock<Value> = OnceLock::new();
+    let schema = SCHEMA.get_or_init(schema::config_schema);
+    let profile_fields = &schema["properties"]["profiles"]["items"]["properties"];
+    let global_fields = &schema["properties"]["global"]["properties"];
+    // "3" for a string field is still the string "3"
+    let candidates = || [serde_json::from_str(&o.raw).ok(), Some(Value::String(o.raw.clone()))].into_iter().flatten();
+    let ok = if o.path[0] != "characters" && has_field(profile_fields, &o.path) {
+        candidates().any(|value| set(profile, &o.path, value))
+    } else if has_field(global_fields, &o.path) {
+        candidates().any(|value| set(global, &o.path, value))
+    } else {
+        return Err(ApplyError::Unknown);
+    };
+    if ok { Ok(()) } else { Err(ApplyError::Invalid) }
+}
+
+/// Apply `overrides` in order; returns how many were applied
+fn apply_overrides(profile: &mut Profile, global: &mut GlobalSettings, overrides: impl IntoIterator<Item = Override>) -> usize {
+    let mut applied = 0;
+    for o in overrides {
+        match apply_one(profile, global, &o) {
+            Ok(()) => {
+                info!(source = %o.source, value = %o.raw, "Setting overridden");
+                applied += 1;
+            }
+            Err(ApplyError::Unknown) => warn!(source = %o.source, "Unknown setting in override, ignored"),
+            Err(ApplyError::Invalid) => warn!(source = %o.source, value = %o.raw, "Invalid value in override, ignored"),
+        }
+    }
+    applied
+}
+
+/// Apply the process environment's EVELP_* variables, then the --set arguments
+pub fn apply(profile: &mut Profile, global: &mut GlobalSettings) -> usize {
+    let cli = CLI.get().cloned().unwrap_or_default();
+    apply_overrides(profile, global, from_env(std::env::vars()).into_iter().chain(cli))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_overrides_profile_global_and_nested_fields() {
+        let mut profile = Profile::default_with_name("main".to_string(), String::new());
+        let mut global = GlobalSettings::default();
+        let applied = apply_overrides(&mut profile, &mut global, from_env(vars(&[
+            ("EVELP_OPACITY_PERCENT", "60"),
+            ("EVELP_BORDER_COLOR", "#FF00FF00"),
+            ("EVELP_CYCLE_WRAP", "false"),
+            ("EVELP_NIGHT_MODE__ENABLED", "true"),
+            ("EVELP_PRIVACY_HOTKEY", "F12"),
+            ("HOME", "/home/pilot"),
+        ])));
+        assert_eq!(applied, 5);
+        assert_eq!(profile.opacity_percent, 60);
+        assert_eq!(profile.border_color, "#FF00FF00");
+        assert!(!global.cycle_wrap);
+        assert!(global.night_mode.enabled);
+        // Profile fields come first
+        assert_eq!(profile.privacy_hotkey.as_deref(), Some("F12"));
+        assert_eq!(global.privacy_hotkey, None);
+    }
+
+    #[test]
+    fn test_invalid_and_unknown_overrides_are_ignored() {
+        let mut profile = Profile::default_with_name("main".to_string(), String::new());
+        let mut global = GlobalSettings::default();
+        let before = profile.opacity_percent;
+        let applied = apply_overrides(&mut profile, &mut global, from_env(vars(&[
+            ("EVELP_OPACITY_PERCENT", "lots"),
+            ("EVELP_NO_SUCH_SETTING", "1"),
+            ("EVELP_CHARACTERS", "{}"),
+            // Numeric-looking text for a string field
+            ("EVELP_TEXT_FONT_FAMILY", "1942"),
+        ])));
+        assert_eq!(applied, 1);
+        assert_eq!(profile.opacity_percent, before);
+        assert_eq!(profile.text_font_family, "1942");
+    }
+
+    #[test]
+    fn test_cli_overrides_are_checked_when_parsed() {
+        let o = parse_cli("night_mode.enabled=true").unwrap();
+        assert_eq!(o.path, vec!["night_mode", "enabled"]);
+        assert!(parse_cli("opacity_percent=40").is_ok());
+        assert!(parse_cli("opacity_percent=400").is_err());
+        assert!(parse_cli("opacity=40").is_err());
+        assert!(parse_cli("opacity_percent").is_err());
+
+        // Applied after the environment, so --set wins
+        let mut profile = Profile::default_with_name("main".to_string(), String::new());
+        let mut global = GlobalSettings::default();
+        let env = from_env(vars(&[("EVELP_OPACITY_PERCENT", "60")]));
+        apply_overrides(&mut profile, &mut global, env.into_iter().chain([parse_cli("opacity_percent=40").unwrap()]));
+        assert_eq!(profile.opacity_percent, 40);
+    }
+}
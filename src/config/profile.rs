@@ -4,25 +4,30 @@
 //! each containing a complete set of visual and behavioral settings.
 
 use anyhow::{Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use std::path::PathBuf;
 use tracing::info;
 
-use crate::types::CharacterSettings;
+use super::format::{self, ConfigFormat};
+use crate::types::{CharacterSettings, ThumbnailGeometry};
 
 /// Top-level configuration with profile support
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
     #[serde(default)]
     pub global: GlobalSettings,
     #[serde(default = "default_profiles")]
     pub profiles: Vec<Profile>,
+    /// Characters shared by the profiles naming each layout
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub layouts: HashMap<String, HashMap<String, CharacterSettings>>,
 }
 
 /// Global daemon behavior (applies to all profiles)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GlobalSettings {
     #[serde(default = "default_profile_name")]
     pub selected_profile: String,
@@ -34,35 +39,540 @@ pub struct GlobalSettings {
     pub window_x: Option<i16>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub window_y: Option<i16>,
+    /// Manager tab that was open when the GUI was last closed
+    #[serde(default)]
+    pub manager_tab: ManagerTab,
+    /// Start the manager hidden with only the tray icon (the daemon still starts)
+    #[serde(default)]
+    pub start_minimized_to_tray: bool,
     #[serde(default)]
     pub minimize_clients_on_switch: bool,
+    /// What happens to the other clients when minimize_clients_on_switch is enabled
+    #[serde(default)]
+    pub client_switch_action: ClientSwitchAction,
     #[serde(default)]
     pub hotkey_require_eve_focus: bool,
+    /// Where hotkeys come from (evdev devices or the desktop's GlobalShortcuts portal)
+    #[serde(default)]
+    pub hotkey_backend: HotkeyBackend,
+    /// Key toggling privacy mode (pixelated previews), evdev key name like the character hotkeys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privacy_hotkey: Option<String>,
+    /// Key flipping back to the previously focused character (e.g. "F11")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_last_hotkey: Option<String>,
+    /// Key stepping back through the focus history (browser style)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_back_hotkey: Option<String>,
+    /// Key stepping forward through the focus history after going back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_forward_hotkey: Option<String>,
+    /// X mouse button cycling to the next character (e.g. 9, the "forward" side button)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mouse_cycle_forward: Option<u8>,
+    /// X mouse button cycling to the previous character (e.g. 8, the "back" side button)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mouse_cycle_backward: Option<u8>,
+    /// Chord leader key combo ("Ctrl+Space"): then 1-9 focus that character in cycle order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chord_leader: Option<String>,
+    /// How long the chord waits for its follow-up key
+    #[serde(default = "default_chord_timeout_ms")]
+    pub chord_timeout_ms: u32,
+    /// Tab past the last character goes back to the first (off: it stays on the last)
+    #[serde(default = "default_cycle_wrap")]
+    pub cycle_wrap: bool,
+    /// Holding this modifier with Tab shows a strip of the characters; releasing it switches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle_strip: Option<CycleStripModifier>,
+    /// Show the target character's name mid-screen after a hotkey switch
+    #[serde(default)]
+    pub switch_osd: bool,
+    /// How long the switch OSD stays up
+    #[serde(default = "default_switch_osd_ms")]
+    pub switch_osd_ms: u32,
     #[serde(default)]
     pub hide_when_no_focus: bool,
+    /// Wine stops rendering minimized clients, so restore and lower them instead
+    #[serde(default)]
+    pub prevent_minimize: bool,
     #[serde(default = "default_snap_threshold")]
     pub snap_threshold: u16,
+    /// Draw guide lines for snap targets and the screen margins while dragging
+    #[serde(default)]
+    pub show_drag_guides: bool,
+    /// Cap on drag repositions per second; extra motion events are coalesced (0 = unlimited)
+    #[serde(default = "default_drag_max_fps")]
+    pub drag_max_fps: u16,
+    /// Drag an outline instead of the live thumbnail (moved into place on release)
+    #[serde(default)]
+    pub drag_ghost: bool,
+    /// What to do when a dragged thumbnail is dropped on top of another
+    #[serde(default)]
+    pub overlap_avoidance: OverlapAvoidance,
+    /// Space kept clear along each screen edge (for bars that don't set struts)
+    #[serde(default)]
+    pub screen_margins: ScreenMargins,
+    /// Bring the hovered thumbnail above overlapping ones
+    #[serde(default)]
+    pub raise_thumbnail_on_hover: bool,
+    /// Keep the focused character's thumbnail above overlapping ones
+    #[serde(default)]
+    pub raise_focused_thumbnail: bool,
     /// When a new character logs in without saved coordinates, inherit the previous character's thumbnail position
     /// This keeps thumbnails in place when swapping characters on the same EVE client
     #[serde(default = "default_preserve_thumbnail_position_on_swap")]
     pub preserve_thumbnail_position_on_swap: bool,
+    /// Keep thumbnail positions per monitor setup and move the thumbnails when the setup changes (docked/undocked)
+    #[serde(default = "default_monitor_position_sets")]
+    pub monitor_position_sets: bool,
     /// Default thumbnail width for new characters
     #[serde(default = "default_thumbnail_width")]
     pub default_thumbnail_width: u16,
     /// Default thumbnail height for new characters
     #[serde(default = "default_thumbnail_height")]
     pub default_thumbnail_height: u16,
+    /// Optional MQTT publishing of focus/character events
+    #[serde(default)]
+    pub mqtt: MqttSettings,
+    /// Declarative rules applied to matching EVE client windows on map/focus
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub window_rules: Vec<WindowRule>,
+    /// Cap on redraws per second of each thumbnail; damage in between is coalesced (0 = unlimited)
+    #[serde(default = "default_thumbnail_max_fps")]
+    pub thumbnail_max_fps: u16,
+    /// Cap preview size and update rate for slow links (e.g. ssh -X)
+    #[serde(default)]
+    pub low_bandwidth: LowBandwidth,
+    /// Colour correction of thumbnails shown on particular monitors
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub monitor_colors: Vec<MonitorColor>,
+    /// Warm, dimmed thumbnails at night
+    #[serde(default)]
+    pub night_mode: NightModeSettings,
+    /// How thumbnail opacity is applied (compositor property or blended by us)
+    #[serde(default)]
+    pub opacity_mode: OpacityMode,
+    /// How thumbnails are drawn (OpenGL needs the `gl` build feature)
+    #[serde(default)]
+    pub render_backend: RenderBackend,
+    /// Draw all previews inside one regular window instead of one window each
+    #[serde(default)]
+    pub panel: PanelSettings,
+    /// Show each client's CPU and memory use on its thumbnail
+    #[serde(default)]
+    pub show_process_stats: bool,
+    /// Mark a thumbnail stalled after this many seconds without frames from its client (0 = off)
+    #[serde(default = "default_stall_warning_secs")]
+    pub stall_warning_secs: u16,
+    /// Longest time thumbnail positions reported by the previews may stay unsaved (0 = only when they settle)
+    #[serde(default = "default_autosave_interval_secs")]
+    pub autosave_interval_secs: u16,
+    /// Keep redrawing a thumbnail while its client is being moved/resized (not just afterwards)
+    #[serde(default)]
+    pub live_source_refresh: bool,
+    /// Regex recognizing EVE client titles; its `character` group is the character name
+    #[serde(default = "default_title_pattern")]
+    pub title_pattern: String,
+    /// Show a tooltip with the client's full details when hovering a thumbnail
+    #[serde(default)]
+    pub hover_tooltip: bool,
+    /// Keep a local history of session counters for the stats view (never sent anywhere)
+    #[serde(default)]
+    pub usage_stats: bool,
+    /// Bigger buttons, sliders and checkboxes in the manager window
+    #[serde(default)]
+    pub large_controls: bool,
+    /// Window manager compatibility preset (auto = detected at daemon start)
+    #[serde(default)]
+    pub wm_preset: WmPreset,
+    /// Redirect fullscreen clients ourselves while a compositor runs (it may unredirect them)
+    #[serde(default)]
+    pub redirect_fullscreen_clients: bool,
+}
+
+/// Window manager the daemon adapts its thumbnail windows and client activation to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WmPreset {
+    /// Detected from _NET_SUPPORTING_WM_CHECK
+    #[default]
+    Auto,
+    /// Any other WM: override-redirect thumbnails, raise before activating
+    Generic,
+    /// i3
+    I3,
+    /// awesome
+    Awesome,
+    /// KDE's KWin
+    Kwin,
+    /// GNOME's Mutter
+    Mutter,
+}
+
+/// Action applied to non-active EVE clients when switching characters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientSwitchAction {
+    /// Iconify the other clients (wine stops rendering them, previews freeze)
+    #[default]
+    Minimize,
+    /// Push the other clients behind the active one (they keep rendering)
+    Lower,
+}
+
+/// How Tab/Shift+Tab order the characters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CycleSort {
+    /// The profile's cycle_group list
+    #[default]
+    Manual,
+    /// Every running character by name
+    Alphabetical,
+    /// Every running character in the order they logged in
+    Login,
+    /// Every running character by thumbnail position, left to right, then top to bottom
+    Position,
+}
+
+/// Modifier that, held with Tab, opens the cycle strip instead of switching right away
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CycleStripModifier {
+    /// Either Alt key
+    #[default]
+    Alt,
+    /// Either Ctrl key
+    Ctrl,
+    /// The Windows/Super key
+    Super,
+}
+
+/// Tabs of the GUI manager window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ManagerTab {
+    /// Settings shared by every profile
+    #[default]
+    GlobalSettings,
+    /// Settings of the selected profile
+    ProfileSettings,
+    /// Per-character settings and hotkeys
+    Characters,
+    /// Last session summary and usage stats
+    Session,
+}
+
+/// Source of the cycle and per-character hotkeys
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyBackend {
+    /// Portal in Wayland sessions, evdev otherwise
+    #[default]
+    Auto,
+    /// Read keyboards from /dev/input (needs the input group)
+    Evdev,
+    /// org.freedesktop.portal.GlobalShortcuts (GNOME/KDE Wayland)
+    Portal,
+}
+
+/// Low bandwidth mode for remote X displays
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LowBandwidth {
+    /// Enable when DISPLAY points at another host (ssh -X forwarding, remote X server)
+    #[default]
+    Auto,
+    /// Always enabled
+    On,
+    /// Never enabled
+    Off,
+}
+
+/// How the profile's opacity_percent is applied
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OpacityMode {
+    /// Blend ourselves when no compositing manager is running
+    #[default]
+    Auto,
+    /// Only set _NET_WM_WINDOW_OPACITY (needs a compositor)
+    Compositor,
+    /// Always blend against the wallpaper with XRender
+    Render,
+}
+
+/// Thumbnail rendering backend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderBackend {
+    /// Scale with XRender inside the X server
+    #[default]
+    Xrender,
+    /// Bind client pixmaps as OpenGL textures (falls back to XRender if unavailable)
+    Gl,
+}
+
+/// Resolution for thumbnails dropped on top of each other
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlapAvoidance {
+    /// Leave the thumbnail where it was dropped
+    #[default]
+    Off,
+    /// Move the dropped thumbnail to the nearest free space
+    Nudge,
+    /// Keep the dropped thumbnail in place and move the covered ones aside
+    Push,
+}
+
+/// Per-edge screen margins in pixels
+/// Thumbnails snap to the margin lines and new thumbnails are placed inside them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ScreenMargins {
+    #[serde(default)]
+    pub top: u16,
+    #[serde(default)]
+    pub bottom: u16,
+    #[serde(default)]
+    pub left: u16,
+    #[serde(default)]
+    pub right: u16,
+}
+
+/// Panel mode: previews are laid out inside a single top-level window
+/// The window is managed by the WM like any other (tiling, moving, stacking)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PanelSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// How previews are arranged (and where the panel is anchored)
+    #[serde(default)]
+    pub layout: PanelLayout,
+    /// Previews per row in the grid layout (0 = roughly square grid)
+    #[serde(default)]
+    pub columns: u16,
+    #[serde(default)]
+    pub grid: PanelLayoutSettings,
+    #[serde(default)]
+    pub row: PanelLayoutSettings,
+    #[serde(default)]
+    pub column: PanelLayoutSettings,
+}
+
+impl PanelSettings {
+    /// Spacing and size limit of the selected layout
+    pub fn active_layout(&self) -> &PanelLayoutSettings {
+        match self.layout {
+            PanelLayout::Grid => &self.grid,
+            PanelLayout::Row => &self.row,
+            PanelLayout::Column => &self.column,
+        }
+    }
+}
+
+/// Arrangement of previews inside the panel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PanelLayout {
+    /// Grid wherever the WM puts the panel
+    #[default]
+    Grid,
+    /// Single row along the bottom edge of the screen
+    Row,
+    /// Single column along the right edge of the screen
+    Column,
+}
+
+/// Spacing and preview size limit of one panel layout
+/// Row and column layouts also shrink previews so the panel fits the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PanelLayoutSettings {
+    /// Gap between previews and around the panel edge, in pixels
+    #[serde(default = "default_panel_spacing")]
+    pub spacing: u16,
+    /// Largest preview drawn in this layout (0 = no limit), aspect ratio kept
+    #[serde(default)]
+    pub max_width: u16,
+    #[serde(default)]
+    pub max_height: u16,
+}
+
+impl Default for PanelLayoutSettings {
+    fn default() -> Self {
+        Self {
+            spacing: default_panel_spacing(),
+            max_width: 0,
+            max_height: 0,
+        }
+    }
+}
+
+/// MQTT broker connection for publishing focus/character events
+/// Topics are published per character under `topic_prefix`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MqttSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_mqtt_broker_url")]
+    pub broker_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default = "default_mqtt_topic_prefix")]
+    pub topic_prefix: String,
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+}
+
+/// Window rule: match EVE clients by character and/or title, then apply actions
+/// A rule without any matcher applies to every EVE client
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct WindowRule {
+    /// Free-form label shown in logs
+    #[serde(default)]
+    pub name: String,
+    /// Exact character name to match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub character: Option<String>,
+    /// Regex matched against the full window title (e.g. "^EVE - .* Alt$")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_regex: Option<String>,
+    /// When the rule is applied
+    #[serde(default)]
+    pub trigger: RuleTrigger,
+    #[serde(default)]
+    pub actions: RuleActions,
+}
+
+/// Event that applies a window rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleTrigger {
+    /// Client window appears or a character logs in on it
+    #[default]
+    Map,
+    /// Client window gains focus
+    Focus,
+}
+
+/// Actions applied to a matching client (unset fields are left alone)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RuleActions {
+    /// Move the client to this monitor (0-based, counted left to right)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<usize>,
+    /// Resize the client area
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u16>,
+    /// Set or clear always-on-top (_NET_WM_STATE_ABOVE)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub always_on_top: Option<bool>,
+    /// Move the client to this virtual desktop (0-based, _NET_WM_DESKTOP)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desktop: Option<u32>,
+}
+
+/// Where previews go while a matching application is the active window
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct StackingRule {
+    /// WM_CLASS instance or class of the application, any case ("firefox", "discord")
+    pub class: String,
+    #[serde(default)]
+    pub previews: PreviewStacking,
+}
+
+/// Previews on top of the active window or underneath it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewStacking {
+    /// On top of the active window
+    #[default]
+    Above,
+    /// Underneath the active window
+    Below,
+}
+
+/// How the client image is sampled when scaled down to the thumbnail (XRender filter)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScaleFilter {
+    /// One source pixel per thumbnail pixel: sharp but aliased (X server default)
+    #[default]
+    Nearest,
+    /// Blend the four closest source pixels: smoother, slightly softer
+    Bilinear,
+    /// Smoothest filter the X server offers (often bilinear, some servers do better)
+    Best,
+}
+
+/// Colour correction for thumbnails on one monitor
+/// An approximation of the display's colour profile: wide-gamut panels show the
+/// sRGB client image oversaturated, the clients themselves are usually corrected elsewhere
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MonitorColor {
+    /// Monitor the thumbnail is on (0-based, counted left to right)
+    pub monitor: usize,
+    /// Saturation kept, 100 = unchanged
+    #[serde(default = "default_color_percent")]
+    #[schemars(range(max = 100))]
+    pub saturation_percent: u8,
+    /// Brightness kept, 100 = unchanged
+    #[serde(default = "default_color_percent")]
+    #[schemars(range(max = 100))]
+    pub brightness_percent: u8,
+}
+
+/// Night mode: thumbnails (not the clients) get a warm tint and less brightness
+/// during set hours, or while redshift/gammastep has warmed the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct NightModeSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Follow redshift/gammastep (warm gamma ramp) instead of start_hour/end_hour
+    #[serde(default)]
+    pub follow_gamma: bool,
+    /// Local hour night mode starts
+    #[serde(default = "default_night_start_hour")]
+    #[schemars(range(max = 23))]
+    pub start_hour: u8,
+    /// Local hour night mode ends, may be before start_hour (overnight)
+    #[serde(default = "default_night_end_hour")]
+    #[schemars(range(max = 23))]
+    pub end_hour: u8,
+    /// Strength of the warm tint, 0 = none
+    #[serde(default = "default_night_tint_percent")]
+    #[schemars(range(max = 100))]
+    pub tint_percent: u8,
+    /// Brightness kept, 100 = unchanged
+    #[serde(default = "default_night_brightness_percent")]
+    #[schemars(range(max = 100))]
+    pub brightness_percent: u8,
 }
 
 /// Profile - A complete set of visual and behavioral settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Profile {
     pub name: String,
     #[serde(default)]
     pub description: String,
+    /// Base profile: every setting not given here comes from it (characters excepted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inherits: Option<String>,
+    /// Fields an inheriting profile sets itself; the others follow the base profile
+    #[serde(skip)]
+    pub own_fields: BTreeSet<String>,
+    /// Shared position layout: characters are kept under this name in `layouts`, shared with every profile naming it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
     
     // Visual settings
     #[serde(rename = "opacity_percent")]
+    #[schemars(range(max = 100))]
     pub opacity_percent: u8,
     #[serde(default = "default_border_enabled")]
     pub border_enabled: bool,
@@ -76,14 +586,44 @@ pub struct Profile {
     pub text_color: String,
     #[serde(default = "default_text_font_family")]
     pub text_font_family: String,
+    /// Label text with {character}, {name}, {index} and {status} placeholders, expanded for each thumbnail
+    #[serde(default = "default_label_template")]
+    pub label_template: String,
+    /// Box drawn behind the name label (hex, e.g. #80000000 for half-transparent black), empty = no box
+    #[serde(default)]
+    pub text_background: String,
+    /// Space between the name label and the edge of its box in pixels
+    #[serde(default = "default_text_background_padding")]
+    #[schemars(range(max = 20))]
+    pub text_background_padding: u16,
+    /// Filter used to scale clients down to their previews
+    #[serde(default)]
+    pub scale_filter: ScaleFilter,
     
     // Hotkey settings (cycle_group is per-profile)
     #[serde(default)]
     pub cycle_group: Vec<String>,
+    /// Cycle order: the cycle_group list, or worked out from the running characters
+    #[serde(default)]
+    pub cycle_sort: CycleSort,
+    /// Privacy mode key for this profile, overrides the global privacy_hotkey
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privacy_hotkey: Option<String>,
+    
+    // Stacking against other applications (first matching rule wins)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stacking_rules: Vec<StackingRule>,
+    /// Where previews go while an application no rule matches is active
+    #[serde(default)]
+    pub stacking_default: PreviewStacking,
     
     // Per-profile character positions and dimensions
     #[serde(rename = "characters", default)]
     pub character_positions: HashMap<String, CharacterSettings>,
+
+    /// Thumbnail geometry per character for each monitor setup seen (monitor_position_sets), by setup fingerprint
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub monitor_positions: HashMap<String, HashMap<String, ThumbnailGeometry>>,
 }
 
 // Default value functions
@@ -103,10 +643,66 @@ fn default_snap_threshold() -> u16 {
     crate::constants::defaults::behavior::SNAP_THRESHOLD
 }
 
+fn default_drag_max_fps() -> u16 {
+    crate::constants::defaults::behavior::DRAG_MAX_FPS
+}
+
+fn default_thumbnail_max_fps() -> u16 {
+    crate::constants::defaults::behavior::THUMBNAIL_MAX_FPS
+}
+
+fn default_color_percent() -> u8 {
+    100
+}
+
+fn default_night_start_hour() -> u8 {
+    crate::constants::defaults::night_mode::START_HOUR
+}
+
+fn default_night_end_hour() -> u8 {
+    crate::constants::defaults::night_mode::END_HOUR
+}
+
+fn default_night_tint_percent() -> u8 {
+    crate::constants::defaults::night_mode::TINT_PERCENT
+}
+
+fn default_night_brightness_percent() -> u8 {
+    crate::constants::defaults::night_mode::BRIGHTNESS_PERCENT
+}
+
+fn default_chord_timeout_ms() -> u32 {
+    crate::constants::defaults::behavior::CHORD_TIMEOUT_MS
+}
+
+fn default_cycle_wrap() -> bool {
+    crate::constants::defaults::behavior::CYCLE_WRAP
+}
+
+fn default_switch_osd_ms() -> u32 {
+    crate::constants::defaults::behavior::SWITCH_OSD_MS
+}
+
+fn default_stall_warning_secs() -> u16 {
+    crate::constants::defaults::behavior::STALL_WARNING_SECS
+}
+
+fn default_autosave_interval_secs() -> u16 {
+    crate::constants::defaults::behavior::AUTOSAVE_INTERVAL_SECS
+}
+
+fn default_title_pattern() -> String {
+    crate::constants::eve::DEFAULT_TITLE_PATTERN.to_string()
+}
+
 fn default_preserve_thumbnail_position_on_swap() -> bool {
     crate::constants::defaults::behavior::PRESERVE_POSITION_ON_SWAP
 }
 
+fn default_monitor_position_sets() -> bool {
+    crate::constants::defaults::behavior::MONITOR_POSITION_SETS
+}
+
 fn default_thumbnail_width() -> u16 {
     crate::constants::defaults::thumbnail::WIDTH
 }
@@ -115,10 +711,34 @@ fn default_thumbnail_height() -> u16 {
     crate::constants::defaults::thumbnail::HEIGHT
 }
 
+fn default_panel_spacing() -> u16 {
+    crate::constants::defaults::panel::SPACING
+}
+
+fn default_mqtt_broker_url() -> String {
+    crate::constants::defaults::mqtt::BROKER_URL.to_string()
+}
+
+fn default_mqtt_topic_prefix() -> String {
+    crate::constants::defaults::mqtt::TOPIC_PREFIX.to_string()
+}
+
+fn default_mqtt_client_id() -> String {
+    crate::constants::defaults::mqtt::CLIENT_ID.to_string()
+}
+
 fn default_border_enabled() -> bool {
     crate::constants::defaults::border::ENABLED
 }
 
+fn default_label_template() -> String {
+    crate::constants::defaults::text::LABEL_TEMPLATE.to_string()
+}
+
+fn default_text_background_padding() -> u16 {
+    crate::constants::defaults::text::BACKGROUND_PADDING
+}
+
 fn default_text_font_family() -> String {
     // Try to detect best default TrueType font, but don't fail config creation
     match crate::preview::select_best_default_font() {
@@ -138,6 +758,9 @@ fn default_profiles() -> Vec<Profile> {
     vec![Profile {
         name: crate::constants::defaults::behavior::PROFILE_NAME.to_string(),
         description: crate::constants::defaults::behavior::PROFILE_DESCRIPTION.to_string(),
+        inherits: None,
+        own_fields: BTreeSet::new(),
+        layout: None,
         opacity_percent: crate::constants::defaults::thumbnail::OPACITY_PERCENT,
         border_enabled: crate::constants::defaults::border::ENABLED,
         border_size: crate::constants::defaults::border::SIZE,
@@ -147,8 +770,17 @@ fn default_profiles() -> Vec<Profile> {
         text_y: crate::constants::defaults::text::OFFSET_Y,
         text_color: crate::constants::defaults::text::COLOR.to_string(),
         text_font_family: default_text_font_family(),
+        label_template: default_label_template(),
+        text_background: String::new(),
+        text_background_padding: default_text_background_padding(),
+        scale_filter: ScaleFilter::default(),
         cycle_group: Vec::new(),
+        cycle_sort: CycleSort::default(),
+        privacy_hotkey: None,
+        stacking_rules: Vec::new(),
+        stacking_default: PreviewStacking::default(),
         character_positions: HashMap::new(),
+        monitor_positions: HashMap::new(),
     }]
 }
 
@@ -160,13 +792,83 @@ impl Default for GlobalSettings {
             window_height: default_window_height(),
             window_x: None,
             window_y: None,
+            manager_tab: ManagerTab::default(),
+            start_minimized_to_tray: false,
             minimize_clients_on_switch: crate::constants::defaults::behavior::MINIMIZE_CLIENTS_ON_SWITCH,
+            client_switch_action: ClientSwitchAction::default(),
             hotkey_require_eve_focus: crate::constants::defaults::behavior::HOTKEY_REQUIRE_EVE_FOCUS,
+            hotkey_backend: HotkeyBackend::default(),
+            privacy_hotkey: None,
+            focus_last_hotkey: None,
+            focus_back_hotkey: None,
+            focus_forward_hotkey: None,
+            mouse_cycle_forward: None,
+            mouse_cycle_backward: None,
+            chord_leader: None,
+            chord_timeout_ms: default_chord_timeout_ms(),
+            cycle_wrap: default_cycle_wrap(),
+            cycle_strip: None,
+            switch_osd: false,
+            switch_osd_ms: default_switch_osd_ms(),
             hide_when_no_focus: crate::constants::defaults::behavior::HIDE_WHEN_NO_FOCUS,
+            prevent_minimize: crate::constants::defaults::behavior::PREVENT_MINIMIZE,
             snap_threshold: default_snap_threshold(),
+            show_drag_guides: crate::constants::defaults::behavior::SHOW_DRAG_GUIDES,
+            drag_max_fps: default_drag_max_fps(),
+            drag_ghost: crate::constants::defaults::behavior::DRAG_GHOST,
+            overlap_avoidance: OverlapAvoidance::default(),
+            screen_margins: ScreenMargins::default(),
+            raise_thumbnail_on_hover: crate::constants::defaults::behavior::RAISE_THUMBNAIL_ON_HOVER,
+            raise_focused_thumbnail: crate::constants::defaults::behavior::RAISE_FOCUSED_THUMBNAIL,
             preserve_thumbnail_position_on_swap: default_preserve_thumbnail_position_on_swap(),
+            monitor_position_sets: default_monitor_position_sets(),
             default_thumbnail_width: default_thumbnail_width(),
             default_thumbnail_height: default_thumbnail_height(),
+            mqtt: MqttSettings::default(),
+            window_rules: Vec::new(),
+            monitor_colors: Vec::new(),
+            night_mode: NightModeSettings::default(),
+            thumbnail_max_fps: default_thumbnail_max_fps(),
+            low_bandwidth: LowBandwidth::default(),
+            opacity_mode: OpacityMode::default(),
+            render_backend: RenderBackend::default(),
+            panel: PanelSettings::default(),
+            show_process_stats: false,
+            stall_warning_secs: default_stall_warning_secs(),
+            autosave_interval_secs: default_autosave_interval_secs(),
+            live_source_refresh: false,
+            title_pattern: default_title_pattern(),
+            hover_tooltip: false,
+            usage_stats: false,
+            large_controls: false,
+            wm_preset: WmPreset::default(),
+            redirect_fullscreen_clients: false,
+        }
+    }
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            broker_url: default_mqtt_broker_url(),
+            username: None,
+            password: None,
+            topic_prefix: default_mqtt_topic_prefix(),
+            client_id: default_mqtt_client_id(),
+        }
+    }
+}
+
+impl Default for NightModeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            follow_gamma: false,
+            start_hour: default_night_start_hour(),
+            end_hour: default_night_end_hour(),
+            tint_percent: default_night_tint_percent(),
+            brightness_percent: default_night_brightness_percent(),
         }
     }
 }
@@ -180,13 +882,57 @@ impl GlobalSettings {
             window_height: 0,
             window_x: None,
             window_y: None,
+            manager_tab: ManagerTab::default(),
+            start_minimized_to_tray: false,
             minimize_clients_on_switch: false,
+            client_switch_action: ClientSwitchAction::default(),
             hotkey_require_eve_focus: false,
+            hotkey_backend: HotkeyBackend::default(),
+            privacy_hotkey: None,
+            focus_last_hotkey: None,
+            focus_back_hotkey: None,
+            focus_forward_hotkey: None,
+            mouse_cycle_forward: None,
+            mouse_cycle_backward: None,
+            chord_leader: None,
+            chord_timeout_ms: default_chord_timeout_ms(),
+            cycle_wrap: default_cycle_wrap(),
+            cycle_strip: None,
+            switch_osd: false,
+            switch_osd_ms: default_switch_osd_ms(),
             hide_when_no_focus: false,
+            prevent_minimize: false,
             snap_threshold: 0,
+            show_drag_guides: false,
+            drag_max_fps: 0,
+            drag_ghost: false,
+            overlap_avoidance: OverlapAvoidance::default(),
+            screen_margins: ScreenMargins::default(),
+            raise_thumbnail_on_hover: false,
+            raise_focused_thumbnail: false,
             preserve_thumbnail_position_on_swap: false,
+            monitor_position_sets: false,
             default_thumbnail_width: 0,
             default_thumbnail_height: 0,
+            mqtt: MqttSettings::default(),
+            window_rules: Vec::new(),
+            monitor_colors: Vec::new(),
+            night_mode: NightModeSettings::default(),
+            thumbnail_max_fps: default_thumbnail_max_fps(),
+            low_bandwidth: LowBandwidth::default(),
+            opacity_mode: OpacityMode::default(),
+            render_backend: RenderBackend::default(),
+            panel: PanelSettings::default(),
+            show_process_stats: false,
+            stall_warning_secs: 0,
+            autosave_interval_secs: 0,
+            live_source_refresh: false,
+            title_pattern: default_title_pattern(),
+            hover_tooltip: false,
+            usage_stats: false,
+            large_controls: false,
+            wm_preset: WmPreset::default(),
+            redirect_fullscreen_clients: false,
         }
     }
 }
@@ -200,11 +946,27 @@ impl Profile {
         profile
     }
     
+    /// Privacy mode key in effect: this profile's own, else the global one
+    pub fn effective_privacy_hotkey<'a>(&'a self, global: &'a GlobalSettings) -> Option<&'a str> {
+        self.privacy_hotkey.as_deref()
+            .filter(|name| !name.trim().is_empty())
+            .or(global.privacy_hotkey.as_deref())
+    }
+    
+    /// Remember `positions` as the thumbnail geometry of monitor setup `setup` (monitor_position_sets)
+    pub fn remember_monitor_positions(&mut self, setup: &str, positions: &HashMap<String, CharacterSettings>) {
+        self.monitor_positions.entry(setup.to_string()).or_default()
+            .extend(positions.iter().map(|(character, settings)| (character.clone(), ThumbnailGeometry::of(settings))));
+    }
+    
     /// Create empty Profile (will be populated via IPC)
     pub fn empty() -> Self {
         Self {
             name: String::new(),
             description: String::new(),
+            inherits: None,
+            own_fields: BTreeSet::new(),
+            layout: None,
             opacity_percent: 0,
             border_enabled: false,
             border_size: 0,
@@ -214,21 +976,35 @@ impl Profile {
             text_y: 0,
             text_color: String::new(),
             text_font_family: String::new(),
+            label_template: String::new(),
+            text_background: String::new(),
+            text_background_padding: 0,
+            scale_filter: ScaleFilter::default(),
             cycle_group: Vec::new(),
+            cycle_sort: CycleSort::default(),
+            privacy_hotkey: None,
+            stacking_rules: Vec::new(),
+            stacking_default: PreviewStacking::default(),
             character_positions: HashMap::new(),
+            monitor_positions: HashMap::new(),
         }
     }
 }
 
 impl Config {
+    /// Config file: eve-l-preview.toml when it exists, eve-l-preview.json otherwise
     pub fn path() -> PathBuf {
-        let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-        path.push(crate::constants::config::APP_DIR);
-        path.push(crate::constants::config::FILENAME);
-        path
+        let mut dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+        dir.push(crate::constants::config::APP_DIR);
+        let toml = dir.join(crate::constants::config::TOML_FILENAME);
+        if toml.exists() {
+            toml
+        } else {
+            dir.join(crate::constants::config::FILENAME)
+        }
     }
     
-    /// Load configuration from JSON file or create default
+    /// Load configuration from the JSON or TOML file, or create a default one
     pub fn load() -> Result<Self> {
         let config_path = Self::path();
         
@@ -242,28 +1018,29 @@ impl Config {
         let contents = fs::read_to_string(&config_path)
             .with_context(|| format!("Failed to read config from {:?}", config_path))?;
         
-        let config: Config = serde_json::from_str(&contents)
-            .with_context(|| format!("Failed to parse JSON from {:?}", config_path))?;
+        let format = ConfigFormat::detect(&config_path, &contents);
+        let config = format::parse(&contents, format)
+            .with_context(|| format!("Failed to parse {} from {:?}", format.name(), config_path))?;
         
         info!("Loaded config with {} profile(s)", config.profiles.len());
         Ok(config)
     }
     
-    /// Save configuration to JSON file
+    /// Save configuration, in the format of the file it goes to
     /// GUI is now the single source of truth for all config writes (including character positions)
     pub fn save(&self) -> Result<()> {
         let config_path = Self::path();
         
-        // Ensure config directory exists
+        // Ensure config directory exists (owner-only, the config may hold credentials)
         if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)
+            crate::permissions::create_private_dir(parent)
                 .with_context(|| format!("Failed to create config directory {:?}", parent))?;
         }
         
-        let json_string = serde_json::to_string_pretty(self)
-            .context("Failed to serialize config to JSON")?;
+        let existing = fs::read_to_string(&config_path).unwrap_or_default();
+        let contents = format::to_string(self, ConfigFormat::detect(&config_path, &existing))?;
         
-        fs::write(&config_path, json_string)
+        crate::permissions::write_private(&config_path, contents.as_bytes())
             .with_context(|| format!("Failed to write config to {:?}", config_path))?;
         
         info!("Saved config to {:?}", config_path);
@@ -271,11 +1048,172 @@ impl Config {
     }
 }
 
+/// Part of the config the manager resets to defaults (and undoes) on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetScope {
+    /// The profile's appearance: opacity, border, label text and font, scale filter
+    Visual,
+    /// Global behavior settings, without hotkeys and the manager window's own state
+    Behavior,
+    /// Global and per-profile hotkey settings (the cycle_group list is kept)
+    Hotkeys,
+    /// Every setting of the profile; its name, description, base profile, layout, cycle_group, characters and monitor positions are kept
+    Profile,
+}
+
+impl ResetScope {
+    pub fn label(self) -> &'static str {
+        match self {
+            ResetScope::Visual => "visual settings",
+            ResetScope::Behavior => "behavior settings",
+            ResetScope::Hotkeys => "hotkey settings",
+            ResetScope::Profile => "profile",
+        }
+    }
+}
+
+/// Config as it was before a reset, for undo
+#[derive(Debug, Clone)]
+pub struct ResetUndo {
+    pub scope: ResetScope,
+    profile_idx: usize,
+    before: Config,
+}
+
+/// Copy the global hotkey settings from `from`
+fn copy_global_hotkeys(to: &mut GlobalSettings, from: &GlobalSettings) {
+    to.hotkey_require_eve_focus = from.hotkey_require_eve_focus;
+    to.hotkey_backend = from.hotkey_backend;
+    to.cycle_wrap = from.cycle_wrap;
+    to.cycle_strip = from.cycle_strip;
+    to.privacy_hotkey = from.privacy_hotkey.clone();
+    to.focus_last_hotkey = from.focus_last_hotkey.clone();
+    to.focus_back_hotkey = from.focus_back_hotkey.clone();
+    to.focus_forward_hotkey = from.focus_forward_hotkey.clone();
+    to.mouse_cycle_forward = from.mouse_cycle_forward;
+    to.mouse_cycle_backward = from.mouse_cycle_backward;
+    to.chord_leader = from.chord_leader.clone();
+    to.chord_timeout_ms = from.chord_timeout_ms;
+    to.switch_osd = from.switch_osd;
+    to.switch_osd_ms = from.switch_osd_ms;
+}
+
+impl Config {
+    /// Copy one scope of settings from `from` (global settings, and the profile at `profile_idx`)
+    fn copy_scope(&mut self, scope: ResetScope, from: &GlobalSettings, from_profile: &Profile, profile_idx: usize) {
+        let Some(profile) = self.profiles.get_mut(profile_idx) else {
+            return;
+        };
+        match scope {
+            ResetScope::Visual => {
+                profile.opacity_percent = from_profile.opacity_percent;
+                profile.border_enabled = from_profile.border_enabled;
+                profile.border_size = from_profile.border_size;
+                profile.border_color = from_profile.border_color.clone();
+                profile.text_size = from_profile.text_size;
+                profile.text_x = from_profile.text_x;
+                profile.text_y = from_profile.text_y;
+                profile.text_color = from_profile.text_color.clone();
+                profile.text_font_family = from_profile.text_font_family.clone();
+                profile.label_template = from_profile.label_template.clone();
+                profile.text_background = from_profile.text_background.clone();
+                profile.text_background_padding = from_profile.text_background_padding;
+                profile.scale_filter = from_profile.scale_filter;
+            }
+            ResetScope::Hotkeys => {
+                copy_global_hotkeys(&mut self.global, from);
+                profile.cycle_sort = from_profile.cycle_sort;
+                profile.privacy_hotkey = from_profile.privacy_hotkey.clone();
+            }
+            ResetScope::Behavior => {
+                let current = std::mem::replace(&mut self.global, from.clone());
+                copy_global_hotkeys(&mut self.global, &current);
+                self.global.selected_profile = current.selected_profile;
+                self.global.window_width = current.window_width;
+                self.global.window_height = current.window_height;
+                self.global.window_x = current.window_x;
+                self.global.window_y = current.window_y;
+                self.global.manager_tab = current.manager_tab;
+            }
+            ResetScope::Profile => {
+                let current = std::mem::replace(profile, from_profile.clone());
+                profile.name = current.name;
+                profile.description = current.description;
+                profile.inherits = current.inherits;
+                profile.own_fields = current.own_fields;
+                profile.layout = current.layout;
+                profile.cycle_group = current.cycle_group;
+                profile.character_positions = current.character_positions;
+                profile.monitor_positions = current.monitor_positions;
+            }
+        }
+    }
+
+    /// Reset `scope` to defaults, the profile-level parts in the profile at `profile_idx`
+    /// Returns what undo needs to put the previous values back
+    pub fn reset(&mut self, scope: ResetScope, profile_idx: usize) -> ResetUndo {
+        let before = self.clone();
+        let defaults = GlobalSettings::default();
+        let default_profile = Profile::default_with_name(String::new(), String::new());
+        self.copy_scope(scope, &defaults, &default_profile, profile_idx);
+        ResetUndo { scope, profile_idx, before }
+    }
+
+    /// Put back the settings a reset replaced; anything else changed since is kept
+    pub fn undo_reset(&mut self, undo: ResetUndo) {
+        let Some(profile) = undo.before.profiles.get(undo.profile_idx) else {
+            return;
+        };
+        self.copy_scope(undo.scope, &undo.before.global, profile, undo.profile_idx);
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             global: GlobalSettings::default(),
             profiles: default_profiles(),
+            layouts: HashMap::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_and_undo_only_touch_their_scope() {
+        let mut config = Config::default();
+        config.global.snap_threshold = 40;
+        config.global.privacy_hotkey = Some("F12".to_string());
+        config.global.window_width = 900;
+        config.profiles[0].opacity_percent = 30;
+        config.profiles[0].cycle_group = vec!["FC".to_string()];
+
+        let undo = config.reset(ResetScope::Behavior, 0);
+        assert_eq!(config.global.snap_threshold, GlobalSettings::default().snap_threshold);
+        // Hotkeys and the manager window's state aren't behavior
+        assert_eq!(config.global.privacy_hotkey.as_deref(), Some("F12"));
+        assert_eq!(config.global.window_width, 900);
+        assert_eq!(config.profiles[0].opacity_percent, 30);
+
+        // Changes made after the reset survive its undo
+        config.profiles[0].opacity_percent = 50;
+        config.undo_reset(undo);
+        assert_eq!(config.global.snap_threshold, 40);
+        assert_eq!(config.profiles[0].opacity_percent, 50);
+    }
+
+    #[test]
+    fn test_profile_reset_keeps_identity_and_characters() {
+        let mut config = Config::default();
+        config.profiles[0].name = "Mining".to_string();
+        config.profiles[0].opacity_percent = 30;
+        config.profiles[0].cycle_group = vec!["FC".to_string()];
+        config.reset(ResetScope::Profile, 0);
+        assert_eq!(config.profiles[0].name, "Mining");
+        assert_eq!(config.profiles[0].cycle_group, vec!["FC".to_string()]);
+        assert_ne!(config.profiles[0].opacity_percent, 30);
+    }
+}
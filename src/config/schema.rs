@@ -0,0 +1,125 @@
+//! Machine-readable schema of the config file (`--dump-config-schema`)
+//!
+//! Derived from the config structs with schemars so it can't drift from them:
+//! field names, types, descriptions (doc comments) and ranges
+//! (`#[schemars(range)]`) come from the types, defaults from serializing the
+//! default config. Subschemas are inlined, so every field sits under its
+//! parent's `properties` where the settings forms and overrides look it up.
+//! The output is JSON Schema (draft 2020-12).
+
+use schemars::generate::SchemaSettings;
+use schemars::transform::{transform_subschemas, Transform};
+use schemars::Schema;
+use serde_json::{json, Value};
+
+use super::profile::{Config, Profile};
+use crate::types::CharacterSettings;
+
+/// Unit enums as a plain `enum` of their serialized names, optional ones with
+/// `"type": ["string", "null"]` like the other options (schemars writes enums
+/// with documented variants as a `oneOf` of consts, putting any undocumented
+/// ones first in a single `enum`, and wraps optional ones in an `anyOf`)
+#[derive(Clone)]
+struct UnitEnums;
+
+fn variant_names(variant: &Value) -> Option<Vec<Value>> {
+    match variant.get("const") {
+        Some(name) => Some(vec![name.clone()]),
+        None => variant.get("enum")?.as_array().cloned(),
+    }
+}
+
+impl Transform for UnitEnums {
+    fn transform(&mut self, schema: &mut Schema) {
+        transform_subschemas(self, schema);
+        let names: Option<Vec<Vec<Value>>> = schema.get("oneOf").and_then(Value::as_array)
+            .and_then(|variants| variants.iter().map(variant_names).collect());
+        if let Some(names) = names {
+            let names = names.concat();
+            schema.remove("oneOf");
+            schema.insert("type".to_string(), json!("string"));
+            schema.insert("enum".to_string(), Value::Array(names));
+        }
+        let inner = match schema.get("anyOf").and_then(Value::as_array).map(Vec::as_slice) {
+            Some([inner, null]) if null == &json!({ "type": "null" }) && inner["type"] == "string" => inner.clone(),
+            _ => return,
+        };
+        schema.remove("anyOf");
+        for (key, value) in inner.as_object().into_iter().flatten() {
+            if schema.get(key.as_str()).is_none() {
+                schema.insert(key.clone(), value.clone());
+            }
+        }
+        schema.insert("type".to_string(), json!(["string", "null"]));
+    }
+}
+
+/// Give every property without a default (fields serde requires) the value serialized for it in `default`
+fn add_defaults(schema: &mut Value, default: &Value) {
+    let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) else {
+        return;
+    };
+    for (name, field) in properties {
+        let Some(value) = default.get(name) else {
+            continue;
+        };
+        add_defaults(field, value);
+        if let Some(field) = field.as_object_mut() {
+            field.entry("default").or_insert_with(|| value.clone());
+        }
+    }
+}
+
+/// JSON Schema of the whole config file
+pub fn config_schema() -> Value {
+    let generator = SchemaSettings::draft2020_12()
+        .with(|settings| settings.inline_subschemas = true)
+        .with_transform(UnitEnums)
+        .into_generator();
+    let mut schema = generator.into_root_schema_for::<Config>().to_value();
+    schema["title"] = json!("EVE-L Preview config");
+
+    let config = serde_json::to_value(Config::default()).unwrap_or_default();
+    let profile = serde_json::to_value(Profile::default_with_name(String::new(), String::new())).unwrap_or_default();
+    let character = serde_json::to_value(CharacterSettings::new(0, 0, 0, 0)).unwrap_or_default();
+    let properties = &mut schema["properties"];
+    add_defaults(&mut properties["global"], &config["global"]);
+    add_defaults(&mut properties["profiles"]["items"], &profile);
+    add_defaults(&mut properties["profiles"]["items"]["properties"]["characters"]["additionalProperties"], &character);
+    add_defaults(&mut properties["layouts"]["additionalProperties"]["additionalProperties"], &character);
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_serialized_field_is_described() {
+        let schema = config_schema();
+        let global = &schema["properties"]["global"]["properties"];
+        let defaults = serde_json::to_value(Config::default()).unwrap();
+        for key in defaults["global"].as_object().unwrap().keys() {
+            assert!(global.get(key).is_some_and(|field| field.get("type").is_some()), "global.{} missing from schema", key);
+        }
+        let profile = &schema["properties"]["profiles"]["items"]["properties"];
+        for key in defaults["profiles"][0].as_object().unwrap().keys() {
+            assert!(profile.get(key).is_some_and(|field| field.get("type").is_some()), "profile.{} missing from schema", key);
+        }
+    }
+
+    #[test]
+    fn test_field_details() {
+        let schema = config_schema();
+        let global = &schema["properties"]["global"]["properties"];
+        assert_eq!(global["wm_preset"]["enum"][0], "auto");
+        assert_eq!(global["night_mode"]["properties"]["end_hour"]["maximum"], 23);
+        assert_eq!(global["cycle_wrap"]["default"], true);
+        assert_eq!(global["window_x"]["type"], json!(["integer", "null"]));
+        let profile = &schema["properties"]["profiles"]["items"]["properties"];
+        assert_eq!(profile["opacity_percent"]["maximum"], 100);
+        let character = &profile["characters"]["additionalProperties"]["properties"];
+        assert!(character["width"].is_object());
+        assert_eq!(character["label_opacity"]["maximum"], 100);
+    }
+}
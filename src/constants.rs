@@ -17,11 +17,26 @@ pub mod x11 {
     /// Source indication for _NET_ACTIVE_WINDOW (2 = pager/direct user action)
     pub const ACTIVE_WINDOW_SOURCE_PAGER: u32 = 2;
     
+    /// _NET_WM_STATE action: remove/unset property (0)
+    pub const NET_WM_STATE_REMOVE: u32 = 0;
+    
     /// _NET_WM_STATE action: add/set property (1)
     pub const NET_WM_STATE_ADD: u32 = 1;
 
     /// WM_CHANGE_STATE iconic value (requests the WM to minimize)
     pub const ICONIC_STATE: u32 = 3;
+    
+    /// _MOTIF_WM_HINTS flag: decorations field is valid
+    pub const MWM_HINTS_DECORATIONS: u32 = 1 << 1;
+    
+    /// Number of 32-bit fields in _MOTIF_WM_HINTS (flags, functions, decorations, input_mode, status)
+    pub const MWM_HINTS_LENGTH: u32 = 5;
+    
+    /// Keysym for the Escape key (XK_Escape)
+    pub const KEYSYM_ESCAPE: u32 = 0xff1b;
+    
+    /// WM_CLASS (instance and class) of thumbnail windows
+    pub const THUMBNAIL_WM_CLASS: &str = "eve-l-preview";
 }
 
 /// Input event constants (from evdev)
@@ -37,14 +52,93 @@ pub mod input {
     
     /// Key code for Right Shift key  
     pub const KEY_RIGHTSHIFT: u16 = 54;
+    
+    /// X keycode = evdev key code + 8 (evdev and libinput X drivers)
+    pub const X_KEYCODE_OFFSET: u16 = 8;
+    
+    /// Keys suggested in place of a hotkey another app holds, in order of preference
+    pub const HOTKEY_SUGGESTIONS: [&str; 24] = [
+        "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12",
+        "KP1", "KP2", "KP3", "KP4", "KP5", "KP6", "KP7", "KP8", "KP9", "KP0",
+        "PAUSE", "SCROLLLOCK",
+    ];
+    
+    /// How many replacement keys to suggest per conflict
+    pub const MAX_HOTKEY_SUGGESTIONS: usize = 3;
+    
+    /// X keysym names accepted for hotkeys, with the evdev key they mean
+    /// (also what the portal is offered, since desktops speak keysyms)
+    pub const KEYSYM_ALIASES: [(&str, &str); 26] = [
+        ("KP_Enter", "KPENTER"),
+        ("KP_Add", "KPPLUS"),
+        ("KP_Subtract", "KPMINUS"),
+        ("KP_Multiply", "KPASTERISK"),
+        ("KP_Divide", "KPSLASH"),
+        ("KP_Decimal", "KPDOT"),
+        ("Num_Lock", "NUMLOCK"),
+        ("Scroll_Lock", "SCROLLLOCK"),
+        ("Caps_Lock", "CAPSLOCK"),
+        ("Control_L", "LEFTCTRL"),
+        ("Control_R", "RIGHTCTRL"),
+        ("Shift_L", "LEFTSHIFT"),
+        ("Shift_R", "RIGHTSHIFT"),
+        ("Alt_L", "LEFTALT"),
+        ("Alt_R", "RIGHTALT"),
+        ("Super_L", "LEFTMETA"),
+        ("Super_R", "RIGHTMETA"),
+        ("Prior", "PAGEUP"),
+        ("Next", "PAGEDOWN"),
+        ("XF86AudioPlay", "PLAYPAUSE"),
+        ("XF86AudioStop", "STOPCD"),
+        ("XF86AudioNext", "NEXTSONG"),
+        ("XF86AudioPrev", "PREVIOUSSONG"),
+        ("XF86AudioMute", "MUTE"),
+        ("XF86AudioRaiseVolume", "VOLUMEUP"),
+        ("XF86AudioLowerVolume", "VOLUMEDOWN"),
+    ];
+    
+    /// Key code for Escape (cancels "press a key to bind")
+    pub const KEY_ESC: u16 = 1;
+}
+
+/// XDG desktop portal GlobalShortcuts identifiers
+pub mod portal {
+    /// Shortcut id for cycling to the next character
+    pub const CYCLE_FORWARD: &str = "cycle-forward";
+
+    /// Shortcut id for cycling to the previous character
+    pub const CYCLE_BACKWARD: &str = "cycle-backward";
+
+    /// Prefix of per-character shortcut ids (followed by the character name)
+    pub const FOCUS_PREFIX: &str = "focus:";
+
+    /// Shortcut id for toggling privacy mode
+    pub const TOGGLE_PRIVACY: &str = "toggle-privacy";
+
+    /// Shortcut id for going back to the previously focused character
+    pub const FOCUS_LAST: &str = "focus-last";
+
+    /// Shortcut id for a step back along the focus history
+    pub const FOCUS_BACK: &str = "focus-back";
+
+    /// Shortcut id for a step forward along the focus history
+    pub const FOCUS_FORWARD: &str = "focus-forward";
+
+    /// Prefix of portal request/session handle tokens
+    pub const TOKEN_PREFIX: &str = "eve_l_preview";
 }
 
 /// Mouse button constants
 pub mod mouse {
     /// Left mouse button number
     pub const BUTTON_LEFT: u8 = 1;
+    /// Middle mouse button number
+    pub const BUTTON_MIDDLE: u8 = 2;
     /// Right mouse button number
     pub const BUTTON_RIGHT: u8 = 3;
+    /// Side buttons ("back"/MB4 and "forward"/MB5) and the extra buttons some mice have,
+    /// offered for mouse_cycle_forward/backward
+    pub const HOTKEY_BUTTONS: [u8; 5] = [8, 9, 10, 11, 12];
 }
 
 /// Wine process detection constants
@@ -58,14 +152,32 @@ pub mod wine {
 
 /// EVE Online window detection constants
 pub mod eve {
-    /// Prefix for EVE client window titles (followed by character name)
-    pub const WINDOW_TITLE_PREFIX: &str = "EVE - ";
+    /// Default title_pattern: "EVE - CharacterName", or just "EVE" when logged out
+    pub const DEFAULT_TITLE_PATTERN: &str = r"^EVE(?: - (?P<character>.+))?$";
     
-    /// Window title for logged-out EVE clients
-    pub const LOGGED_OUT_TITLE: &str = "EVE";
+    /// Named group in title_pattern holding the character name
+    pub const TITLE_PATTERN_GROUP: &str = "character";
     
     /// Display name for logged-out character (shown in logs)
     pub const LOGGED_OUT_DISPLAY_NAME: &str = "login_screen";
+    
+    /// WM_CLASS wine gives EVE client windows
+    pub const WINDOW_CLASS: &str = "exefile.exe";
+}
+
+/// MQTT protocol constants (3.1.1, QoS 0 publishing only)
+pub mod mqtt {
+    /// Default broker port when the URL doesn't specify one
+    pub const DEFAULT_PORT: u16 = 1883;
+    
+    /// Protocol level for MQTT 3.1.1
+    pub const PROTOCOL_LEVEL: u8 = 4;
+    
+    /// Timeout for TCP connect, CONNACK and each publish write in milliseconds
+    pub const CONNECT_TIMEOUT_MS: u64 = 3000;
+    
+    /// Delay before retrying a failed broker connection in milliseconds
+    pub const RECONNECT_DELAY_MS: u64 = 5000;
 }
 
 /// Default window positioning constants
@@ -74,6 +186,186 @@ pub mod positioning {
     pub const DEFAULT_SPAWN_OFFSET: i16 = 20;
 }
 
+/// Low bandwidth mode limits (remote X displays)
+pub mod low_bandwidth {
+    /// Maximum thumbnail redraws per second, per thumbnail
+    pub const MAX_FPS: u16 = 2;
+    
+    /// Largest thumbnail drawn; bigger saved sizes are scaled down (aspect kept)
+    pub const MAX_WIDTH: u16 = 320;
+    pub const MAX_HEIGHT: u16 = 180;
+}
+
+/// Client CPU/RAM readout (show_process_stats)
+pub mod process_stats {
+    /// Seconds between /proc samples
+    pub const INTERVAL_SECS: u64 = 2;
+    
+    /// Kernel clock ticks per second in /proc/<pid>/stat (USER_HZ, 100 on every Linux port)
+    pub const CLOCK_TICKS: u64 = 100;
+    
+    /// /proc files read per client
+    pub const STAT_FORMAT: &str = "/proc/{}/stat";
+    pub const STATUS_FORMAT: &str = "/proc/{}/status";
+}
+
+/// Marking of clients that stopped drawing (stall_warning_secs)
+pub mod stall {
+    /// Badge drawn under the name label
+    pub const LABEL: &str = "STALLED";
+}
+
+/// Periodic sanity pass over the thumbnails
+pub mod watchdog {
+    /// Seconds between checks of every thumbnail against the X server
+    pub const INTERVAL_SECS: u64 = 30;
+}
+
+/// Refreshing thumbnails after the displays slept (DPMS)
+pub mod display_sleep {
+    /// How often the DPMS power level is checked
+    pub const POLL_SECS: u64 = 2;
+    
+    /// Without DPMS: a frame after this long without any counts as the displays waking up
+    pub const DAMAGE_GAP_SECS: u64 = 60;
+}
+
+/// Refreshing thumbnails after their client was moved/resized
+pub mod source_refresh {
+    /// Quiet time after the last ConfigureNotify before a move/resize counts as finished
+    pub const SETTLE_MS: u64 = 150;
+}
+
+/// OpenGL thumbnail backend (render_backend = "gl")
+#[cfg(feature = "gl")]
+pub mod gl {
+    /// Longest a GL draw waits for its frame to be finished while events keep arriving
+    pub const FRAME_MS: u64 = 16;
+}
+
+/// Opacity drawn with XRender when no compositor is running (opacity_mode)
+pub mod opacity_blend {
+    /// Root window property holding the wallpaper pixmap
+    pub const WALLPAPER_ATOM: &str = "_XROOTPMAP_ID";
+    
+    /// Background (RGB) shown through thumbnails when there is no wallpaper
+    pub const BACKGROUND: u32 = 0x000000;
+}
+
+/// Privacy mode (pixelated previews)
+pub mod privacy {
+    /// Edge of one pixelation block, in thumbnail pixels
+    pub const BLOCK_SIZE: u16 = 12;
+}
+
+/// Redacted client areas (per-character redactions)
+pub mod redaction {
+    /// Smallest area drawn on a thumbnail that is kept, in thumbnail pixels
+    pub const MIN_DRAW_SIZE: u16 = 4;
+    
+    /// Outline color while drawing an area (RGB pixel value: red)
+    pub const OUTLINE_COLOR: u32 = 0xFF4040;
+}
+
+/// Night mode schedule checks
+pub mod night_mode {
+    /// Seconds between checks of the clock / gamma ramp
+    pub const CHECK_INTERVAL_SECS: u64 = 30;
+    
+    /// Colour multiplied into thumbnails at full tint strength (RGB: warm orange)
+    pub const TINT_COLOR: u32 = 0xFFB46B;
+    
+    /// Blue end of the gamma ramp below this fraction of red counts as warmed by redshift/gammastep
+    pub const WARM_RAMP_RATIO: f32 = 0.9;
+}
+
+/// Rebuilding thumbnails whose X resources keep failing
+pub mod x_errors {
+    /// Errors within this window count towards a rebuild
+    pub const WINDOW_SECS: u64 = 10;
+
+    /// Errors within the window after which the thumbnail is recreated
+    pub const REBUILD_THRESHOLD: usize = 5;
+}
+
+/// Hover tooltip (hover_tooltip)
+pub mod tooltip {
+    /// Window over which the client frame rate is averaged
+    pub const FPS_WINDOW_MS: u64 = 2000;
+    
+    /// Gap between the text and the tooltip edge, and between thumbnail and tooltip
+    pub const PADDING: u16 = 6;
+    
+    /// Text color (ARGB)
+    pub const TEXT_COLOR: u32 = 0xFFFFFFFF;
+    
+    /// Background pixel (RGB)
+    pub const BACKGROUND: u32 = 0x202020;
+    
+    /// Background pixel behind the selected item of a strip (RGB)
+    pub const HIGHLIGHT: u32 = 0x3D5A80;
+}
+
+/// Recently focused characters (focus last, back and forward)
+pub mod focus_history {
+    /// Characters remembered, and steps back the history goes
+    pub const LENGTH: usize = 10;
+}
+
+/// Character name shown mid-screen after a hotkey switch
+pub mod osd {
+    /// OSD font size relative to the label text size
+    pub const TEXT_SCALE: f32 = 2.5;
+    
+    /// Smallest OSD font size, so tiny labels still give a readable OSD
+    pub const MIN_TEXT_SIZE: f32 = 28.0;
+}
+
+/// Terminating wedged clients from the GUI
+pub mod client_control {
+    /// Seconds a client gets to exit after SIGTERM before SIGKILL
+    pub const TERM_GRACE_SECS: u64 = 5;
+    
+    /// Seconds to wait for the process to disappear after SIGKILL
+    pub const KILL_WAIT_SECS: u64 = 2;
+    
+    /// How often to check whether the process is gone
+    pub const POLL_MS: u64 = 100;
+    
+    /// Shell running the per-character launch command
+    pub const SHELL: &str = "sh";
+}
+
+/// Panel mode window constants
+pub mod panel {
+    /// Window title shown by the WM
+    pub const TITLE: &[u8] = b"EVE-L-Preview";
+    
+    /// Panel background (RGB pixel value: black)
+    pub const BACKGROUND: u32 = 0x000000;
+}
+
+/// Drag guide line constants
+pub mod drag_guides {
+    /// Guide line thickness in pixels
+    pub const LINE_WIDTH: u16 = 1;
+    
+    /// Line color for snap target edges (RGB pixel value: cyan)
+    pub const TARGET_COLOR: u32 = 0x00FFFF;
+    
+    /// Line color for the work-area boundary (RGB pixel value: orange)
+    pub const AREA_COLOR: u32 = 0xFF8000;
+    
+    /// Ghost outline thickness in pixels
+    pub const GHOST_WIDTH: u16 = 2;
+    
+    /// Ghost outline color (RGB pixel value: white)
+    pub const GHOST_COLOR: u32 = 0xFFFFFF;
+    
+    /// Selection outline color for group moves (RGB pixel value: gold)
+    pub const SELECTION_COLOR: u32 = 0xFFD700;
+}
+
 /// Fixed-point arithmetic constants (X11 render transforms)
 pub mod fixed_point {
     /// Fixed-point multiplier for conversion (2^16)
@@ -89,13 +381,19 @@ pub mod paths {
     pub const DEV_INPUT: &str = "/dev/input";
 }
 
-/// User group permissions
+/// User group and file permissions
 pub mod permissions {
     /// Linux group name for input device access
     pub const INPUT_GROUP: &str = "input";
     
     /// Command to add user to input group
     pub const ADD_TO_INPUT_GROUP: &str = "sudo usermod -a -G input $USER";
+    
+    /// Owner-only mode for the config, state files and IPC socket
+    pub const PRIVATE_FILE_MODE: u32 = 0o600;
+    
+    /// Owner-only mode for the directories holding them
+    pub const PRIVATE_DIR_MODE: u32 = 0o700;
 }
 
 /// Configuration paths and filenames
@@ -105,6 +403,36 @@ pub mod config {
     
     /// Configuration filename
     pub const FILENAME: &str = "eve-l-preview.json";
+    
+    /// TOML configuration filename, used instead of FILENAME when it exists
+    pub const TOML_FILENAME: &str = "eve-l-preview.toml";
+}
+
+/// Session summary written at shutdown
+pub mod session {
+    /// Filename under the XDG state directory (in APP_DIR)
+    pub const SUMMARY_FILENAME: &str = "last-session.json";
+}
+
+/// Local usage statistics history (usage_stats)
+pub mod usage_stats {
+    /// Filename under the XDG state directory (in APP_DIR)
+    pub const FILENAME: &str = "usage-stats.json";
+    
+    /// Days of history kept, older ones are dropped
+    pub const MAX_DAYS: usize = 365;
+    
+    /// Days shown in the GUI graph
+    pub const GRAPH_DAYS: usize = 14;
+    
+    /// Height of the GUI graph in points
+    pub const GRAPH_HEIGHT: f32 = 80.0;
+}
+
+/// Write-ahead journal of layout changes received over IPC (GUI)
+pub mod journal {
+    /// Filename under the XDG state directory (in APP_DIR)
+    pub const FILENAME: &str = "ipc-journal.jsonl";
 }
 
 /// GUI-specific constants (egui manager window)
@@ -128,6 +456,18 @@ pub mod gui {
     
     /// Daemon monitoring
     pub const DAEMON_CHECK_INTERVAL_MS: u64 = 500;
+    
+    /// Command palette (Ctrl+P)
+    pub const PALETTE_WIDTH: f32 = 420.0;
+    pub const PALETTE_MAX_RESULTS: usize = 12;
+    
+    /// Width of the profile layout preview before switching
+    pub const LAYOUT_PREVIEW_WIDTH: f32 = 480.0;
+    
+    /// Larger hit targets (large_controls)
+    pub const LARGE_CONTROL_HEIGHT: f32 = 30.0;
+    pub const LARGE_BUTTON_PADDING: egui::Vec2 = egui::vec2(10.0, 6.0);
+    pub const LARGE_ICON_WIDTH: f32 = 22.0;
 }
 
 /// Default configuration values
@@ -180,6 +520,15 @@ pub mod defaults {
         /// Default text color (ARGB hex string: opaque white)
         pub const COLOR: &str = "#40FF00";
         
+        /// Default label template: the alias or character name, as before templates existed
+        pub const LABEL_TEMPLATE: &str = "{character}";
+        
+        /// Label background box color offered when the box is turned on (half-transparent black)
+        pub const BACKGROUND_COLOR: &str = "#80000000";
+        
+        /// Padding of the label background box in pixels
+        pub const BACKGROUND_PADDING: u16 = 4;
+        
         /// Preferred TrueType fonts (tried in order)
         /// First available font will be selected
         pub const FONT_CANDIDATES: &[&str] = &[
@@ -187,6 +536,23 @@ pub mod defaults {
             "Liberation Mono",
             "Noto Sans Mono",
         ];
+
+        /// X core fonts tried when no TrueType font is found, `{}` being the pixel size
+        /// Unicode (iso10646-1) fonts first so non-Latin character names still render
+        pub const CORE_FONT_PATTERNS: &[&str] = &[
+            "-*-*-medium-r-normal--{}-*-*-*-*-*-iso10646-1",
+            "-*-*-medium-r-*--{}-*-*-*-*-*-iso10646-1",
+            "-*-*-*-*-*--{}-*-*-*-*-*-*-*",
+        ];
+
+        /// Core font that every X server has
+        pub const CORE_FONT_FALLBACK: &str = "fixed";
+    }
+    
+    /// Panel mode layout settings
+    pub mod panel {
+        /// Gap between previews and around the panel edge, in pixels
+        pub const SPACING: u16 = 4;
     }
     
     /// Daemon behavior settings
@@ -203,6 +569,18 @@ pub mod defaults {
         /// Preserve thumbnail position when character switches
         pub const PRESERVE_POSITION_ON_SWAP: bool = true;
         
+        /// Remember thumbnail positions per monitor setup
+        pub const MONITOR_POSITION_SETS: bool = false;
+        
+        /// How long a chord waits for its follow-up key after the leader
+        pub const CHORD_TIMEOUT_MS: u32 = 1500;
+        
+        /// Cycling wraps around at the ends of the cycle order
+        pub const CYCLE_WRAP: bool = true;
+        
+        /// How long the switch OSD stays up
+        pub const SWITCH_OSD_MS: u32 = 800;
+        
         /// Minimize other clients when switching via hotkey
         pub const MINIMIZE_CLIENTS_ON_SWITCH: bool = false;
         
@@ -211,5 +589,59 @@ pub mod defaults {
         
         /// Hide thumbnails when EVE window loses focus
         pub const HIDE_WHEN_NO_FOCUS: bool = false;
+        
+        /// Undo iconify requests on tracked clients (restore + lower instead)
+        pub const PREVENT_MINIMIZE: bool = false;
+        
+        /// Draw snap target and work-area guide lines while dragging
+        pub const SHOW_DRAG_GUIDES: bool = false;
+        
+        /// Maximum thumbnail repositions per second while dragging (0 = unlimited)
+        pub const DRAG_MAX_FPS: u16 = 60;
+        
+        /// Drag an outline and move the thumbnail only on release
+        pub const DRAG_GHOST: bool = false;
+        
+        /// Maximum redraws per second of each thumbnail (0 = every damage event)
+        pub const THUMBNAIL_MAX_FPS: u16 = 0;
+        
+        /// Seconds without frames before a client's thumbnail is marked stalled (0 = off)
+        pub const STALL_WARNING_SECS: u16 = 15;
+        
+        /// Seconds reported positions may stay unsaved while they keep changing
+        pub const AUTOSAVE_INTERVAL_SECS: u16 = 30;
+        
+        /// Bring the thumbnail under the pointer to the top of the stack
+        pub const RAISE_THUMBNAIL_ON_HOVER: bool = false;
+        
+        /// Keep the focused character's thumbnail on top of the stack
+        pub const RAISE_FOCUSED_THUMBNAIL: bool = false;
+    }
+    
+    /// Night mode tint of thumbnails
+    pub mod night_mode {
+        /// Local hour night mode starts
+        pub const START_HOUR: u8 = 22;
+        
+        /// Local hour night mode ends
+        pub const END_HOUR: u8 = 6;
+        
+        /// Strength of the warm tint in percent
+        pub const TINT_PERCENT: u8 = 50;
+        
+        /// Brightness kept in percent
+        pub const BRIGHTNESS_PERCENT: u8 = 60;
+    }
+    
+    /// MQTT publisher settings
+    pub mod mqtt {
+        /// Default broker URL (plain TCP, default MQTT port)
+        pub const BROKER_URL: &str = "mqtt://localhost:1883";
+        
+        /// Default topic prefix (topics are "<prefix>/<character>/...")
+        pub const TOPIC_PREFIX: &str = "eve-l-preview";
+        
+        /// Default MQTT client identifier
+        pub const CLIENT_ID: &str = "eve-l-preview";
     }
 }
@@ -0,0 +1,228 @@
+//! Environment doctor (`eve-l-preview doctor`)
+//!
+//! Checks what the previews need from the X server and the desktop, plus the
+//! config file, and prints one pass/warn/fail line per check with a hint on
+//! how to fix anything that isn't right. Exits non-zero if a check failed.
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::ConnectionExt;
+
+use crate::config::format::{self, ConfigFormat};
+use crate::config::profile::Config;
+use crate::constants::{paths, wine};
+use crate::permissions;
+use crate::preview::wm_compat;
+use crate::x11_utils::{compositor_running, window_manager_name, window_manager_running, CachedAtoms};
+
+/// X extensions the previews can't work without (name as the server reports it)
+const REQUIRED_EXTENSIONS: [(&str, &str); 4] = [
+    ("DAMAGE", "tells us when a client redraws"),
+    ("Composite", "keeps client contents available for the previews"),
+    ("RENDER", "scales clients into the previews"),
+    ("XFIXES", "needed by DAMAGE regions"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone)]
+struct Check {
+    status: Status,
+    name: String,
+    detail: String,
+    /// How to fix it (shown for warnings and failures)
+    hint: Option<String>,
+}
+
+impl Check {
+    fn new(status: Status, name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self { status, name: name.into(), detail: detail.into(), hint: None }
+    }
+
+    fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    fn render(&self) -> String {
+        let tag = match self.status {
+            Status::Pass => "PASS",
+            Status::Warn => "WARN",
+            Status::Fail => "FAIL",
+        };
+        let mut line = format!("[{}] {}: {}", tag, self.name, self.detail);
+        if let Some(hint) = self.hint.as_ref().filter(|_| self.status != Status::Pass) {
+            line.push_str(&format!("\n       -> {}", hint));
+        }
+        line
+    }
+}
+
+/// Check a config file's contents: parses, has profiles, the selected profile exists
+fn check_config_contents(contents: &str) -> Check {
+    match format::parse(contents, ConfigFormat::sniff(contents)) {
+        Err(e) => Check::new(Status::Fail, "Config", format!("does not parse: {:#}", e))
+            .hint("Fix the syntax error at the reported line, or move the file away to start with defaults"),
+        Ok(config) if config.profiles.is_empty() => Check::new(Status::Fail, "Config", "has no profiles")
+            .hint("Open the manager and create a profile"),
+        Ok(config) if !config.profiles.iter().any(|p| p.name == config.global.selected_profile) => {
+            Check::new(Status::Warn, "Config", format!(
+                "selected profile \"{}\" doesn't exist, the first profile is used",
+                config.global.selected_profile))
+                .hint("Pick a profile in the manager and save")
+        }
+        Ok(config) => Check::new(Status::Pass, "Config", format!(
+            "{} profile(s), using \"{}\"", config.profiles.len(), config.global.selected_profile)),
+    }
+}
+
+fn check_config() -> Vec<Check> {
+    let path = Config::path();
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return vec![Check::new(Status::Warn, "Config", format!("{} doesn't exist yet", path.display()))
+                .hint("Start the manager once; it creates the config")];
+        }
+        Err(e) => {
+            return vec![Check::new(Status::Fail, "Config", format!("can't read {}: {}", path.display(), e))
+                .hint("Check the file's owner and permissions")];
+        }
+    };
+    let mut checks = vec![check_config_contents(&contents)];
+    let world_writable = [Some(path.as_path()), path.parent()]
+        .into_iter()
+        .flatten()
+        .filter(|checked| fs::metadata(checked).is_ok_and(|m| permissions::is_world_writable(m.permissions().mode())))
+        .map(|checked| checked.display().to_string())
+        .collect::<Vec<_>>();
+    if !world_writable.is_empty() {
+        checks.push(Check::new(Status::Fail, "Config permissions", format!("world-writable: {}", world_writable.join(", ")))
+            .hint(format!("chmod o-w {}", world_writable.join(" "))));
+    }
+    checks
+}
+
+fn is_wine_exe(exe: &Path) -> bool {
+    let exe = exe.to_string_lossy();
+    exe.contains(wine::WINE64_PRELOADER) || exe.contains(wine::WINE_PRELOADER)
+}
+
+fn check_wine() -> Check {
+    let count = fs::read_dir("/proc")
+        .map(|entries| entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter(|pid| fs::read_link(paths::PROC_EXE_FORMAT.replace("{}", &pid.to_string()))
+                .is_ok_and(|exe| is_wine_exe(&exe)))
+            .count())
+        .unwrap_or(0);
+    if count == 0 {
+        Check::new(Status::Warn, "Wine", "no wine processes running")
+            .hint("Start EVE through wine/Proton; the previews only pick up clients running under wine")
+    } else {
+        Check::new(Status::Pass, "Wine", format!("{} wine process(es) running", count))
+    }
+}
+
+fn check_x11() -> Vec<Check> {
+    let (conn, screen_num) = match x11rb::connect(None) {
+        Ok(connection) => connection,
+        Err(e) => {
+            return vec![Check::new(Status::Fail, "X server", format!("can't connect: {}", e))
+                .hint("Run from your desktop session, or set DISPLAY (EVE-L-Preview needs X11 or XWayland)")];
+        }
+    };
+    let screen = &conn.setup().roots[screen_num];
+    let mut checks = vec![Check::new(Status::Pass, "X server", format!(
+        "connected to {} ({} screen(s))",
+        std::env::var("DISPLAY").unwrap_or_default(),
+        conn.setup().roots.len()))];
+
+    for (extension, purpose) in REQUIRED_EXTENSIONS {
+        let present = conn.query_extension(extension.as_bytes())
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .is_some_and(|reply| reply.present);
+        checks.push(if present {
+            Check::new(Status::Pass, extension, "available")
+        } else {
+            Check::new(Status::Fail, extension, format!("missing ({})", purpose))
+                .hint(format!("Enable the {} extension in your X server (Xvfb/Xvnc: +extension {})", extension, extension))
+        });
+    }
+
+    checks.push(if compositor_running(&conn, screen) {
+        Check::new(Status::Pass, "Compositor", "running")
+    } else {
+        Check::new(Status::Warn, "Compositor", "none running")
+            .hint("Previews work, but thumbnail opacity needs a compositor (picom, or your WM's compositing)")
+    });
+
+    let wm_name = CachedAtoms::new(&conn)
+        .and_then(|atoms| window_manager_name(&conn, screen, &atoms))
+        .ok()
+        .flatten();
+    checks.push(match wm_name {
+        Some(name) => Check::new(Status::Pass, "Window manager", format!(
+            "{} (EWMH, wm_preset auto uses {:?})", name, wm_compat::detect(Some(&name)))),
+        None if window_manager_running(&conn, screen) => {
+            Check::new(Status::Warn, "Window manager", "running but doesn't advertise EWMH support")
+                .hint("Switching, minimizing and always-on-top may not work; an EWMH WM (i3, KWin, Openbox, ...) is recommended")
+        }
+        None => Check::new(Status::Warn, "Window manager", "none running")
+            .hint("Fine for a bare X server: the previews raise and focus clients themselves"),
+    });
+    checks
+}
+
+/// Run every check and print the report
+pub fn run() -> Result<()> {
+    let mut checks = check_x11();
+    checks.push(check_wine());
+    checks.extend(check_config());
+
+    for check in &checks {
+        println!("{}", check.render());
+    }
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let failed = count(Status::Fail);
+    println!();
+    println!("{} passed, {} warning(s), {} failed", count(Status::Pass), count(Status::Warn), failed);
+    if failed > 0 {
+        bail!("{} check(s) failed", failed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_shows_hint_only_when_not_passing() {
+        let pass = Check::new(Status::Pass, "DAMAGE", "available").hint("never shown");
+        assert_eq!(pass.render(), "[PASS] DAMAGE: available");
+        let fail = Check::new(Status::Fail, "RENDER", "missing").hint("enable it");
+        assert_eq!(fail.render(), "[FAIL] RENDER: missing\n       -> enable it");
+    }
+
+    #[test]
+    fn test_check_config_contents() {
+        assert_eq!(check_config_contents("{ not json").status, Status::Fail);
+        let config = serde_json::to_string(&Config::default()).unwrap();
+        assert_eq!(check_config_contents(&config).status, Status::Pass);
+        let mut missing = Config::default();
+        missing.global.selected_profile = "Gone".to_string();
+        assert_eq!(check_config_contents(&serde_json::to_string(&missing).unwrap()).status, Status::Warn);
+    }
+}
@@ -0,0 +1,45 @@
+//! Accessibility helpers: screen reader labels and larger controls
+
+use eframe::egui;
+use crate::config::profile::ManagerTab;
+use crate::constants::gui::*;
+
+/// Add `widget` after a text label that screen readers announce as its name
+pub fn labelled(ui: &mut egui::Ui, text: &str, widget: impl egui::Widget) -> egui::Response {
+    let label = ui.label(text);
+    ui.add(widget).labelled_by(label.id)
+}
+
+/// Grow buttons, sliders and checkboxes (large_controls) or restore egui's sizes
+pub fn apply_control_size(ctx: &egui::Context, large: bool) {
+    let defaults = egui::Spacing::default();
+    ctx.style_mut(|style| {
+        let spacing = &mut style.spacing;
+        if large {
+            spacing.interact_size = egui::vec2(defaults.interact_size.x, LARGE_CONTROL_HEIGHT);
+            spacing.button_padding = LARGE_BUTTON_PADDING;
+            spacing.icon_width = LARGE_ICON_WIDTH;
+            spacing.icon_width_inner = LARGE_ICON_WIDTH / 2.0;
+            spacing.slider_rail_height = LARGE_ICON_WIDTH / 2.0;
+        } else {
+            spacing.interact_size = defaults.interact_size;
+            spacing.button_padding = defaults.button_padding;
+            spacing.icon_width = defaults.icon_width;
+            spacing.icon_width_inner = defaults.icon_width_inner;
+            spacing.slider_rail_height = defaults.slider_rail_height;
+        }
+    });
+}
+
+/// Tab picked with Ctrl+1 … Ctrl+4 this frame (matches the tab bar order)
+pub fn tab_shortcut(ctx: &egui::Context) -> Option<ManagerTab> {
+    [
+        (egui::Key::Num1, ManagerTab::GlobalSettings),
+        (egui::Key::Num2, ManagerTab::ProfileSettings),
+        (egui::Key::Num3, ManagerTab::Characters),
+        (egui::Key::Num4, ManagerTab::Session),
+    ]
+        .into_iter()
+        .find(|(key, _)| ctx.input_mut(|input| input.consume_key(egui::Modifiers::COMMAND, *key)))
+        .map(|(_, tab)| tab)
+}
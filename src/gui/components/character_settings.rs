@@ -0,0 +1,516 @@
+use std::collections::{BTreeSet, HashMap};
+
+use eframe::egui;
+use crate::config::profile::Profile;
+use crate::constants::gui::*;
+use crate::hotkey_conflicts::{HotkeyAction, HotkeyConflict};
+use crate::types::CharacterSettings;
+
+use super::accessibility::labelled;
+use super::hotkey_settings::{conflict_hint, hotkey_field};
+use super::visual_settings::{format_hex_color, parse_hex_color};
+
+/// Order of the character list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CharacterSort {
+    #[default]
+    Name,
+    LastSeen,
+    Online,
+}
+
+impl CharacterSort {
+    const ALL: [Self; 3] = [Self::Name, Self::LastSeen, Self::Online];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Name => "Name",
+            Self::LastSeen => "Last seen",
+            Self::Online => "Online first",
+        }
+    }
+}
+
+/// State for the per-character settings editor
+#[derive(Default)]
+pub struct CharacterSettingsState {
+    selected_character: String,
+    /// Characters with a running client, as reported by the preview daemon
+    live_characters: BTreeSet<String>,
+    /// Case-insensitive substring filter for the character list (matches name or alias)
+    filter: String,
+    sort: CharacterSort,
+    /// Character whose "terminate client" button was pressed, waiting for confirmation
+    confirm_terminate: Option<String>,
+    /// Confirmed terminate request (character, relaunch) for the manager to send
+    terminate_request: Option<(String, bool)>,
+    /// Character whose preview is in redaction edit mode
+    redact_editing: Option<String>,
+    /// Edit mode change (Some(None) = leave edit mode) for the manager to send
+    redact_edit_request: Option<Option<String>>,
+}
+
+impl CharacterSettingsState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the live list (daemon connected, so redaction edit mode starts off)
+    pub fn set_live(&mut self, characters: impl IntoIterator<Item = String>) {
+        self.live_characters = characters.into_iter().collect();
+        self.redact_editing = None;
+    }
+
+    /// Character client appeared (login or new window)
+    pub fn character_online(&mut self, character: &str) {
+        self.live_characters.insert(character.to_string());
+    }
+
+    /// Characters with a running client, by name
+    pub fn live_characters(&self) -> impl Iterator<Item = &str> {
+        self.live_characters.iter().map(String::as_str)
+    }
+
+    /// Character client went away (logout or window closed)
+    pub fn character_offline(&mut self, character: &str) {
+        self.live_characters.remove(character);
+        if self.confirm_terminate.as_deref() == Some(character) {
+            self.confirm_terminate = None;
+        }
+        if self.redact_editing.as_deref() == Some(character) {
+            self.set_redact_editing(None);
+        }
+    }
+
+    /// Redaction edit mode change waiting to be sent to the daemon
+    pub fn take_redact_edit_request(&mut self) -> Option<Option<String>> {
+        self.redact_edit_request.take()
+    }
+
+    fn set_redact_editing(&mut self, character: Option<String>) {
+        if self.redact_editing != character {
+            self.redact_editing = character.clone();
+            self.redact_edit_request = Some(character);
+        }
+    }
+
+    /// Confirmed "terminate client" action (character, relaunch), if one is waiting
+    pub fn take_terminate_request(&mut self) -> Option<(String, bool)> {
+        self.terminate_request.take()
+    }
+}
+
+/// Render the character list and the editor for the selected character
+/// Returns the name of the character whose settings changed, so they can be pushed to the daemon
+pub fn ui(ui: &mut egui::Ui, profile: &mut Profile, state: &mut CharacterSettingsState, hotkey_conflicts: &[HotkeyConflict]) -> Option<String> {
+    let mut changed = false;
+
+    ui.columns(2, |columns| {
+        character_list(&mut columns[0], profile, state);
+
+        let selected = state.selected_character.clone();
+        // Edit mode follows the selection, it never stays on with another character shown
+        if state.redact_editing.as_ref().is_some_and(|editing| *editing != selected) {
+            state.set_redact_editing(None);
+        }
+        match profile.character_positions.get_mut(&selected) {
+            Some(settings) => {
+                let online = state.live_characters.contains(&selected);
+                changed = character_editor(&mut columns[1], &selected, online, &profile.text_color, &profile.border_color, settings, hotkey_conflicts);
+                if online {
+                    client_actions(&mut columns[1], &selected, settings, state);
+                }
+            }
+            None => {
+                columns[1].weak("Select a character to edit its settings");
+            }
+        }
+    });
+
+    changed.then(|| state.selected_character.clone())
+}
+
+/// Filtered and sorted character names for the list
+fn list_characters(
+    characters: &HashMap<String, CharacterSettings>,
+    live: &BTreeSet<String>,
+    filter: &str,
+    sort: CharacterSort,
+) -> Vec<String> {
+    let filter = filter.trim().to_lowercase();
+    let mut names: Vec<&String> = characters.iter()
+        .filter(|(name, settings)| filter.is_empty()
+            || name.to_lowercase().contains(&filter)
+            || settings.alias.as_ref().is_some_and(|alias| alias.to_lowercase().contains(&filter)))
+        .map(|(name, _)| name)
+        .collect();
+
+    names.sort_by_key(|name| name.to_lowercase());
+    match sort {
+        CharacterSort::Name => {}
+        // Online characters count as seen now; never-seen ones go last
+        CharacterSort::LastSeen => names.sort_by_key(|name| std::cmp::Reverse(
+            (live.contains(*name), characters[*name].last_seen)
+        )),
+        CharacterSort::Online => names.sort_by_key(|name| !live.contains(*name)),
+    }
+    names.into_iter().cloned().collect()
+}
+
+/// Filter box, sort selector and the character list (● = client running)
+fn character_list(ui: &mut egui::Ui, profile: &Profile, state: &mut CharacterSettingsState) {
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Characters").strong());
+        ui.add_space(ITEM_SPACING);
+
+        ui.horizontal(|ui| {
+            labelled(ui, "Filter:", egui::TextEdit::singleline(&mut state.filter)
+                .hint_text("name or alias")
+                .desired_width(120.0));
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Sort:");
+            egui::ComboBox::from_id_salt("character_sort")
+                .selected_text(state.sort.label())
+                .show_ui(ui, |ui| {
+                    for sort in CharacterSort::ALL {
+                        ui.selectable_value(&mut state.sort, sort, sort.label());
+                    }
+                });
+        });
+
+        ui.add_space(ITEM_SPACING / 2.0);
+
+        let names = list_characters(&profile.character_positions, &state.live_characters, &state.filter, state.sort);
+        if names.is_empty() {
+            ui.weak(if profile.character_positions.is_empty() { "No characters yet" } else { "No matching characters" });
+        }
+        for name in names {
+            let label = if state.live_characters.contains(&name) {
+                format!("● {}", name)
+            } else {
+                name.clone()
+            };
+            if ui.selectable_label(state.selected_character == name, label).clicked() {
+                state.selected_character = name;
+            }
+        }
+    });
+}
+
+/// Editor for one character; returns true if anything changed
+fn character_editor(
+    ui: &mut egui::Ui,
+    name: &str,
+    online: bool,
+    profile_text_color: &str,
+    profile_border_color: &str,
+    settings: &mut CharacterSettings,
+    hotkey_conflicts: &[HotkeyConflict],
+) -> bool {
+    let mut changed = false;
+
+    ui.group(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(name).strong());
+            if online {
+                ui.colored_label(STATUS_RUNNING, "online");
+            } else {
+                ui.weak("offline");
+            }
+        });
+        ui.add_space(ITEM_SPACING);
+
+        // Thumbnail size
+        ui.horizontal(|ui| {
+            if labelled(ui, "Width:", egui::DragValue::new(&mut settings.dimensions.width).range(0..=1920).suffix(" px")).changed() {
+                changed = true;
+            }
+            if labelled(ui, "Height:", egui::DragValue::new(&mut settings.dimensions.height).range(0..=1080).suffix(" px")).changed() {
+                changed = true;
+            }
+        });
+
+        ui.label(egui::RichText::new(
+            "0 uses the default thumbnail size")
+            .small()
+            .weak());
+
+        ui.add_space(ITEM_SPACING);
+
+        // Label
+        ui.horizontal(|ui| {
+            ui.label("Alias:");
+            let mut alias = settings.alias.clone().unwrap_or_default();
+            let text_edit = egui::TextEdit::singleline(&mut alias)
+                .hint_text(name)
+                .desired_width(150.0);
+            if ui.add(text_edit).changed() {
+                settings.alias = (!alias.is_empty()).then_some(alias);
+                changed = true;
+            }
+        });
+
+        ui.label(egui::RichText::new(
+            "Shown on the thumbnail label instead of the character name")
+            .small()
+            .weak());
+
+        ui.add_space(ITEM_SPACING / 2.0);
+
+        let mut override_color = settings.label_color.is_some();
+        if ui.checkbox(&mut override_color, "Custom label color").changed() {
+            settings.label_color = override_color.then(|| profile_text_color.to_string());
+            changed = true;
+        }
+
+        if let Some(label_color) = &mut settings.label_color {
+            changed |= hex_color_row(ui, "Label Color:", label_color);
+        }
+
+        let mut override_opacity = settings.label_opacity.is_some();
+        if ui.checkbox(&mut override_opacity, "Custom label opacity").changed() {
+            settings.label_opacity = override_opacity.then_some(100);
+            changed = true;
+        }
+
+        if let Some(label_opacity) = &mut settings.label_opacity {
+            ui.horizontal(|ui| {
+                if labelled(ui, "Label Opacity:", egui::Slider::new(label_opacity, 0..=100).suffix("%")).changed() {
+                    changed = true;
+                }
+            });
+        }
+
+        ui.add_space(ITEM_SPACING / 2.0);
+
+        let mut override_border = settings.border_color.is_some();
+        if ui.checkbox(&mut override_border, "Custom border color").changed() {
+            settings.border_color = override_border.then(|| profile_border_color.to_string());
+            changed = true;
+        }
+
+        if let Some(border_color) = &mut settings.border_color {
+            changed |= hex_color_row(ui, "Border Color:", border_color);
+        }
+
+        ui.label(egui::RichText::new(
+            "Makes important characters (FC, booster) stand out; unset values follow the profile")
+            .small()
+            .weak());
+
+        ui.add_space(ITEM_SPACING);
+
+        // Hotkey and cycling
+        ui.horizontal(|ui| {
+            ui.label("Hotkey:");
+            if hotkey_field(ui, "character_hotkey", "e.g. F1", &mut settings.hotkey) {
+                changed = true;
+            }
+        });
+        conflict_hint(ui, hotkey_conflicts, &HotkeyAction::Focus(name.to_string()));
+
+        ui.label(egui::RichText::new(
+            "Focuses this character's client directly (press Bind, or type a key name: F1, KP_1, XF86AudioPlay, Control_R)")
+            .small()
+            .weak());
+
+        ui.add_space(ITEM_SPACING / 2.0);
+
+        if ui.checkbox(&mut settings.exclude_from_cycle,
+            "Skip in Tab/Shift+Tab cycling").changed() {
+            changed = true;
+        }
+
+        if ui.checkbox(&mut settings.preview_hidden,
+            "Hide this character's preview").changed() {
+            changed = true;
+        }
+
+        ui.label(egui::RichText::new(
+            "A hidden character still takes part in Tab cycling unless skipped above")
+            .small()
+            .weak());
+
+        ui.add_space(ITEM_SPACING / 2.0);
+
+        if ui.checkbox(&mut settings.panel_detached,
+            "Keep out of the panel (panel mode)").changed() {
+            changed = true;
+        }
+
+        ui.add_space(ITEM_SPACING);
+
+        // Client window options
+        if ui.checkbox(&mut settings.borderless_fullscreen,
+            "Borderless fullscreen while focused").changed() {
+            changed = true;
+        }
+
+        ui.label(egui::RichText::new(
+            "Strips decorations and fills the monitor on focus, restores the window when focus leaves")
+            .small()
+            .weak());
+
+        ui.add_space(ITEM_SPACING / 2.0);
+
+        ui.horizontal(|ui| {
+            if labelled(ui, "Stacking order:", egui::DragValue::new(&mut settings.z_order).range(-100..=100)).changed() {
+                changed = true;
+            }
+        });
+
+        ui.label(egui::RichText::new(
+            "Higher values are drawn above overlapping thumbnails")
+            .small()
+            .weak());
+
+        ui.add_space(ITEM_SPACING / 2.0);
+
+        ui.horizontal(|ui| {
+            ui.label("Launch command:");
+            let mut command = settings.launch_command.clone().unwrap_or_default();
+            if ui.add(egui::TextEdit::singleline(&mut command)
+                .hint_text("e.g. steam -applaunch 8500")
+                .desired_width(200.0)).changed() {
+                settings.launch_command = Some(command).filter(|command| !command.trim().is_empty());
+                changed = true;
+            }
+        });
+
+        ui.label(egui::RichText::new(
+            "Run through sh -c to start this character's client again after terminating it")
+            .small()
+            .weak());
+
+        ui.add_space(ITEM_SPACING);
+
+        // Redacted areas (drawn on the preview, see client_actions)
+        ui.horizontal(|ui| {
+            ui.label(format!("Redacted areas: {}", settings.redactions.len()));
+            if ui.add_enabled(!settings.redactions.is_empty(), egui::Button::new("Clear")).clicked() {
+                settings.redactions.clear();
+                changed = true;
+            }
+        });
+
+        ui.label(egui::RichText::new(
+            "Blacked out in the preview only, e.g. the chat panel")
+            .small()
+            .weak());
+    });
+
+    changed
+}
+
+/// Redaction edit mode toggle and the "Terminate client" button with its confirmation step
+/// (online characters only)
+fn client_actions(ui: &mut egui::Ui, name: &str, settings: &CharacterSettings, state: &mut CharacterSettingsState) {
+    ui.add_space(ITEM_SPACING);
+    ui.group(|ui| {
+        let editing = state.redact_editing.as_deref() == Some(name);
+        if ui.selectable_label(editing, "Draw redactions on preview").clicked() {
+            state.set_redact_editing((!editing).then(|| name.to_string()));
+        }
+        ui.label(egui::RichText::new(
+            "While on, left-drag on the preview to black out an area and right-click an area to remove it")
+            .small()
+            .weak());
+    });
+    ui.add_space(ITEM_SPACING);
+    ui.group(|ui| {
+        if state.confirm_terminate.as_deref() != Some(name) {
+            if ui.button("Terminate client...").clicked() {
+                state.confirm_terminate = Some(name.to_string());
+            }
+            ui.label(egui::RichText::new(
+                "For a frozen client: SIGTERM, then SIGKILL if it doesn't exit")
+                .small()
+                .weak());
+            return;
+        }
+
+        ui.colored_label(STATUS_STOPPED, format!("Terminate {}'s client? Anything not saved in game is lost.", name));
+        ui.horizontal(|ui| {
+            if ui.button("Terminate").clicked() {
+                state.terminate_request = Some((name.to_string(), false));
+                state.confirm_terminate = None;
+            }
+            if ui.add_enabled(settings.launch_command.is_some(), egui::Button::new("Terminate and relaunch"))
+                .on_disabled_hover_text("Set a launch command first")
+                .clicked()
+            {
+                state.terminate_request = Some((name.to_string(), true));
+                state.confirm_terminate = None;
+            }
+            if ui.button("Cancel").clicked() {
+                state.confirm_terminate = None;
+            }
+        });
+    });
+}
+
+/// Hex text field with a color picker button; returns true if the color changed
+fn hex_color_row(ui: &mut egui::Ui, label: &str, hex: &mut String) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label(label);
+        let text_edit = egui::TextEdit::singleline(hex)
+            .desired_width(100.0);
+        if ui.add(text_edit).changed() {
+            changed = true;
+        }
+
+        if let Ok(mut color) = parse_hex_color(hex)
+            && ui.color_edit_button_srgba(&mut color).changed() {
+            *hex = format_hex_color(color);
+            changed = true;
+        }
+    });
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn characters() -> HashMap<String, CharacterSettings> {
+        let mut booster = CharacterSettings::new(0, 0, 240, 135);
+        booster.alias = Some("Links".to_string());
+        booster.last_seen = Some(100);
+        let mut alpha = CharacterSettings::new(0, 0, 240, 135);
+        alpha.last_seen = Some(200);
+        HashMap::from([
+            ("alpha".to_string(), alpha),
+            ("Booster".to_string(), booster),
+            ("Carrier".to_string(), CharacterSettings::new(0, 0, 240, 135)),
+        ])
+    }
+
+    #[test]
+    fn test_list_sorts_by_name_case_insensitive() {
+        let names = list_characters(&characters(), &BTreeSet::new(), "", CharacterSort::Name);
+        assert_eq!(names, vec!["alpha", "Booster", "Carrier"]);
+    }
+
+    #[test]
+    fn test_list_filters_name_and_alias() {
+        let live = BTreeSet::new();
+        assert_eq!(list_characters(&characters(), &live, "CARR", CharacterSort::Name), vec!["Carrier"]);
+        assert_eq!(list_characters(&characters(), &live, "links", CharacterSort::Name), vec!["Booster"]);
+    }
+
+    #[test]
+    fn test_list_last_seen_and_online() {
+        let live = BTreeSet::from(["Carrier".to_string()]);
+        assert_eq!(
+            list_characters(&characters(), &live, "", CharacterSort::LastSeen),
+            vec!["Carrier", "alpha", "Booster"],
+        );
+        assert_eq!(
+            list_characters(&characters(), &live, "", CharacterSort::Online),
+            vec!["Carrier", "alpha", "Booster"],
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Command palette (Ctrl+P): run manager actions by typing part of their name
+
+use eframe::egui;
+use crate::config::profile::ManagerTab;
+use crate::constants::gui::*;
+
+/// What a palette entry does (carried out by the manager)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteAction {
+    FocusCharacter(String),
+    TogglePrivacy,
+    SwitchProfile(usize),
+    OpenTab(ManagerTab),
+    StartDaemon,
+    StopDaemon,
+    RestartDaemon,
+    SaveAndApply,
+    Quit,
+}
+
+#[derive(Debug, Clone)]
+pub struct PaletteCommand {
+    pub label: String,
+    pub action: PaletteAction,
+}
+
+impl PaletteCommand {
+    pub fn new(label: impl Into<String>, action: PaletteAction) -> Self {
+        Self { label: label.into(), action }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CommandPaletteState {
+    open: bool,
+    query: String,
+    /// Highlighted entry among the current matches
+    selected: usize,
+}
+
+impl CommandPaletteState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open on Ctrl+P (closes it again if already open)
+    pub fn handle_shortcut(&mut self, ctx: &egui::Context) {
+        let shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::P);
+        if ctx.input_mut(|input| input.consume_shortcut(&shortcut)) {
+            self.open = !self.open;
+            self.query.clear();
+            self.selected = 0;
+        }
+    }
+}
+
+/// How well `query` fuzzy-matches `label`, None if it doesn't
+/// Every query character must appear in order (case-insensitive); consecutive
+/// characters and matches at word starts score higher, skipped characters lower
+pub fn fuzzy_score(query: &str, label: &str) -> Option<i32> {
+    let label: Vec<char> = label.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.chars().flat_map(char::to_lowercase).filter(|c| !c.is_whitespace()) {
+        let found = position + label[position..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !label[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - position) as i32 / 4;
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// Indices of the commands matching `query`, best first (ties keep their order)
+pub fn matches(query: &str, commands: &[PaletteCommand]) -> Vec<usize> {
+    let mut scored: Vec<(usize, i32)> = commands.iter()
+        .enumerate()
+        .filter_map(|(index, command)| fuzzy_score(query, &command.label).map(|score| (index, score)))
+        .collect();
+    scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(index, _)| index).collect()
+}
+
+/// Renders the palette while open; returns the action picked with Enter or a click
+pub fn ui(ctx: &egui::Context, state: &mut CommandPaletteState, commands: &[PaletteCommand]) -> Option<PaletteAction> {
+    if !state.open {
+        return None;
+    }
+
+    let found = matches(&state.query, commands);
+    let shown = found.len().min(PALETTE_MAX_RESULTS);
+    let (up, down, enter) = ctx.input_mut(|input| (
+        input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+        input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+        input.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+    ));
+    if up {
+        state.selected = state.selected.saturating_sub(1);
+    }
+    if down {
+        state.selected += 1;
+    }
+    state.selected = state.selected.min(shown.saturating_sub(1));
+
+    let mut picked = enter.then(|| found.get(state.selected)).flatten().copied();
+    let response = egui::Modal::new(egui::Id::new("command_palette")).show(ctx, |ui| {
+        ui.set_width(PALETTE_WIDTH);
+        let input = ui.add(egui::TextEdit::singleline(&mut state.query)
+            .hint_text("Type a command or character name")
+            .desired_width(f32::INFINITY));
+        input.request_focus();
+        if input.changed() {
+            state.selected = 0;
+        }
+        ui.add_space(ITEM_SPACING);
+
+        if found.is_empty() {
+            ui.weak("No matching command");
+        }
+        for (row, &index) in found.iter().take(shown).enumerate() {
+            let label = ui.selectable_label(row == state.selected, &commands[index].label);
+            if row == state.selected {
+                label.scroll_to_me(None);
+            }
+            if label.clicked() {
+                picked = Some(index);
+            }
+        }
+        if found.len() > shown {
+            ui.weak(format!("{} more, keep typing to narrow down", found.len() - shown));
+        }
+    });
+
+    if picked.is_some() || response.should_close() {
+        state.open = false;
+    }
+    picked.map(|index| commands[index].action.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_subsequence() {
+        assert!(fuzzy_score("rsd", "Restart daemon").is_some());
+        assert!(fuzzy_score("RESTART", "Restart daemon").is_some());
+        assert!(fuzzy_score("dr", "Restart daemon").is_none());
+        assert_eq!(fuzzy_score("", "Anything"), Some(0));
+    }
+
+    #[test]
+    fn test_matches_prefers_consecutive_and_word_starts() {
+        let commands = [
+            PaletteCommand::new("Switch to profile: Mining", PaletteAction::SwitchProfile(0)),
+            PaletteCommand::new("Focus: Main", PaletteAction::FocusCharacter("Main".to_string())),
+            PaletteCommand::new("Quit", PaletteAction::Quit),
+        ];
+        assert_eq!(matches("main", &commands), vec![1]);
+        assert_eq!(matches("min", &commands), vec![0, 1]);
+        assert_eq!(matches("q", &commands), vec![2]);
+        assert_eq!(matches("", &commands), vec![0, 1, 2]);
+    }
+}
@@ -1,11 +1,17 @@
 //! Global settings component (applies to all profiles)
 
 use eframe::egui;
-use crate::config::profile::GlobalSettings;
+use crate::config::profile::{ClientSwitchAction, CycleStripModifier, GlobalSettings, HotkeyBackend, LowBandwidth, OpacityMode, OverlapAvoidance, PanelLayout, RenderBackend, WmPreset};
 use crate::constants::gui::*;
+use crate::constants::{input, mouse};
+use crate::hotkeys;
+use crate::hotkey_conflicts::{HotkeyAction, HotkeyConflict};
+use crate::types::TitlePattern;
+use super::accessibility::labelled;
+use super::hotkey_settings::{conflict_hint, hotkey_field};
 
 /// Renders global settings UI and returns true if changes were made
-pub fn ui(ui: &mut egui::Ui, global: &mut GlobalSettings) -> bool {
+pub fn ui(ui: &mut egui::Ui, global: &mut GlobalSettings, hotkey_conflicts: &[HotkeyConflict]) -> bool {
     let mut changed = false;
     
     // Behavior Settings (Global)
@@ -24,6 +30,25 @@ pub fn ui(ui: &mut egui::Ui, global: &mut GlobalSettings) -> bool {
             .small()
             .weak());
         
+        ui.add_enabled_ui(global.minimize_clients_on_switch, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Other clients:");
+                egui::ComboBox::from_id_salt("client_switch_action")
+                    .selected_text(match global.client_switch_action {
+                        ClientSwitchAction::Minimize => "Minimize",
+                        ClientSwitchAction::Lower => "Lower (keep previews live)",
+                    })
+                    .show_ui(ui, |ui| {
+                        if ui.selectable_value(&mut global.client_switch_action, ClientSwitchAction::Minimize, "Minimize").changed() {
+                            changed = true;
+                        }
+                        if ui.selectable_value(&mut global.client_switch_action, ClientSwitchAction::Lower, "Lower (keep previews live)").changed() {
+                            changed = true;
+                        }
+                    });
+            });
+        });
+        
         ui.add_space(ITEM_SPACING);
         
         // Hide when no focus
@@ -39,6 +64,19 @@ pub fn ui(ui: &mut egui::Ui, global: &mut GlobalSettings) -> bool {
         
         ui.add_space(ITEM_SPACING);
         
+        // Prevent minimize
+        if ui.checkbox(&mut global.prevent_minimize, 
+            "Prevent EVE clients from minimizing").changed() {
+            changed = true;
+        }
+        
+        ui.label(egui::RichText::new(
+            "Minimized clients stop rendering, so they get restored and sent to the back instead")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
         // Preserve thumbnail position on character swap
         if ui.checkbox(&mut global.preserve_thumbnail_position_on_swap, 
             "Keep thumbnail position when switching characters").changed() {
@@ -52,10 +90,22 @@ pub fn ui(ui: &mut egui::Ui, global: &mut GlobalSettings) -> bool {
         
         ui.add_space(ITEM_SPACING);
         
+        // Position sets per monitor setup
+        if ui.checkbox(&mut global.monitor_position_sets,
+            "Remember positions per monitor setup").changed() {
+            changed = true;
+        }
+        
+        ui.label(egui::RichText::new(
+            "Thumbnails move to where they were the last time these monitors were connected")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
         // Snap threshold
         ui.horizontal(|ui| {
-            ui.label("Thumbnail Snap Distance:");
-            if ui.add(egui::Slider::new(&mut global.snap_threshold, 0..=50)
+            if labelled(ui, "Thumbnail Snap Distance:", egui::Slider::new(&mut global.snap_threshold, 0..=50)
                 .suffix(" px")).changed() {
                 changed = true;
             }
@@ -66,6 +116,449 @@ pub fn ui(ui: &mut egui::Ui, global: &mut GlobalSettings) -> bool {
             .small()
             .weak());
         
+        if ui.checkbox(&mut global.show_drag_guides, 
+            "Show snap guides while dragging").changed() {
+            changed = true;
+        }
+        
+        if ui.checkbox(&mut global.drag_ghost, 
+            "Drag an outline instead of the live thumbnail").changed() {
+            changed = true;
+        }
+        
+        ui.horizontal(|ui| {
+            if labelled(ui, "Drag Update Rate:", egui::Slider::new(&mut global.drag_max_fps, 0..=240)
+                .suffix(" fps")).changed() {
+                changed = true;
+            }
+        });
+        
+        ui.label(egui::RichText::new(
+            "Maximum thumbnail moves per second while dragging (0 = every mouse event)")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Screen edge margins
+        ui.horizontal(|ui| {
+            ui.label("Screen Margins:");
+            for (label, value) in [
+                ("Top", &mut global.screen_margins.top),
+                ("Bottom", &mut global.screen_margins.bottom),
+                ("Left", &mut global.screen_margins.left),
+                ("Right", &mut global.screen_margins.right),
+            ] {
+                ui.label(label);
+                if ui.add(egui::DragValue::new(value).range(0..=500).suffix(" px")).changed() {
+                    changed = true;
+                }
+            }
+        });
+        
+        ui.label(egui::RichText::new(
+            "Space kept clear along each screen edge (e.g. for a panel); thumbnails snap to these lines")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Overlap avoidance on drop
+        ui.horizontal(|ui| {
+            ui.label("When dropped on another thumbnail:");
+            egui::ComboBox::from_id_salt("overlap_avoidance")
+                .selected_text(match global.overlap_avoidance {
+                    OverlapAvoidance::Off => "Allow overlap",
+                    OverlapAvoidance::Nudge => "Move to free space",
+                    OverlapAvoidance::Push => "Push others aside",
+                })
+                .show_ui(ui, |ui| {
+                    if ui.selectable_value(&mut global.overlap_avoidance, OverlapAvoidance::Off, "Allow overlap").changed() {
+                        changed = true;
+                    }
+                    if ui.selectable_value(&mut global.overlap_avoidance, OverlapAvoidance::Nudge, "Move to free space").changed() {
+                        changed = true;
+                    }
+                    if ui.selectable_value(&mut global.overlap_avoidance, OverlapAvoidance::Push, "Push others aside").changed() {
+                        changed = true;
+                    }
+                });
+        });
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Thumbnail stacking
+        if ui.checkbox(&mut global.raise_thumbnail_on_hover, 
+            "Raise thumbnail under the mouse").changed() {
+            changed = true;
+        }
+        
+        if ui.checkbox(&mut global.raise_focused_thumbnail, 
+            "Raise thumbnail of the focused client").changed() {
+            changed = true;
+        }
+        
+        ui.label(egui::RichText::new(
+            "Overlapping thumbnails otherwise follow each character's stacking order")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        if ui.checkbox(&mut global.hover_tooltip, 
+            "Show client details when hovering a thumbnail").changed() {
+            changed = true;
+        }
+        
+        ui.label(egui::RichText::new(
+            "Full character name, client window geometry, frame rate and last update")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        if ui.checkbox(&mut global.start_minimized_to_tray, 
+            "Start the manager minimized to the tray").changed() {
+            changed = true;
+        }
+        
+        ui.label(egui::RichText::new(
+            "Previews still start; open this window again from the tray icon (Linux only)")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        ui.horizontal(|ui| {
+            if labelled(ui, "Preview Update Rate:", egui::Slider::new(&mut global.thumbnail_max_fps, 0..=120)
+                .suffix(" fps")).changed() {
+                changed = true;
+            }
+        });
+        
+        ui.label(egui::RichText::new(
+            "Maximum redraws per second of each preview (0 = every change); 24-30 keeps CPU use down with many clients")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Low bandwidth mode for remote displays
+        ui.horizontal(|ui| {
+            ui.label("Low bandwidth mode:");
+            egui::ComboBox::from_id_salt("low_bandwidth")
+                .selected_text(match global.low_bandwidth {
+                    LowBandwidth::Auto => "Auto (remote displays)",
+                    LowBandwidth::On => "Always",
+                    LowBandwidth::Off => "Never",
+                })
+                .show_ui(ui, |ui| {
+                    if ui.selectable_value(&mut global.low_bandwidth, LowBandwidth::Auto, "Auto (remote displays)").changed() {
+                        changed = true;
+                    }
+                    if ui.selectable_value(&mut global.low_bandwidth, LowBandwidth::On, "Always").changed() {
+                        changed = true;
+                    }
+                    if ui.selectable_value(&mut global.low_bandwidth, LowBandwidth::Off, "Never").changed() {
+                        changed = true;
+                    }
+                });
+        });
+        
+        ui.label(egui::RichText::new(
+            "Smaller previews updated a few times per second, for X forwarded over ssh")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Panel mode
+        if ui.checkbox(&mut global.panel.enabled, 
+            "Show previews in a single panel window").changed() {
+            changed = true;
+        }
+        
+        ui.add_enabled_ui(global.panel.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Panel Layout:");
+                egui::ComboBox::from_id_salt("panel_layout")
+                    .selected_text(match global.panel.layout {
+                        PanelLayout::Grid => "Grid",
+                        PanelLayout::Row => "Row along the bottom",
+                        PanelLayout::Column => "Column along the right",
+                    })
+                    .show_ui(ui, |ui| {
+                        if ui.selectable_value(&mut global.panel.layout, PanelLayout::Grid, "Grid").changed() {
+                            changed = true;
+                        }
+                        if ui.selectable_value(&mut global.panel.layout, PanelLayout::Row, "Row along the bottom").changed() {
+                            changed = true;
+                        }
+                        if ui.selectable_value(&mut global.panel.layout, PanelLayout::Column, "Column along the right").changed() {
+                            changed = true;
+                        }
+                    });
+            });
+            
+            if global.panel.layout == PanelLayout::Grid {
+                ui.horizontal(|ui| {
+                    if labelled(ui, "Panel Columns:", egui::Slider::new(&mut global.panel.columns, 0..=12)).changed() {
+                        changed = true;
+                    }
+                });
+            }
+            
+            let layout = match global.panel.layout {
+                PanelLayout::Grid => &mut global.panel.grid,
+                PanelLayout::Row => &mut global.panel.row,
+                PanelLayout::Column => &mut global.panel.column,
+            };
+            ui.horizontal(|ui| {
+                if labelled(ui, "Spacing:", egui::Slider::new(&mut layout.spacing, 0..=50).suffix(" px")).changed() {
+                    changed = true;
+                }
+            });
+            ui.horizontal(|ui| {
+                if labelled(ui, "Max Preview Size:", egui::DragValue::new(&mut layout.max_width).range(0..=3840)).changed() {
+                    changed = true;
+                }
+                ui.label("x");
+                if ui.add(egui::DragValue::new(&mut layout.max_height).range(0..=2160)).changed() {
+                    changed = true;
+                }
+            });
+        });
+        
+        ui.label(egui::RichText::new(
+            "One regular window your WM can tile or move, previews laid out in cycle order (0 columns = square grid, 0 size = no limit). Rows and columns stick to the screen edge and shrink previews to fit")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Thumbnail rendering backend
+        ui.horizontal(|ui| {
+            ui.label("Renderer:");
+            egui::ComboBox::from_id_salt("render_backend")
+                .selected_text(match global.render_backend {
+                    RenderBackend::Xrender => "XRender",
+                    RenderBackend::Gl => "OpenGL",
+                })
+                .show_ui(ui, |ui| {
+                    if ui.selectable_value(&mut global.render_backend, RenderBackend::Xrender, "XRender").changed() {
+                        changed = true;
+                    }
+                    if ui.selectable_value(&mut global.render_backend, RenderBackend::Gl, "OpenGL").changed() {
+                        changed = true;
+                    }
+                });
+        });
+        
+        ui.label(egui::RichText::new(
+            "OpenGL scales previews on the GPU (needs a build with the gl feature, falls back to XRender). Applies to new previews")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        ui.horizontal(|ui| {
+            ui.label("Opacity:");
+            egui::ComboBox::from_id_salt("opacity_mode")
+                .selected_text(match global.opacity_mode {
+                    OpacityMode::Auto => "Auto",
+                    OpacityMode::Compositor => "Compositor",
+                    OpacityMode::Render => "Blend over wallpaper",
+                })
+                .show_ui(ui, |ui| {
+                    if ui.selectable_value(&mut global.opacity_mode, OpacityMode::Auto, "Auto").changed() {
+                        changed = true;
+                    }
+                    if ui.selectable_value(&mut global.opacity_mode, OpacityMode::Compositor, "Compositor").changed() {
+                        changed = true;
+                    }
+                    if ui.selectable_value(&mut global.opacity_mode, OpacityMode::Render, "Blend over wallpaper").changed() {
+                        changed = true;
+                    }
+                });
+        });
+        
+        ui.label(egui::RichText::new(
+            "Without a compositor, opacity is drawn by blending the desktop wallpaper into the preview. Applies to new previews")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        ui.horizontal(|ui| {
+            ui.label("Window manager:");
+            let label = |preset: WmPreset| match preset {
+                WmPreset::Auto => "Detect",
+                WmPreset::Generic => "Other",
+                WmPreset::I3 => "i3",
+                WmPreset::Awesome => "awesome",
+                WmPreset::Kwin => "KWin",
+                WmPreset::Mutter => "Mutter / GNOME",
+            };
+            egui::ComboBox::from_id_salt("wm_preset")
+                .selected_text(label(global.wm_preset))
+                .show_ui(ui, |ui| {
+                    for preset in [WmPreset::Auto, WmPreset::Generic, WmPreset::I3, WmPreset::Awesome, WmPreset::Kwin, WmPreset::Mutter] {
+                        if ui.selectable_value(&mut global.wm_preset, preset, label(preset)).changed() {
+                            changed = true;
+                        }
+                    }
+                });
+        });
+        
+        ui.label(egui::RichText::new(
+            "Adapts thumbnail windows and client switching to your WM. Detect picks it when the previews start")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        if ui.checkbox(&mut global.redirect_fullscreen_clients, 
+            "Keep fullscreen clients redirected").changed() {
+            changed = true;
+        }
+        
+        ui.label(egui::RichText::new(
+            "For compositors that unredirect fullscreen windows (picom unredir-if-possible), which leaves their previews showing whatever covers them")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Night mode tint
+        if ui.checkbox(&mut global.night_mode.enabled, 
+            "Warm and dim previews at night").changed() {
+            changed = true;
+        }
+        
+        ui.add_enabled_ui(global.night_mode.enabled, |ui| {
+            if ui.checkbox(&mut global.night_mode.follow_gamma, 
+                "Follow redshift/gammastep instead of the hours").changed() {
+                changed = true;
+            }
+            ui.add_enabled_ui(!global.night_mode.follow_gamma, |ui| {
+                ui.horizontal(|ui| {
+                    if labelled(ui, "From:", egui::DragValue::new(&mut global.night_mode.start_hour).range(0..=23).suffix(":00")).changed() {
+                        changed = true;
+                    }
+                    ui.label("to");
+                    if ui.add(egui::DragValue::new(&mut global.night_mode.end_hour).range(0..=23).suffix(":00")).changed() {
+                        changed = true;
+                    }
+                });
+            });
+            ui.horizontal(|ui| {
+                if labelled(ui, "Warm Tint:", egui::Slider::new(&mut global.night_mode.tint_percent, 0..=100).suffix("%")).changed() {
+                    changed = true;
+                }
+            });
+            ui.horizontal(|ui| {
+                if labelled(ui, "Brightness:", egui::Slider::new(&mut global.night_mode.brightness_percent, 10..=100).suffix("%")).changed() {
+                    changed = true;
+                }
+            });
+        });
+        
+        ui.label(egui::RichText::new(
+            "Only the previews are tinted, not the clients. Uses the XRender renderer")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        if ui.checkbox(&mut global.show_process_stats, 
+            "Show client CPU/RAM usage on thumbnails").changed() {
+            changed = true;
+        }
+        
+        ui.label(egui::RichText::new(
+            "Sampled from /proc every 2 seconds; 100% CPU is one full core")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        if ui.checkbox(&mut global.usage_stats, 
+            "Keep local usage stats").changed() {
+            changed = true;
+        }
+        
+        ui.label(egui::RichText::new(
+            "Daily hotkey, focus switch and client counts for the Session tab; stored locally, never sent anywhere")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        if ui.checkbox(&mut global.large_controls, 
+            "Larger controls in this window").changed() {
+            changed = true;
+        }
+        
+        ui.label(egui::RichText::new(
+            "Bigger buttons, sliders and checkboxes, easier to hit. Ctrl+1-4 switch tabs, Tab moves between controls")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        ui.horizontal(|ui| {
+            if labelled(ui, "Stalled Client Warning:", egui::Slider::new(&mut global.stall_warning_secs, 0..=120).suffix(" s")).changed() {
+                changed = true;
+            }
+        });
+        
+        ui.label(egui::RichText::new(
+            "Grey out a preview whose client drew nothing for this long, usually a hung wine process (0 = off)")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        ui.horizontal(|ui| {
+            if labelled(ui, "Position Autosave:", egui::Slider::new(&mut global.autosave_interval_secs, 0..=300).suffix(" s")).changed() {
+                changed = true;
+            }
+        });
+        
+        ui.label(egui::RichText::new(
+            "Save moved previews at least this often while they keep changing (0 = only once they settle)")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        if ui.checkbox(&mut global.live_source_refresh, 
+            "Refresh thumbnails while moving/resizing a client").changed() {
+            changed = true;
+        }
+        
+        ui.label(egui::RichText::new(
+            "Previews are always refreshed once a move/resize ends; this also redraws them during it")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        ui.horizontal(|ui| {
+            if labelled(ui, "Title Pattern:", egui::TextEdit::singleline(&mut global.title_pattern)).changed() {
+                changed = true;
+            }
+        });
+        
+        if let Err(e) = TitlePattern::new(&global.title_pattern) {
+            ui.colored_label(STATUS_STOPPED, format!("{e:#}, the default pattern will be used"));
+        }
+        
+        ui.label(egui::RichText::new(
+            "Regex matching EVE client titles; the (?P<character>...) group is the character name")
+            .small()
+            .weak());
+        
         ui.add_space(ITEM_SPACING);
         
         // Default thumbnail dimensions with aspect ratio controls
@@ -128,8 +621,7 @@ pub fn ui(ui: &mut egui::Ui, global: &mut GlobalSettings) -> bool {
             
             // Width slider (primary control)
             ui.horizontal(|ui| {
-                ui.label("Width:");
-                if ui.add(egui::Slider::new(&mut global.default_thumbnail_width, 100..=800)
+                if labelled(ui, "Width:", egui::Slider::new(&mut global.default_thumbnail_width, 100..=800)
                     .suffix(" px")).changed() {
                     // If not custom, maintain aspect ratio
                     if selected_mode != "Custom" {
@@ -197,6 +689,202 @@ pub fn ui(ui: &mut egui::Ui, global: &mut GlobalSettings) -> bool {
             "When enabled, Tab/Shift+Tab only work when an EVE window is focused")
             .small()
             .weak());
+        conflict_hint(ui, hotkey_conflicts, &HotkeyAction::Cycle);
+        
+        ui.add_space(ITEM_SPACING);
+        
+        if ui.checkbox(&mut global.cycle_wrap, "Wrap around at the ends of the cycle order").changed() {
+            changed = true;
+        }
+        
+        ui.label(egui::RichText::new(
+            "When disabled, Tab stops on the last character and Shift+Tab on the first")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Hotkey source
+        ui.horizontal(|ui| {
+            ui.label("Hotkeys via:");
+            egui::ComboBox::from_id_salt("hotkey_backend")
+                .selected_text(match global.hotkey_backend {
+                    HotkeyBackend::Auto => "Auto",
+                    HotkeyBackend::Evdev => "Input devices (evdev)",
+                    HotkeyBackend::Portal => "Desktop portal",
+                })
+                .show_ui(ui, |ui| {
+                    if ui.selectable_value(&mut global.hotkey_backend, HotkeyBackend::Auto, "Auto").changed() {
+                        changed = true;
+                    }
+                    if ui.selectable_value(&mut global.hotkey_backend, HotkeyBackend::Evdev, "Input devices (evdev)").changed() {
+                        changed = true;
+                    }
+                    if ui.selectable_value(&mut global.hotkey_backend, HotkeyBackend::Portal, "Desktop portal").changed() {
+                        changed = true;
+                    }
+                });
+        });
+        
+        ui.label(egui::RichText::new(
+            "Auto uses the desktop's GlobalShortcuts portal on Wayland sessions (GNOME/KDE) and /dev/input elsewhere")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Privacy mode toggle key
+        ui.horizontal(|ui| {
+            ui.label("Privacy Mode Hotkey:");
+            if hotkey_field(ui, "global_privacy", "e.g. F12", &mut global.privacy_hotkey) {
+                changed = true;
+            }
+        });
+        conflict_hint(ui, hotkey_conflicts, &HotkeyAction::Privacy);
+        
+        ui.label(egui::RichText::new(
+            "Pixelates all previews for streaming or screen sharing (also in the tray menu). Works even when no EVE window is focused. Profiles can override it")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Focus last: flip between the two most recent clients
+        ui.horizontal(|ui| {
+            ui.label("Focus Last Hotkey:");
+            if hotkey_field(ui, "global_focus_last", "e.g. F11", &mut global.focus_last_hotkey) {
+                changed = true;
+            }
+        });
+        conflict_hint(ui, hotkey_conflicts, &HotkeyAction::FocusLast);
+        
+        ui.label(egui::RichText::new(
+            "Goes back to the EVE client focused before the current one, whatever the cycle order; press it again to come back")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Focus history navigation
+        ui.horizontal(|ui| {
+            ui.label("Focus Back:");
+            if hotkey_field(ui, "global_focus_back", "e.g. F9", &mut global.focus_back_hotkey) {
+                changed = true;
+            }
+            ui.label("Forward:");
+            if hotkey_field(ui, "global_focus_forward", "e.g. F10", &mut global.focus_forward_hotkey) {
+                changed = true;
+            }
+        });
+        conflict_hint(ui, hotkey_conflicts, &HotkeyAction::FocusBack);
+        conflict_hint(ui, hotkey_conflicts, &HotkeyAction::FocusForward);
+        
+        ui.label(egui::RichText::new(
+            "Walk through the last few focused clients like browser back/forward. Shift reverses either key")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Mouse-button cycling
+        ui.horizontal(|ui| {
+            ui.label("Mouse: next character");
+            if mouse_button_combo(ui, "mouse_cycle_forward", &mut global.mouse_cycle_forward) {
+                changed = true;
+            }
+            ui.label("previous");
+            if mouse_button_combo(ui, "mouse_cycle_backward", &mut global.mouse_cycle_backward) {
+                changed = true;
+            }
+        });
+        
+        ui.label(egui::RichText::new(
+            "Side mouse buttons cycling like Tab/Shift+Tab (hold Shift to go the other way). With \"Require EVE window focused\" they only act over EVE clients and keep working normally elsewhere")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Chord: leader, then a digit
+        ui.horizontal(|ui| {
+            ui.label("Chord Leader:");
+            let mut leader = global.chord_leader.clone().unwrap_or_default();
+            let text_edit = egui::TextEdit::singleline(&mut leader)
+                .hint_text("e.g. Ctrl+Space")
+                .desired_width(120.0);
+            if ui.add(text_edit).changed() {
+                global.chord_leader = (!leader.trim().is_empty()).then_some(leader);
+                changed = true;
+            }
+            
+            if let Some(leader) = &global.chord_leader {
+                match hotkeys::parse_key_combo(leader) {
+                    Some((_, input::KEY_TAB)) => { ui.colored_label(STATUS_STOPPED, "Tab is reserved for cycling"); }
+                    Some(_) => {}
+                    None => { ui.colored_label(STATUS_STOPPED, "Unknown key combo"); }
+                }
+            }
+        });
+        
+        if global.chord_leader.is_some() {
+            ui.horizontal(|ui| {
+                if labelled(ui, "Chord Timeout:", egui::Slider::new(&mut global.chord_timeout_ms, 500..=5000).suffix(" ms")).changed() {
+                    changed = true;
+                }
+            });
+        }
+        
+        ui.label(egui::RichText::new(
+            "Press the leader, then 1-9 to focus that character in cycle order (a hint lists them). The digit doesn't reach EVE")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Cycle strip: modifier+Tab previews, releasing switches
+        ui.horizontal(|ui| {
+            ui.label("Cycle Strip:");
+            let label = |modifier: Option<CycleStripModifier>| match modifier {
+                None => "Off",
+                Some(CycleStripModifier::Alt) => "Alt+Tab",
+                Some(CycleStripModifier::Ctrl) => "Ctrl+Tab",
+                Some(CycleStripModifier::Super) => "Super+Tab",
+            };
+            egui::ComboBox::from_id_salt("cycle_strip")
+                .selected_text(label(global.cycle_strip))
+                .show_ui(ui, |ui| {
+                    for modifier in [None, Some(CycleStripModifier::Alt), Some(CycleStripModifier::Ctrl), Some(CycleStripModifier::Super)] {
+                        if ui.selectable_value(&mut global.cycle_strip, modifier, label(modifier)).changed() {
+                            changed = true;
+                        }
+                    }
+                });
+        });
+        
+        ui.label(egui::RichText::new(
+            "Holding the modifier with Tab shows all characters with the next one highlighted; Tab moves on, releasing the modifier switches, Escape cancels")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        // Switch OSD
+        if ui.checkbox(&mut global.switch_osd, "Show character name on switch").changed() {
+            changed = true;
+        }
+        
+        if global.switch_osd {
+            ui.horizontal(|ui| {
+                if labelled(ui, "OSD Duration:", egui::Slider::new(&mut global.switch_osd_ms, 300..=3000).suffix(" ms")).changed() {
+                    changed = true;
+                }
+            });
+        }
+        
+        ui.label(egui::RichText::new(
+            "After a hotkey switch, the character's name shows large in the middle of the screen for a moment")
+            .small()
+            .weak());
         
         ui.add_space(ITEM_SPACING);
         ui.separator();
@@ -209,5 +897,90 @@ pub fn ui(ui: &mut egui::Ui, global: &mut GlobalSettings) -> bool {
         ui.label("• Toggle preview visibility");
     });
     
+    ui.add_space(SECTION_SPACING);
+    
+    // MQTT Settings (Global)
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("MQTT Integration").strong());
+        ui.add_space(ITEM_SPACING);
+        
+        if ui.checkbox(&mut global.mqtt.enabled, 
+            "Publish focus and character events to MQTT").changed() {
+            changed = true;
+        }
+        
+        ui.label(egui::RichText::new(
+            "Retained topics: <prefix>/active_character, <prefix>/<character>/focused, <prefix>/<character>/online")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        ui.add_enabled_ui(global.mqtt.enabled, |ui| {
+            ui.horizontal(|ui| {
+                if labelled(ui, "Broker URL:", egui::TextEdit::singleline(&mut global.mqtt.broker_url)).changed() {
+                    changed = true;
+                }
+            });
+            
+            ui.horizontal(|ui| {
+                if labelled(ui, "Topic Prefix:", egui::TextEdit::singleline(&mut global.mqtt.topic_prefix)).changed() {
+                    changed = true;
+                }
+            });
+            
+            // Empty username/password fields are stored as None (anonymous login)
+            let mut username = global.mqtt.username.clone().unwrap_or_default();
+            ui.horizontal(|ui| {
+                if labelled(ui, "Username:", egui::TextEdit::singleline(&mut username)).changed() {
+                    global.mqtt.username = (!username.is_empty()).then(|| username.clone());
+                    changed = true;
+                }
+            });
+            
+            let mut password = global.mqtt.password.clone().unwrap_or_default();
+            ui.horizontal(|ui| {
+                if labelled(ui, "Password:", egui::TextEdit::singleline(&mut password).password(true)).changed() {
+                    global.mqtt.password = (!password.is_empty()).then(|| password.clone());
+                    changed = true;
+                }
+            });
+        });
+        
+        ui.label(egui::RichText::new(
+            "Plain mqtt:// brokers only (e.g. mqtt://homeassistant.local:1883)")
+            .small()
+            .weak());
+    });
+    
+    // Options without a hand-written widget yet
+    if super::schema_form::uncovered_ui(ui, "global", global) {
+        changed = true;
+    }
+    
+    changed
+}
+
+fn mouse_button_label(button: Option<u8>) -> String {
+    match button {
+        None => "None".to_string(),
+        Some(8) => "Button 8 (back)".to_string(),
+        Some(9) => "Button 9 (forward)".to_string(),
+        Some(button) => format!("Button {}", button),
+    }
+}
+
+/// Mouse button picker for a cycle direction, returns true if changed
+fn mouse_button_combo(ui: &mut egui::Ui, id_salt: &str, value: &mut Option<u8>) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(mouse_button_label(*value))
+        .show_ui(ui, |ui| {
+            for button in std::iter::once(None).chain(mouse::HOTKEY_BUTTONS.into_iter().map(Some)) {
+                if ui.selectable_value(value, button, mouse_button_label(button)).changed() {
+                    changed = true;
+                }
+            }
+        });
     changed
 }
@@ -1,8 +1,13 @@
 //! Hotkey settings component for profile configuration
 
+use std::sync::{Arc, Mutex};
+
 use eframe::egui;
-use crate::config::profile::Profile;
+use crate::config::profile::{CycleSort, Profile};
 use crate::constants::gui::*;
+use crate::constants::input;
+use crate::hotkey_conflicts::{HotkeyAction, HotkeyConflict};
+use crate::hotkeys;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum EditorMode {
@@ -17,6 +22,8 @@ pub struct HotkeySettingsState {
     editor_mode: EditorMode,
     show_add_characters_popup: bool,
     character_selections: std::collections::HashMap<String, bool>,
+    /// Hotkeys another application has grabbed, as reported by the preview daemon
+    conflicts: Vec<HotkeyConflict>,
 }
 
 impl HotkeySettingsState {
@@ -27,9 +34,18 @@ impl HotkeySettingsState {
             editor_mode: EditorMode::DragDrop,
             show_add_characters_popup: false,
             character_selections: std::collections::HashMap::new(),
+            conflicts: Vec::new(),
         }
     }
     
+    pub fn set_conflicts(&mut self, conflicts: Vec<HotkeyConflict>) {
+        self.conflicts = conflicts;
+    }
+    
+    pub fn conflicts(&self) -> &[HotkeyConflict] {
+        &self.conflicts
+    }
+    
     /// Load cycle group from profile into text buffer
     pub fn load_from_profile(&mut self, profile: &Profile) {
         self.cycle_group_text = profile.cycle_group.join("\n");
@@ -52,14 +68,165 @@ impl Default for HotkeySettingsState {
     }
 }
 
+/// Key capture in progress for one hotkey field (kept in egui memory across frames)
+#[derive(Clone)]
+enum Capture {
+    /// Reading /dev/input, tells keypad, media and left/right modifier keys apart
+    Evdev(Arc<Mutex<hotkeys::KeyCapture>>),
+    /// No /dev/input access: the manager window's own key events
+    Window,
+}
+
+/// Key name field with a "press a key to bind" button
+/// Returns true if the value changed
+pub fn hotkey_field(ui: &mut egui::Ui, id_salt: &str, hint: &str, value: &mut Option<String>) -> bool {
+    let mut changed = false;
+    let id = ui.id().with(id_salt).with("hotkey_capture");
+    let capture = ui.data(|data| data.get_temp::<Capture>(id));
+    
+    let mut bind = |code: u16, value: &mut Option<String>| {
+        if code != input::KEY_ESC {
+            *value = Some(hotkeys::key_name(code));
+            changed = true;
+        }
+    };
+    match &capture {
+        Some(Capture::Evdev(key_capture)) => {
+            if let Some(code) = key_capture.lock().unwrap().poll() {
+                bind(code, value);
+                ui.data_mut(|data| data.remove::<Capture>(id));
+            } else {
+                ui.ctx().request_repaint_after(std::time::Duration::from_millis(50));
+            }
+        }
+        Some(Capture::Window) => {
+            let pressed = ui.input(|i| i.events.iter().find_map(|event| match event {
+                egui::Event::Key { key, pressed: true, .. } => Some(*key),
+                _ => None,
+            }));
+            if let Some(key) = pressed {
+                if let Some(code) = hotkeys::parse_key_name(key.name()) {
+                    bind(code, value);
+                }
+                ui.data_mut(|data| data.remove::<Capture>(id));
+            }
+        }
+        None => {}
+    }
+    let capturing = ui.data(|data| data.get_temp::<Capture>(id)).is_some();
+    
+    if capturing {
+        ui.label(egui::RichText::new("Press a key... (Esc cancels)").italics());
+        if ui.button("Cancel").clicked() {
+            ui.data_mut(|data| data.remove::<Capture>(id));
+        }
+    } else {
+        let mut hotkey = value.clone().unwrap_or_default();
+        let text_edit = egui::TextEdit::singleline(&mut hotkey)
+            .hint_text(hint)
+            .desired_width(100.0);
+        if ui.add(text_edit).changed() {
+            *value = (!hotkey.trim().is_empty()).then_some(hotkey);
+            changed = true;
+        }
+        if ui.button("⌨ Bind").on_hover_text("Press the key to use instead of typing its name").clicked() {
+            let capture = hotkeys::KeyCapture::start()
+                .map_or(Capture::Window, |key_capture| Capture::Evdev(Arc::new(Mutex::new(key_capture))));
+            ui.data_mut(|data| data.insert_temp(id, capture));
+        }
+    }
+    
+    if let Some(hotkey) = value.as_deref() {
+        match hotkeys::parse_key_name(hotkey) {
+            Some(input::KEY_TAB) => { ui.colored_label(STATUS_STOPPED, "Tab is reserved for cycling"); }
+            Some(_) => {}
+            None => { ui.colored_label(STATUS_STOPPED, "Unknown key name"); }
+        }
+    }
+    changed
+}
+
+fn action_label(action: &HotkeyAction) -> String {
+    match action {
+        HotkeyAction::Cycle => "Tab (cycle)".to_string(),
+        HotkeyAction::Privacy => "Privacy mode".to_string(),
+        HotkeyAction::FocusLast => "Focus last".to_string(),
+        HotkeyAction::FocusBack => "Focus back".to_string(),
+        HotkeyAction::FocusForward => "Focus forward".to_string(),
+        HotkeyAction::Focus(character) => character.clone(),
+    }
+}
+
+/// Warning next to a hotkey field whose key another application holds (nothing if it's free)
+pub fn conflict_hint(ui: &mut egui::Ui, conflicts: &[HotkeyConflict], action: &HotkeyAction) {
+    let Some(conflict) = conflicts.iter().find(|conflict| conflict.action == *action) else {
+        return;
+    };
+    let mut text = format!("{} is grabbed by another application", conflict.key);
+    if !conflict.suggestions.is_empty() {
+        text.push_str(&format!(" - try {}", conflict.suggestions.join(", ")));
+    }
+    ui.colored_label(STATUS_STARTING, text);
+}
+
 /// Renders hotkey settings UI and returns true if changes were made
 pub fn ui(ui: &mut egui::Ui, profile: &mut Profile, state: &mut HotkeySettingsState) -> bool {
     let mut changed = false;
     
+    if !state.conflicts.is_empty() {
+        ui.group(|ui| {
+            ui.label(egui::RichText::new("Hotkey Conflicts").strong());
+            ui.add_space(ITEM_SPACING);
+            for conflict in &state.conflicts {
+                ui.horizontal(|ui| {
+                    ui.colored_label(STATUS_STARTING, format!("{}: {}", action_label(&conflict.action), conflict.key));
+                    if !conflict.suggestions.is_empty() {
+                        ui.label(format!("free: {}", conflict.suggestions.join(", ")));
+                    }
+                });
+            }
+            ui.add_space(ITEM_SPACING / 2.0);
+            ui.label(egui::RichText::new(
+                "These keys are also bound by your window manager or another app, which runs its own action too (or eats the key). Rebind them there, or pick a free key")
+                .small()
+                .weak());
+        });
+        ui.add_space(SECTION_SPACING);
+    }
+    
     ui.group(|ui| {
         ui.label(egui::RichText::new("Character Cycle Order").strong());
         ui.add_space(ITEM_SPACING);
         
+        // Automatic orderings instead of the list
+        ui.horizontal(|ui| {
+            ui.label("Order:");
+            let label = |sort: CycleSort| match sort {
+                CycleSort::Manual => "Manual (list below)",
+                CycleSort::Alphabetical => "Alphabetical",
+                CycleSort::Login => "Login order",
+                CycleSort::Position => "Thumbnail position",
+            };
+            egui::ComboBox::from_id_salt("cycle_sort")
+                .selected_text(label(profile.cycle_sort))
+                .show_ui(ui, |ui| {
+                    for sort in [CycleSort::Manual, CycleSort::Alphabetical, CycleSort::Login, CycleSort::Position] {
+                        if ui.selectable_value(&mut profile.cycle_sort, sort, label(sort)).changed() {
+                            changed = true;
+                        }
+                    }
+                });
+        });
+        
+        if profile.cycle_sort != CycleSort::Manual {
+            ui.label(egui::RichText::new(
+                "Every running character cycles, in an order worked out live (thumbnail position: left to right, then top to bottom). The list below only applies to Manual")
+                .small()
+                .weak());
+        }
+        
+        ui.add_space(ITEM_SPACING);
+        
         // Mode selector
         ui.horizontal(|ui| {
             ui.label("Editor Mode:");
@@ -228,6 +395,52 @@ pub fn ui(ui: &mut egui::Ui, profile: &mut Profile, state: &mut HotkeySettingsSt
             .weak());
     });
     
+    ui.add_space(SECTION_SPACING);
+    
+    // This profile's keymap: switching profiles switches these keys
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Profile Hotkeys").strong());
+        ui.add_space(ITEM_SPACING);
+        
+        ui.horizontal(|ui| {
+            ui.label("Privacy Mode Hotkey:");
+            if hotkey_field(ui, "profile_privacy", "global key", &mut profile.privacy_hotkey) {
+                changed = true;
+            }
+        });
+        conflict_hint(ui, &state.conflicts, &HotkeyAction::Privacy);
+        
+        ui.label(egui::RichText::new(
+            "Overrides the global privacy key while this profile is active; leave empty to use the global one")
+            .small()
+            .weak());
+        
+        ui.add_space(ITEM_SPACING);
+        
+        let mut bound: Vec<(&String, &String)> = profile.character_positions.iter()
+            .filter_map(|(character, settings)| settings.hotkey.as_ref()
+                .filter(|key| !key.trim().is_empty())
+                .map(|key| (key, character)))
+            .collect();
+        bound.sort();
+        if bound.is_empty() {
+            ui.label(egui::RichText::new("No character hotkeys in this profile").weak());
+        } else {
+            egui::Grid::new("profile_character_hotkeys").num_columns(2).show(ui, |ui| {
+                for (key, character) in bound {
+                    ui.label(key);
+                    ui.label(character);
+                    ui.end_row();
+                }
+            });
+        }
+        
+        ui.label(egui::RichText::new(
+            "Character hotkeys are set per character on the Characters tab and belong to this profile")
+            .small()
+            .weak());
+    });
+    
     // Add Characters popup modal
     if state.show_add_characters_popup {
         egui::Window::new("Add Characters")
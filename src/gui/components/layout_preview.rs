@@ -0,0 +1,113 @@
+//! Mock-up of where a profile's thumbnails go (shown before switching profiles)
+
+use eframe::egui;
+use crate::config::profile::Profile;
+use crate::constants::gui::*;
+use crate::types::CharacterSettings;
+
+/// A thumbnail's rectangle in screen pixels, sized like the daemon would
+/// (unset dimensions fall back to the default thumbnail size)
+pub fn thumbnail_rect(settings: &CharacterSettings, default_size: egui::Vec2) -> egui::Rect {
+    let width = if settings.dimensions.width == 0 { default_size.x } else { settings.dimensions.width as f32 };
+    let height = if settings.dimensions.height == 0 { default_size.y } else { settings.dimensions.height as f32 };
+    egui::Rect::from_min_size(egui::pos2(settings.x as f32, settings.y as f32), egui::vec2(width, height))
+}
+
+/// Screen area to draw: the monitor (if known) and every thumbnail of both profiles
+pub fn bounds(rects: impl IntoIterator<Item = egui::Rect>, monitor: Option<egui::Vec2>) -> egui::Rect {
+    let start = monitor.map_or(egui::Rect::NOTHING, |size| egui::Rect::from_min_size(egui::Pos2::ZERO, size));
+    let bounds = rects.into_iter().fold(start, |bounds, rect| bounds.union(rect));
+    if bounds.is_positive() {
+        bounds
+    } else {
+        egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(1920.0, 1080.0))
+    }
+}
+
+/// Maps screen pixels inside `bounds` onto `area`, keeping the aspect ratio
+pub fn screen_to_preview(bounds: egui::Rect, area: egui::Rect) -> egui::emath::RectTransform {
+    let scale = (area.width() / bounds.width()).min(area.height() / bounds.height());
+    let fitted = egui::Rect::from_min_size(area.min, bounds.size() * scale);
+    egui::emath::RectTransform::from_to(bounds, fitted)
+}
+
+/// Draws `target`'s thumbnails filled and `current`'s as outlines on a monitor mock-up
+pub fn ui(ui: &mut egui::Ui, target: &Profile, current: &Profile, default_size: egui::Vec2, monitor: Option<egui::Vec2>) {
+    let visible = |profile: &Profile| -> Vec<(String, egui::Rect)> {
+        let mut rects: Vec<_> = profile.character_positions.iter()
+            .filter(|(_, settings)| !settings.preview_hidden)
+            .map(|(name, settings)| (name.clone(), thumbnail_rect(settings, default_size)))
+            .collect();
+        rects.sort_by(|a, b| a.0.cmp(&b.0));
+        rects
+    };
+    let (target_rects, current_rects) = (visible(target), visible(current));
+    let bounds = bounds(target_rects.iter().chain(&current_rects).map(|(_, rect)| *rect), monitor);
+
+    let width = ui.available_width().min(LAYOUT_PREVIEW_WIDTH);
+    let height = width * bounds.height() / bounds.width();
+    let (response, painter) = ui.allocate_painter(egui::vec2(width, height), egui::Sense::hover());
+    let to_preview = screen_to_preview(bounds, response.rect);
+    let visuals = ui.visuals();
+
+    painter.rect_filled(to_preview.transform_rect(bounds), 2.0, visuals.extreme_bg_color);
+    if let Some(size) = monitor {
+        let screen = to_preview.transform_rect(egui::Rect::from_min_size(egui::Pos2::ZERO, size));
+        painter.rect_stroke(screen, 2.0, visuals.widgets.noninteractive.bg_stroke, egui::StrokeKind::Inside);
+    }
+    for (_, rect) in &current_rects {
+        painter.rect_stroke(to_preview.transform_rect(*rect), 0.0, egui::Stroke::new(1.0, visuals.weak_text_color()), egui::StrokeKind::Inside);
+    }
+    let hover = response.hover_pos();
+    let mut hovered = None;
+    for (name, rect) in &target_rects {
+        let shown = to_preview.transform_rect(*rect);
+        painter.rect_filled(shown, 0.0, visuals.selection.bg_fill.gamma_multiply(0.8));
+        painter.rect_stroke(shown, 0.0, visuals.selection.stroke, egui::StrokeKind::Inside);
+        painter.with_clip_rect(shown).text(
+            shown.left_top() + egui::vec2(2.0, 1.0),
+            egui::Align2::LEFT_TOP,
+            name,
+            egui::FontId::proportional(10.0),
+            visuals.strong_text_color(),
+        );
+        if hover.is_some_and(|pos| shown.contains(pos)) {
+            hovered = Some((name, rect));
+        }
+    }
+    if let Some((name, rect)) = hovered {
+        response.on_hover_text(format!("{}: {}×{} at {}, {}", name, rect.width(), rect.height(), rect.min.x, rect.min.y));
+    }
+
+    if target_rects.is_empty() {
+        ui.weak("This profile has no saved thumbnail positions yet; thumbnails appear next to their clients");
+    } else {
+        ui.label(egui::RichText::new(format!(
+            "{} thumbnails in \"{}\" (filled); outlines show where they are now",
+            target_rects.len(), target.name))
+            .small()
+            .weak());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds_include_monitor_and_offscreen_thumbnails() {
+        let rects = [egui::Rect::from_min_size(egui::pos2(-100.0, 50.0), egui::vec2(200.0, 100.0))];
+        let bounds = bounds(rects, Some(egui::vec2(1920.0, 1080.0)));
+        assert_eq!(bounds, egui::Rect::from_min_max(egui::pos2(-100.0, 0.0), egui::pos2(1920.0, 1080.0)));
+        // Nothing known: a 1080p screen
+        assert_eq!(super::bounds([], None).size(), egui::vec2(1920.0, 1080.0));
+    }
+
+    #[test]
+    fn test_screen_to_preview_keeps_aspect_ratio() {
+        let bounds = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(1920.0, 1080.0));
+        let area = egui::Rect::from_min_size(egui::pos2(10.0, 10.0), egui::vec2(480.0, 400.0));
+        let to_preview = screen_to_preview(bounds, area);
+        assert_eq!(to_preview.transform_pos(egui::pos2(1920.0, 1080.0)), egui::pos2(490.0, 280.0));
+    }
+}
@@ -1,4 +1,11 @@
+pub mod accessibility;
 pub mod profile_selector;
 pub mod visual_settings;
 pub mod hotkey_settings;
 pub mod global_settings;
+pub mod schema_form;
+pub mod layout_preview;
+pub mod character_settings;
+pub mod command_palette;
+pub mod session_summary;
+pub mod usage_stats;
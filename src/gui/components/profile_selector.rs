@@ -1,14 +1,22 @@
 use eframe::egui;
 use crate::config::profile::{Config, Profile};
 use crate::constants::gui::*;
+use super::accessibility::labelled;
+use super::layout_preview;
 
 pub struct ProfileSelector {
     edit_profile_name: String,
     edit_profile_desc: String,
+    /// Base profile picked in the edit dialog
+    edit_profile_inherits: Option<String>,
+    /// Shared layout typed in the edit dialog (empty: the profile's own characters)
+    edit_profile_layout: String,
     show_new_dialog: bool,
     show_duplicate_dialog: bool,
     show_delete_confirm: bool,
     show_edit_dialog: bool,
+    /// Profile picked in the dropdown, waiting for the layout preview to be confirmed
+    pending_switch: Option<usize>,
 }
 
 impl ProfileSelector {
@@ -16,10 +24,13 @@ impl ProfileSelector {
         Self {
             edit_profile_name: String::new(),
             edit_profile_desc: String::new(),
+            edit_profile_inherits: None,
+            edit_profile_layout: String::new(),
             show_new_dialog: false,
             show_duplicate_dialog: false,
             show_delete_confirm: false,
             show_edit_dialog: false,
+            pending_switch: None,
         }
     }
     
@@ -36,6 +47,7 @@ impl ProfileSelector {
                 ui.label(egui::RichText::new("Profile:").strong());
                 
                 // Profile dropdown
+                let mut picked = false;
                 let selected_profile = &config.profiles[*selected_idx];
                 egui::ComboBox::from_id_salt("profile_selector")
                     .selected_text(&selected_profile.name)
@@ -47,12 +59,17 @@ impl ProfileSelector {
                                 format!("{} - {}", profile.name, profile.description)
                             };
                             
-                            if ui.selectable_value(selected_idx, idx, label).clicked() {
-                                config.global.selected_profile = profile.name.clone();
-                                action = ProfileAction::SwitchProfile;
+                            // Switching waits for the layout preview to be confirmed
+                            if ui.selectable_label(*selected_idx == idx, label).clicked() && idx != *selected_idx {
+                                self.pending_switch = Some(idx);
+                                picked = true;
                             }
                         }
                     });
+                if picked {
+                    // The preview shows the target's copy of a shared layout
+                    config.share_layouts();
+                }
             });
             
             ui.add_space(ITEM_SPACING);
@@ -77,6 +94,8 @@ impl ProfileSelector {
                     let current = &config.profiles[*selected_idx];
                     self.edit_profile_name = current.name.clone();
                     self.edit_profile_desc = current.description.clone();
+                    self.edit_profile_inherits = current.inherits.clone();
+                    self.edit_profile_layout = current.layout.clone().unwrap_or_default();
                 }
                 
                 if ui.button("🗑 Delete").clicked() && config.profiles.len() > 1 {
@@ -106,6 +125,10 @@ impl ProfileSelector {
             action = self.delete_confirm_dialog(ui.ctx(), config, selected_idx);
         }
         
+        if let Some(target_idx) = self.pending_switch {
+            action = self.switch_preview_dialog(ui.ctx(), config, selected_idx, target_idx);
+        }
+        
         action
     }
     
@@ -116,11 +139,9 @@ impl ProfileSelector {
             .collapsible(false)
             .resizable(false)
             .show(ctx, |ui| {
-                ui.label("Profile Name:");
-                ui.text_edit_singleline(&mut self.edit_profile_name);
+                labelled(ui, "Profile Name:", egui::TextEdit::singleline(&mut self.edit_profile_name));
                 
-                ui.label("Description (optional):");
-                ui.text_edit_singleline(&mut self.edit_profile_desc);
+                labelled(ui, "Description (optional):", egui::TextEdit::singleline(&mut self.edit_profile_desc));
                 
                 ui.add_space(ITEM_SPACING);
                 
@@ -159,11 +180,9 @@ impl ProfileSelector {
             .collapsible(false)
             .resizable(false)
             .show(ctx, |ui| {
-                ui.label("New Profile Name:");
-                ui.text_edit_singleline(&mut self.edit_profile_name);
+                labelled(ui, "New Profile Name:", egui::TextEdit::singleline(&mut self.edit_profile_name));
                 
-                ui.label("Description (optional):");
-                ui.text_edit_singleline(&mut self.edit_profile_desc);
+                labelled(ui, "Description (optional):", egui::TextEdit::singleline(&mut self.edit_profile_desc));
                 
                 ui.add_space(ITEM_SPACING);
                 
@@ -200,21 +219,66 @@ impl ProfileSelector {
             .collapsible(false)
             .resizable(false)
             .show(ctx, |ui| {
-                ui.label("Profile Name:");
-                ui.text_edit_singleline(&mut self.edit_profile_name);
+                labelled(ui, "Profile Name:", egui::TextEdit::singleline(&mut self.edit_profile_name));
+
+                labelled(ui, "Description (optional):", egui::TextEdit::singleline(&mut self.edit_profile_desc));
+
+                ui.horizontal(|ui| {
+                    ui.label("Inherits from:");
+                    egui::ComboBox::from_id_salt("profile_inherits")
+                        .selected_text(self.edit_profile_inherits.as_deref().unwrap_or("(none)"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.edit_profile_inherits, None, "(none)");
+                            for name in config.possible_bases(selected_idx) {
+                                ui.selectable_value(&mut self.edit_profile_inherits, Some(name.clone()), name);
+                            }
+                        });
+                });
+                ui.label(egui::RichText::new("Settings left as the base profile has them follow its later changes; characters are never inherited")
+                    .small()
+                    .weak());
 
-                ui.label("Description (optional):");
-                ui.text_edit_singleline(&mut self.edit_profile_desc);
+                ui.horizontal(|ui| {
+                    ui.label("Shared layout:");
+                    ui.add(egui::TextEdit::singleline(&mut self.edit_profile_layout).hint_text("(own positions)"));
+                    let names = config.layout_names();
+                    if !names.is_empty() {
+                        ui.menu_button("▾", |ui| {
+                            for name in names {
+                                if ui.button(&name).clicked() {
+                                    self.edit_profile_layout = name;
+                                    ui.close();
+                                }
+                            }
+                        });
+                    }
+                });
+                ui.label(egui::RichText::new("Profiles with the same layout name share their characters, so switching between them keeps every thumbnail in place")
+                    .small()
+                    .weak());
 
                 ui.add_space(ITEM_SPACING);
 
                 ui.horizontal(|ui| {
                     if ui.button("Save").clicked() {
                         if !self.edit_profile_name.is_empty() {
+                            let old_name = config.profiles[selected_idx].name.clone();
+                            config.rename_base(&old_name, Some(&self.edit_profile_name));
                             let profile = &mut config.profiles[selected_idx];
                             profile.name = self.edit_profile_name.clone();
                             profile.description = self.edit_profile_desc.clone();
+                            if profile.inherits != self.edit_profile_inherits {
+                                // Values differing from the new base become the profile's own
+                                profile.inherits = self.edit_profile_inherits.clone();
+                                profile.own_fields.clear();
+                            }
                             config.global.selected_profile = profile.name.clone();
+                            config.profile_edited(selected_idx);
+                            let layout = Some(self.edit_profile_layout.trim().to_string()).filter(|name| !name.is_empty());
+                            let profile = &config.profiles[selected_idx];
+                            if profile.layout != layout {
+                                config.set_layout(selected_idx, layout);
+                            }
                             action = ProfileAction::ProfileUpdated;
                             self.show_edit_dialog = false;
                         }
@@ -229,6 +293,53 @@ impl ProfileSelector {
         action
     }
     
+    fn switch_preview_dialog(
+        &mut self,
+        ctx: &egui::Context,
+        config: &mut Config,
+        selected_idx: &mut usize,
+        target_idx: usize,
+    ) -> ProfileAction {
+        let mut action = ProfileAction::None;
+        if target_idx >= config.profiles.len() {
+            self.pending_switch = None;
+            return action;
+        }
+        
+        let default_size = egui::vec2(
+            config.global.default_thumbnail_width as f32,
+            config.global.default_thumbnail_height as f32,
+        );
+        // The monitor the manager is on, in (approximate) screen pixels
+        let monitor = ctx.input(|input| input.viewport().monitor_size)
+            .map(|size| size * ctx.pixels_per_point());
+        
+        egui::Window::new(format!("Switch to '{}'?", config.profiles[target_idx].name))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                layout_preview::ui(ui, &config.profiles[target_idx], &config.profiles[*selected_idx], default_size, monitor);
+                
+                ui.add_space(ITEM_SPACING);
+                
+                ui.horizontal(|ui| {
+                    if ui.button("Switch").clicked() {
+                        config.share_layouts();
+                        *selected_idx = target_idx;
+                        config.global.selected_profile = config.profiles[target_idx].name.clone();
+                        action = ProfileAction::SwitchProfile;
+                        self.pending_switch = None;
+                    }
+                    
+                    if ui.button("Cancel").clicked() {
+                        self.pending_switch = None;
+                    }
+                });
+            });
+        
+        action
+    }
+    
     fn delete_confirm_dialog(
         &mut self,
         ctx: &egui::Context,
@@ -254,7 +365,8 @@ impl ProfileSelector {
                 
                 ui.horizontal(|ui| {
                     if ui.button("Delete").clicked() {
-                        config.profiles.remove(*selected_idx);
+                        let removed = config.profiles.remove(*selected_idx);
+                        config.rename_base(&removed.name, None);
                         if *selected_idx >= config.profiles.len() {
                             *selected_idx = config.profiles.len() - 1;
                         }
@@ -0,0 +1,306 @@
+//! Settings forms generated from the config schema
+//!
+//! Config fields that no hand-written widget edits yet (no `global.<field>` /
+//! `profile.<field>` in the GUI sources) still show up in the manager: they are
+//! rendered from their schema entry, with the field's doc comment as tooltip,
+//! the schema's range on numbers and a button back to the default. Edits go
+//! through JSON and are only kept when the result deserializes again.
+
+use std::sync::LazyLock;
+
+use eframe::egui;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::schema;
+use crate::constants::gui::*;
+
+/// GUI code with the hand-written widgets
+const GUI_SOURCES: [&str; 7] = [
+    include_str!("global_settings.rs"),
+    include_str!("visual_settings.rs"),
+    include_str!("hotkey_settings.rs"),
+    include_str!("character_settings.rs"),
+    include_str!("profile_selector.rs"),
+    include_str!("layout_preview.rs"),
+    include_str!("../manager.rs"),
+];
+
+static SCHEMA: LazyLock<Value> = LazyLock::new(schema::config_schema);
+static COVERED_GLOBAL: LazyLock<Vec<String>> = LazyLock::new(|| covered_fields("global", &GUI_SOURCES));
+static COVERED_PROFILE: LazyLock<Vec<String>> = LazyLock::new(|| covered_fields("profile", &GUI_SOURCES));
+
+/// Fields of `prefix` ("global" / "profile") the GUI code touches itself
+fn covered_fields(prefix: &str, sources: &[&str]) -> Vec<String> {
+    let pattern = Regex::new(&format!(r"\b{}\.(\w+)", prefix)).unwrap();
+    let mut fields: Vec<String> = sources.iter()
+        .flat_map(|source| pattern.captures_iter(source).map(|caps| caps[1].to_string()))
+        .collect();
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
+/// "window_rules" → "Window rules"
+fn title(name: &str) -> String {
+    let words = name.replace('_', " ");
+    let mut chars = words.chars();
+    chars.next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Value for a field or list item that has none yet: its default, else an empty value of its type
+fn initial(schema: &Value) -> Value {
+    if let Some(default) = schema.get("default") {
+        return default.clone();
+    }
+    match schema.get("type") {
+        Some(Value::Array(_)) => Value::Null,
+        Some(Value::String(kind)) => match kind.as_str() {
+            "boolean" => Value::Bool(false),
+            "integer" => Value::from(schema["minimum"].as_i64().unwrap_or(0).max(0)),
+            "number" => Value::from(0.0),
+            "string" => schema["enum"].get(0).cloned().unwrap_or_else(|| Value::from("")),
+            "array" => Value::Array(Vec::new()),
+            "object" => Value::Object(schema["properties"].as_object()
+                .map(|properties| properties.iter().map(|(name, field)| (name.clone(), initial(field))).collect())
+                .unwrap_or_default()),
+            _ => Value::Null,
+        },
+        _ => Value::Null,
+    }
+}
+
+/// Schema of the non-null type of an optional field
+fn without_null(schema: &Value) -> Value {
+    let mut inner = schema.clone();
+    if let Some(kind) = schema["type"].as_array().and_then(|kinds| kinds.iter().find(|kind| *kind != "null")) {
+        inner["type"] = kind.clone();
+    }
+    inner
+}
+
+/// Whether a generated widget can edit the field: maps keyed by name (characters) need their own editor
+fn editable(schema: &Value) -> bool {
+    schema["type"] != "object" || schema["properties"].is_object()
+}
+
+/// Edit one value as its schema describes; true when it changed
+fn field_ui(ui: &mut egui::Ui, name: &str, schema: &Value, value: &mut Value) -> bool {
+    let description = schema["description"].as_str().unwrap_or_default();
+    let label = title(name);
+    let mut changed = false;
+
+    if schema["type"].is_array() {
+        // Optional: a checkbox sets or clears it
+        let mut set = !value.is_null();
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut set, &label).on_hover_text(description).changed() {
+                *value = if set { initial(&without_null(schema)) } else { Value::Null };
+                changed = true;
+            }
+        });
+        if set {
+            ui.indent(name, |ui| {
+                changed |= field_ui(ui, name, &without_null(schema), value);
+            });
+        }
+        return changed;
+    }
+
+    match schema["type"].as_str().unwrap_or_default() {
+        "boolean" => {
+            let mut checked = value.as_bool().unwrap_or(false);
+            if ui.checkbox(&mut checked, &label).on_hover_text(description).changed() {
+                *value = Value::Bool(checked);
+                changed = true;
+            }
+        }
+        "integer" => {
+            let min = schema["minimum"].as_i64().unwrap_or(i64::MIN);
+            let max = schema["maximum"].as_i64().unwrap_or(i64::MAX);
+            let mut number = value.as_i64().unwrap_or(min.max(0));
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", label)).on_hover_text(description);
+                if ui.add(egui::DragValue::new(&mut number).range(min..=max)).changed() {
+                    *value = Value::from(number);
+                    changed = true;
+                }
+            });
+        }
+        "number" => {
+            let mut number = value.as_f64().unwrap_or(0.0);
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", label)).on_hover_text(description);
+                if ui.add(egui::DragValue::new(&mut number).speed(0.1)).changed() {
+                    *value = Value::from(number);
+                    changed = true;
+                }
+            });
+        }
+        "string" => {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", label)).on_hover_text(description);
+                if let Some(variants) = schema["enum"].as_array() {
+                    let selected = value.as_str().unwrap_or_default().to_string();
+                    egui::ComboBox::from_id_salt(("schema_enum", name, ui.id()))
+                        .selected_text(&selected)
+                        .show_ui(ui, |ui| {
+                            for variant in variants.iter().filter_map(Value::as_str) {
+                                if ui.selectable_label(selected == variant, variant).clicked() && selected != variant {
+                                    *value = Value::from(variant);
+                                    changed = true;
+                                }
+                            }
+                        });
+                } else {
+                    let mut text = value.as_str().unwrap_or_default().to_string();
+                    if ui.text_edit_singleline(&mut text).changed() {
+                        *value = Value::from(text);
+                        changed = true;
+                    }
+                }
+            });
+        }
+        "array" => {
+            let items = &schema["items"];
+            let list = value.as_array().cloned().unwrap_or_default();
+            egui::CollapsingHeader::new(format!("{} ({})", label, list.len()))
+                .id_salt(("schema_list", name, ui.id()))
+                .show(ui, |ui| {
+                    if !description.is_empty() {
+                        ui.label(egui::RichText::new(description).small().weak());
+                    }
+                    let mut list = list;
+                    let mut to_delete = None;
+                    for (idx, item) in list.iter_mut().enumerate() {
+                        ui.push_id(idx, |ui| {
+                            ui.group(|ui| {
+                                changed |= field_ui(ui, &format!("{} {}", name.trim_end_matches('s'), idx + 1), items, item);
+                                if ui.button("🗑 Remove").clicked() {
+                                    to_delete = Some(idx);
+                                }
+                            });
+                        });
+                    }
+                    if let Some(idx) = to_delete {
+                        list.remove(idx);
+                        changed = true;
+                    }
+                    if ui.button("➕ Add").clicked() {
+                        list.push(initial(items));
+                        changed = true;
+                    }
+                    if changed {
+                        *value = Value::Array(list);
+                    }
+                });
+        }
+        "object" => {
+            // Maps (e.g. per-character settings) aren't generated, see editable()
+            let Some(properties) = schema["properties"].as_object() else {
+                return false;
+            };
+            if !value.is_object() {
+                *value = initial(schema);
+            }
+            ui.label(&label).on_hover_text(description);
+            ui.indent(name, |ui| {
+                let object = value.as_object_mut().unwrap();
+                for (field, field_schema) in properties {
+                    let field_value = object.entry(field.clone()).or_insert_with(|| initial(field_schema));
+                    changed |= field_ui(ui, field, field_schema, field_value);
+                }
+            });
+        }
+        _ => {}
+    }
+    changed
+}
+
+/// Render the fields of `settings` without a hand-written widget ("global" or "profile")
+/// Returns true when one of them changed
+pub fn uncovered_ui<T: Serialize + DeserializeOwned>(ui: &mut egui::Ui, prefix: &str, settings: &mut T) -> bool {
+    let (properties, covered) = match prefix {
+        "global" => (&SCHEMA["properties"]["global"]["properties"], &*COVERED_GLOBAL),
+        _ => (&SCHEMA["properties"]["profiles"]["items"]["properties"], &*COVERED_PROFILE),
+    };
+    let fields: Vec<(&String, &Value)> = properties.as_object()
+        .map(|properties| properties.iter()
+            .filter(|(name, field)| !covered.contains(name) && editable(field))
+            .collect())
+        .unwrap_or_default();
+    if fields.is_empty() {
+        return false;
+    }
+    let Ok(Value::Object(mut values)) = serde_json::to_value(&*settings) else {
+        return false;
+    };
+
+    let mut changed = false;
+    ui.add_space(SECTION_SPACING);
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("More Settings").strong());
+        ui.label(egui::RichText::new("Options without their own controls yet, generated from the config schema (hover for details)")
+            .small()
+            .weak());
+        ui.add_space(ITEM_SPACING);
+        for (name, field_schema) in fields {
+            let mut value = values.get(name).cloned().unwrap_or(Value::Null);
+            let mut edited = field_ui(ui, name, field_schema, &mut value);
+            if let Some(default) = field_schema.get("default")
+                && *default != value
+                && ui.small_button("↺ Reset to default").clicked()
+            {
+                value = default.clone();
+                edited = true;
+            }
+            if edited {
+                values.insert(name.clone(), value);
+                changed = true;
+            }
+        }
+    });
+    if !changed {
+        return false;
+    }
+    // Only keep edits that still make a valid config
+    match serde_json::from_value::<T>(Value::Object(values)) {
+        Ok(updated) => {
+            *settings = updated;
+            true
+        }
+        Err(e) => {
+            tracing::debug!(error = %e, "Generated settings form produced an invalid value, edit dropped");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_covered_fields_found_in_sources() {
+        let sources = ["if ui.checkbox(&mut global.cycle_wrap, \"Wrap\")", "let x = global.panel.enabled; profile.name"];
+        assert_eq!(covered_fields("global", &sources), vec!["cycle_wrap", "panel"]);
+        assert_eq!(covered_fields("profile", &sources), vec!["name"]);
+    }
+
+    #[test]
+    fn test_initial_values_follow_schema() {
+        let item = json!({ "type": "object", "properties": {
+            "monitor": { "type": "integer", "minimum": 0, "maximum": 10 },
+            "saturation_percent": { "type": "integer", "default": 100 },
+            "title_regex": { "type": ["string", "null"] },
+            "trigger": { "type": "string", "enum": ["map", "focus"] },
+        }});
+        assert_eq!(initial(&item), json!({ "monitor": 0, "saturation_percent": 100, "title_regex": null, "trigger": "map" }));
+        assert_eq!(title("window_rules"), "Window rules");
+    }
+}
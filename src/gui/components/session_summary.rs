@@ -0,0 +1,72 @@
+//! Last session summary component (written by the daemon on clean shutdown)
+
+use std::time::SystemTime;
+
+use eframe::egui;
+use crate::constants::gui::*;
+use crate::session_summary::{format_duration, SessionSummary};
+
+/// Renders the last session's summary and returns true if a reload was requested
+pub fn ui(ui: &mut egui::Ui, summary: Option<&SessionSummary>) -> bool {
+    let mut reload = false;
+
+    ui.group(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Last Session").strong());
+            if ui.button("⟳ Reload").clicked() {
+                reload = true;
+            }
+        });
+        ui.add_space(ITEM_SPACING);
+
+        let Some(summary) = summary else {
+            ui.label("No finished session yet");
+            ui.label(egui::RichText::new(
+                "A summary is written when the preview daemon shuts down cleanly")
+                .small()
+                .weak());
+            return;
+        };
+
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        ui.label(format!("Started {} ago, ran for {}",
+            format_duration(now.saturating_sub(summary.started_at)),
+            format_duration(summary.duration_secs)));
+        ui.label(format!("Characters seen: {}", summary.characters.len()));
+        ui.label(format!("Hotkey presses: {}, average clients: {:.1}",
+            summary.hotkey_presses,
+            summary.average_clients()));
+
+        if summary.characters.is_empty() {
+            return;
+        }
+
+        ui.add_space(ITEM_SPACING);
+
+        let mut characters: Vec<_> = summary.characters.iter().collect();
+        characters.sort_by(|a, b| b.focus_secs.cmp(&a.focus_secs)
+            .then_with(|| b.focus_switches.cmp(&a.focus_switches))
+            .then_with(|| a.name.cmp(&b.name)));
+
+        egui::Grid::new("last_session_characters")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new("Character").strong());
+                ui.label(egui::RichText::new("Focus time").strong());
+                ui.label(egui::RichText::new("Focus switches").strong());
+                ui.end_row();
+
+                for character in characters {
+                    ui.label(&character.name);
+                    ui.label(format_duration(character.focus_secs));
+                    ui.label(character.focus_switches.to_string());
+                    ui.end_row();
+                }
+            });
+    });
+
+    reload
+}
@@ -0,0 +1,82 @@
+//! Local usage stats component (history kept by the daemon when usage_stats is on)
+
+use eframe::egui;
+use crate::constants::gui::*;
+use crate::constants::usage_stats::{GRAPH_DAYS, GRAPH_HEIGHT};
+use crate::session_summary::format_duration;
+use crate::usage_stats::{DayStats, UsageStats};
+
+/// Renders totals and a per-day graph of the recorded history
+pub fn ui(ui: &mut egui::Ui, stats: &UsageStats, enabled: bool) {
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Usage Stats").strong());
+        ui.add_space(ITEM_SPACING);
+
+        if !enabled {
+            ui.label(egui::RichText::new(
+                "Enable \"Keep local usage stats\" in Global Settings to record daily counters")
+                .small()
+                .weak());
+        }
+
+        if stats.days.is_empty() {
+            ui.label("No sessions recorded yet");
+            return;
+        }
+
+        let total = stats.total();
+        egui::Grid::new("usage_stats_totals")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label(format!("Since {}:", stats.days[0].date));
+                ui.label(format!("{} sessions, {}", total.sessions, format_duration(total.duration_secs)));
+                ui.end_row();
+                ui.label("Hotkey presses:");
+                ui.label(total.hotkey_presses.to_string());
+                ui.end_row();
+                ui.label("Focus switches:");
+                ui.label(total.focus_switches.to_string());
+                ui.end_row();
+                ui.label("Average clients:");
+                ui.label(format!("{:.1}", total.average_clients()));
+                ui.end_row();
+            });
+
+        ui.add_space(ITEM_SPACING);
+        let recent = &stats.days[stats.days.len().saturating_sub(GRAPH_DAYS)..];
+        ui.label(format!("Focus switches per day (last {} recorded days)", recent.len()));
+        graph(ui, recent, |day| day.focus_switches as f32);
+        ui.label(format!("Hotkey presses per day (last {} recorded days)", recent.len()));
+        graph(ui, recent, |day| day.hotkey_presses as f32);
+    });
+}
+
+/// Bar per day, scaled to the largest value; hovering a bar shows its date and value
+fn graph(ui: &mut egui::Ui, days: &[DayStats], value: impl Fn(&DayStats) -> f32) {
+    let width = ui.available_width();
+    let (response, painter) = ui.allocate_painter(egui::vec2(width, GRAPH_HEIGHT), egui::Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let max = days.iter().map(&value).fold(0.0, f32::max).max(1.0);
+    let slot = rect.width() / days.len() as f32;
+    let hover = response.hover_pos();
+    for (index, day) in days.iter().enumerate() {
+        let left = rect.left() + slot * index as f32;
+        let height = value(day) / max * (rect.height() - 4.0);
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(left + slot * 0.15, rect.bottom() - height),
+            egui::pos2(left + slot * 0.85, rect.bottom()),
+        );
+        let hovered = hover.is_some_and(|pos| pos.x >= left && pos.x < left + slot);
+        let color = if hovered {
+            ui.visuals().selection.stroke.color
+        } else {
+            ui.visuals().selection.bg_fill
+        };
+        painter.rect_filled(bar, 1.0, color);
+        if hovered {
+            response.clone().on_hover_text(format!("{}: {}", day.date, value(day)));
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use eframe::egui;
-use crate::config::profile::Profile;
+use crate::config::profile::{PreviewStacking, Profile, ScaleFilter, StackingRule};
 use crate::constants::gui::*;
+use crate::constants::defaults::text::BACKGROUND_COLOR;
 use crate::types::Dimensions;
+use super::accessibility::labelled;
 
 /// State for visual settings UI
 pub struct VisualSettingsState {
@@ -52,8 +54,7 @@ pub fn ui(ui: &mut egui::Ui, profile: &mut Profile, state: &mut VisualSettingsSt
         
         // Opacity
         ui.horizontal(|ui| {
-            ui.label("Opacity:");
-            if ui.add(egui::Slider::new(&mut profile.opacity_percent, 0..=100)
+            if labelled(ui, "Opacity:", egui::Slider::new(&mut profile.opacity_percent, 0..=100)
                 .suffix("%")).changed() {
                 changed = true;
             }
@@ -71,8 +72,7 @@ pub fn ui(ui: &mut egui::Ui, profile: &mut Profile, state: &mut VisualSettingsSt
         if profile.border_enabled {
             ui.indent("border_settings", |ui| {
                 ui.horizontal(|ui| {
-                    ui.label("Border Size:");
-                    if ui.add(egui::DragValue::new(&mut profile.border_size)
+                    if labelled(ui, "Border Size:", egui::DragValue::new(&mut profile.border_size)
                         .range(1..=20)).changed() {
                         changed = true;
                     }
@@ -101,8 +101,7 @@ pub fn ui(ui: &mut egui::Ui, profile: &mut Profile, state: &mut VisualSettingsSt
         
         // Text settings
         ui.horizontal(|ui| {
-            ui.label("Text Size:");
-            if ui.add(egui::DragValue::new(&mut profile.text_size)
+            if labelled(ui, "Text Size:", egui::DragValue::new(&mut profile.text_size)
                 .range(8..=48)).changed() {
                 changed = true;
             }
@@ -110,13 +109,11 @@ pub fn ui(ui: &mut egui::Ui, profile: &mut Profile, state: &mut VisualSettingsSt
         
         ui.horizontal(|ui| {
             ui.label("Text Position:");
-            ui.label("X:");
-            if ui.add(egui::DragValue::new(&mut profile.text_x)
+            if labelled(ui, "X:", egui::DragValue::new(&mut profile.text_x)
                 .range(0..=100)).changed() {
                 changed = true;
             }
-            ui.label("Y:");
-            if ui.add(egui::DragValue::new(&mut profile.text_y)
+            if labelled(ui, "Y:", egui::DragValue::new(&mut profile.text_y)
                 .range(0..=100)).changed() {
                 changed = true;
             }
@@ -139,6 +136,50 @@ pub fn ui(ui: &mut egui::Ui, profile: &mut Profile, state: &mut VisualSettingsSt
             }
         });
         
+        ui.horizontal(|ui| {
+            if labelled(ui, "Label:", egui::TextEdit::singleline(&mut profile.label_template)
+                .desired_width(200.0)).changed() {
+                changed = true;
+            }
+        });
+        
+        ui.label(egui::RichText::new(
+            "{character} alias or name, {name} character name, {index} cycle number, {status} logged out/stalled")
+            .small()
+            .weak());
+        
+        // Label background box
+        ui.horizontal(|ui| {
+            let mut boxed = !profile.text_background.is_empty();
+            if ui.checkbox(&mut boxed, "Label Background").changed() {
+                profile.text_background = if boxed { BACKGROUND_COLOR.to_string() } else { String::new() };
+                changed = true;
+            }
+            if boxed {
+                let text_edit = egui::TextEdit::singleline(&mut profile.text_background)
+                    .desired_width(100.0);
+                if ui.add(text_edit).changed() {
+                    changed = true;
+                }
+                if let Ok(mut color) = parse_hex_color(&profile.text_background)
+                    && ui.color_edit_button_srgba(&mut color).changed()
+                {
+                    profile.text_background = format_hex_color(color);
+                    changed = true;
+                }
+                if labelled(ui, "Padding:", egui::DragValue::new(&mut profile.text_background_padding)
+                    .range(0..=20)
+                    .suffix(" px")).changed() {
+                    changed = true;
+                }
+            }
+        });
+        
+        ui.label(egui::RichText::new(
+            "Box behind the character name so it stays readable over bright scenes")
+            .small()
+            .weak());
+        
         // Font family selector
         ui.horizontal(|ui| {
             ui.label("Font:");
@@ -163,6 +204,92 @@ pub fn ui(ui: &mut egui::Ui, profile: &mut Profile, state: &mut VisualSettingsSt
                     }
                 });
         });
+        
+        // Scaling filter
+        let filter_label = |filter: ScaleFilter| match filter {
+            ScaleFilter::Nearest => "Sharp (nearest)",
+            ScaleFilter::Bilinear => "Smooth (bilinear)",
+            ScaleFilter::Best => "Smoothest (best)",
+        };
+        ui.horizontal(|ui| {
+            ui.label("Scaling:");
+            egui::ComboBox::from_id_salt("scale_filter")
+                .selected_text(filter_label(profile.scale_filter))
+                .show_ui(ui, |ui| {
+                    for filter in [ScaleFilter::Nearest, ScaleFilter::Bilinear, ScaleFilter::Best] {
+                        if ui.selectable_value(&mut profile.scale_filter, filter, filter_label(filter)).changed() {
+                            changed = true;
+                        }
+                    }
+                });
+        });
+        ui.label(egui::RichText::new(
+            "Smoother filters reduce jagged edges on small previews at some cost in sharpness and GPU/CPU time")
+            .small()
+            .weak());
+    });
+    
+    ui.add_space(SECTION_SPACING);
+    
+    // Stacking against other applications
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Stacking").strong());
+        ui.add_space(ITEM_SPACING);
+        
+        let label = |previews: PreviewStacking| match previews {
+            PreviewStacking::Above => "Previews above",
+            PreviewStacking::Below => "Previews below",
+        };
+        let mut to_delete = None;
+        for (idx, rule) in profile.stacking_rules.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.add(egui::TextEdit::singleline(&mut rule.class)
+                    .hint_text("WM_CLASS, e.g. firefox")
+                    .desired_width(160.0)).changed() {
+                    changed = true;
+                }
+                egui::ComboBox::from_id_salt(("stacking_rule", idx))
+                    .selected_text(label(rule.previews))
+                    .show_ui(ui, |ui| {
+                        for previews in [PreviewStacking::Above, PreviewStacking::Below] {
+                            if ui.selectable_value(&mut rule.previews, previews, label(previews)).changed() {
+                                changed = true;
+                            }
+                        }
+                    });
+                if ui.button("🗑").on_hover_text("Remove rule").clicked() {
+                    to_delete = Some(idx);
+                }
+            });
+        }
+        if let Some(idx) = to_delete {
+            profile.stacking_rules.remove(idx);
+            changed = true;
+        }
+        
+        ui.horizontal(|ui| {
+            if ui.button("➕ Add rule").clicked() {
+                profile.stacking_rules.push(StackingRule::default());
+                changed = true;
+            }
+        });
+        
+        ui.horizontal(|ui| {
+            ui.label("Other applications:");
+            egui::ComboBox::from_id_salt("stacking_default")
+                .selected_text(label(profile.stacking_default))
+                .show_ui(ui, |ui| {
+                    for previews in [PreviewStacking::Above, PreviewStacking::Below] {
+                        if ui.selectable_value(&mut profile.stacking_default, previews, label(previews)).changed() {
+                            changed = true;
+                        }
+                    }
+                });
+        });
+        ui.label(egui::RichText::new(
+            "Applies while that application is the active window, matched by WM_CLASS (xprop WM_CLASS shows it). Takes effect on the next window switch")
+            .small()
+            .weak());
     });
     
     ui.add_space(SECTION_SPACING);
@@ -275,8 +402,7 @@ pub fn ui(ui: &mut egui::Ui, profile: &mut Profile, state: &mut VisualSettingsSt
             
             // Width slider
             ui.horizontal(|ui| {
-                ui.label("Width:");
-                if ui.add(egui::Slider::new(&mut state.current_width, 100..=800)
+                if labelled(ui, "Width:", egui::Slider::new(&mut state.current_width, 100..=800)
                     .suffix(" px")).changed() {
                     // Maintain aspect ratio if not custom
                     if aspect_mode != "Custom" {
@@ -377,11 +503,16 @@ pub fn ui(ui: &mut egui::Ui, profile: &mut Profile, state: &mut VisualSettingsSt
             });
     }
     
+    // Options without a hand-written widget yet
+    if super::schema_form::uncovered_ui(ui, "profile", profile) {
+        changed = true;
+    }
+    
     changed
 }
 
 /// Parse hex color string - supports both #RRGGBB and #AARRGGBB formats
-fn parse_hex_color(hex: &str) -> Result<egui::Color32, ()> {
+pub(super) fn parse_hex_color(hex: &str) -> Result<egui::Color32, ()> {
     let hex = hex.trim_start_matches('#');
     
     match hex.len() {
@@ -405,7 +536,7 @@ fn parse_hex_color(hex: &str) -> Result<egui::Color32, ()> {
 }
 
 /// Format egui Color32 to hex string (#AARRGGBB or #RRGGBB)
-fn format_hex_color(color: egui::Color32) -> String {
+pub(super) fn format_hex_color(color: egui::Color32) -> String {
     if color.a() == 255 {
         // Full opacity - use shorter RGB format
         format!("#{:02X}{:02X}{:02X}", color.r(), color.g(), color.b())
@@ -8,16 +8,21 @@ use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Context, Result};
 use eframe::{egui, NativeOptions};
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
 #[cfg(target_os = "linux")]
 use ksni::TrayMethods;
 
 use super::components;
 use crate::constants::gui::*;
-use crate::config::profile::Config;
+use crate::config::profile::{Config, ManagerTab, ResetScope, ResetUndo};
+use crate::gui::components::command_palette::{CommandPaletteState, PaletteAction, PaletteCommand};
 use crate::gui::components::profile_selector::{ProfileSelector, ProfileAction};
+use crate::ipc::journal::{self, Journal};
 use crate::ipc::{PreviewClient, PreviewRequest, PreviewResponse};
+use crate::session_summary::{self, SessionSummary};
+use crate::usage_stats::{self, UsageStats};
+use crate::x11_trace;
 
 // Debounce delay for config saves after IPC position updates (milliseconds)
 // Wait this long after last position change before saving to disk
@@ -32,8 +37,12 @@ thread_local! {
 #[cfg(target_os = "linux")]
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum TrayMessage {
+    Show,
+    /// No StatusNotifier host to show the icon (e.g. GNOME without the AppIndicator extension, bare sway)
+    Unavailable(String),
     Refresh,
     SwitchProfile(usize),
+    TogglePrivacy,
     Quit,
 }
 
@@ -42,6 +51,8 @@ struct AppTray {
     tx: std::sync::mpsc::Sender<TrayMessage>,
     // Cache profile state to avoid loading config from disk on every menu open
     cached_profiles: std::sync::Arc<std::sync::Mutex<(usize, Vec<String>)>>,
+    // Privacy mode as last reported by the daemon
+    privacy: std::sync::Arc<std::sync::atomic::AtomicBool>,
 }
 
 #[cfg(target_os = "linux")]
@@ -68,6 +79,23 @@ impl ksni::Tray for AppTray {
             .unwrap_or_default()
     }
 
+    fn watcher_online(&self) {
+        info!("Tray icon registered with the StatusNotifier host");
+    }
+
+    /// The tray host went away (shell restart, extension disabled): keep waiting for it to
+    /// come back, but make sure the manager window isn't stuck hidden meanwhile
+    fn watcher_offline(&self, reason: ksni::OfflineReason) -> bool {
+        warn!(reason = ?reason, "Tray host went offline");
+        let _ = self.tx.send(TrayMessage::Unavailable(format!("{reason:?}")));
+        true
+    }
+
+    /// Left click on the tray icon brings the manager window up
+    fn activate(&mut self, _x: i32, _y: i32) {
+        let _ = self.tx.send(TrayMessage::Show);
+    }
+
     fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
         use ksni::menu::*;
         
@@ -75,6 +103,15 @@ impl ksni::Tray for AppTray {
         let (current_profile_idx, profile_names) = self.load_current_state();
         
         vec![
+            // Show the manager window (hidden with start_minimized_to_tray)
+            StandardItem {
+                label: "Show Manager".into(),
+                activate: Box::new(|this: &mut AppTray| {
+                    let _ = this.tx.send(TrayMessage::Show);
+                }),
+                ..Default::default()
+            }.into(),
+            
             // Refresh item
             StandardItem {
                 label: "Refresh".into(),
@@ -84,6 +121,16 @@ impl ksni::Tray for AppTray {
                 ..Default::default()
             }.into(),
             
+            // Pixelate all previews (streaming, screen sharing)
+            CheckmarkItem {
+                label: "Privacy Mode".into(),
+                checked: self.privacy.load(std::sync::atomic::Ordering::Relaxed),
+                activate: Box::new(|this: &mut AppTray| {
+                    let _ = this.tx.send(TrayMessage::TogglePrivacy);
+                }),
+                ..Default::default()
+            }.into(),
+            
             // Separator
             MenuItem::Separator,
             
@@ -162,8 +209,11 @@ struct ManagerApp {
     #[cfg(target_os = "linux")]
     tray_profile_cache: std::sync::Arc<std::sync::Mutex<(usize, Vec<String>)>>,
     #[cfg(target_os = "linux")]
+    tray_privacy: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    #[cfg(target_os = "linux")]
     shutdown_signal: std::sync::Arc<tokio::sync::Notify>,
     should_quit: bool,
+    should_show: bool,
     
     // Configuration state with profiles
     config: Config,
@@ -171,20 +221,28 @@ struct ManagerApp {
     profile_selector: ProfileSelector,
     hotkey_settings_state: components::hotkey_settings::HotkeySettingsState,
     visual_settings_state: components::visual_settings::VisualSettingsState,
+    character_settings_state: components::character_settings::CharacterSettingsState,
     settings_changed: bool,
+    /// Section resets that can still be undone, newest last
+    reset_undo: Vec<ResetUndo>,
     
     // Save debouncing for IPC position updates
     pending_save: bool,
     last_position_update: Option<Instant>,
+    /// When the oldest unsaved position update arrived (autosave_interval_secs)
+    dirty_since: Option<Instant>,
+    /// Layout changes received since the last save, written before they are applied
+    journal: Journal,
+    /// Fingerprint of the monitor setup the daemon runs on (monitor_position_sets)
+    monitor_setup: Option<String>,
     
     // UI state
-    active_tab: ActiveTab,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum ActiveTab {
-    GlobalSettings,
-    ProfileSettings,
+    active_tab: ManagerTab,
+    last_session: Option<SessionSummary>,
+    usage_stats: UsageStats,
+    command_palette: CommandPaletteState,
+    /// large_controls value the egui style was last set up for
+    control_size: Option<bool>,
 }
 
 impl ManagerApp {
@@ -204,16 +262,36 @@ impl ManagerApp {
         // Create channel for tray icon commands
         #[cfg(target_os = "linux")]
         let (tx_to_app, tray_rx) = mpsc::channel();
+        #[cfg(target_os = "linux")]
+        let tx_no_tray = tx_to_app.clone();
 
         // Load configuration from thread-local cache (populated by run_gui())
         // If cache is empty (shouldn't happen), fall back to loading from disk
-        let config = CONFIG_CACHE.with(|cache| {
+        let mut config = CONFIG_CACHE.with(|cache| {
             cache.borrow_mut().take()
         }).unwrap_or_else(|| {
             warn!("CONFIG_CACHE empty, loading from disk (this shouldn't happen during normal startup)");
             Config::load().unwrap_or_default()
         });
         
+        // Layout changes a previous run received over IPC but never saved (crash, power loss)
+        let mut journal = Journal::new();
+        match journal::recover(&mut config) {
+            Ok(0) => {}
+            Ok(replayed) => {
+                info!(changes = replayed, "Replayed unsaved layout changes from the IPC journal");
+                match config.save() {
+                    Ok(()) => {
+                        if let Err(e) = journal.clear() {
+                            warn!(error = ?e, "Failed to clear IPC journal after recovery");
+                        }
+                    }
+                    Err(e) => warn!(error = ?e, "Failed to save layout changes recovered from the IPC journal"),
+                }
+            }
+            Err(e) => warn!(error = ?e, "Failed to recover layout changes from the IPC journal"),
+        }
+        
         // Find selected profile index
         let selected_profile_idx = config.profiles
             .iter()
@@ -230,6 +308,10 @@ impl ManagerApp {
         };
         #[cfg(target_os = "linux")]
         let tray_cache_clone = tray_profile_cache.clone();
+        #[cfg(target_os = "linux")]
+        let tray_privacy = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        #[cfg(target_os = "linux")]
+        let tray_privacy_clone = tray_privacy.clone();
 
         // Spawn Tokio thread for ksni tray
         #[cfg(target_os = "linux")]
@@ -248,6 +330,7 @@ impl ManagerApp {
                 let tray = AppTray {
                     tx: tx_to_app,
                     cached_profiles: tray_cache_clone,
+                    privacy: tray_privacy_clone,
                 };
                 
                 match tray.spawn().await {
@@ -261,7 +344,8 @@ impl ManagerApp {
                         handle.shutdown().await;
                     }
                     Err(e) => {
-                        error!(error = ?e, "Failed to create tray icon (D-Bus unavailable?)");
+                        warn!(error = ?e, "Failed to create tray icon, running without one");
+                        let _ = tx_no_tray.send(TrayMessage::Unavailable(e.to_string()));
                     }
                 }
             });
@@ -273,6 +357,9 @@ impl ManagerApp {
         
         // Initialize visual settings state
         let visual_settings_state = components::visual_settings::VisualSettingsState::default();
+        
+        // Reopen the tab that was open last time
+        let active_tab = config.global.manager_tab;
 
         #[cfg(target_os = "linux")]
         let mut app = Self {
@@ -283,17 +370,28 @@ impl ManagerApp {
             status_message: None,
             tray_rx,
             tray_profile_cache,
+            tray_privacy,
             shutdown_signal,
             should_quit: false,
+            should_show: false,
             config,
             selected_profile_idx,
             profile_selector: ProfileSelector::new(),
             hotkey_settings_state,
             visual_settings_state,
+            character_settings_state: components::character_settings::CharacterSettingsState::new(),
             settings_changed: false,
+            reset_undo: Vec::new(),
             pending_save: false,
             last_position_update: None,
-            active_tab: ActiveTab::GlobalSettings,
+            dirty_since: None,
+            journal,
+            monitor_setup: None,
+            active_tab,
+            last_session: None,
+            usage_stats: UsageStats::default(),
+            command_palette: CommandPaletteState::new(),
+            control_size: None,
         };
 
         #[cfg(not(target_os = "linux"))]
@@ -304,17 +402,29 @@ impl ManagerApp {
             last_health_check: Instant::now(),
             status_message: None,
             should_quit: false,
+            should_show: false,
             config,
             selected_profile_idx,
             profile_selector: ProfileSelector::new(),
             hotkey_settings_state,
             visual_settings_state,
+            character_settings_state: components::character_settings::CharacterSettingsState::new(),
             settings_changed: false,
+            reset_undo: Vec::new(),
             pending_save: false,
             last_position_update: None,
-            active_tab: ActiveTab::GlobalSettings,
+            dirty_since: None,
+            journal,
+            monitor_setup: None,
+            active_tab,
+            last_session: None,
+            usage_stats: UsageStats::default(),
+            command_palette: CommandPaletteState::new(),
+            control_size: None,
         };
 
+        app.reload_last_session();
+
         if let Err(err) = app.start_daemon() {
             error!(error = ?err, "Failed to start preview daemon");
             app.status_message = Some(StatusMessage {
@@ -375,14 +485,31 @@ impl ManagerApp {
         // Send complete configuration in one message
         let profile = self.config.profiles[self.selected_profile_idx].clone();
         let global = self.config.global.clone();
-        let resp = client.request(PreviewRequest::SetProfile { profile, global })
+        client.send_request(&PreviewRequest::SetProfile { profile, global })
             .context("Failed to send configuration to preview process")?;
-        match resp {
-            PreviewResponse::Ready => info!("Preview process received configuration via IPC"),
-            _ => warn!(response = ?resp, "Unexpected response from preview process"),
+        
+        // The live character list may arrive before the acknowledgment
+        let mut early_events = Vec::new();
+        loop {
+            match client.recv_response()
+                .context("Failed to receive configuration acknowledgment from preview process")?
+            {
+                PreviewResponse::Ready => {
+                    info!("Preview process received configuration via IPC");
+                    break;
+                }
+                event @ PreviewResponse::LiveCharacters(_) => early_events.push(event),
+                resp => {
+                    warn!(response = ?resp, "Unexpected response from preview process");
+                    break;
+                }
+            }
         }
         
         self.daemon_client = Some(client);
+        for event in early_events {
+            self.handle_ipc_event(event);
+        }
         
         // Note: We no longer spawn a separate event listener thread
         // Instead, we poll the same client connection in poll_ipc_events()
@@ -454,8 +581,10 @@ impl ManagerApp {
     fn save_config(&mut self) -> Result<()> {
         // With IPC, the GUI already has the complete state from CharacterAdded/PositionChanged events
         // No need to reload from disk and merge - just save what we have in memory
+        self.remember_monitor_positions();
         self.config.save()
             .context("Failed to save configuration")?;
+        self.clear_journal();
         
         self.settings_changed = false;
         self.status_message = Some(StatusMessage {
@@ -471,8 +600,17 @@ impl ManagerApp {
         Ok(())
     }
 
+    /// Drop the journaled layout changes, the saved config has them now
+    fn clear_journal(&mut self) {
+        if let Err(e) = self.journal.clear() {
+            warn!(error = ?e, "Failed to clear IPC journal");
+        }
+    }
+
     fn discard_changes(&mut self) {
         self.config = Config::load().unwrap_or_default();
+        // Journaled layout changes were discarded too, recovery mustn't replay them
+        self.clear_journal();
         
         // Re-find selected profile index after reload
         self.selected_profile_idx = self.config.profiles
@@ -485,6 +623,7 @@ impl ManagerApp {
         self.update_tray_cache();
         
         self.settings_changed = false;
+        self.reset_undo.clear();
         self.status_message = Some(StatusMessage {
             text: "Changes discarded".to_string(),
             color: STATUS_STOPPED,
@@ -495,25 +634,39 @@ impl ManagerApp {
     fn process_debounced_save(&mut self) {
         // Check if we have a pending save and enough time has elapsed
         if !self.pending_save {
+            self.dirty_since = None;
             return;
         }
         
+        // Updates that keep coming (scripts, repeated group moves) would put the save off
+        // forever; autosave_interval_secs caps how long they stay unsaved
+        let dirty_since = *self.dirty_since.get_or_insert_with(Instant::now);
+        let interval = self.config.global.autosave_interval_secs;
+        let overdue = interval > 0 && dirty_since.elapsed() >= Duration::from_secs(interval as u64);
+        
         if let Some(last_update) = self.last_position_update {
             let elapsed = last_update.elapsed();
-            if elapsed >= Duration::from_millis(SAVE_DEBOUNCE_MS) {
+            if elapsed >= Duration::from_millis(SAVE_DEBOUNCE_MS) || overdue {
                 // Time to save - enough time has passed since last position update
                 if let Err(e) = self.config.save() {
                     warn!(error = ?e, "Failed to save config after debounced position updates");
                 } else {
                     // Successfully saved
+                    if overdue {
+                        debug!(dirty_secs = dirty_since.elapsed().as_secs(), "Autosaved positions still being updated");
+                    }
                     self.pending_save = false;
                     self.last_position_update = None;
+                    self.dirty_since = None;
+                    self.clear_journal();
                 }
             }
         } else {
             // No timestamp recorded, save immediately
             if let Err(e) = self.config.save() {
                 warn!(error = ?e, "Failed to save config");
+            } else {
+                self.clear_journal();
             }
             self.pending_save = false;
         }
@@ -561,35 +714,29 @@ impl ManagerApp {
         #[cfg(target_os = "linux")]
         while let Ok(msg) = self.tray_rx.try_recv() {
             match msg {
+                TrayMessage::Show => {
+                    self.should_show = true;
+                }
+                TrayMessage::Unavailable(reason) => {
+                    // No tray: the window is the only way back in, so it stays up
+                    info!(reason = %reason, "No system tray, showing the manager window");
+                    self.should_show = true;
+                    self.status_message = Some(StatusMessage {
+                        text: "No system tray available, closing this window quits".to_string(),
+                        color: STATUS_STARTING,
+                    });
+                }
                 TrayMessage::Refresh => {
                     info!("Refresh requested from tray menu");
                     self.reload_daemon_config();
                 }
                 TrayMessage::SwitchProfile(idx) => {
                     info!(profile_idx = idx, "Profile switch requested from tray");
-                    
-                    // Update config's selected_profile field
-                    if idx < self.config.profiles.len() {
-                        self.config.global.selected_profile = 
-                            self.config.profiles[idx].name.clone();
-                        self.selected_profile_idx = idx;
-                        
-                        // Update tray cache immediately
-                        #[cfg(target_os = "linux")]
-                        self.update_tray_cache();
-                        
-                        // Save config with new selection
-                        if let Err(err) = self.save_config() {
-                            error!(error = ?err, "Failed to save config after profile switch");
-                            self.status_message = Some(StatusMessage {
-                                text: format!("Profile switch failed: {err}"),
-                                color: STATUS_STOPPED,
-                            });
-                        } else {
-                            // Reload daemon with new profile
-                            self.reload_daemon_config();
-                        }
-                    }
+                    self.switch_profile(idx);
+                }
+                TrayMessage::TogglePrivacy => {
+                    info!("Privacy mode toggle requested from tray");
+                    self.toggle_privacy();
                 }
                 TrayMessage::Quit => {
                     info!("Quit requested from tray menu");
@@ -599,6 +746,124 @@ impl ManagerApp {
         }
     }
 
+    /// Select a profile, save and restart the daemon with it (tray, command palette)
+    fn switch_profile(&mut self, idx: usize) {
+        if idx >= self.config.profiles.len() {
+            return;
+        }
+        // The outgoing profile has the current copy of a shared layout
+        self.config.share_layouts();
+        // Update config's selected_profile field
+        self.config.global.selected_profile = self.config.profiles[idx].name.clone();
+        self.selected_profile_idx = idx;
+        
+        // Update tray cache immediately
+        #[cfg(target_os = "linux")]
+        self.update_tray_cache();
+        
+        // Save config with new selection
+        if let Err(err) = self.save_config() {
+            error!(error = ?err, "Failed to save config after profile switch");
+            self.status_message = Some(StatusMessage {
+                text: format!("Profile switch failed: {err}"),
+                color: STATUS_STOPPED,
+            });
+        } else {
+            // Reload daemon with new profile
+            self.reload_daemon_config();
+        }
+    }
+    
+    fn toggle_privacy(&mut self) {
+        if let Some(client) = &mut self.daemon_client
+            && let Err(e) = client.send_request(&PreviewRequest::TogglePrivacy)
+        {
+            warn!(error = ?e, "Failed to send privacy toggle to preview process");
+        }
+    }
+    
+    fn save_and_apply(&mut self) {
+        if let Err(err) = self.save_config() {
+            error!(error = ?err, "Failed to save config");
+            self.status_message = Some(StatusMessage {
+                text: format!("Save failed: {err}"),
+                color: STATUS_STOPPED,
+            });
+        } else {
+            self.reload_daemon_config();
+        }
+    }
+    
+    /// Entries of the command palette for the current state
+    fn palette_commands(&self) -> Vec<PaletteCommand> {
+        let mut commands: Vec<PaletteCommand> = self.character_settings_state.live_characters()
+            .map(|character| PaletteCommand::new(format!("Focus: {}", character), PaletteAction::FocusCharacter(character.to_string())))
+            .collect();
+        commands.extend(self.config.profiles.iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != self.selected_profile_idx)
+            .map(|(idx, profile)| PaletteCommand::new(format!("Switch to profile: {}", profile.name), PaletteAction::SwitchProfile(idx))));
+        commands.push(PaletteCommand::new("Toggle privacy mode", PaletteAction::TogglePrivacy));
+        if self.daemon.is_some() {
+            commands.push(PaletteCommand::new("Stop previews", PaletteAction::StopDaemon));
+            commands.push(PaletteCommand::new("Restart daemon", PaletteAction::RestartDaemon));
+        } else {
+            commands.push(PaletteCommand::new("Start previews", PaletteAction::StartDaemon));
+        }
+        commands.push(PaletteCommand::new("Save & apply settings", PaletteAction::SaveAndApply));
+        for (tab, name) in [
+            (ManagerTab::GlobalSettings, "Global Settings"),
+            (ManagerTab::ProfileSettings, "Profile Settings"),
+            (ManagerTab::Characters, "Characters"),
+            (ManagerTab::Session, "Session"),
+        ] {
+            commands.push(PaletteCommand::new(format!("Go to tab: {}", name), PaletteAction::OpenTab(tab)));
+        }
+        commands.push(PaletteCommand::new("Quit", PaletteAction::Quit));
+        commands
+    }
+    
+    /// Switch tabs from the command palette or a Ctrl+number shortcut
+    fn open_tab(&mut self, tab: ManagerTab) {
+        if tab == ManagerTab::Session && self.active_tab != ManagerTab::Session {
+            self.reload_last_session();
+        }
+        self.active_tab = tab;
+    }
+    
+    fn run_palette_action(&mut self, action: PaletteAction) {
+        info!(action = ?action, "Command palette action");
+        match action {
+            PaletteAction::FocusCharacter(character) => {
+                if let Some(client) = &mut self.daemon_client
+                    && let Err(e) = client.send_request(&PreviewRequest::FocusCharacter { character })
+                {
+                    warn!(error = ?e, "Failed to send focus request to preview process");
+                }
+            }
+            PaletteAction::TogglePrivacy => self.toggle_privacy(),
+            PaletteAction::SwitchProfile(idx) => self.switch_profile(idx),
+            PaletteAction::OpenTab(tab) => self.open_tab(tab),
+            PaletteAction::StartDaemon => {
+                if let Err(err) = self.start_daemon() {
+                    error!(error = ?err, "Failed to start preview daemon");
+                    self.status_message = Some(StatusMessage {
+                        text: format!("Failed to start daemon: {err}"),
+                        color: STATUS_STOPPED,
+                    });
+                }
+            }
+            PaletteAction::StopDaemon => {
+                if let Err(err) = self.stop_daemon() {
+                    error!(error = ?err, "Failed to stop preview daemon");
+                }
+            }
+            PaletteAction::RestartDaemon => self.restart_daemon(),
+            PaletteAction::SaveAndApply => self.save_and_apply(),
+            PaletteAction::Quit => self.should_quit = true,
+        }
+    }
+
     fn poll_ipc_events(&mut self) {
         // Collect all pending events first (to avoid borrowing issues)
         let mut events = Vec::new();
@@ -629,34 +894,38 @@ impl ManagerApp {
     }
     
     fn handle_ipc_event(&mut self, event: PreviewResponse) {
+        // Layout changes are journaled before they are applied, the config is saved later (debounced)
+        if journal::is_layout_change(&event) {
+            let profile = &mut self.config.profiles[self.selected_profile_idx];
+            if let Err(e) = self.journal.record(&profile.name, &event) {
+                warn!(error = ?e, "Failed to journal layout change");
+            }
+            journal::apply(profile, &event);
+        }
+        
         match event {
-                PreviewResponse::PositionChanged { character, x, y, width, height } => {
+                PreviewResponse::PositionChanged { character, x, y, .. } => {
                     info!(character = %character, x = x, y = y, "Received PositionChanged event via IPC");
-                    
-                    // Update character position in current profile
-                    let profile = &mut self.config.profiles[self.selected_profile_idx];
-                    let settings = crate::types::CharacterSettings::new(x, y, width, height);
-                    profile.character_positions.insert(character, settings);
+                    self.character_settings_state.character_online(&character);
+                    self.mark_seen(&character);
                     
                     // Mark for debounced save instead of immediate save
                     self.pending_save = true;
                     self.last_position_update = Some(Instant::now());
                 }
                 
-                PreviewResponse::CharacterAdded { character, x, y, width, height } => {
+                PreviewResponse::CharacterAdded { character, x, y, .. } => {
                     info!(character = %character, x = x, y = y, "Received CharacterAdded event via IPC");
-                    
-                    // Add new character to current profile
-                    let profile = &mut self.config.profiles[self.selected_profile_idx];
-                    let settings = crate::types::CharacterSettings::new(x, y, width, height);
-                    profile.character_positions.insert(character.clone(), settings);
+                    self.character_settings_state.character_online(&character);
                     
                     // Add to cycle group if not already present
+                    let profile = &mut self.config.profiles[self.selected_profile_idx];
                     if !profile.cycle_group.contains(&character) {
                         profile.cycle_group.push(character.clone());
                         // Reload hotkey settings UI to reflect the change
                         self.hotkey_settings_state.load_from_profile(profile);
                     }
+                    self.mark_seen(&character);
                     
                     // Mark for debounced save
                     self.pending_save = true;
@@ -665,9 +934,71 @@ impl ManagerApp {
                 
                 PreviewResponse::CharacterRemoved(character) => {
                     info!(character = %character, "Received CharacterRemoved event via IPC");
+                    self.character_settings_state.character_offline(&character);
+                    self.mark_seen(&character);
+                    self.pending_save = true;
+                    self.last_position_update = Some(Instant::now());
                     // Note: We don't remove from config - preserve positions for when they log back in
                 }
                 
+                PreviewResponse::LiveCharacters(characters) => {
+                    info!(count = characters.len(), "Received LiveCharacters event via IPC");
+                    for character in &characters {
+                        self.mark_seen(character);
+                    }
+                    self.character_settings_state.set_live(characters);
+                    self.pending_save = true;
+                    self.last_position_update = Some(Instant::now());
+                }
+                
+                PreviewResponse::DuplicateCharacter(character) => {
+                    warn!(character = %character, "Received DuplicateCharacter event via IPC");
+                    self.status_message = Some(StatusMessage {
+                        text: format!("Two clients claim '{character}' - the older window was relabelled"),
+                        color: STATUS_STARTING,
+                    });
+                }
+                
+                PreviewResponse::PanelDetachedChanged { character, detached } => {
+                    info!(character = %character, detached = detached, "Received PanelDetachedChanged event via IPC");
+                    self.pending_save = true;
+                    self.last_position_update = Some(Instant::now());
+                }
+                
+                PreviewResponse::RedactionsChanged { character, redactions } => {
+                    info!(character = %character, count = redactions.len(), "Received RedactionsChanged event via IPC");
+                    self.pending_save = true;
+                    self.last_position_update = Some(Instant::now());
+                }
+                
+                PreviewResponse::PrivacyChanged(enabled) => {
+                    info!(enabled = enabled, "Received PrivacyChanged event via IPC");
+                    #[cfg(target_os = "linux")]
+                    self.tray_privacy.store(enabled, std::sync::atomic::Ordering::Relaxed);
+                }
+                
+                PreviewResponse::HotkeyConflicts(conflicts) => {
+                    info!(count = conflicts.len(), "Received HotkeyConflicts event via IPC");
+                    if !conflicts.is_empty() {
+                        self.status_message = Some(StatusMessage {
+                            text: format!("{} hotkey(s) grabbed by another application - see Hotkey Conflicts", conflicts.len()),
+                            color: STATUS_STARTING,
+                        });
+                    }
+                    self.hotkey_settings_state.set_conflicts(conflicts);
+                }
+                
+                PreviewResponse::MonitorSetupChanged { previous, current } => {
+                    info!(previous = ?previous, current = %current, "Received MonitorSetupChanged event via IPC");
+                    // Positions so far belong to the previous setup; the daemon moves the thumbnails itself
+                    if previous.is_some() {
+                        self.remember_monitor_positions();
+                        self.pending_save = true;
+                        self.last_position_update = Some(Instant::now());
+                    }
+                    self.monitor_setup = Some(current);
+                }
+                
                 // Ignore response-only messages (these shouldn't come unsolicited)
                 PreviewResponse::Ready | PreviewResponse::Pong | PreviewResponse::Positions(_) | PreviewResponse::Error(_) => {
                     warn!(response = ?event, "Received unexpected IPC response (not an event)");
@@ -675,9 +1006,117 @@ impl ManagerApp {
         }
     }
 
+    /// Keep the selected profile's positions as those of the current monitor setup (monitor_position_sets)
+    fn remember_monitor_positions(&mut self) {
+        if let Some(setup) = &self.monitor_setup
+            && self.config.global.monitor_position_sets
+        {
+            let profile = &mut self.config.profiles[self.selected_profile_idx];
+            let positions = profile.character_positions.clone();
+            profile.remember_monitor_positions(setup, &positions);
+        }
+    }
+
+    /// Record that a character's client is (or just was) running, for the character list's "last seen" order
+    fn mark_seen(&mut self, character: &str) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let profile = &mut self.config.profiles[self.selected_profile_idx];
+        if let Some(settings) = profile.character_positions.get_mut(character) {
+            settings.last_seen = Some(now);
+        }
+    }
+
+    /// Keep the manager window's size and position in the config (saved on exit)
+    fn track_window_geometry(&mut self, ctx: &egui::Context) {
+        let (inner, outer) = ctx.input(|i| (i.viewport().inner_rect, i.viewport().outer_rect));
+        let global = &mut self.config.global;
+        if let Some(inner) = inner {
+            global.window_width = inner.width().round() as u16;
+            global.window_height = inner.height().round() as u16;
+        }
+        // Not every platform reports the position (e.g. Wayland)
+        if let Some(outer) = outer {
+            global.window_x = Some(outer.min.x.round() as i16);
+            global.window_y = Some(outer.min.y.round() as i16);
+        }
+        global.manager_tab = self.active_tab;
+    }
+
+    /// Write the window geometry and open tab to the config on disk, leaving unsaved edits out
+    fn save_window_state(&self) -> Result<()> {
+        let mut config = Config::load()
+            .context("Failed to load config to store window state")?;
+        let global = &self.config.global;
+        config.global.window_width = global.window_width;
+        config.global.window_height = global.window_height;
+        config.global.window_x = global.window_x;
+        config.global.window_y = global.window_y;
+        config.global.manager_tab = global.manager_tab;
+        config.save()
+            .context("Failed to save manager window state")?;
+        info!(width = global.window_width, height = global.window_height, tab = ?global.manager_tab, "Saved manager window state");
+        Ok(())
+    }
+
+    /// Reset one section of the config to defaults, keeping it undoable
+    fn reset_section(&mut self, scope: ResetScope) {
+        let undo = self.config.reset(scope, self.selected_profile_idx);
+        self.reset_undo.push(undo);
+        self.config.profile_edited(self.selected_profile_idx);
+        self.hotkey_settings_state.load_from_profile(&self.config.profiles[self.selected_profile_idx]);
+        self.settings_changed = true;
+        self.status_message = Some(StatusMessage {
+            text: format!("Reset {} to defaults", scope.label()),
+            color: STATUS_RUNNING,
+        });
+        info!(scope = ?scope, profile = %self.config.profiles[self.selected_profile_idx].name, "Reset settings to defaults");
+    }
+
+    fn undo_reset(&mut self) {
+        let Some(undo) = self.reset_undo.pop() else {
+            return;
+        };
+        let scope = undo.scope;
+        self.config.undo_reset(undo);
+        self.config.profile_edited(self.selected_profile_idx);
+        self.hotkey_settings_state.load_from_profile(&self.config.profiles[self.selected_profile_idx]);
+        self.settings_changed = true;
+        self.status_message = Some(StatusMessage {
+            text: format!("Restored {}", scope.label()),
+            color: STATUS_RUNNING,
+        });
+        info!(scope = ?scope, "Undid settings reset");
+    }
+
+    /// Row of "reset to defaults" buttons; returns the one clicked
+    fn reset_buttons_ui(ui: &mut egui::Ui, scopes: &[(ResetScope, &str)]) -> Option<ResetScope> {
+        let mut clicked = None;
+        ui.horizontal(|ui| {
+            for &(scope, text) in scopes {
+                if ui.small_button(text)
+                    .on_hover_text(format!("Put the {} back to their defaults (can be undone until saved)", scope.label()))
+                    .clicked()
+                {
+                    clicked = Some(scope);
+                }
+            }
+        });
+        clicked
+    }
+
     fn render_global_settings_tab(&mut self, ui: &mut egui::Ui) {
+        if let Some(scope) = Self::reset_buttons_ui(ui, &[
+            (ResetScope::Behavior, "↺ Reset behavior"),
+            (ResetScope::Hotkeys, "↺ Reset hotkeys"),
+        ]) {
+            self.reset_section(scope);
+        }
+        ui.add_space(ITEM_SPACING);
+
         // Global Settings
-        if components::global_settings::ui(ui, &mut self.config.global) {
+        if components::global_settings::ui(ui, &mut self.config.global, self.hotkey_settings_state.conflicts()) {
             self.settings_changed = true;
         }
     }
@@ -708,6 +1147,9 @@ impl ManagerApp {
                 }
             }
             ProfileAction::ProfileCreated | ProfileAction::ProfileDeleted | ProfileAction::ProfileUpdated => {
+                // Profile indices of pending undos may point elsewhere now
+                self.reset_undo.clear();
+
                 // Save config and reload daemon
                 if let Err(err) = self.save_config() {
                     error!(error = ?err, "Failed to save config after profile action");
@@ -722,24 +1164,111 @@ impl ManagerApp {
             ProfileAction::None => {}
         }
 
+        if let Some(scope) = Self::reset_buttons_ui(ui, &[
+            (ResetScope::Visual, "↺ Reset visual"),
+            (ResetScope::Hotkeys, "↺ Reset hotkeys"),
+            (ResetScope::Profile, "↺ Reset whole profile"),
+        ]) {
+            self.reset_section(scope);
+        }
+
         ui.add_space(SECTION_SPACING);
         ui.separator();
         ui.add_space(SECTION_SPACING);
 
         // Visual Settings and Hotkey Settings side-by-side
         let current_profile = &mut self.config.profiles[self.selected_profile_idx];
+        let mut edited = false;
         
         ui.columns(2, |columns| {
             // Left column: Visual Settings
             if components::visual_settings::ui(&mut columns[0], current_profile, &mut self.visual_settings_state) {
-                self.settings_changed = true;
+                edited = true;
             }
             
             // Right column: Hotkey Settings
             if components::hotkey_settings::ui(&mut columns[1], current_profile, &mut self.hotkey_settings_state) {
-                self.settings_changed = true;
+                edited = true;
             }
         });
+
+        if edited {
+            self.config.profile_edited(self.selected_profile_idx);
+            self.settings_changed = true;
+        }
+    }
+    
+    fn render_characters_tab(&mut self, ui: &mut egui::Ui) {
+        let profile = &mut self.config.profiles[self.selected_profile_idx];
+        if let Some(character) = components::character_settings::ui(ui, profile, &mut self.character_settings_state, self.hotkey_settings_state.conflicts()) {
+            self.settings_changed = true;
+            self.push_character_settings(&character);
+        }
+        if let Some((character, relaunch)) = self.character_settings_state.take_terminate_request() {
+            self.terminate_client(&character, relaunch);
+        }
+        if let Some(character) = self.character_settings_state.take_redact_edit_request() {
+            self.edit_redactions(character);
+        }
+    }
+    
+    fn render_session_tab(&mut self, ui: &mut egui::Ui) {
+        if components::session_summary::ui(ui, self.last_session.as_ref()) {
+            self.reload_last_session();
+        }
+        ui.add_space(SECTION_SPACING);
+        components::usage_stats::ui(ui, &self.usage_stats, self.config.global.usage_stats);
+    }
+
+    fn reload_last_session(&mut self) {
+        match session_summary::load_last() {
+            Ok(summary) => self.last_session = summary,
+            Err(err) => warn!(error = ?err, "Failed to load last session summary"),
+        }
+        match usage_stats::load() {
+            Ok(stats) => self.usage_stats = stats,
+            Err(err) => warn!(error = ?err, "Failed to load usage stats"),
+        }
+    }
+    
+    /// Ask the daemon to terminate a character's client (it knows the client PIDs)
+    fn terminate_client(&mut self, character: &str, relaunch: bool) {
+        let Some(client) = &mut self.daemon_client else {
+            return;
+        };
+        let request = PreviewRequest::TerminateClient {
+            character: character.to_string(),
+            relaunch,
+        };
+        match client.send_request(&request) {
+            Ok(()) => info!(character = %character, relaunch = relaunch, "Requested client termination"),
+            Err(e) => warn!(character = %character, error = ?e, "Failed to send terminate request to preview process"),
+        }
+    }
+    
+    /// Put a character's preview in redaction edit mode (None = leave it)
+    fn edit_redactions(&mut self, character: Option<String>) {
+        let Some(client) = &mut self.daemon_client else {
+            return;
+        };
+        if let Err(e) = client.send_request(&PreviewRequest::EditRedactions { character }) {
+            warn!(error = ?e, "Failed to send redaction edit mode to preview process");
+        }
+    }
+    
+    /// Send one character's edited settings to the running daemon so they apply immediately
+    fn push_character_settings(&mut self, character: &str) {
+        let profile = &self.config.profiles[self.selected_profile_idx];
+        let (Some(client), Some(settings)) = (&mut self.daemon_client, profile.character_positions.get(character)) else {
+            return;
+        };
+        let request = PreviewRequest::UpdateCharacter {
+            character: character.to_string(),
+            settings: settings.clone(),
+        };
+        if let Err(e) = client.send_request(&request) {
+            warn!(character = %character, error = ?e, "Failed to send character settings to preview process");
+        }
     }
 }
 
@@ -754,11 +1283,28 @@ impl eframe::App for ManagerApp {
         // This ensures tray menu actions are processed promptly
         ctx.request_repaint_after(std::time::Duration::from_millis(100));
 
+        self.track_window_geometry(ctx);
+        self.command_palette.handle_shortcut(ctx);
+        if let Some(tab) = components::accessibility::tab_shortcut(ctx) {
+            self.open_tab(tab);
+        }
+        if self.control_size != Some(self.config.global.large_controls) {
+            self.control_size = Some(self.config.global.large_controls);
+            components::accessibility::apply_control_size(ctx, self.config.global.large_controls);
+        }
+
         // Handle quit request from tray menu
         if self.should_quit {
             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
             return;
         }
+        
+        if self.should_show {
+            self.should_show = false;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
 
         egui::CentralPanel::default().show(ctx, |ui| {
             // Slim status bar at top
@@ -779,10 +1325,19 @@ impl eframe::App for ManagerApp {
             // Tab Bar
             ui.horizontal(|ui| {
                 let prev_tab = self.active_tab;
-                ui.selectable_value(&mut self.active_tab, ActiveTab::GlobalSettings, "⚙ Global Settings");
-                ui.selectable_value(&mut self.active_tab, ActiveTab::ProfileSettings, "📋 Profile Settings");
+                ui.selectable_value(&mut self.active_tab, ManagerTab::GlobalSettings, "⚙ Global Settings")
+                    .on_hover_text("Ctrl+1");
+                ui.selectable_value(&mut self.active_tab, ManagerTab::ProfileSettings, "📋 Profile Settings")
+                    .on_hover_text("Ctrl+2");
+                ui.selectable_value(&mut self.active_tab, ManagerTab::Characters, "👤 Characters")
+                    .on_hover_text("Ctrl+3");
+                ui.selectable_value(&mut self.active_tab, ManagerTab::Session, "📊 Session")
+                    .on_hover_text("Ctrl+4");
                 
                 // Tab switched - IPC event listener handles new character discovery automatically
+                if self.active_tab == ManagerTab::Session && prev_tab != ManagerTab::Session {
+                    self.reload_last_session();
+                }
             });
 
             ui.add_space(SECTION_SPACING);
@@ -792,8 +1347,10 @@ impl eframe::App for ManagerApp {
             // Tab Content
             egui::ScrollArea::vertical().show(ui, |ui| {
                 match self.active_tab {
-                    ActiveTab::GlobalSettings => self.render_global_settings_tab(ui),
-                    ActiveTab::ProfileSettings => self.render_profile_settings_tab(ui),
+                    ManagerTab::GlobalSettings => self.render_global_settings_tab(ui),
+                    ManagerTab::ProfileSettings => self.render_profile_settings_tab(ui),
+                    ManagerTab::Characters => self.render_characters_tab(ui),
+                    ManagerTab::Session => self.render_session_tab(ui),
                 }
             });
 
@@ -804,20 +1361,20 @@ impl eframe::App for ManagerApp {
             // Save/Discard buttons (always visible at bottom)
             ui.horizontal(|ui| {
                 if ui.button("💾 Save & Apply").clicked() {
-                    if let Err(err) = self.save_config() {
-                        error!(error = ?err, "Failed to save config");
-                        self.status_message = Some(StatusMessage {
-                            text: format!("Save failed: {err}"),
-                            color: STATUS_STOPPED,
-                        });
-                    } else {
-                        self.reload_daemon_config();
-                    }
+                    self.save_and_apply();
                 }
                 
                 if ui.button("↶ Discard Changes").clicked() {
                     self.discard_changes();
                 }
+
+                if let Some(undo) = self.reset_undo.last()
+                    && ui.button("↶ Undo reset")
+                        .on_hover_text(format!("Restore the {} as they were before the reset", undo.scope.label()))
+                        .clicked()
+                {
+                    self.undo_reset();
+                }
                 
                 if self.settings_changed {
                     ui.colored_label(
@@ -825,8 +1382,19 @@ impl eframe::App for ManagerApp {
                         "● Unsaved changes"
                     );
                 }
+                
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.weak("Ctrl+P: commands");
+                });
             });
         });
+        
+        if self.command_palette.is_open() {
+            let commands = self.palette_commands();
+            if let Some(action) = components::command_palette::ui(ctx, &mut self.command_palette, &commands) {
+                self.run_palette_action(action);
+            }
+        }
 
         ctx.request_repaint_after(Duration::from_millis(DAEMON_CHECK_INTERVAL_MS));
     }
@@ -838,9 +1406,14 @@ impl eframe::App for ManagerApp {
                 error!(error = ?e, "Failed to save config during shutdown");
             } else {
                 info!("Saved pending config changes during shutdown");
+                self.clear_journal();
             }
         }
         
+        if let Err(e) = self.save_window_state() {
+            error!(error = ?e, "Failed to save manager window state during shutdown");
+        }
+        
         if let Err(err) = self.stop_daemon() {
             error!(error = ?err, "Failed to stop daemon during shutdown");
         }
@@ -860,6 +1433,8 @@ fn spawn_preview_daemon() -> Result<Child> {
     let exe_path = std::env::current_exe().context("Failed to resolve executable path")?;
     Command::new(exe_path)
         .arg("--preview")
+        .args(x11_trace::daemon_args())
+        .args(crate::config::overrides::daemon_args())
         .spawn()
         .context("Failed to spawn preview daemon")
 }
@@ -939,10 +1514,17 @@ fn load_window_icon() -> Result<egui::IconData> {
 }
 
 pub fn run_gui() -> Result<()> {
+    // A config anyone could edit can't be trusted with the control socket or credentials
+    crate::permissions::ensure_not_world_writable(&Config::path())
+        .inspect_err(|e| error!(error = %e, "Unsafe config permissions"))?;
+    
     // Load config ONCE at startup
     let config = Config::load().unwrap_or_default();
     let window_width = config.global.window_width as f32;
     let window_height = config.global.window_height as f32;
+    let window_position = config.global.window_x.zip(config.global.window_y);
+    // Only the tray can bring a hidden window back, so this is Linux-only
+    let start_hidden = cfg!(target_os = "linux") && config.global.start_minimized_to_tray;
     
     // Store config in thread-local for ManagerApp::new() to access
     // This eliminates the second Config::load() call
@@ -971,6 +1553,15 @@ pub fn run_gui() -> Result<()> {
         .with_min_inner_size([WINDOW_MIN_WIDTH, WINDOW_MIN_HEIGHT])
         .with_title("EVE-L Preview Manager");
     
+    if let Some((x, y)) = window_position {
+        viewport_builder = viewport_builder.with_position([x as f32, y as f32]);
+    }
+    
+    if start_hidden {
+        info!("Starting minimized to tray");
+        viewport_builder = viewport_builder.with_visible(false);
+    }
+    
     if let Some(icon_data) = icon {
         viewport_builder = viewport_builder.with_icon(icon_data);
     }
@@ -0,0 +1,155 @@
+//! Hotkeys another X client already holds
+//!
+//! evdev hotkeys read the keyboard directly, so a key the WM or another app has
+//! grabbed (a workspace binding, a screenshot tool) still reaches us, but it
+//! runs that app's action too, and some WMs swallow it before EVE sees it.
+//! After settings arrive the daemon test-grabs every configured key on the root
+//! window: BadAccess means somebody else holds it. Conflicts go to the GUI with
+//! a few free keys to try instead.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use x11rb::connection::Connection;
+use x11rb::errors::ReplyError;
+use x11rb::protocol::xproto::{ConnectionExt, GrabMode, ModMask, Window};
+use x11rb::protocol::ErrorKind;
+use x11rb::rust_connection::RustConnection;
+
+use crate::constants::input;
+use crate::hotkeys::{key_name, parse_key_name, CycleCommand};
+
+/// What a conflicting hotkey is bound to
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum HotkeyAction {
+    /// Tab/Shift+Tab
+    Cycle,
+    Privacy,
+    FocusLast,
+    FocusBack,
+    FocusForward,
+    Focus(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HotkeyConflict {
+    pub action: HotkeyAction,
+    /// Key name as evdev spells it, without the KEY_ prefix ("F1")
+    pub key: String,
+    /// Free keys to bind instead
+    pub suggestions: Vec<String>,
+}
+
+/// Conflicts among the bound keys (plus Tab), given which keys are grabbed elsewhere
+fn conflicts_from(bindings: &HashMap<u16, CycleCommand>, mut grabbed: impl FnMut(u16) -> bool) -> Vec<HotkeyConflict> {
+    let mut actions: Vec<(HotkeyAction, u16)> = bindings.iter()
+        .filter_map(|(code, command)| match command {
+            CycleCommand::TogglePrivacy => Some((HotkeyAction::Privacy, *code)),
+            CycleCommand::FocusLast => Some((HotkeyAction::FocusLast, *code)),
+            CycleCommand::FocusBack => Some((HotkeyAction::FocusBack, *code)),
+            CycleCommand::FocusForward => Some((HotkeyAction::FocusForward, *code)),
+            CycleCommand::Focus(character) => Some((HotkeyAction::Focus(character.clone()), *code)),
+            // The leader usually comes with modifiers, which the unmodified test grab doesn't cover
+            CycleCommand::Forward | CycleCommand::Backward | CycleCommand::Leader(_) => None,
+        })
+        .collect();
+    actions.push((HotkeyAction::Cycle, input::KEY_TAB));
+    actions.sort();
+
+    let mut conflicts: Vec<HotkeyConflict> = actions.into_iter()
+        .filter(|(_, code)| grabbed(*code))
+        .map(|(action, code)| HotkeyConflict { action, key: key_name(code), suggestions: Vec::new() })
+        .collect();
+    if conflicts.is_empty() {
+        return conflicts;
+    }
+
+    // The same few free keys are offered for every conflict, the user picks one per action
+    let bound: HashSet<u16> = bindings.keys().copied().chain([input::KEY_TAB]).collect();
+    let suggestions: Vec<String> = input::HOTKEY_SUGGESTIONS.iter()
+        .filter_map(|name| parse_key_name(name))
+        .filter(|code| !bound.contains(code) && !grabbed(*code))
+        .take(input::MAX_HOTKEY_SUGGESTIONS)
+        .map(key_name)
+        .collect();
+    for conflict in &mut conflicts {
+        conflict.suggestions = suggestions.clone();
+    }
+    conflicts
+}
+
+/// X connection used to test-grab hotkeys (the IPC thread's own, the main loop's is busy)
+pub struct GrabChecker {
+    conn: RustConnection,
+    root: Window,
+}
+
+impl GrabChecker {
+    pub fn connect() -> Result<Self> {
+        let (conn, screen_num) = x11rb::connect(None)
+            .context("Failed to connect to X11 server for the hotkey conflict check")?;
+        let root = conn.setup().roots[screen_num].root;
+        Ok(Self { conn, root })
+    }
+
+    /// Whether another client holds a passive grab on the unmodified key
+    fn grabbed(&self, code: u16) -> Result<bool> {
+        let Ok(keycode) = u8::try_from(code + input::X_KEYCODE_OFFSET) else {
+            return Ok(false);
+        };
+        let modifiers = ModMask::from(0u16);
+        match self.conn.grab_key(false, self.root, modifiers, keycode, GrabMode::ASYNC, GrabMode::ASYNC)?.check() {
+            Ok(()) => {
+                self.conn.ungrab_key(keycode, self.root, modifiers)?;
+                self.conn.flush()?;
+                Ok(false)
+            }
+            Err(ReplyError::X11Error(e)) if e.error_kind == ErrorKind::Access => Ok(true),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Bound hotkeys that another X client has grabbed, with replacement suggestions
+    pub fn conflicts(&self, bindings: &HashMap<u16, CycleCommand>) -> Vec<HotkeyConflict> {
+        conflicts_from(bindings, |code| {
+            self.grabbed(code)
+                .inspect_err(|e| debug!(error = ?e, code = code, "Hotkey grab test failed"))
+                .unwrap_or(false)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use evdev::KeyCode;
+
+    #[test]
+    fn test_conflicts_only_for_grabbed_keys() {
+        let bindings = HashMap::from([
+            (KeyCode::KEY_F1.code(), CycleCommand::Focus("FC".to_string())),
+            (KeyCode::KEY_F2.code(), CycleCommand::Focus("Scout".to_string())),
+            (KeyCode::KEY_F12.code(), CycleCommand::TogglePrivacy),
+        ]);
+        let grabbed = [KeyCode::KEY_F2.code(), KeyCode::KEY_F12.code()];
+        let conflicts = conflicts_from(&bindings, |code| grabbed.contains(&code));
+        let actions: Vec<_> = conflicts.iter().map(|c| (c.action.clone(), c.key.as_str())).collect();
+        assert_eq!(actions, vec![
+            (HotkeyAction::Privacy, "F12"),
+            (HotkeyAction::Focus("Scout".to_string()), "F2"),
+        ]);
+        assert!(conflicts_from(&bindings, |_| false).is_empty());
+    }
+
+    #[test]
+    fn test_suggestions_skip_bound_and_grabbed_keys() {
+        let bindings = HashMap::from([(KeyCode::KEY_F1.code(), CycleCommand::Focus("FC".to_string()))]);
+        let grabbed = [KeyCode::KEY_F1.code(), KeyCode::KEY_F3.code(), input::KEY_TAB];
+        let conflicts = conflicts_from(&bindings, |code| grabbed.contains(&code));
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].action, HotkeyAction::Cycle);
+        assert_eq!(conflicts[1].suggestions, vec!["F2", "F4", "F5"]);
+    }
+}
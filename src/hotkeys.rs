@@ -1,15 +1,240 @@
 use anyhow::{Context, Result};
 use evdev::{Device, EventType, KeyCode};
-use std::sync::mpsc::Sender;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::mpsc::{self, Sender};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use tracing::{debug, error, info, warn};
 
 use crate::constants::{input, paths, permissions};
+use crate::types::CharacterSettings;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CycleCommand {
     Forward,
     Backward,
+    /// Per-character hotkey pressed: focus this character directly
+    Focus(String),
+    /// Pixelate all previews, or stop doing so (privacy mode)
+    TogglePrivacy,
+    /// Chord leader pressed (with these modifiers held): the next key picks a character
+    Leader(Modifiers),
+    /// Back to the previously focused character
+    FocusLast,
+    /// One step back along the focus history
+    FocusBack,
+    /// One step forward along the focus history (after going back)
+    FocusForward,
+}
+
+impl CycleCommand {
+    /// What the binding does while Shift is held: cycling and focus history
+    /// navigation reverse (Shift+Tab), other commands ignore Shift
+    pub fn shifted(&self) -> Option<Self> {
+        match self {
+            CycleCommand::Forward => Some(CycleCommand::Backward),
+            CycleCommand::Backward => Some(CycleCommand::Forward),
+            CycleCommand::FocusBack => Some(CycleCommand::FocusForward),
+            CycleCommand::FocusForward => Some(CycleCommand::FocusBack),
+            _ => None,
+        }
+    }
+}
+
+/// Modifiers a key combo needs held, either side counts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    /// Whether all of them are held in a device's key state
+    fn held(&self, keys: &evdev::AttributeSetRef<KeyCode>) -> bool {
+        let either = |left: KeyCode, right: KeyCode| keys.contains(left) || keys.contains(right);
+        (!self.ctrl || either(KeyCode::KEY_LEFTCTRL, KeyCode::KEY_RIGHTCTRL))
+            && (!self.shift || either(KeyCode::KEY_LEFTSHIFT, KeyCode::KEY_RIGHTSHIFT))
+            && (!self.alt || either(KeyCode::KEY_LEFTALT, KeyCode::KEY_RIGHTALT))
+            && (!self.meta || either(KeyCode::KEY_LEFTMETA, KeyCode::KEY_RIGHTMETA))
+    }
+}
+
+/// Hotkeys shared with the listener threads: evdev key code → command
+/// (per-character focus keys and the privacy toggle)
+pub type CharacterHotkeys = Arc<Mutex<HashMap<u16, CycleCommand>>>;
+
+/// Parse an evdev key name, with or without the KEY_ prefix ("F1", "KEY_KP1"),
+/// or an X keysym name for keypad, media and left/right modifier keys ("KP_1", "XF86AudioPlay", "Control_R")
+pub fn parse_key_name(name: &str) -> Option<u16> {
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let name = match input::KEYSYM_ALIASES.iter().find(|(keysym, _)| keysym.eq_ignore_ascii_case(name)) {
+        Some((_, evdev)) => evdev.to_string(),
+        None => name.to_ascii_uppercase(),
+    };
+    // Keypad digits as keysyms: KP_1 → KP1
+    let name = match name.strip_prefix("KP_") {
+        Some(digit) if digit.len() == 1 && digit.chars().all(|c| c.is_ascii_digit()) => format!("KP{}", digit),
+        _ => name,
+    };
+    let name = if name.starts_with("KEY_") { name } else { format!("KEY_{}", name) };
+    KeyCode::from_str(&name).ok().map(|key| key.code())
+}
+
+/// evdev name of a key code without the KEY_ prefix ("F1", "KP1", "RIGHTCTRL")
+pub fn key_name(code: u16) -> String {
+    let name = format!("{:?}", KeyCode(code));
+    name.strip_prefix("KEY_").map(str::to_string).unwrap_or(name)
+}
+
+/// X keysym name of a key code, for the keys that are spelled differently there
+pub fn keysym_name(code: u16) -> Option<&'static str> {
+    let name = key_name(code);
+    input::KEYSYM_ALIASES.iter()
+        .find(|(_, evdev)| *evdev == name)
+        .map(|(keysym, _)| *keysym)
+}
+
+/// "Press a key to bind": waits for the next key pressed on any keyboard device
+/// Reading evdev gets the exact key (keypad, media keys, left vs right modifiers),
+/// which the GUI toolkit's own key events don't tell apart
+pub struct KeyCapture {
+    pressed: mpsc::Receiver<u16>,
+}
+
+impl KeyCapture {
+    /// None without access to /dev/input
+    pub fn start() -> Option<Self> {
+        let devices = find_all_keyboard_devices()
+            .inspect_err(|e| debug!(error = %e, "Key capture unavailable"))
+            .ok()?;
+        let (sender, pressed) = mpsc::channel();
+        for mut device in devices {
+            let sender = sender.clone();
+            // Threads on the other devices end with their next key press (the receiver is gone by then)
+            thread::spawn(move || {
+                while let Ok(events) = device.fetch_events() {
+                    let press = events
+                        .filter(|event| event.event_type() == EventType::KEY && event.value() == input::KEY_PRESS)
+                        .map(|event| event.code())
+                        .next();
+                    if let Some(code) = press {
+                        sender.send(code).ok();
+                        return;
+                    }
+                }
+            });
+        }
+        Some(Self { pressed })
+    }
+
+    /// The captured key code, once one was pressed
+    pub fn poll(&self) -> Option<u16> {
+        self.pressed.try_recv().ok()
+    }
+}
+
+/// Build the key code → character map from per-character hotkey settings
+/// Tab is reserved for cycling; unparseable names are skipped with a warning
+pub fn character_bindings<'a>(
+    characters: impl IntoIterator<Item = (&'a String, &'a CharacterSettings)>,
+) -> HashMap<u16, String> {
+    let mut bindings = HashMap::new();
+    for (character, settings) in characters {
+        let Some(name) = settings.hotkey.as_deref().filter(|name| !name.trim().is_empty()) else {
+            continue;
+        };
+        match parse_key_name(name) {
+            Some(input::KEY_TAB) => warn!(character = %character, "Tab is reserved for cycling, ignoring character hotkey"),
+            Some(code) => {
+                if let Some(previous) = bindings.insert(code, character.clone()) {
+                    warn!(key = %name, character = %character, previous = %previous, "Hotkey assigned to several characters");
+                }
+            }
+            None => warn!(key = %name, character = %character, "Unknown hotkey name"),
+        }
+    }
+    bindings
+}
+
+/// All hotkey bindings: per-character focus keys plus the privacy mode toggle
+/// A character bound to the privacy key loses it
+pub fn hotkey_bindings<'a>(
+    characters: impl IntoIterator<Item = (&'a String, &'a CharacterSettings)>,
+    privacy_hotkey: Option<&str>,
+) -> HashMap<u16, CycleCommand> {
+    let mut bindings: HashMap<u16, CycleCommand> = character_bindings(characters)
+        .into_iter()
+        .map(|(code, character)| (code, CycleCommand::Focus(character)))
+        .collect();
+    let Some(name) = privacy_hotkey.filter(|name| !name.trim().is_empty()) else {
+        return bindings;
+    };
+    match parse_key_name(name) {
+        Some(input::KEY_TAB) => warn!("Tab is reserved for cycling, ignoring privacy hotkey"),
+        Some(code) => {
+            if let Some(CycleCommand::Focus(character)) = bindings.insert(code, CycleCommand::TogglePrivacy) {
+                warn!(key = %name, character = %character, "Privacy hotkey is also a character hotkey, using it for privacy mode");
+            }
+        }
+        None => warn!(key = %name, "Unknown privacy hotkey name"),
+    }
+    bindings
+}
+
+/// Parse a key with optional modifiers ("Ctrl+Space", "Alt+Shift+KP_0", "F13")
+pub fn parse_key_combo(combo: &str) -> Option<(Modifiers, u16)> {
+    let mut parts: Vec<&str> = combo.split('+').map(str::trim).collect();
+    let key = parse_key_name(parts.pop()?)?;
+    let mut modifiers = Modifiers::default();
+    for part in parts {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => modifiers.ctrl = true,
+            "shift" => modifiers.shift = true,
+            "alt" => modifiers.alt = true,
+            "super" | "meta" | "win" => modifiers.meta = true,
+            _ => return None,
+        }
+    }
+    Some((modifiers, key))
+}
+
+/// Add a single-key hotkey for `command` (focus last, back, forward); it wins over a character key on the same key
+pub fn bind_key(bindings: &mut HashMap<u16, CycleCommand>, key: Option<&str>, command: CycleCommand) {
+    let Some(name) = key.filter(|name| !name.trim().is_empty()) else {
+        return;
+    };
+    match parse_key_name(name) {
+        Some(input::KEY_TAB) => warn!(command = ?command, "Tab is reserved for cycling, ignoring hotkey"),
+        Some(code) => {
+            if let Some(previous) = bindings.insert(code, command.clone()) {
+                warn!(key = %name, command = ?command, previous = ?previous, "Hotkey is also bound to another action, using it for this one");
+            }
+        }
+        None => warn!(key = %name, command = ?command, "Unknown hotkey name"),
+    }
+}
+
+/// Add the chord leader to the bindings; it wins over a character key on the same key
+pub fn bind_leader(bindings: &mut HashMap<u16, CycleCommand>, leader: Option<&str>) {
+    let Some(combo) = leader.filter(|combo| !combo.trim().is_empty()) else {
+        return;
+    };
+    match parse_key_combo(combo) {
+        Some((_, input::KEY_TAB)) => warn!("Tab is reserved for cycling, ignoring chord leader"),
+        Some((modifiers, code)) => {
+            if let Some(previous) = bindings.insert(code, CycleCommand::Leader(modifiers)) {
+                warn!(leader = %combo, previous = ?previous, "Chord leader key is also bound to another hotkey, using it for the chord");
+            }
+        }
+        None => warn!(leader = %combo, "Unknown chord leader"),
+    }
 }
 
 /// Find all keyboard devices that support Tab key
@@ -52,16 +277,19 @@ fn find_all_keyboard_devices() -> Result<Vec<Device>> {
     Ok(devices)
 }
 
-/// Spawn background threads to listen for Tab/Shift+Tab on all keyboard devices
-pub fn spawn_listener(sender: Sender<CycleCommand>) -> Result<Vec<thread::JoinHandle<()>>> {
+/// Spawn background threads to listen for Tab/Shift+Tab and per-character hotkeys on all keyboard devices
+/// Once `stop` is set the threads exit at their next key event without forwarding it
+pub fn spawn_listener(sender: Sender<CycleCommand>, bindings: CharacterHotkeys, stop: Arc<AtomicBool>) -> Result<Vec<thread::JoinHandle<()>>> {
     let devices = find_all_keyboard_devices()?;
     let mut handles = Vec::new();
 
     for device in devices {
         let sender = sender.clone();
+        let bindings = bindings.clone();
+        let stop = stop.clone();
         let handle = thread::spawn(move || {
             info!(device = ?device.name(), "Hotkey listener started");
-            if let Err(e) = listen_for_hotkeys(device, sender) {
+            if let Err(e) = listen_for_hotkeys(device, sender, &bindings, &stop) {
                 error!(error = %e, "Hotkey listener error");
             }
         });
@@ -71,16 +299,20 @@ pub fn spawn_listener(sender: Sender<CycleCommand>) -> Result<Vec<thread::JoinHa
     Ok(handles)
 }
 
-/// Listen for Tab/Shift+Tab events on a single device
-fn listen_for_hotkeys(mut device: Device, sender: Sender<CycleCommand>) -> Result<()> {
+/// Listen for Tab/Shift+Tab and per-character hotkey events on a single device
+fn listen_for_hotkeys(mut device: Device, sender: Sender<CycleCommand>, bindings: &CharacterHotkeys, stop: &AtomicBool) -> Result<()> {
     loop {
         // Fetch events (blocks until available)
         let events = device.fetch_events()
             .context("Failed to fetch events")?;
+        if stop.load(Ordering::Relaxed) {
+            info!("Hotkey listener stopped");
+            return Ok(());
+        }
 
-        // Collect Tab press events that need processing
+        // Collect the commands of pressed keys
         // We need to finish with the events iterator before querying key state
-        let mut tab_presses = Vec::new();
+        let mut commands = Vec::new();
 
         for event in events {
             // Only care about key events
@@ -96,30 +328,35 @@ fn listen_for_hotkeys(mut device: Device, sender: Sender<CycleCommand>) -> Resul
             debug!(key_code = key_code, value = event.value(), "Key event");
 
             if key_code == input::KEY_TAB && pressed {
-                tab_presses.push(());
+                // Tab is the built-in cycle binding
+                commands.push(CycleCommand::Forward);
+            } else if pressed && let Some(command) = bindings.lock().unwrap().get(&key_code) {
+                commands.push(command.clone());
             }
         }
 
-        // Now process Tab presses with current keyboard state
-        for _ in tab_presses {
-            // Check real-time state of shift keys when Tab was pressed
-            // This avoids race conditions from batched events
-            let key_state = device.get_key_state()
-                .context("Failed to get keyboard state")?;
-            
-            let shift_pressed = key_state.contains(KeyCode(input::KEY_LEFTSHIFT)) 
-                || key_state.contains(KeyCode(input::KEY_RIGHTSHIFT));
-
-            let command = if shift_pressed {
-                CycleCommand::Backward
-            } else {
-                CycleCommand::Forward
-            };
-
-            info!(shift = shift_pressed, command = ?command, "Tab hotkey pressed, sending command");
-
+        for mut command in commands {
+            if let CycleCommand::Leader(modifiers) = command {
+                let key_state = device.get_key_state()
+                    .context("Failed to get keyboard state")?;
+                if !modifiers.held(&key_state) {
+                    continue;
+                }
+            }
+            if let Some(shifted) = command.shifted() {
+                // Check real-time state of shift keys when the key was pressed
+                // This avoids race conditions from batched events
+                let key_state = device.get_key_state()
+                    .context("Failed to get keyboard state")?;
+                let shift_pressed = key_state.contains(KeyCode(input::KEY_LEFTSHIFT))
+                    || key_state.contains(KeyCode(input::KEY_RIGHTSHIFT));
+                if shift_pressed {
+                    command = shifted;
+                }
+            }
+            info!(command = ?command, "Hotkey pressed, sending command");
             sender.send(command)
-                .context("Failed to send cycle command")?;
+                .context("Failed to send hotkey command")?;
         }
     }
 }
@@ -137,3 +374,107 @@ pub fn print_permission_error() {
     error!("  Then log out and back in");
     warn!(continuing = true, "Continuing without hotkey support...");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_name() {
+        assert_eq!(parse_key_name("F1"), Some(KeyCode::KEY_F1.code()));
+        assert_eq!(parse_key_name("key_kp1"), Some(KeyCode::KEY_KP1.code()));
+        assert_eq!(parse_key_name(""), None);
+        assert_eq!(parse_key_name("NotAKey"), None);
+    }
+
+    #[test]
+    fn test_keysym_names() {
+        assert_eq!(parse_key_name("KP_1"), Some(KeyCode::KEY_KP1.code()));
+        assert_eq!(parse_key_name("kp_enter"), Some(KeyCode::KEY_KPENTER.code()));
+        assert_eq!(parse_key_name("XF86AudioPlay"), Some(KeyCode::KEY_PLAYPAUSE.code()));
+        assert_eq!(parse_key_name("Control_R"), Some(KeyCode::KEY_RIGHTCTRL.code()));
+        assert_ne!(parse_key_name("Control_R"), parse_key_name("Control_L"));
+        assert_eq!(key_name(KeyCode::KEY_RIGHTCTRL.code()), "RIGHTCTRL");
+        assert_eq!(keysym_name(KeyCode::KEY_VOLUMEUP.code()), Some("XF86AudioRaiseVolume"));
+        assert_eq!(keysym_name(KeyCode::KEY_F1.code()), None);
+    }
+
+    #[test]
+    fn test_character_bindings_skip_tab_and_unknown() {
+        let mut fc = CharacterSettings::new(0, 0, 240, 135);
+        fc.hotkey = Some("F1".to_string());
+        let mut tab = CharacterSettings::new(0, 0, 240, 135);
+        tab.hotkey = Some("Tab".to_string());
+        let mut typo = CharacterSettings::new(0, 0, 240, 135);
+        typo.hotkey = Some("F99".to_string());
+        let characters = HashMap::from([
+            ("FC".to_string(), fc),
+            ("Tab".to_string(), tab),
+            ("Typo".to_string(), typo),
+        ]);
+
+        let bindings = character_bindings(&characters);
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings.get(&KeyCode::KEY_F1.code()).map(String::as_str), Some("FC"));
+    }
+
+    #[test]
+    fn test_hotkey_bindings_privacy_key() {
+        let mut fc = CharacterSettings::new(0, 0, 240, 135);
+        fc.hotkey = Some("F1".to_string());
+        let mut scout = CharacterSettings::new(0, 0, 240, 135);
+        scout.hotkey = Some("F12".to_string());
+        let characters = HashMap::from([("FC".to_string(), fc), ("Scout".to_string(), scout)]);
+
+        let bindings = hotkey_bindings(&characters, Some("F12"));
+        assert_eq!(bindings.get(&KeyCode::KEY_F1.code()), Some(&CycleCommand::Focus("FC".to_string())));
+        assert_eq!(bindings.get(&KeyCode::KEY_F12.code()), Some(&CycleCommand::TogglePrivacy));
+        assert_eq!(hotkey_bindings(&characters, Some("Tab")).len(), 2);
+    }
+
+    #[test]
+    fn test_shift_reverses_cycling_only() {
+        assert_eq!(CycleCommand::Forward.shifted(), Some(CycleCommand::Backward));
+        assert_eq!(CycleCommand::Backward.shifted(), Some(CycleCommand::Forward));
+        assert_eq!(CycleCommand::Focus("FC".to_string()).shifted(), None);
+        assert_eq!(CycleCommand::TogglePrivacy.shifted(), None);
+    }
+
+    #[test]
+    fn test_bind_key_wins_over_character_key() {
+        let mut bindings = HashMap::from([(KeyCode::KEY_F11.code(), CycleCommand::Focus("FC".to_string()))]);
+        bind_key(&mut bindings, Some("F11"), CycleCommand::FocusLast);
+        assert_eq!(bindings.get(&KeyCode::KEY_F11.code()), Some(&CycleCommand::FocusLast));
+        bind_key(&mut bindings, Some("Tab"), CycleCommand::FocusLast);
+        bind_key(&mut bindings, Some(" "), CycleCommand::FocusLast);
+        assert_eq!(bindings.len(), 1);
+    }
+
+    #[test]
+    fn test_leader_combo() {
+        let ctrl = Modifiers { ctrl: true, ..Modifiers::default() };
+        assert_eq!(parse_key_combo("Ctrl+Space"), Some((ctrl, KeyCode::KEY_SPACE.code())));
+        assert_eq!(parse_key_combo("F13"), Some((Modifiers::default(), KeyCode::KEY_F13.code())));
+        assert_eq!(parse_key_combo("Hyper+Space"), None);
+        let mut bindings = HashMap::from([(KeyCode::KEY_SPACE.code(), CycleCommand::Focus("FC".to_string()))]);
+        bind_leader(&mut bindings, Some("ctrl + space"));
+        assert_eq!(bindings.get(&KeyCode::KEY_SPACE.code()), Some(&CycleCommand::Leader(ctrl)));
+        bind_leader(&mut bindings, Some("Shift+Tab"));
+        assert_eq!(bindings.len(), 1);
+    }
+
+    #[test]
+    fn test_profile_privacy_hotkey_overrides_global() {
+        use crate::config::profile::{GlobalSettings, Profile};
+
+        let global = GlobalSettings { privacy_hotkey: Some("F12".to_string()), ..GlobalSettings::default() };
+        let mut profile = Profile::empty();
+        assert_eq!(profile.effective_privacy_hotkey(&global), Some("F12"));
+        profile.privacy_hotkey = Some(" ".to_string());
+        assert_eq!(profile.effective_privacy_hotkey(&global), Some("F12"));
+        profile.privacy_hotkey = Some("KP0".to_string());
+        let bindings = hotkey_bindings(&profile.character_positions, profile.effective_privacy_hotkey(&global));
+        assert_eq!(bindings.get(&KeyCode::KEY_KP0.code()), Some(&CycleCommand::TogglePrivacy));
+        assert!(!bindings.contains_key(&KeyCode::KEY_F12.code()));
+    }
+}
@@ -0,0 +1,187 @@
+//! Write-ahead journal of layout changes received over IPC
+//!
+//! Position and per-character updates from the previews (drags, group moves,
+//! panel and redaction edits) are applied to the GUI's copy of the config and
+//! only written once they settle. Each one is appended to
+//! `$XDG_STATE_HOME/eve-l-preview/ipc-journal.jsonl` before it is applied, so
+//! a crash halfway through a batch still leaves a record: on the next start
+//! the GUI replays the journal onto the loaded config and saves it. A
+//! successful config save empties the journal.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::profile::{Config, Profile};
+use crate::constants::{config, journal};
+use crate::permissions;
+use crate::types::CharacterSettings;
+
+use super::PreviewResponse;
+
+/// One journaled change and the profile it was applied to
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    profile: String,
+    change: PreviewResponse,
+}
+
+/// Whether an IPC event changes the saved layout (and so is journaled)
+pub fn is_layout_change(event: &PreviewResponse) -> bool {
+    matches!(
+        event,
+        PreviewResponse::PositionChanged { .. }
+            | PreviewResponse::CharacterAdded { .. }
+            | PreviewResponse::PanelDetachedChanged { .. }
+            | PreviewResponse::RedactionsChanged { .. }
+    )
+}
+
+/// Apply a layout change to a profile (live and on replay)
+pub fn apply(profile: &mut Profile, event: &PreviewResponse) {
+    match event {
+        PreviewResponse::PositionChanged { character, x, y, width, height }
+        | PreviewResponse::CharacterAdded { character, x, y, width, height } => {
+            profile.character_positions.entry(character.clone())
+                .and_modify(|settings| settings.set_geometry(*x, *y, *width, *height))
+                .or_insert_with(|| CharacterSettings::new(*x, *y, *width, *height));
+        }
+        PreviewResponse::PanelDetachedChanged { character, detached } => {
+            if let Some(settings) = profile.character_positions.get_mut(character) {
+                settings.panel_detached = *detached;
+            }
+        }
+        PreviewResponse::RedactionsChanged { character, redactions } => {
+            if let Some(settings) = profile.character_positions.get_mut(character) {
+                settings.redactions = redactions.clone();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Where the journal is kept (XDG state dir, local data dir as fallback)
+fn path() -> PathBuf {
+    let mut path = dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."));
+    path.push(config::APP_DIR);
+    path.push(journal::FILENAME);
+    path
+}
+
+/// Append-only journal file, opened on the first change
+#[derive(Debug, Default)]
+pub struct Journal {
+    file: Option<File>,
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a change made to `profile` and flush it to disk, before it is applied
+    pub fn record(&mut self, profile: &str, event: &PreviewResponse) -> Result<()> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => {
+                let path = path();
+                if let Some(parent) = path.parent() {
+                    permissions::create_private_dir(parent)
+                        .with_context(|| format!("Failed to create state directory {:?}", parent))?;
+                }
+                let file = permissions::append_private(&path)
+                    .with_context(|| format!("Failed to open IPC journal {:?}", path))?;
+                self.file.insert(file)
+            }
+        };
+        let entry = Entry { profile: profile.to_string(), change: event.clone() };
+        let mut line = serde_json::to_string(&entry)
+            .context("Failed to serialize journal entry")?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .context("Failed to append to IPC journal")?;
+        file.sync_data()
+            .context("Failed to flush IPC journal")?;
+        Ok(())
+    }
+
+    /// Forget the journaled changes once the config holding them was saved
+    pub fn clear(&mut self) -> Result<()> {
+        self.file = None;
+        let path = path();
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove IPC journal {:?}", path))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Apply journal contents to `config`, returning how many changes were replayed
+/// A torn last line (crash mid-write) and changes to deleted profiles are skipped
+pub fn replay_into(contents: &str, config: &mut Config) -> usize {
+    let mut replayed = 0;
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let entry: Entry = match serde_json::from_str(line) {
+            Ok(entry) => entry,
+            Err(e) => {
+                warn!(error = %e, "Skipping unreadable IPC journal entry");
+                continue;
+            }
+        };
+        if let Some(profile) = config.profiles.iter_mut().find(|profile| profile.name == entry.profile) {
+            apply(profile, &entry.change);
+            replayed += 1;
+        }
+    }
+    replayed
+}
+
+/// Replay changes left over from a run that ended before saving them
+pub fn recover(config: &mut Config) -> Result<usize> {
+    let path = path();
+    if !path.exists() {
+        return Ok(0);
+    }
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read IPC journal {:?}", path))?;
+    Ok(replay_into(&contents, config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(profile: &str, change: PreviewResponse) -> String {
+        serde_json::to_string(&Entry { profile: profile.to_string(), change }).unwrap()
+    }
+
+    #[test]
+    fn test_replay_applies_changes_in_order() {
+        let mut config = Config::default();
+        let profile = config.profiles[0].name.clone();
+        let contents = [
+            line(&profile, PreviewResponse::CharacterAdded { character: "Main".to_string(), x: 0, y: 0, width: 240, height: 135 }),
+            line(&profile, PreviewResponse::PositionChanged { character: "Main".to_string(), x: 100, y: 50, width: 240, height: 135 }),
+            line("Deleted profile", PreviewResponse::PositionChanged { character: "Main".to_string(), x: 9, y: 9, width: 9, height: 9 }),
+        ].join("\n");
+        assert_eq!(replay_into(&contents, &mut config), 2);
+        assert_eq!(config.profiles[0].character_positions["Main"].position(), crate::types::Position::new(100, 50));
+    }
+
+    #[test]
+    fn test_replay_skips_torn_last_line() {
+        let mut config = Config::default();
+        let profile = config.profiles[0].name.clone();
+        let full = line(&profile, PreviewResponse::PositionChanged { character: "Main".to_string(), x: 1, y: 2, width: 3, height: 4 });
+        let contents = format!("{}\n{}", full, &full[..full.len() / 2]);
+        assert_eq!(replay_into(&contents, &mut config), 1);
+    }
+}
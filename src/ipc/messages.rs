@@ -4,7 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::config::profile::{GlobalSettings, Profile};
-use crate::types::CharacterSettings;
+use crate::hotkey_conflicts::HotkeyConflict;
+use crate::types::{CharacterSettings, RedactRect};
 
 /// Requests sent from GUI to Preview process
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -16,6 +17,33 @@ pub enum PreviewRequest {
         global: GlobalSettings,
     },
     
+    /// Replace one character's settings (GUI character editor), applied live
+    /// No response is sent; the GUI keeps its own copy for saving
+    UpdateCharacter {
+        character: String,
+        settings: CharacterSettings,
+    },
+    
+    /// Terminate a character's client (SIGTERM, then SIGKILL), optionally running its launch_command after
+    TerminateClient {
+        character: String,
+        relaunch: bool,
+    },
+    
+    /// Turn privacy mode (pixelated previews) on or off (tray menu)
+    TogglePrivacy,
+    
+    /// Activate a character's client as its hotkey would (GUI command palette)
+    /// Works while the manager has focus, whatever hotkey_require_eve_focus says
+    FocusCharacter {
+        character: String,
+    },
+    
+    /// Put a character's preview in redaction edit mode (None = leave edit mode)
+    EditRedactions {
+        character: Option<String>,
+    },
+    
     /// Query current character positions
     GetPositions,
     
@@ -56,6 +84,37 @@ pub enum PreviewResponse {
     /// Character window closed/logged out
     CharacterRemoved(String),
     
+    /// Characters that already have a client running (sent once when the GUI connects)
+    LiveCharacters(Vec<String>),
+    
+    /// Two clients claim the same character; the older window was relabelled
+    DuplicateCharacter(String),
+    
+    /// Character's preview was dragged out of (true) or back into (false) the panel
+    PanelDetachedChanged {
+        character: String,
+        detached: bool,
+    },
+    
+    /// Redacted areas drawn or removed on a character's preview (edit mode)
+    RedactionsChanged {
+        character: String,
+        redactions: Vec<RedactRect>,
+    },
+    
+    /// Privacy mode was turned on or off (also sent when the GUI connects)
+    PrivacyChanged(bool),
+    
+    /// Hotkeys another X client has grabbed (sent after settings arrive, empty once resolved)
+    HotkeyConflicts(Vec<HotkeyConflict>),
+    
+    /// Monitor setup fingerprint, sent when the GUI connects and whenever the setup changes
+    /// Thumbnails moved to the new setup's saved positions follow as PositionChanged
+    MonitorSetupChanged {
+        previous: Option<String>,
+        current: String,
+    },
+    
     /// Acknowledgment that request was processed
     Ready,
     
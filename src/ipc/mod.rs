@@ -9,6 +9,7 @@ use std::io::{Read, Write};
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::{Path, PathBuf};
 
+pub mod journal;
 mod messages;
 pub use messages::{PreviewRequest, PreviewResponse};
 
@@ -79,12 +80,6 @@ impl PreviewClient {
         self.stream.set_nonblocking(false)?;
         result
     }
-    
-    /// Send request and wait for response (convenience method)
-    pub fn request(&mut self, req: PreviewRequest) -> Result<PreviewResponse> {
-        self.send_request(&req)?;
-        self.recv_response()
-    }
 }
 
 /// Server listener for Preview process
@@ -102,9 +97,9 @@ impl PreviewServer {
     
     /// Create server and bind to specific socket path
     pub fn bind_to(socket_path: PathBuf) -> Result<Self> {
-        // Create directory if needed
+        // Create directory if needed, owner-only so nobody else can reach the socket
         if let Some(parent) = socket_path.parent() {
-            std::fs::create_dir_all(parent)
+            crate::permissions::create_private_dir(parent)
                 .context(format!("Failed to create socket directory: {}", parent.display()))?;
         }
         
@@ -117,11 +112,11 @@ impl PreviewServer {
         let listener = UnixListener::bind(&socket_path)
             .context(format!("Failed to bind socket at {}", socket_path.display()))?;
         
-        // Set permissions to 0700 (owner only)
+        // Set permissions to 0600 (owner only)
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
-            std::fs::set_permissions(&socket_path, std::fs::Permissions::from_mode(0o700))
+            std::fs::set_permissions(&socket_path, std::fs::Permissions::from_mode(crate::constants::permissions::PRIVATE_FILE_MODE))
                 .context("Failed to set socket permissions")?;
         }
         
@@ -132,10 +127,17 @@ impl PreviewServer {
     }
     
     /// Accept incoming connection (blocking)
+    /// Connections from processes of other users are dropped and waited past
     pub fn accept(&self) -> Result<PreviewClient> {
-        let (stream, _addr) = self.listener.accept()
-            .context("Failed to accept IPC connection")?;
-        Ok(PreviewClient { stream })
+        loop {
+            let (stream, _addr) = self.listener.accept()
+                .context("Failed to accept IPC connection")?;
+            match crate::permissions::peer_is_same_user(&stream) {
+                Ok(true) => return Ok(PreviewClient { stream }),
+                Ok(false) => tracing::warn!("Rejected IPC connection from a process of another user"),
+                Err(e) => tracing::warn!(error = ?e, "Rejected IPC connection with unknown peer"),
+            }
+        }
     }
     
     /// Get socket path
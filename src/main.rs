@@ -3,15 +3,24 @@
 mod color;
 mod config;
 mod constants;
+mod doctor;
 mod gui;
+mod hotkey_conflicts;
 mod hotkeys;
 mod ipc;
+mod mqtt;
+mod permissions;
+mod picom;
+mod portal_shortcuts;
 mod preview;
+mod session_summary;
 mod types;
+mod usage_stats;
+mod x11_trace;
 mod x11_utils;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use tracing::Level as TraceLevel;
 use tracing_subscriber::FmtSubscriber;
 
@@ -19,12 +28,55 @@ use tracing_subscriber::FmtSubscriber;
 #[command(name = "eve-l-preview")]
 #[command(about = "EVE Online window preview manager", long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Run in preview daemon mode (background process showing thumbnails)
     #[arg(long)]
     preview: bool,
+
+    /// Log the thumbnails the preview daemon would create, without creating any windows
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Log X requests, replies and events for thumbnails; optionally only for one client window id (e.g. 0x3c00007)
+    #[arg(long, value_name = "WINDOW", num_args = 0..=1, default_missing_value = "all", value_parser = x11_trace::TraceFilter::parse)]
+    trace_x11: Option<x11_trace::TraceFilter>,
+
+    /// Print a JSON schema of every config field (type, default, range, description) and exit
+    #[arg(long)]
+    dump_config_schema: bool,
+
+    /// Override a setting for this run without touching the config (e.g. opacity_percent=40, night_mode.enabled=true); repeatable
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = config::overrides::parse_cli)]
+    set: Vec<config::overrides::Override>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check X extensions, compositor, window manager, wine and the config, with hints for anything missing
+    Doctor,
+    /// Print recommended picom rules for the thumbnail and client windows
+    PicomRules {
+        /// Use the `rules` block of picom 12 and later
+        #[arg(long)]
+        v12: bool,
+    },
+    /// Rewrite the config file as TOML or JSON (the old file is kept as .bak)
+    ConvertConfig {
+        #[arg(value_parser = config::format::ConfigFormat::parse_name)]
+        format: config::format::ConfigFormat,
+    },
 }
 
 fn main() -> Result<()> {
+    let cli = Cli::parse();
+    if cli.dump_config_schema {
+        // Before logging starts, so nothing but the schema reaches stdout
+        println!("{}", serde_json::to_string_pretty(&config::schema::config_schema())?);
+        return Ok(());
+    }
+
     // Initialize logging
     let subscriber = FmtSubscriber::builder()
         .with_max_level(TraceLevel::INFO)
@@ -32,9 +84,27 @@ fn main() -> Result<()> {
     tracing::subscriber::set_global_default(subscriber)
         .expect("Failed to set tracing subscriber");
 
-    let cli = Cli::parse();
+    if let Some(filter) = cli.trace_x11 {
+        x11_trace::enable(filter);
+    }
+    config::overrides::set_cli(cli.set);
 
-    if cli.preview {
+    if let Some(command) = cli.command {
+        match command {
+            Command::Doctor => doctor::run(),
+            Command::PicomRules { v12 } => {
+                print!("{}", picom::rules(v12));
+                Ok(())
+            }
+            Command::ConvertConfig { format } => {
+                let path = config::format::convert(format)?;
+                println!("Config written to {}", path.display());
+                Ok(())
+            }
+        }
+    } else if cli.dry_run {
+        preview::run_dry_run()
+    } else if cli.preview {
         // Run preview daemon (background process showing thumbnails)
         preview::run_preview_daemon()
     } else {
@@ -0,0 +1,322 @@
+//! MQTT publisher for focus/character events
+//!
+//! Minimal MQTT 3.1.1 client (QoS 0 PUBLISH only) running on a background thread.
+//! Home-automation setups can subscribe to per-character topics:
+//!
+//! ```text
+//! <prefix>/active_character        → name of the focused character (retained)
+//! <prefix>/<character>/focused     → "true" / "false" (retained)
+//! <prefix>/<character>/online      → "true" / "false" (retained)
+//! ```
+
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::config::profile::MqttSettings;
+use crate::constants::mqtt;
+
+/// Daemon events that get published to the broker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MqttEvent {
+    /// Character's client window gained input focus
+    FocusGained(String),
+    /// Character's client window lost input focus
+    FocusLost(String),
+    /// Character logged in (or its window appeared)
+    CharacterOnline(String),
+    /// Character logged out or its window was destroyed
+    CharacterOffline(String),
+}
+
+/// Handle for queueing events to the publisher thread
+pub struct MqttPublisher {
+    tx: Sender<(MqttSettings, MqttEvent)>,
+}
+
+impl MqttPublisher {
+    /// Spawn the publisher thread
+    /// The broker connection is opened lazily on the first event while enabled
+    pub fn spawn() -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || run_publisher(rx));
+        Self { tx }
+    }
+
+    /// Queue an event for publishing (no-op when MQTT is disabled)
+    pub fn publish(&self, settings: &MqttSettings, event: MqttEvent) {
+        if !settings.enabled {
+            return;
+        }
+        if self.tx.send((settings.clone(), event)).is_err() {
+            warn!("MQTT publisher thread is gone, dropping event");
+        }
+    }
+}
+
+/// Open broker session, remembering which settings it was created with
+struct Session {
+    settings: MqttSettings,
+    stream: TcpStream,
+}
+
+fn run_publisher(rx: Receiver<(MqttSettings, MqttEvent)>) {
+    let mut session: Option<Session> = None;
+    let mut last_failure: Option<Instant> = None;
+
+    for (settings, event) in rx {
+        // Reconnect when settings changed (profile/global update via IPC)
+        if session.as_ref().is_some_and(|s| s.settings != settings)
+            && let Some(mut old) = session.take()
+        {
+            let _ = old.stream.write_all(&encode_disconnect());
+        }
+
+        if session.is_none() {
+            // Don't hammer an unreachable broker on every focus change
+            if last_failure.is_some_and(|t| t.elapsed() < Duration::from_millis(mqtt::RECONNECT_DELAY_MS)) {
+                debug!(event = ?event, "MQTT broker unavailable, dropping event");
+                continue;
+            }
+            match connect(&settings) {
+                Ok(stream) => {
+                    info!(broker = %settings.broker_url, "Connected to MQTT broker");
+                    session = Some(Session { settings: settings.clone(), stream });
+                    last_failure = None;
+                }
+                Err(e) => {
+                    warn!(broker = %settings.broker_url, error = ?e, "Failed to connect to MQTT broker");
+                    last_failure = Some(Instant::now());
+                    continue;
+                }
+            }
+        }
+
+        let Some(active) = session.as_mut() else { continue };
+        for (topic, payload) in event_messages(&settings.topic_prefix, &event) {
+            if let Err(e) = active.stream.write_all(&encode_publish(&topic, payload.as_bytes(), true)) {
+                warn!(topic = %topic, error = %e, "Failed to publish MQTT message, will reconnect");
+                session = None;
+                break;
+            }
+            debug!(topic = %topic, payload = %payload, "Published MQTT message");
+        }
+    }
+}
+
+/// Open TCP connection and perform the CONNECT/CONNACK handshake
+fn connect(settings: &MqttSettings) -> Result<TcpStream> {
+    let (host, port) = parse_broker_url(&settings.broker_url)?;
+    let timeout = Duration::from_millis(mqtt::CONNECT_TIMEOUT_MS);
+    let addr = (host.as_str(), port)
+        .to_socket_addrs()
+        .context(format!("Failed to resolve MQTT broker host '{}'", host))?
+        .next()
+        .context(format!("No addresses found for MQTT broker host '{}'", host))?;
+
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)
+        .context(format!("Failed to connect to MQTT broker at {}", addr))?;
+    stream.set_read_timeout(Some(timeout))
+        .context("Failed to set MQTT read timeout")?;
+    // A broker that stops reading must not block the publisher: the write fails and the session reconnects
+    stream.set_write_timeout(Some(timeout))
+        .context("Failed to set MQTT write timeout")?;
+
+    stream.write_all(&encode_connect(
+        &settings.client_id,
+        settings.username.as_deref(),
+        settings.password.as_deref(),
+    ))
+    .context("Failed to send MQTT CONNECT")?;
+
+    let mut connack = [0u8; 4];
+    stream.read_exact(&mut connack)
+        .context("Failed to read MQTT CONNACK")?;
+    if connack[0] != 0x20 || connack[3] != 0 {
+        anyhow::bail!("MQTT broker refused connection (return code {})", connack[3]);
+    }
+    Ok(stream)
+}
+
+/// Topics and payloads published for a daemon event
+fn event_messages(prefix: &str, event: &MqttEvent) -> Vec<(String, String)> {
+    let topic = |name: &str, leaf: &str| format!("{}/{}/{}", prefix, sanitize_topic_level(name), leaf);
+    match event {
+        MqttEvent::FocusGained(name) => vec![
+            (format!("{}/active_character", prefix), name.clone()),
+            (topic(name, "focused"), "true".to_string()),
+        ],
+        MqttEvent::FocusLost(name) => vec![(topic(name, "focused"), "false".to_string())],
+        MqttEvent::CharacterOnline(name) => vec![(topic(name, "online"), "true".to_string())],
+        MqttEvent::CharacterOffline(name) => vec![
+            (topic(name, "online"), "false".to_string()),
+            (topic(name, "focused"), "false".to_string()),
+        ],
+    }
+}
+
+/// Replace characters that have special meaning in MQTT topics
+fn sanitize_topic_level(name: &str) -> String {
+    name.chars()
+        .map(|c| if matches!(c, '/' | '+' | '#') { '_' } else { c })
+        .collect()
+}
+
+/// Parse "mqtt://host:port", "host:port" or "host" into (host, port)
+fn parse_broker_url(url: &str) -> Result<(String, u16)> {
+    if url.starts_with("mqtts://") {
+        anyhow::bail!("TLS brokers (mqtts://) are not supported, use a plain mqtt:// listener");
+    }
+    let rest = url.strip_prefix("mqtt://").or_else(|| url.strip_prefix("tcp://")).unwrap_or(url);
+    let rest = rest.trim_end_matches('/');
+    if rest.is_empty() {
+        anyhow::bail!("MQTT broker URL is empty");
+    }
+    match rest.rsplit_once(':') {
+        Some((host, port)) => {
+            let port = port.parse::<u16>()
+                .context(format!("Invalid port in MQTT broker URL '{}'", url))?;
+            Ok((host.to_string(), port))
+        }
+        None => Ok((rest.to_string(), mqtt::DEFAULT_PORT)),
+    }
+}
+
+/// Encode MQTT variable-length "remaining length" field
+fn encode_remaining_length(mut len: usize, out: &mut Vec<u8>) {
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+}
+
+/// Append length-prefixed UTF-8 string
+fn encode_string(s: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    out.extend_from_slice(s);
+}
+
+fn encode_connect(client_id: &str, username: Option<&str>, password: Option<&str>) -> Vec<u8> {
+    let mut body = Vec::new();
+    encode_string(b"MQTT", &mut body);
+    body.push(mqtt::PROTOCOL_LEVEL);
+
+    let mut flags = 0x02; // Clean session
+    if username.is_some() {
+        flags |= 0x80;
+    }
+    if username.is_some() && password.is_some() {
+        flags |= 0x40;
+    }
+    body.push(flags);
+    body.extend_from_slice(&0u16.to_be_bytes()); // Keep-alive disabled (publish-only client)
+
+    encode_string(client_id.as_bytes(), &mut body);
+    if let Some(username) = username {
+        encode_string(username.as_bytes(), &mut body);
+        if let Some(password) = password {
+            encode_string(password.as_bytes(), &mut body);
+        }
+    }
+
+    let mut packet = vec![0x10];
+    encode_remaining_length(body.len(), &mut packet);
+    packet.extend_from_slice(&body);
+    packet
+}
+
+fn encode_publish(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = Vec::new();
+    encode_string(topic.as_bytes(), &mut body);
+    body.extend_from_slice(payload);
+
+    let mut packet = vec![0x30 | retain as u8];
+    encode_remaining_length(body.len(), &mut packet);
+    packet.extend_from_slice(&body);
+    packet
+}
+
+fn encode_disconnect() -> [u8; 2] {
+    [0xE0, 0x00]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remaining_length_encoding() {
+        let encode = |len| {
+            let mut out = Vec::new();
+            encode_remaining_length(len, &mut out);
+            out
+        };
+        assert_eq!(encode(0), vec![0x00]);
+        assert_eq!(encode(127), vec![0x7F]);
+        assert_eq!(encode(128), vec![0x80, 0x01]);
+        assert_eq!(encode(16_383), vec![0xFF, 0x7F]);
+        assert_eq!(encode(16_384), vec![0x80, 0x80, 0x01]);
+    }
+
+    #[test]
+    fn test_connect_packet_without_credentials() {
+        let packet = encode_connect("id", None, None);
+        assert_eq!(
+            packet,
+            vec![0x10, 14, 0, 4, b'M', b'Q', b'T', b'T', 4, 0x02, 0, 0, 0, 2, b'i', b'd']
+        );
+    }
+
+    #[test]
+    fn test_connect_packet_with_credentials() {
+        let packet = encode_connect("id", Some("u"), Some("p"));
+        // Username + password + clean session flags
+        assert_eq!(packet[9], 0xC2);
+        assert_eq!(&packet[packet.len() - 6..], &[0, 1, b'u', 0, 1, b'p']);
+    }
+
+    #[test]
+    fn test_publish_packet_retained() {
+        let packet = encode_publish("a/b", b"true", true);
+        assert_eq!(packet, vec![0x31, 9, 0, 3, b'a', b'/', b'b', b't', b'r', b'u', b'e']);
+    }
+
+    #[test]
+    fn test_parse_broker_url() {
+        assert_eq!(parse_broker_url("mqtt://broker.lan:1884").unwrap(), ("broker.lan".to_string(), 1884));
+        assert_eq!(parse_broker_url("tcp://10.0.0.2:1883/").unwrap(), ("10.0.0.2".to_string(), 1883));
+        assert_eq!(parse_broker_url("broker.lan").unwrap(), ("broker.lan".to_string(), 1883));
+        assert!(parse_broker_url("mqtts://broker.lan:8883").is_err());
+        assert!(parse_broker_url("mqtt://broker.lan:notaport").is_err());
+        assert!(parse_broker_url("").is_err());
+    }
+
+    #[test]
+    fn test_event_messages_sanitize_topics() {
+        let messages = event_messages("evelp", &MqttEvent::FocusGained("A/B+C".to_string()));
+        assert_eq!(messages, vec![
+            ("evelp/active_character".to_string(), "A/B+C".to_string()),
+            ("evelp/A_B_C/focused".to_string(), "true".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_event_messages_offline_clears_focus() {
+        let messages = event_messages("evelp", &MqttEvent::CharacterOffline("Main".to_string()));
+        assert_eq!(messages, vec![
+            ("evelp/Main/online".to_string(), "false".to_string()),
+            ("evelp/Main/focused".to_string(), "false".to_string()),
+        ]);
+    }
+}
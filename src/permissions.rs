@@ -0,0 +1,112 @@
+//! File and socket permission hardening
+//!
+//! The config and the files the GUI keeps next to it are written owner-only
+//! (0600), the IPC socket lives in an owner-only directory and only serves
+//! processes of the same user, and the GUI refuses to start from a config that
+//! any user on the machine could have edited.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::constants::permissions::{PRIVATE_DIR_MODE, PRIVATE_FILE_MODE};
+
+/// Write `contents` to `path`, readable and writable by the owner only
+/// An existing file is tightened as well (the creation mode only applies to new files)
+pub fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(PRIVATE_FILE_MODE)
+        .open(path)
+        .with_context(|| format!("Failed to open {:?} for writing", path))?;
+    file.set_permissions(fs::Permissions::from_mode(PRIVATE_FILE_MODE))
+        .with_context(|| format!("Failed to restrict permissions of {:?}", path))?;
+    file.write_all(contents)
+        .with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(())
+}
+
+/// Open `path` for appending, creating it owner-only
+pub fn append_private(path: &Path) -> Result<fs::File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(PRIVATE_FILE_MODE)
+        .open(path)
+        .with_context(|| format!("Failed to open {:?} for appending", path))
+}
+
+/// Create `dir` (and missing parents) and restrict it to the owner
+pub fn create_private_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory {:?}", dir))?;
+    fs::set_permissions(dir, fs::Permissions::from_mode(PRIVATE_DIR_MODE))
+        .with_context(|| format!("Failed to restrict permissions of {:?}", dir))?;
+    Ok(())
+}
+
+/// Whether a file mode lets every user write
+pub fn is_world_writable(mode: u32) -> bool {
+    mode & 0o002 != 0
+}
+
+/// Fail if `path` or its directory can be modified by any user (missing ones are fine)
+pub fn ensure_not_world_writable(path: &Path) -> Result<()> {
+    for checked in [Some(path), path.parent()].into_iter().flatten() {
+        let Ok(metadata) = fs::metadata(checked) else {
+            continue;
+        };
+        let mode = metadata.permissions().mode();
+        if is_world_writable(mode) {
+            bail!(
+                "{} is world-writable (mode {:o}), so any user on this machine could change it. \
+                 Refusing to start; fix it with: chmod o-w {}",
+                checked.display(),
+                mode & 0o7777,
+                checked.display(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Whether the process at the other end of a socket connection runs as our user
+#[cfg(target_os = "linux")]
+pub fn peer_is_same_user(stream: &UnixStream) -> Result<bool> {
+    use nix::sys::socket::{getsockopt, sockopt::PeerCredentials};
+
+    let credentials = getsockopt(stream, PeerCredentials)
+        .context("Failed to read peer credentials of IPC connection")?;
+    Ok(credentials.uid() == nix::unistd::getuid().as_raw())
+}
+
+/// Without SO_PEERCRED the owner-only socket directory is the only check
+#[cfg(not(target_os = "linux"))]
+pub fn peer_is_same_user(_stream: &UnixStream) -> Result<bool> {
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_world_writable() {
+        assert!(is_world_writable(0o100666));
+        assert!(is_world_writable(0o40777));
+        assert!(!is_world_writable(0o100644));
+        assert!(!is_world_writable(0o100600));
+    }
+
+    #[test]
+    fn test_peer_is_same_user_for_own_socket_pair() {
+        let (a, _b) = UnixStream::pair().unwrap();
+        assert!(peer_is_same_user(&a).unwrap());
+    }
+}
@@ -0,0 +1,59 @@
+//! Recommended compositor rules (`eve-l-preview picom-rules`)
+//!
+//! picom's defaults get in the way of previews: shadows and fades on the
+//! thumbnail windows, inactive-opacity dimming them, and unredirecting a
+//! fullscreen client, after which the XRender path reads whatever covers it
+//! on screen (including the previews themselves). The generated rules turn
+//! those off for the thumbnail and client window classes only.
+
+use crate::constants::{eve, x11};
+
+/// Rules for picom's config file; `v12` uses the `rules` block picom 12 introduced,
+/// otherwise the per-option exclude lists older versions (and forks) understand
+pub fn rules(v12: bool) -> String {
+    let thumbnail = format!("class_g = '{}'", x11::THUMBNAIL_WM_CLASS);
+    let client = format!("class_g = '{}'", eve::WINDOW_CLASS);
+    if v12 {
+        format!(
+            "# EVE-L-Preview: add to picom.conf (picom 12+)\n\
+             rules = (\n  \
+               # Thumbnails: no shadow, fade or blur\n  \
+               {{ match = \"{thumbnail}\"; shadow = false; fade = false; blur-background = false; }},\n  \
+               # EVE clients: stay redirected when fullscreen, so previews keep their contents\n  \
+               {{ match = \"{client}\"; unredir = false; }},\n\
+             );\n"
+        )
+    } else {
+        format!(
+            "# EVE-L-Preview: merge into picom.conf (add to existing lists instead of replacing them)\n\
+             # Thumbnails: no shadow, fade or blur\n\
+             shadow-exclude = [ \"{thumbnail}\" ];\n\
+             fade-exclude = [ \"{thumbnail}\" ];\n\
+             blur-background-exclude = [ \"{thumbnail}\" ];\n\
+             # Thumbnails keep the opacity they set (_NET_WM_WINDOW_OPACITY), never dimmed as inactive\n\
+             focus-exclude = [ \"{thumbnail}\" ];\n\
+             # EVE clients: stay redirected when fullscreen, so previews keep their contents\n\
+             unredir-if-possible-exclude = [ \"{client}\" ];\n"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_rules_exclude_thumbnails_and_clients() {
+        let rules = rules(false);
+        assert!(rules.contains("shadow-exclude = [ \"class_g = 'eve-l-preview'\" ];"));
+        assert!(rules.contains("unredir-if-possible-exclude = [ \"class_g = 'exefile.exe'\" ];"));
+    }
+
+    #[test]
+    fn test_v12_rules_block() {
+        let rules = rules(true);
+        assert!(rules.contains("rules = ("));
+        assert!(rules.contains("{ match = \"class_g = 'exefile.exe'\"; unredir = false; },"));
+        assert!(!rules.contains("shadow-exclude"));
+    }
+}
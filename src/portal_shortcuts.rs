@@ -0,0 +1,358 @@
+//! Hotkeys through the XDG desktop portal (org.freedesktop.portal.GlobalShortcuts)
+//!
+//! Wayland compositors don't let clients read the keyboard globally, and on
+//! GNOME/KDE Wayland sessions the user usually isn't in the `input` group either.
+//! The GlobalShortcuts portal lets us register the cycle and per-character
+//! shortcuts with the desktop instead: the compositor asks the user to confirm
+//! (or change) the keys once and then tells us whenever one is pressed.
+//!
+//! The shortcuts are bound once when the listener starts (when the first
+//! settings arrive), so per-character hotkey changes apply after the daemon
+//! is restarted.
+
+use anyhow::{Context, Result};
+use evdev::KeyCode;
+use std::collections::HashMap;
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread;
+use tracing::{debug, error, info, warn};
+use zbus::export::futures_core::Stream;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+
+use crate::config::profile::HotkeyBackend;
+use crate::constants::portal;
+use crate::hotkeys::{self, CharacterHotkeys, CycleCommand};
+
+#[zbus::proxy(
+    interface = "org.freedesktop.portal.GlobalShortcuts",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop",
+    gen_blocking = false
+)]
+trait GlobalShortcuts {
+    fn create_session(&self, options: HashMap<&str, Value<'_>>) -> zbus::Result<OwnedObjectPath>;
+
+    fn bind_shortcuts(
+        &self,
+        session_handle: &ObjectPath<'_>,
+        shortcuts: &[(&str, HashMap<&str, Value<'_>>)],
+        parent_window: &str,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(signal)]
+    fn activated(
+        &self,
+        session_handle: ObjectPath<'_>,
+        shortcut_id: &str,
+        timestamp: u64,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<()>;
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.portal.Request",
+    default_service = "org.freedesktop.portal.Desktop",
+    gen_blocking = false
+)]
+trait Request {
+    #[zbus(signal)]
+    fn response(&self, response: u32, results: HashMap<String, OwnedValue>) -> zbus::Result<()>;
+}
+
+/// Whether this daemon runs inside a Wayland session (XDG_SESSION_TYPE / WAYLAND_DISPLAY)
+pub fn is_wayland_session(session_type: Option<&str>, wayland_display: Option<&str>) -> bool {
+    session_type.is_some_and(|session| session.eq_ignore_ascii_case("wayland"))
+        || wayland_display.is_some_and(|display| !display.is_empty())
+}
+
+/// Whether hotkeys should go through the portal instead of evdev
+pub fn use_portal(backend: HotkeyBackend) -> bool {
+    match backend {
+        HotkeyBackend::Evdev => false,
+        HotkeyBackend::Portal => true,
+        HotkeyBackend::Auto => is_wayland_session(
+            std::env::var("XDG_SESSION_TYPE").ok().as_deref(),
+            std::env::var("WAYLAND_DISPLAY").ok().as_deref(),
+        ),
+    }
+}
+
+/// Portal trigger suggestion for an evdev key ("KEY_F1" → "F1", "KEY_KP1" → "KP_1", "KEY_PLAYPAUSE" → "XF86AudioPlay")
+/// The desktop shows it in its confirmation dialog, the user can pick another key
+fn preferred_trigger(code: u16) -> Option<String> {
+    if let Some(keysym) = hotkeys::keysym_name(code) {
+        return Some(keysym.to_string());
+    }
+    let name = format!("{:?}", KeyCode(code));
+    let key = name.strip_prefix("KEY_")?;
+    Some(match key.strip_prefix("KP") {
+        Some(digit) if digit.len() == 1 && digit.chars().all(|c| c.is_ascii_digit()) => format!("KP_{}", digit),
+        _ if key.len() == 1 => key.to_ascii_lowercase(),
+        _ => key.to_string(),
+    })
+}
+
+/// Shortcuts to register: (id, description, preferred trigger)
+fn shortcut_list(bindings: &HashMap<u16, CycleCommand>) -> Vec<(String, String, Option<String>)> {
+    let mut shortcuts = vec![
+        (portal::CYCLE_FORWARD.to_string(), "Next character".to_string(), Some("Tab".to_string())),
+        (portal::CYCLE_BACKWARD.to_string(), "Previous character".to_string(), Some("SHIFT+Tab".to_string())),
+    ];
+    if let Some((code, _)) = bindings.iter().find(|(_, command)| **command == CycleCommand::TogglePrivacy) {
+        shortcuts.push((portal::TOGGLE_PRIVACY.to_string(), "Toggle privacy mode".to_string(), preferred_trigger(*code)));
+    }
+    let history = [
+        (CycleCommand::FocusLast, portal::FOCUS_LAST, "Previously focused character"),
+        (CycleCommand::FocusBack, portal::FOCUS_BACK, "Back in focus history"),
+        (CycleCommand::FocusForward, portal::FOCUS_FORWARD, "Forward in focus history"),
+    ];
+    for (history_command, id, description) in history {
+        if let Some((code, _)) = bindings.iter().find(|(_, command)| **command == history_command) {
+            shortcuts.push((id.to_string(), description.to_string(), preferred_trigger(*code)));
+        }
+    }
+    let mut characters: Vec<_> = bindings.iter()
+        .filter_map(|(code, command)| match command {
+            CycleCommand::Focus(character) => Some((code, character)),
+            _ => None,
+        })
+        .collect();
+    characters.sort_by(|a, b| a.1.cmp(b.1));
+    for (code, character) in characters {
+        shortcuts.push((
+            format!("{}{}", portal::FOCUS_PREFIX, character),
+            format!("Focus {}", character),
+            preferred_trigger(*code),
+        ));
+    }
+    shortcuts
+}
+
+/// Map an activated shortcut id back to its command
+fn command_for(shortcut_id: &str) -> Option<CycleCommand> {
+    match shortcut_id {
+        portal::CYCLE_FORWARD => Some(CycleCommand::Forward),
+        portal::CYCLE_BACKWARD => Some(CycleCommand::Backward),
+        portal::TOGGLE_PRIVACY => Some(CycleCommand::TogglePrivacy),
+        portal::FOCUS_LAST => Some(CycleCommand::FocusLast),
+        portal::FOCUS_BACK => Some(CycleCommand::FocusBack),
+        portal::FOCUS_FORWARD => Some(CycleCommand::FocusForward),
+        id => id.strip_prefix(portal::FOCUS_PREFIX)
+            .filter(|character| !character.is_empty())
+            .map(|character| CycleCommand::Focus(character.to_string())),
+    }
+}
+
+/// Register the shortcuts with the portal and forward their activations on a background thread
+/// Returns once the portal session exists, so the caller can fall back to evdev on error
+/// Once `stop` is set the thread exits at the next activation without forwarding it
+pub fn spawn_listener(sender: Sender<CycleCommand>, bindings: CharacterHotkeys, stop: Arc<AtomicBool>) -> Result<thread::JoinHandle<()>> {
+    let (ready_tx, ready_rx) = mpsc::channel();
+
+    let handle = thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                let _ = ready_tx.send(Err(anyhow::Error::new(e).context("Failed to create portal hotkey runtime")));
+                return;
+            }
+        };
+        runtime.block_on(async move {
+            let session = match create_session().await {
+                Ok(session) => {
+                    let _ = ready_tx.send(Ok(()));
+                    session
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            if let Err(e) = listen(session, &bindings, sender, &stop).await {
+                error!(error = %e, "Portal hotkey listener error");
+            }
+        });
+    });
+
+    ready_rx.recv()
+        .context("Portal hotkey thread exited before creating a session")??;
+    Ok(handle)
+}
+
+/// Portal connection with its GlobalShortcuts session
+struct PortalSession {
+    connection: zbus::Connection,
+    proxy: GlobalShortcutsProxy<'static>,
+    handle: OwnedObjectPath,
+}
+
+async fn create_session() -> Result<PortalSession> {
+    let connection = zbus::Connection::session().await
+        .context("Failed to connect to the D-Bus session bus")?;
+    let proxy = GlobalShortcutsProxy::new(&connection).await
+        .context("Failed to create GlobalShortcuts portal proxy")?;
+
+    let token = request_token("session");
+    let results = request(&connection, &token, async {
+        let options = HashMap::from([
+            ("handle_token", Value::from(token.as_str())),
+            ("session_handle_token", Value::from(token.as_str())),
+        ]);
+        proxy.create_session(options).await
+    })
+    .await
+    .context("GlobalShortcuts portal CreateSession failed (is xdg-desktop-portal running with GlobalShortcuts support?)")?;
+
+    let handle = results.get("session_handle")
+        .and_then(|handle| String::try_from(handle.try_clone().ok()?).ok())
+        .context("GlobalShortcuts portal returned no session handle")?;
+    let handle = OwnedObjectPath::try_from(handle)
+        .context("GlobalShortcuts portal returned an invalid session handle")?;
+    info!(session = %handle.as_str(), "GlobalShortcuts portal session created");
+
+    Ok(PortalSession { connection, proxy, handle })
+}
+
+/// Bind the shortcuts and forward activations until the session or channel closes
+async fn listen(
+    session: PortalSession,
+    bindings: &CharacterHotkeys,
+    sender: Sender<CycleCommand>,
+    stop: &AtomicBool,
+) -> Result<()> {
+    let PortalSession { connection, proxy, handle } = session;
+    // Read as late as possible, the settings arrive from the GUI after startup
+    let shortcuts = shortcut_list(&bindings.lock().unwrap());
+
+    // Subscribe first so no activation between binding and listening is lost
+    let mut activated = proxy.receive_activated().await
+        .context("Failed to subscribe to GlobalShortcuts Activated signal")?;
+
+    let token = request_token("bind");
+    let results = request(&connection, &token, async {
+        let shortcuts: Vec<(&str, HashMap<&str, Value<'_>>)> = shortcuts.iter()
+            .map(|(id, description, trigger)| {
+                let mut options = HashMap::from([("description", Value::from(description.as_str()))]);
+                if let Some(trigger) = trigger {
+                    options.insert("preferred_trigger", Value::from(trigger.as_str()));
+                }
+                (id.as_str(), options)
+            })
+            .collect();
+        let options = HashMap::from([("handle_token", Value::from(token.as_str()))]);
+        proxy.bind_shortcuts(&handle.as_ref(), &shortcuts, "", options).await
+    })
+    .await
+    .context("GlobalShortcuts portal BindShortcuts failed")?;
+
+    let bound = results.get("shortcuts")
+        .and_then(|bound| <Vec<(String, HashMap<String, OwnedValue>)>>::try_from(bound.try_clone().ok()?).ok())
+        .unwrap_or_default();
+    for (id, properties) in &bound {
+        let trigger = properties.get("trigger_description")
+            .and_then(|trigger| String::try_from(trigger.try_clone().ok()?).ok())
+            .unwrap_or_default();
+        info!(shortcut = %id, trigger = %trigger, "Portal shortcut bound");
+    }
+    info!(count = bound.len(), "Hotkey support enabled through the GlobalShortcuts portal");
+
+    while let Some(signal) = poll_fn(|cx| Pin::new(&mut activated).poll_next(cx)).await {
+        if stop.load(Ordering::Relaxed) {
+            info!("Portal hotkey listener stopped");
+            break;
+        }
+        let args = match signal.args() {
+            Ok(args) => args,
+            Err(e) => {
+                warn!(error = %e, "Malformed GlobalShortcuts Activated signal");
+                continue;
+            }
+        };
+        if args.session_handle().as_str() != handle.as_str() {
+            continue;
+        }
+        let Some(command) = command_for(args.shortcut_id()) else {
+            debug!(shortcut = %args.shortcut_id(), "Unknown portal shortcut activated");
+            continue;
+        };
+        info!(shortcut = %args.shortcut_id(), command = ?command, "Portal shortcut activated, sending command");
+        sender.send(command)
+            .context("Failed to send portal hotkey command")?;
+    }
+
+    Ok(())
+}
+
+/// Unique handle token for a portal request
+fn request_token(kind: &str) -> String {
+    format!("{}_{}_{}", portal::TOKEN_PREFIX, kind, std::process::id())
+}
+
+/// Run a portal method returning a Request handle and wait for its Response
+/// Subscribes on the predictable request path before the call so the response can't be missed
+async fn request(
+    connection: &zbus::Connection,
+    token: &str,
+    call: impl Future<Output = zbus::Result<OwnedObjectPath>>,
+) -> Result<HashMap<String, OwnedValue>> {
+    let sender = connection.unique_name()
+        .context("D-Bus connection has no unique name")?
+        .trim_start_matches(':')
+        .replace('.', "_");
+    let path = format!("/org/freedesktop/portal/desktop/request/{}/{}", sender, token);
+    let request = RequestProxy::builder(connection)
+        .path(path)?
+        .build()
+        .await
+        .context("Failed to create portal request proxy")?;
+    let mut responses = request.receive_response().await
+        .context("Failed to subscribe to portal request response")?;
+
+    call.await.context("Portal request failed")?;
+
+    let response = poll_fn(|cx| Pin::new(&mut responses).poll_next(cx)).await
+        .context("Portal request ended without a response")?;
+    let args = response.args()
+        .context("Malformed portal response")?;
+    match args.response() {
+        0 => Ok(args.results().clone()),
+        1 => anyhow::bail!("request was cancelled by the user"),
+        code => anyhow::bail!("request failed (response code {})", code),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_wayland_session() {
+        assert!(is_wayland_session(Some("wayland"), None));
+        assert!(is_wayland_session(None, Some("wayland-0")));
+        assert!(!is_wayland_session(Some("x11"), None));
+        assert!(!is_wayland_session(None, Some("")));
+    }
+
+    #[test]
+    fn test_shortcut_round_trip() {
+        let bindings = HashMap::from([
+            (KeyCode::KEY_F1.code(), CycleCommand::Focus("Pilot One".to_string())),
+            (KeyCode::KEY_KP2.code(), CycleCommand::Focus("Alt".to_string())),
+        ]);
+        let shortcuts = shortcut_list(&bindings);
+        let triggers: Vec<_> = shortcuts.iter().map(|(_, _, trigger)| trigger.as_deref()).collect();
+        assert_eq!(triggers, vec![Some("Tab"), Some("SHIFT+Tab"), Some("KP_2"), Some("F1")]);
+        let commands: Vec<_> = shortcuts.iter().filter_map(|(id, _, _)| command_for(id)).collect();
+        assert_eq!(commands, vec![
+            CycleCommand::Forward,
+            CycleCommand::Backward,
+            CycleCommand::Focus("Alt".to_string()),
+            CycleCommand::Focus("Pilot One".to_string()),
+        ]);
+    }
+}
@@ -0,0 +1,127 @@
+//! Borderless fullscreen emulation for wine clients
+//!
+//! Wine often can't switch EVE into a proper borderless window on its own.
+//! For characters with `borderless_fullscreen` enabled, the daemon strips WM
+//! decorations via _MOTIF_WM_HINTS and resizes the client to cover its monitor
+//! on focus, then puts the original hints and frame geometry back on focus loss.
+
+use anyhow::{Context, Result};
+use tracing::{debug, info};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::wrapper::ConnectionExt as WrapperExt;
+
+use crate::constants::x11;
+use crate::types::{Dimensions, Position};
+use crate::x11_utils::{frame_window, monitor_geometry_at, AppContext};
+
+/// Client state captured before going borderless, used to restore it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedClientGeometry {
+    /// Top-left of the WM frame (what a NorthWest-gravity ConfigureWindow positions)
+    pub position: Position,
+    /// Client area size (excluding decorations)
+    pub dimensions: Dimensions,
+    /// Original _MOTIF_WM_HINTS value (None if the client never set it)
+    pub motif_hints: Option<Vec<u32>>,
+}
+
+/// Strip decorations and resize the client to fill the monitor it is on
+pub fn enter(ctx: &AppContext, window: Window) -> Result<SavedClientGeometry> {
+    let frame = frame_window(ctx.conn, window)
+        .context(format!("Failed to find frame window for client {}", window))?;
+    let frame_geom = ctx.conn.get_geometry(frame)
+        .context(format!("Failed to query frame geometry for client {}", window))?
+        .reply()
+        .context(format!("Failed to get frame geometry reply for client {}", window))?;
+    let client_geom = ctx.conn.get_geometry(window)
+        .context(format!("Failed to query geometry for client {}", window))?
+        .reply()
+        .context(format!("Failed to get geometry reply for client {}", window))?;
+
+    let hints_reply = ctx.conn
+        .get_property(false, window, ctx.atoms.motif_wm_hints, ctx.atoms.motif_wm_hints, 0, x11::MWM_HINTS_LENGTH)
+        .context(format!("Failed to query _MOTIF_WM_HINTS for client {}", window))?
+        .reply()
+        .context(format!("Failed to get _MOTIF_WM_HINTS reply for client {}", window))?;
+    let motif_hints = hints_reply.value32().map(|values| values.collect::<Vec<u32>>());
+
+    let saved = SavedClientGeometry {
+        position: Position::new(frame_geom.x, frame_geom.y),
+        dimensions: Dimensions::new(client_geom.width, client_geom.height),
+        motif_hints,
+    };
+
+    // Pick the monitor under the center of the current frame
+    let center_x = frame_geom.x.saturating_add((frame_geom.width / 2) as i16);
+    let center_y = frame_geom.y.saturating_add((frame_geom.height / 2) as i16);
+    let (monitor_pos, monitor_size) = monitor_geometry_at(ctx.conn, ctx.screen, center_x, center_y);
+
+    ctx.conn.change_property32(
+        PropMode::REPLACE,
+        window,
+        ctx.atoms.motif_wm_hints,
+        ctx.atoms.motif_wm_hints,
+        &[x11::MWM_HINTS_DECORATIONS, 0, 0, 0, 0],
+    )
+    .context(format!("Failed to clear decorations for client {}", window))?;
+
+    ctx.conn.configure_window(
+        window,
+        &ConfigureWindowAux::new()
+            .x(monitor_pos.x as i32)
+            .y(monitor_pos.y as i32)
+            .width(monitor_size.width as u32)
+            .height(monitor_size.height as u32),
+    )
+    .context(format!("Failed to resize client {} to monitor", window))?;
+
+    ctx.conn.flush()
+        .context("Failed to flush X11 connection after entering borderless fullscreen")?;
+
+    info!(
+        window = window,
+        x = monitor_pos.x,
+        y = monitor_pos.y,
+        width = monitor_size.width,
+        height = monitor_size.height,
+        "Entered borderless fullscreen"
+    );
+    Ok(saved)
+}
+
+/// Restore decorations and the geometry captured by `enter`
+pub fn leave(ctx: &AppContext, window: Window, saved: &SavedClientGeometry) -> Result<()> {
+    match &saved.motif_hints {
+        Some(hints) => {
+            ctx.conn.change_property32(
+                PropMode::REPLACE,
+                window,
+                ctx.atoms.motif_wm_hints,
+                ctx.atoms.motif_wm_hints,
+                hints,
+            )
+            .context(format!("Failed to restore _MOTIF_WM_HINTS for client {}", window))?;
+        }
+        None => {
+            ctx.conn.delete_property(window, ctx.atoms.motif_wm_hints)
+                .context(format!("Failed to remove _MOTIF_WM_HINTS for client {}", window))?;
+        }
+    }
+
+    ctx.conn.configure_window(
+        window,
+        &ConfigureWindowAux::new()
+            .x(saved.position.x as i32)
+            .y(saved.position.y as i32)
+            .width(saved.dimensions.width as u32)
+            .height(saved.dimensions.height as u32),
+    )
+    .context(format!("Failed to restore geometry for client {}", window))?;
+
+    ctx.conn.flush()
+        .context("Failed to flush X11 connection after leaving borderless fullscreen")?;
+
+    debug!(window = window, saved = ?saved, "Restored client from borderless fullscreen");
+    Ok(())
+}
@@ -0,0 +1,178 @@
+//! Chord hotkeys (chord_leader, chord_timeout_ms)
+//!
+//! After the leader combo (e.g. Ctrl+Space) the next key picks a character:
+//! 1-9 (top row or keypad) focus that character in cycle order. The daemon
+//! grabs the keyboard for the follow-up so the digit doesn't also reach EVE,
+//! and shows a hint listing the follow-up keys in the middle of the screen.
+//! Any other key, or the timeout, cancels the chord.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use evdev::KeyCode;
+use tracing::{debug, info, warn};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+use x11rb::CURRENT_TIME;
+
+use crate::constants::input;
+use crate::preview::font::FontRenderer;
+
+use super::tooltip::{Placement, Tooltip};
+
+/// Position in the follow-up list for a key (1 → 0), None for other keys
+fn follow_up_index(code: u16) -> Option<usize> {
+    const DIGITS: [[KeyCode; 2]; 9] = [
+        [KeyCode::KEY_1, KeyCode::KEY_KP1],
+        [KeyCode::KEY_2, KeyCode::KEY_KP2],
+        [KeyCode::KEY_3, KeyCode::KEY_KP3],
+        [KeyCode::KEY_4, KeyCode::KEY_KP4],
+        [KeyCode::KEY_5, KeyCode::KEY_KP5],
+        [KeyCode::KEY_6, KeyCode::KEY_KP6],
+        [KeyCode::KEY_7, KeyCode::KEY_KP7],
+        [KeyCode::KEY_8, KeyCode::KEY_KP8],
+        [KeyCode::KEY_9, KeyCode::KEY_KP9],
+    ];
+    DIGITS.iter().position(|keys| keys.iter().any(|key| key.code() == code))
+}
+
+/// Modifier keys still held from the leader don't end the chord
+fn is_modifier(code: u16) -> bool {
+    [
+        KeyCode::KEY_LEFTCTRL, KeyCode::KEY_RIGHTCTRL,
+        KeyCode::KEY_LEFTSHIFT, KeyCode::KEY_RIGHTSHIFT,
+        KeyCode::KEY_LEFTALT, KeyCode::KEY_RIGHTALT,
+        KeyCode::KEY_LEFTMETA, KeyCode::KEY_RIGHTMETA,
+    ]
+    .iter()
+    .any(|key| key.code() == code)
+}
+
+/// Hint lines: one "key  character" line per follow-up
+fn hint_lines(characters: &[String]) -> Vec<String> {
+    if characters.is_empty() {
+        return vec!["No characters to switch to".to_string()];
+    }
+    characters.iter()
+        .enumerate()
+        .map(|(i, character)| format!("{}  {}", i + 1, character))
+        .collect()
+}
+
+#[derive(Debug)]
+struct Pending {
+    /// Follow-up targets, the first is picked with 1
+    characters: Vec<String>,
+    deadline: Instant,
+    hint: Option<Tooltip>,
+    keyboard_grabbed: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Chord {
+    pending: Option<Pending>,
+}
+
+impl Chord {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn active(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// When the pending chord times out
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.as_ref().map(|pending| pending.deadline)
+    }
+
+    /// Leader pressed: grab the keyboard and show the follow-up keys
+    pub fn start(
+        &mut self,
+        conn: &RustConnection,
+        screen: &Screen,
+        font_renderer: &FontRenderer,
+        mut characters: Vec<String>,
+        timeout: Duration,
+    ) -> Result<()> {
+        self.finish(conn);
+        characters.truncate(9);
+        let keyboard_grabbed = conn.grab_keyboard(false, screen.root, CURRENT_TIME, GrabMode::ASYNC, GrabMode::ASYNC)
+            .context("Failed to grab keyboard for chord")?
+            .reply()
+            .context("Failed to grab keyboard for chord")?
+            .status == GrabStatus::SUCCESS;
+        if !keyboard_grabbed {
+            // Another client holds the keyboard (a menu is open): no follow-up key would reach us
+            warn!("Keyboard is grabbed by another client, chord cancelled");
+            return Ok(());
+        }
+        let hint = Tooltip::show(conn, screen, font_renderer, &hint_lines(&characters), Placement::Centered)
+            .inspect_err(|e| warn!(error = ?e, "Failed to show chord hint"))
+            .ok();
+        info!(characters = characters.len(), timeout_ms = timeout.as_millis() as u64, "Chord leader pressed, waiting for follow-up key");
+        self.pending = Some(Pending { characters, deadline: Instant::now() + timeout, hint, keyboard_grabbed });
+        Ok(())
+    }
+
+    /// A key pressed while the keyboard is grabbed: the character it picks, if any
+    /// Every key except modifiers ends the chord
+    pub fn key(&mut self, conn: &RustConnection, keycode: Keycode) -> Option<String> {
+        let code = (keycode as u16).checked_sub(input::X_KEYCODE_OFFSET)?;
+        if is_modifier(code) {
+            return None;
+        }
+        let picked = self.pending.as_ref()
+            .and_then(|pending| follow_up_index(code).and_then(|i| pending.characters.get(i).cloned()));
+        debug!(code = code, picked = ?picked, "Chord follow-up key");
+        self.finish(conn);
+        picked
+    }
+
+    /// Cancel the chord once its time is up
+    pub fn expire(&mut self, conn: &RustConnection, now: Instant) {
+        if self.deadline().is_some_and(|deadline| deadline <= now) {
+            debug!("Chord timed out");
+            self.finish(conn);
+        }
+    }
+
+    fn finish(&mut self, conn: &RustConnection) {
+        let Some(pending) = self.pending.take() else {
+            return;
+        };
+        if pending.keyboard_grabbed {
+            let _ = conn.ungrab_keyboard(CURRENT_TIME)
+                .inspect_err(|e| warn!(error = ?e, "Failed to release keyboard after chord"));
+        }
+        if let Some(hint) = pending.hint {
+            let _ = hint.close(conn)
+                .inspect_err(|e| warn!(error = ?e, "Failed to close chord hint"));
+        }
+        let _ = conn.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follow_up_keys() {
+        assert_eq!(follow_up_index(KeyCode::KEY_1.code()), Some(0));
+        assert_eq!(follow_up_index(KeyCode::KEY_KP3.code()), Some(2));
+        assert_eq!(follow_up_index(KeyCode::KEY_9.code()), Some(8));
+        assert_eq!(follow_up_index(KeyCode::KEY_0.code()), None);
+        assert!(is_modifier(KeyCode::KEY_RIGHTCTRL.code()));
+        assert!(!is_modifier(KeyCode::KEY_SPACE.code()));
+    }
+
+    #[test]
+    fn test_hint_lines() {
+        let characters = vec!["FC".to_string(), "Scout".to_string()];
+        assert_eq!(hint_lines(&characters), vec!["1  FC", "2  Scout"]);
+        assert_eq!(hint_lines(&[]).len(), 1);
+    }
+}
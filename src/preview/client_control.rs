@@ -0,0 +1,110 @@
+//! Terminating (and relaunching) a wedged EVE client
+//!
+//! The GUI asks for it by character name; the daemon knows the client's PID
+//! (_NET_WM_PID of its window, the wine process). The client gets SIGTERM
+//! first and SIGKILL if it is still there after a grace period. The optional
+//! relaunch runs the character's launch_command through `sh -c` once the old
+//! process is gone. All of this runs on its own thread so previews keep
+//! updating while a frozen client takes its time.
+
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use nix::errno::Errno;
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::Pid;
+use tracing::{debug, error, info, warn};
+
+use crate::constants::client_control::{KILL_WAIT_SECS, POLL_MS, SHELL, TERM_GRACE_SECS};
+use crate::constants::process_stats::STAT_FORMAT;
+
+/// Terminate `pid` in the background, then run `relaunch` (if any) once it has exited
+pub fn terminate(pid: u32, character: String, relaunch: Option<String>) {
+    std::thread::spawn(move || {
+        if let Err(e) = terminate_and_relaunch(pid, &character, relaunch.as_deref()) {
+            error!(pid = pid, character = %character, error = ?e, "Failed to terminate client");
+        }
+    });
+}
+
+fn terminate_and_relaunch(pid: u32, character: &str, relaunch: Option<&str>) -> Result<()> {
+    let target = Pid::from_raw(pid as i32);
+    info!(pid = pid, character = %character, "Sending SIGTERM to client");
+    if send(target, Signal::SIGTERM)? && !wait_for_exit(pid, Duration::from_secs(TERM_GRACE_SECS)) {
+        warn!(pid = pid, character = %character, grace_secs = TERM_GRACE_SECS, "Client ignored SIGTERM, sending SIGKILL");
+        if send(target, Signal::SIGKILL)? && !wait_for_exit(pid, Duration::from_secs(KILL_WAIT_SECS)) {
+            bail!("Client {} ('{}') is still running after SIGKILL", pid, character);
+        }
+    }
+    info!(pid = pid, character = %character, "Client terminated");
+
+    if let Some(command) = relaunch {
+        launch(character, command)?;
+    }
+    Ok(())
+}
+
+/// Send a signal; false if the process is already gone
+fn send(pid: Pid, signal: Signal) -> Result<bool> {
+    match kill(pid, signal) {
+        Ok(()) => Ok(true),
+        Err(Errno::ESRCH) => Ok(false),
+        Err(e) => Err(e).context(format!("Failed to send {} to process {}", signal, pid)),
+    }
+}
+
+/// Poll /proc until the process is gone (or only a zombie is left), up to `timeout`
+fn wait_for_exit(pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !is_running(pid) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(POLL_MS));
+    }
+}
+
+fn is_running(pid: u32) -> bool {
+    std::fs::read_to_string(STAT_FORMAT.replace("{}", &pid.to_string()))
+        .ok()
+        .and_then(|stat| parse_state(&stat))
+        .is_some_and(|state| state != 'Z')
+}
+
+/// Process state letter (R, S, D, Z, ...) from the contents of /proc/<pid>/stat
+pub fn parse_state(stat: &str) -> Option<char> {
+    stat[stat.rfind(')')? + 1..].split_whitespace().next()?.chars().next()
+}
+
+/// Start the character's launch command; a thread waits for it so it never lingers as a zombie
+fn launch(character: &str, command: &str) -> Result<()> {
+    let mut child = Command::new(SHELL)
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .spawn()
+        .context(format!("Failed to run launch command for '{}': {}", character, command))?;
+    info!(character = %character, pid = child.id(), command = %command, "Relaunched client");
+    let character = character.to_string();
+    std::thread::spawn(move || match child.wait() {
+        Ok(status) => debug!(character = %character, status = %status, "Launch command exited"),
+        Err(e) => warn!(character = %character, error = %e, "Failed to wait for launch command"),
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_state() {
+        assert_eq!(parse_state("4242 (exefile.exe) S 1 4242"), Some('S'));
+        assert_eq!(parse_state("4242 (wine (x) Z) Z 1 4242"), Some('Z'));
+        assert_eq!(parse_state(""), None);
+    }
+}
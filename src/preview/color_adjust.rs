@@ -0,0 +1,152 @@
+//! Colour adjustments of thumbnails: per-monitor correction (monitor_colors)
+//! and the night mode tint
+//!
+//! XRender has no colour matrices or lookup tables, so this isn't a real ICC
+//! transform. It approximates one with composites over the scaled client
+//! image: a grey fill under PictOp::HSL_SATURATION pulls saturation towards
+//! grey (wide-gamut panels stretch sRGB colours), a warm fill under
+//! PictOp::MULTIPLY tints, and translucent black darkens. The fill alpha is
+//! how much of each effect is applied.
+
+use anyhow::{Context, Result};
+use x11rb::connection::Connection;
+use x11rb::protocol::render::{Color, ConnectionExt as RenderExt, PictOp, Picture};
+use x11rb::rust_connection::RustConnection;
+
+use crate::config::profile::{MonitorColor, NightModeSettings};
+use crate::constants::night_mode;
+
+use super::snapping::Rect;
+
+#[derive(Debug)]
+pub struct ColorAdjust {
+    /// Monitor and night mode these fills were made for
+    monitor: usize,
+    night: Option<NightModeSettings>,
+    /// Grey blended in with HSL_SATURATION, None = saturation unchanged
+    desaturate: Option<Picture>,
+    /// Warm colour multiplied in, None = no tint
+    warm: Option<Picture>,
+    /// Black composited OVER, None = brightness unchanged
+    darken: Option<Picture>,
+}
+
+impl ColorAdjust {
+    /// Fills for thumbnails on `monitor`, with the night tint while `night` is set
+    /// None if nothing needs adjusting
+    pub fn new(
+        conn: &RustConnection,
+        settings: &[MonitorColor],
+        monitor: usize,
+        night: Option<NightModeSettings>,
+    ) -> Result<Option<Self>> {
+        let setting = settings.iter().find(|setting| setting.monitor == monitor);
+        let saturation = setting.map_or(100, |setting| setting.saturation_percent);
+        let brightness = combined_percent(
+            setting.map_or(100, |setting| setting.brightness_percent),
+            night.map_or(100, |night| night.brightness_percent),
+        );
+        let tint = night.map_or(0, |night| night.tint_percent);
+
+        let desaturate = solid_fill(conn, 0x808080, reduction_alpha(saturation))
+            .context(format!("Failed to create desaturation fill for monitor {}", monitor))?;
+        let warm = solid_fill(conn, night_mode::TINT_COLOR, reduction_alpha(100 - tint.min(100)))
+            .context("Failed to create night tint fill")?;
+        let darken = solid_fill(conn, 0x000000, reduction_alpha(brightness))
+            .context(format!("Failed to create darkening fill for monitor {}", monitor))?;
+        if desaturate.is_none() && warm.is_none() && darken.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self { monitor, night, desaturate, warm, darken }))
+    }
+
+    /// Whether these fills are the ones for `monitor` and `night`
+    pub fn matches(&self, monitor: usize, night: Option<NightModeSettings>) -> bool {
+        self.monitor == monitor && self.night == night
+    }
+
+    /// Correct `region` of `dst`
+    pub fn apply(&self, conn: &RustConnection, dst: Picture, region: Rect) -> Result<()> {
+        let steps = [
+            (PictOp::HSL_SATURATION, self.desaturate),
+            (PictOp::MULTIPLY, self.warm),
+            (PictOp::OVER, self.darken),
+        ];
+        for (op, fill) in steps {
+            let Some(fill) = fill else {
+                continue;
+            };
+            conn.render_composite(
+                op,
+                fill,
+                0u32,
+                dst,
+                0,
+                0,
+                0,
+                0,
+                region.x,
+                region.y,
+                region.width,
+                region.height,
+            )
+            .context("Failed to apply colour correction")?;
+        }
+        Ok(())
+    }
+
+    pub fn free(&self, conn: &RustConnection) -> Result<()> {
+        for fill in [self.desaturate, self.warm, self.darken].into_iter().flatten() {
+            conn.render_free_picture(fill)
+                .context("Failed to free colour correction fill")?;
+        }
+        Ok(())
+    }
+}
+
+/// Solid `rgb` fill at `alpha`, None when fully transparent
+fn solid_fill(conn: &RustConnection, rgb: u32, alpha: u16) -> Result<Option<Picture>> {
+    if alpha == 0 {
+        return Ok(None);
+    }
+    let fill = conn.generate_id()?;
+    // Solid fills take premultiplied colours
+    let channel = |shift: u32| (((rgb >> shift) & 0xFF) * 0x101 * alpha as u32 / 0xFFFF) as u16;
+    conn.render_create_solid_fill(fill, Color { red: channel(16), green: channel(8), blue: channel(0), alpha })?;
+    Ok(Some(fill))
+}
+
+/// Two "percent kept" settings applied one after the other
+fn combined_percent(first: u8, second: u8) -> u8 {
+    (first.min(100) as u32 * second.min(100) as u32 / 100) as u8
+}
+
+/// Fill alpha removing everything above `percent` of an effect (100 = nothing)
+fn reduction_alpha(percent: u8) -> u16 {
+    ((100 - percent.min(100) as u32) * 0xFFFF / 100) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduction_alpha() {
+        assert_eq!(reduction_alpha(100), 0);
+        assert_eq!(reduction_alpha(0), 0xFFFF);
+        assert_eq!(reduction_alpha(75), 0x3FFF);
+    }
+
+    #[test]
+    fn test_reduction_alpha_caps_at_unchanged() {
+        // Boosting isn't possible by compositing over the image
+        assert_eq!(reduction_alpha(150), 0);
+    }
+
+    #[test]
+    fn test_combined_percent() {
+        assert_eq!(combined_percent(100, 60), 60);
+        assert_eq!(combined_percent(90, 50), 45);
+        assert_eq!(combined_percent(200, 200), 100);
+    }
+}
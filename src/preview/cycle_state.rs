@@ -1,10 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tracing::{debug, warn};
 use x11rb::protocol::xproto::Window;
 
+use crate::config::profile::CycleSort;
+
 /// Maps character names to their window IDs and positions in cycle order
 pub struct CycleState {
-    /// Configured order from profile's cycle_group (persistent across sessions)
+    /// Cycle order: the profile's cycle_group, or worked out by cycle_sort
     config_order: Vec<String>,
 
     /// Current index in config_order (0-based)
@@ -13,6 +15,24 @@ pub struct CycleState {
     /// Active windows: character_name → window_id
     /// Only includes characters that currently have windows
     active_windows: HashMap<String, Window>,
+
+    /// Characters skipped by Tab/Shift+Tab (exclude_from_cycle)
+    excluded: HashSet<String>,
+
+    /// Whether Tab past the last character goes back to the first (cycle_wrap)
+    wrap: bool,
+
+    /// Logged-in characters, in the order they logged in (for CycleSort::Login)
+    logins: Vec<String>,
+}
+
+/// What the cycle strip shows: the characters Tab/Shift+Tab visit, in cycle order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleTargets {
+    pub characters: Vec<String>,
+    /// Position of the current character among them
+    pub current: Option<usize>,
+    pub wrap: bool,
 }
 
 impl CycleState {
@@ -21,6 +41,9 @@ impl CycleState {
             config_order,
             current_index: 0,
             active_windows: HashMap::new(),
+            excluded: HashSet::new(),
+            wrap: true,
+            logins: Vec::new(),
         }
     }
 
@@ -29,6 +52,9 @@ impl CycleState {
         debug!(character = %character_name, window = window, "Adding window for character");
         self.active_windows
             .insert(character_name.clone(), window);
+        if !character_name.is_empty() && !self.logins.contains(&character_name) {
+            self.logins.push(character_name);
+        }
 
         // DO NOT auto-add to config order - only configured characters can be cycled
         // Characters not in hotkey_order config will be ignored for Tab/Shift+Tab
@@ -45,6 +71,7 @@ impl CycleState {
         {
             debug!(character = %name, window = window, "Removing window for character");
             self.active_windows.remove(&name);
+            self.logins.retain(|login| *login != name);
 
             // If we removed the current character, clamp index
             self.clamp_index();
@@ -61,6 +88,7 @@ impl CycleState {
             .map(|(k, v)| (k.clone(), *v))
         {
             self.active_windows.remove(&old_name);
+            self.logins.retain(|login| *login != old_name);
         }
 
         // Add new entry
@@ -83,11 +111,17 @@ impl CycleState {
 
         let start_index = self.current_index;
         loop {
+            if !self.wrap && self.current_index + 1 == self.config_order.len() {
+                self.current_index = start_index;
+                return self.park();
+            }
             self.current_index = (self.current_index + 1) % self.config_order.len();
 
             // Found an active character that's in the config order
             let character_name = &self.config_order[self.current_index];
-            if let Some(&window) = self.active_windows.get(character_name) {
+            if let Some(&window) = self.active_windows.get(character_name)
+                && !self.excluded.contains(character_name)
+            {
                 debug!(character = %character_name, index = self.current_index, "Cycling forward to character");
                 return Some((window, character_name.as_str()));
             }
@@ -116,6 +150,10 @@ impl CycleState {
 
         let start_index = self.current_index;
         loop {
+            if !self.wrap && self.current_index == 0 {
+                self.current_index = start_index;
+                return self.park();
+            }
             self.current_index = if self.current_index == 0 {
                 self.config_order.len() - 1
             } else {
@@ -124,7 +162,9 @@ impl CycleState {
 
             // Found an active character that's in the config order
             let character_name = &self.config_order[self.current_index];
-            if let Some(&window) = self.active_windows.get(character_name) {
+            if let Some(&window) = self.active_windows.get(character_name)
+                && !self.excluded.contains(character_name)
+            {
                 debug!(character = %character_name, index = self.current_index, "Cycling backward to character");
                 return Some((window, character_name.as_str()));
             }
@@ -137,6 +177,66 @@ impl CycleState {
         }
     }
 
+    /// End of the list without cycle_wrap: stay on the current character
+    fn park(&self) -> Option<(Window, &str)> {
+        let character_name = &self.config_order[self.current_index];
+        debug!(character = %character_name, index = self.current_index, "End of cycle order, staying on current character");
+        self.active_windows.get(character_name)
+            .filter(|_| !self.excluded.contains(character_name))
+            .map(|&window| (window, character_name.as_str()))
+    }
+
+    /// Replace the set of characters that Tab/Shift+Tab skips
+    pub fn set_excluded(&mut self, excluded: HashSet<String>) {
+        self.excluded = excluded;
+    }
+
+    /// Recompute the cycle order for `sort` (live, as characters log in and
+    /// thumbnails move), keeping the current character current
+    /// `position` is where a character's thumbnail sits
+    pub fn apply_sort(&mut self, sort: CycleSort, manual: &[String], position: impl Fn(&str) -> Option<(i16, i16)>) {
+        let mut order = match sort {
+            CycleSort::Manual => manual.to_vec(),
+            CycleSort::Alphabetical | CycleSort::Login | CycleSort::Position => self.logins.clone(),
+        };
+        match sort {
+            CycleSort::Alphabetical => order.sort_by_key(|character| character.to_lowercase()),
+            // Characters without a thumbnail position go last
+            CycleSort::Position => order.sort_by_key(|character| position(character).unwrap_or((i16::MAX, i16::MAX))),
+            CycleSort::Manual | CycleSort::Login => {}
+        }
+        if order == self.config_order {
+            return;
+        }
+        let current = self.config_order.get(self.current_index);
+        self.current_index = current
+            .and_then(|current| order.iter().position(|character| character == current))
+            .unwrap_or(0);
+        debug!(sort = ?sort, order = ?order, "Cycle order changed");
+        self.config_order = order;
+    }
+
+    /// Whether cycling wraps around at the ends of the list
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    /// Characters Tab/Shift+Tab visit, in cycle order, and where the current one is among them
+    pub fn cycle_targets(&self) -> CycleTargets {
+        let current = self.config_order.get(self.current_index);
+        let targets: Vec<String> = self.config_order.iter()
+            .filter(|character| self.active_windows.contains_key(*character) && !self.excluded.contains(*character))
+            .cloned()
+            .collect();
+        let position = current.and_then(|current| targets.iter().position(|character| character == current));
+        CycleTargets { characters: targets, current: position, wrap: self.wrap }
+    }
+
+    /// Window of an active character (for direct focus hotkeys)
+    pub fn window_for(&self, character_name: &str) -> Option<Window> {
+        self.active_windows.get(character_name).copied()
+    }
+
     /// Set current character (called when clicking thumbnail)
     /// Returns true if character exists in config order
     pub fn set_current(&mut self, character_name: &str) -> bool {
@@ -280,4 +380,88 @@ mod tests {
         assert!(!state.active_windows.contains_key("OldName"));
         assert_eq!(state.active_windows.get("NewName"), Some(&100));
     }
+
+    #[test]
+    fn test_excluded_characters_are_skipped() {
+        let mut state = CycleState::new(vec![
+            "Char1".to_string(),
+            "Char2".to_string(),
+            "Char3".to_string(),
+        ]);
+
+        state.add_window("Char1".to_string(), 100);
+        state.add_window("Char2".to_string(), 200);
+        state.add_window("Char3".to_string(), 300);
+        state.set_excluded(HashSet::from(["Char2".to_string()]));
+
+        assert_eq!(state.cycle_forward(), Some((300, "Char3")));
+        assert_eq!(state.cycle_forward(), Some((100, "Char1")));
+        assert_eq!(state.cycle_backward(), Some((300, "Char3")));
+        assert_eq!(state.window_for("Char2"), Some(200));
+    }
+
+    #[test]
+    fn test_cycle_targets() {
+        let mut state = CycleState::new(vec![
+            "Char1".to_string(),
+            "Char2".to_string(),
+            "Char3".to_string(),
+            "Char4".to_string(),
+        ]);
+
+        state.add_window("Char1".to_string(), 100);
+        state.add_window("Char3".to_string(), 300);
+        state.add_window("Char4".to_string(), 400);
+        state.set_excluded(HashSet::from(["Char4".to_string()]));
+        state.set_current("Char3");
+
+        let targets = state.cycle_targets();
+        assert_eq!(targets.characters, vec!["Char1".to_string(), "Char3".to_string()]);
+        assert_eq!(targets.current, Some(1));
+        state.set_current("Char2");
+        assert_eq!(state.cycle_targets().current, None);
+    }
+
+    #[test]
+    fn test_no_wrap_parks_at_the_ends() {
+        let mut state = CycleState::new(vec![
+            "Char1".to_string(),
+            "Char2".to_string(),
+            "Char3".to_string(),
+        ]);
+
+        state.add_window("Char1".to_string(), 100);
+        state.add_window("Char2".to_string(), 200);
+        state.set_wrap(false);
+
+        // Char3 isn't running, so Char2 is the last stop
+        assert_eq!(state.cycle_forward(), Some((200, "Char2")));
+        assert_eq!(state.cycle_forward(), Some((200, "Char2")));
+        assert_eq!(state.cycle_backward(), Some((100, "Char1")));
+        assert_eq!(state.cycle_backward(), Some((100, "Char1")));
+    }
+
+    #[test]
+    fn test_auto_sort_keeps_current_character() {
+        let mut state = CycleState::new(vec![]);
+
+        state.add_window("Charlie".to_string(), 300);
+        state.add_window("alpha".to_string(), 100);
+        state.add_window(String::new(), 400);
+        state.add_window("Bravo".to_string(), 200);
+
+        state.apply_sort(CycleSort::Login, &[], |_| None);
+        assert_eq!(state.config_order(), ["Charlie", "alpha", "Bravo"]);
+        state.set_current("Bravo");
+
+        state.apply_sort(CycleSort::Alphabetical, &[], |_| None);
+        assert_eq!(state.config_order(), ["alpha", "Bravo", "Charlie"]);
+        assert_eq!(state.cycle_forward(), Some((300, "Charlie")));
+
+        // Left to right, then top to bottom
+        let positions = HashMap::from([("alpha", (500, 0)), ("Bravo", (0, 300)), ("Charlie", (0, 0))]);
+        state.apply_sort(CycleSort::Position, &[], |character| positions.get(character).copied());
+        assert_eq!(state.config_order(), ["Charlie", "Bravo", "alpha"]);
+        assert_eq!(state.cycle_forward(), Some((200, "Bravo")));
+    }
 }
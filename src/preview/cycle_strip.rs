@@ -0,0 +1,213 @@
+//! Cycle strip (cycle_strip)
+//!
+//! With the cycle_strip modifier held, Tab doesn't switch right away: a strip of
+//! the characters Tab visits shows mid-screen with the prospective target
+//! highlighted, and every further Tab/Shift+Tab moves the highlight. Releasing
+//! the modifier switches to it and Escape cancels, like alt-tab switchers. The
+//! keyboard is grabbed while the strip is up so the releases and Escape come
+//! to us (and Escape doesn't also reach EVE).
+
+use anyhow::{Context, Result};
+use tracing::{debug, info, warn};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+use x11rb::CURRENT_TIME;
+
+use crate::config::profile::CycleStripModifier;
+use crate::constants::input;
+use crate::preview::font::FontRenderer;
+
+use super::cycle_state::CycleTargets;
+use super::tooltip::{Placement, Tooltip};
+
+fn modifier_mask(modifier: CycleStripModifier) -> KeyButMask {
+    match modifier {
+        CycleStripModifier::Alt => KeyButMask::MOD1,
+        CycleStripModifier::Ctrl => KeyButMask::CONTROL,
+        CycleStripModifier::Super => KeyButMask::MOD4,
+    }
+}
+
+/// Whether the modifier is held right now, as the X server sees it
+fn modifier_held(conn: &RustConnection, root: Window, modifier: CycleStripModifier) -> Result<bool> {
+    let reply = conn.query_pointer(root)
+        .context("Failed to query modifier state")?
+        .reply()
+        .context("Failed to query modifier state")?;
+    Ok(reply.mask.contains(modifier_mask(modifier)))
+}
+
+/// Highlight position after one Tab (or Shift+Tab) from `from`, wrapping
+/// around or stopping at the ends like the cycle does
+fn step_index(len: usize, from: Option<usize>, backward: bool, wrap: bool) -> usize {
+    match (from, backward) {
+        (None, false) => 0,
+        (None, true) => len - 1,
+        (Some(i), false) if !wrap => (i + 1).min(len - 1),
+        (Some(i), true) if !wrap => i.saturating_sub(1),
+        (Some(i), false) => (i + 1) % len,
+        (Some(i), true) => (i + len - 1) % len,
+    }
+}
+
+#[derive(Debug)]
+struct Pending {
+    /// Strip contents in cycle order
+    characters: Vec<String>,
+    selected: usize,
+    wrap: bool,
+    modifier: CycleStripModifier,
+    root: Window,
+    strip: Option<Tooltip>,
+}
+
+#[derive(Debug, Default)]
+pub struct CycleStrip {
+    pending: Option<Pending>,
+}
+
+impl CycleStrip {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn active(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Whether a Tab press should open the strip: its modifier is held
+    pub fn wanted(conn: &RustConnection, root: Window, modifier: CycleStripModifier) -> bool {
+        modifier_held(conn, root, modifier)
+            .inspect_err(|e| warn!(error = ?e, "Failed to check cycle strip modifier"))
+            .unwrap_or(false)
+    }
+
+    /// Tab pressed with the modifier held: show the strip of `targets` with the
+    /// next target highlighted
+    /// Returns a character to switch to right away when the strip can't stay up
+    /// (the keyboard is grabbed elsewhere, or the modifier was already released)
+    pub fn start(
+        &mut self,
+        conn: &RustConnection,
+        screen: &Screen,
+        font_renderer: &FontRenderer,
+        targets: CycleTargets,
+        backward: bool,
+        modifier: CycleStripModifier,
+    ) -> Result<Option<String>> {
+        self.finish(conn);
+        let CycleTargets { characters, current, wrap } = targets;
+        if characters.is_empty() {
+            return Ok(None);
+        }
+        let selected = step_index(characters.len(), current, backward, wrap);
+        let keyboard_grabbed = conn.grab_keyboard(false, screen.root, CURRENT_TIME, GrabMode::ASYNC, GrabMode::ASYNC)
+            .context("Failed to grab keyboard for cycle strip")?
+            .reply()
+            .context("Failed to grab keyboard for cycle strip")?
+            .status == GrabStatus::SUCCESS;
+        if !keyboard_grabbed {
+            // The modifier release would never reach us: plain Tab behaviour
+            warn!("Keyboard is grabbed by another client, switching without the cycle strip");
+            return Ok(characters.into_iter().nth(selected));
+        }
+        info!(characters = characters.len(), selected = %characters[selected], "Cycle strip opened");
+        self.pending = Some(Pending { characters, selected, wrap, modifier, root: screen.root, strip: None });
+        self.redraw(conn, screen, font_renderer);
+        // Released between the Tab press and the grab: no release event is coming
+        if !modifier_held(conn, screen.root, modifier)? {
+            return Ok(self.commit(conn));
+        }
+        Ok(None)
+    }
+
+    /// Another Tab (or Shift+Tab) while the strip is up: move the highlight
+    pub fn step(&mut self, conn: &RustConnection, screen: &Screen, font_renderer: &FontRenderer, backward: bool) {
+        let Some(pending) = self.pending.as_mut() else {
+            return;
+        };
+        pending.selected = step_index(pending.characters.len(), Some(pending.selected), backward, pending.wrap);
+        debug!(selected = %pending.characters[pending.selected], "Cycle strip moved");
+        self.redraw(conn, screen, font_renderer);
+    }
+
+    fn redraw(&mut self, conn: &RustConnection, screen: &Screen, font_renderer: &FontRenderer) {
+        let Some(pending) = self.pending.as_mut() else {
+            return;
+        };
+        if let Some(strip) = pending.strip.take() {
+            let _ = strip.close(conn)
+                .inspect_err(|e| warn!(error = ?e, "Failed to close cycle strip"));
+        }
+        pending.strip = Tooltip::show_strip(conn, screen, font_renderer, &pending.characters, pending.selected, Placement::Centered)
+            .inspect_err(|e| warn!(error = ?e, "Failed to show cycle strip"))
+            .ok();
+    }
+
+    /// A key pressed while the keyboard is grabbed: Escape cancels, the rest
+    /// (Tab comes through the hotkey listener) is ignored
+    pub fn key_press(&mut self, conn: &RustConnection, keycode: Keycode) {
+        if (keycode as u16).checked_sub(input::X_KEYCODE_OFFSET) == Some(input::KEY_ESC) {
+            info!("Cycle strip cancelled");
+            self.finish(conn);
+        }
+    }
+
+    /// A key released while the keyboard is grabbed: the character to switch to
+    /// once the modifier is let go
+    pub fn key_release(&mut self, conn: &RustConnection) -> Option<String> {
+        let pending = self.pending.as_ref()?;
+        let held = modifier_held(conn, pending.root, pending.modifier)
+            .inspect_err(|e| warn!(error = ?e, "Failed to check cycle strip modifier"))
+            .unwrap_or(false);
+        if held {
+            return None;
+        }
+        self.commit(conn)
+    }
+
+    fn commit(&mut self, conn: &RustConnection) -> Option<String> {
+        let picked = self.pending.as_ref().map(|pending| pending.characters[pending.selected].clone());
+        info!(character = ?picked, "Cycle strip committed");
+        self.finish(conn);
+        picked
+    }
+
+    fn finish(&mut self, conn: &RustConnection) {
+        let Some(pending) = self.pending.take() else {
+            return;
+        };
+        let _ = conn.ungrab_keyboard(CURRENT_TIME)
+            .inspect_err(|e| warn!(error = ?e, "Failed to release keyboard after cycle strip"));
+        if let Some(strip) = pending.strip {
+            let _ = strip.close(conn)
+                .inspect_err(|e| warn!(error = ?e, "Failed to close cycle strip"));
+        }
+        let _ = conn.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_index_wraps() {
+        assert_eq!(step_index(3, Some(0), false, true), 1);
+        assert_eq!(step_index(3, Some(2), false, true), 0);
+        assert_eq!(step_index(3, Some(0), true, true), 2);
+        // Current character isn't in the strip: start from an end
+        assert_eq!(step_index(3, None, false, true), 0);
+        assert_eq!(step_index(3, None, true, true), 2);
+        // cycle_wrap off: stop at the ends
+        assert_eq!(step_index(3, Some(2), false, false), 2);
+        assert_eq!(step_index(3, Some(0), true, false), 0);
+    }
+
+    #[test]
+    fn test_modifier_masks() {
+        assert_eq!(modifier_mask(CycleStripModifier::Alt), KeyButMask::MOD1);
+        assert_eq!(modifier_mask(CycleStripModifier::Super), KeyButMask::MOD4);
+    }
+}
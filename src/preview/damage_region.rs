@@ -0,0 +1,66 @@
+//! Mapping client damage onto the scaled thumbnail
+//!
+//! With raw-rectangle damage reports most frames only touch part of the client
+//! (a chat line, a module timer). The thumbnail window keeps what was scaled
+//! into it last time, so only the part of it covering the damaged rectangle has
+//! to be scaled again. Anything that changes the transform (client or thumbnail
+//! resized) still needs a full redraw.
+
+use x11rb::protocol::xproto::Rectangle;
+
+use crate::types::Dimensions;
+
+use super::snapping::Rect;
+
+/// Thumbnail region showing `area` of a `source`-sized client scaled to `target`
+/// Rounded outwards with a pixel of margin so no changed pixel is left stale;
+/// None if the damage is outside the client or either size is empty
+pub fn scaled_region(area: Rectangle, source: Dimensions, target: Dimensions) -> Option<Rect> {
+    if source.width == 0 || source.height == 0 || target.width == 0 || target.height == 0 {
+        return None;
+    }
+    let scale_x = target.width as f32 / source.width as f32;
+    let scale_y = target.height as f32 / source.height as f32;
+
+    let left = ((area.x as f32 * scale_x).floor() as i32 - 1).max(0);
+    let top = ((area.y as f32 * scale_y).floor() as i32 - 1).max(0);
+    let right = (((area.x as i32 + area.width as i32) as f32 * scale_x).ceil() as i32 + 1).min(target.width as i32);
+    let bottom = (((area.y as i32 + area.height as i32) as f32 * scale_y).ceil() as i32 + 1).min(target.height as i32);
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some(Rect {
+        x: left as i16,
+        y: top as i16,
+        width: (right - left) as u16,
+        height: (bottom - top) as u16,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(x: i16, y: i16, width: u16, height: u16) -> Rectangle {
+        Rectangle { x, y, width, height }
+    }
+
+    #[test]
+    fn test_scaled_region_rounds_outwards() {
+        let source = Dimensions::new(1920, 1080);
+        let target = Dimensions::new(240, 135);
+        // 8:1 scale, 100..200 maps to 12.5..25 → 12..25 plus the margin
+        let region = scaled_region(area(100, 100, 100, 100), source, target).unwrap();
+        assert_eq!((region.x, region.y, region.width, region.height), (11, 11, 15, 15));
+    }
+
+    #[test]
+    fn test_scaled_region_clamps_to_thumbnail() {
+        let source = Dimensions::new(1920, 1080);
+        let target = Dimensions::new(240, 135);
+        let full = scaled_region(area(0, 0, 1920, 1080), source, target).unwrap();
+        assert_eq!((full.x, full.y, full.width, full.height), (0, 0, 240, 135));
+        assert!(scaled_region(area(2000, 0, 10, 10), source, target).is_none());
+        assert!(scaled_region(area(0, 0, 10, 10), Dimensions::new(0, 0), target).is_none());
+    }
+}
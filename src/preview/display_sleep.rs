@@ -0,0 +1,100 @@
+//! Refresh after the displays slept (DPMS)
+//!
+//! Some drivers and compositors leave previews black once monitors come
+//! back from DPMS standby/suspend/off. The X server sends no event for DPMS
+//! changes, so its power level is polled every few seconds and every
+//! thumbnail is rebound (like after a resume) when the displays wake up.
+//! Without the DPMS extension, the first frame after a long gap in which no
+//! client drew anything counts as waking up instead.
+
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+use x11rb::protocol::dpms::{ConnectionExt as DpmsExt, DPMSMode};
+use x11rb::rust_connection::RustConnection;
+
+use crate::constants::display_sleep::{DAMAGE_GAP_SECS, POLL_SECS};
+
+#[derive(Debug)]
+pub struct DisplaySleep {
+    /// The server answers DPMS queries; otherwise damage gaps are used
+    dpms: bool,
+    asleep: bool,
+    next_poll: Instant,
+    /// Last frame from any client (fallback without DPMS)
+    last_damage: Option<Instant>,
+}
+
+/// Displays are on: DPMS disabled, or enabled at power level On
+fn displays_on(conn: &RustConnection) -> Option<bool> {
+    let info = conn.dpms_info().ok()?.reply().ok()?;
+    Some(!info.state || info.power_level == DPMSMode::ON)
+}
+
+impl DisplaySleep {
+    pub fn new(conn: &RustConnection) -> Self {
+        let dpms = displays_on(conn).is_some();
+        if !dpms {
+            info!("DPMS unavailable, a long pause in client frames counts as the displays waking up");
+        }
+        Self { dpms, asleep: false, next_poll: Instant::now(), last_damage: None }
+    }
+
+    /// When the power level is due to be checked again (None without DPMS, or
+    /// without thumbnails that would need rebinding, so an idle daemon can block)
+    pub fn deadline(&self, thumbnails: bool) -> Option<Instant> {
+        (self.dpms && thumbnails).then_some(self.next_poll)
+    }
+
+    /// Check the power level if due; true when the displays just woke up
+    pub fn poll(&mut self, conn: &RustConnection, now: Instant) -> bool {
+        if !self.dpms || now < self.next_poll {
+            return false;
+        }
+        self.next_poll = now + Duration::from_secs(POLL_SECS);
+        displays_on(conn).is_some_and(|on| self.observe(on))
+    }
+
+    /// Follow the power level; true on the change from asleep to on
+    fn observe(&mut self, on: bool) -> bool {
+        let woke = self.asleep && on;
+        if self.asleep == on {
+            debug!(on = on, "Display power level changed");
+        }
+        self.asleep = !on;
+        woke
+    }
+
+    /// A client drew a frame; true if it ends a long gap and DPMS can't tell
+    pub fn damage(&mut self, now: Instant) -> bool {
+        let gap = self.last_damage
+            .is_some_and(|last| now.duration_since(last) >= Duration::from_secs(DAMAGE_GAP_SECS));
+        self.last_damage = Some(now);
+        gap && !self.dpms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wakes_once_per_sleep() {
+        let start = Instant::now();
+        let mut watch = DisplaySleep { dpms: true, asleep: false, next_poll: start, last_damage: None };
+        // Nothing to rebind without thumbnails, so nothing to wake up for
+        assert_eq!(watch.deadline(false), None);
+        assert_eq!(watch.deadline(true), Some(start));
+        assert!(!watch.observe(true));
+        assert!(!watch.observe(false));
+        assert!(!watch.observe(false));
+        assert!(watch.observe(true));
+        assert!(!watch.observe(true));
+        // Damage gaps only count without DPMS
+        watch.damage(start);
+        assert!(!watch.damage(start + Duration::from_secs(DAMAGE_GAP_SECS)));
+
+        watch.dpms = false;
+        assert!(!watch.damage(start + Duration::from_secs(DAMAGE_GAP_SECS + 1)));
+        assert!(watch.damage(start + Duration::from_secs(DAMAGE_GAP_SECS * 3)));
+    }
+}
@@ -0,0 +1,259 @@
+//! Guide lines and ghost outline shown while dragging a thumbnail
+//!
+//! Each line is a thin override-redirect window filled with its background
+//! pixel, so the guides don't need a compositor or ARGB visual. Lines mark the
+//! edges of every snap target and the work area left after screen margins.
+//! Snapping has no grid, so there are no grid lines to draw.
+//!
+//! With `drag_ghost` enabled, the thumbnail itself stays put during a drag and
+//! only a four-line outline follows the pointer until release. The same
+//! outline marks thumbnails selected for a group move.
+
+use anyhow::{Context, Result};
+use tracing::{debug, error};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as WrapperExt;
+
+use crate::constants::{drag_guides, x11};
+use crate::types::Position;
+use crate::x11_utils::AppContext;
+
+use super::snapping::Rect;
+
+/// Line geometry and color for every guide (snap target edges, then work area)
+fn guide_lines(targets: &[Rect], area: Rect, screen: Rect) -> Vec<(Rect, u32)> {
+    let thickness = drag_guides::LINE_WIDTH;
+    let vertical = |x: i16| Rect { x, y: screen.y, width: thickness, height: screen.height };
+    let horizontal = |y: i16| Rect { x: screen.x, y, width: screen.width, height: thickness };
+
+    let mut xs: Vec<i16> = targets.iter().flat_map(|t| [t.left(), t.right()]).collect();
+    let mut ys: Vec<i16> = targets.iter().flat_map(|t| [t.top(), t.bottom()]).collect();
+    xs.sort_unstable();
+    xs.dedup();
+    ys.sort_unstable();
+    ys.dedup();
+
+    let mut lines: Vec<(Rect, u32)> = xs.into_iter().map(|x| (vertical(x), drag_guides::TARGET_COLOR))
+        .chain(ys.into_iter().map(|y| (horizontal(y), drag_guides::TARGET_COLOR)))
+        .collect();
+
+    // Work-area edges that coincide with the screen edge aren't worth drawing
+    let area_right = area.right().saturating_sub(thickness as i16);
+    let area_bottom = area.bottom().saturating_sub(thickness as i16);
+    if area.left() > screen.left() {
+        lines.push((vertical(area.left()), drag_guides::AREA_COLOR));
+    }
+    if area.right() < screen.right() {
+        lines.push((vertical(area_right), drag_guides::AREA_COLOR));
+    }
+    if area.top() > screen.top() {
+        lines.push((horizontal(area.top()), drag_guides::AREA_COLOR));
+    }
+    if area.bottom() < screen.bottom() {
+        lines.push((horizontal(area_bottom), drag_guides::AREA_COLOR));
+    }
+    lines
+}
+
+/// Create and map one solid line window (caller flushes)
+fn create_line(ctx: &AppContext, line: Rect, color: u32) -> Result<Window> {
+    let window = ctx.conn.generate_id()
+        .context("Failed to generate X11 window ID for drag guide")?;
+    ctx.conn.create_window(
+        ctx.screen.root_depth,
+        window,
+        ctx.screen.root,
+        line.x,
+        line.y,
+        line.width,
+        line.height,
+        0,
+        WindowClass::INPUT_OUTPUT,
+        ctx.screen.root_visual,
+        &CreateWindowAux::new()
+            .override_redirect(x11::OVERRIDE_REDIRECT)
+            .background_pixel(color),
+    )
+    .context("Failed to create drag guide window")?;
+
+    // Tag with our PID so CreateNotify skips it like a thumbnail
+    ctx.conn.change_property32(
+        PropMode::REPLACE,
+        window,
+        ctx.atoms.net_wm_pid,
+        AtomEnum::CARDINAL,
+        &[std::process::id()],
+    )
+    .context(format!("Failed to set _NET_WM_PID on drag guide {}", window))?;
+    ctx.conn.map_window(window)
+        .context(format!("Failed to map drag guide {}", window))?;
+    Ok(window)
+}
+
+/// Map guide windows for a drag and return them (pass to `hide` when the drag ends)
+pub fn show(ctx: &AppContext, targets: &[Rect], area: Rect) -> Result<Vec<Window>> {
+    let screen = Rect {
+        x: 0,
+        y: 0,
+        width: ctx.screen.width_in_pixels,
+        height: ctx.screen.height_in_pixels,
+    };
+
+    let mut windows = Vec::new();
+    for (line, color) in guide_lines(targets, area, screen) {
+        if line.width == 0 || line.height == 0 {
+            continue;
+        }
+        windows.push(create_line(ctx, line, color)?);
+    }
+    ctx.conn.flush()
+        .context("Failed to flush X11 connection after showing drag guides")?;
+
+    debug!(count = windows.len(), "Showing drag guides");
+    Ok(windows)
+}
+
+/// Outline drawn over a thumbnail (drag ghost or selection marker)
+#[derive(Debug)]
+pub struct Outline {
+    /// Top, bottom, left and right edge windows
+    pub windows: Vec<Window>,
+    /// Top-left corner (for a ghost: where the thumbnail lands on release)
+    pub position: Position,
+    width: u16,
+    height: u16,
+}
+
+/// Edges of an outline around `rect`, in the order stored in `Outline::windows`
+fn outline_edges(rect: Rect) -> [Rect; 4] {
+    let thickness = drag_guides::GHOST_WIDTH.min(rect.width).min(rect.height);
+    let right = rect.right().saturating_sub(thickness as i16);
+    let bottom = rect.bottom().saturating_sub(thickness as i16);
+    [
+        Rect { x: rect.x, y: rect.y, width: rect.width, height: thickness },
+        Rect { x: rect.x, y: bottom, width: rect.width, height: thickness },
+        Rect { x: rect.x, y: rect.y, width: thickness, height: rect.height },
+        Rect { x: right, y: rect.y, width: thickness, height: rect.height },
+    ]
+}
+
+impl Outline {
+    /// Map an outline over the thumbnail's current rectangle
+    pub fn show(ctx: &AppContext, rect: Rect, color: u32) -> Result<Self> {
+        let windows = outline_edges(rect)
+            .into_iter()
+            .map(|edge| create_line(ctx, edge, color))
+            .collect::<Result<Vec<_>>>()?;
+        ctx.conn.flush()
+            .context("Failed to flush X11 connection after showing drag outline")?;
+        Ok(Self {
+            windows,
+            position: Position::new(rect.x, rect.y),
+            width: rect.width,
+            height: rect.height,
+        })
+    }
+
+    /// Move the outline so its top-left corner is at (x, y)
+    pub fn move_to(&mut self, conn: &RustConnection, x: i16, y: i16) -> Result<()> {
+        let rect = Rect { x, y, width: self.width, height: self.height };
+        for (window, edge) in self.windows.iter().zip(outline_edges(rect)) {
+            conn.configure_window(*window, &ConfigureWindowAux::new().x(edge.x as i32).y(edge.y as i32))
+                .context(format!("Failed to move drag outline to ({}, {})", x, y))?;
+        }
+        conn.flush()
+            .context("Failed to flush X11 connection after moving drag outline")?;
+        self.position = Position::new(x, y);
+        Ok(())
+    }
+
+    /// Move and resize the outline to frame `rect`
+    pub fn reshape(&mut self, conn: &RustConnection, rect: Rect) -> Result<()> {
+        for (window, edge) in self.windows.iter().zip(outline_edges(rect)) {
+            let aux = ConfigureWindowAux::new()
+                .x(edge.x as i32)
+                .y(edge.y as i32)
+                .width(edge.width.max(1) as u32)
+                .height(edge.height.max(1) as u32);
+            conn.configure_window(*window, &aux)
+                .context(format!("Failed to reshape outline window {}", window))?;
+        }
+        conn.flush()
+            .context("Failed to flush X11 connection after reshaping outline")?;
+        self.position = Position::new(rect.x, rect.y);
+        self.width = rect.width;
+        self.height = rect.height;
+        Ok(())
+    }
+
+    /// Put the outline back on top after its thumbnail was raised (caller flushes)
+    pub fn raise(&self, conn: &RustConnection) -> Result<()> {
+        for window in &self.windows {
+            conn.configure_window(*window, &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE))
+                .context(format!("Failed to raise outline window {}", window))?;
+        }
+        Ok(())
+    }
+}
+
+/// Destroy the guide windows created by `show`
+pub fn hide(conn: &RustConnection, windows: &mut Vec<Window>) {
+    if windows.is_empty() {
+        return;
+    }
+    for window in windows.drain(..) {
+        if let Err(e) = conn.destroy_window(window) {
+            error!(window = window, error = %e, "Failed to destroy drag guide");
+        }
+    }
+    if let Err(e) = conn.flush() {
+        error!(error = %e, "Failed to flush X11 connection after hiding drag guides");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Rect = Rect { x: 0, y: 0, width: 1920, height: 1080 };
+
+    #[test]
+    fn test_target_edges_become_lines() {
+        let target = Rect { x: 100, y: 200, width: 50, height: 40 };
+        let lines = guide_lines(&[target], SCREEN, SCREEN);
+        let xs: Vec<i16> = lines.iter().filter(|(r, _)| r.height == SCREEN.height).map(|(r, _)| r.x).collect();
+        let ys: Vec<i16> = lines.iter().filter(|(r, _)| r.width == SCREEN.width).map(|(r, _)| r.y).collect();
+        assert_eq!(xs, vec![100, 150]);
+        assert_eq!(ys, vec![200, 240]);
+    }
+
+    #[test]
+    fn test_shared_edges_are_deduplicated() {
+        let a = Rect { x: 0, y: 0, width: 50, height: 50 };
+        let b = Rect { x: 50, y: 0, width: 50, height: 50 };
+        let lines = guide_lines(&[a, b], SCREEN, SCREEN);
+        assert_eq!(lines.len(), 3 + 2);
+    }
+
+    #[test]
+    fn test_outline_edges_frame_rect() {
+        let rect = Rect { x: 10, y: 20, width: 100, height: 50 };
+        let [top, bottom, left, right] = outline_edges(rect);
+        let t = drag_guides::GHOST_WIDTH as i16;
+        assert_eq!((top.x, top.y, top.width), (10, 20, 100));
+        assert_eq!(bottom.y, 70 - t);
+        assert_eq!((left.x, left.height), (10, 50));
+        assert_eq!(right.x, 110 - t);
+    }
+
+    #[test]
+    fn test_work_area_only_drawn_inside_screen() {
+        let area = Rect { x: 0, y: 40, width: 1920, height: 1040 };
+        let lines = guide_lines(&[], area, SCREEN);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].0.y, 40);
+        assert_eq!(lines[0].1, drag_guides::AREA_COLOR);
+    }
+}
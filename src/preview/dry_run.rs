@@ -0,0 +1,143 @@
+//! Dry run (--dry-run): log the thumbnails the daemon would create, create none
+//!
+//! Connects to X11 and reads the saved config like a daemon started by the GUI
+//! would get it, then walks the client windows with the same checks and
+//! placement rules as the initial scan. Nothing is mapped, moved or redirected.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use tracing::{info, warn};
+use x11rb::protocol::xproto::*;
+
+use crate::config::PersistentState;
+use crate::config::profile::ScreenMargins;
+use crate::constants::positioning;
+use crate::types::{Dimensions, Position};
+use crate::x11_utils::{is_window_eve, is_window_minimized, screen_of_window, CachedAtoms};
+
+use super::session_state::SessionState;
+use super::snapping;
+use super::{client_windows, thumbnail_dimensions, window_owner, WindowOwner};
+
+/// Where a new thumbnail goes: its saved position, or next to the client inside the screen margins
+fn planned_position(
+    saved: Option<Position>,
+    source: Position,
+    dimensions: Dimensions,
+    screen: (u16, u16),
+    margins: &ScreenMargins,
+) -> Position {
+    if let Some(position) = saved {
+        return position;
+    }
+    let area = snapping::safe_area(screen.0, screen.1, margins);
+    snapping::clamp_into(
+        snapping::Rect {
+            x: source.x + positioning::DEFAULT_SPAWN_OFFSET,
+            y: source.y + positioning::DEFAULT_SPAWN_OFFSET,
+            width: dimensions.width,
+            height: dimensions.height,
+        },
+        area,
+    )
+}
+
+pub fn run_dry_run() -> Result<()> {
+    let (conn, _) = x11rb::connect(None)
+        .context("Failed to connect to X11 server. Is DISPLAY set correctly?")?;
+    let atoms = CachedAtoms::new(&conn)
+        .context("Failed to cache X11 atoms")?;
+    let persistent_state = PersistentState::load();
+    let title_pattern = persistent_state.title_pattern();
+    let session_state = SessionState::new();
+    info!(profile = %persistent_state.profile.name, "Dry run: no windows will be created");
+
+    let mut seen = HashSet::new();
+    let mut planned = 0;
+    let mut skipped = 0;
+    for window in client_windows(&conn, &atoms)? {
+        match window_owner(&conn, window)? {
+            WindowOwner::Ours => continue,
+            WindowOwner::Native => {
+                skipped += 1;
+                continue;
+            }
+            WindowOwner::Wine(_) => {}
+        }
+        let Some(eve_window) = is_window_eve(&conn, window, &atoms, &title_pattern)
+            .context(format!("Failed to check if window {} is EVE client", window))?
+        else {
+            skipped += 1;
+            continue;
+        };
+        let character_name = eve_window.character_name().to_string();
+        if !seen.insert(character_name.clone()) {
+            warn!(window = window, character = %character_name, "Character name seen twice, the daemon would keep it on the newest client");
+        }
+
+        let screen = screen_of_window(&conn, window)
+            .context(format!("Failed to find screen of EVE window {} ('{}')", window, character_name))?;
+        let geom = conn.get_geometry(window)
+            .context(format!("Failed to query geometry of window {}", window))?
+            .reply()
+            .context(format!("Failed to get geometry of window {}", window))?;
+        let saved = session_state.get_position(
+            &character_name,
+            window,
+            &persistent_state.character_positions,
+            persistent_state.global.preserve_thumbnail_position_on_swap,
+        );
+        let dimensions = thumbnail_dimensions(&persistent_state, &character_name, screen);
+        let position = planned_position(
+            saved,
+            Position::new(geom.x, geom.y),
+            dimensions,
+            (screen.width_in_pixels, screen.height_in_pixels),
+            &persistent_state.global.screen_margins,
+        );
+        let hidden = persistent_state.configured_character(&character_name)
+            .is_some_and(|settings| settings.preview_hidden);
+        let minimized = is_window_minimized(&conn, window, &atoms)
+            .context(format!("Failed to query minimized state for window {}", window))?;
+
+        info!(
+            window = window,
+            character = %character_name,
+            x = position.x,
+            y = position.y,
+            width = dimensions.width,
+            height = dimensions.height,
+            source = if saved.is_some() { "saved" } else { "auto" },
+            hidden = hidden,
+            minimized = minimized,
+            "Would create thumbnail"
+        );
+        planned += 1;
+    }
+
+    info!(thumbnails = planned, skipped_windows = skipped, "Dry run finished");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_planned_position_prefers_saved() {
+        let saved = Position::new(-50, 3000);
+        let position = planned_position(Some(saved), Position::new(0, 0), Dimensions::new(240, 135), (1920, 1080), &ScreenMargins::default());
+        assert_eq!(position, saved);
+    }
+
+    #[test]
+    fn test_planned_position_offsets_and_clamps() {
+        let margins = ScreenMargins::default();
+        let position = planned_position(None, Position::new(100, 100), Dimensions::new(240, 135), (1920, 1080), &margins);
+        assert_eq!(position, Position::new(100 + positioning::DEFAULT_SPAWN_OFFSET, 100 + positioning::DEFAULT_SPAWN_OFFSET));
+        // Client at the bottom right: the thumbnail stays on screen
+        let position = planned_position(None, Position::new(1900, 1060), Dimensions::new(240, 135), (1920, 1080), &margins);
+        assert_eq!(position, Position::new(1680, 945));
+    }
+}
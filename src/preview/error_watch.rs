@@ -0,0 +1,99 @@
+//! Per-thumbnail X error tracking and self-healing rebuilds
+//!
+//! Drawing requests are sent unchecked, so a thumbnail whose picture or pixmap
+//! went bad (e.g. after a driver reset or a client pixmap swap the rebind
+//! didn't catch) fails quietly with an error event on every frame and shows a
+//! black rectangle until restart. Errors are attributed to the thumbnail
+//! owning the failing resource; one that keeps erroring within the window is
+//! destroyed and recreated from scratch.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use x11rb::protocol::xproto::Window;
+
+use crate::constants::x_errors;
+
+#[derive(Debug)]
+pub struct ErrorWatch {
+    window: Duration,
+    threshold: usize,
+    /// Recent errors per source window, oldest first
+    recent: HashMap<Window, VecDeque<Instant>>,
+    /// Errors per source window since the daemon started (kept across rebuilds)
+    totals: HashMap<Window, u64>,
+}
+
+impl Default for ErrorWatch {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(x_errors::WINDOW_SECS),
+            threshold: x_errors::REBUILD_THRESHOLD,
+            recent: HashMap::new(),
+            totals: HashMap::new(),
+        }
+    }
+}
+
+impl ErrorWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An X error hit a resource of the thumbnail for `window`
+    /// Returns true once the thumbnail errored often enough to be rebuilt
+    pub fn record(&mut self, window: Window, now: Instant) -> bool {
+        *self.totals.entry(window).or_default() += 1;
+        let recent = self.recent.entry(window).or_default();
+        recent.push_back(now);
+        while recent.front().is_some_and(|error| now.duration_since(*error) > self.window) {
+            recent.pop_front();
+        }
+        if recent.len() < self.threshold {
+            return false;
+        }
+        recent.clear();
+        true
+    }
+
+    /// Errors seen for `window` since the daemon started
+    pub fn total(&self, window: Window) -> u64 {
+        self.totals.get(&window).copied().unwrap_or(0)
+    }
+
+    /// Drop state for a client window that no longer exists
+    pub fn forget(&mut self, window: Window) {
+        self.recent.remove(&window);
+        self.totals.remove(&window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebuild_after_threshold_within_window() {
+        let mut watch = ErrorWatch::new();
+        let start = Instant::now();
+        for i in 0..x_errors::REBUILD_THRESHOLD - 1 {
+            assert!(!watch.record(1, start + Duration::from_millis(i as u64)));
+        }
+        assert!(watch.record(1, start + Duration::from_secs(1)));
+        // Counting starts over after a rebuild, the total keeps going
+        assert!(!watch.record(1, start + Duration::from_secs(2)));
+        assert_eq!(watch.total(1), x_errors::REBUILD_THRESHOLD as u64 + 1);
+    }
+
+    #[test]
+    fn test_spread_out_errors_dont_rebuild() {
+        let mut watch = ErrorWatch::new();
+        let start = Instant::now();
+        for i in 0..x_errors::REBUILD_THRESHOLD * 2 {
+            let at = start + Duration::from_secs(i as u64 * x_errors::WINDOW_SECS);
+            assert!(!watch.record(1, at));
+        }
+        watch.forget(1);
+        assert_eq!(watch.total(1), 0);
+    }
+}
@@ -1,26 +1,37 @@
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use x11rb::connection::Connection;
-use x11rb::protocol::damage::ConnectionExt as DamageExt;
+use x11rb::protocol::damage::{ConnectionExt as DamageExt, Damage};
 use x11rb::protocol::Event::{self, CreateNotify, DamageNotify, DestroyNotify, PropertyNotify};
 use x11rb::protocol::xproto::*;
 use tracing::{debug, info, trace, warn};
 
 use crate::config::PersistentState;
-use crate::constants::mouse;
+use crate::config::profile::{ClientSwitchAction, MqttSettings, OverlapAvoidance, RuleTrigger};
+use crate::constants::drag_guides::{GHOST_COLOR, SELECTION_COLOR};
+use crate::constants::{mouse, redaction, x11};
 use crate::ipc::PreviewResponse;
+use crate::mqtt::MqttEvent;
 use crate::preview::ipc_handler::ClientConnection;
-use crate::types::{Position, ThumbnailState, CharacterSettings};
-use crate::x11_utils::{is_window_eve, minimize_window, AppContext};
+use crate::types::{Dimensions, Position, ThumbnailState, CharacterSettings};
+use crate::x11_utils::{apply_switch_action, focus_window_directly, is_window_eve, keycode_for_keysym, lower_window, root_geometry, set_window_above, unminimize_window, window_manager_running, AppContext};
 
+use super::borderless;
+use super::drag_guides::{self, Outline};
+use super::foreign_toplevel;
+use super::lifecycle::{LifecycleAction, LifecycleEvent};
+use super::redaction::{self as redact, RedactDraw};
+use super::window_rules;
 use super::cycle_state::CycleState;
 use super::session_state::SessionState;
 use super::snapping::{self, Rect};
+use super::stacking_rules;
 use super::thumbnail::Thumbnail;
 
 /// Send position update event to GUI via IPC
-fn send_position_changed(
+pub(super) fn send_position_changed(
     ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
     character_name: &str,
     x: i16,
@@ -75,6 +86,133 @@ fn send_character_added(
     }
 }
 
+/// Send the characters that already have thumbnails to a newly connected GUI
+pub(super) fn send_live_characters(
+    ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+    eves: &HashMap<Window, Thumbnail>,
+) {
+    if let Some(client) = ipc_client {
+        let mut characters: Vec<String> = eves.values()
+            .map(|t| t.character_name.clone())
+            .filter(|name| !name.is_empty())
+            .collect();
+        characters.sort();
+        
+        if let Ok(mut client_lock) = client.lock() {
+            if let Err(e) = client_lock.send_response(&PreviewResponse::LiveCharacters(characters)) {
+                warn!(error = ?e, "Failed to send LiveCharacters via IPC");
+            } else {
+                debug!("Sent LiveCharacters via IPC");
+            }
+        }
+    }
+}
+
+/// Tell the GUI whether privacy mode is on (tray checkmark)
+pub(super) fn send_privacy_changed(
+    ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+    enabled: bool,
+) {
+    if let Some(client) = ipc_client
+        && let Ok(mut client_lock) = client.lock()
+        && let Err(e) = client_lock.send_response(&PreviewResponse::PrivacyChanged(enabled))
+    {
+        warn!(error = ?e, "Failed to send PrivacyChanged via IPC");
+    }
+}
+
+/// Feed a lifecycle event to a thumbnail and carry out the resulting login/logout actions
+/// Duplicates don't publish logouts: the character is still online in the newer client
+pub(super) fn advance_lifecycle(
+    ctx: &AppContext,
+    persistent_state: &PersistentState,
+    thumbnail: &mut Thumbnail,
+    event: LifecycleEvent,
+) {
+    for action in thumbnail.lifecycle.transition(event) {
+        debug!(window = thumbnail.src, action = ?action, state = ?thumbnail.lifecycle, "Client lifecycle transition");
+        match action {
+            LifecycleAction::Login(name) => {
+                ctx.mqtt.publish(&persistent_state.global.mqtt, MqttEvent::CharacterOnline(name));
+            }
+            LifecycleAction::Logout(name) if thumbnail.duplicate_of.is_none() => {
+                ctx.mqtt.publish(&persistent_state.global.mqtt, MqttEvent::CharacterOffline(name));
+            }
+            LifecycleAction::Logout(_) => {}
+        }
+    }
+}
+
+/// Publish every logged-in character as online, for a broker that missed their logins
+/// (the startup scan runs before settings arrive, while MQTT still counts as off)
+pub(super) fn publish_online(ctx: &AppContext, mqtt: &MqttSettings, eves: &HashMap<Window, Thumbnail>) {
+    for thumbnail in eves.values().filter(|thumbnail| thumbnail.duplicate_of.is_none()) {
+        if let Some(name) = thumbnail.lifecycle.character() {
+            ctx.mqtt.publish(mqtt, MqttEvent::CharacterOnline(name.to_string()));
+        }
+    }
+}
+
+/// Warn the GUI that two clients claim the same character
+fn send_duplicate_character(
+    ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+    character_name: &str,
+) {
+    if let Some(client) = ipc_client
+        && let Ok(mut client_lock) = client.lock()
+        && let Err(e) = client_lock.send_response(&PreviewResponse::DuplicateCharacter(character_name.to_string()))
+    {
+        warn!(error = ?e, character = %character_name, "Failed to send DuplicateCharacter via IPC");
+    }
+}
+
+/// Another client already shows `newest`'s character (e.g. a stuck wine process next to a fresh login):
+/// the newest window keeps the name so positions and cycling follow it, older ones are relabelled
+pub(super) fn demote_duplicates(
+    eves: &mut HashMap<Window, Thumbnail>,
+    newest: Window,
+    ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+) -> Result<()> {
+    let Some(name) = eves.get(&newest)
+        .map(|t| t.character_name.clone())
+        .filter(|name| !name.is_empty())
+    else {
+        return Ok(());
+    };
+    
+    for (window, thumbnail) in eves.iter_mut().filter(|(w, t)| **w != newest && t.character_name == name) {
+        warn!(character = %name, window = *window, newest = newest, "Two clients claim the same character, keeping the newest window");
+        thumbnail.mark_duplicate()
+            .context(format!("Failed to relabel duplicate window for '{}'", name))?;
+        send_duplicate_character(ipc_client, &name);
+    }
+    Ok(())
+}
+
+/// The window owning `character_name` closed or switched character: hand the name back to a duplicate
+fn restore_duplicate(
+    persistent_state: &PersistentState,
+    eves: &mut HashMap<Window, Thumbnail>,
+    cycle_state: &mut CycleState,
+    character_name: &str,
+) -> Result<()> {
+    if character_name.is_empty() || eves.values().any(|t| t.character_name == character_name) {
+        return Ok(());
+    }
+    
+    if let Some((window, thumbnail)) = eves.iter_mut()
+        .filter(|(_, t)| t.duplicate_of.as_deref() == Some(character_name))
+        .max_by_key(|(w, _)| **w)
+    {
+        info!(character = %character_name, window = *window, "Newer client gone, restoring character name to remaining window");
+        thumbnail.restore_duplicate()
+            .context(format!("Failed to restore name of duplicate window for '{}'", character_name))?;
+        thumbnail.apply_character_settings(persistent_state.configured_character(character_name))
+            .context(format!("Failed to apply character settings for '{}'", character_name))?;
+        cycle_state.add_window(character_name.to_string(), *window);
+    }
+    Ok(())
+}
 
 /// Handle DamageNotify events - update damaged thumbnail
 #[tracing::instrument(skip(ctx, eves))]
@@ -84,7 +222,7 @@ fn handle_damage_notify(ctx: &AppContext, eves: &HashMap<Window, Thumbnail>, eve
         .values()
         .find(|thumbnail| thumbnail.damage == event.damage)
     {
-        thumbnail.update()
+        thumbnail.update_damaged(event.area, event.geometry)
             .context(format!("Failed to update thumbnail for damage event (damage={})", event.damage))?;
         ctx.conn.damage_subtract(event.damage, 0u32, 0u32)
             .context(format!("Failed to subtract damage region (damage={})", event.damage))?;
@@ -94,6 +232,24 @@ fn handle_damage_notify(ctx: &AppContext, eves: &HashMap<Window, Thumbnail>, eve
     Ok(())
 }
 
+/// Redraw the thumbnail owning `damage` and clear the damaged region
+/// Also called by the main loop for damage the frame pacer held back
+pub(super) fn redraw_damaged(ctx: &AppContext, eves: &HashMap<Window, Thumbnail>, damage: Damage) -> Result<()> {
+    // No logging - this fires every frame and would flood logs
+    if let Some(thumbnail) = eves
+        .values()
+        .find(|thumbnail| thumbnail.damage == damage)
+    {
+        thumbnail.update()
+            .context(format!("Failed to update thumbnail for damage event (damage={})", damage))?;
+        ctx.conn.damage_subtract(damage, 0u32, 0u32)
+            .context(format!("Failed to subtract damage region (damage={})", damage))?;
+        ctx.conn.flush()
+            .context("Failed to flush X11 connection after damage update")?;
+    }
+    Ok(())
+}
+
 /// Handle CreateNotify events - create thumbnail for new EVE window
 #[tracing::instrument(skip(ctx, persistent_state, eves, session_state, cycle_state, check_and_create_window, ipc_client))]
 fn handle_create_notify<'a>(
@@ -107,21 +263,19 @@ fn handle_create_notify<'a>(
     ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
 ) -> Result<()> {
     debug!(window = event.window, "CreateNotify received");
-    if let Some(thumbnail) = check_and_create_window(ctx, persistent_state, event.window, session_state)
+    if let Some(mut thumbnail) = check_and_create_window(ctx, persistent_state, event.window, session_state)
         .context(format!("Failed to check/create window for new window {}", event.window))? {
         // Register with cycle state
         info!(window = event.window, character = %thumbnail.character_name, "Created thumbnail for new EVE window");
         
         // Query geometry to get actual position from X11
-        let geom = ctx.conn.get_geometry(thumbnail.window)
-            .context("Failed to query geometry for new thumbnail")?
-            .reply()
+        let geom = root_geometry(ctx.conn, thumbnail.window)
             .context("Failed to get geometry reply for new thumbnail")?;
         
         // Update in-memory state (don't save to disk - GUI will do that via IPC)
         persistent_state.character_positions.insert(
             thumbnail.character_name.clone(),
-            CharacterSettings::new(geom.x, geom.y, thumbnail.dimensions.width, thumbnail.dimensions.height),
+            CharacterSettings::new(geom.x, geom.y, thumbnail.identity.dimensions().width, thumbnail.identity.dimensions().height),
         );
         
         // Send CharacterAdded event to GUI via IPC
@@ -130,42 +284,90 @@ fn handle_create_notify<'a>(
             &thumbnail.character_name,
             geom.x,
             geom.y,
-            thumbnail.dimensions.width,
-            thumbnail.dimensions.height,
+            thumbnail.identity.dimensions().width,
+            thumbnail.identity.dimensions().height,
         );
         
+        let character_name = thumbnail.character_name.clone();
+        advance_lifecycle(ctx, persistent_state, &mut thumbnail, LifecycleEvent::Detected(&character_name));
+        
+        let _ = window_rules::apply_matching(&ctx.on_screen(thumbnail.screen), &session_state.window_rules, RuleTrigger::Map, event.window, &thumbnail.character_name)
+            .inspect_err(|e| warn!(window = event.window, error = ?e, "Failed to apply window rules for new window, keeping the thumbnail"));
+        
         cycle_state.add_window(thumbnail.character_name.clone(), event.window);
         eves.insert(event.window, thumbnail);
+        demote_duplicates(eves, event.window, ipc_client)?;
+        
+        restack_thumbnails(ctx, persistent_state, eves)
+            .context("Failed to restack thumbnails after creating new thumbnail")?;
     }
     Ok(())
 }
 
 /// Handle DestroyNotify events - remove destroyed window
-#[tracing::instrument(skip(eves, cycle_state))]
+#[tracing::instrument(skip(ctx, persistent_state, eves, cycle_state))]
 fn handle_destroy_notify(
+    ctx: &AppContext,
+    persistent_state: &PersistentState,
     eves: &mut HashMap<Window, Thumbnail>,
     event: DestroyNotifyEvent,
     cycle_state: &mut CycleState,
 ) -> Result<()> {
     info!(window = event.window, "DestroyNotify received");
     cycle_state.remove_window(event.window);
-    eves.remove(&event.window);
+    if let Some(mut thumbnail) = eves.remove(&event.window) {
+        advance_lifecycle(ctx, persistent_state, &mut thumbnail, LifecycleEvent::Destroyed);
+        restore_duplicate(persistent_state, eves, cycle_state, &thumbnail.character_name)?;
+    }
     Ok(())
 }
 
+/// Whether a focus event reflects focus actually moving to/from the client
+/// Grab transitions (WM alt-tab, drags) and moves into child windows are ignored
+fn is_real_focus_change(mode: NotifyMode, detail: NotifyDetail) -> bool {
+    mode != NotifyMode::GRAB && mode != NotifyMode::UNGRAB && detail != NotifyDetail::INFERIOR
+}
+
 /// Handle FocusIn events - update focused state and visibility
-#[tracing::instrument(skip(ctx, eves))]
+#[tracing::instrument(skip(ctx, persistent_state, eves, session_state))]
 fn handle_focus_in(
     ctx: &AppContext,
+    persistent_state: &PersistentState,
     eves: &mut HashMap<Window, Thumbnail>,
     event: FocusInEvent,
+    session_state: &mut SessionState,
 ) -> Result<()> {
     debug!(window = event.event, "FocusIn received");
     if let Some(thumbnail) = eves.get_mut(&event.event) {
+        let ctx = &ctx.on_screen(thumbnail.screen);
+        if !thumbnail.state.is_focused() && !thumbnail.character_name.is_empty() {
+            ctx.mqtt.publish(&persistent_state.global.mqtt, MqttEvent::FocusGained(thumbnail.character_name.clone()));
+            session_state.activity.focused(&thumbnail.character_name, Instant::now());
+            session_state.focus_history.focused(&thumbnail.character_name);
+        }
+        
         // Transition to focused normal state (from minimized or unfocused)
+        if thumbnail.lifecycle.is_minimized() {
+            advance_lifecycle(ctx, persistent_state, thumbnail, LifecycleEvent::Restored);
+        }
         thumbnail.state = ThumbnailState::Normal { focused: true };
         thumbnail.border(true)
             .context(format!("Failed to update border on focus for '{}'", thumbnail.character_name))?;
+        
+        if is_real_focus_change(event.mode, event.detail)
+            && thumbnail.borderless_restore.is_none()
+            && persistent_state.configured_character(&thumbnail.character_name).is_some_and(|s| s.borderless_fullscreen)
+        {
+            // Not fatal: the rest of focus handling still runs, and the next focus tries again
+            thumbnail.borderless_restore = borderless::enter(ctx, event.event)
+                .inspect_err(|e| warn!(character = %thumbnail.character_name, error = ?e, "Failed to enter borderless fullscreen"))
+                .ok();
+        }
+        
+        if is_real_focus_change(event.mode, event.detail) {
+            let _ = window_rules::apply_matching(ctx, &session_state.window_rules, RuleTrigger::Focus, event.event, &thumbnail.character_name)
+                .inspect_err(|e| warn!(character = %thumbnail.character_name, error = ?e, "Failed to apply focus window rules"));
+        }
         if ctx.config.hide_when_no_focus && eves.values().any(|x| !x.state.is_visible()) {
             // Reveal all hidden thumbnails (visibility sets focused=false, so we fix the focused one after)
             for thumbnail in eves.values_mut() {
@@ -178,62 +380,134 @@ fn handle_focus_in(
                 focused_thumbnail.state = ThumbnailState::Normal { focused: true };
             }
         }
+        
+        if persistent_state.global.raise_focused_thumbnail {
+            restack_thumbnails(ctx, persistent_state, eves)
+                .context("Failed to restack thumbnails after focus change")?;
+        }
     }
     Ok(())
 }
 
 /// Handle FocusOut events - update focused state and visibility  
-#[tracing::instrument(skip(ctx, eves))]
+#[tracing::instrument(skip(ctx, persistent_state, eves, session_state))]
 fn handle_focus_out(
     ctx: &AppContext,
+    persistent_state: &PersistentState,
     eves: &mut HashMap<Window, Thumbnail>,
     event: FocusOutEvent,
+    session_state: &mut SessionState,
 ) -> Result<()> {
     debug!(window = event.event, "FocusOut received");
     if let Some(thumbnail) = eves.get_mut(&event.event) {
+        let ctx = &ctx.on_screen(thumbnail.screen);
+        if thumbnail.state.is_focused() && !thumbnail.character_name.is_empty() {
+            ctx.mqtt.publish(&persistent_state.global.mqtt, MqttEvent::FocusLost(thumbnail.character_name.clone()));
+            session_state.activity.unfocused(&thumbnail.character_name, Instant::now());
+        }
+        
         // Transition to unfocused normal state
         thumbnail.state = ThumbnailState::Normal { focused: false };
         thumbnail.border(false)
             .context(format!("Failed to clear border on focus loss for '{}'", thumbnail.character_name))?;
+        
+        if is_real_focus_change(event.mode, event.detail)
+            && let Some(saved) = thumbnail.borderless_restore.take()
+        {
+            borderless::leave(ctx, event.event, &saved)
+                .context(format!("Failed to leave borderless fullscreen for '{}'", thumbnail.character_name))?;
+        }
+        
         if ctx.config.hide_when_no_focus && eves.values().all(|x| !x.state.is_focused() && !x.state.is_minimized()) {
             for thumbnail in eves.values_mut() {
                 debug!(character = %thumbnail.character_name, "Hiding thumbnail due to focus loss");
                 thumbnail.visibility(false)
                     .context(format!("Failed to hide thumbnail '{}' on focus loss", thumbnail.character_name))?;
             }
+        } else if persistent_state.global.raise_focused_thumbnail {
+            restack_thumbnails(ctx, persistent_state, eves)
+                .context("Failed to restack thumbnails after focus loss")?;
         }
     }
     Ok(())
 }
 
 /// Handle ButtonPress events - start dragging or set current character
-#[tracing::instrument(skip(ctx, eves, cycle_state))]
+#[tracing::instrument(skip(ctx, persistent_state, eves, cycle_state))]
 fn handle_button_press(
     ctx: &AppContext,
+    persistent_state: &PersistentState,
     eves: &mut HashMap<Window, Thumbnail>,
     event: ButtonPressEvent,
     cycle_state: &mut CycleState,
 ) -> Result<()> {
     debug!(x = event.root_x, y = event.root_y, detail = event.detail, "ButtonPress received");
+    // Pointer coordinates are relative to the screen the pointer is on
+    let ctx = &ctx.on_root(event.root);
     
     // First, find which window was clicked (if any)
     let clicked_window = eves
         .iter()
-        .find(|(_, thumb)| thumb.is_hovered(event.root_x, event.root_y) && thumb.state.is_visible())
+        .find(|(_, thumb)| thumb.screen.root == event.root && thumb.is_hovered(event.root_x, event.root_y) && thumb.is_displayed())
         .map(|(win, _)| *win);
     
     let Some(clicked_window) = clicked_window else {
         return Ok(());  // No thumbnail was clicked
     };
+    if let Some(thumbnail) = eves.get_mut(&clicked_window) {
+        // The tooltip would be left behind by a drag (and is in the way of a click anyway)
+        thumbnail.hide_tooltip()?;
+        
+        // Redaction edit mode: left-drag draws an area, right-click removes one (on release)
+        if thumbnail.input_state.redact_edit {
+            if event.detail == mouse::BUTTON_LEFT {
+                start_redaction(ctx, thumbnail, Position::new(event.root_x, event.root_y))
+                    .context(format!("Failed to start drawing a redaction on '{}'", thumbnail.character_name))?;
+            }
+            return Ok(());
+        }
+    }
+    // Panel mode lays thumbnails out itself: no group selection inside the panel,
+    // and a right-drag takes the thumbnail out of it
+    let in_panel = eves.get(&clicked_window).is_some_and(|t| t.panel.is_some());
+    
+    // Ctrl+left-click toggles selection, a plain left-click clears it
+    if event.detail == mouse::BUTTON_LEFT {
+        if event.state.contains(KeyButMask::CONTROL) {
+            if in_panel {
+                return Ok(());
+            }
+            return toggle_selection(ctx, eves, clicked_window);
+        }
+        clear_selection(ctx, eves);
+    }
+    
+    // Right-dragging a selected thumbnail takes the rest of the selection along
+    let group: Vec<(Window, Position)> = if event.detail == mouse::BUTTON_RIGHT
+        && !in_panel
+        && eves.get(&clicked_window).is_some_and(|t| t.input_state.selection.is_some())
+    {
+        eves
+            .iter()
+            .filter(|(win, t)| **win != clicked_window && t.input_state.selection.is_some() && t.is_displayed())
+            .filter(|(_, t)| t.screen.root == event.root && t.panel.is_none())
+            .filter_map(|(win, t)| {
+                root_geometry(ctx.conn, t.window).ok()
+                    .map(|geom| (*win, Position::new(geom.x, geom.y)))
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
     
     // For right-click drags, collect snap targets BEFORE getting mutable reference
     let snap_targets = if event.detail == mouse::BUTTON_RIGHT {
         eves
             .iter()
-            .filter(|(win, t)| **win != clicked_window && t.state.is_visible())
+            .filter(|(win, t)| **win != clicked_window && t.is_displayed() && t.screen.root == event.root && t.panel.is_none())
+            .filter(|(win, _)| !group.iter().any(|(member, _)| member == *win))
             .filter_map(|(_, t)| {
-                ctx.conn.get_geometry(t.window).ok()
-                    .and_then(|req| req.reply().ok())
+                root_geometry(ctx.conn, t.window).ok()
                     .map(|geom| Rect {
                         x: geom.x,
                         y: geom.y,
@@ -250,19 +524,46 @@ fn handle_button_press(
     if let Some(thumbnail) = eves.get_mut(&clicked_window)
     {
         debug!(window = thumbnail.window, character = %thumbnail.character_name, "ButtonPress on thumbnail");
-        let geom = ctx.conn.get_geometry(thumbnail.window)
-            .context("Failed to send geometry query on button press")?
-            .reply()
+        if event.detail == mouse::BUTTON_RIGHT && in_panel {
+            thumbnail.float_out_of_panel()
+                .context(format!("Failed to take '{}' out of the panel", thumbnail.character_name))?;
+        }
+        let geom = root_geometry(ctx.conn, thumbnail.window)
             .context(format!("Failed to get geometry on button press for '{}'", thumbnail.character_name))?;
         thumbnail.input_state.drag_start = Position::new(event.root_x, event.root_y);
         thumbnail.input_state.win_start = Position::new(geom.x, geom.y);
         
         // Only allow dragging with right-click
         if event.detail == mouse::BUTTON_RIGHT {
+            if persistent_state.global.show_drag_guides {
+                let area = snapping::safe_area(
+                    ctx.screen.width_in_pixels,
+                    ctx.screen.height_in_pixels,
+                    &persistent_state.global.screen_margins,
+                );
+                thumbnail.input_state.guides = drag_guides::show(ctx, &snap_targets, area)
+                    .context("Failed to show drag guides")?;
+                // Keep the dragged thumbnail above its guide lines
+                thumbnail.raise()?;
+                ctx.conn.flush()
+                    .context("Failed to flush X11 connection after raising dragged thumbnail")?;
+            }
+            if persistent_state.global.drag_ghost {
+                let rect = Rect {
+                    x: geom.x,
+                    y: geom.y,
+                    width: thumbnail.dimensions.width,
+                    height: thumbnail.dimensions.height,
+                };
+                thumbnail.input_state.ghost = Some(Outline::show(ctx, rect, GHOST_COLOR)
+                    .context(format!("Failed to show drag outline for '{}'", thumbnail.character_name))?);
+            }
             // Store the pre-computed snap targets
             thumbnail.input_state.snap_targets = snap_targets;
             thumbnail.input_state.dragging = true;
-            debug!(window = thumbnail.window, snap_target_count = thumbnail.input_state.snap_targets.len(), "Started dragging thumbnail with cached snap targets");
+            thumbnail.input_state.cancel_key = grab_cancel_key(ctx);
+            thumbnail.input_state.group = group;
+            debug!(window = thumbnail.window, snap_target_count = thumbnail.input_state.snap_targets.len(), group_size = thumbnail.input_state.group.len(), "Started dragging thumbnail with cached snap targets");
         }
         // Left-click sets current character for cycling
         if event.detail == mouse::BUTTON_LEFT {
@@ -284,65 +585,137 @@ fn handle_button_release(
     ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
 ) -> Result<()> {
     debug!(x = event.root_x, y = event.root_y, detail = event.detail, "ButtonRelease received");
+    let ctx = &ctx.on_root(event.root);
+    
+    // Releasing off-screen (e.g. on another X screen) aborts the drag
+    let off_screen = !event.same_screen
+        || event.root_x < 0
+        || event.root_y < 0
+        || event.root_x >= ctx.screen.width_in_pixels as i16
+        || event.root_y >= ctx.screen.height_in_pixels as i16;
+    if off_screen && event.detail == mouse::BUTTON_RIGHT {
+        return cancel_drags(ctx, eves, |_| true);
+    }
+    
+    // Guides go away on any release, even if the pointer ended up off the thumbnail
+    // A ghost-dragged thumbnail moves to the outline now, before its position is saved
+    for thumbnail in eves.values_mut() {
+        drag_guides::hide(ctx.conn, &mut thumbnail.input_state.guides);
+        release_cancel_key(ctx, thumbnail);
+        thumbnail.land_ghost()
+            .context(format!("Failed to move '{}' to its drag outline", thumbnail.character_name))?;
+    }
+    
+    if finish_redaction(ctx, persistent_state, eves, &event, ipc_client)
+        .context("Failed to edit redactions")?
+    {
+        return Ok(());
+    }
     
     // First pass: identify the hovered thumbnail by the EVE window key
-    let clicked_key = eves
+    // The dragged thumbnail wins over the ones it was dropped on (e.g. panel children)
+    let hovered: Vec<Window> = eves
         .iter()
-        .find(|(_, thumb)| {
-            let hovered = thumb.is_hovered(event.root_x, event.root_y);
+        .filter(|(_, thumb)| {
+            let hovered = thumb.screen.root == event.root && thumb.is_hovered(event.root_x, event.root_y);
             if hovered {
                 debug!(window = thumb.window, character = %thumb.character_name, "Found hovered thumbnail");
             }
             hovered
         })
-        .map(|(eve_window, _)| *eve_window);
+        .map(|(eve_window, _)| *eve_window)
+        .collect();
+    let clicked_key = hovered.iter()
+        .find(|key| eves.get(key).is_some_and(|t| t.input_state.dragging))
+        .or(hovered.first())
+        .copied();
     
     let Some(clicked_key) = clicked_key else {
         debug!("No thumbnail hovered at release position");
         return Ok(());
     };
     
+    // Ctrl+middle-click on a selected thumbnail lines the selection up with it (with Shift: gives it its size)
+    if event.detail == mouse::BUTTON_MIDDLE
+        && event.state.contains(KeyButMask::CONTROL)
+        && eves.get(&clicked_key).is_some_and(|t| t.input_state.selection.is_some())
+    {
+        let resize = event.state.contains(KeyButMask::SHIFT);
+        return arrange_selection(ctx, persistent_state, eves, clicked_key, resize, session_state, ipc_client)
+            .context("Failed to arrange selected thumbnails");
+    }
+    
     let mut clicked_src: Option<Window> = None;
-    let is_left_click = event.detail == mouse::BUTTON_LEFT;
+    // Ctrl+left-click only edits the selection (handled on press)
+    let is_left_click = event.detail == mouse::BUTTON_LEFT && !event.state.contains(KeyButMask::CONTROL);
+    
+    if let Some(thumbnail) = eves.get(&clicked_key)
+        && thumbnail.input_state.dragging
+    {
+        if thumbnail.input_state.group.is_empty() && over_panel(ctx, eves, event.root_x, event.root_y) {
+            if let Some(thumbnail) = eves.get_mut(&clicked_key) {
+                drop_into_panel(persistent_state, thumbnail, ipc_client)
+                    .context(format!("Failed to put '{}' back into the panel", thumbnail.character_name))?;
+            }
+            return Ok(());
+        }
+        if thumbnail.input_state.group.is_empty() {
+            resolve_drop_overlap(ctx, persistent_state, eves, clicked_key, session_state, ipc_client)
+                .context("Failed to resolve thumbnail overlap after drop")?;
+        } else {
+            finish_group_move(ctx, persistent_state, eves, clicked_key, session_state, ipc_client)
+                .context("Failed to move selected thumbnails")?;
+        }
+    }
     
     if let Some(thumbnail) = eves.get_mut(&clicked_key) {
         debug!(window = thumbnail.window, character = %thumbnail.character_name, "ButtonRelease on thumbnail");
         clicked_src = Some(thumbnail.src);
         
         // Left-click focuses the window (dragging is right-click only)
-        if is_left_click {
-            thumbnail.focus()
-                .context(format!("Failed to focus window for '{}'", thumbnail.character_name))?;
+        if is_left_click && !foreign_toplevel::try_activate(ctx, thumbnail.src) {
+            if window_manager_running(ctx.conn, thumbnail.screen) {
+                thumbnail.focus()
+            } else {
+                focus_window_directly(ctx.conn, thumbnail.src)
+            }
+            .context(format!("Failed to focus window for '{}'", thumbnail.character_name))?;
         }
         
         // Send position update via IPC after drag ends (right-click release)
         // GUI will save to disk - preview process only updates in-memory state
         if thumbnail.input_state.dragging {
             // Query actual position from X11
-            let geom = ctx.conn.get_geometry(thumbnail.window)
-                .context("Failed to send geometry query after drag")?
-                .reply()
+            let geom = root_geometry(ctx.conn, thumbnail.window)
                 .context(format!("Failed to get geometry after drag for '{}'", thumbnail.character_name))?;
             
             // Update session state (in-memory only)
             session_state.update_window_position(thumbnail.window, geom.x, geom.y);
             debug!(window = thumbnail.window, x = geom.x, y = geom.y, "Saved session position after drag");
             
-            // Update in-memory character positions (don't save to disk)
-            persistent_state.character_positions.insert(
-                thumbnail.character_name.clone(),
-                CharacterSettings::new(geom.x, geom.y, thumbnail.dimensions.width, thumbnail.dimensions.height),
-            );
+            // Duplicate windows don't own their character's position
+            if thumbnail.duplicate_of.is_none() {
+                // Update in-memory character positions (don't save to disk)
+                persistent_state.character_positions.insert(
+                    thumbnail.character_name.clone(),
+                    CharacterSettings::new(geom.x, geom.y, thumbnail.identity.dimensions().width, thumbnail.identity.dimensions().height),
+                );
+                
+                // Send PositionChanged event to GUI via IPC
+                send_position_changed(
+                    ipc_client,
+                    &thumbnail.character_name,
+                    geom.x,
+                    geom.y,
+                    thumbnail.identity.dimensions().width,
+                    thumbnail.identity.dimensions().height,
+                );
+            }
             
-            // Send PositionChanged event to GUI via IPC
-            send_position_changed(
-                ipc_client,
-                &thumbnail.character_name,
-                geom.x,
-                geom.y,
-                thumbnail.dimensions.width,
-                thumbnail.dimensions.height,
-            );
+            // Dragged out of the panel: it stays free-floating
+            if thumbnail.input_state.from_panel.take().is_some() {
+                record_panel_detached(persistent_state, ipc_client, thumbnail);
+            }
         }
         
         // Clear dragging state and free cached snap targets
@@ -350,18 +723,27 @@ fn handle_button_release(
         thumbnail.input_state.snap_targets.clear();
     }
     
-    // After releasing mutable borrow, optionally minimize other EVE clients
+    // After releasing mutable borrow, optionally minimize (or lower) other EVE clients
     if is_left_click
         && persistent_state.global.minimize_clients_on_switch
         && let Some(clicked_src) = clicked_src
     {
-        for other_window in eves
+        // Without a window manager nothing can be minimized, lower the others instead
+        let action = if window_manager_running(ctx.conn, ctx.screen) {
+            persistent_state.global.client_switch_action
+        } else {
+            ClientSwitchAction::Lower
+        };
+        for (other_window, other_screen) in eves
             .values()
             .filter(|t| t.src != clicked_src)
-            .map(|t| t.src)
+            .map(|t| (t.src, t.screen))
         {
-            if let Err(e) = minimize_window(ctx.conn, ctx.screen, ctx.atoms, other_window) {
-                debug!(error = ?e, window = other_window, "Failed to minimize window");
+            if action == ClientSwitchAction::Minimize && foreign_toplevel::try_minimize(ctx, other_window) {
+                continue;
+            }
+            if let Err(e) = apply_switch_action(ctx.conn, other_screen, ctx.atoms, other_window, action) {
+                debug!(error = ?e, window = other_window, action = ?action, "Failed to minimize/lower window");
             }
         }
     }
@@ -369,15 +751,665 @@ fn handle_button_release(
     Ok(())
 }
 
+/// Grab Escape on the root window so a drag can be cancelled (thumbnails never get focus)
+fn grab_cancel_key(ctx: &AppContext) -> Option<Keycode> {
+    let keycode = keycode_for_keysym(ctx.conn, x11::KEYSYM_ESCAPE)
+        .inspect_err(|e| warn!(error = ?e, "Failed to look up Escape keycode, drag can't be cancelled"))
+        .ok()
+        .flatten()?;
+    let grabbed = ctx.conn.grab_key(false, ctx.screen.root, ModMask::ANY, keycode, GrabMode::ASYNC, GrabMode::ASYNC)
+        .map_err(anyhow::Error::from)
+        .and_then(|cookie| cookie.check().map_err(anyhow::Error::from));
+    match grabbed {
+        Ok(()) => Some(keycode),
+        Err(e) => {
+            // Usually another client already grabbed Escape
+            debug!(error = ?e, keycode = keycode, "Failed to grab Escape for drag cancel");
+            None
+        }
+    }
+}
+
+/// Undo `grab_cancel_key` for a thumbnail whose drag ended
+fn release_cancel_key(ctx: &AppContext, thumbnail: &mut Thumbnail) {
+    let Some(keycode) = thumbnail.input_state.cancel_key.take() else {
+        return;
+    };
+    if let Err(e) = ctx.conn.ungrab_key(keycode, ctx.screen.root, ModMask::ANY) {
+        warn!(error = ?e, keycode = keycode, "Failed to release Escape grab after drag");
+    }
+}
+
+/// Abort a drag: put the thumbnail back where it started and persist nothing
+fn cancel_drag(ctx: &AppContext, thumbnail: &mut Thumbnail) -> Result<()> {
+    info!(character = %thumbnail.character_name, "Drag cancelled, restoring original position");
+    drag_guides::hide(ctx.conn, &mut thumbnail.input_state.guides);
+    if let Some(mut ghost) = thumbnail.input_state.ghost.take() {
+        drag_guides::hide(ctx.conn, &mut ghost.windows);
+    }
+    release_cancel_key(ctx, thumbnail);
+    thumbnail.input_state.dragging = false;
+    thumbnail.input_state.snap_targets.clear();
+    
+    // A drag out of the panel goes back in at the panel's next layout
+    let Position { x, y } = match thumbnail.input_state.from_panel.take() {
+        Some(saved) => {
+            thumbnail.panel_detached = false;
+            saved
+        }
+        None => thumbnail.input_state.win_start,
+    };
+    thumbnail.reposition(x, y)
+}
+
+/// Whether (x, y) on the context's screen is over a shown panel (a drop there joins it)
+fn over_panel(ctx: &AppContext, eves: &HashMap<Window, Thumbnail>, x: i16, y: i16) -> bool {
+    let mut panels: Vec<Window> = eves.values()
+        .filter(|t| t.screen.root == ctx.screen.root && t.is_displayed())
+        .filter_map(|t| t.panel)
+        .collect();
+    panels.sort_unstable();
+    panels.dedup();
+    panels.into_iter().any(|panel| {
+        let Some(geom) = ctx.conn.get_geometry(panel).ok().and_then(|req| req.reply().ok()) else {
+            return false;
+        };
+        let Some(inside) = ctx.conn.translate_coordinates(ctx.screen.root, panel, x, y).ok()
+            .and_then(|req| req.reply().ok())
+        else {
+            return false;
+        };
+        inside.dst_x >= 0
+            && inside.dst_y >= 0
+            && inside.dst_x < geom.width as i16
+            && inside.dst_y < geom.height as i16
+    })
+}
+
+/// Finish a drag dropped on the panel: the panel adopts the thumbnail at its next layout
+/// The free-floating position from before the drag is kept, the drop spot isn't saved
+fn drop_into_panel(
+    persistent_state: &mut PersistentState,
+    thumbnail: &mut Thumbnail,
+    ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+) -> Result<()> {
+    thumbnail.input_state.dragging = false;
+    thumbnail.input_state.snap_targets.clear();
+    let Position { x, y } = thumbnail.input_state.from_panel.take()
+        .unwrap_or(thumbnail.input_state.win_start);
+    thumbnail.reposition(x, y)?;
+    thumbnail.set_panel_detached(false)?;
+    info!(character = %thumbnail.character_name, "Thumbnail dropped into the panel");
+    record_panel_detached(persistent_state, ipc_client, thumbnail);
+    Ok(())
+}
+
+/// Remember whether a character floats outside the panel (runtime config and GUI, which saves it)
+fn record_panel_detached(
+    persistent_state: &mut PersistentState,
+    ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+    thumbnail: &Thumbnail,
+) {
+    let detached = thumbnail.panel_detached;
+    // Duplicate windows don't own their character's settings
+    if thumbnail.character_name.is_empty()
+        || thumbnail.duplicate_of.is_some()
+        || persistent_state.configured_character(&thumbnail.character_name).is_some_and(|s| s.panel_detached) == detached
+    {
+        return;
+    }
+    let Position { x, y } = thumbnail.identity.position();
+    let dimensions = thumbnail.identity.dimensions();
+    persistent_state.profile.character_positions
+        .entry(thumbnail.character_name.clone())
+        .or_insert_with(|| CharacterSettings::new(x, y, dimensions.width, dimensions.height))
+        .panel_detached = detached;
+    
+    if let Some(client) = ipc_client
+        && let Ok(mut client_lock) = client.lock()
+    {
+        let response = PreviewResponse::PanelDetachedChanged {
+            character: thumbnail.character_name.clone(),
+            detached,
+        };
+        if let Err(e) = client_lock.send_response(&response) {
+            warn!(error = ?e, character = %thumbnail.character_name, "Failed to send PanelDetachedChanged via IPC");
+        }
+    }
+}
+
+/// Thumbnail's top-left corner on the root window (also inside the panel)
+fn thumbnail_origin(ctx: &AppContext, thumbnail: &Thumbnail) -> Result<Position> {
+    let origin = ctx.conn.translate_coordinates(thumbnail.window, thumbnail.screen.root, 0, 0)
+        .context("Failed to send coordinate translation for thumbnail")?
+        .reply()
+        .context(format!("Failed to locate thumbnail for '{}'", thumbnail.character_name))?;
+    Ok(Position::new(origin.dst_x, origin.dst_y))
+}
+
+/// Start drawing a redaction area on a thumbnail in edit mode
+fn start_redaction(ctx: &AppContext, thumbnail: &mut Thumbnail, pointer: Position) -> Result<()> {
+    let origin = thumbnail_origin(ctx, thumbnail)?;
+    let rect = Rect { x: pointer.x, y: pointer.y, width: 1, height: 1 };
+    let outline = Outline::show(ctx, rect, redaction::OUTLINE_COLOR)
+        .context("Failed to show redaction outline")?;
+    thumbnail.input_state.redact_draw = Some(RedactDraw { origin, start: pointer, outline });
+    Ok(())
+}
+
+/// Finish a redaction edit on release: keep the drawn area (left) or remove the clicked one (right)
+/// Returns true if the release belonged to edit mode
+fn finish_redaction(
+    ctx: &AppContext,
+    persistent_state: &mut PersistentState,
+    eves: &mut HashMap<Window, Thumbnail>,
+    event: &ButtonReleaseEvent,
+    ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+) -> Result<bool> {
+    let pointer = Position::new(event.root_x, event.root_y);
+    let thumbnail = match event.detail {
+        mouse::BUTTON_LEFT => eves.values_mut().find(|t| t.input_state.redact_draw.is_some()),
+        mouse::BUTTON_RIGHT => eves.values_mut().find(|t| {
+            t.input_state.redact_edit && t.screen.root == event.root && t.is_hovered(event.root_x, event.root_y)
+        }),
+        _ => None,
+    };
+    let Some(thumbnail) = thumbnail else {
+        return Ok(false);
+    };
+    let geom = ctx.conn.get_geometry(thumbnail.src)
+        .context("Failed to send geometry query for redacted client")?
+        .reply()
+        .context(format!("Failed to get client geometry for '{}'", thumbnail.character_name))?;
+    let source = Dimensions::new(geom.width, geom.height);
+    
+    let mut redactions = thumbnail.redactions().to_vec();
+    if let Some(mut draw) = thumbnail.input_state.redact_draw.take() {
+        drag_guides::hide(ctx.conn, &mut draw.outline.windows);
+        let Some(rect) = redact::from_thumbnail(draw.local(draw.start), draw.local(pointer), source, thumbnail.dimensions) else {
+            return Ok(true);
+        };
+        redactions.push(rect);
+    } else {
+        let origin = thumbnail_origin(ctx, thumbnail)?;
+        let local = Position::new(pointer.x - origin.x, pointer.y - origin.y);
+        let clicked = redact::client_point(local, source, thumbnail.dimensions)
+            .and_then(|(x, y)| redactions.iter().rposition(|rect| rect.contains(x, y)));
+        let Some(index) = clicked else {
+            return Ok(true);
+        };
+        redactions.remove(index);
+    }
+    
+    thumbnail.set_redactions(&redactions)?;
+    info!(character = %thumbnail.character_name, count = redactions.len(), "Redacted areas edited");
+    record_redactions(persistent_state, ipc_client, thumbnail);
+    Ok(true)
+}
+
+/// Remember a character's redacted areas (runtime config and GUI, which saves them)
+fn record_redactions(
+    persistent_state: &mut PersistentState,
+    ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+    thumbnail: &Thumbnail,
+) {
+    let redactions = thumbnail.redactions().to_vec();
+    let Position { x, y } = thumbnail.identity.position();
+    let dimensions = thumbnail.identity.dimensions();
+    persistent_state.profile.character_positions
+        .entry(thumbnail.character_name.clone())
+        .or_insert_with(|| CharacterSettings::new(x, y, dimensions.width, dimensions.height))
+        .redactions = redactions.clone();
+    
+    if let Some(client) = ipc_client
+        && let Ok(mut client_lock) = client.lock()
+    {
+        let response = PreviewResponse::RedactionsChanged {
+            character: thumbnail.character_name.clone(),
+            redactions,
+        };
+        if let Err(e) = client_lock.send_response(&response) {
+            warn!(error = ?e, character = %thumbnail.character_name, "Failed to send RedactionsChanged via IPC");
+        }
+    }
+}
+
+/// Cancel every drag matching `filter`, including the selected thumbnails moved along with it
+fn cancel_drags(
+    ctx: &AppContext,
+    eves: &mut HashMap<Window, Thumbnail>,
+    filter: impl Fn(&Thumbnail) -> bool,
+) -> Result<()> {
+    let dragging: Vec<Window> = eves.iter()
+        .filter(|(_, t)| t.input_state.dragging && filter(t))
+        .map(|(key, _)| *key)
+        .collect();
+    for key in dragging {
+        let Some(thumbnail) = eves.get_mut(&key) else {
+            continue;
+        };
+        cancel_drag(ctx, thumbnail)
+            .context(format!("Failed to cancel drag for '{}'", thumbnail.character_name))?;
+        let group = std::mem::take(&mut thumbnail.input_state.group);
+        for (member, start) in group {
+            if let Some(thumbnail) = eves.get_mut(&member) {
+                thumbnail.reposition(start.x, start.y)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handle KeyPress events - Escape cancels an in-progress drag
+#[tracing::instrument(skip(ctx, eves))]
+fn handle_key_press(
+    ctx: &AppContext,
+    eves: &mut HashMap<Window, Thumbnail>,
+    event: KeyPressEvent,
+) -> Result<()> {
+    cancel_drags(ctx, eves, |t| t.input_state.cancel_key == Some(event.detail))
+}
+
+/// Select or deselect a thumbnail for group moves
+fn toggle_selection(ctx: &AppContext, eves: &mut HashMap<Window, Thumbnail>, key: Window) -> Result<()> {
+    let Some(thumbnail) = eves.get_mut(&key) else {
+        return Ok(());
+    };
+    if let Some(mut selection) = thumbnail.input_state.selection.take() {
+        drag_guides::hide(ctx.conn, &mut selection.windows);
+        debug!(character = %thumbnail.character_name, "Deselected thumbnail");
+        return Ok(());
+    }
+    let rect = thumbnail_rect(ctx, thumbnail)?;
+    thumbnail.input_state.selection = Some(Outline::show(ctx, rect, SELECTION_COLOR)
+        .context(format!("Failed to show selection outline for '{}'", thumbnail.character_name))?);
+    debug!(character = %thumbnail.character_name, "Selected thumbnail");
+    Ok(())
+}
+
+/// Deselect all thumbnails
+fn clear_selection(ctx: &AppContext, eves: &mut HashMap<Window, Thumbnail>) {
+    for thumbnail in eves.values_mut() {
+        if let Some(mut selection) = thumbnail.input_state.selection.take() {
+            drag_guides::hide(ctx.conn, &mut selection.windows);
+        }
+    }
+}
+
+/// Move the rest of a selection by the same offset as the dragged thumbnail
+fn move_group(eves: &mut HashMap<Window, Thumbnail>, group: &[(Window, Position)], dx: i16, dy: i16) -> Result<()> {
+    for (member, start) in group {
+        if let Some(thumbnail) = eves.get_mut(member) {
+            thumbnail.reposition(start.x.saturating_add(dx), start.y.saturating_add(dy))?;
+        }
+    }
+    Ok(())
+}
+
+/// Save a thumbnail position the way a finished drag does (session, runtime state, GUI)
+fn record_position(
+    persistent_state: &mut PersistentState,
+    session_state: &mut SessionState,
+    ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+    thumbnail: &Thumbnail,
+    x: i16,
+    y: i16,
+) {
+    session_state.update_window_position(thumbnail.window, x, y);
+    if thumbnail.duplicate_of.is_some() {
+        return;
+    }
+    persistent_state.character_positions.insert(
+        thumbnail.character_name.clone(),
+        CharacterSettings::new(x, y, thumbnail.identity.dimensions().width, thumbnail.identity.dimensions().height),
+    );
+    send_position_changed(
+        ipc_client,
+        &thumbnail.character_name,
+        x,
+        y,
+        thumbnail.identity.dimensions().width,
+        thumbnail.identity.dimensions().height,
+    );
+}
+
+/// Place the selected thumbnails that followed a drag and save their positions
+/// The GUI debounces saves, so the whole group lands in a single config write
+fn finish_group_move(
+    ctx: &AppContext,
+    persistent_state: &mut PersistentState,
+    eves: &mut HashMap<Window, Thumbnail>,
+    dragged_key: Window,
+    session_state: &mut SessionState,
+    ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+) -> Result<()> {
+    let Some(dragged) = eves.get_mut(&dragged_key) else {
+        return Ok(());
+    };
+    let group = std::mem::take(&mut dragged.input_state.group);
+    let start = dragged.input_state.win_start;
+    let landed = thumbnail_rect(ctx, dragged)?;
+    let (dx, dy) = (landed.x - start.x, landed.y - start.y);
+    
+    // Ghost drags leave the group in place until now; live drags already moved it
+    move_group(eves, &group, dx, dy)?;
+    for (member, start) in &group {
+        if let Some(thumbnail) = eves.get(member) {
+            let (x, y) = (start.x.saturating_add(dx), start.y.saturating_add(dy));
+            record_position(persistent_state, session_state, ipc_client, thumbnail, x, y);
+        }
+    }
+    info!(count = group.len() + 1, dx = dx, dy = dy, "Moved selected thumbnails");
+    Ok(())
+}
+
+/// Line the other selected thumbnails up with `anchor_key`, or with `resize` give them its size,
+/// and save them like a group move
+fn arrange_selection(
+    ctx: &AppContext,
+    persistent_state: &mut PersistentState,
+    eves: &mut HashMap<Window, Thumbnail>,
+    anchor_key: Window,
+    resize: bool,
+    session_state: &mut SessionState,
+    ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+) -> Result<()> {
+    let Some(anchor) = eves.get(&anchor_key) else {
+        return Ok(());
+    };
+    let anchor_rect = thumbnail_rect(ctx, anchor)?;
+    let anchor_size = anchor.identity.dimensions();
+    let members: Vec<(Window, Rect)> = eves
+        .iter()
+        .filter(|(key, t)| **key != anchor_key && t.input_state.selection.is_some() && t.is_displayed())
+        .filter(|(_, t)| t.screen.root == ctx.screen.root && t.panel.is_none())
+        .map(|(key, t)| Ok((*key, thumbnail_rect(ctx, t)?)))
+        .collect::<Result<_>>()?;
+    let rects: Vec<Rect> = members.iter().map(|(_, rect)| *rect).collect();
+    let targets = if resize {
+        rects.iter().map(|rect| Position::new(rect.x, rect.y)).collect()
+    } else {
+        snapping::align_to(anchor_rect, &rects)
+    };
+    
+    for ((key, _), Position { x, y }) in members.iter().zip(targets) {
+        let Some(thumbnail) = eves.get_mut(key) else {
+            continue;
+        };
+        if resize {
+            thumbnail.resize(anchor_size)?;
+            let rect = thumbnail_rect(ctx, thumbnail)?;
+            if let Some(selection) = &mut thumbnail.input_state.selection {
+                selection.reshape(ctx.conn, rect)
+                    .context(format!("Failed to resize selection outline for '{}'", thumbnail.character_name))?;
+            }
+        } else {
+            thumbnail.reposition(x, y)?;
+        }
+        record_position(persistent_state, session_state, ipc_client, thumbnail, x, y);
+    }
+    info!(count = members.len() + 1, resize = resize, "Arranged selected thumbnails");
+    Ok(())
+}
+
+/// Current on-screen rectangle of a thumbnail
+fn thumbnail_rect(ctx: &AppContext, thumbnail: &Thumbnail) -> Result<Rect> {
+    let geom = root_geometry(ctx.conn, thumbnail.window)
+        .context(format!("Failed to get geometry reply for '{}'", thumbnail.character_name))?;
+    Ok(Rect {
+        x: geom.x,
+        y: geom.y,
+        width: thumbnail.dimensions.width,
+        height: thumbnail.dimensions.height,
+    })
+}
+
+/// Apply overlap_avoidance after a drag: nudge the dropped thumbnail into free space,
+/// or push the thumbnails it covers out of the way
+fn resolve_drop_overlap(
+    ctx: &AppContext,
+    persistent_state: &mut PersistentState,
+    eves: &mut HashMap<Window, Thumbnail>,
+    dropped_key: Window,
+    session_state: &mut SessionState,
+    ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+) -> Result<()> {
+    let mode = persistent_state.global.overlap_avoidance;
+    if mode == OverlapAvoidance::Off {
+        return Ok(());
+    }
+    
+    let bounds = snapping::safe_area(
+        ctx.screen.width_in_pixels,
+        ctx.screen.height_in_pixels,
+        &persistent_state.global.screen_margins,
+    );
+    let mut rects: Vec<(Window, Rect)> = eves
+        .iter()
+        .filter(|(_, t)| t.is_displayed() && t.screen.root == ctx.screen.root && t.panel.is_none())
+        .map(|(key, t)| Ok((*key, thumbnail_rect(ctx, t)?)))
+        .collect::<Result<_>>()?;
+    let Some(dropped) = rects.iter().find(|(key, _)| *key == dropped_key).map(|(_, r)| *r) else {
+        return Ok(());
+    };
+    
+    match mode {
+        OverlapAvoidance::Off => {}
+        OverlapAvoidance::Nudge => {
+            let others: Vec<Rect> = rects.iter()
+                .filter(|(key, _)| *key != dropped_key)
+                .map(|(_, r)| *r)
+                .collect();
+            if let Some(Position { x, y }) = snapping::find_free_position(dropped, &others, bounds)
+                && let Some(thumbnail) = eves.get_mut(&dropped_key)
+            {
+                debug!(character = %thumbnail.character_name, x = x, y = y, "Nudging dropped thumbnail into free space");
+                thumbnail.reposition(x, y)?;
+            }
+        }
+        OverlapAvoidance::Push => {
+            let covered: Vec<Window> = rects.iter()
+                .filter(|(key, r)| *key != dropped_key && r.overlaps(&dropped))
+                .map(|(key, _)| *key)
+                .collect();
+            for key in covered {
+                let Some(index) = rects.iter().position(|(k, _)| *k == key) else {
+                    continue;
+                };
+                let rect = rects[index].1;
+                let others: Vec<Rect> = rects.iter()
+                    .filter(|(k, _)| *k != key)
+                    .map(|(_, r)| *r)
+                    .collect();
+                let Some(Position { x, y }) = snapping::find_free_position(rect, &others, bounds) else {
+                    continue;
+                };
+                let Some(thumbnail) = eves.get_mut(&key) else {
+                    continue;
+                };
+                debug!(character = %thumbnail.character_name, x = x, y = y, "Pushing covered thumbnail aside");
+                thumbnail.reposition(x, y)?;
+                rects[index].1 = Rect { x, y, ..rect };
+                
+                // Pushed thumbnails are saved like a drag so their new spot sticks
+                record_position(persistent_state, session_state, ipc_client, thumbnail, x, y);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Re-apply thumbnail stacking: per-character z_order, then focused and hovered on top
+/// While a stacking rule keeps previews below the active window they are stacked under its frame instead
+pub(super) fn restack_thumbnails(
+    ctx: &AppContext,
+    persistent_state: &PersistentState,
+    eves: &HashMap<Window, Thumbnail>,
+) -> Result<()> {
+    let mut order: Vec<&Thumbnail> = eves.values().filter(|t| t.is_displayed()).collect();
+    order.sort_by_key(|t| (
+        persistent_state.global.raise_thumbnail_on_hover && t.input_state.hovered,
+        persistent_state.global.raise_focused_thumbnail && t.state.is_focused(),
+        persistent_state.configured_character(&t.character_name).map_or(0, |s| s.z_order),
+        t.window,
+    ));
+    
+    // Raising bottom-to-top leaves the last one on top (likewise stacking each right under the anchor)
+    for thumbnail in order {
+        match ctx.stack_below.get() {
+            Some((root, frame)) if thumbnail.screen.root == root => thumbnail.lower_beneath(frame)?,
+            _ => thumbnail.raise()?,
+        }
+    }
+    ctx.conn.flush()
+        .context("Failed to flush X11 connection after restacking thumbnails")?;
+    Ok(())
+}
+
+/// Whether any option orders the thumbnails: z_order, raise on hover/focus or a stacking rule anchor
+fn stacking_active(ctx: &AppContext, persistent_state: &PersistentState, eves: &HashMap<Window, Thumbnail>) -> bool {
+    persistent_state.global.raise_thumbnail_on_hover
+        || persistent_state.global.raise_focused_thumbnail
+        || ctx.stack_below.get().is_some()
+        || eves.values().any(|t| persistent_state.configured_character(&t.character_name).is_some_and(|s| s.z_order != 0))
+}
+
+/// Handle a _NET_ACTIVE_WINDOW change on a root window - follow the profile's stacking_rules
+fn handle_active_window_change(
+    ctx: &AppContext,
+    persistent_state: &PersistentState,
+    eves: &HashMap<Window, Thumbnail>,
+    root: Window,
+) -> Result<()> {
+    let anchor = stacking_rules::anchor(ctx, &persistent_state.profile, root)
+        .context("Failed to work out preview stacking for the active window")?;
+    let previous = ctx.stack_below.get();
+    ctx.stack_below.set(anchor);
+    if anchor.is_some() != previous.is_some() {
+        debug!(below = ?anchor, "Preview stacking changed");
+        if !ctx.wm.get().override_redirect {
+            // Managed thumbnails are kept on top by the WM's keep-above layer, not by our restacking
+            for thumbnail in eves.values() {
+                set_window_above(ctx.conn, thumbnail.screen, ctx.atoms, thumbnail.window, anchor.is_none())
+                    .context(format!("Failed to update always-on-top for '{}'", thumbnail.character_name))?;
+            }
+        }
+    }
+    if anchor.is_none() && previous.is_none() {
+        return Ok(());
+    }
+    restack_thumbnails(ctx, persistent_state, eves)
+}
+
+/// Apply a character's settings after a live edit in the GUI (size, label, border, visibility, stacking)
+pub(super) fn apply_character_update(
+    ctx: &AppContext,
+    persistent_state: &PersistentState,
+    eves: &mut HashMap<Window, Thumbnail>,
+    character_name: &str,
+) -> Result<()> {
+    let settings = persistent_state.configured_character(character_name);
+    for thumbnail in eves.values_mut().filter(|t| t.character_name == character_name) {
+        if let Some(dimensions) = settings.map(|s| s.dimensions)
+            && dimensions.width > 0
+            && dimensions.height > 0
+        {
+            thumbnail.resize(dimensions)
+                .context(format!("Failed to resize thumbnail for '{}'", character_name))?;
+        }
+        thumbnail.apply_character_settings(settings)
+            .context(format!("Failed to apply settings for '{}'", character_name))?;
+    }
+    restack_thumbnails(ctx, persistent_state, eves)
+}
+
+/// Handle EnterNotify/LeaveNotify on thumbnails - track hover for raise_thumbnail_on_hover
+#[tracing::instrument(skip(ctx, persistent_state, eves))]
+fn handle_hover_change(
+    ctx: &AppContext,
+    persistent_state: &PersistentState,
+    eves: &mut HashMap<Window, Thumbnail>,
+    thumbnail_window: Window,
+    hovered: bool,
+) -> Result<()> {
+    let Some(thumbnail) = eves.values_mut().find(|t| t.window == thumbnail_window) else {
+        return Ok(());
+    };
+    if thumbnail.input_state.hovered == hovered {
+        return Ok(());
+    }
+    thumbnail.input_state.hovered = hovered;
+    
+    if !hovered {
+        thumbnail.hide_tooltip()?;
+    } else if persistent_state.global.hover_tooltip && !thumbnail.input_state.dragging {
+        thumbnail.show_tooltip()?;
+    }
+    
+    if persistent_state.global.raise_thumbnail_on_hover {
+        restack_thumbnails(ctx, persistent_state, eves)
+            .context("Failed to restack thumbnails after hover change")?;
+    }
+    Ok(())
+}
+
+/// Handle ConfigureNotify events - put thumbnails back in order after other windows restack
+#[tracing::instrument(skip(ctx, persistent_state, eves))]
+fn handle_configure_notify(
+    ctx: &AppContext,
+    persistent_state: &PersistentState,
+    eves: &HashMap<Window, Thumbnail>,
+    event: ConfigureNotifyEvent,
+) -> Result<()> {
+    // Ignore our own restacks (thumbnails), popup menus/tooltips (override-redirect)
+    // and the copy of a client's ConfigureNotify delivered through its own STRUCTURE_NOTIFY mask
+    if event.override_redirect
+        || eves.values().any(|t| t.window == event.window)
+        || (event.event == event.window && eves.contains_key(&event.window))
+    {
+        return Ok(());
+    }
+    // Without a stacking option there's no order to restore, and client moves would each send a restack
+    if !stacking_active(ctx, persistent_state, eves) {
+        return Ok(());
+    }
+    trace!(window = event.window, above = event.above_sibling, "Top-level window configured, restoring thumbnail stacking");
+    restack_thumbnails(ctx, persistent_state, eves)
+        .context(format!("Failed to restack thumbnails after window {} was configured", event.window))
+}
+
+/// Handle ReparentNotify/MapNotify on a tracked client - rebind damage and the source picture
+/// so a WM reparenting or remapping the window (e.g. toggling decorations) doesn't leave it black
+fn handle_client_rebind(
+    eves: &mut HashMap<Window, Thumbnail>,
+    window: Window,
+    reason: &str,
+) -> Result<()> {
+    if let Some(thumbnail) = eves.get_mut(&window) {
+        debug!(window = window, character = %thumbnail.character_name, reason = reason, "Client window changed, rebinding thumbnail");
+        thumbnail.rebind_source()
+            .context(format!("Failed to rebind thumbnail for '{}' after client was {}", thumbnail.character_name, reason))?;
+    }
+    Ok(())
+}
+
 /// Handle MotionNotify events - process drag motion with snapping
-#[tracing::instrument(skip(persistent_state, eves))]
+#[tracing::instrument(skip(ctx, persistent_state, eves))]
 fn handle_motion_notify(
+    ctx: &AppContext,
     persistent_state: &PersistentState,
     eves: &mut HashMap<Window, Thumbnail>,
     event: MotionNotifyEvent,
 ) -> Result<()> {
     trace!(x = event.root_x, y = event.root_y, "MotionNotify received");
     
+    // Redaction area being drawn (edit mode) follows the pointer
+    if let Some(draw) = eves.values_mut().find_map(|t| t.input_state.redact_draw.as_mut()) {
+        let rect = draw.rect(Position::new(event.root_x, event.root_y));
+        return draw.outline.reshape(ctx.conn, rect)
+            .context("Failed to resize redaction outline");
+    }
+    
     // Find the dragging thumbnail (typically only one at a time)
     let dragging_window = eves.iter()
         .find(|(_, t)| t.input_state.dragging)
@@ -387,7 +1419,13 @@ fn handle_motion_notify(
         return Ok(());  // No thumbnail is being dragged
     };
     
+    let ctx = &ctx.on_screen(eves[&dragging_window].screen);
     let snap_threshold = persistent_state.global.snap_threshold;
+    let area = snapping::safe_area(
+        ctx.screen.width_in_pixels,
+        ctx.screen.height_in_pixels,
+        &persistent_state.global.screen_margins,
+    );
     
     // Get the dragging thumbnail and clone snap targets to avoid borrow conflict
     // Snap targets were computed once in ButtonPress, avoiding repeated X11 queries
@@ -395,30 +1433,41 @@ fn handle_motion_notify(
     let thumbnail = eves.get_mut(&dragging_window).unwrap();
     let snap_targets = thumbnail.input_state.snap_targets.clone();
     
-    handle_drag_motion(
+    let Position { x, y } = handle_drag_motion(
         thumbnail,
         &event,
         &snap_targets,  // Use cached data (cloned to avoid borrow conflict)
+        area,
         thumbnail.dimensions.width,
         thumbnail.dimensions.height,
         snap_threshold,
     )
     .context(format!("Failed to handle drag motion for '{}'", thumbnail.character_name))?;
     
+    // The rest of the selection follows live (ghost drags move it on release)
+    if thumbnail.input_state.ghost.is_none() && !thumbnail.input_state.group.is_empty() {
+        let group = thumbnail.input_state.group.clone();
+        let start = thumbnail.input_state.win_start;
+        move_group(eves, &group, x - start.x, y - start.y)
+            .context("Failed to move selected thumbnails with drag")?;
+    }
+    
     Ok(())
 }
 
-/// Handle drag motion for a single thumbnail with snapping
+/// Handle drag motion for a single thumbnail with snapping, returning where it was moved
+/// Screen margin lines take priority over other thumbnails on each axis
 fn handle_drag_motion(
     thumbnail: &mut Thumbnail,
     event: &MotionNotifyEvent,
     snap_targets: &[Rect],
+    area: Rect,
     config_width: u16,
     config_height: u16,
     snap_threshold: u16,
-) -> Result<()> {
+) -> Result<Position> {
     if !thumbnail.input_state.dragging {
-        return Ok(());
+        return Ok(thumbnail.input_state.win_start);
     }
 
     let dx = event.root_x - thumbnail.input_state.drag_start.x;
@@ -438,13 +1487,17 @@ fn handle_drag_motion(
         snap_targets,
         snap_threshold,
     ).unwrap_or_else(|| Position::new(new_x, new_y));
+    
+    let area_snap = snapping::find_area_snap_position(dragged_rect, area, snap_threshold);
+    let final_x = area_snap.filter(|p| p.x != new_x).map_or(final_x, |p| p.x);
+    let final_y = area_snap.filter(|p| p.y != new_y).map_or(final_y, |p| p.y);
 
     trace!(window = thumbnail.window, from_x = thumbnail.input_state.win_start.x, from_y = thumbnail.input_state.win_start.y, to_x = final_x, to_y = final_y, "Dragging thumbnail to new position");
 
     // Always reposition (let X11 handle no-op if position unchanged)
-    thumbnail.reposition(final_x, final_y)?;
+    thumbnail.drag_to(final_x, final_y)?;
 
-    Ok(())
+    Ok(Position::new(final_x, final_y))
 }
 
 pub fn handle_event<'a>(
@@ -460,100 +1513,132 @@ pub fn handle_event<'a>(
     match event {
         DamageNotify(event) => handle_damage_notify(ctx, eves, event),
         CreateNotify(event) => handle_create_notify(ctx, persistent_state, eves, event, session_state, cycle_state, &check_and_create_window, ipc_client),
-        DestroyNotify(event) => handle_destroy_notify(eves, event, cycle_state),
-        Event::FocusIn(event) => handle_focus_in(ctx, eves, event),
-        Event::FocusOut(event) => handle_focus_out(ctx, eves, event),
-        Event::ButtonPress(event) => handle_button_press(ctx, eves, event, cycle_state),
+        DestroyNotify(event) => handle_destroy_notify(ctx, persistent_state, eves, event, cycle_state),
+        Event::FocusIn(event) => handle_focus_in(ctx, persistent_state, eves, event, session_state),
+        Event::FocusOut(event) => handle_focus_out(ctx, persistent_state, eves, event, session_state),
+        Event::ButtonPress(event) => handle_button_press(ctx, persistent_state, eves, event, cycle_state),
         Event::ButtonRelease(event) => handle_button_release(ctx, persistent_state, eves, event, session_state, ipc_client),
-        Event::MotionNotify(event) => handle_motion_notify(persistent_state, eves, event),
+        Event::MotionNotify(event) => handle_motion_notify(ctx, persistent_state, eves, event),
+        Event::KeyPress(event) => handle_key_press(ctx, eves, event),
+        Event::EnterNotify(event) => handle_hover_change(ctx, persistent_state, eves, event.event, true),
+        Event::LeaveNotify(event) => handle_hover_change(ctx, persistent_state, eves, event.event, false),
+        Event::ConfigureNotify(event) => handle_configure_notify(ctx, persistent_state, eves, event),
+        // Clients select STRUCTURE_NOTIFY, so only act on that copy (root's SUBSTRUCTURE_NOTIFY sends another)
+        Event::ReparentNotify(event) if event.event == event.window => handle_client_rebind(eves, event.window, "reparented"),
+        Event::MapNotify(event) if event.event == event.window => handle_client_rebind(eves, event.window, "mapped"),
+        Event::UnmapNotify(event) if event.event == event.window => {
+            if let Some(thumbnail) = eves.get(&event.window) {
+                // Nothing to render from while unmapped; the binding is rebuilt on the next MapNotify
+                debug!(window = event.window, character = %thumbnail.character_name, "Client window unmapped");
+            }
+            Ok(())
+        }
+        PropertyNotify(event) if ctx.conn.setup().roots.iter().any(|screen| screen.root == event.window) => {
+            if event.atom == ctx.atoms.net_active_window {
+                handle_active_window_change(ctx, persistent_state, eves, event.window)?;
+            }
+            Ok(())
+        }
         PropertyNotify(event) => {
-            if event.atom == ctx.atoms.wm_name
+            // Clients may set both title properties; whichever changes, the title is re-read
+            let title_changed = event.atom == ctx.atoms.wm_name || event.atom == ctx.atoms.net_wm_name;
+            if title_changed
                 && let Some(thumbnail) = eves.get_mut(&event.window)
-                && let Some(eve_window) = is_window_eve(ctx.conn, event.window, ctx.atoms)
+                && let Some(eve_window) = is_window_eve(ctx.conn, event.window, ctx.atoms, &session_state.title_pattern)
                     .context(format!("Failed to check if window {} is EVE client during property change", event.window))?
             {
                 // Character name changed (login/logout/character switch)
                 let old_name = thumbnail.character_name.clone();
+                let was_duplicate = thumbnail.duplicate_of.is_some();
                 let new_character_name = eve_window.character_name();
+                if new_character_name == old_name {
+                    // The other title property caught up, nothing changed
+                    return Ok(());
+                }
                 
-                // Query actual position from X11
-                let geom = ctx.conn.get_geometry(thumbnail.window)
-                    .context("Failed to send geometry query during character change")?
-                    .reply()
-                    .context(format!("Failed to get geometry during character change for window {}", thumbnail.window))?;
-                let current_pos = Position::new(geom.x, geom.y);
+                // Last position of the window before it switches characters
+                let current_pos = thumbnail.identity.position();
                 
                 // Update cycle state with new character name
                 cycle_state.update_character(event.window, new_character_name.to_string());
                 
-                // Save old character's position (in-memory only, no disk write)
-                if !old_name.is_empty() {
-                    persistent_state.character_positions.insert(
-                        old_name.clone(),
-                        CharacterSettings::new(current_pos.x, current_pos.y, thumbnail.dimensions.width, thumbnail.dimensions.height),
-                    );
-                    
-                    // Send position update for old character via IPC
+                // Publish logout of the old character and login of the new one
+                advance_lifecycle(ctx, persistent_state, thumbnail, LifecycleEvent::TitleChanged(new_character_name));
+                
+                let _ = window_rules::apply_matching(&ctx.on_screen(thumbnail.screen), &session_state.window_rules, RuleTrigger::Map, event.window, new_character_name)
+                    .inspect_err(|e| warn!(character = %new_character_name, error = ?e, "Failed to apply window rules after character change"));
+                
+                // The window identity owns the geometry; character entries are projections of it
+                let change = thumbnail.identity.change_character(new_character_name, &persistent_state.character_positions);
+                debug!(window = thumbnail.identity.window(), history = ?thumbnail.identity.name_history(), "Window character history");
+                
+                // Save old character's geometry (in-memory only, no disk write)
+                if let Some((name, settings)) = change.left
+                    && !was_duplicate
+                {
                     send_position_changed(
                         ipc_client,
-                        &old_name,
-                        current_pos.x,
-                        current_pos.y,
-                        thumbnail.dimensions.width,
-                        thumbnail.dimensions.height,
+                        &name,
+                        settings.x,
+                        settings.y,
+                        settings.dimensions.width,
+                        settings.dimensions.height,
                     );
+                    persistent_state.character_positions.insert(name, settings);
                 }
                 
-                // Determine new position: use saved position if available, otherwise keep current
-                let new_position = if !new_character_name.is_empty() {
-                    if let Some(settings) = persistent_state.character_positions.get(new_character_name) {
-                        info!(character = %new_character_name, x = settings.x, y = settings.y, "Moving to saved position for character");
-                        Some(settings.position())
-                    } else {
-                        // New character with no saved position - add it
-                        persistent_state.character_positions.insert(
-                            new_character_name.to_string(),
-                            CharacterSettings::new(current_pos.x, current_pos.y, thumbnail.dimensions.width, thumbnail.dimensions.height),
-                        );
-                        
+                // Take over the new character's saved geometry, or register it with the current one
+                let new_position = if let Some(settings) = &change.restored {
+                    info!(character = %new_character_name, x = settings.x, y = settings.y, "Moving to saved position for character");
+                    Some(settings.position())
+                } else {
+                    if let Some((name, settings)) = thumbnail.identity.projection()
+                        && !persistent_state.character_positions.contains_key(&name)
+                    {
                         // Send CharacterAdded for new character via IPC
                         send_character_added(
                             ipc_client,
-                            &new_character_name,
-                            current_pos.x,
-                            current_pos.y,
-                            thumbnail.dimensions.width,
-                            thumbnail.dimensions.height,
+                            &name,
+                            settings.x,
+                            settings.y,
+                            settings.dimensions.width,
+                            settings.dimensions.height,
                         );
-                        None
+                        persistent_state.character_positions.insert(name, settings);
                     }
-                } else {
                     None
                 };
                 
                 // Update session state
                 session_state.update_window_position(event.window, current_pos.x, current_pos.y);
                 
-                // Update thumbnail (may move to new position)
+                // Update thumbnail (may move to new position and size)
                 thumbnail.set_character_name(new_character_name.to_string(), new_position)
                     .context(format!("Failed to update thumbnail after character change from '{}'", old_name))?;
+                thumbnail.resize(thumbnail.identity.dimensions())
+                    .context(format!("Failed to resize thumbnail after character change to '{}'", new_character_name))?;
+                thumbnail.apply_character_settings(persistent_state.configured_character(new_character_name))
+                    .context(format!("Failed to apply character settings for '{}'", new_character_name))?;
                 
-            } else if event.atom == ctx.atoms.wm_name
-                && let Some(thumbnail) = check_and_create_window(ctx, persistent_state, event.window, session_state)
+                demote_duplicates(eves, event.window, ipc_client)?;
+                if !was_duplicate {
+                    restore_duplicate(persistent_state, eves, cycle_state, &old_name)?;
+                }
+                
+            } else if title_changed
+                && let Some(mut thumbnail) = check_and_create_window(ctx, persistent_state, event.window, session_state)
                     .context(format!("Failed to create thumbnail for newly detected EVE window {}", event.window))?
             {
                 // New EVE window detected via property change (EVE → EVE - CharacterName)
                 
                 // Query geometry for newly detected character
-                let geom = ctx.conn.get_geometry(thumbnail.window)
-                    .context("Failed to query geometry for newly detected thumbnail")?
-                    .reply()
+                let geom = root_geometry(ctx.conn, thumbnail.window)
                     .context("Failed to get geometry reply for newly detected thumbnail")?;
                 
                 // Update in-memory state (don't save to disk)
                 persistent_state.character_positions.insert(
                     thumbnail.character_name.clone(),
-                    CharacterSettings::new(geom.x, geom.y, thumbnail.dimensions.width, thumbnail.dimensions.height),
+                    CharacterSettings::new(geom.x, geom.y, thumbnail.identity.dimensions().width, thumbnail.identity.dimensions().height),
                 );
                 
                 // Send CharacterAdded event to GUI via IPC
@@ -562,12 +1647,19 @@ pub fn handle_event<'a>(
                     &thumbnail.character_name,
                     geom.x,
                     geom.y,
-                    thumbnail.dimensions.width,
-                    thumbnail.dimensions.height,
+                    thumbnail.identity.dimensions().width,
+                    thumbnail.identity.dimensions().height,
                 );
                 
+                let character_name = thumbnail.character_name.clone();
+                advance_lifecycle(ctx, persistent_state, &mut thumbnail, LifecycleEvent::Detected(&character_name));
+                
+                let _ = window_rules::apply_matching(&ctx.on_screen(thumbnail.screen), &session_state.window_rules, RuleTrigger::Map, event.window, &thumbnail.character_name)
+                    .inspect_err(|e| warn!(window = event.window, error = ?e, "Failed to apply window rules for newly detected window, keeping the thumbnail"));
+                
                 cycle_state.add_window(thumbnail.character_name.clone(), event.window);
                 eves.insert(event.window, thumbnail);
+                demote_duplicates(eves, event.window, ipc_client)?;
             } else if event.atom == ctx.atoms.net_wm_state
                 && let Some(thumbnail) = eves.get_mut(&event.window)
                 && let Some(state) = ctx.conn
@@ -578,8 +1670,20 @@ pub fn handle_event<'a>(
                     .value32()
                 && state.collect::<Vec<_>>().contains(&ctx.atoms.net_wm_state_hidden)
             {
-                thumbnail.minimized()
-                    .context(format!("Failed to set minimized state for '{}'", thumbnail.character_name))?;
+                if persistent_state.global.prevent_minimize {
+                    // Keep the client rendering: undo the iconify and push it behind everything instead
+                    info!(window = event.window, character = %thumbnail.character_name, "Client minimized, restoring and lowering (prevent_minimize)");
+                    unminimize_window(ctx.conn, thumbnail.screen, ctx.atoms, event.window)
+                        .context(format!("Failed to restore minimized client '{}'", thumbnail.character_name))?;
+                    lower_window(ctx.conn, event.window)
+                        .context(format!("Failed to lower restored client '{}'", thumbnail.character_name))?;
+                    ctx.conn.flush()
+                        .context("Failed to flush X11 connection after preventing minimize")?;
+                } else {
+                    thumbnail.minimized()
+                        .context(format!("Failed to set minimized state for '{}'", thumbnail.character_name))?;
+                    advance_lifecycle(ctx, persistent_state, thumbnail, LifecycleEvent::Minimized);
+                }
             }
             Ok(())
         }
@@ -0,0 +1,121 @@
+//! Recently focused characters (focus_last_hotkey, focus_back_hotkey, focus_forward_hotkey)
+//!
+//! Every time an EVE client gains focus, however it happened (hotkey, thumbnail
+//! click, the WM, clicking the client itself), its character is recorded, and
+//! the hotkeys move through that record independent of the cycle order:
+//! - focus last goes to the most recent one before the current, so one key
+//!   flips between two clients
+//! - focus back/forward walk the trail of focused characters like browser
+//!   navigation: back retraces it, forward returns, and focusing something new
+//!   after going back drops the forward part
+
+use std::collections::VecDeque;
+
+use crate::constants::focus_history;
+
+#[derive(Debug, Default)]
+pub struct FocusHistory {
+    /// Most recently focused first, each character once
+    recent: VecDeque<String>,
+    /// Focus trail, oldest first
+    trail: VecDeque<String>,
+    /// Position of the current character in the trail
+    cursor: usize,
+}
+
+impl FocusHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A character's client gained focus
+    pub fn focused(&mut self, character: &str) {
+        if self.recent.front().is_none_or(|front| front != character) {
+            self.recent.retain(|recent| recent != character);
+            self.recent.push_front(character.to_string());
+            self.recent.truncate(focus_history::LENGTH);
+        }
+
+        // Arriving where back/forward just went (or focus coming back to the same client)
+        if self.trail.get(self.cursor).is_some_and(|current| current == character) {
+            return;
+        }
+        self.trail.truncate(self.cursor + 1);
+        self.trail.push_back(character.to_string());
+        if self.trail.len() > focus_history::LENGTH {
+            self.trail.pop_front();
+        }
+        self.cursor = self.trail.len() - 1;
+    }
+
+    /// The most recently focused character before the current one that's still running
+    pub fn previous(&self, running: impl Fn(&str) -> bool) -> Option<&str> {
+        self.recent.iter()
+            .skip(1)
+            .map(String::as_str)
+            .find(|character| running(character))
+    }
+
+    /// Step back along the trail to the nearest character that's still running
+    pub fn back(&mut self, running: impl Fn(&str) -> bool) -> Option<&str> {
+        let index = (0..self.cursor).rev().find(|i| running(&self.trail[*i]))?;
+        self.cursor = index;
+        Some(&self.trail[index])
+    }
+
+    /// Step forward along the trail (after going back) to the nearest character that's still running
+    pub fn forward(&mut self, running: impl Fn(&str) -> bool) -> Option<&str> {
+        let index = (self.cursor + 1..self.trail.len()).find(|i| running(&self.trail[*i]))?;
+        self.cursor = index;
+        Some(&self.trail[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_previous_flips_between_two() {
+        let mut history = FocusHistory::new();
+        assert_eq!(history.previous(|_| true), None);
+        history.focused("Main");
+        history.focused("Scout");
+        assert_eq!(history.previous(|_| true), Some("Main"));
+        history.focused("Main");
+        assert_eq!(history.previous(|_| true), Some("Scout"));
+    }
+
+    #[test]
+    fn test_previous_skips_closed_clients() {
+        let mut history = FocusHistory::new();
+        for character in ["Hauler", "Scout", "Main"] {
+            history.focused(character);
+        }
+        assert_eq!(history.previous(|character| character != "Scout"), Some("Hauler"));
+        for i in 0..focus_history::LENGTH {
+            history.focused(&format!("Alt {}", i));
+        }
+        assert_eq!(history.recent.len(), focus_history::LENGTH);
+        assert_eq!(history.trail.len(), focus_history::LENGTH);
+    }
+
+    #[test]
+    fn test_back_and_forward_like_a_browser() {
+        let mut history = FocusHistory::new();
+        for character in ["Hauler", "Scout", "Main"] {
+            history.focused(character);
+        }
+        assert_eq!(history.forward(|_| true), None);
+        assert_eq!(history.back(|_| true), Some("Scout"));
+        // The client focused by going back reports focus: the trail stays
+        history.focused("Scout");
+        assert_eq!(history.back(|character| character != "Scout"), Some("Hauler"));
+        assert_eq!(history.back(|_| true), None);
+        assert_eq!(history.forward(|_| true), Some("Scout"));
+        // Something new after going back drops the forward part
+        history.focused("Cyno");
+        assert_eq!(history.forward(|_| true), None);
+        assert_eq!(history.back(|_| true), Some("Scout"));
+    }
+}
@@ -6,7 +6,7 @@ use std::fs;
 use std::path::PathBuf;
 use tracing::{info, warn};
 use x11rb::connection::Connection;
-use x11rb::protocol::xproto::{ConnectionExt as XprotoExt, Font as X11Font}; // X11 Font is just u32
+use x11rb::protocol::xproto::{Char2b, ConnectionExt as XprotoExt, Font as X11Font}; // X11 Font is just u32
 
 /// Rendered text as ARGB bitmap
 pub struct RenderedText {
@@ -15,6 +15,16 @@ pub struct RenderedText {
     pub data: Vec<u32>, // ARGB pixels (premultiplied alpha)
 }
 
+/// Text as the 16-bit characters core fonts draw (ImageText16)
+/// Characters beyond the Basic Multilingual Plane become '?'; one request takes at most 255
+pub fn core_font_text(text: &str) -> Vec<Char2b> {
+    text.chars()
+        .map(|ch| u16::try_from(u32::from(ch)).unwrap_or(u16::from(b'?')))
+        .map(|code| Char2b { byte1: (code >> 8) as u8, byte2: code as u8 })
+        .take(u8::MAX as usize)
+        .collect()
+}
+
 /// Font renderer with two-tier fallback: TrueType (fontdue) or X11 core fonts
 #[derive(Debug)]
 pub enum FontRenderer {
@@ -86,14 +96,35 @@ impl FontRenderer {
                 // Generate font ID and open the font
                 let font_id = conn.generate_id()
                     .context("Failed to generate X11 font ID")?;
-                conn.open_font(font_id, b"fixed")
-                    .context("Failed to open X11 'fixed' font")?;
+                let name = Self::open_core_font(conn, font_id, size)?;
                 
-                info!("Using X11 core font 'fixed' (basic rendering)");
+                info!(font = %name, "Using X11 core font (basic rendering)");
                 Ok(Self::X11Fallback { font_id, size })
             }
         }
     }
+
+    /// Open the first core font of about `size` pixels, else `fixed`; returns its name
+    fn open_core_font<C: Connection>(conn: &C, font_id: X11Font, size: f32) -> Result<String> {
+        use crate::constants::defaults::text::{CORE_FONT_FALLBACK, CORE_FONT_PATTERNS};
+        let pixels = (size.round() as u32).max(1);
+        for pattern in CORE_FONT_PATTERNS {
+            let name = pattern.replace("{}", &pixels.to_string());
+            // A pattern no font matches fails with BadName, leaving the ID free for the next try
+            let opened = conn.open_font(font_id, name.as_bytes())
+                .map_err(anyhow::Error::from)
+                .and_then(|cookie| cookie.check().map_err(anyhow::Error::from));
+            if opened.is_ok() {
+                return Ok(name);
+            }
+        }
+        conn.open_font(font_id, CORE_FONT_FALLBACK.as_bytes())
+            .context("Failed to send X11 'fixed' font open")?
+            .check()
+            .context("Failed to open X11 'fixed' font")?;
+        warn!(size = pixels, "No core font of the configured text size, labels use 'fixed'");
+        Ok(CORE_FONT_FALLBACK.to_string())
+    }
     
     /// Check if this renderer requires direct X11 rendering (cannot pre-render to bitmap)
     pub fn requires_direct_rendering(&self) -> bool {
@@ -117,7 +148,7 @@ impl FontRenderer {
     }
     
     /// Render text to an ARGB bitmap with the given foreground color (transparent background)
-    /// For X11 fallback variant, returns empty (rendering happens directly via ImageText8)
+    /// For X11 fallback variant, returns empty (rendering happens directly via ImageText16)
     pub fn render_text(
         &self,
         text: &str,
@@ -210,7 +241,7 @@ impl FontRenderer {
                 })
             }
             Self::X11Fallback { .. } => {
-                // X11 fonts use immediate-mode rendering (ImageText8)
+                // X11 fonts use immediate-mode rendering (ImageText16)
                 // Cannot pre-render to bitmap - return empty
                 // Actual rendering happens in thumbnail.rs overlay method
                 Ok(RenderedText {
@@ -222,3 +253,16 @@ impl FontRenderer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_font_text_is_ucs2() {
+        let text = core_font_text("Ré 凤🚀");
+        let codes: Vec<u16> = text.iter().map(|c| u16::from(c.byte1) << 8 | u16::from(c.byte2)).collect();
+        assert_eq!(codes, vec![0x52, 0xE9, 0x20, 0x51E4, u16::from(b'?')]);
+        assert_eq!(core_font_text(&"x".repeat(300)).len(), 255);
+    }
+}
@@ -0,0 +1,252 @@
+//! Client focus through the wlr foreign-toplevel protocol (wlroots compositors)
+//!
+//! Under a Wayland session the EVE clients run in XWayland, and wlroots
+//! compositors (sway, Hyprland, river, ...) largely ignore EWMH requests such as
+//! _NET_ACTIVE_WINDOW coming from another X client. They do list every toplevel,
+//! XWayland ones included, through zwlr_foreign_toplevel_manager_v1 and let
+//! taskbars activate or minimize them. The EVE clients are matched by their
+//! title, which the compositor copies from the X11 window.
+//!
+//! The Wayland connection is dispatched on its own thread; requests are sent
+//! from the main loop through the shared proxies.
+
+use anyhow::{Context, Result};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tracing::{debug, error, info};
+use wayland_client::globals::{registry_queue_init, BindError, GlobalListContents};
+use wayland_client::protocol::{wl_registry, wl_seat::WlSeat};
+use wayland_client::{event_created_child, Connection, Dispatch, Proxy, QueueHandle};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_handle_v1::{
+    self, ZwlrForeignToplevelHandleV1,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_manager_v1::{
+    self, ZwlrForeignToplevelManagerV1,
+};
+use x11rb::protocol::xproto::Window;
+
+use crate::x11_utils::{window_title, AppContext};
+
+/// A toplevel the compositor listed
+#[derive(Debug)]
+struct Toplevel {
+    handle: ZwlrForeignToplevelHandleV1,
+    title: String,
+    minimized: bool,
+}
+
+/// Toplevels as last reported by the compositor, shared with the dispatch thread
+type Toplevels = Arc<Mutex<Vec<Toplevel>>>;
+
+/// Connection to a compositor offering zwlr_foreign_toplevel_manager_v1
+#[derive(Debug)]
+pub struct ForeignToplevels {
+    conn: Connection,
+    seat: WlSeat,
+    toplevels: Toplevels,
+}
+
+impl ForeignToplevels {
+    /// Connect to the session's compositor and start tracking its toplevels
+    /// Returns None outside Wayland sessions and on compositors without the protocol
+    pub fn connect() -> Result<Option<Self>> {
+        if std::env::var_os("WAYLAND_DISPLAY").is_none() {
+            return Ok(None);
+        }
+        let conn = Connection::connect_to_env()
+            .context("Failed to connect to the Wayland compositor")?;
+        let (globals, mut queue) = registry_queue_init::<State>(&conn)
+            .context("Failed to list Wayland globals")?;
+        let qh = queue.handle();
+
+        let manager: ZwlrForeignToplevelManagerV1 = match globals.bind(&qh, 1..=3, ()) {
+            Ok(manager) => manager,
+            Err(BindError::NotPresent) => {
+                info!("Compositor has no wlr foreign-toplevel manager, focusing clients through EWMH");
+                return Ok(None);
+            }
+            Err(e) => return Err(e).context("Failed to bind zwlr_foreign_toplevel_manager_v1"),
+        };
+        let seat: WlSeat = globals.bind(&qh, 1..=1, ())
+            .context("Compositor has no wl_seat to activate clients with")?;
+
+        let toplevels = Toplevels::default();
+        let mut state = State { toplevels: toplevels.clone() };
+        queue.roundtrip(&mut state)
+            .context("Failed to receive the initial toplevel list")?;
+        info!(
+            toplevels = toplevels.lock().unwrap().len(),
+            version = manager.version(),
+            "Focusing clients through the wlr foreign-toplevel protocol"
+        );
+
+        thread::spawn(move || {
+            loop {
+                if let Err(e) = queue.blocking_dispatch(&mut state) {
+                    error!(error = %e, "Wayland foreign-toplevel connection lost");
+                    break;
+                }
+            }
+        });
+
+        Ok(Some(Self { conn, seat, toplevels }))
+    }
+
+    /// Activate the toplevel titled `title`, false if the compositor doesn't list it
+    pub fn activate(&self, title: &str) -> bool {
+        self.request(title, |toplevel| {
+            if toplevel.minimized {
+                toplevel.handle.unset_minimized();
+            }
+            toplevel.handle.activate(&self.seat);
+        })
+    }
+
+    /// Minimize the toplevel titled `title`, false if the compositor doesn't list it
+    pub fn minimize(&self, title: &str) -> bool {
+        self.request(title, |toplevel| toplevel.handle.set_minimized())
+    }
+
+    fn request(&self, title: &str, send: impl FnOnce(&Toplevel)) -> bool {
+        let toplevels = self.toplevels.lock().unwrap();
+        let Some(toplevel) = toplevels.iter().find(|toplevel| toplevel.title == title) else {
+            return false;
+        };
+        send(toplevel);
+        if let Err(e) = self.conn.flush() {
+            error!(error = %e, "Failed to flush Wayland connection");
+            return false;
+        }
+        true
+    }
+}
+
+/// Title of an X11 client, which the compositor reports for its XWayland toplevel
+fn client_title(ctx: &AppContext, window: Window) -> Option<String> {
+    window_title(ctx.conn, window, ctx.atoms)
+        .inspect_err(|e| debug!(window = window, error = ?e, "Failed to read client title"))
+        .ok()
+        .flatten()
+}
+
+/// Activate a client through the compositor, true if it handled the request
+/// (callers fall back to _NET_ACTIVE_WINDOW otherwise)
+pub fn try_activate(ctx: &AppContext, window: Window) -> bool {
+    let Some(toplevels) = ctx.toplevels else {
+        return false;
+    };
+    let Some(title) = client_title(ctx, window) else {
+        return false;
+    };
+    let activated = toplevels.activate(&title);
+    if activated {
+        info!(window = window, title = %title, "Activated client through foreign-toplevel");
+    }
+    activated
+}
+
+/// Minimize a client through the compositor, true if it handled the request
+pub fn try_minimize(ctx: &AppContext, window: Window) -> bool {
+    let Some(toplevels) = ctx.toplevels else {
+        return false;
+    };
+    client_title(ctx, window).is_some_and(|title| toplevels.minimize(&title))
+}
+
+/// Whether a toplevel state array (native-endian u32s) contains `wanted`
+fn has_state(states: &[u8], wanted: zwlr_foreign_toplevel_handle_v1::State) -> bool {
+    states.chunks_exact(4)
+        .map(|state| u32::from_ne_bytes([state[0], state[1], state[2], state[3]]))
+        .any(|state| state == wanted as u32)
+}
+
+struct State {
+    toplevels: Toplevels,
+}
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for State {
+    fn event(
+        _: &mut Self,
+        _: &wl_registry::WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlSeat, ()> for State {
+    fn event(_: &mut Self, _: &WlSeat, _: <WlSeat as Proxy>::Event, _: &(), _: &Connection, _: &QueueHandle<Self>) {}
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } => {
+                state.toplevels.lock().unwrap().push(Toplevel {
+                    handle: toplevel,
+                    title: String::new(),
+                    minimized: false,
+                });
+            }
+            zwlr_foreign_toplevel_manager_v1::Event::Finished => {
+                info!("Compositor stopped sending foreign-toplevel updates");
+                state.toplevels.lock().unwrap().clear();
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(State, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let mut toplevels = state.toplevels.lock().unwrap();
+        if let zwlr_foreign_toplevel_handle_v1::Event::Closed = event {
+            toplevels.retain(|toplevel| toplevel.handle != *handle);
+            handle.destroy();
+            return;
+        }
+        let Some(toplevel) = toplevels.iter_mut().find(|toplevel| toplevel.handle == *handle) else {
+            return;
+        };
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Title { title } => toplevel.title = title,
+            zwlr_foreign_toplevel_handle_v1::Event::State { state } => {
+                toplevel.minimized = has_state(&state, zwlr_foreign_toplevel_handle_v1::State::Minimized);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_state() {
+        let states: Vec<u8> = [0u32, 1].iter().flat_map(|state| state.to_ne_bytes()).collect();
+        assert!(has_state(&states, zwlr_foreign_toplevel_handle_v1::State::Minimized));
+        assert!(!has_state(&states, zwlr_foreign_toplevel_handle_v1::State::Activated));
+        assert!(!has_state(&[], zwlr_foreign_toplevel_handle_v1::State::Minimized));
+    }
+}
@@ -0,0 +1,138 @@
+//! Rate limiting for DamageNotify-driven thumbnail redraws
+//!
+//! Each damage object (one per thumbnail) may redraw at most once per frame
+//! interval. Damage arriving early is remembered and redrawn once its frame is
+//! due; the main loop wakes up for the earliest pending frame, so the last
+//! change on screen is never lost. With an unlimited rate every damage event
+//! redraws immediately, as before. The rate is thumbnail_max_fps, lowered
+//! further while low bandwidth mode is on.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use x11rb::protocol::damage::Damage;
+
+use crate::constants::low_bandwidth;
+
+/// Redraw rate to pace with: the configured cap, or low bandwidth mode's if that is lower (0 = unlimited)
+pub fn max_fps(configured: u16, low_bandwidth_active: bool) -> u16 {
+    if !low_bandwidth_active {
+        return configured;
+    }
+    match configured {
+        0 => low_bandwidth::MAX_FPS,
+        fps => fps.min(low_bandwidth::MAX_FPS),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FramePacer {
+    interval: Duration,
+    /// Last redraw per damage object
+    last: HashMap<Damage, Instant>,
+    /// Damage objects waiting for their next frame
+    pending: Vec<Damage>,
+}
+
+impl FramePacer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum redraw rate per thumbnail (0 = unlimited)
+    pub fn set_max_fps(&mut self, fps: u16) {
+        self.interval = if fps == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / fps as u32
+        };
+    }
+
+    /// Offer a damage event; returns true if the thumbnail should redraw right away
+    pub fn offer(&mut self, damage: Damage, now: Instant) -> bool {
+        let due = self.last.get(&damage).is_none_or(|last| now.duration_since(*last) >= self.interval);
+        if due {
+            self.last.insert(damage, now);
+            self.pending.retain(|d| *d != damage);
+        } else if !self.pending.contains(&damage) {
+            self.pending.push(damage);
+        }
+        due
+    }
+
+    /// When the earliest held-back redraw is due (None if nothing is pending)
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.iter()
+            .map(|damage| self.last.get(damage).map_or_else(Instant::now, |last| *last + self.interval))
+            .min()
+    }
+
+    /// Take the held-back redraws whose frame is due
+    pub fn take_due(&mut self, now: Instant) -> Vec<Damage> {
+        let interval = self.interval;
+        let last = &self.last;
+        let (due, waiting): (Vec<Damage>, Vec<Damage>) = self.pending.iter()
+            .partition(|damage| last.get(damage).is_none_or(|l| now.duration_since(*l) >= interval));
+        self.pending = waiting;
+        for damage in &due {
+            self.last.insert(*damage, now);
+        }
+        due
+    }
+
+    /// Drop state for a damage object that no longer exists
+    pub fn forget(&mut self, damage: Damage) {
+        self.last.remove(&damage);
+        self.pending.retain(|d| *d != damage);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_redraws_everything() {
+        let mut pacer = FramePacer::new();
+        let now = Instant::now();
+        assert!(pacer.offer(1, now));
+        assert!(pacer.offer(1, now));
+        assert_eq!(pacer.deadline(), None);
+    }
+
+    #[test]
+    fn test_holds_back_within_frame() {
+        let mut pacer = FramePacer::new();
+        pacer.set_max_fps(2);
+        let start = Instant::now();
+        assert!(pacer.offer(1, start));
+        assert!(!pacer.offer(1, start + Duration::from_millis(100)));
+        assert!(!pacer.offer(1, start + Duration::from_millis(200)));
+        assert_eq!(pacer.deadline(), Some(start + Duration::from_millis(500)));
+
+        assert!(pacer.take_due(start + Duration::from_millis(400)).is_empty());
+        assert_eq!(pacer.take_due(start + Duration::from_millis(500)), vec![1]);
+        assert_eq!(pacer.deadline(), None);
+    }
+
+    #[test]
+    fn test_max_fps_takes_the_lower_cap() {
+        assert_eq!(max_fps(0, false), 0);
+        assert_eq!(max_fps(24, false), 24);
+        assert_eq!(max_fps(0, true), low_bandwidth::MAX_FPS);
+        assert_eq!(max_fps(24, true), low_bandwidth::MAX_FPS);
+        assert_eq!(max_fps(1, true), 1);
+    }
+
+    #[test]
+    fn test_thumbnails_are_paced_independently() {
+        let mut pacer = FramePacer::new();
+        pacer.set_max_fps(2);
+        let start = Instant::now();
+        assert!(pacer.offer(1, start));
+        assert!(pacer.offer(2, start + Duration::from_millis(100)));
+        assert!(!pacer.offer(1, start + Duration::from_millis(100)));
+        pacer.forget(1);
+        assert_eq!(pacer.deadline(), None);
+    }
+}
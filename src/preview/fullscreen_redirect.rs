@@ -0,0 +1,62 @@
+//! Keep fullscreen clients redirected under a compositor (redirect_fullscreen_clients)
+//!
+//! Compositors like picom unredirect a fullscreen window to skip compositing it
+//! (unredir-if-possible). The XRender path then reads the client straight from
+//! the screen, so its preview shows whatever covers it, previews included.
+//! Redirecting the client ourselves keeps its contents off-screen; the
+//! redirection is automatic, so the X server still shows it as usual.
+
+use anyhow::{Context, Result};
+use tracing::info;
+
+use crate::config::profile::GlobalSettings;
+use crate::types::{Dimensions, Position};
+use crate::x11_utils::{compositor_running, list_monitors, root_geometry, AppContext};
+
+use super::thumbnail::Thumbnail;
+
+/// Whether a window at `position` with `dimensions` covers a whole monitor
+pub fn covers_monitor(position: Position, dimensions: Dimensions, monitors: &[(Position, Dimensions)]) -> bool {
+    let (left, top) = (position.x as i32, position.y as i32);
+    let (right, bottom) = (left + dimensions.width as i32, top + dimensions.height as i32);
+    monitors.iter().any(|(origin, size)| {
+        let (x, y) = (origin.x as i32, origin.y as i32);
+        left <= x && top <= y && right >= x + size.width as i32 && bottom >= y + size.height as i32
+    })
+}
+
+/// Redirect `thumbnail`'s client if it went fullscreen while a compositor runs
+/// Once redirected it stays so until the daemon exits
+pub fn check(ctx: &AppContext, global: &GlobalSettings, thumbnail: &mut Thumbnail) -> Result<()> {
+    if !global.redirect_fullscreen_clients || thumbnail.is_redirected() {
+        return Ok(());
+    }
+    let geom = root_geometry(ctx.conn, thumbnail.src)
+        .context(format!("Failed to query geometry of client '{}'", thumbnail.character_name))?;
+    let monitors = list_monitors(ctx.conn, thumbnail.screen);
+    if !covers_monitor(Position::new(geom.x, geom.y), Dimensions::new(geom.width, geom.height), &monitors)
+        || !compositor_running(ctx.conn, thumbnail.screen)
+    {
+        return Ok(());
+    }
+    info!(window = thumbnail.src, character = %thumbnail.character_name, "Fullscreen client under a compositor, keeping it redirected for its preview");
+    thumbnail.keep_redirected()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_covers_monitor() {
+        let monitors = [
+            (Position::new(0, 0), Dimensions::new(1920, 1080)),
+            (Position::new(1920, 0), Dimensions::new(2560, 1440)),
+        ];
+        assert!(covers_monitor(Position::new(1920, 0), Dimensions::new(2560, 1440), &monitors));
+        // Spanning both monitors covers each of them
+        assert!(covers_monitor(Position::new(0, 0), Dimensions::new(4480, 1440), &monitors));
+        assert!(!covers_monitor(Position::new(0, 0), Dimensions::new(1920, 1050), &monitors));
+        assert!(!covers_monitor(Position::new(10, 0), Dimensions::new(1920, 1080), &monitors));
+    }
+}
@@ -0,0 +1,646 @@
+//! Optional OpenGL rendering backend (cargo feature `gl`)
+//!
+//! The XRender path scales every thumbnail inside the X server on each damage
+//! event. With `render_backend: "gl"` the client's window contents (named as a
+//! pixmap through Composite) are bound as a texture with
+//! GLX_EXT_texture_from_pixmap and scaled by the GPU instead. All thumbnails
+//! share one GL context on a second (Xlib) connection; each thumbnail window
+//! gets a GLX drawable, and the border/name overlay is still composited with
+//! XRender on top. Draws are batched into frames: the main loop waits for the
+//! GPU once per frame (`finish_frame`) and only then puts the overlays on, so
+//! ten clients drawing cost one wait rather than ten. Anything missing on the
+//! display (no GLX, no texture_from_pixmap, no usable config) makes setup fail
+//! and the thumbnail stays on XRender.
+//!
+//! GLX and GL are C APIs, so this module is exempt from the crate-wide
+//! `deny(unsafe_code)`; every unsafe block is kept to a single FFI call or
+//! object lifetime rule.
+
+#![allow(unsafe_code)]
+
+use std::cell::{Cell, RefCell};
+use std::ffi::{c_int, c_ulong, c_void, CStr, CString};
+use std::fmt;
+use std::ptr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Instant;
+
+use anyhow::{anyhow, Context, Result};
+use glow::HasContext;
+use tracing::{debug, info};
+use x11_dl::glx::{self, Glx, GLXContext, GLXFBConfig};
+use x11_dl::xlib::{self, Display, Xlib};
+use x11rb::connection::Connection;
+use x11rb::protocol::composite::{ConnectionExt as CompositeExt, Redirect};
+use x11rb::protocol::xproto::{ConnectionExt as _, GetGeometryReply, Pixmap, Window};
+use x11rb::rust_connection::RustConnection;
+
+use crate::types::Dimensions;
+use crate::x11_utils::AppContext;
+
+// GLX_EXT_texture_from_pixmap tokens (not exported by x11-dl)
+const GLX_BIND_TO_TEXTURE_RGB_EXT: c_int = 0x20D0;
+const GLX_BIND_TO_TEXTURE_RGBA_EXT: c_int = 0x20D1;
+const GLX_BIND_TO_TEXTURE_TARGETS_EXT: c_int = 0x20D3;
+const GLX_Y_INVERTED_EXT: c_int = 0x20D4;
+const GLX_TEXTURE_FORMAT_EXT: c_int = 0x20D5;
+const GLX_TEXTURE_TARGET_EXT: c_int = 0x20D6;
+const GLX_TEXTURE_FORMAT_RGB_EXT: c_int = 0x20D9;
+const GLX_TEXTURE_FORMAT_RGBA_EXT: c_int = 0x20DA;
+const GLX_TEXTURE_2D_BIT_EXT: c_int = 0x0002;
+const GLX_TEXTURE_2D_EXT: c_int = 0x20DC;
+const GLX_FRONT_LEFT_EXT: c_int = 0x20DE;
+
+type BindTexImageFn = unsafe extern "C" fn(*mut Display, glx::GLXDrawable, c_int, *const c_int);
+type ReleaseTexImageFn = unsafe extern "C" fn(*mut Display, glx::GLXDrawable, c_int);
+
+/// Full-viewport quad; the texture row is picked per fbconfig orientation
+const VERTEX_SHADER: &str = "#version 120
+attribute vec2 position;
+uniform float inverted;
+varying vec2 uv;
+void main() {
+    uv = vec2((position.x + 1.0) * 0.5, mix((1.0 + position.y) * 0.5, (1.0 - position.y) * 0.5, inverted));
+    gl_Position = vec4(position, 0.0, 1.0);
+}
+";
+
+const FRAGMENT_SHADER: &str = "#version 120
+uniform sampler2D source;
+varying vec2 uv;
+void main() {
+    gl_FragColor = vec4(texture2D(source, uv).rgb, 1.0);
+}
+";
+
+/// Last Xlib error code seen on the GL connection (0 = none)
+/// Xlib's default handler exits the process, so errors are recorded and checked instead
+static LAST_X_ERROR: AtomicU8 = AtomicU8::new(0);
+
+unsafe extern "C" fn record_x_error(_display: *mut Display, event: *mut xlib::XErrorEvent) -> c_int {
+    // SAFETY: Xlib passes a valid error event for the duration of the call
+    let code = unsafe { (*event).error_code };
+    LAST_X_ERROR.store(code.max(1), Ordering::Relaxed);
+    0
+}
+
+/// fbconfig that can bind pixmaps of one depth as a texture
+struct PixmapConfig {
+    depth: u8,
+    config: GLXFBConfig,
+    format: c_int,
+    y_inverted: bool,
+}
+
+/// Shared GL state: Xlib connection, context and the thumbnail shader
+pub struct GlBackend {
+    xlib: Xlib,
+    glx: Glx,
+    display: *mut Display,
+    /// Root of the screen the backend renders on (thumbnails elsewhere stay on XRender)
+    root: Window,
+    window_config: GLXFBConfig,
+    pixmap_configs: Vec<PixmapConfig>,
+    context: GLXContext,
+    gl: glow::Context,
+    program: glow::Program,
+    quad: glow::Buffer,
+    inverted: Option<glow::UniformLocation>,
+    bind_tex_image: BindTexImageFn,
+    release_tex_image: ReleaseTexImageFn,
+    /// When the first draw of the unfinished frame was issued (None = nothing to finish)
+    frame_started: Cell<Option<Instant>>,
+    /// Drawable the context is current on (0 = unbound)
+    current: Cell<glx::GLXDrawable>,
+}
+
+impl fmt::Debug for GlBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GlBackend").field("root", &self.root).finish_non_exhaustive()
+    }
+}
+
+impl GlBackend {
+    /// Set up GLX on the context's screen; fails if texture_from_pixmap isn't usable there
+    pub fn new(ctx: &AppContext) -> Result<Self> {
+        ctx.conn.composite_query_version(0, 4)
+            .context("Failed to query Composite extension version")?
+            .reply()
+            .context("Composite extension is not available")?;
+
+        let xlib = Xlib::open().context("Failed to load libX11")?;
+        let glx = Glx::open().context("Failed to load libGL")?;
+        // SAFETY: XOpenDisplay with NULL opens $DISPLAY, the same server x11rb talks to
+        let display = unsafe { (xlib.XOpenDisplay)(ptr::null()) };
+        if display.is_null() {
+            return Err(anyhow!("Failed to open Xlib connection for GLX"));
+        }
+        // From here on `backend` owns the display and closes it on any error
+        let mut backend = PartialBackend { xlib, glx, display, context: ptr::null_mut() };
+        // SAFETY: installing a handler that only records the error code
+        unsafe { (backend.xlib.XSetErrorHandler)(Some(record_x_error)) };
+
+        let screen = (0..ctx.conn.setup().roots.len() as c_int)
+            // SAFETY: screen index is within the display's screen count
+            .find(|&n| unsafe { (backend.xlib.XRootWindow)(display, n) } == ctx.screen.root as c_ulong)
+            .ok_or_else(|| anyhow!("Root window {} not found on Xlib connection", ctx.screen.root))?;
+
+        // SAFETY: valid display and screen; the returned string is owned by GLX
+        let extensions = unsafe {
+            let raw = (backend.glx.glXQueryExtensionsString)(display, screen);
+            if raw.is_null() { String::new() } else { CStr::from_ptr(raw).to_string_lossy().into_owned() }
+        };
+        if !extensions.split_whitespace().any(|ext| ext == "GLX_EXT_texture_from_pixmap") {
+            return Err(anyhow!("GLX_EXT_texture_from_pixmap is not supported"));
+        }
+
+        let window_config = backend.window_config(screen, ctx.screen.root_visual)?;
+        let pixmap_configs = backend.pixmap_configs(screen);
+        if pixmap_configs.is_empty() {
+            return Err(anyhow!("No GLX config can bind pixmaps as textures"));
+        }
+
+        // SAFETY: valid display and fbconfig, no share context
+        backend.context = unsafe {
+            (backend.glx.glXCreateNewContext)(display, window_config, glx::GLX_RGBA_TYPE, ptr::null_mut(), 1)
+        };
+        if backend.context.is_null() {
+            return Err(anyhow!("Failed to create GLX context"));
+        }
+
+        let bind_tex_image = backend.proc_address("glXBindTexImageEXT")?;
+        let release_tex_image = backend.proc_address("glXReleaseTexImageEXT")?;
+        // SAFETY: the looked-up symbols have the signatures from the extension spec
+        let (bind_tex_image, release_tex_image) = unsafe {
+            (
+                std::mem::transmute::<unsafe extern "C" fn(), BindTexImageFn>(bind_tex_image),
+                std::mem::transmute::<unsafe extern "C" fn(), ReleaseTexImageFn>(release_tex_image),
+            )
+        };
+
+        // Shader objects are created with the context current on a GLX window; any thumbnail
+        // would do, but none exists yet, so a throwaway window on the root is used
+        let probe = ctx.conn.generate_id().context("Failed to generate ID for GL probe window")?;
+        ctx.conn.create_window(
+            ctx.screen.root_depth,
+            probe,
+            ctx.screen.root,
+            0, 0, 1, 1, 0,
+            x11rb::protocol::xproto::WindowClass::INPUT_OUTPUT,
+            ctx.screen.root_visual,
+            &Default::default(),
+        )
+        .context("Failed to create GL probe window")?
+        .check()
+        .context("Failed to create GL probe window")?;
+        let setup = backend.make_current_on(probe as c_ulong, window_config)
+            .and_then(|probe_drawable| {
+                let glx_open = &backend.glx;
+                // SAFETY: context is current; function pointers come from this context's GLX
+                let gl = unsafe {
+                    glow::Context::from_loader_function(|name| {
+                        let name = CString::new(name).expect("GL symbol names contain no NUL");
+                        (glx_open.glXGetProcAddress)(name.as_ptr().cast())
+                            .map_or(ptr::null(), |f| f as *const c_void)
+                    })
+                };
+                // SAFETY: context is current
+                let objects = unsafe { create_objects(&gl) };
+                // SAFETY: unbind before the probe drawable goes away
+                unsafe {
+                    (backend.glx.glXMakeContextCurrent)(display, 0, 0, ptr::null_mut());
+                    (backend.glx.glXDestroyWindow)(display, probe_drawable);
+                }
+                objects.map(|(program, quad, inverted)| (gl, program, quad, inverted))
+            });
+        let _ = ctx.conn.destroy_window(probe);
+        let (gl, program, quad, inverted) = setup?;
+
+        info!(
+            root = ctx.screen.root,
+            depths = ?pixmap_configs.iter().map(|c| c.depth).collect::<Vec<_>>(),
+            "OpenGL thumbnail backend ready"
+        );
+        // Ownership of the handles moves to the finished backend
+        let partial = std::mem::ManuallyDrop::new(backend);
+        // SAFETY: each field is read exactly once and `partial` is never dropped
+        let (xlib, glx) = unsafe { (ptr::read(&partial.xlib), ptr::read(&partial.glx)) };
+        Ok(Self {
+            xlib,
+            glx,
+            display,
+            root: ctx.screen.root,
+            window_config,
+            pixmap_configs,
+            context: partial.context,
+            gl,
+            program,
+            quad,
+            inverted,
+            bind_tex_image,
+            release_tex_image,
+            frame_started: Cell::new(None),
+            current: Cell::new(0),
+        })
+    }
+
+    /// Whether thumbnails on this root window can use the backend
+    pub fn serves(&self, root: Window) -> bool {
+        self.root == root
+    }
+
+    /// Sync the Xlib connection and turn a recorded X error into an error
+    fn check_errors(&self, what: &str) -> Result<()> {
+        // SAFETY: valid display
+        unsafe { (self.xlib.XSync)(self.display, 0) };
+        match LAST_X_ERROR.swap(0, Ordering::Relaxed) {
+            0 => Ok(()),
+            code => Err(anyhow!("X error {} while trying to {}", code, what)),
+        }
+    }
+
+    /// When the first draw waiting for `finish_frame` was issued
+    pub fn frame_started(&self) -> Option<Instant> {
+        self.frame_started.get()
+    }
+
+    /// Wait once for every draw issued since the last frame
+    /// An X error during any of them fails the whole frame
+    pub fn finish_frame(&self) -> Result<()> {
+        if self.frame_started.take().is_none() {
+            return Ok(());
+        }
+        self.wait_gl();
+        self.check_errors("draw thumbnails")
+    }
+
+    /// Block until the GPU is done with everything issued so far
+    fn wait_gl(&self) {
+        if self.current.get() != 0 {
+            // SAFETY: the context is current
+            unsafe { self.gl.finish() };
+        }
+    }
+
+    fn make_current(&self, drawable: glx::GLXDrawable) -> Result<()> {
+        // SAFETY: valid display, drawable created from window_config and a live context
+        if unsafe { (self.glx.glXMakeContextCurrent)(self.display, drawable, drawable, self.context) } == 0 {
+            return Err(anyhow!("Failed to make GL context current"));
+        }
+        self.current.set(drawable);
+        Ok(())
+    }
+}
+
+impl Drop for GlBackend {
+    fn drop(&mut self) {
+        // SAFETY: objects belong to this context; it is destroyed last, then the display closed
+        unsafe {
+            self.gl.delete_program(self.program);
+            self.gl.delete_buffer(self.quad);
+            (self.glx.glXMakeContextCurrent)(self.display, 0, 0, ptr::null_mut());
+            (self.glx.glXDestroyContext)(self.display, self.context);
+            (self.xlib.XCloseDisplay)(self.display);
+        }
+    }
+}
+
+/// Xlib/GLX handles during setup, released again if setup fails half-way
+struct PartialBackend {
+    xlib: Xlib,
+    glx: Glx,
+    display: *mut Display,
+    context: GLXContext,
+}
+
+impl PartialBackend {
+    /// Single-buffered RGBA config matching the thumbnail windows' visual
+    fn window_config(&self, screen: c_int, visual: u32) -> Result<GLXFBConfig> {
+        let attribs = [
+            glx::GLX_DRAWABLE_TYPE, glx::GLX_WINDOW_BIT,
+            glx::GLX_RENDER_TYPE, glx::GLX_RGBA_BIT,
+            glx::GLX_X_RENDERABLE, 1,
+            glx::GLX_DOUBLEBUFFER, 0,
+            0,
+        ];
+        self.choose_configs(screen, &attribs).into_iter()
+            .find(|&config| self.config_attrib(config, glx::GLX_VISUAL_ID) as u32 == visual)
+            .ok_or_else(|| anyhow!("No single-buffered GLX config for visual {:#x}", visual))
+    }
+
+    /// First texture-bindable pixmap config per depth (24 for EVE clients, 32 for ARGB windows)
+    fn pixmap_configs(&self, screen: c_int) -> Vec<PixmapConfig> {
+        let attribs = [
+            glx::GLX_DRAWABLE_TYPE, glx::GLX_PIXMAP_BIT,
+            GLX_BIND_TO_TEXTURE_TARGETS_EXT, GLX_TEXTURE_2D_BIT_EXT,
+            glx::GLX_X_RENDERABLE, 1,
+            0,
+        ];
+        let mut found: Vec<PixmapConfig> = Vec::new();
+        for config in self.choose_configs(screen, &attribs) {
+            // SAFETY: valid display and config; the visual info is freed right after reading it
+            let depth = unsafe {
+                let info = (self.glx.glXGetVisualFromFBConfig)(self.display, config);
+                if info.is_null() {
+                    continue;
+                }
+                let depth = (*info).depth;
+                (self.xlib.XFree)(info.cast());
+                depth
+            };
+            let format = match depth {
+                24 if self.config_attrib(config, GLX_BIND_TO_TEXTURE_RGB_EXT) != 0 => GLX_TEXTURE_FORMAT_RGB_EXT,
+                32 if self.config_attrib(config, GLX_BIND_TO_TEXTURE_RGBA_EXT) != 0 => GLX_TEXTURE_FORMAT_RGBA_EXT,
+                _ => continue,
+            };
+            if found.iter().any(|c| c.depth as c_int == depth) {
+                continue;
+            }
+            found.push(PixmapConfig {
+                depth: depth as u8,
+                config,
+                format,
+                y_inverted: self.config_attrib(config, GLX_Y_INVERTED_EXT) != 0,
+            });
+        }
+        found
+    }
+
+    fn choose_configs(&self, screen: c_int, attribs: &[c_int]) -> Vec<GLXFBConfig> {
+        let mut count = 0;
+        // SAFETY: zero-terminated attribute list; the returned array is copied and freed
+        unsafe {
+            let configs = (self.glx.glXChooseFBConfig)(self.display, screen, attribs.as_ptr(), &mut count);
+            if configs.is_null() {
+                return Vec::new();
+            }
+            let list = std::slice::from_raw_parts(configs, count.max(0) as usize).to_vec();
+            (self.xlib.XFree)(configs.cast());
+            list
+        }
+    }
+
+    fn config_attrib(&self, config: GLXFBConfig, attrib: c_int) -> c_int {
+        let mut value = 0;
+        // SAFETY: valid display and config
+        unsafe { (self.glx.glXGetFBConfigAttrib)(self.display, config, attrib, &mut value) };
+        value
+    }
+
+    fn proc_address(&self, name: &str) -> Result<unsafe extern "C" fn()> {
+        let symbol = CString::new(name).expect("GLX symbol names contain no NUL");
+        // SAFETY: NUL-terminated symbol name
+        unsafe { (self.glx.glXGetProcAddress)(symbol.as_ptr().cast()) }
+            .ok_or_else(|| anyhow!("{} is not available", name))
+    }
+
+    /// Wrap `window` in a GLX drawable and make the context current on it
+    fn make_current_on(&self, window: c_ulong, config: GLXFBConfig) -> Result<glx::GLXDrawable> {
+        // SAFETY: valid display, config and X window
+        let drawable = unsafe { (self.glx.glXCreateWindow)(self.display, config, window, ptr::null()) };
+        // SAFETY: valid display
+        unsafe { (self.xlib.XSync)(self.display, 0) };
+        if drawable == 0 || LAST_X_ERROR.swap(0, Ordering::Relaxed) != 0 {
+            return Err(anyhow!("Failed to create GLX window"));
+        }
+        // SAFETY: drawable was created from the context's config
+        if unsafe { (self.glx.glXMakeContextCurrent)(self.display, drawable, drawable, self.context) } == 0 {
+            // SAFETY: drawable is ours
+            unsafe { (self.glx.glXDestroyWindow)(self.display, drawable) };
+            return Err(anyhow!("Failed to make GL context current"));
+        }
+        Ok(drawable)
+    }
+}
+
+impl Drop for PartialBackend {
+    fn drop(&mut self) {
+        // SAFETY: handles were created by this setup attempt and are not shared
+        unsafe {
+            if !self.context.is_null() {
+                (self.glx.glXDestroyContext)(self.display, self.context);
+            }
+            (self.xlib.XCloseDisplay)(self.display);
+        }
+    }
+}
+
+/// Compile the thumbnail shader and upload the quad (context must be current)
+unsafe fn create_objects(gl: &glow::Context) -> Result<(glow::Program, glow::Buffer, Option<glow::UniformLocation>)> {
+    // SAFETY: caller guarantees a current context
+    unsafe {
+        let program = gl.create_program().map_err(|e| anyhow!("Failed to create GL program: {}", e))?;
+        let mut shaders = Vec::new();
+        for (kind, source) in [(glow::VERTEX_SHADER, VERTEX_SHADER), (glow::FRAGMENT_SHADER, FRAGMENT_SHADER)] {
+            let shader = gl.create_shader(kind).map_err(|e| anyhow!("Failed to create GL shader: {}", e))?;
+            gl.shader_source(shader, source);
+            gl.compile_shader(shader);
+            if !gl.get_shader_compile_status(shader) {
+                let log = gl.get_shader_info_log(shader);
+                gl.delete_shader(shader);
+                gl.delete_program(program);
+                return Err(anyhow!("Failed to compile thumbnail shader: {}", log));
+            }
+            gl.attach_shader(program, shader);
+            shaders.push(shader);
+        }
+        gl.bind_attrib_location(program, 0, "position");
+        gl.link_program(program);
+        for shader in shaders {
+            gl.detach_shader(program, shader);
+            gl.delete_shader(shader);
+        }
+        if !gl.get_program_link_status(program) {
+            let log = gl.get_program_info_log(program);
+            gl.delete_program(program);
+            return Err(anyhow!("Failed to link thumbnail shader: {}", log));
+        }
+
+        gl.use_program(Some(program));
+        gl.uniform_1_i32(gl.get_uniform_location(program, "source").as_ref(), 0);
+        let inverted = gl.get_uniform_location(program, "inverted");
+
+        let quad = gl.create_buffer().map_err(|e| anyhow!("Failed to create GL buffer: {}", e))?;
+        let vertices: Vec<u8> = [-1.0f32, -1.0, 1.0, -1.0, -1.0, 1.0, 1.0, 1.0]
+            .iter()
+            .flat_map(|v| v.to_ne_bytes())
+            .collect();
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(quad));
+        gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, &vertices, glow::STATIC_DRAW);
+        Ok((program, quad, inverted))
+    }
+}
+
+/// Source window pixmap bound as a texture; renamed whenever the client is resized
+struct BoundSource {
+    pixmap: Pixmap,
+    glx_pixmap: glx::GLXPixmap,
+    texture: glow::Texture,
+    width: u16,
+    height: u16,
+    y_inverted: bool,
+}
+
+/// Per-thumbnail GL target: GLX drawable for the thumbnail window and its bound source
+pub struct GlSurface<'a> {
+    backend: &'a GlBackend,
+    conn: &'a RustConnection,
+    drawable: glx::GLXWindow,
+    source: RefCell<Option<BoundSource>>,
+}
+
+impl fmt::Debug for GlSurface<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GlSurface").field("drawable", &self.drawable).finish_non_exhaustive()
+    }
+}
+
+impl<'a> GlSurface<'a> {
+    /// Prepare GL drawing from `src` into the thumbnail `window`
+    pub fn new(backend: &'a GlBackend, conn: &'a RustConnection, window: Window, src: Window) -> Result<Self> {
+        // Window contents only exist as a pixmap while redirected; automatic redirection
+        // coexists with a running compositor and ends with our connection
+        conn.composite_redirect_window(src, Redirect::AUTOMATIC)
+            .context(format!("Failed to send redirect request for window {}", src))?
+            .check()
+            .context(format!("Failed to redirect window {}", src))?;
+
+        // SAFETY: valid display and config; the thumbnail window uses the root visual the config matches
+        let drawable = unsafe {
+            (backend.glx.glXCreateWindow)(backend.display, backend.window_config, window as c_ulong, ptr::null())
+        };
+        backend.check_errors("create GLX window")?;
+        if drawable == 0 {
+            return Err(anyhow!("Failed to create GLX window for thumbnail {}", window));
+        }
+        Ok(Self { backend, conn, drawable, source: RefCell::new(None) })
+    }
+
+    /// Draw the source window scaled to `dimensions`
+    /// Only issues the draw: it is done once the backend's `finish_frame` returns
+    pub fn draw(&self, src: Window, geom: &GetGeometryReply, dimensions: Dimensions) -> Result<()> {
+        let backend = self.backend;
+        backend.make_current(self.drawable)?;
+
+        let mut source = self.source.borrow_mut();
+        // Composite allocates a new pixmap whenever the client is resized
+        if source.as_ref().is_some_and(|s| s.width != geom.width || s.height != geom.height)
+            && let Some(stale) = source.take()
+        {
+            self.free_source(stale);
+        }
+        if source.is_none() {
+            *source = Some(self.bind_source(src, geom)?);
+        }
+        let Some(bound) = source.as_ref() else {
+            return Ok(());
+        };
+
+        let gl = &backend.gl;
+        // SAFETY: context is current on this surface; all objects belong to it
+        unsafe {
+            gl.viewport(0, 0, dimensions.width as i32, dimensions.height as i32);
+            gl.use_program(Some(backend.program));
+            gl.uniform_1_f32(backend.inverted.as_ref(), if bound.y_inverted { 1.0 } else { 0.0 });
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, Some(bound.texture));
+            (backend.bind_tex_image)(backend.display, bound.glx_pixmap, GLX_FRONT_LEFT_EXT, ptr::null());
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(backend.quad));
+            gl.enable_vertex_attrib_array(0);
+            gl.vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, 0, 0);
+            gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+            (backend.release_tex_image)(backend.display, bound.glx_pixmap, GLX_FRONT_LEFT_EXT);
+        }
+        if backend.frame_started.get().is_none() {
+            backend.frame_started.set(Some(Instant::now()));
+        }
+        Ok(())
+    }
+
+    /// Forget the bound pixmap (client was reparented or remapped); rebound on the next draw
+    pub fn release_source(&self) {
+        if let Some(bound) = self.source.borrow_mut().take() {
+            self.free_source(bound);
+        }
+    }
+
+    fn bind_source(&self, src: Window, geom: &GetGeometryReply) -> Result<BoundSource> {
+        let backend = self.backend;
+        let config = backend.pixmap_configs.iter()
+            .find(|c| c.depth == geom.depth)
+            .ok_or_else(|| anyhow!("No GLX config binds depth {} pixmaps", geom.depth))?;
+
+        let pixmap = self.conn.generate_id()
+            .context("Failed to generate ID for source pixmap")?;
+        // Checked so the pixmap exists on the server before the Xlib connection refers to it
+        self.conn.composite_name_window_pixmap(src, pixmap)
+            .context(format!("Failed to send name pixmap request for window {}", src))?
+            .check()
+            .context(format!("Failed to name pixmap of window {} (unmapped?)", src))?;
+
+        let attribs = [
+            GLX_TEXTURE_TARGET_EXT, GLX_TEXTURE_2D_EXT,
+            GLX_TEXTURE_FORMAT_EXT, config.format,
+            0,
+        ];
+        // SAFETY: valid display, config matching the pixmap depth, zero-terminated attributes
+        let glx_pixmap = unsafe {
+            (backend.glx.glXCreatePixmap)(backend.display, config.config, pixmap as c_ulong, attribs.as_ptr())
+        };
+        if let Err(e) = backend.check_errors("create GLX pixmap") {
+            let _ = self.conn.free_pixmap(pixmap);
+            return Err(e);
+        }
+
+        let gl = &backend.gl;
+        // SAFETY: context is current (draw made it current before binding)
+        let texture = unsafe {
+            let texture = gl.create_texture().map_err(|e| anyhow!("Failed to create GL texture: {}", e))?;
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, glow::LINEAR as i32);
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, glow::LINEAR as i32);
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE as i32);
+            gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE as i32);
+            texture
+        };
+        debug!(window = src, width = geom.width, height = geom.height, depth = geom.depth, "Bound source pixmap as GL texture");
+        Ok(BoundSource {
+            pixmap,
+            glx_pixmap,
+            texture,
+            width: geom.width,
+            height: geom.height,
+            y_inverted: config.y_inverted,
+        })
+    }
+
+    fn free_source(&self, bound: BoundSource) {
+        let backend = self.backend;
+        // SAFETY: the texture and GLX pixmap were created by this backend
+        unsafe {
+            if backend.make_current(self.drawable).is_ok() {
+                backend.gl.delete_texture(bound.texture);
+            }
+            (backend.glx.glXDestroyPixmap)(backend.display, bound.glx_pixmap);
+        }
+        let _ = backend.check_errors("destroy GLX pixmap");
+        let _ = self.conn.free_pixmap(bound.pixmap);
+    }
+}
+
+impl Drop for GlSurface<'_> {
+    fn drop(&mut self) {
+        self.release_source();
+        let backend = self.backend;
+        // The frame's other draws must be done before the context is unbound;
+        // their overlays still go on when the main loop finishes the frame
+        backend.wait_gl();
+        // SAFETY: the drawable was created by this backend; unbind before destroying it
+        unsafe {
+            (backend.glx.glXMakeContextCurrent)(backend.display, 0, 0, ptr::null_mut());
+            (backend.glx.glXDestroyWindow)(backend.display, self.drawable);
+        }
+        backend.current.set(0);
+        let _ = backend.check_errors("destroy GLX window");
+    }
+}
@@ -0,0 +1,69 @@
+//! Keyboard hotkey listener for the configured backend (hotkey_backend)
+//!
+//! The backend is a global setting, and settings only arrive from the GUI
+//! after startup, so the listener starts once they are there and is started
+//! again whenever hotkey_backend changes. Listener threads block on their
+//! input, so a replaced one is only told to stop: it exits at its next event
+//! without forwarding it.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::config::profile::HotkeyBackend;
+use crate::hotkeys::{self, CharacterHotkeys, CycleCommand};
+use crate::portal_shortcuts;
+
+pub struct HotkeyListener {
+    sender: Sender<CycleCommand>,
+    bindings: CharacterHotkeys,
+    /// Backend the running listener was started for, and its stop flag
+    running: Option<(HotkeyBackend, Arc<AtomicBool>)>,
+}
+
+impl HotkeyListener {
+    pub fn new(sender: Sender<CycleCommand>, bindings: CharacterHotkeys) -> Self {
+        Self { sender, bindings, running: None }
+    }
+
+    /// Start the listener for `backend`, replacing one started for another backend
+    pub fn sync(&mut self, backend: HotkeyBackend) {
+        if self.running.as_ref().is_some_and(|(running, _)| *running == backend) {
+            return;
+        }
+        if let Some((previous, stop)) = self.running.take() {
+            info!(previous = ?previous, backend = ?backend, "Hotkey backend changed, restarting hotkey listener");
+            stop.store(true, Ordering::Relaxed);
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        self.spawn(backend, stop.clone());
+        // Remembered even if it failed, so every settings update doesn't retry it
+        self.running = Some((backend, stop));
+    }
+
+    /// The GlobalShortcuts portal on Wayland sessions, evdev otherwise (skipped if permissions are denied)
+    fn spawn(&self, backend: HotkeyBackend, stop: Arc<AtomicBool>) {
+        if portal_shortcuts::use_portal(backend) {
+            match portal_shortcuts::spawn_listener(self.sender.clone(), self.bindings.clone(), stop.clone()) {
+                Ok(_) => return,
+                Err(e) => warn!(error = %e, "GlobalShortcuts portal unavailable, falling back to evdev hotkeys"),
+            }
+        }
+        self.spawn_evdev(stop);
+    }
+
+    fn spawn_evdev(&self, stop: Arc<AtomicBool>) {
+        if !hotkeys::check_permissions() {
+            hotkeys::print_permission_error();
+            return;
+        }
+        match hotkeys::spawn_listener(self.sender.clone(), self.bindings.clone(), stop) {
+            Ok(_) => info!(enabled = true, "Hotkey support enabled (Tab/Shift+Tab for character cycling)"),
+            Err(e) => {
+                error!(error = %e, "Failed to start hotkey listener");
+                hotkeys::print_permission_error();
+            }
+        }
+    }
+}
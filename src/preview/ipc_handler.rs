@@ -6,7 +6,11 @@ use std::sync::{Arc, Mutex};
 use tracing::{debug, error, info, warn};
 
 use crate::config::daemon_state::PersistentState;
+use crate::config::overrides;
+use crate::hotkey_conflicts::{GrabChecker, HotkeyConflict};
+use crate::hotkeys::{self, CharacterHotkeys, CycleCommand};
 use crate::ipc::{PreviewRequest, PreviewResponse, PreviewServer};
+use crate::portal_shortcuts;
 
 /// Connection handle for a single GUI client
 pub struct ClientConnection {
@@ -25,27 +29,77 @@ impl ClientConnection {
     }
 }
 
+/// Where the IPC thread hands requests to the main loop
+pub struct MainLoopChannels {
+    pub shutdown: mpsc::Sender<()>,
+    pub client: mpsc::Sender<Arc<Mutex<ClientConnection>>>,
+    /// Characters edited live, so the main loop can redraw them
+    pub character: mpsc::Sender<String>,
+    /// Clients to terminate (character, relaunch), only the main loop knows their PIDs
+    pub terminate: mpsc::Sender<(String, bool)>,
+    /// Privacy mode toggles from the tray
+    pub privacy: mpsc::Sender<()>,
+    /// Character in redaction edit mode (None = edit mode off)
+    pub redact_edit: mpsc::Sender<Option<String>>,
+    /// Characters to activate (command palette)
+    pub focus: mpsc::Sender<String>,
+    /// Settings arrived (SetProfile)
+    pub settings: mpsc::Sender<()>,
+}
+
 /// Spawn IPC listener thread to handle GUI requests
 pub fn spawn_ipc_listener(
     server: PreviewServer,
     state: Arc<Mutex<PersistentState>>,
-    shutdown_tx: mpsc::Sender<()>,
-    client_tx: mpsc::Sender<Arc<Mutex<ClientConnection>>>,
+    hotkeys: CharacterHotkeys,
+    channels: MainLoopChannels,
 ) -> std::thread::JoinHandle<()> {
     std::thread::spawn(move || {
-        if let Err(e) = run_ipc_loop(&server, &state, &shutdown_tx, &client_tx) {
+        if let Err(e) = run_ipc_loop(&server, &state, &hotkeys, &channels) {
             error!(error = ?e, "IPC listener thread crashed");
         }
     })
 }
 
+/// Rebuild the hotkey map from the current settings
+/// Returns the bound keys other X clients hold if that changed since `reported`
+fn rebind_hotkeys(
+    state: &PersistentState,
+    hotkeys: &CharacterHotkeys,
+    grab_checker: Option<&GrabChecker>,
+    reported: &mut Option<Vec<HotkeyConflict>>,
+) -> Option<Vec<HotkeyConflict>> {
+    let mut bindings = hotkeys::hotkey_bindings(&state.profile.character_positions, state.profile.effective_privacy_hotkey(&state.global));
+    hotkeys::bind_key(&mut bindings, state.global.focus_last_hotkey.as_deref(), CycleCommand::FocusLast);
+    hotkeys::bind_key(&mut bindings, state.global.focus_back_hotkey.as_deref(), CycleCommand::FocusBack);
+    hotkeys::bind_key(&mut bindings, state.global.focus_forward_hotkey.as_deref(), CycleCommand::FocusForward);
+    hotkeys::bind_leader(&mut bindings, state.global.chord_leader.as_deref());
+    // Portal shortcuts are assigned by the desktop, which resolves its own conflicts
+    let conflicts = match grab_checker {
+        Some(checker) if !portal_shortcuts::use_portal(state.global.hotkey_backend) => checker.conflicts(&bindings),
+        _ => Vec::new(),
+    };
+    *hotkeys.lock().unwrap() = bindings;
+    if reported.as_ref() == Some(&conflicts) {
+        return None;
+    }
+    for conflict in &conflicts {
+        warn!(key = %conflict.key, action = ?conflict.action, "Hotkey is grabbed by another application");
+    }
+    *reported = Some(conflicts.clone());
+    Some(conflicts)
+}
+
 fn run_ipc_loop(
     server: &PreviewServer,
     state: &Arc<Mutex<PersistentState>>,
-    shutdown_tx: &mpsc::Sender<()>,
-    client_tx: &mpsc::Sender<Arc<Mutex<ClientConnection>>>,
+    hotkeys: &CharacterHotkeys,
+    channels: &MainLoopChannels,
 ) -> Result<()> {
     info!(socket = ?server.path(), "IPC listener started");
+    let grab_checker = GrabChecker::connect()
+        .inspect_err(|e| warn!(error = ?e, "Hotkey conflict check unavailable"))
+        .ok();
 
     loop {
         // Accept connection (blocks until GUI connects)
@@ -59,12 +113,14 @@ fn run_ipc_loop(
         }));
         
         // Send client to main loop so it can send unsolicited events
-        if client_tx.send(client.clone()).is_err() {
+        if channels.client.send(client.clone()).is_err() {
             warn!("Failed to send client to main loop (shutting down?)");
             break Ok(());
         }
 
         info!("GUI connected to preview process");
+        // Conflicts already reported to this GUI (None = nothing sent yet)
+        let mut reported_conflicts = None;
 
         // Handle messages from this client
         loop {
@@ -74,13 +130,61 @@ fn run_ipc_loop(
             };
             
             match recv_result {
-                Ok(PreviewRequest::SetProfile { profile, global }) => {
+                Ok(PreviewRequest::SetProfile { mut profile, mut global }) => {
                     info!(profile = %profile.name, "Received profile configuration via IPC");
+                    overrides::apply(&mut profile, &mut global);
                     let mut state = state.lock().unwrap();
                     state.profile = profile;
                     state.global = global;
+                    let conflicts = rebind_hotkeys(&state, hotkeys, grab_checker.as_ref(), &mut reported_conflicts);
+                    // After the rebind, so a hotkey listener started for these settings sees their bindings
+                    channels.settings.send(()).ok();
                     // TODO: Trigger thumbnail re-render with new settings
-                    client.lock().unwrap().send_response(&PreviewResponse::Ready)?;
+                    let mut client = client.lock().unwrap();
+                    client.send_response(&PreviewResponse::Ready)?;
+                    if let Some(conflicts) = conflicts {
+                        client.send_response(&PreviewResponse::HotkeyConflicts(conflicts))?;
+                    }
+                }
+
+                Ok(PreviewRequest::UpdateCharacter { character, settings }) => {
+                    info!(character = %character, "Received character settings via IPC");
+                    let mut state = state.lock().unwrap();
+                    state.set_character_settings(&character, settings);
+                    if let Some(conflicts) = rebind_hotkeys(&state, hotkeys, grab_checker.as_ref(), &mut reported_conflicts) {
+                        client.lock().unwrap().send_response(&PreviewResponse::HotkeyConflicts(conflicts))?;
+                    }
+                    if channels.character.send(character).is_err() {
+                        warn!("Failed to forward character update to main loop (shutting down?)");
+                    }
+                }
+
+                Ok(PreviewRequest::TerminateClient { character, relaunch }) => {
+                    info!(character = %character, relaunch = relaunch, "Received terminate request via IPC");
+                    if channels.terminate.send((character, relaunch)).is_err() {
+                        warn!("Failed to forward terminate request to main loop (shutting down?)");
+                    }
+                }
+
+                Ok(PreviewRequest::TogglePrivacy) => {
+                    info!("Received privacy mode toggle via IPC");
+                    if channels.privacy.send(()).is_err() {
+                        warn!("Failed to forward privacy toggle to main loop (shutting down?)");
+                    }
+                }
+
+                Ok(PreviewRequest::FocusCharacter { character }) => {
+                    info!(character = %character, "Received focus request via IPC");
+                    if channels.focus.send(character).is_err() {
+                        warn!("Failed to forward focus request to main loop (shutting down?)");
+                    }
+                }
+
+                Ok(PreviewRequest::EditRedactions { character }) => {
+                    info!(character = ?character, "Received redaction edit mode via IPC");
+                    if channels.redact_edit.send(character).is_err() {
+                        warn!("Failed to forward redaction edit mode to main loop (shutting down?)");
+                    }
                 }
 
                 Ok(PreviewRequest::GetPositions) => {
@@ -96,7 +200,7 @@ fn run_ipc_loop(
 
                 Ok(PreviewRequest::Shutdown) => {
                     info!("Received shutdown request via IPC");
-                    shutdown_tx.send(()).ok();
+                    channels.shutdown.send(()).ok();
                     break;  // Break inner loop, outer loop continues (but shutdown will stop it)
                 }
 
@@ -0,0 +1,64 @@
+//! Thumbnail label templates (label_template)
+//!
+//! `{character}` is the alias if one is set, else the character name;
+//! `{name}` is always the character name; `{index}` is the character's number
+//! among the running characters in cycle order (the key a chord picks it
+//! with); `{status}` says "logged out" or "stalled" when that applies.
+//! Placeholders without a value expand to nothing, unknown ones are kept as
+//! typed, and the result is trimmed.
+
+/// Values a template can show for one thumbnail
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LabelFields<'a> {
+    pub character: &'a str,
+    pub name: &'a str,
+    /// 1-based, None when the character isn't cycled
+    pub index: Option<usize>,
+    pub status: &'a str,
+}
+
+pub const STATUS_LOGGED_OUT: &str = "logged out";
+pub const STATUS_STALLED: &str = "stalled";
+
+/// Label text for `fields`
+pub fn expand(template: &str, fields: &LabelFields) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let placeholder = &rest[start..=start + len];
+        match placeholder {
+            "{character}" => out.push_str(fields.character),
+            "{name}" => out.push_str(fields.name),
+            "{index}" => {
+                if let Some(index) = fields.index {
+                    out.push_str(&index.to_string());
+                }
+            }
+            "{status}" => out.push_str(fields.status),
+            _ => out.push_str(placeholder),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let fields = LabelFields { character: "FC", name: "Main Pilot", index: Some(2), status: "" };
+        assert_eq!(expand("{character} [{index}] {status}", &fields), "FC [2]");
+        assert_eq!(expand("{name} {unknown} {", &fields), "Main Pilot {unknown} {");
+
+        let logged_out = LabelFields { status: STATUS_LOGGED_OUT, ..LabelFields::default() };
+        assert_eq!(expand("{character} {status}", &logged_out), "logged out");
+    }
+}
@@ -0,0 +1,175 @@
+//! Client window lifecycle state machine
+//!
+//! An EVE client goes through created → logged out ("EVE") → character A →
+//! minimized → character B → destroyed, in almost any order. Each thumbnail
+//! keeps a `ClientLifecycle` and feeds it the X11 events that matter; the
+//! transition returns the login/logout actions the caller has to carry out
+//! (MQTT events today), so the rules live here instead of in every handler.
+
+use tracing::warn;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ClientLifecycle {
+    /// Thumbnail exists but the window title hasn't been read yet
+    #[default]
+    Created,
+    /// Client is at the character selection screen (title "EVE")
+    LoggedOut,
+    /// Client is logged in as a character
+    Active { character: String },
+    /// Client is minimized by the window manager (None = minimized while logged out)
+    Minimized { character: Option<String> },
+    /// Client window is gone; no further transitions
+    Destroyed,
+}
+
+/// X11-derived events that drive the lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleEvent<'a> {
+    /// Window identified as an EVE client with this character name ("" = logged out)
+    Detected(&'a str),
+    /// WM_NAME changed to this character name ("" = logged out)
+    TitleChanged(&'a str),
+    /// _NET_WM_STATE_HIDDEN set
+    Minimized,
+    /// Client got focus again after being minimized
+    Restored,
+    /// DestroyNotify
+    Destroyed,
+}
+
+/// Side effects of a transition, in the order they should be carried out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifecycleAction {
+    /// Character went offline on this client
+    Logout(String),
+    /// Character came online on this client
+    Login(String),
+}
+
+fn logged_in(character: &str) -> ClientLifecycle {
+    if character.is_empty() {
+        ClientLifecycle::LoggedOut
+    } else {
+        ClientLifecycle::Active { character: character.to_string() }
+    }
+}
+
+impl ClientLifecycle {
+    /// Character the client is logged in as, if any
+    pub fn character(&self) -> Option<&str> {
+        match self {
+            Self::Active { character } => Some(character),
+            Self::Minimized { character } => character.as_deref(),
+            Self::Created | Self::LoggedOut | Self::Destroyed => None,
+        }
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        matches!(self, Self::Minimized { .. })
+    }
+
+    /// Apply an event and return the actions it implies
+    pub fn transition(&mut self, event: LifecycleEvent) -> Vec<LifecycleAction> {
+        let previous = self.character().map(str::to_string);
+        let next = match (&*self, event) {
+            (Self::Destroyed, _) => {
+                warn!(event = ?event, "Ignoring event for destroyed client");
+                return Vec::new();
+            }
+            (_, LifecycleEvent::Destroyed) => Self::Destroyed,
+            (Self::Created, LifecycleEvent::Detected(name) | LifecycleEvent::TitleChanged(name)) => logged_in(name),
+            (Self::Created, LifecycleEvent::Minimized) => Self::Minimized { character: None },
+            (Self::Created, LifecycleEvent::Restored) => Self::Created,
+            (Self::Minimized { .. }, LifecycleEvent::Detected(name) | LifecycleEvent::TitleChanged(name)) => {
+                Self::Minimized { character: (!name.is_empty()).then(|| name.to_string()) }
+            }
+            (Self::Minimized { character }, LifecycleEvent::Restored) => logged_in(character.as_deref().unwrap_or("")),
+            (Self::Minimized { .. }, LifecycleEvent::Minimized) => self.clone(),
+            (Self::LoggedOut | Self::Active { .. }, LifecycleEvent::Detected(name) | LifecycleEvent::TitleChanged(name)) => logged_in(name),
+            (Self::LoggedOut | Self::Active { .. }, LifecycleEvent::Minimized) => {
+                Self::Minimized { character: previous.clone() }
+            }
+            (Self::LoggedOut | Self::Active { .. }, LifecycleEvent::Restored) => self.clone(),
+        };
+
+        let mut actions = Vec::new();
+        let current = next.character().map(str::to_string);
+        if previous != current {
+            actions.extend(previous.map(LifecycleAction::Logout));
+            actions.extend(current.map(LifecycleAction::Login));
+        }
+        *self = next;
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn login(name: &str) -> LifecycleAction {
+        LifecycleAction::Login(name.to_string())
+    }
+
+    fn logout(name: &str) -> LifecycleAction {
+        LifecycleAction::Logout(name.to_string())
+    }
+
+    #[test]
+    fn test_detected_logged_out_then_login() {
+        let mut lifecycle = ClientLifecycle::default();
+        assert!(lifecycle.transition(LifecycleEvent::Detected("")).is_empty());
+        assert_eq!(lifecycle, ClientLifecycle::LoggedOut);
+
+        assert_eq!(lifecycle.transition(LifecycleEvent::TitleChanged("Alice")), vec![login("Alice")]);
+        assert_eq!(lifecycle.character(), Some("Alice"));
+    }
+
+    #[test]
+    fn test_character_switch_logs_out_then_in() {
+        let mut lifecycle = ClientLifecycle::Active { character: "Alice".to_string() };
+        assert_eq!(
+            lifecycle.transition(LifecycleEvent::TitleChanged("Bob")),
+            vec![logout("Alice"), login("Bob")],
+        );
+        assert!(lifecycle.transition(LifecycleEvent::TitleChanged("Bob")).is_empty());
+    }
+
+    #[test]
+    fn test_minimize_keeps_character_online() {
+        let mut lifecycle = ClientLifecycle::Active { character: "Alice".to_string() };
+        assert!(lifecycle.transition(LifecycleEvent::Minimized).is_empty());
+        assert!(lifecycle.is_minimized());
+        assert_eq!(lifecycle.character(), Some("Alice"));
+
+        assert!(lifecycle.transition(LifecycleEvent::Restored).is_empty());
+        assert_eq!(lifecycle, ClientLifecycle::Active { character: "Alice".to_string() });
+    }
+
+    #[test]
+    fn test_relog_while_minimized() {
+        let mut lifecycle = ClientLifecycle::Active { character: "Alice".to_string() };
+        lifecycle.transition(LifecycleEvent::Minimized);
+        assert_eq!(lifecycle.transition(LifecycleEvent::TitleChanged("")), vec![logout("Alice")]);
+        assert_eq!(lifecycle.transition(LifecycleEvent::TitleChanged("Bob")), vec![login("Bob")]);
+        assert!(lifecycle.is_minimized());
+
+        lifecycle.transition(LifecycleEvent::Restored);
+        assert_eq!(lifecycle, ClientLifecycle::Active { character: "Bob".to_string() });
+    }
+
+    #[test]
+    fn test_destroy_logs_out_and_is_final() {
+        let mut lifecycle = ClientLifecycle::Minimized { character: Some("Alice".to_string()) };
+        assert_eq!(lifecycle.transition(LifecycleEvent::Destroyed), vec![logout("Alice")]);
+        assert!(lifecycle.transition(LifecycleEvent::TitleChanged("Bob")).is_empty());
+        assert_eq!(lifecycle, ClientLifecycle::Destroyed);
+    }
+
+    #[test]
+    fn test_destroy_logged_out_has_no_actions() {
+        let mut lifecycle = ClientLifecycle::LoggedOut;
+        assert!(lifecycle.transition(LifecycleEvent::Destroyed).is_empty());
+    }
+}
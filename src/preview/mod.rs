@@ -1,49 +1,124 @@
 //! Preview daemon - runs in background showing EVE window thumbnails
 
+mod borderless;
+mod chord;
+mod client_control;
+mod color_adjust;
 mod cycle_state;
+mod cycle_strip;
+mod damage_region;
+mod display_sleep;
+mod drag_guides;
+mod dry_run;
+mod error_watch;
 mod event_handler;
+mod focus_history;
 pub mod font;
 mod font_discovery;
+pub mod foreign_toplevel;
+mod frame_pacer;
+mod fullscreen_redirect;
+#[cfg(feature = "gl")]
+pub mod gl_backend;
+mod hotkey_listener;
 mod ipc_handler;
+mod label_template;
+mod lifecycle;
+mod monitor_setup;
+mod motion_throttle;
+mod mouse_hotkeys;
+mod night_mode;
+mod opacity_blend;
+mod osd;
+mod panel;
+mod pixelate;
+mod process_stats;
+mod redaction;
 mod session_state;
+mod sleep_watch;
 mod snapping;
+mod source_refresh;
+mod stacking_rules;
+mod stall_watch;
 mod thumbnail;
+mod tooltip;
+mod watchdog;
+mod window_identity;
+mod window_rules;
+pub mod wm_compat;
 
+pub use dry_run::run_dry_run;
 pub use font_discovery::{find_font_path, list_fonts, select_best_default_font};
 
 use anyhow::{Context, Result};
+use nix::errno::Errno;
+use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
 use std::collections::HashMap;
+use std::os::fd::AsFd;
 use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 use x11rb::connection::Connection;
 use x11rb::protocol::damage::ConnectionExt as DamageExt;
 use x11rb::protocol::xproto::*;
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
 
 use crate::config::PersistentState;
+use crate::config::profile::{ClientSwitchAction, RenderBackend, RuleTrigger};
 use crate::constants::{self, eve, paths, wine};
-use crate::hotkeys::{self, spawn_listener, CycleCommand};
+use crate::hotkeys::{CharacterHotkeys, CycleCommand};
 use crate::ipc::PreviewServer;
-use crate::types::Dimensions;
-use crate::x11_utils::{activate_window, is_window_eve, is_window_minimized, minimize_window, AppContext, CachedAtoms};
+use crate::mqtt::MqttPublisher;
+use crate::session_summary;
+use crate::usage_stats;
+use crate::x11_trace;
+use crate::types::{Dimensions, Position, ThumbnailState};
+use crate::x11_utils::{activate_window, apply_switch_action, client_windows_from_tree, focus_window_directly, is_window_eve, is_remote_display, is_window_minimized, low_bandwidth_active, root_geometry, screen_of_window, window_manager_name, window_manager_running, AppContext, CachedAtoms};
 
 use cycle_state::CycleState;
+use display_sleep::DisplaySleep;
+use watchdog::{Problem, Watchdog};
+use window_rules::WindowRules;
+use error_watch::ErrorWatch;
 use event_handler::handle_event;
-use ipc_handler::spawn_ipc_listener;
+use lifecycle::LifecycleEvent;
+use monitor_setup::MonitorSetup;
+use ipc_handler::{spawn_ipc_listener, MainLoopChannels};
+use chord::Chord;
+use cycle_strip::CycleStrip;
+use frame_pacer::FramePacer;
+use hotkey_listener::HotkeyListener;
+use motion_throttle::MotionThrottle;
+use mouse_hotkeys::MouseHotkeys;
+use night_mode::NightWatch;
+use osd::Osd;
+use process_stats::{ProcessSampler, ProcessStats};
 use session_state::SessionState;
+use source_refresh::SourceRefresh;
+use stall_watch::StallWatch;
+use foreign_toplevel::ForeignToplevels;
 use thumbnail::Thumbnail;
 
-fn check_and_create_window<'a>(
-    ctx: &AppContext<'a>,
-    persistent_state: &PersistentState,
-    window: Window,
-    state: &SessionState,
-) -> Result<Option<Thumbnail<'a>>> {
-    let pid_atom = ctx.conn.intern_atom(false, b"_NET_WM_PID")
+/// Which process a client window belongs to, as far as _NET_WM_PID tells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WindowOwner {
+    /// One of our own thumbnail windows
+    Ours,
+    /// A process that isn't running through wine (can't be EVE)
+    Native,
+    /// A wine process (or one we can't tell), with its PID if known
+    Wine(Option<u32>),
+}
+
+fn window_owner(conn: &RustConnection, window: Window) -> Result<WindowOwner> {
+    let pid_atom = conn.intern_atom(false, b"_NET_WM_PID")
         .context("Failed to intern _NET_WM_PID atom")?
         .reply()
         .context("Failed to get reply for _NET_WM_PID atom")?
         .atom;
-    if let Ok(prop) = ctx.conn
+    let mut client_pid = None;
+    if let Ok(prop) = conn
         .get_property(false, window, pid_atom, AtomEnum::CARDINAL, 0, 1)
         .context(format!("Failed to query _NET_WM_PID property for window {}", window))?
         .reply()
@@ -54,8 +129,9 @@ fn check_and_create_window<'a>(
             
             // Skip our own thumbnail windows
             if pid == std::process::id() {
-                return Ok(None);
+                return Ok(WindowOwner::Ours);
             }
+            x11_trace::reply(window, "GetProperty(_NET_WM_PID)", format_args!("pid={}", pid));
             
             if !std::fs::read_link(format!("{}", paths::PROC_EXE_FORMAT.replace("{}", &pid.to_string())))
                 .map(|x| {
@@ -71,8 +147,9 @@ fn check_and_create_window<'a>(
                 })
                 .unwrap_or(true)
             {
-                return Ok(None); // Return if we can determine that the window is not running through wine.
+                return Ok(WindowOwner::Native); // We can determine that the window is not running through wine.
             }
+            client_pid = Some(pid);
         } else {
             warn!(
                 window = window,
@@ -80,6 +157,31 @@ fn check_and_create_window<'a>(
             );
         }
     }
+    Ok(WindowOwner::Wine(client_pid))
+}
+
+/// Thumbnail size for a character: its saved dimensions, or the defaults while unset
+fn thumbnail_dimensions(persistent_state: &PersistentState, character_name: &str, screen: &Screen) -> Dimensions {
+    match persistent_state.character_positions.get(character_name) {
+        Some(settings) if settings.dimensions.width != 0 && settings.dimensions.height != 0 => settings.dimensions,
+        // Character not in settings yet, or dimensions not saved yet - auto-detect
+        _ => {
+            let (w, h) = persistent_state.default_thumbnail_size(screen.width_in_pixels, screen.height_in_pixels);
+            Dimensions::new(w, h)
+        }
+    }
+}
+
+fn check_and_create_window<'a>(
+    ctx: &AppContext<'a>,
+    persistent_state: &PersistentState,
+    window: Window,
+    state: &SessionState,
+) -> Result<Option<Thumbnail<'a>>> {
+    let client_pid = match window_owner(ctx.conn, window)? {
+        WindowOwner::Ours | WindowOwner::Native => return Ok(None),
+        WindowOwner::Wine(pid) => pid,
+    };
 
     ctx.conn.change_window_attributes(
         window,
@@ -87,14 +189,20 @@ fn check_and_create_window<'a>(
     )
     .context(format!("Failed to set event mask for window {}", window))?;
 
-    if let Some(eve_window) = is_window_eve(ctx.conn, window, ctx.atoms)
+    if let Some(eve_window) = is_window_eve(ctx.conn, window, ctx.atoms, &state.title_pattern)
         .context(format!("Failed to check if window {} is EVE client", window))? {
+        x11_trace::reply(window, "GetProperty(WM_NAME)", format_args!("{:?}", eve_window));
         let character_name = eve_window.character_name().to_string();
         
+        // Thumbnails go on the X screen the client lives on (matters for non-Xinerama multi-screen)
+        let screen_ctx = ctx.on_screen(screen_of_window(ctx.conn, window)
+            .context(format!("Failed to find screen of EVE window {} ('{}')", window, character_name))?);
+        let ctx = &screen_ctx;
+        
         ctx.conn.change_window_attributes(
             window,
             &ChangeWindowAttributesAux::new()
-                .event_mask(EventMask::PROPERTY_CHANGE | EventMask::FOCUS_CHANGE),
+                .event_mask(EventMask::PROPERTY_CHANGE | EventMask::FOCUS_CHANGE | EventMask::STRUCTURE_NOTIFY),
         )
         .context(format!("Failed to set focus event mask for EVE window {} ('{}')", window, character_name))?;
         
@@ -107,28 +215,45 @@ fn check_and_create_window<'a>(
         );
         
         // Get dimensions from CharacterSettings or use auto-detected defaults
-        let dimensions = if let Some(settings) = persistent_state.character_positions.get(&character_name) {
-            // If dimensions are 0 (not yet saved), auto-detect
-            if settings.dimensions.width == 0 || settings.dimensions.height == 0 {
-                let (w, h) = persistent_state.default_thumbnail_size(
-                    ctx.screen.width_in_pixels,
-                    ctx.screen.height_in_pixels,
-                );
-                Dimensions::new(w, h)
-            } else {
-                settings.dimensions
+        let dimensions = thumbnail_dimensions(persistent_state, &character_name, ctx.screen);
+        
+        let low_bandwidth = low_bandwidth_active(persistent_state.global.low_bandwidth);
+        let mut thumbnail = Thumbnail::new(ctx, character_name.clone(), window, ctx.font_renderer, position, dimensions, low_bandwidth)
+            .context(format!("Failed to create thumbnail for '{}' (window {})", character_name, window))?;
+        thumbnail.pid = client_pid;
+        if persistent_state.global.render_backend == RenderBackend::Gl {
+            use_gl_backend(ctx, &mut thumbnail);
+        }
+        
+        if let Some(settings) = persistent_state.configured_character(&character_name) {
+            thumbnail.apply_character_settings(Some(settings))
+                .context(format!("Failed to apply character settings for '{}'", character_name))?;
+            if settings.preview_hidden {
+                debug!(window = window, character = %character_name, "Preview hidden by config");
             }
-        } else {
-            // Character not in settings yet - auto-detect
-            let (w, h) = persistent_state.default_thumbnail_size(
+        }
+        
+        // Auto-placed thumbnails (no saved position) are kept out of the screen margins
+        if position.is_none() {
+            let geom = root_geometry(ctx.conn, thumbnail.window)
+                .context(format!("Failed to get geometry of new thumbnail for '{}'", character_name))?;
+            let area = snapping::safe_area(
                 ctx.screen.width_in_pixels,
                 ctx.screen.height_in_pixels,
+                &persistent_state.global.screen_margins,
             );
-            Dimensions::new(w, h)
-        };
-        
-        let mut thumbnail = Thumbnail::new(ctx, character_name.clone(), window, ctx.font_renderer, position, dimensions)
-            .context(format!("Failed to create thumbnail for '{}' (window {})", character_name, window))?;
+            let placed = snapping::clamp_into(
+                snapping::Rect { x: geom.x, y: geom.y, width: dimensions.width, height: dimensions.height },
+                area,
+            );
+            if placed != Position::new(geom.x, geom.y) {
+                debug!(character = %character_name, x = placed.x, y = placed.y, "Moving auto-placed thumbnail inside screen margins");
+                thumbnail.reposition(placed.x, placed.y)
+                    .context(format!("Failed to move new thumbnail for '{}' inside screen margins", character_name))?;
+            }
+        }
+        fullscreen_redirect::check(ctx, &persistent_state.global, &mut thumbnail)
+            .context(format!("Failed to check fullscreen redirection for '{}'", character_name))?;
         if is_window_minimized(ctx.conn, window, ctx.atoms)
             .context(format!("Failed to query minimized state for window {}", window))?
         {
@@ -136,6 +261,8 @@ fn check_and_create_window<'a>(
             thumbnail
                 .minimized()
                 .context(format!("Failed to set minimized state for '{}'", character_name))?;
+            // Not detected yet, so this can't log anyone in or out
+            thumbnail.lifecycle.transition(LifecycleEvent::Minimized);
         }
         info!(
             window = window,
@@ -148,55 +275,161 @@ fn check_and_create_window<'a>(
     }
 }
 
-fn get_eves<'a>(
-    ctx: &AppContext<'a>,
-    persistent_state: &mut PersistentState,
-    state: &SessionState,
-) -> Result<HashMap<Window, Thumbnail<'a>>> {
-    let net_client_list = ctx.conn.intern_atom(false, b"_NET_CLIENT_LIST")
+/// Move a new thumbnail to the OpenGL backend; it stays on XRender if GL can't be used
+#[cfg(feature = "gl")]
+fn use_gl_backend<'a>(ctx: &AppContext<'a>, thumbnail: &mut Thumbnail<'a>) {
+    let cell: &'a std::cell::OnceCell<Option<gl_backend::GlBackend>> = ctx.gl;
+    let backend = cell.get_or_init(|| {
+        gl_backend::GlBackend::new(ctx)
+            .inspect_err(|e| warn!(error = %e, "OpenGL backend unavailable, thumbnails use XRender"))
+            .ok()
+    });
+    // The backend renders on the screen it was set up on; other screens keep XRender
+    let Some(backend) = backend.as_ref().filter(|b| b.serves(thumbnail.screen.root)) else {
+        return;
+    };
+    if let Err(e) = thumbnail.use_gl(backend) {
+        warn!(character = %thumbnail.character_name, error = ?e, "Falling back to XRender");
+    }
+}
+
+#[cfg(not(feature = "gl"))]
+fn use_gl_backend(_ctx: &AppContext, _thumbnail: &mut Thumbnail) {
+    static WARNED: std::sync::Once = std::sync::Once::new();
+    WARNED.call_once(|| warn!("render_backend is \"gl\" but this build has no OpenGL support (--features gl), using XRender"));
+}
+
+/// Unfinished OpenGL draws are finished as soon as no event is queued
+#[cfg(feature = "gl")]
+fn gl_frame_deadline(ctx: &AppContext) -> Option<Instant> {
+    ctx.gl.get()?.as_ref()?.frame_started()
+}
+
+#[cfg(not(feature = "gl"))]
+fn gl_frame_deadline(_ctx: &AppContext) -> Option<Instant> {
+    None
+}
+
+/// Wait once for the OpenGL draws issued since the last frame, then put their overlays on
+/// Done when the event queue ran dry, or a frame after the first draw if events keep coming
+#[cfg(feature = "gl")]
+fn finish_gl_frame(ctx: &AppContext, eves: &HashMap<Window, Thumbnail>, queue_empty: bool) {
+    let Some(backend) = ctx.gl.get().and_then(Option::as_ref) else {
+        return;
+    };
+    if !queue_empty
+        && backend.frame_started().is_some_and(|started| started.elapsed() < Duration::from_millis(constants::gl::FRAME_MS))
+    {
+        return;
+    }
+    let drawn = backend.finish_frame()
+        .inspect_err(|e| debug!(error = %e, "OpenGL frame failed, redrawing it with XRender"))
+        .is_ok();
+    for thumbnail in eves.values() {
+        let _ = thumbnail.finish_gl_frame(drawn)
+            .inspect_err(|e| error!(character = %thumbnail.character_name, error = ?e, "Failed to finish OpenGL frame"));
+    }
+    let _ = ctx.conn.flush()
+        .inspect_err(|e| error!(error = ?e, "Failed to flush X11 connection after OpenGL frame"));
+}
+
+#[cfg(not(feature = "gl"))]
+fn finish_gl_frame(_ctx: &AppContext, _eves: &HashMap<Window, Thumbnail>, _queue_empty: bool) {}
+
+/// Client windows of every X screen: _NET_CLIENT_LIST, or a window tree scan without an EWMH WM
+fn client_windows(conn: &RustConnection, atoms: &CachedAtoms) -> Result<Vec<Window>> {
+    let net_client_list = conn.intern_atom(false, b"_NET_CLIENT_LIST")
         .context("Failed to intern _NET_CLIENT_LIST atom")?
         .reply()
         .context("Failed to get reply for _NET_CLIENT_LIST atom")?
         .atom;
-    let prop = ctx.conn
-        .get_property(
-            false,
-            ctx.screen.root,
-            net_client_list,
-            AtomEnum::WINDOW,
-            0,
-            u32::MAX,
-        )
-        .context("Failed to query _NET_CLIENT_LIST property")?
-        .reply()
-        .context("Failed to get window list from X11 server")?;
-    let windows: Vec<u32> = prop
-        .value32()
-        .ok_or_else(|| anyhow::anyhow!("Invalid return from _NET_CLIENT_LIST"))?
-        .collect();
+    // Every X screen has its own root and client list (multi-screen / Zaphod setups)
+    let mut windows: Vec<u32> = Vec::new();
+    for screen in &conn.setup().roots {
+        let prop = conn
+            .get_property(
+                false,
+                screen.root,
+                net_client_list,
+                AtomEnum::WINDOW,
+                0,
+                u32::MAX,
+            )
+            .context("Failed to query _NET_CLIENT_LIST property")?
+            .reply()
+            .context("Failed to get window list from X11 server")?;
+        match prop.value32() {
+            Some(list) => windows.extend(list),
+            // No EWMH window manager on this screen (minimal WMs, no WM at all): walk the window tree
+            None => {
+                debug!(root = screen.root, "No _NET_CLIENT_LIST on screen, scanning the window tree");
+                windows.extend(client_windows_from_tree(conn, screen.root, atoms)
+                    .context(format!("Failed to scan window tree of root {}", screen.root))?);
+            }
+        }
+    }
+    Ok(windows)
+}
+
+/// A title change on every client window, so each is classified again after title_pattern
+/// changed: clients it now matches get their thumbnail, renamed characters follow
+fn rescan_titles(ctx: &AppContext) -> Result<Vec<Event>> {
+    Ok(client_windows(ctx.conn, ctx.atoms)?.into_iter()
+        .map(|window| Event::PropertyNotify(PropertyNotifyEvent {
+            response_type: PROPERTY_NOTIFY_EVENT,
+            sequence: 0,
+            window,
+            atom: ctx.atoms.wm_name,
+            time: x11rb::CURRENT_TIME,
+            state: Property::NEW_VALUE,
+        }))
+        .collect())
+}
 
+/// Client window an event is about, for --trace-x11 (events on a thumbnail count for its client)
+fn traced_client(eves: &HashMap<Window, Thumbnail>, event: &Event) -> Option<Window> {
+    let window = x11_trace::event_window(event)?;
+    if eves.contains_key(&window) {
+        return Some(window);
+    }
+    Some(eves.values().find(|t| t.owns_resource(window)).map_or(window, |t| t.src))
+}
+
+fn get_eves<'a>(
+    ctx: &AppContext<'a>,
+    persistent_state: &mut PersistentState,
+    state: &SessionState,
+) -> Result<HashMap<Window, Thumbnail<'a>>> {
     let mut eves = HashMap::new();
-    for w in windows {
-        if let Some(eve) = check_and_create_window(ctx, persistent_state, w, state)
+    for w in client_windows(ctx.conn, ctx.atoms)? {
+        if let Some(mut eve) = check_and_create_window(ctx, persistent_state, w, state)
             .context(format!("Failed to process window {} during initial scan", w))? {
             
             // Save initial position and dimensions (important for first-time characters)
             // Query geometry to get actual position from X11
-            let geom = ctx.conn.get_geometry(eve.window)
-                .context("Failed to query geometry during initial scan")?
-                .reply()
+            let geom = root_geometry(ctx.conn, eve.window)
                 .context("Failed to get geometry reply during initial scan")?;
             
             persistent_state.update_position(
                 &eve.character_name,
                 geom.x,
                 geom.y,
-                eve.dimensions.width,
-                eve.dimensions.height,
+                eve.identity.dimensions().width,
+                eve.identity.dimensions().height,
             )
             .context(format!("Failed to save initial position during scan for '{}'", eve.character_name))?;
             
+            let character_name = eve.character_name.clone();
+            event_handler::advance_lifecycle(ctx, persistent_state, &mut eve, LifecycleEvent::Detected(&character_name));
+            
+            let _ = window_rules::apply_matching(ctx, &state.window_rules, RuleTrigger::Map, w, &eve.character_name)
+                .inspect_err(|e| warn!(character = %eve.character_name, error = ?e, "Failed to apply window rules during scan, keeping the thumbnail"));
+            
             eves.insert(w, eve);
+            // _NET_CLIENT_LIST is in mapping order (the tree fallback in stacking order, close enough),
+            // so the newest client keeps a duplicated name
+            event_handler::demote_duplicates(&mut eves, w, &None)
+                .context(format!("Failed to resolve duplicate character during scan for window {}", w))?;
         }
     }
     ctx.conn.flush()
@@ -211,6 +444,8 @@ pub fn run_preview_daemon() -> Result<()> {
     let screen = &conn.setup().roots[screen_num];
     info!(
         screen = screen_num,
+        screens = conn.setup().roots.len(),
+        remote = std::env::var("DISPLAY").is_ok_and(|display| is_remote_display(&display)),
         width = screen.width_in_pixels,
         height = screen.height_in_pixels,
         "Connected to X11 server"
@@ -239,30 +474,44 @@ pub fn run_preview_daemon() -> Result<()> {
     let (shutdown_tx, shutdown_rx) = mpsc::channel();
     let (hotkey_tx, hotkey_rx) = mpsc::channel();
     let (client_tx, client_rx) = mpsc::channel();
+    let (character_tx, character_rx) = mpsc::channel::<String>();
+    let (terminate_tx, terminate_rx) = mpsc::channel::<(String, bool)>();
+    let (privacy_tx, privacy_rx) = mpsc::channel::<()>();
+    let (redact_edit_tx, redact_edit_rx) = mpsc::channel::<Option<String>>();
+    let (focus_tx, focus_rx) = mpsc::channel::<String>();
+    let (settings_tx, settings_rx) = mpsc::channel::<()>();
+    
+    // Per-character hotkeys, rebuilt by the IPC thread whenever settings arrive
+    let character_hotkeys = CharacterHotkeys::default();
     
     // Spawn IPC listener thread
-    let _ipc_thread = spawn_ipc_listener(ipc_server, persistent_state_clone, shutdown_tx.clone(), client_tx);
+    let _ipc_thread = spawn_ipc_listener(
+        ipc_server,
+        persistent_state_clone,
+        character_hotkeys.clone(),
+        MainLoopChannels {
+            shutdown: shutdown_tx.clone(),
+            client: client_tx,
+            character: character_tx,
+            terminate: terminate_tx,
+            privacy: privacy_tx,
+            redact_edit: redact_edit_tx,
+            focus: focus_tx,
+            settings: settings_tx,
+        },
+    );
     
     // Initialize cycle state from config
     let mut cycle_state = CycleState::new(persistent_state.lock().unwrap().profile.cycle_group.clone());
     
-    // Spawn hotkey listener (optional - skip if permissions denied)
-    let _hotkey_handle = if hotkeys::check_permissions() {
-        match spawn_listener(hotkey_tx) {
-            Ok(handle) => {
-                info!(enabled = true, "Hotkey support enabled (Tab/Shift+Tab for character cycling)");
-                Some(handle)
-            }
-            Err(e) => {
-                error!(error = %e, "Failed to start hotkey listener");
-                hotkeys::print_permission_error();
-                None
-            }
-        }
-    } else {
-        hotkeys::print_permission_error();
-        None
-    };
+    // Damage tracking and source pictures can go stale over suspend, so they are rebuilt on resume
+    let (resume_tx, resume_rx) = mpsc::channel::<()>();
+    let _sleep_watch = sleep_watch::spawn(resume_tx);
+    
+    // The hotkey listener starts once the GUI sent settings, they pick its backend (hotkey_backend)
+    // Hotkeys that arrive as X events (mouse-button grabs, chord follow-ups) join the same channel
+    let x_hotkey_tx = hotkey_tx.clone();
+    let mut hotkey_listener = HotkeyListener::new(hotkey_tx, character_hotkeys);
     
     // Pre-cache atoms once at startup (eliminates roundtrip overhead)
     let atoms = CachedAtoms::new(&conn)
@@ -309,18 +558,45 @@ pub fn run_preview_daemon() -> Result<()> {
     // Build display config (will contain empty values until SetProfile arrives)
     let config = persistent_state.lock().unwrap().build_display_config();
     
+    // MQTT publisher connects lazily once enabled in global settings
+    let mqtt = MqttPublisher::spawn();
+    
     conn.damage_query_version(1, 1)
         .context("Failed to query DAMAGE extension version. Is DAMAGE extension available?")?;
-    conn.change_window_attributes(
-        screen.root,
-        &ChangeWindowAttributesAux::new().event_mask(
-            EventMask::SUBSTRUCTURE_NOTIFY
-                | EventMask::BUTTON_PRESS
-                | EventMask::BUTTON_RELEASE
-                | EventMask::POINTER_MOTION,
-        ),
-    )
-    .context("Failed to set event mask on root window")?;
+    for root_screen in &conn.setup().roots {
+        conn.change_window_attributes(
+            root_screen.root,
+            &ChangeWindowAttributesAux::new().event_mask(
+                EventMask::SUBSTRUCTURE_NOTIFY
+                    | EventMask::PROPERTY_CHANGE
+                    | EventMask::BUTTON_PRESS
+                    | EventMask::BUTTON_RELEASE
+                    | EventMask::POINTER_MOTION,
+            ),
+        )
+        .context(format!("Failed to set event mask on root window {}", root_screen.root))?;
+    }
+
+    // Thumbnail window type and client activation follow the WM (wm_preset)
+    let wm_name = window_manager_name(&conn, screen, &atoms)
+        .inspect_err(|e| debug!(error = ?e, "Failed to read the window manager's name"))
+        .ok()
+        .flatten();
+    let detected_wm = wm_compat::detect(wm_name.as_deref());
+    let wm_preset = wm_compat::resolve(persistent_state.lock().unwrap().global.wm_preset, detected_wm);
+    info!(wm = ?wm_name, detected = ?detected_wm, preset = ?wm_preset, "Window manager compatibility");
+    let wm = std::cell::Cell::new(wm_compat::compat(wm_preset));
+    let stack_below = std::cell::Cell::new(None);
+
+    // Set up on first use so displays without GL never load libGL
+    #[cfg(feature = "gl")]
+    let gl_backend = std::cell::OnceCell::new();
+
+    // wlroots Wayland sessions: activate/minimize clients through the compositor
+    let toplevels = ForeignToplevels::connect()
+        .inspect_err(|e| warn!(error = ?e, "Foreign-toplevel focus unavailable, focusing clients through EWMH"))
+        .ok()
+        .flatten();
 
     let ctx = AppContext {
         conn: &conn,
@@ -328,8 +604,18 @@ pub fn run_preview_daemon() -> Result<()> {
         config: &config,
         atoms: &atoms,
         font_renderer: &font_renderer,
+        mqtt: &mqtt,
+        toplevels: toplevels.as_ref(),
+        wm: &wm,
+        stack_below: &stack_below,
+        #[cfg(feature = "gl")]
+        gl: &gl_backend,
     };
 
+    if !window_manager_running(&conn, screen) {
+        info!("No window manager running, focusing and switching clients directly");
+    }
+
     let mut eves = {
         let mut state = persistent_state.lock().unwrap();
         get_eves(&ctx, &mut *state, &session_state)
@@ -341,44 +627,279 @@ pub fn run_preview_daemon() -> Result<()> {
         cycle_state.add_window(thumbnail.character_name.clone(), *window);
     }
     
+    event_handler::restack_thumbnails(&ctx, &persistent_state.lock().unwrap(), &eves)
+        .context("Failed to apply initial thumbnail stacking order")?;
+    
     // Track IPC client connection (None until GUI connects)
     let mut ipc_client: Option<Arc<Mutex<ipc_handler::ClientConnection>>> = None;
     
+    // Position set of the current monitor setup, switched on RandR changes
+    monitor_setup::watch(&conn);
+    let mut monitor_setup = MonitorSetup::new();
+    monitor_setup.check(&ctx, &mut persistent_state.lock().unwrap(), &mut eves, &ipc_client)
+        .context("Failed to apply the monitor setup's thumbnail positions")?;
+    
+    // Privacy mode pixelates every preview, toggled from the tray or its hotkey
+    let mut privacy = false;
+    
+    // Character whose preview takes redaction drawing clicks (GUI edit mode)
+    let mut redact_edit: Option<String> = None;
+    
+    // Drag motion is coalesced to at most drag_max_fps repositions per second
+    let mut motion_throttle: MotionThrottle<MotionNotifyEvent> = MotionThrottle::new();
+    
+    // Damage-driven redraws are paced per thumbnail (low bandwidth mode)
+    let mut frame_pacer = FramePacer::new();
+    
+    // Side mouse buttons cycling characters, grabbed on the clients (or root)
+    let mut mouse_hotkeys = MouseHotkeys::new();
+    
+    // Leader pressed, waiting for the key that picks a character
+    let mut chord = Chord::new();
+    
+    // Alt-tab style strip while the cycle_strip modifier is held with Tab
+    let mut cycle_strip = CycleStrip::new();
+    
+    // Name of the character a hotkey just switched to, in the middle of the screen
+    let mut osd = {
+        let state = persistent_state.lock().unwrap();
+        Osd::new(&conn, &state.profile.text_font_family, state.profile.text_size)?
+    };
+    
+    // Single window holding every preview (panel mode), created when the setting is on
+    let mut panel: Option<panel::Panel> = None;
+    
+    // Client CPU/RAM readout, sampled while show_process_stats is on
+    let mut process_sampler = ProcessSampler::new();
+    let mut night_watch = NightWatch::new();
+    
+    // Clients that stopped sending frames get a greyed-out thumbnail
+    let mut stall_watch = StallWatch::new();
+    
+    // Client windows being moved/resized, refreshed once they settle
+    let mut source_refresh = SourceRefresh::new();
+    let mut error_watch = ErrorWatch::new();
+    
+    // Displays coming back from DPMS sleep get every thumbnail rebound
+    let mut display_sleep = DisplaySleep::new(&conn);
+    
+    // Thumbnails are checked against the server now and then, in case an event was missed
+    let mut watchdog = Watchdog::new(Instant::now());
+    
+    // MQTT settings the running characters were announced with; when MQTT is turned on
+    // or pointed elsewhere they are announced again (their logins went nowhere)
+    let mut mqtt_announced = persistent_state.lock().unwrap().global.mqtt.clone();
+    
+    // Client windows to classify again, queued when title_pattern changes
+    let mut rescan: Vec<Event> = Vec::new();
+    
     info!("Preview process running");
     
     loop {
         // Check for shutdown signal from IPC
         if shutdown_rx.try_recv().is_ok() {
             info!("Shutdown signal received, exiting");
+            let summary = session_state.activity.summary(Instant::now());
+            if let Err(e) = session_summary::save(&summary) {
+                warn!(error = ?e, "Failed to save session summary");
+            }
+            if persistent_state.lock().unwrap().global.usage_stats {
+                let _ = usage_stats::record_session(&summary)
+                    .inspect_err(|e| warn!(error = ?e, "Failed to record usage stats"));
+            }
+            // Give borderless clients their decorations and geometry back
+            for thumbnail in eves.values_mut() {
+                if let Some(saved) = thumbnail.borderless_restore.take() {
+                    let _ = borderless::leave(&ctx, thumbnail.src, &saved)
+                        .inspect_err(|e| warn!(character = %thumbnail.character_name, error = ?e, "Failed to restore client from borderless fullscreen"));
+                }
+            }
             break Ok(());
         }
         
+        session_state.activity.clients(eves.len(), Instant::now());
+        
         // Check for new IPC client connection
         if let Ok(client) = client_rx.try_recv() {
             info!("Main loop received IPC client connection");
             ipc_client = Some(client);
+            redact_edit = None;
+            event_handler::send_live_characters(&ipc_client, &eves);
+            event_handler::send_privacy_changed(&ipc_client, privacy);
+            monitor_setup.send_current(&ipc_client);
+        }
+        
+        // New settings may turn on monitor_position_sets or bring the current setup's positions
+        if settings_rx.try_recv().is_ok() {
+            while settings_rx.try_recv().is_ok() {}
+            hotkey_listener.sync(persistent_state.lock().unwrap().global.hotkey_backend);
+            session_state.window_rules = WindowRules::compile(&persistent_state.lock().unwrap().global.window_rules);
+            let title_pattern = persistent_state.lock().unwrap().title_pattern();
+            if title_pattern.as_str() != session_state.title_pattern.as_str() {
+                info!(title_pattern = %title_pattern.as_str(), "title_pattern changed, rescanning client windows");
+                session_state.title_pattern = title_pattern;
+                match rescan_titles(&ctx) {
+                    Ok(events) => rescan = events,
+                    Err(e) => error!(error = ?e, "Failed to list client windows for the new title_pattern"),
+                }
+            }
+            let mqtt_settings = persistent_state.lock().unwrap().global.mqtt.clone();
+            if mqtt_settings != mqtt_announced {
+                event_handler::publish_online(&ctx, &mqtt_settings, &eves);
+                mqtt_announced = mqtt_settings;
+            }
+            let _ = monitor_setup.check(&ctx, &mut persistent_state.lock().unwrap(), &mut eves, &ipc_client)
+                .inspect_err(|e| error!(error = ?e, "Failed to apply the monitor setup's thumbnail positions"));
         }
         
+        // Apply character settings edited live in the GUI
+        while let Ok(character) = character_rx.try_recv() {
+            let state = persistent_state.lock().unwrap();
+            let _ = event_handler::apply_character_update(&ctx, &state, &mut eves, &character)
+                .inspect_err(|e| error!(character = %character, error = ?e, "Failed to apply character settings"));
+        }
+        
+        while let Ok(character) = redact_edit_rx.try_recv() {
+            redact_edit = character;
+        }
+        
+        // Terminate (and maybe relaunch) clients the GUI asked for
+        while let Ok((character, relaunch)) = terminate_rx.try_recv() {
+            let state = persistent_state.lock().unwrap();
+            let command = state.configured_character(&character)
+                .and_then(|settings| settings.launch_command.clone())
+                .filter(|command| !command.trim().is_empty());
+            if relaunch && command.is_none() {
+                warn!(character = %character, "No launch_command set, terminating without relaunch");
+            }
+            match eves.values().find(|t| t.character_name == character).and_then(|t| t.pid) {
+                Some(pid) => client_control::terminate(pid, character, command.filter(|_| relaunch)),
+                None => warn!(character = %character, "Can't terminate client: no running window with a known PID"),
+            }
+        }
+        
+        // Back from suspend (the pointer and focus changes on wake get the loop running)
+        if resume_rx.try_recv().is_ok() {
+            while resume_rx.try_recv().is_ok() {}
+            info!(thumbnails = eves.len(), "Resumed from sleep, rebinding thumbnails");
+            rebind_thumbnails(&mut eves);
+        }
+        
+        // Monitors back from DPMS standby/off can leave previews black as well
+        if display_sleep.poll(&conn, Instant::now()) {
+            info!(thumbnails = eves.len(), "Displays woke up, rebinding thumbnails");
+            rebind_thumbnails(&mut eves);
+        }
+        
+        // The privacy toggle works regardless of hotkey_require_eve_focus
+        let mut toggle_privacy = privacy_rx.try_recv().is_ok();
+        let command = hotkey_rx.try_recv().ok().filter(|command| {
+            let privacy_hotkey = *command == CycleCommand::TogglePrivacy;
+            toggle_privacy |= privacy_hotkey;
+            !privacy_hotkey
+        });
+        if toggle_privacy {
+            privacy = !privacy;
+            info!(enabled = privacy, "Privacy mode toggled");
+            event_handler::send_privacy_changed(&ipc_client, privacy);
+        }
+        
+        // Focus requests from the GUI act like the character's hotkey, minus the EVE focus check
+        let (command, from_gui) = match command {
+            Some(command) => (Some(command), false),
+            None => (focus_rx.try_recv().ok().map(CycleCommand::Focus), true),
+        };
+        
         // Check for hotkey commands (non-blocking)
-        if let Ok(command) = hotkey_rx.try_recv() {
+        if let Some(command) = command {
             // Check if we should only allow hotkeys when EVE window is focused
             let state = persistent_state.lock().unwrap();
-            let should_process = if state.global.hotkey_require_eve_focus {
-                crate::x11_utils::is_eve_window_focused(&conn, screen, &atoms)
-                    .inspect_err(|e| error!(error = %e, "Failed to check focused window"))
-                    .unwrap_or(false)
+            let should_process = if state.global.hotkey_require_eve_focus && !from_gui {
+                conn.setup().roots.iter().any(|root_screen| {
+                    crate::x11_utils::is_eve_window_focused(&conn, root_screen, &atoms, &session_state.title_pattern)
+                        .inspect_err(|e| error!(error = %e, "Failed to check focused window"))
+                        .unwrap_or(false)
+                })
             } else {
                 true
             };
             
             let minimize_on_switch = state.global.minimize_clients_on_switch;
+            let chord_timeout = Duration::from_millis(state.global.chord_timeout_ms as u64);
+            let strip_modifier = state.global.cycle_strip;
+            let osd_duration = state.global.switch_osd
+                .then(|| Duration::from_millis(state.global.switch_osd_ms as u64));
+            let switch_action = state.global.client_switch_action;
+            cycle_state.set_excluded(state.profile.character_positions.iter()
+                .filter(|(_, settings)| settings.exclude_from_cycle)
+                .map(|(name, _)| name.clone())
+                .collect());
+            cycle_state.set_wrap(state.global.cycle_wrap);
+            cycle_state.apply_sort(state.profile.cycle_sort, &state.profile.cycle_group, |character| {
+                state.character_positions.get(character).map(|settings| (settings.x, settings.y))
+            });
             drop(state); // Release lock before window operations
             
             if should_process {
-                info!(command = ?command, "Received hotkey command");
+                info!(command = ?command, from_gui = from_gui, "Received hotkey command");
+                if !from_gui {
+                    session_state.activity.hotkey_pressed();
+                }
+                // The cycle strip or a chord took the command: nothing to activate yet
+                let mut deferred = false;
                 let result = match command {
+                    CycleCommand::Forward | CycleCommand::Backward
+                        if cycle_strip.active() || strip_modifier.is_some_and(|modifier| CycleStrip::wanted(&conn, screen.root, modifier)) =>
+                    {
+                        let backward = command == CycleCommand::Backward;
+                        if cycle_strip.active() {
+                            cycle_strip.step(&conn, screen, &font_renderer, backward);
+                        } else if let Some(modifier) = strip_modifier {
+                            match cycle_strip.start(&conn, screen, &font_renderer, cycle_state.cycle_targets(), backward, modifier) {
+                                // The strip couldn't stay up: switch like the release would have
+                                Ok(Some(character)) => { x_hotkey_tx.send(CycleCommand::Focus(character)).ok(); }
+                                Ok(None) => {}
+                                Err(e) => error!(error = ?e, "Failed to open cycle strip"),
+                            }
+                        }
+                        deferred = true;
+                        None
+                    }
                     CycleCommand::Forward => cycle_state.cycle_forward(),
                     CycleCommand::Backward => cycle_state.cycle_backward(),
+                    CycleCommand::Focus(ref character) => {
+                        cycle_state.set_current(character);
+                        cycle_state.window_for(character).map(|window| (window, character.as_str()))
+                    }
+                    CycleCommand::FocusLast | CycleCommand::FocusBack | CycleCommand::FocusForward => {
+                        let running = |character: &str| cycle_state.window_for(character).is_some();
+                        let history = &mut session_state.focus_history;
+                        let target = match command {
+                            CycleCommand::FocusBack => history.back(running),
+                            CycleCommand::FocusForward => history.forward(running),
+                            _ => history.previous(running),
+                        };
+                        if target.is_none() {
+                            info!(command = ?command, "Nothing in the focus history to go to");
+                            deferred = true;
+                        }
+                        target.and_then(|character| {
+                            cycle_state.set_current(character);
+                            cycle_state.window_for(character).map(|window| (window, character))
+                        })
+                    }
+                    // Filtered out above
+                    CycleCommand::TogglePrivacy => None,
+                    CycleCommand::Leader(_) => {
+                        let characters = cycle_state.config_order().iter()
+                            .filter(|character| cycle_state.window_for(character).is_some())
+                            .cloned()
+                            .collect();
+                        let _ = chord.start(&conn, screen, &font_renderer, characters, chord_timeout)
+                            .inspect_err(|e| error!(error = ?e, "Failed to start chord"));
+                        deferred = true;
+                        None
+                    }
                 };
 
                 if let Some((window, character_name)) = result {
@@ -392,22 +913,40 @@ pub fn run_preview_daemon() -> Result<()> {
                         character = %display_name,
                         "Activating window via hotkey"
                     );
-                    if let Err(e) = activate_window(&conn, screen, &atoms, window) {
+                    let client_screen = |w: &Window| eves.get(w).map_or(screen, |t| t.screen);
+                    // Bare X server (kiosk setups): no WM to focus or minimize clients for us
+                    let window_manager = window_manager_running(&conn, client_screen(&window));
+                    let switch_action = if window_manager { switch_action } else { ClientSwitchAction::Lower };
+                    let activated = if foreign_toplevel::try_activate(&ctx, window) {
+                        Ok(())
+                    } else if window_manager {
+                        activate_window(&conn, client_screen(&window), &atoms, window, ctx.wm.get().raise_before_activate)
+                    } else {
+                        focus_window_directly(&conn, window)
+                    };
+                    if let Err(e) = &activated {
                         error!(window = window, error = %e, "Failed to activate window");
-                    } else if minimize_on_switch {
-                        // Minimize all other EVE clients after successful activation
+                    } else if let Some(duration) = osd_duration.filter(|_| !from_gui) {
+                        let _ = osd.show(&conn, client_screen(&window), display_name, duration)
+                            .inspect_err(|e| warn!(error = ?e, "Failed to show switch OSD"));
+                    }
+                    if activated.is_ok() && minimize_on_switch {
+                        // Minimize (or lower) all other EVE clients after successful activation
                         let other_windows: Vec<Window> = eves
                             .keys()
                             .copied()
                             .filter(|w| *w != window)
                             .collect();
                         for other_window in other_windows {
-                            if let Err(e) = minimize_window(&conn, screen, &atoms, other_window) {
-                                debug!(window = other_window, error = %e, "Failed to minimize window via hotkey");
+                            if switch_action == ClientSwitchAction::Minimize && foreign_toplevel::try_minimize(&ctx, other_window) {
+                                continue;
+                            }
+                            if let Err(e) = apply_switch_action(&conn, client_screen(&other_window), &atoms, other_window, switch_action) {
+                                debug!(window = other_window, error = %e, action = ?switch_action, "Failed to minimize/lower window via hotkey");
                             }
                         }
                     }
-                } else {
+                } else if !deferred {
                     warn!(active_windows = cycle_state.config_order().len(), "No window to activate, cycle state is empty");
                 }
             } else {
@@ -416,18 +955,397 @@ pub fn run_preview_daemon() -> Result<()> {
             }
         }
 
-        let event = conn.wait_for_event()
-            .context("Failed to wait for X11 event")?;
-        let mut state = persistent_state.lock().unwrap();
-        let _ = handle_event(
-            &ctx,
-            &mut *state,
-            &mut eves,
-            event,
-            &mut session_state,
-            &mut cycle_state,
-            check_and_create_window,
-            &ipc_client,
-        ).inspect_err(|err| error!(error = ?err, "Event handling error"));
+        chord.expire(&conn, Instant::now());
+        osd.expire(&conn, Instant::now());
+        
+        // Only block indefinitely when no held-back motion is waiting for its frame
+        let deadline = [
+            motion_throttle.deadline(),
+            frame_pacer.deadline(),
+            process_sampler.deadline(),
+            night_watch.deadline(),
+            stall_watch.deadline(Instant::now()),
+            display_sleep.deadline(!eves.is_empty()),
+            watchdog.deadline(!eves.is_empty()),
+            source_refresh.deadline(),
+            chord.deadline(),
+            osd.deadline(),
+            gl_frame_deadline(&ctx),
+            (!rescan.is_empty()).then(Instant::now),
+        ]
+            .into_iter()
+            .flatten()
+            .min();
+        let event = match deadline {
+            Some(deadline) => poll_event_until(&conn, deadline)?,
+            None => Some(conn.wait_for_event()
+                .context("Failed to wait for X11 event")?),
+        };
+        finish_gl_frame(&ctx, &eves, event.is_none());
+        
+        // A held-back motion always runs before the next other event (e.g. ButtonRelease)
+        let mut ready: Vec<Event> = Vec::with_capacity(2);
+        match event {
+            Some(Event::MotionNotify(motion)) => {
+                motion_throttle.set_max_fps(persistent_state.lock().unwrap().global.drag_max_fps);
+                ready.extend(motion_throttle.offer(motion, Instant::now()).map(Event::MotionNotify));
+            }
+            Some(event) => {
+                ready.extend(motion_throttle.take_pending().map(Event::MotionNotify));
+                ready.push(event);
+            }
+            None => {
+                if motion_throttle.deadline().is_some_and(|deadline| deadline <= Instant::now()) {
+                    ready.extend(motion_throttle.take_pending().map(Event::MotionNotify));
+                }
+            }
+        }
+        
+        // Held-back damage whose frame is due gets drawn before anything else
+        for damage in frame_pacer.take_due(Instant::now()) {
+            let _ = event_handler::redraw_damaged(&ctx, &eves, damage)
+                .inspect_err(|err| error!(error = ?err, "Failed to redraw held-back damage"));
+        }
+        
+        // Moved/resized clients often send no damage, so redraw once the move/resize is over
+        for window in source_refresh.take_settled(Instant::now()) {
+            if let Some(thumbnail) = eves.get(&window) {
+                let _ = thumbnail.refresh_source()
+                    .inspect_err(|err| error!(window = window, error = ?err, "Failed to refresh thumbnail after client was configured"));
+            }
+        }
+        
+        // Gone clients go through the DestroyNotify handling below like a real one
+        if watchdog.due(Instant::now()) {
+            let problems = watchdog::inspect(&eves);
+            let state = persistent_state.lock().unwrap();
+            ready.extend(repair_thumbnails(&ctx, &state, &mut eves, &session_state, problems));
+        }
+        ready.append(&mut rescan);
+        
+        for event in ready {
+            if x11_trace::active() {
+                x11_trace::event(traced_client(&eves, &event), &event);
+            }
+            let mut state = persistent_state.lock().unwrap();
+            match &event {
+                Event::RandrScreenChangeNotify(_) | Event::RandrNotify(_) => {
+                    let _ = monitor_setup.check(&ctx, &mut state, &mut eves, &ipc_client)
+                        .inspect_err(|e| error!(error = ?e, "Failed to apply the monitor setup's thumbnail positions"));
+                    continue;
+                }
+                Event::DamageNotify(notify) => {
+                    stall_watch.frame(notify.damage, Instant::now());
+                    if let Some(thumbnail) = eves.values_mut().find(|t| t.damage == notify.damage) {
+                        thumbnail.frames.record(Instant::now());
+                    }
+                    if display_sleep.damage(Instant::now()) {
+                        info!(thumbnails = eves.len(), "First frame after a long pause, rebinding thumbnails");
+                        rebind_thumbnails(&mut eves);
+                        continue;
+                    }
+                    frame_pacer.set_max_fps(frame_pacer::max_fps(
+                        state.global.thumbnail_max_fps,
+                        low_bandwidth_active(state.global.low_bandwidth),
+                    ));
+                    if !frame_pacer.offer(notify.damage, Instant::now()) {
+                        continue;
+                    }
+                }
+                Event::DestroyNotify(destroy) => {
+                    if let Some(thumbnail) = eves.get(&destroy.window) {
+                        frame_pacer.forget(thumbnail.damage);
+                        stall_watch.forget(thumbnail.damage);
+                    }
+                    source_refresh.forget(destroy.window);
+                    error_watch.forget(destroy.window);
+                }
+                // Unchecked drawing requests report failures here; a thumbnail that keeps
+                // failing is recreated instead of staying black until restart
+                Event::Error(err) => {
+                    let Some(thumbnail) = eves.values().find(|t| t.owns_resource(err.bad_value)) else {
+                        debug!(error = ?err, "X error not caused by a thumbnail");
+                        continue;
+                    };
+                    let (window, damage) = (thumbnail.src, thumbnail.damage);
+                    debug!(window = window, character = %thumbnail.character_name, error = ?err, "X error on thumbnail");
+                    if error_watch.record(window, Instant::now()) {
+                        frame_pacer.forget(damage);
+                        stall_watch.forget(damage);
+                        let reason = format!("{} X errors", error_watch.total(window));
+                        rebuild_thumbnail(&ctx, &state, &mut eves, window, &session_state, &reason);
+                    }
+                    continue;
+                }
+                // Grabbed mouse-button hotkeys go through the hotkey channel like Tab does
+                Event::ButtonPress(press) => {
+                    if let Some(command) = mouse_hotkeys.command(press.event, press.detail, press.state) {
+                        x_hotkey_tx.send(command).ok();
+                        continue;
+                    }
+                }
+                Event::ButtonRelease(release) if mouse_hotkeys.command(release.event, release.detail, release.state).is_some() => continue,
+                // Escape and the modifier release while the cycle strip holds the keyboard
+                Event::KeyPress(key) if cycle_strip.active() => {
+                    cycle_strip.key_press(&conn, key.detail);
+                    continue;
+                }
+                Event::KeyRelease(_) if cycle_strip.active() => {
+                    if let Some(character) = cycle_strip.key_release(&conn) {
+                        x_hotkey_tx.send(CycleCommand::Focus(character)).ok();
+                    }
+                    continue;
+                }
+                // Follow-up key of a chord (the keyboard is grabbed until it arrives)
+                Event::KeyPress(key) if chord.active() => {
+                    if let Some(character) = chord.key(&conn, key.detail) {
+                        x_hotkey_tx.send(CycleCommand::Focus(character)).ok();
+                    }
+                    continue;
+                }
+                // The client's own copy (STRUCTURE_NOTIFY); root's SUBSTRUCTURE_NOTIFY copy is for restacking
+                Event::ConfigureNotify(configure) if configure.event == configure.window => {
+                    if let Some(thumbnail) = eves.get_mut(&configure.window) {
+                        let _ = fullscreen_redirect::check(&ctx, &state.global, thumbnail)
+                            .inspect_err(|err| error!(error = ?err, "Failed to keep fullscreen client redirected"));
+                        let thumbnail = &eves[&configure.window];
+                        source_refresh.configured(configure.window, Instant::now());
+                        if state.global.live_source_refresh && frame_pacer.offer(thumbnail.damage, Instant::now()) {
+                            let _ = event_handler::redraw_damaged(&ctx, &eves, thumbnail.damage)
+                                .inspect_err(|err| error!(error = ?err, "Failed to redraw thumbnail during client move/resize"));
+                        }
+                    }
+                }
+                _ => {}
+            }
+            let _ = handle_event(
+                &ctx,
+                &mut state,
+                &mut eves,
+                event,
+                &mut session_state,
+                &mut cycle_state,
+                check_and_create_window,
+                &ipc_client,
+            ).inspect_err(|err| error!(error = ?err, "Event handling error"));
+        }
+        
+        // Follow panel mode being toggled and re-pack after logins, logouts and resizes
+        let (panel_settings, margins, wm_preset) = {
+            let state = persistent_state.lock().unwrap();
+            (state.global.panel, state.global.screen_margins, wm_compat::resolve(state.global.wm_preset, detected_wm))
+        };
+        mouse_hotkeys.sync(&conn, screen.root, &persistent_state.lock().unwrap().global, eves.keys().copied());
+        if wm.get() != wm_compat::compat(wm_preset) {
+            info!(preset = ?wm_preset, "Window manager compatibility preset changed, applies to new thumbnails and switches");
+            wm.set(wm_compat::compat(wm_preset));
+        }
+        let _ = panel::sync(&ctx, &mut panel, panel_settings, &margins, &mut eves, cycle_state.config_order())
+            .inspect_err(|err| error!(error = ?err, "Failed to update preview panel"));
+        
+        let (show_process_stats, stall_warning_secs, night_settings) = {
+            let state = persistent_state.lock().unwrap();
+            (state.global.show_process_stats, state.global.stall_warning_secs, state.global.night_mode)
+        };
+        for thumbnail in eves.values().filter(|t| t.duplicate_of.is_none()) {
+            session_state.activity.seen(&thumbnail.character_name);
+        }
+        update_process_stats(&mut process_sampler, show_process_stats, &mut eves);
+        stall_watch.set_timeout_secs(stall_warning_secs);
+        update_stalls(&mut stall_watch, &mut eves);
+        let label_template = persistent_state.lock().unwrap().profile.label_template.clone();
+        update_labels(&label_template, &cycle_state, &mut eves);
+        night_watch.set_settings(night_settings);
+        update_night_mode(&mut night_watch, &conn, screen, &mut eves);
+        for thumbnail in eves.values_mut() {
+            let _ = thumbnail.set_privacy(privacy)
+                .inspect_err(|e| warn!(character = %thumbnail.character_name, error = ?e, "Failed to update privacy mode"));
+            let editing = thumbnail.duplicate_of.is_none() && redact_edit.as_ref() == Some(&thumbnail.character_name);
+            thumbnail.set_redact_edit(editing);
+        }
+    }
+}
+
+/// Destroy a broken thumbnail (X errors, watchdog) and create it again from scratch
+/// Its position, size and settings come back from the in-memory state like on login
+fn rebuild_thumbnail<'a>(
+    ctx: &AppContext<'a>,
+    persistent_state: &PersistentState,
+    eves: &mut HashMap<Window, Thumbnail<'a>>,
+    window: Window,
+    session_state: &SessionState,
+    reason: &str,
+) {
+    let Some(old) = eves.get(&window) else {
+        return;
+    };
+    if old.duplicate_of.is_some() {
+        warn!(window = window, character = %old.character_name, reason = %reason, "Duplicate thumbnail is broken, not rebuilding it");
+        return;
+    }
+    warn!(window = window, character = %old.character_name, reason = %reason, "Rebuilding thumbnail");
+    let old_state = old.state;
+    // Dropping frees the old resources before the new ones are created
+    eves.remove(&window);
+
+    let mut thumbnail = match check_and_create_window(ctx, persistent_state, window, session_state) {
+        Ok(Some(thumbnail)) => thumbnail,
+        Ok(None) => {
+            info!(window = window, "Client is no longer an EVE window, thumbnail not recreated");
+            return;
+        }
+        Err(e) => {
+            error!(window = window, error = ?e, "Failed to rebuild thumbnail");
+            return;
+        }
+    };
+    let restored = match old_state {
+        ThumbnailState::Normal { focused: true } => {
+            thumbnail.state = old_state;
+            thumbnail.border(true)
+        }
+        ThumbnailState::Normal { focused: false } => Ok(()),
+        ThumbnailState::Minimized => thumbnail.minimized(),
+        ThumbnailState::Hidden => thumbnail.visibility(false),
+    };
+    let _ = restored
+        .inspect_err(|e| warn!(window = window, error = ?e, "Failed to restore state of rebuilt thumbnail"));
+    info!(window = window, character = %thumbnail.character_name, "Rebuilt thumbnail");
+    eves.insert(window, thumbnail);
+}
+
+/// Repair what the watchdog found; returns the DestroyNotify events of clients that are gone
+fn repair_thumbnails<'a>(
+    ctx: &AppContext<'a>,
+    persistent_state: &PersistentState,
+    eves: &mut HashMap<Window, Thumbnail<'a>>,
+    session_state: &SessionState,
+    problems: Vec<(Window, Problem)>,
+) -> Vec<Event> {
+    let mut gone = Vec::new();
+    for (window, problem) in problems {
+        let repaired = match problem {
+            Problem::SourceGone => {
+                gone.push(Event::DestroyNotify(DestroyNotifyEvent {
+                    response_type: DESTROY_NOTIFY_EVENT,
+                    sequence: 0,
+                    event: window,
+                    window,
+                }));
+                Ok(())
+            }
+            Problem::Broken => {
+                rebuild_thumbnail(ctx, persistent_state, eves, window, session_state, "invalid window or pixmap");
+                Ok(())
+            }
+            Problem::WrongSize => match eves.get_mut(&window) {
+                Some(thumbnail) => thumbnail.resize(thumbnail.identity.dimensions()),
+                None => Ok(()),
+            },
+            Problem::StaleSource => eves.get(&window).map_or(Ok(()), Thumbnail::refresh_source),
+        };
+        let _ = repaired
+            .inspect_err(|e| warn!(window = window, problem = ?problem, error = ?e, "Failed to repair thumbnail"));
+    }
+    gone
+}
+
+
+/// Grey out thumbnails whose client stopped drawing, and restore them once frames arrive again
+/// Minimized clients don't draw, so they aren't watched until restored
+fn update_stalls(watch: &mut StallWatch, eves: &mut HashMap<Window, Thumbnail>) {
+    let now = Instant::now();
+    for thumbnail in eves.values_mut() {
+        let stalled = if thumbnail.state.is_minimized() {
+            watch.forget(thumbnail.damage);
+            false
+        } else {
+            watch.watch(thumbnail.damage, now);
+            watch.is_stalled(thumbnail.damage, now)
+        };
+        if stalled == thumbnail.stalled {
+            continue;
+        }
+        if stalled {
+            warn!(window = thumbnail.src, character = %thumbnail.character_name, "Client stopped producing frames (hung?)");
+        } else {
+            info!(window = thumbnail.src, character = %thumbnail.character_name, "Client is drawing again");
+        }
+        let _ = thumbnail.set_stalled(stalled)
+            .inspect_err(|e| warn!(character = %thumbnail.character_name, error = ?e, "Failed to update stalled marker"));
+    }
+}
+
+/// Tint thumbnails while night mode is on and restore them once it's over
+/// Thumbnails created in between pick the tint up here too
+fn update_night_mode(watch: &mut NightWatch, conn: &RustConnection, screen: &Screen, eves: &mut HashMap<Window, Thumbnail>) {
+    let night = watch.update(conn, screen, Instant::now());
+    for thumbnail in eves.values_mut() {
+        let _ = thumbnail.set_night(night)
+            .inspect_err(|e| warn!(character = %thumbnail.character_name, error = ?e, "Failed to update night tint"));
+    }
+}
+
+/// Sample the clients' CPU/RAM when due and put the readout on their thumbnails
+/// Turning the setting off removes the readout
+fn update_process_stats(sampler: &mut ProcessSampler, enabled: bool, eves: &mut HashMap<Window, Thumbnail>) {
+    let toggled = sampler.set_enabled(enabled);
+    let now = Instant::now();
+    if !sampler.is_due(now) && !toggled {
+        return;
+    }
+    let stats = if enabled {
+        sampler.sample(eves.values().filter_map(|t| t.pid), now)
+    } else {
+        HashMap::new()
+    };
+    for thumbnail in eves.values_mut() {
+        let label = thumbnail.pid.and_then(|pid| stats.get(&pid)).map(ProcessStats::label);
+        let _ = thumbnail.set_stats(label)
+            .inspect_err(|e| warn!(character = %thumbnail.character_name, error = ?e, "Failed to show process stats"));
+    }
+}
+
+/// Fresh damage tracking and source picture for every thumbnail (after resume or display sleep)
+fn rebind_thumbnails(eves: &mut HashMap<Window, Thumbnail>) {
+    for thumbnail in eves.values_mut() {
+        let _ = thumbnail.rebind_source()
+            .inspect_err(|e| warn!(character = %thumbnail.character_name, error = ?e, "Failed to rebind thumbnail"));
+    }
+}
+
+/// Expand label_template for every thumbnail; {index} numbers the running characters in cycle order
+fn update_labels(template: &str, cycle_state: &CycleState, eves: &mut HashMap<Window, Thumbnail>) {
+    let running: Vec<&String> = cycle_state.config_order().iter()
+        .filter(|character| cycle_state.window_for(character).is_some())
+        .collect();
+    for thumbnail in eves.values_mut() {
+        let index = running.iter().position(|character| **character == thumbnail.character_name).map(|index| index + 1);
+        let _ = thumbnail.set_label_template(template, index)
+            .inspect_err(|e| warn!(character = %thumbnail.character_name, error = ?e, "Failed to update label"));
+    }
+}
+
+/// Wait for an X11 event until `deadline`, returning None if none arrived in time
+/// Blocks in poll(2) on the connection's socket, like wait_for_event but with a timeout
+fn poll_event_until(conn: &RustConnection, deadline: Instant) -> Result<Option<Event>> {
+    loop {
+        if let Some(event) = conn.poll_for_event()
+            .context("Failed to poll for X11 event")?
+        {
+            return Ok(Some(event));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        // wait_for_event sends queued requests while it waits, poll(2) only reads
+        conn.flush().context("Failed to flush X11 connection")?;
+        // Rounded up, a timeout of 0 would spin until the deadline
+        let timeout = PollTimeout::try_from((deadline - now).as_micros().div_ceil(1000)).unwrap_or(PollTimeout::MAX);
+        match poll(&mut [PollFd::new(conn.stream().as_fd(), PollFlags::POLLIN)], timeout) {
+            Ok(_) | Err(Errno::EINTR) => {}
+            Err(e) => return Err(e).context("Failed to wait for X11 event"),
+        }
     }
 }
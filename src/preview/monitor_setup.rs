@@ -0,0 +1,164 @@
+//! Position sets per monitor setup (monitor_position_sets)
+//!
+//! The monitor setup is identified by a fingerprint of the RandR monitor
+//! geometries. When it changes (docking, unplugging a screen) the positions
+//! the thumbnails had are remembered under the old fingerprint and the
+//! thumbnails move to the positions saved for the new one, if there are any.
+//! The GUI is told about every setup change so it can keep the same sets in
+//! the config; the moves reach it as ordinary PositionChanged events.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+use x11rb::protocol::randr::{ConnectionExt as RandrExt, NotifyMask};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::Window;
+use x11rb::rust_connection::RustConnection;
+
+use crate::config::PersistentState;
+use crate::ipc::PreviewResponse;
+use crate::types::{CharacterSettings, Dimensions, Position, ThumbnailGeometry};
+use crate::x11_utils::{list_monitors, AppContext};
+
+use super::event_handler::send_position_changed;
+use super::ipc_handler::ClientConnection;
+use super::thumbnail::Thumbnail;
+
+/// "1920x1080+0+0,2560x1440+1920+0" for the monitors as list_monitors orders them
+pub fn fingerprint(monitors: &[(Position, Dimensions)]) -> String {
+    monitors.iter()
+        .map(|(position, size)| format!("{}x{}+{}+{}", size.width, size.height, position.x, position.y))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Ask for RandR notifications on monitor changes (no-op without RandR)
+pub fn watch(conn: &RustConnection) {
+    let mask = NotifyMask::SCREEN_CHANGE | NotifyMask::CRTC_CHANGE | NotifyMask::OUTPUT_CHANGE;
+    for root_screen in &conn.setup().roots {
+        let _ = conn.randr_select_input(root_screen.root, mask)
+            .inspect_err(|e| warn!(error = %e, "RandR unavailable, monitor changes won't move thumbnails"));
+    }
+}
+
+/// Characters of `set` whose saved geometry differs from the runtime one
+fn moves<'a>(
+    set: &'a HashMap<String, ThumbnailGeometry>,
+    runtime: &HashMap<String, CharacterSettings>,
+) -> Vec<(&'a String, ThumbnailGeometry)> {
+    let mut moves: Vec<_> = set.iter()
+        .filter(|(character, geometry)| runtime.get(*character).map(ThumbnailGeometry::of) != Some(**geometry))
+        .map(|(character, geometry)| (character, *geometry))
+        .collect();
+    moves.sort_by(|a, b| a.0.cmp(b.0));
+    moves
+}
+
+#[derive(Debug, Default)]
+pub struct MonitorSetup {
+    current: Option<String>,
+    /// The current setup's saved positions were applied (settings can arrive after startup)
+    applied: bool,
+}
+
+impl MonitorSetup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tell a newly connected GUI which setup is active
+    pub fn send_current(&self, ipc_client: &Option<Arc<Mutex<ClientConnection>>>) {
+        if let Some(current) = &self.current {
+            send_setup_changed(ipc_client, None, current);
+        }
+    }
+
+    /// Re-read the monitors (startup, RandR notification, new settings)
+    /// On a new setup, remember the old one's positions and move thumbnails to the new one's
+    pub fn check(
+        &mut self,
+        ctx: &AppContext,
+        state: &mut PersistentState,
+        eves: &mut HashMap<Window, Thumbnail>,
+        ipc_client: &Option<Arc<Mutex<ClientConnection>>>,
+    ) -> Result<()> {
+        let current = fingerprint(&list_monitors(ctx.conn, ctx.screen));
+        let enabled = state.global.monitor_position_sets;
+        if self.current.as_deref() != Some(current.as_str()) {
+            let previous = self.current.replace(current.clone());
+            info!(previous = ?previous, current = %current, "Monitor setup changed");
+            if enabled && let Some(previous) = &previous {
+                let runtime = &state.character_positions;
+                state.profile.remember_monitor_positions(previous, runtime);
+            }
+            send_setup_changed(ipc_client, previous.as_deref(), &current);
+            self.applied = false;
+        }
+        if !enabled || self.applied {
+            return Ok(());
+        }
+        self.applied = true;
+
+        let Some(set) = state.profile.monitor_positions.get(&current) else {
+            return Ok(());
+        };
+        for (character, geometry) in moves(set, &state.character_positions) {
+            for thumbnail in eves.values_mut().filter(|t| t.character_name == *character && t.duplicate_of.is_none()) {
+                if geometry.dimensions.width > 0 && geometry.dimensions.height > 0 && thumbnail.identity.dimensions() != geometry.dimensions {
+                    thumbnail.resize(geometry.dimensions)
+                        .context(format!("Failed to resize thumbnail for '{}' to the monitor setup's size", character))?;
+                }
+                thumbnail.reposition(geometry.x, geometry.y)
+                    .context(format!("Failed to move thumbnail for '{}' to the monitor setup's position", character))?;
+            }
+            let (x, y, Dimensions { width, height }) = (geometry.x, geometry.y, geometry.dimensions);
+            state.character_positions.entry(character.clone())
+                .and_modify(|settings| settings.set_geometry(x, y, width, height))
+                .or_insert_with(|| CharacterSettings::new(x, y, width, height));
+            send_position_changed(ipc_client, character, x, y, width, height);
+        }
+        info!(setup = %current, "Moved thumbnails to the monitor setup's saved positions");
+        Ok(())
+    }
+}
+
+fn send_setup_changed(ipc_client: &Option<Arc<Mutex<ClientConnection>>>, previous: Option<&str>, current: &str) {
+    let response = PreviewResponse::MonitorSetupChanged {
+        previous: previous.map(str::to_string),
+        current: current.to_string(),
+    };
+    if let Some(client) = ipc_client
+        && let Ok(mut client_lock) = client.lock()
+        && let Err(e) = client_lock.send_response(&response)
+    {
+        warn!(error = ?e, "Failed to send MonitorSetupChanged via IPC");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_and_moves() {
+        let docked = [
+            (Position::new(0, 0), Dimensions::new(1920, 1080)),
+            (Position::new(1920, 0), Dimensions::new(2560, 1440)),
+        ];
+        assert_eq!(fingerprint(&docked), "1920x1080+0+0,2560x1440+1920+0");
+
+        let geometry = |x, y| ThumbnailGeometry { x, y, dimensions: Dimensions::new(480, 270) };
+        let set = HashMap::from([
+            ("Main".to_string(), geometry(2000, 10)),
+            ("Alt".to_string(), geometry(10, 10)),
+            ("Scout".to_string(), geometry(500, 10)),
+        ]);
+        let runtime = HashMap::from([
+            ("Main".to_string(), CharacterSettings::new(100, 10, 480, 270)),
+            ("Alt".to_string(), CharacterSettings::new(10, 10, 480, 270)),
+        ]);
+        let moved: Vec<&str> = moves(&set, &runtime).into_iter().map(|(character, _)| character.as_str()).collect();
+        assert_eq!(moved, vec!["Main", "Scout"]);
+    }
+}
@@ -0,0 +1,98 @@
+//! Rate limiting for pointer motion during thumbnail drags
+//!
+//! Every MotionNotify used to trigger a ConfigureWindow. The throttle lets at
+//! most one motion through per frame interval and keeps only the newest one
+//! in between; the main loop hands the pending event back once the frame is
+//! due or before any other event, so the final drag position is never lost.
+
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub struct MotionThrottle<T> {
+    pending: Option<T>,
+    last: Option<Instant>,
+    interval: Duration,
+}
+
+impl<T> MotionThrottle<T> {
+    pub fn new() -> Self {
+        Self {
+            pending: None,
+            last: None,
+            interval: Duration::ZERO,
+        }
+    }
+
+    /// Set the maximum processing rate (0 = unlimited)
+    pub fn set_max_fps(&mut self, fps: u16) {
+        self.interval = if fps == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / fps as u32
+        };
+    }
+
+    /// Offer a new motion event; returns it if it should be processed right away
+    pub fn offer(&mut self, event: T, now: Instant) -> Option<T> {
+        let due = self.last.is_none_or(|last| now.duration_since(last) >= self.interval);
+        if due {
+            self.last = Some(now);
+            self.pending = None;
+            Some(event)
+        } else {
+            // Newer motion supersedes the one we were holding back
+            self.pending = Some(event);
+            None
+        }
+    }
+
+    /// When the held-back event should be processed (None if nothing is pending)
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.as_ref()?;
+        Some(self.last.map_or_else(Instant::now, |last| last + self.interval))
+    }
+
+    /// Take the held-back event, e.g. when its deadline passed or another event arrived
+    pub fn take_pending(&mut self) -> Option<T> {
+        let event = self.pending.take()?;
+        self.last = Some(Instant::now());
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_passes_everything() {
+        let mut throttle = MotionThrottle::new();
+        let now = Instant::now();
+        assert_eq!(throttle.offer(1, now), Some(1));
+        assert_eq!(throttle.offer(2, now), Some(2));
+        assert_eq!(throttle.deadline(), None);
+    }
+
+    #[test]
+    fn test_coalesces_within_frame() {
+        let mut throttle = MotionThrottle::new();
+        throttle.set_max_fps(60);
+        let start = Instant::now();
+        assert_eq!(throttle.offer(1, start), Some(1));
+        assert_eq!(throttle.offer(2, start + Duration::from_millis(5)), None);
+        assert_eq!(throttle.offer(3, start + Duration::from_millis(10)), None);
+        assert!(throttle.deadline().is_some());
+        assert_eq!(throttle.take_pending(), Some(3));
+        assert_eq!(throttle.take_pending(), None);
+    }
+
+    #[test]
+    fn test_passes_after_interval() {
+        let mut throttle = MotionThrottle::new();
+        throttle.set_max_fps(60);
+        let start = Instant::now();
+        assert_eq!(throttle.offer(1, start), Some(1));
+        assert_eq!(throttle.offer(2, start + Duration::from_millis(20)), Some(2));
+        assert_eq!(throttle.deadline(), None);
+    }
+}
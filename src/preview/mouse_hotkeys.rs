@@ -0,0 +1,173 @@
+//! Mouse-button hotkeys (mouse_cycle_forward / mouse_cycle_backward)
+//!
+//! Extra mouse buttons cycle characters like Tab/Shift+Tab. They are passive
+//! button grabs: on every client window while hotkey_require_eve_focus is on,
+//! so the buttons keep their usual meaning in the browser and everywhere else,
+//! or on the root window when hotkeys work globally. A grabbed press comes to
+//! the daemon instead of the window under the pointer. Shift reverses a
+//! button, like Shift+Tab, so a single button can cycle both ways.
+
+use std::collections::HashSet;
+
+use tracing::{debug, info, warn};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::NONE;
+
+use crate::config::profile::GlobalSettings;
+use crate::hotkeys::CycleCommand;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Buttons {
+    forward: Option<u8>,
+    backward: Option<u8>,
+}
+
+impl Buttons {
+    fn from_settings(global: &GlobalSettings) -> Self {
+        Self { forward: global.mouse_cycle_forward, backward: global.mouse_cycle_backward }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = u8> {
+        self.forward.into_iter().chain(self.backward)
+    }
+
+    fn command(&self, button: u8) -> Option<CycleCommand> {
+        if self.forward == Some(button) {
+            Some(CycleCommand::Forward)
+        } else if self.backward == Some(button) {
+            Some(CycleCommand::Backward)
+        } else {
+            None
+        }
+    }
+}
+
+/// Windows the buttons get grabbed on: the clients, or root when hotkeys are global
+fn grab_windows(buttons: Buttons, require_eve_focus: bool, root: Window, clients: impl Iterator<Item = Window>) -> HashSet<Window> {
+    if buttons.iter().next().is_none() {
+        HashSet::new()
+    } else if require_eve_focus {
+        clients.collect()
+    } else {
+        HashSet::from([root])
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MouseHotkeys {
+    buttons: Buttons,
+    grabbed: HashSet<Window>,
+}
+
+impl MouseHotkeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grab the configured buttons where they belong and release them everywhere else
+    /// Cheap when nothing changed, so the main loop calls it every iteration
+    pub fn sync(&mut self, conn: &impl Connection, root: Window, global: &GlobalSettings, clients: impl Iterator<Item = Window>) {
+        let buttons = Buttons::from_settings(global);
+        if buttons != self.buttons {
+            for window in std::mem::take(&mut self.grabbed) {
+                self.ungrab(conn, window);
+            }
+            self.buttons = buttons;
+            info!(forward = ?buttons.forward, backward = ?buttons.backward, "Mouse-button hotkeys changed");
+        }
+        let wanted = grab_windows(buttons, global.hotkey_require_eve_focus, root, clients);
+        let released: Vec<Window> = self.grabbed.difference(&wanted).copied().collect();
+        for window in released {
+            self.ungrab(conn, window);
+            self.grabbed.remove(&window);
+        }
+        let added: Vec<Window> = wanted.difference(&self.grabbed).copied().collect();
+        for window in added {
+            self.grab(conn, window);
+            // Kept even if the grab failed, so it isn't retried every iteration
+            self.grabbed.insert(window);
+        }
+    }
+
+    fn grab(&self, conn: &impl Connection, window: Window) {
+        for button in self.buttons.iter() {
+            let grabbed = conn.grab_button(
+                false,
+                window,
+                EventMask::BUTTON_PRESS,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+                NONE,
+                NONE,
+                ButtonIndex::from(button),
+                ModMask::ANY,
+            )
+            .map_err(anyhow::Error::from)
+            .and_then(|cookie| cookie.check().map_err(anyhow::Error::from));
+            if let Err(e) = grabbed {
+                // Usually another client (the WM) already grabbed the button there
+                warn!(window = window, button = button, error = ?e, "Failed to grab mouse button for hotkey");
+            }
+        }
+    }
+
+    fn ungrab(&self, conn: &impl Connection, window: Window) {
+        for button in self.buttons.iter() {
+            let released = conn.ungrab_button(ButtonIndex::from(button), window, ModMask::ANY)
+                .map_err(anyhow::Error::from)
+                .and_then(|cookie| cookie.check().map_err(anyhow::Error::from));
+            if let Err(e) = released {
+                // The client is gone, and its grabs with it
+                debug!(window = window, button = button, error = ?e, "Failed to release mouse button grab");
+            }
+        }
+    }
+
+    /// The cycle command for a press (or release) of a grabbed hotkey button,
+    /// reversed while Shift is held
+    pub fn command(&self, window: Window, button: u8, state: KeyButMask) -> Option<CycleCommand> {
+        if !self.grabbed.contains(&window) {
+            return None;
+        }
+        let command = self.buttons.command(button)?;
+        match command.shifted() {
+            Some(shifted) if state.contains(KeyButMask::SHIFT) => Some(shifted),
+            _ => Some(command),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grab_windows_follow_focus_setting() {
+        let buttons = Buttons { forward: Some(9), backward: Some(8) };
+        assert_eq!(grab_windows(buttons, true, 1, [10, 11].into_iter()), HashSet::from([10, 11]));
+        assert_eq!(grab_windows(buttons, false, 1, [10, 11].into_iter()), HashSet::from([1]));
+        assert!(grab_windows(Buttons::default(), false, 1, [10].into_iter()).is_empty());
+    }
+
+    #[test]
+    fn test_button_commands() {
+        let buttons = Buttons { forward: Some(9), backward: Some(8) };
+        assert_eq!(buttons.command(9), Some(CycleCommand::Forward));
+        assert_eq!(buttons.command(8), Some(CycleCommand::Backward));
+        assert_eq!(buttons.command(1), None);
+        let forward_only = Buttons { forward: Some(9), backward: None };
+        assert_eq!(forward_only.iter().collect::<Vec<_>>(), vec![9]);
+    }
+
+    #[test]
+    fn test_shift_reverses_button() {
+        let hotkeys = MouseHotkeys {
+            buttons: Buttons { forward: Some(9), backward: None },
+            grabbed: HashSet::from([10]),
+        };
+        assert_eq!(hotkeys.command(10, 9, KeyButMask::default()), Some(CycleCommand::Forward));
+        assert_eq!(hotkeys.command(10, 9, KeyButMask::SHIFT | KeyButMask::MOD2), Some(CycleCommand::Backward));
+        assert_eq!(hotkeys.command(11, 9, KeyButMask::default()), None);
+    }
+}
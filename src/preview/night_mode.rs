@@ -0,0 +1,122 @@
+//! Night mode schedule (night_mode)
+//!
+//! Every half minute the daemon decides whether night mode is on: either the
+//! local hour is within start_hour..end_hour (wrapping past midnight), or with
+//! follow_gamma, redshift/gammastep has warmed the screen. The latter is read
+//! from the RandR gamma ramp of the first CRTC, where those tools lower the
+//! blue end below the red one.
+
+use std::time::{Duration, Instant};
+
+use chrono::Timelike;
+use tracing::{debug, info};
+use x11rb::protocol::randr::ConnectionExt as RandrExt;
+use x11rb::protocol::xproto::Screen;
+use x11rb::rust_connection::RustConnection;
+
+use crate::config::profile::NightModeSettings;
+use crate::constants::night_mode::{CHECK_INTERVAL_SECS, WARM_RAMP_RATIO};
+
+/// Whether `hour` is inside the night from `start` to `end` (end exclusive)
+pub fn in_night_hours(hour: u8, start: u8, end: u8) -> bool {
+    if start <= end {
+        (start..end).contains(&hour)
+    } else {
+        hour >= start || hour < end
+    }
+}
+
+/// Whether a gamma ramp was warmed (blue end noticeably below red)
+pub fn ramp_is_warm(red: &[u16], blue: &[u16]) -> bool {
+    match (red.last(), blue.last()) {
+        (Some(&red), Some(&blue)) if red > 0 => (blue as f32) < red as f32 * WARM_RAMP_RATIO,
+        _ => false,
+    }
+}
+
+/// Gamma ramp of the screen's first CRTC is warm, false if it can't be read
+fn gamma_is_warm(conn: &RustConnection, screen: &Screen) -> bool {
+    let ramp = || -> anyhow::Result<bool> {
+        let resources = conn.randr_get_screen_resources_current(screen.root)?.reply()?;
+        let Some(&crtc) = resources.crtcs.first() else {
+            return Ok(false);
+        };
+        let gamma = conn.randr_get_crtc_gamma(crtc)?.reply()?;
+        Ok(ramp_is_warm(&gamma.red, &gamma.blue))
+    };
+    ramp()
+        .inspect_err(|e| debug!(error = %e, "Failed to read gamma ramp for night mode"))
+        .unwrap_or(false)
+}
+
+/// Periodic check of the night mode schedule
+#[derive(Debug, Default)]
+pub struct NightWatch {
+    settings: Option<NightModeSettings>,
+    /// When the next check is due
+    next: Option<Instant>,
+    active: bool,
+}
+
+impl NightWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Follow changed settings; a change is checked right away
+    pub fn set_settings(&mut self, settings: NightModeSettings) {
+        if self.settings != Some(settings) {
+            self.settings = Some(settings);
+            self.next = None;
+        }
+    }
+
+    /// When the main loop has to wake up for the next check (None while disabled)
+    pub fn deadline(&self) -> Option<Instant> {
+        let enabled = self.settings.is_some_and(|settings| settings.enabled) || self.active;
+        enabled.then(|| self.next.unwrap_or_else(Instant::now))
+    }
+
+    /// Settings to tint thumbnails with, None while night mode is off
+    /// Rechecks the clock / gamma ramp when due
+    pub fn update(&mut self, conn: &RustConnection, screen: &Screen, now: Instant) -> Option<NightModeSettings> {
+        if self.deadline().is_some_and(|deadline| deadline <= now) {
+            self.next = Some(now + Duration::from_secs(CHECK_INTERVAL_SECS));
+            let active = match self.settings {
+                Some(settings) if settings.enabled && settings.follow_gamma => gamma_is_warm(conn, screen),
+                Some(settings) if settings.enabled => {
+                    let hour = chrono::Local::now().hour() as u8;
+                    in_night_hours(hour, settings.start_hour, settings.end_hour)
+                }
+                _ => false,
+            };
+            if active != self.active {
+                info!(active = active, "Night mode changed");
+                self.active = active;
+            }
+        }
+        self.settings.filter(|_| self.active)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_night_hours_wraps_midnight() {
+        assert!(in_night_hours(23, 22, 6));
+        assert!(in_night_hours(2, 22, 6));
+        assert!(!in_night_hours(6, 22, 6));
+        assert!(!in_night_hours(12, 22, 6));
+        assert!(in_night_hours(1, 0, 5));
+        assert!(!in_night_hours(5, 0, 5));
+    }
+
+    #[test]
+    fn test_ramp_is_warm() {
+        assert!(ramp_is_warm(&[0, 65535], &[0, 40000]));
+        assert!(!ramp_is_warm(&[0, 65535], &[0, 65535]));
+        assert!(!ramp_is_warm(&[], &[]));
+    }
+}
@@ -0,0 +1,128 @@
+//! Thumbnail opacity drawn with XRender (opacity_mode)
+//!
+//! _NET_WM_WINDOW_OPACITY is only honoured by a compositing manager. Without
+//! one the thumbnail is blended in the render pipeline instead: after the
+//! client image is scaled in, the desktop wallpaper (_XROOTPMAP_ID, as set by
+//! feh, nitrogen and most desktops) is composited over it at the inverse of
+//! the opacity, so the thumbnail looks see-through over the wallpaper. Other
+//! windows behind it can't be shown this way; when there is no wallpaper, or
+//! the thumbnail sits inside the panel, a solid colour stands in for it.
+
+use anyhow::{Context, Result};
+use x11rb::connection::Connection;
+use x11rb::protocol::render::{Color, ConnectionExt as RenderExt, CreatePictureAux, PictOp, Picture};
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt, Screen};
+use x11rb::rust_connection::RustConnection;
+use tracing::debug;
+
+use crate::config::profile::OpacityMode;
+use crate::constants::opacity_blend;
+use crate::types::Position;
+use crate::x11_utils::{compositor_running, get_pictformat, AppContext};
+
+use super::snapping::Rect;
+
+#[derive(Debug)]
+pub struct OpacityBlend {
+    /// Solid alpha of how much background shows through
+    mask: Picture,
+    /// Wallpaper of the thumbnail's screen, None if the desktop doesn't publish one
+    wallpaper: Option<Picture>,
+    /// Stand-in background without a wallpaper
+    fill: Picture,
+}
+
+impl OpacityBlend {
+    /// Set up blending for a new thumbnail, None when the compositor handles opacity
+    /// (or the thumbnail is fully opaque anyway)
+    pub fn new(ctx: &AppContext) -> Result<Option<Self>> {
+        let blend = match ctx.config.opacity_mode {
+            OpacityMode::Compositor => false,
+            OpacityMode::Render => true,
+            OpacityMode::Auto => !compositor_running(ctx.conn, ctx.screen),
+        };
+        let see_through = 0xFF - (ctx.config.opacity >> 24) as u16;
+        if !blend || see_through == 0 {
+            return Ok(None);
+        }
+
+        let mask = ctx.conn.generate_id()
+            .context("Failed to generate ID for opacity mask")?;
+        ctx.conn.render_create_solid_fill(mask, Color { red: 0, green: 0, blue: 0, alpha: see_through * 0x101 })
+            .context("Failed to create opacity mask")?;
+        let fill = ctx.conn.generate_id()
+            .context("Failed to generate ID for opacity background")?;
+        let channel = |shift: u32| ((opacity_blend::BACKGROUND >> shift) & 0xFF) as u16 * 0x101;
+        let background = Color { red: channel(16), green: channel(8), blue: channel(0), alpha: 0xFFFF };
+        ctx.conn.render_create_solid_fill(fill, background)
+            .context("Failed to create opacity background")?;
+        let wallpaper = wallpaper_picture(ctx.conn, ctx.screen)
+            .inspect_err(|e| debug!(error = ?e, "No usable wallpaper, blending against a solid colour"))
+            .ok()
+            .flatten();
+
+        Ok(Some(Self { mask, wallpaper, fill }))
+    }
+
+    /// Blend the background into `region` of `dst`
+    /// `origin` is the thumbnail's position on the root window, None if it isn't a top-level window
+    pub fn apply(&self, conn: &RustConnection, dst: Picture, region: Rect, origin: Option<Position>) -> Result<()> {
+        let (background, x, y) = match (self.wallpaper, origin) {
+            (Some(wallpaper), Some(origin)) => (wallpaper, origin.x + region.x, origin.y + region.y),
+            _ => (self.fill, 0, 0),
+        };
+        conn.render_composite(
+            PictOp::OVER,
+            background,
+            self.mask,
+            dst,
+            x,
+            y,
+            0,
+            0,
+            region.x,
+            region.y,
+            region.width,
+            region.height,
+        )
+        .context("Failed to blend thumbnail background")?;
+        Ok(())
+    }
+
+    pub fn free(&self, conn: &RustConnection) -> Result<()> {
+        for picture in [Some(self.mask), Some(self.fill), self.wallpaper].into_iter().flatten() {
+            conn.render_free_picture(picture)
+                .context("Failed to free opacity blend picture")?;
+        }
+        Ok(())
+    }
+}
+
+/// Picture of the root window's wallpaper pixmap (_XROOTPMAP_ID)
+fn wallpaper_picture(conn: &RustConnection, screen: &Screen) -> Result<Option<Picture>> {
+    let atom = conn.intern_atom(false, opacity_blend::WALLPAPER_ATOM.as_bytes())
+        .context("Failed to intern _XROOTPMAP_ID atom")?
+        .reply()
+        .context("Failed to get reply for _XROOTPMAP_ID atom")?
+        .atom;
+    let pixmap = conn.get_property(false, screen.root, atom, AtomEnum::PIXMAP, 0, 1)
+        .context("Failed to query _XROOTPMAP_ID")?
+        .reply()
+        .context("Failed to get _XROOTPMAP_ID reply")?
+        .value32()
+        .and_then(|mut values| values.next());
+    let Some(pixmap) = pixmap.filter(|pixmap| *pixmap != 0) else {
+        return Ok(None);
+    };
+    let picture = conn.generate_id()
+        .context("Failed to generate ID for wallpaper picture")?;
+    conn.render_create_picture(
+        picture,
+        pixmap,
+        get_pictformat(conn, screen.root_depth, false)
+            .context("Failed to get picture format for wallpaper")?,
+        &CreatePictureAux::new(),
+    )
+    .context("Failed to create wallpaper picture")?;
+    Ok(Some(picture))
+}
@@ -0,0 +1,95 @@
+//! Switch OSD (switch_osd, switch_osd_ms)
+//!
+//! After a hotkey switches clients, the target character's name shows in the
+//! middle of the screen for a moment, in a larger font than the labels, so it's
+//! clear which client just got focus. Another switch replaces it.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use tracing::{debug, warn};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::Screen;
+use x11rb::rust_connection::RustConnection;
+
+use crate::constants::osd;
+use crate::preview::font::FontRenderer;
+
+use super::tooltip::{Placement, Tooltip};
+
+/// Font size of the OSD for a given label size
+fn osd_font_size(text_size: u16) -> f32 {
+    (text_size as f32 * osd::TEXT_SCALE).max(osd::MIN_TEXT_SIZE)
+}
+
+#[derive(Debug)]
+struct Shown {
+    tooltip: Tooltip,
+    deadline: Instant,
+}
+
+#[derive(Debug)]
+pub struct Osd {
+    font_renderer: FontRenderer,
+    shown: Option<Shown>,
+}
+
+impl Osd {
+    /// Loads the label font at OSD size (the system font if the family doesn't load)
+    pub fn new(conn: &RustConnection, font_family: &str, text_size: u16) -> Result<Self> {
+        let size = osd_font_size(text_size);
+        let font_renderer = if font_family.is_empty() {
+            FontRenderer::from_system_font(conn, size)
+        } else {
+            FontRenderer::from_font_name(font_family, size)
+                .or_else(|e| {
+                    warn!(font = %font_family, error = ?e, "Failed to load OSD font, falling back to system default");
+                    FontRenderer::from_system_font(conn, size)
+                })
+        }
+        .context(format!("Failed to initialize OSD font with size {}", size))?;
+        Ok(Self { font_renderer, shown: None })
+    }
+
+    /// When the OSD on screen should go away
+    pub fn deadline(&self) -> Option<Instant> {
+        self.shown.as_ref().map(|shown| shown.deadline)
+    }
+
+    /// Show `character` for `duration`, replacing the OSD already on screen
+    pub fn show(&mut self, conn: &RustConnection, screen: &Screen, character: &str, duration: Duration) -> Result<()> {
+        self.close(conn);
+        let tooltip = Tooltip::show(conn, screen, &self.font_renderer, &[character.to_string()], Placement::Centered)
+            .context(format!("Failed to show switch OSD for '{}'", character))?;
+        self.shown = Some(Shown { tooltip, deadline: Instant::now() + duration });
+        Ok(())
+    }
+
+    /// Close the OSD once its time is up
+    pub fn expire(&mut self, conn: &RustConnection, now: Instant) {
+        if self.deadline().is_some_and(|deadline| deadline <= now) {
+            debug!("Switch OSD timed out");
+            self.close(conn);
+        }
+    }
+
+    fn close(&mut self, conn: &RustConnection) {
+        let Some(shown) = self.shown.take() else {
+            return;
+        };
+        let _ = shown.tooltip.close(conn)
+            .inspect_err(|e| warn!(error = ?e, "Failed to close switch OSD"));
+        let _ = conn.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osd_font_size_scales_label_size() {
+        assert_eq!(osd_font_size(20), 20.0 * osd::TEXT_SCALE);
+        assert_eq!(osd_font_size(4), osd::MIN_TEXT_SIZE);
+    }
+}
@@ -0,0 +1,359 @@
+//! Panel mode: all previews inside one top-level window
+//!
+//! Instead of one override-redirect window per thumbnail, the thumbnail
+//! windows become children of a single regular window that the WM manages
+//! (tiled, moved and stacked as one). The panel lays its children out in cycle
+//! order and resizes itself to fit; it is unmapped while no preview is shown
+//! (e.g. hide_when_no_focus). Thumbnails keep their own saved positions and
+//! sizes for when panel mode is turned off again.
+//!
+//! Layouts: a grid wherever the WM puts the panel, or a single row along the
+//! bottom / column along the right screen edge that shrinks previews so the
+//! strip fits the screen. Each layout has its own spacing and size limit.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use tracing::{debug, info};
+use x11rb::connection::Connection;
+use x11rb::properties::WmHints;
+use x11rb::protocol::xproto::*;
+use x11rb::wrapper::ConnectionExt as WrapperExt;
+
+use crate::config::profile::{PanelLayout, PanelLayoutSettings, PanelSettings, ScreenMargins};
+use crate::constants::panel;
+use crate::types::{Dimensions, Position};
+use crate::x11_utils::AppContext;
+
+use super::snapping::{self, Rect};
+use super::thumbnail::Thumbnail;
+
+/// Largest preview the layout allows for `count` previews in `area` (None = no limit)
+/// Rows and columns split the screen length between their previews
+pub fn layout_cap(layout: PanelLayout, settings: &PanelLayoutSettings, count: usize, area: Rect) -> Option<Dimensions> {
+    let limit = |max: u16| if max == 0 { u16::MAX } else { max };
+    let mut cap = Dimensions::new(limit(settings.max_width), limit(settings.max_height));
+    let share = |length: u16| {
+        let count = count.max(1) as u32;
+        let gaps = settings.spacing as u32 * (count + 1);
+        ((length as u32).saturating_sub(gaps) / count).clamp(1, u16::MAX as u32) as u16
+    };
+    match layout {
+        PanelLayout::Grid => {}
+        PanelLayout::Row => cap.width = cap.width.min(share(area.width)),
+        PanelLayout::Column => cap.height = cap.height.min(share(area.height)),
+    }
+    (cap != Dimensions::new(u16::MAX, u16::MAX)).then_some(cap)
+}
+
+/// Where a panel of `size` goes for the layout (None = wherever the WM puts it)
+pub fn anchor(layout: PanelLayout, size: Dimensions, area: Rect) -> Option<Position> {
+    match layout {
+        PanelLayout::Grid => None,
+        PanelLayout::Row => Some(Position::new(area.left(), area.bottom().saturating_sub(size.height as i16))),
+        PanelLayout::Column => Some(Position::new(area.right().saturating_sub(size.width as i16), area.top())),
+    }
+}
+
+/// Grid positions for thumbnails of the given sizes, and the panel size that fits them
+/// Each column is as wide as its widest thumbnail, each row as tall as its tallest
+pub fn grid_layout(sizes: &[Dimensions], columns: u16, spacing: u16) -> (Vec<Position>, Dimensions) {
+    if sizes.is_empty() {
+        return (Vec::new(), Dimensions::new(0, 0));
+    }
+    let columns = match columns {
+        0 => (sizes.len() as f64).sqrt().ceil() as usize,
+        n => n as usize,
+    }
+    .clamp(1, sizes.len());
+    let rows = sizes.len().div_ceil(columns);
+
+    let mut widths = vec![0u32; columns];
+    let mut heights = vec![0u32; rows];
+    for (i, size) in sizes.iter().enumerate() {
+        widths[i % columns] = widths[i % columns].max(size.width as u32);
+        heights[i / columns] = heights[i / columns].max(size.height as u32);
+    }
+
+    // Leading edge of each column/row, and the total extent including the outer gap
+    let offsets = |extents: &[u32]| -> (Vec<u32>, u32) {
+        let mut next = spacing as u32;
+        let starts = extents.iter().map(|extent| {
+            let start = next;
+            next += extent + spacing as u32;
+            start
+        }).collect();
+        (starts, next)
+    };
+    let (xs, width) = offsets(&widths);
+    let (ys, height) = offsets(&heights);
+
+    let clamp_pos = |v: u32| v.min(i16::MAX as u32) as i16;
+    let positions = (0..sizes.len())
+        .map(|i| Position::new(clamp_pos(xs[i % columns]), clamp_pos(ys[i / columns])))
+        .collect();
+    (positions, Dimensions::new(width.min(u16::MAX as u32) as u16, height.min(u16::MAX as u32) as u16))
+}
+
+#[derive(Debug)]
+pub struct Panel {
+    pub window: Window,
+    /// Root of the screen the panel lives on (thumbnails elsewhere stay free-floating)
+    root: Window,
+    /// Where each child was last placed, so unchanged layouts send no requests
+    placed: HashMap<Window, Position>,
+    size: Dimensions,
+    /// Last position the panel was moved to (row/column layouts)
+    anchored: Option<Position>,
+    mapped: bool,
+}
+
+impl Panel {
+    /// Create the (still unmapped) panel window on the context's screen
+    pub fn create(ctx: &AppContext) -> Result<Self> {
+        let window = ctx.conn.generate_id()
+            .context("Failed to generate X11 window ID for panel")?;
+        ctx.conn.create_window(
+            ctx.screen.root_depth,
+            window,
+            ctx.screen.root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            ctx.screen.root_visual,
+            &CreateWindowAux::new().background_pixel(panel::BACKGROUND),
+        )
+        .context("Failed to create panel window")?;
+
+        ctx.conn.change_property8(PropMode::REPLACE, window, AtomEnum::WM_NAME, AtomEnum::STRING, panel::TITLE)
+            .context("Failed to set panel title")?;
+        ctx.conn.change_property8(PropMode::REPLACE, window, AtomEnum::WM_CLASS, AtomEnum::STRING, b"eve-l-preview\0eve-l-preview\0")
+            .context("Failed to set panel WM_CLASS")?;
+        ctx.conn.change_property32(PropMode::REPLACE, window, ctx.atoms.net_wm_pid, AtomEnum::CARDINAL, &[std::process::id()])
+            .context("Failed to set panel _NET_WM_PID")?;
+
+        let opacity_atom = ctx.conn.intern_atom(false, b"_NET_WM_WINDOW_OPACITY")
+            .context("Failed to intern _NET_WM_WINDOW_OPACITY atom")?
+            .reply()
+            .context("Failed to get reply for _NET_WM_WINDOW_OPACITY atom")?
+            .atom;
+        ctx.conn.change_property32(PropMode::REPLACE, window, opacity_atom, AtomEnum::CARDINAL, &[ctx.config.opacity])
+            .context("Failed to set panel opacity")?;
+
+        // Clicking a preview must not pull focus away from EVE (hide_when_no_focus)
+        let mut hints = WmHints::new();
+        hints.input = Some(false);
+        hints.set(ctx.conn, window)
+            .context("Failed to set panel WM_HINTS")?;
+
+        // Closing the panel from the WM is ignored instead of killing the daemon's connection
+        let wm_protocols = ctx.conn.intern_atom(false, b"WM_PROTOCOLS")
+            .context("Failed to intern WM_PROTOCOLS atom")?
+            .reply()
+            .context("Failed to get reply for WM_PROTOCOLS atom")?
+            .atom;
+        let wm_delete_window = ctx.conn.intern_atom(false, b"WM_DELETE_WINDOW")
+            .context("Failed to intern WM_DELETE_WINDOW atom")?
+            .reply()
+            .context("Failed to get reply for WM_DELETE_WINDOW atom")?
+            .atom;
+        ctx.conn.change_property32(PropMode::REPLACE, window, wm_protocols, AtomEnum::ATOM, &[wm_delete_window])
+            .context("Failed to set panel WM_PROTOCOLS")?;
+
+        info!(window = window, "Created preview panel");
+        Ok(Self {
+            window,
+            root: ctx.screen.root,
+            placed: HashMap::new(),
+            size: Dimensions::new(0, 0),
+            anchored: None,
+            mapped: false,
+        })
+    }
+
+    /// Adopt new thumbnails, lay out the shown ones and fit the panel around them
+    pub fn arrange(
+        &mut self,
+        ctx: &AppContext,
+        eves: &mut HashMap<Window, Thumbnail>,
+        order: &[String],
+        settings: &PanelSettings,
+        area: Rect,
+    ) -> Result<()> {
+        for thumbnail in eves.values_mut().filter(|t| t.panel.is_none() && !t.panel_detached && t.screen.root == self.root) {
+            thumbnail.attach_to_panel(self.window)
+                .context(format!("Failed to move '{}' into the panel", thumbnail.character_name))?;
+            self.placed.remove(&thumbnail.window);
+        }
+        self.placed.retain(|window, _| eves.values().any(|t| t.window == *window));
+
+        let rank = |t: &Thumbnail| order.iter().position(|name| *name == t.character_name).unwrap_or(usize::MAX);
+        let mut shown: Vec<&Thumbnail> = eves.values()
+            .filter(|t| t.panel == Some(self.window) && t.is_displayed())
+            .collect();
+        shown.sort_by(|a, b| rank(a).cmp(&rank(b))
+            .then_with(|| a.character_name.cmp(&b.character_name))
+            .then(a.window.cmp(&b.window)));
+        let shown: Vec<Window> = shown.iter().map(|t| t.src).collect();
+
+        // Shrink previews to the layout's limit first, their drawn sizes drive the layout
+        let layout = settings.active_layout();
+        let cap = layout_cap(settings.layout, layout, shown.len(), area);
+        for src in &shown {
+            if let Some(thumbnail) = eves.get_mut(src) {
+                thumbnail.set_layout_cap(cap)
+                    .context(format!("Failed to fit '{}' to the panel layout", thumbnail.character_name))?;
+            }
+        }
+
+        let shown: Vec<&Thumbnail> = shown.iter().filter_map(|src| eves.get(src)).collect();
+        let columns = match settings.layout {
+            PanelLayout::Grid => settings.columns,
+            PanelLayout::Row => shown.len().min(u16::MAX as usize) as u16,
+            PanelLayout::Column => 1,
+        };
+        let sizes: Vec<Dimensions> = shown.iter().map(|t| t.dimensions).collect();
+        let (positions, size) = grid_layout(&sizes, columns, layout.spacing);
+
+        let mut changed = false;
+        for (thumbnail, position) in shown.iter().zip(positions) {
+            if self.placed.get(&thumbnail.window) != Some(&position) {
+                thumbnail.place_in_panel(position)?;
+                self.placed.insert(thumbnail.window, position);
+                changed = true;
+            }
+        }
+
+        if shown.is_empty() {
+            if self.mapped {
+                ctx.conn.unmap_window(self.window)
+                    .context("Failed to unmap empty panel")?;
+                self.mapped = false;
+                changed = true;
+            }
+        } else {
+            let position = anchor(settings.layout, size, area);
+            if size != self.size || position != self.anchored {
+                let mut aux = ConfigureWindowAux::new().width(size.width as u32).height(size.height as u32);
+                if let Some(Position { x, y }) = position {
+                    aux = aux.x(x as i32).y(y as i32);
+                }
+                ctx.conn.configure_window(self.window, &aux)
+                    .context("Failed to resize panel")?;
+                self.size = size;
+                self.anchored = position;
+                changed = true;
+            }
+            if !self.mapped {
+                ctx.conn.map_window(self.window)
+                    .context("Failed to map panel")?;
+                self.mapped = true;
+                changed = true;
+            }
+        }
+
+        if changed {
+            debug!(shown = shown.len(), layout = ?settings.layout, width = size.width, height = size.height, "Panel layout updated");
+            ctx.conn.flush()
+                .context("Failed to flush X11 connection after panel layout")?;
+        }
+        Ok(())
+    }
+
+    /// Turn every child back into a free-floating thumbnail and destroy the panel
+    pub fn dissolve(self, ctx: &AppContext, eves: &mut HashMap<Window, Thumbnail>) -> Result<()> {
+        for thumbnail in eves.values_mut().filter(|t| t.panel == Some(self.window)) {
+            thumbnail.detach_from_panel()
+                .context(format!("Failed to move '{}' out of the panel", thumbnail.character_name))?;
+        }
+        ctx.conn.destroy_window(self.window)
+            .context("Failed to destroy panel window")?;
+        ctx.conn.flush()
+            .context("Failed to flush X11 connection after removing panel")?;
+        info!(window = self.window, "Removed preview panel");
+        Ok(())
+    }
+}
+
+/// Create, update or remove the panel to match the settings (once per main loop pass)
+pub fn sync(
+    ctx: &AppContext,
+    panel: &mut Option<Panel>,
+    settings: PanelSettings,
+    margins: &ScreenMargins,
+    eves: &mut HashMap<Window, Thumbnail>,
+    order: &[String],
+) -> Result<()> {
+    match (settings.enabled, panel.take()) {
+        (true, None) => *panel = Some(Panel::create(ctx)?),
+        (false, Some(existing)) => return existing.dissolve(ctx, eves),
+        (_, existing) => *panel = existing,
+    }
+    match panel {
+        Some(panel) => {
+            let area = snapping::safe_area(ctx.screen.width_in_pixels, ctx.screen.height_in_pixels, margins);
+            panel.arrange(ctx, eves, order, &settings, area)
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square_grid_by_default() {
+        let sizes = vec![Dimensions::new(100, 50); 4];
+        let (positions, size) = grid_layout(&sizes, 0, 4);
+        assert_eq!(positions, vec![
+            Position::new(4, 4),
+            Position::new(108, 4),
+            Position::new(4, 58),
+            Position::new(108, 58),
+        ]);
+        assert_eq!(size, Dimensions::new(212, 112));
+    }
+
+    #[test]
+    fn test_columns_fit_widest_thumbnail() {
+        let sizes = [Dimensions::new(100, 50), Dimensions::new(200, 80), Dimensions::new(150, 60)];
+        let (positions, size) = grid_layout(&sizes, 2, 0);
+        assert_eq!(positions, vec![Position::new(0, 0), Position::new(150, 0), Position::new(0, 80)]);
+        assert_eq!(size, Dimensions::new(350, 140));
+    }
+
+    #[test]
+    fn test_row_splits_screen_width() {
+        let area = Rect { x: 0, y: 0, width: 1000, height: 800 };
+        let settings = PanelLayoutSettings { spacing: 10, max_width: 0, max_height: 150 };
+        // 4 previews: (1000 - 5 gaps of 10) / 4 = 237 wide at most
+        assert_eq!(layout_cap(PanelLayout::Row, &settings, 4, area), Some(Dimensions::new(237, 150)));
+        assert_eq!(layout_cap(PanelLayout::Column, &settings, 4, area), Some(Dimensions::new(u16::MAX, 150)));
+    }
+
+    #[test]
+    fn test_grid_without_limit_has_no_cap() {
+        let area = Rect { x: 0, y: 0, width: 1000, height: 800 };
+        assert_eq!(layout_cap(PanelLayout::Grid, &PanelLayoutSettings::default(), 9, area), None);
+    }
+
+    #[test]
+    fn test_anchor_follows_screen_edges() {
+        let area = Rect { x: 10, y: 20, width: 1000, height: 800 };
+        let size = Dimensions::new(300, 100);
+        assert_eq!(anchor(PanelLayout::Row, size, area), Some(Position::new(10, 720)));
+        assert_eq!(anchor(PanelLayout::Column, size, area), Some(Position::new(710, 20)));
+        assert_eq!(anchor(PanelLayout::Grid, size, area), None);
+    }
+
+    #[test]
+    fn test_empty_and_oversized_column_count() {
+        assert_eq!(grid_layout(&[], 3, 4), (Vec::new(), Dimensions::new(0, 0)));
+        let (positions, _) = grid_layout(&[Dimensions::new(10, 10); 2], 8, 0);
+        assert_eq!(positions, vec![Position::new(0, 0), Position::new(10, 0)]);
+    }
+}
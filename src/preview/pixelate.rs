@@ -0,0 +1,102 @@
+//! Pixelated previews (privacy mode)
+//!
+//! While privacy mode is on, the client isn't scaled straight into the
+//! thumbnail. It is first shrunk into a small buffer with one pixel per block,
+//! then scaled back up with the nearest filter, so chat and local are an
+//! unreadable grid of blocks. Both passes run on the X server.
+
+use anyhow::{Context, Result};
+use x11rb::connection::Connection;
+use x11rb::protocol::render::{ConnectionExt as RenderExt, CreatePictureAux, PictOp, Picture, Transform};
+use x11rb::protocol::xproto::{ConnectionExt, Pixmap, Screen};
+use x11rb::rust_connection::RustConnection;
+
+use crate::constants::privacy;
+use crate::types::Dimensions;
+use crate::x11_utils::{get_pictformat, to_fixed};
+
+/// Size of the block buffer for a `target`-sized thumbnail (at least 1x1)
+pub fn block_dimensions(target: Dimensions, block: u16) -> Dimensions {
+    let block = block.max(1);
+    Dimensions::new(target.width.div_ceil(block).max(1), target.height.div_ceil(block).max(1))
+}
+
+#[derive(Debug)]
+pub struct Pixelate {
+    pixmap: Pixmap,
+    picture: Picture,
+    size: Dimensions,
+}
+
+impl Pixelate {
+    /// Block buffer for a thumbnail of `target` size
+    pub fn new(conn: &RustConnection, screen: &Screen, target: Dimensions) -> Result<Self> {
+        let size = block_dimensions(target, privacy::BLOCK_SIZE);
+        let pixmap = conn.generate_id()
+            .context("Failed to generate ID for pixelation buffer")?;
+        conn.create_pixmap(screen.root_depth, pixmap, screen.root, size.width, size.height)
+            .context("Failed to create pixelation buffer")?;
+        let picture = conn.generate_id()
+            .context("Failed to generate ID for pixelation picture")?;
+        conn.render_create_picture(
+            picture,
+            pixmap,
+            get_pictformat(conn, screen.root_depth, false)
+                .context("Failed to get picture format for pixelation buffer")?,
+            &CreatePictureAux::new(),
+        )
+        .context("Failed to create pixelation picture")?;
+        conn.render_set_picture_transform(picture, scale(size, target))
+            .context("Failed to set pixelation transform")?;
+        Ok(Self { pixmap, picture, size })
+    }
+
+    /// Draw the `source`-sized client picture `src` into `dst` as blocks
+    /// `src` is left with the transform of the first pass
+    pub fn draw(&self, conn: &RustConnection, src: Picture, dst: Picture, source: Dimensions, target: Dimensions) -> Result<()> {
+        conn.render_set_picture_transform(src, scale(source, self.size))
+            .context("Failed to set transform for pixelation")?;
+        conn.render_composite(PictOp::SRC, src, 0u32, self.picture, 0, 0, 0, 0, 0, 0, self.size.width, self.size.height)
+            .context("Failed to shrink client into pixelation buffer")?;
+        conn.render_composite(PictOp::SRC, self.picture, 0u32, dst, 0, 0, 0, 0, 0, 0, target.width, target.height)
+            .context("Failed to draw pixelated thumbnail")?;
+        Ok(())
+    }
+
+    pub fn free(&self, conn: &RustConnection) -> Result<()> {
+        conn.render_free_picture(self.picture)
+            .context("Failed to free pixelation picture")?;
+        conn.free_pixmap(self.pixmap)
+            .context("Failed to free pixelation buffer")?;
+        Ok(())
+    }
+}
+
+/// Transform sampling a `from`-sized picture when drawing it at `to` size
+fn scale(from: Dimensions, to: Dimensions) -> Transform {
+    Transform {
+        matrix11: to_fixed(from.width as f32 / to.width as f32),
+        matrix22: to_fixed(from.height as f32 / to.height as f32),
+        matrix33: to_fixed(1.0),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_dimensions_round_up() {
+        let size = block_dimensions(Dimensions::new(240, 135), 12);
+        assert_eq!((size.width, size.height), (20, 12));
+    }
+
+    #[test]
+    fn test_block_dimensions_never_empty() {
+        let size = block_dimensions(Dimensions::new(5, 5), 12);
+        assert_eq!((size.width, size.height), (1, 1));
+        let size = block_dimensions(Dimensions::new(10, 10), 0);
+        assert_eq!((size.width, size.height), (10, 10));
+    }
+}
@@ -0,0 +1,147 @@
+//! CPU and memory readout for EVE clients (show_process_stats)
+//!
+//! Every few seconds the daemon reads /proc/<pid>/stat (CPU time) and
+//! /proc/<pid>/status (resident memory) of each client's wine process. CPU
+//! usage is the CPU time used since the previous sample, so a client shows
+//! its memory first and its CPU usage from the second sample on. 100% is one
+//! full core, like top.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tracing::debug;
+
+use crate::constants::process_stats::{CLOCK_TICKS, INTERVAL_SECS, STATUS_FORMAT, STAT_FORMAT};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcessStats {
+    /// Percent of one core since the previous sample (None on the first sample)
+    pub cpu_percent: Option<f32>,
+    /// Resident memory in KiB
+    pub rss_kib: u64,
+}
+
+impl ProcessStats {
+    /// Short text for the thumbnail, e.g. "CPU 37%  RAM 1.8G"
+    pub fn label(&self) -> String {
+        let ram = if self.rss_kib >= 1024 * 1024 {
+            format!("{:.1}G", self.rss_kib as f64 / (1024.0 * 1024.0))
+        } else {
+            format!("{}M", self.rss_kib / 1024)
+        };
+        match self.cpu_percent {
+            Some(cpu) => format!("CPU {:.0}%  RAM {}", cpu, ram),
+            None => format!("RAM {}", ram),
+        }
+    }
+}
+
+/// utime + stime (clock ticks) from the contents of /proc/<pid>/stat
+/// The command name may contain spaces and parentheses, so fields are counted after the last ')'
+pub fn parse_cpu_ticks(stat: &str) -> Option<u64> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    // rest starts at field 3 (state); utime and stime are fields 14 and 15
+    let mut fields = rest.split_whitespace().skip(11);
+    let utime: u64 = fields.next()?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// VmRSS in KiB from the contents of /proc/<pid>/status
+pub fn parse_rss_kib(status: &str) -> Option<u64> {
+    status.lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.split_whitespace().next())
+        .and_then(|kib| kib.parse().ok())
+}
+
+/// Periodic /proc sampling of the clients' processes
+#[derive(Debug, Default)]
+pub struct ProcessSampler {
+    enabled: bool,
+    /// When the next sample is due
+    next: Option<Instant>,
+    /// CPU ticks per PID at the previous sample
+    last: HashMap<u32, (u64, Instant)>,
+}
+
+impl ProcessSampler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Turn sampling on or off; returns true if that changed anything
+    pub fn set_enabled(&mut self, enabled: bool) -> bool {
+        if self.enabled == enabled {
+            return false;
+        }
+        self.enabled = enabled;
+        self.next = None;
+        self.last.clear();
+        true
+    }
+
+    /// When the main loop has to wake up for the next sample (None while disabled)
+    pub fn deadline(&self) -> Option<Instant> {
+        self.enabled.then(|| self.next.unwrap_or_else(Instant::now))
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.deadline().is_some_and(|deadline| deadline <= now)
+    }
+
+    /// Read /proc for each PID; processes that went away are left out
+    pub fn sample(&mut self, pids: impl IntoIterator<Item = u32>, now: Instant) -> HashMap<u32, ProcessStats> {
+        self.next = Some(now + Duration::from_secs(INTERVAL_SECS));
+        let mut stats = HashMap::new();
+        let mut last = HashMap::new();
+        for pid in pids {
+            let read = |format: &str| std::fs::read_to_string(format.replace("{}", &pid.to_string()))
+                .inspect_err(|e| debug!(pid = pid, error = %e, "Failed to read client process stats"))
+                .ok();
+            let (Some(ticks), Some(rss_kib)) = (
+                read(STAT_FORMAT).as_deref().and_then(parse_cpu_ticks),
+                read(STATUS_FORMAT).as_deref().and_then(parse_rss_kib),
+            ) else {
+                continue;
+            };
+            let cpu_percent = self.last.get(&pid).and_then(|(prev_ticks, prev_time)| {
+                let elapsed = now.duration_since(*prev_time).as_secs_f32();
+                (elapsed > 0.0).then(|| {
+                    ticks.saturating_sub(*prev_ticks) as f32 / CLOCK_TICKS as f32 / elapsed * 100.0
+                })
+            });
+            last.insert(pid, (ticks, now));
+            stats.insert(pid, ProcessStats { cpu_percent, rss_kib });
+        }
+        self.last = last;
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_ticks_with_spaces_in_name() {
+        let stat = "4242 (EVE Online (x)) S 1 4242 4242 0 -1 4194560 1000 0 0 0 1500 250 0 0 20 0 60 0 100 2000000 50000";
+        assert_eq!(parse_cpu_ticks(stat), Some(1750));
+        assert_eq!(parse_cpu_ticks("garbage"), None);
+    }
+
+    #[test]
+    fn test_parse_rss() {
+        let status = "Name:\texefile.exe\nVmPeak:\t 4000000 kB\nVmRSS:\t 1887436 kB\nThreads:\t60\n";
+        assert_eq!(parse_rss_kib(status), Some(1887436));
+        assert_eq!(parse_rss_kib("Name:\tkthreadd\n"), None);
+    }
+
+    #[test]
+    fn test_label() {
+        let stats = ProcessStats { cpu_percent: Some(37.4), rss_kib: 1887436 };
+        assert_eq!(stats.label(), "CPU 37%  RAM 1.8G");
+        let stats = ProcessStats { cpu_percent: None, rss_kib: 512 * 1024 };
+        assert_eq!(stats.label(), "RAM 512M");
+    }
+}
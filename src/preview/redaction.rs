@@ -0,0 +1,173 @@
+//! Redacted client areas (per-character redactions)
+//!
+//! Areas are stored in client pixels, so they stay on the same part of the EVE
+//! window whatever size the thumbnail is. They are filled black after the
+//! client is scaled in; only the preview is affected, never the client. While
+//! the GUI has a character in edit mode, left-dragging on its thumbnail draws a
+//! new area and right-clicking one removes it.
+
+use anyhow::{Context, Result};
+use x11rb::connection::Connection;
+use x11rb::protocol::render::{Color, ConnectionExt as RenderExt, PictOp, Picture};
+use x11rb::rust_connection::RustConnection;
+
+use crate::constants::redaction;
+use crate::types::{Dimensions, Position, RedactRect};
+
+use super::drag_guides::Outline;
+use super::snapping::Rect;
+
+/// Thumbnail pixels covering a client area, rounded outwards so nothing of it shows
+pub fn to_thumbnail(rect: RedactRect, source: Dimensions, target: Dimensions) -> Option<Rect> {
+    if source.width == 0 || source.height == 0 {
+        return None;
+    }
+    let scale = |value: u16, from: u16, to: u16, round_up: bool| {
+        let scaled = value as u32 * to as u32;
+        let scaled = if round_up { scaled.div_ceil(from as u32) } else { scaled / from as u32 };
+        scaled.min(to as u32) as u16
+    };
+    let left = scale(rect.x, source.width, target.width, false);
+    let top = scale(rect.y, source.height, target.height, false);
+    let right = scale(rect.x.saturating_add(rect.width), source.width, target.width, true);
+    let bottom = scale(rect.y.saturating_add(rect.height), source.height, target.height, true);
+    (right > left && bottom > top).then(|| Rect {
+        x: left as i16,
+        y: top as i16,
+        width: right - left,
+        height: bottom - top,
+    })
+}
+
+/// Client area for a rectangle drawn between two thumbnail points (any corner order)
+/// None if the drawn rectangle is too small to be meant as an area
+pub fn from_thumbnail(a: Position, b: Position, source: Dimensions, target: Dimensions) -> Option<RedactRect> {
+    if target.width == 0 || target.height == 0 {
+        return None;
+    }
+    let clamp_x = |x: i16| x.clamp(0, target.width as i16) as u32;
+    let clamp_y = |y: i16| y.clamp(0, target.height as i16) as u32;
+    let (left, right) = (clamp_x(a.x.min(b.x)), clamp_x(a.x.max(b.x)));
+    let (top, bottom) = (clamp_y(a.y.min(b.y)), clamp_y(a.y.max(b.y)));
+    let min = redaction::MIN_DRAW_SIZE as u32;
+    if right - left < min || bottom - top < min {
+        return None;
+    }
+    let to_x = |x: u32| (x * source.width as u32 / target.width as u32) as u16;
+    let to_y = |y: u32| (y * source.height as u32 / target.height as u32) as u16;
+    Some(RedactRect {
+        x: to_x(left),
+        y: to_y(top),
+        width: to_x(right) - to_x(left),
+        height: to_y(bottom) - to_y(top),
+    })
+}
+
+/// Client pixel under a thumbnail point
+pub fn client_point(point: Position, source: Dimensions, target: Dimensions) -> Option<(u16, u16)> {
+    if point.x < 0 || point.y < 0 || target.width == 0 || target.height == 0 {
+        return None;
+    }
+    let x = point.x as u32 * source.width as u32 / target.width as u32;
+    let y = point.y as u32 * source.height as u32 / target.height as u32;
+    Some((x.min(u16::MAX as u32) as u16, y.min(u16::MAX as u32) as u16))
+}
+
+/// Area being drawn on a thumbnail in edit mode
+#[derive(Debug)]
+pub struct RedactDraw {
+    /// Thumbnail's top-left corner on the root window
+    pub origin: Position,
+    /// Where the drag started (root coordinates)
+    pub start: Position,
+    pub outline: Outline,
+}
+
+impl RedactDraw {
+    /// Rectangle between the start and `pointer` (root coordinates), for the outline
+    pub fn rect(&self, pointer: Position) -> Rect {
+        Rect {
+            x: self.start.x.min(pointer.x),
+            y: self.start.y.min(pointer.y),
+            width: self.start.x.abs_diff(pointer.x).max(1),
+            height: self.start.y.abs_diff(pointer.y).max(1),
+        }
+    }
+
+    /// Convert a root position to thumbnail coordinates
+    pub fn local(&self, root: Position) -> Position {
+        Position::new(root.x - self.origin.x, root.y - self.origin.y)
+    }
+}
+
+/// Black fill over a character's redacted areas
+#[derive(Debug)]
+pub struct Redactor {
+    rects: Vec<RedactRect>,
+    fill: Picture,
+}
+
+impl Redactor {
+    /// Fill for `rects`, None if there is nothing to redact
+    pub fn new(conn: &RustConnection, rects: &[RedactRect]) -> Result<Option<Self>> {
+        if rects.is_empty() {
+            return Ok(None);
+        }
+        let fill = conn.generate_id()
+            .context("Failed to generate ID for redaction fill")?;
+        conn.render_create_solid_fill(fill, Color { red: 0, green: 0, blue: 0, alpha: 0xFFFF })
+            .context("Failed to create redaction fill")?;
+        Ok(Some(Self { rects: rects.to_vec(), fill }))
+    }
+
+    pub fn rects(&self) -> &[RedactRect] {
+        &self.rects
+    }
+
+    /// Black out the redacted areas inside `region` of a `target`-sized thumbnail
+    pub fn apply(&self, conn: &RustConnection, dst: Picture, source: Dimensions, target: Dimensions, region: Rect) -> Result<()> {
+        for area in self.rects.iter().filter_map(|rect| to_thumbnail(*rect, source, target)) {
+            let Some(area) = area.intersection(&region) else {
+                continue;
+            };
+            conn.render_composite(PictOp::SRC, self.fill, 0u32, dst, 0, 0, 0, 0, area.x, area.y, area.width, area.height)
+                .context("Failed to black out redacted area")?;
+        }
+        Ok(())
+    }
+
+    pub fn free(&self, conn: &RustConnection) -> Result<()> {
+        conn.render_free_picture(self.fill)
+            .context("Failed to free redaction fill")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT: Dimensions = Dimensions { width: 1920, height: 1080 };
+    const THUMBNAIL: Dimensions = Dimensions { width: 240, height: 135 };
+
+    #[test]
+    fn test_to_thumbnail_rounds_outwards() {
+        let rect = RedactRect { x: 4, y: 4, width: 10, height: 10 };
+        let area = to_thumbnail(rect, CLIENT, THUMBNAIL).unwrap();
+        assert_eq!((area.x, area.y, area.width, area.height), (0, 0, 2, 2));
+
+        let outside = RedactRect { x: 1900, y: 0, width: 100, height: 100 };
+        let area = to_thumbnail(outside, CLIENT, THUMBNAIL).unwrap();
+        assert_eq!((area.x, area.width), (237, 3));
+    }
+
+    #[test]
+    fn test_from_thumbnail_any_corner_order() {
+        let rect = from_thumbnail(Position::new(120, 100), Position::new(0, 60), CLIENT, THUMBNAIL).unwrap();
+        assert_eq!(rect, RedactRect { x: 0, y: 480, width: 960, height: 320 });
+        // Dragged past the edge: clamped to the thumbnail
+        let rect = from_thumbnail(Position::new(200, 100), Position::new(300, 200), CLIENT, THUMBNAIL).unwrap();
+        assert_eq!(rect, RedactRect { x: 1600, y: 800, width: 320, height: 280 });
+        assert!(from_thumbnail(Position::new(10, 10), Position::new(12, 40), CLIENT, THUMBNAIL).is_none());
+    }
+}
@@ -2,7 +2,11 @@ use std::collections::HashMap;
 use tracing::info;
 use x11rb::protocol::xproto::Window;
 
-use crate::types::{CharacterSettings, Position};
+use crate::session_summary::SessionTracker;
+use crate::types::{CharacterSettings, Position, TitlePattern};
+
+use super::focus_history::FocusHistory;
+use super::window_rules::WindowRules;
 
 /// Runtime state for position tracking
 /// Window positions are session-only (not persisted to disk)
@@ -11,12 +15,24 @@ pub struct SessionState {
     /// Used for logged-out windows that show "EVE" without character name
     /// Window IDs are ephemeral and don't survive X11 server restarts
     pub window_positions: HashMap<Window, Position>,
+    /// Characters seen and focus switches, written as the session summary on shutdown
+    pub activity: SessionTracker,
+    /// Recently focused characters, for the focus last hotkey
+    pub focus_history: FocusHistory,
+    /// Window rules compiled from the current global settings
+    pub window_rules: WindowRules,
+    /// Client title pattern compiled from the current global settings
+    pub title_pattern: TitlePattern,
 }
 
 impl Default for SessionState {
     fn default() -> Self {
         Self {
             window_positions: HashMap::new(),
+            activity: SessionTracker::new(),
+            focus_history: FocusHistory::new(),
+            window_rules: WindowRules::default(),
+            title_pattern: TitlePattern::default(),
         }
     }
 }
@@ -89,6 +105,7 @@ mod tests {
     fn test_get_position_new_character_no_inherit() {
         let state = SessionState {
             window_positions: HashMap::from([(456, Position::new(300, 400))]),
+            ..SessionState::default()
         };
         let char_positions = HashMap::new();
         
@@ -101,6 +118,7 @@ mod tests {
     fn test_get_position_new_character_with_inherit() {
         let state = SessionState {
             window_positions: HashMap::from([(789, Position::new(500, 600))]),
+            ..SessionState::default()
         };
         let char_positions = HashMap::new();
         
@@ -113,6 +131,7 @@ mod tests {
     fn test_get_position_new_character_inherit_but_no_window_position() {
         let state = SessionState {
             window_positions: HashMap::new(),
+            ..SessionState::default()
         };
         let char_positions = HashMap::new();
         
@@ -125,6 +144,7 @@ mod tests {
     fn test_get_position_logged_out_window() {
         let state = SessionState {
             window_positions: HashMap::from([(111, Position::new(700, 800))]),
+            ..SessionState::default()
         };
         let char_positions = HashMap::new();
         
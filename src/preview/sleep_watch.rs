@@ -0,0 +1,73 @@
+//! Suspend/resume tracking (logind PrepareForSleep)
+//!
+//! After a laptop resumes, the damage objects and source pictures of some
+//! thumbnails can go stale and their previews freeze. The daemon listens for
+//! logind's PrepareForSleep signal on the system bus and rebinds every
+//! thumbnail (fresh damage tracking and source picture) once the machine is
+//! awake again. Without logind or a system bus nothing changes.
+
+use anyhow::{Context, Result};
+use std::future::poll_fn;
+use std::pin::Pin;
+use std::sync::mpsc::Sender;
+use std::thread;
+use tracing::{debug, info, warn};
+use zbus::export::futures_core::Stream;
+
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1",
+    gen_blocking = false
+)]
+trait LoginManager {
+    /// true right before suspend/hibernate, false once the system is back
+    #[zbus(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+}
+
+/// Forward every resume until the bus connection closes
+async fn listen(sender: Sender<()>) -> Result<()> {
+    let connection = zbus::Connection::system().await
+        .context("Failed to connect to the D-Bus system bus")?;
+    let proxy = LoginManagerProxy::new(&connection).await
+        .context("Failed to create logind proxy")?;
+    let mut signals = proxy.receive_prepare_for_sleep().await
+        .context("Failed to subscribe to logind PrepareForSleep signal")?;
+    debug!("Watching logind for suspend/resume");
+
+    while let Some(signal) = poll_fn(|cx| Pin::new(&mut signals).poll_next(cx)).await {
+        let start = match signal.args() {
+            Ok(args) => *args.start(),
+            Err(e) => {
+                warn!(error = %e, "Malformed logind PrepareForSleep signal");
+                continue;
+            }
+        };
+        if start {
+            info!("System going to sleep");
+            continue;
+        }
+        info!("System resumed from sleep");
+        sender.send(())
+            .context("Failed to send resume notification")?;
+    }
+    Ok(())
+}
+
+/// Watch for resume on a background thread, sending () on `sender` each time
+pub fn spawn(sender: Sender<()>) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                warn!(error = %e, "Failed to create suspend watch runtime, previews aren't rebound after resume");
+                return;
+            }
+        };
+        if let Err(e) = runtime.block_on(listen(sender)) {
+            // No logind (or no system bus): nothing to follow
+            warn!(error = %e, "Suspend/resume tracking unavailable, previews aren't rebound after resume");
+        }
+    })
+}
@@ -1,5 +1,6 @@
 use x11rb::protocol::xproto::Window;
 
+use crate::config::profile::ScreenMargins;
 use crate::types::Position;
 
 #[derive(Debug, Clone, Copy)]
@@ -28,6 +29,55 @@ impl Rect {
         // Use saturating_add to prevent overflow when y + height > i16::MAX
         self.y.saturating_add(self.height as i16)
     }
+    
+    /// True if the two rectangles share any area (touching edges don't count)
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.left() < other.right()
+            && other.left() < self.right()
+            && self.top() < other.bottom()
+            && other.top() < self.bottom()
+    }
+    
+    /// Area shared by the two rectangles, None if they don't overlap
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.overlaps(other) {
+            return None;
+        }
+        let x = self.left().max(other.left());
+        let y = self.top().max(other.top());
+        Some(Rect {
+            x,
+            y,
+            width: (self.right().min(other.right()) - x) as u16,
+            height: (self.bottom().min(other.bottom()) - y) as u16,
+        })
+    }
+    
+    /// True if this rectangle lies completely inside `bounds`
+    fn within(&self, bounds: &Rect) -> bool {
+        self.left() >= bounds.left()
+            && self.right() <= bounds.right()
+            && self.top() >= bounds.top()
+            && self.bottom() <= bounds.bottom()
+    }
+}
+
+/// Screen area left over after applying the configured edge margins
+pub fn safe_area(screen_width: u16, screen_height: u16, margins: &ScreenMargins) -> Rect {
+    Rect {
+        x: margins.left.min(screen_width) as i16,
+        y: margins.top.min(screen_height) as i16,
+        width: screen_width.saturating_sub(margins.left).saturating_sub(margins.right),
+        height: screen_height.saturating_sub(margins.top).saturating_sub(margins.bottom),
+    }
+}
+
+/// Move `rect` the minimum distance needed to lie inside `area`
+/// If it's larger than the area, its top-left corner is kept inside instead
+pub fn clamp_into(rect: Rect, area: Rect) -> Position {
+    let max_x = area.right().saturating_sub(rect.width as i16).max(area.x);
+    let max_y = area.bottom().saturating_sub(rect.height as i16).max(area.y);
+    Position::new(rect.x.clamp(area.x, max_x), rect.y.clamp(area.y, max_y))
 }
 
 #[derive(Debug)]
@@ -97,6 +147,88 @@ pub fn find_snap_position(
     }
 }
 
+/// Find the nearest position where `dragged` doesn't overlap any of `others`
+/// Candidates are built from the edges of the other rectangles (and the current
+/// position on each axis), so the result sits flush against its neighbours.
+/// Returns None if `dragged` is already clear, or if no free spot fits in `bounds`.
+pub fn find_free_position(
+    dragged: Rect,
+    others: &[Rect],
+    bounds: Rect,
+) -> Option<Position> {
+    if !others.iter().any(|o| o.overlaps(&dragged)) {
+        return None;
+    }
+    
+    let mut xs = vec![dragged.x];
+    let mut ys = vec![dragged.y];
+    for other in others {
+        xs.push(other.right());
+        xs.push(other.left().saturating_sub(dragged.width as i16));
+        ys.push(other.bottom());
+        ys.push(other.top().saturating_sub(dragged.height as i16));
+    }
+    
+    let mut best: Option<(i32, Position)> = None;
+    for &x in &xs {
+        for &y in &ys {
+            let candidate = Rect { x, y, ..dragged };
+            if !candidate.within(&bounds) || others.iter().any(|o| o.overlaps(&candidate)) {
+                continue;
+            }
+            let dx = (x as i32) - (dragged.x as i32);
+            let dy = (y as i32) - (dragged.y as i32);
+            let distance = dx * dx + dy * dy;
+            if best.is_none_or(|(d, _)| distance < d) {
+                best = Some((distance, Position::new(x, y)));
+            }
+        }
+    }
+    best.map(|(_, position)| position)
+}
+
+/// Where `rects` go when lined up with `anchor`: on its top edge when they are
+/// spread out wider than tall (a row), otherwise on its left edge (a column)
+pub fn align_to(anchor: Rect, rects: &[Rect]) -> Vec<Position> {
+    let spread = |edge: fn(&Rect) -> i16| {
+        let edges = rects.iter().chain([&anchor]).map(|r| edge(r) as i32);
+        edges.clone().max().unwrap_or(0) - edges.min().unwrap_or(0)
+    };
+    let row = spread(Rect::left) >= spread(Rect::top);
+    rects.iter()
+        .map(|r| if row { Position::new(r.x, anchor.y) } else { Position::new(anchor.x, r.y) })
+        .collect()
+}
+
+/// Snap the edges of `dragged` to the inner edges of `area` (the screen margins)
+/// Returns a position only if at least one axis snapped
+pub fn find_area_snap_position(
+    dragged: Rect,
+    area: Rect,
+    threshold: u16,
+) -> Option<Position> {
+    if threshold == 0 {
+        return None;
+    }
+    
+    let mut best_x: Option<SnapCandidate> = None;
+    let mut best_y: Option<SnapCandidate> = None;
+    let threshold = threshold as i16;
+    
+    check_snap(&mut best_x, dragged.left(), area.left(), threshold);
+    check_snap(&mut best_x, dragged.right(), area.right(), threshold);
+    check_snap(&mut best_y, dragged.top(), area.top(), threshold);
+    check_snap(&mut best_y, dragged.bottom(), area.bottom(), threshold);
+    
+    if best_x.is_none() && best_y.is_none() {
+        return None;
+    }
+    Some(Position::new(
+        dragged.x + best_x.map_or(0, |s| s.offset),
+        dragged.y + best_y.map_or(0, |s| s.offset),
+    ))
+}
+
 fn check_snap(
     best: &mut Option<SnapCandidate>,
     edge: i16,
@@ -226,4 +358,73 @@ mod tests {
         let result = find_snap_position(dragged, &[(0, snap_x), (1, snap_y)], 15);
         assert_eq!(result, Some(Position::new(100, 200))); // X from first, Y from second
     }
+
+    const SCREEN: Rect = Rect { x: 0, y: 0, width: 1920, height: 1080 };
+
+    #[test]
+    fn test_free_position_none_when_clear() {
+        let dragged = Rect { x: 0, y: 0, width: 50, height: 50 };
+        let other = Rect { x: 50, y: 0, width: 50, height: 50 };
+        assert_eq!(find_free_position(dragged, &[other], SCREEN), None);
+    }
+
+    #[test]
+    fn test_free_position_nudges_to_nearest_side() {
+        // Dropped mostly to the right of other: nearest free spot is flush right
+        let dragged = Rect { x: 140, y: 100, width: 50, height: 50 };
+        let other = Rect { x: 100, y: 100, width: 50, height: 50 };
+        assert_eq!(find_free_position(dragged, &[other], SCREEN), Some(Position::new(150, 100)));
+    }
+
+    #[test]
+    fn test_free_position_stays_on_screen() {
+        // Left of other would go off-screen, so it moves right instead
+        let dragged = Rect { x: 10, y: 0, width: 50, height: 50 };
+        let other = Rect { x: 20, y: 0, width: 50, height: 1080 };
+        assert_eq!(find_free_position(dragged, &[other], SCREEN), Some(Position::new(70, 0)));
+    }
+
+    #[test]
+    fn test_free_position_skips_occupied_neighbours() {
+        let dragged = Rect { x: 105, y: 100, width: 50, height: 50 };
+        let others = [
+            Rect { x: 100, y: 100, width: 50, height: 50 },
+            Rect { x: 150, y: 100, width: 50, height: 50 },
+            Rect { x: 50, y: 100, width: 50, height: 50 },
+        ];
+        assert_eq!(find_free_position(dragged, &others, SCREEN), Some(Position::new(105, 150)));
+    }
+
+    #[test]
+    fn test_safe_area_applies_margins() {
+        let margins = ScreenMargins { top: 40, bottom: 0, left: 10, right: 20 };
+        let area = safe_area(1920, 1080, &margins);
+        assert_eq!((area.x, area.y, area.width, area.height), (10, 40, 1890, 1040));
+    }
+
+    #[test]
+    fn test_clamp_into_moves_out_of_margin() {
+        let area = Rect { x: 0, y: 40, width: 1920, height: 1040 };
+        let rect = Rect { x: 1900, y: 10, width: 50, height: 50 };
+        assert_eq!(clamp_into(rect, area), Position::new(1870, 40));
+    }
+
+    #[test]
+    fn test_area_snap_to_margin_line() {
+        let area = Rect { x: 0, y: 40, width: 1920, height: 1040 };
+        let dragged = Rect { x: 500, y: 50, width: 50, height: 50 };
+        assert_eq!(find_area_snap_position(dragged, area, 15), Some(Position::new(500, 40)));
+        assert_eq!(find_area_snap_position(dragged, area, 0), None);
+    }
+
+    #[test]
+    fn test_align_to_row_or_column() {
+        let anchor = Rect { x: 100, y: 100, width: 50, height: 50 };
+        let right = Rect { x: 400, y: 130, width: 50, height: 50 };
+        let below = Rect { x: 120, y: 300, width: 50, height: 50 };
+        // Spread wider than tall: a row on the anchor's top edge
+        assert_eq!(align_to(anchor, &[right]), vec![Position::new(400, 100)]);
+        // Taller than wide: a column on the anchor's left edge
+        assert_eq!(align_to(anchor, &[below]), vec![Position::new(100, 300)]);
+    }
 }
@@ -0,0 +1,90 @@
+//! Refreshing thumbnails after their client window was moved or resized
+//!
+//! Moving or resizing a client produces a burst of ConfigureNotify events but
+//! often no damage, so the thumbnail keeps showing stale content. Each client's
+//! burst is tracked here; once no ConfigureNotify arrived for the settle time
+//! the move/resize counts as finished and the main loop refreshes the thumbnail.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use x11rb::protocol::xproto::Window;
+
+use crate::constants::source_refresh;
+
+#[derive(Debug)]
+pub struct SourceRefresh {
+    settle: Duration,
+    /// Last ConfigureNotify per client still being moved/resized
+    pending: HashMap<Window, Instant>,
+}
+
+impl Default for SourceRefresh {
+    fn default() -> Self {
+        Self {
+            settle: Duration::from_millis(source_refresh::SETTLE_MS),
+            pending: HashMap::new(),
+        }
+    }
+}
+
+impl SourceRefresh {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A client window was moved or resized
+    pub fn configured(&mut self, window: Window, now: Instant) {
+        self.pending.insert(window, now);
+    }
+
+    /// When the earliest move/resize settles (None if nothing is in progress)
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.values().map(|last| *last + self.settle).min()
+    }
+
+    /// Take the clients whose move/resize has finished
+    pub fn take_settled(&mut self, now: Instant) -> Vec<Window> {
+        let settle = self.settle;
+        let settled: Vec<Window> = self.pending.iter()
+            .filter(|(_, last)| now.duration_since(**last) >= settle)
+            .map(|(window, _)| *window)
+            .collect();
+        for window in &settled {
+            self.pending.remove(window);
+        }
+        settled
+    }
+
+    /// Drop state for a client window that no longer exists
+    pub fn forget(&mut self, window: Window) {
+        self.pending.remove(&window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settles_after_last_configure() {
+        let mut refresh = SourceRefresh::new();
+        let start = Instant::now();
+        let settle = Duration::from_millis(source_refresh::SETTLE_MS);
+        refresh.configured(1, start);
+        refresh.configured(1, start + Duration::from_millis(50));
+        assert_eq!(refresh.deadline(), Some(start + Duration::from_millis(50) + settle));
+
+        assert!(refresh.take_settled(start + settle).is_empty());
+        assert_eq!(refresh.take_settled(start + Duration::from_millis(50) + settle), vec![1]);
+        assert_eq!(refresh.deadline(), None);
+    }
+
+    #[test]
+    fn test_forget_drops_pending() {
+        let mut refresh = SourceRefresh::new();
+        refresh.configured(1, Instant::now());
+        refresh.forget(1);
+        assert_eq!(refresh.deadline(), None);
+    }
+}
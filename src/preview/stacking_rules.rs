@@ -0,0 +1,97 @@
+//! Per-application preview stacking (stacking_rules, stacking_default)
+//!
+//! Instead of staying on top of everything, previews can stay above some
+//! applications and drop below others ("above the EVE clients, below my
+//! browser"). The daemon follows _NET_ACTIVE_WINDOW on the root and reads the
+//! active window's WM_CLASS; when its rule puts previews below it, thumbnails
+//! are restacked right under that window's frame until another window becomes
+//! active.
+
+use anyhow::{Context, Result};
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+
+use crate::config::profile::{PreviewStacking, Profile, StackingRule};
+use crate::constants::x11;
+use crate::x11_utils::{frame_window, AppContext};
+
+/// Where previews go for a window with these WM_CLASS names
+/// Our own thumbnails (managed ones can become active) never push previews down
+fn placement(rules: &[StackingRule], default: PreviewStacking, classes: &[String]) -> PreviewStacking {
+    if classes.iter().any(|class| class == x11::THUMBNAIL_WM_CLASS) {
+        return PreviewStacking::Above;
+    }
+    rules.iter()
+        .find(|rule| classes.iter().any(|class| class.eq_ignore_ascii_case(rule.class.trim())))
+        .map_or(default, |rule| rule.previews)
+}
+
+/// WM_CLASS instance and class of a window (empty when unset or the window is gone)
+fn window_classes(conn: &RustConnection, window: Window) -> Result<Vec<String>> {
+    let Ok(reply) = conn.get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)
+        .context(format!("Failed to query WM_CLASS of window {}", window))?
+        .reply()
+    else {
+        return Ok(Vec::new());
+    };
+    Ok(reply.value
+        .split(|&b| b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect())
+}
+
+/// The active window on `root`, None without one (or without an EWMH window manager)
+fn active_window(ctx: &AppContext, root: Window) -> Result<Option<Window>> {
+    let reply = ctx.conn.get_property(false, root, ctx.atoms.net_active_window, AtomEnum::WINDOW, 0, 1)
+        .context("Failed to query _NET_ACTIVE_WINDOW property")?
+        .reply()
+        .context("Failed to get reply for _NET_ACTIVE_WINDOW query")?;
+    Ok(reply.value32()
+        .and_then(|mut windows| windows.next())
+        .filter(|&window| window != x11rb::NONE))
+}
+
+/// Root and frame of the active window on `root` when the profile puts previews
+/// below it, None when previews go on top
+pub fn anchor(ctx: &AppContext, profile: &Profile, root: Window) -> Result<Option<(Window, Window)>> {
+    let Some(window) = active_window(ctx, root)? else {
+        return Ok(None);
+    };
+    let classes = window_classes(ctx.conn, window)?;
+    match placement(&profile.stacking_rules, profile.stacking_default, &classes) {
+        PreviewStacking::Above => Ok(None),
+        PreviewStacking::Below => {
+            let frame = frame_window(ctx.conn, window)
+                .context(format!("Failed to find the frame of active window {}", window))?;
+            Ok(Some((root, frame)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(class: &str, previews: PreviewStacking) -> StackingRule {
+        StackingRule { class: class.to_string(), previews }
+    }
+
+    #[test]
+    fn test_placement_follows_first_matching_rule() {
+        let rules = vec![rule("Firefox", PreviewStacking::Below), rule("wine", PreviewStacking::Above)];
+        let firefox = vec!["Navigator".to_string(), "firefox".to_string()];
+        let eve = vec!["exefile.exe".to_string(), "Wine".to_string()];
+        assert_eq!(placement(&rules, PreviewStacking::Above, &firefox), PreviewStacking::Below);
+        assert_eq!(placement(&rules, PreviewStacking::Below, &eve), PreviewStacking::Above);
+        // Unmatched applications get the default
+        assert_eq!(placement(&rules, PreviewStacking::Below, &["kitty".to_string()]), PreviewStacking::Below);
+        assert_eq!(placement(&rules, PreviewStacking::Above, &[]), PreviewStacking::Above);
+    }
+
+    #[test]
+    fn test_own_thumbnails_keep_previews_above() {
+        let ours = vec![x11::THUMBNAIL_WM_CLASS.to_string()];
+        assert_eq!(placement(&[], PreviewStacking::Below, &ours), PreviewStacking::Above);
+    }
+}
@@ -0,0 +1,95 @@
+//! Detection of clients that stopped producing frames (stall_warning_secs)
+//!
+//! A running EVE client redraws all the time, so a client whose window sends
+//! no DamageNotify for a while has most likely hung under wine. Each damage
+//! object's clock starts when it is first watched and restarts on every frame;
+//! minimized clients are paused because they legitimately stop drawing.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use x11rb::protocol::damage::Damage;
+
+#[derive(Debug, Default)]
+pub struct StallWatch {
+    /// No frames for this long = stalled (zero = off)
+    timeout: Duration,
+    /// Last frame (or start of watching) per damage object
+    last: HashMap<Damage, Instant>,
+}
+
+impl StallWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how long a client may go without frames (0 = never warn)
+    pub fn set_timeout_secs(&mut self, secs: u16) {
+        self.timeout = Duration::from_secs(secs as u64);
+    }
+
+    /// A frame arrived from the client
+    pub fn frame(&mut self, damage: Damage, now: Instant) {
+        self.last.insert(damage, now);
+    }
+
+    /// Start the clock for a client that isn't watched yet
+    pub fn watch(&mut self, damage: Damage, now: Instant) {
+        self.last.entry(damage).or_insert(now);
+    }
+
+    /// Stop watching (minimized or gone); watching again restarts the clock
+    pub fn forget(&mut self, damage: Damage) {
+        self.last.remove(&damage);
+    }
+
+    pub fn is_stalled(&self, damage: Damage, now: Instant) -> bool {
+        !self.timeout.is_zero()
+            && self.last.get(&damage).is_some_and(|last| now.duration_since(*last) >= self.timeout)
+    }
+
+    /// When the next watched client turns stalled if nothing arrives (None if off or none pending)
+    pub fn deadline(&self, now: Instant) -> Option<Instant> {
+        if self.timeout.is_zero() {
+            return None;
+        }
+        self.last.values()
+            .map(|last| *last + self.timeout)
+            .filter(|deadline| *deadline > now)
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stalls_without_frames() {
+        let mut watch = StallWatch::new();
+        watch.set_timeout_secs(10);
+        let start = Instant::now();
+        watch.watch(1, start);
+        assert!(!watch.is_stalled(1, start + Duration::from_secs(9)));
+        assert_eq!(watch.deadline(start), Some(start + Duration::from_secs(10)));
+        assert!(watch.is_stalled(1, start + Duration::from_secs(10)));
+        // Already stalled clients don't keep the main loop awake
+        assert_eq!(watch.deadline(start + Duration::from_secs(11)), None);
+
+        watch.frame(1, start + Duration::from_secs(12));
+        assert!(!watch.is_stalled(1, start + Duration::from_secs(13)));
+    }
+
+    #[test]
+    fn test_off_and_forgotten_never_stall() {
+        let mut watch = StallWatch::new();
+        let start = Instant::now();
+        watch.watch(1, start);
+        assert!(!watch.is_stalled(1, start + Duration::from_secs(3600)));
+        assert_eq!(watch.deadline(start), None);
+
+        watch.set_timeout_secs(5);
+        watch.forget(1);
+        assert!(!watch.is_stalled(1, start + Duration::from_secs(3600)));
+    }
+}
@@ -1,23 +1,46 @@
+use std::cell::Cell;
+use std::time::Instant;
+
 use anyhow::{Context, Result};
-use tracing::{error, info};
+use tracing::{debug, error, info};
 use x11rb::connection::Connection;
+use x11rb::errors::ReplyError;
+use x11rb::protocol::composite::{ConnectionExt as CompositeExt, Redirect};
 use x11rb::protocol::damage::{
     ConnectionExt as DamageExt, Damage, ReportLevel as DamageReportLevel,
 };
 use x11rb::protocol::render::{
-    ConnectionExt as RenderExt, CreatePictureAux, PictOp, Picture, Transform,
+    Color, ConnectionExt as RenderExt, CreatePictureAux, PictOp, Picture, Transform,
 };
+use x11rb::properties::{WmSizeHints, WmSizeHintsSpecification};
 use x11rb::protocol::xproto::*;
 use x11rb::rust_connection::RustConnection;
 use x11rb::wrapper::ConnectionExt as WrapperExt;
 
+use crate::color::HexColor;
 use crate::config::DisplayConfig;
-use crate::constants::{positioning, x11};
-use crate::types::{Dimensions, Position, ThumbnailState};
-use crate::x11_utils::{get_pictformat, to_fixed, AppContext};
+use crate::config::profile::{NightModeSettings, ScaleFilter};
+use crate::constants::{low_bandwidth, positioning, stall, x11};
+use crate::types::{CharacterSettings, Dimensions, Position, RedactRect, ThumbnailState};
+use crate::x11_trace;
+use crate::x11_utils::{get_pictformat, list_monitors, monitor_index_at, root_geometry, to_fixed, AppContext};
 
-use super::font::FontRenderer;
+use super::borderless::SavedClientGeometry;
+use super::color_adjust::ColorAdjust;
+use super::damage_region;
+use super::drag_guides::{self, Outline};
+use super::font::{core_font_text, FontRenderer};
+#[cfg(feature = "gl")]
+use super::gl_backend::{GlBackend, GlSurface};
+use super::label_template::{self, LabelFields};
+use super::lifecycle::ClientLifecycle;
+use super::opacity_blend::OpacityBlend;
+use super::pixelate::Pixelate;
+use super::redaction::{RedactDraw, Redactor};
 use super::snapping::Rect;
+use super::tooltip::{self, FrameMeter, Placement, Tooltip};
+use super::watchdog::Problem;
+use super::window_identity::WindowIdentity;
 
 #[derive(Debug, Default)]
 pub struct InputState {
@@ -25,6 +48,15 @@ pub struct InputState {
     pub drag_start: Position,
     pub win_start: Position,
     pub snap_targets: Vec<Rect>,  // Cached snap targets computed when drag starts
+    pub hovered: bool,            // Pointer is inside the thumbnail window
+    pub guides: Vec<Window>,      // Drag guide line windows (empty unless dragging with guides on)
+    pub ghost: Option<Outline>,   // Outline dragged in place of the thumbnail (drag_ghost)
+    pub cancel_key: Option<Keycode>, // Escape keycode grabbed for the duration of a drag
+    pub selection: Option<Outline>,  // Selection marker (Ctrl+click), Some = selected for group moves
+    pub group: Vec<(Window, Position)>, // Other selected thumbnails (by EVE window) and their start positions
+    pub from_panel: Option<Position>, // Saved free-floating position while dragging out of the panel
+    pub redact_edit: bool,            // GUI has this character in redaction edit mode
+    pub redact_draw: Option<RedactDraw>, // Redaction area being drawn (edit mode left-drag)
 }
 
 #[derive(Debug)]
@@ -33,15 +65,50 @@ pub struct Thumbnail<'a> {
     pub character_name: String,
     pub state: ThumbnailState,
     pub input_state: InputState,
+    /// Client geometry saved while the source window is in borderless fullscreen
+    pub borderless_restore: Option<SavedClientGeometry>,
+    /// Character's preview is hidden by config (window stays unmapped in every state)
+    pub preview_hidden: bool,
+    /// Real character name while a newer client of the same character owns it
+    /// (`character_name` then holds a disambiguated name that is never saved)
+    pub duplicate_of: Option<String>,
+    /// Character's preview floats on its own instead of joining the panel (panel mode)
+    pub panel_detached: bool,
+    /// Name label color (ARGB), the profile's text_color unless overridden per character
+    label_color: u32,
+    /// Label text override (per-character alias), None = character name
+    alias: Option<String>,
+    /// Label expanded from the profile's label_template, None = alias or character name
+    label: Option<String>,
+    /// Client process (_NET_WM_PID), None if the window doesn't say
+    pub pid: Option<u32>,
+    /// CPU/RAM readout drawn along the bottom edge (show_process_stats)
+    stats: Option<String>,
+    /// Client stopped producing frames (stall_warning_secs): drawn grey with a badge
+    pub stalled: bool,
+    /// Client frame rate estimate, shown in the hover tooltip
+    pub frames: FrameMeter,
+    /// Hover tooltip while it is shown (hover_tooltip)
+    tooltip: Option<Tooltip>,
+    /// Session identity of the source window: name history and authoritative geometry
+    pub identity: WindowIdentity,
+    /// Where the client is in its login/minimize/destroy lifecycle
+    pub lifecycle: ClientLifecycle,
     
     // === Geometry (public, immutable after creation) ===
     pub dimensions: Dimensions,
+    /// Drawn size is capped (low bandwidth mode); `identity` keeps the requested size
+    low_bandwidth: bool,
+    /// Largest size the panel layout allows (panel mode), None = no limit
+    layout_cap: Option<Dimensions>,
     
     // === X11 Window Handles (private/public owned resources) ===
     pub window: Window,      // Our thumbnail window (public for event handling)
     pub src: Window,         // Source EVE window (public for event handling)
     pub damage: Damage,      // DAMAGE extension handle (public for event matching)
-    root: Window,            // Root window (private, cached from screen)
+    damage_level: DamageReportLevel, // Report level the damage was created with
+    pub screen: &'a Screen,  // X screen the source window lives on (multi-screen setups)
+    pub panel: Option<Window>, // Panel window the thumbnail is a child of (panel mode), None = top-level
     
     // === X11 Render Resources (private, owned resources) ===
     border_fill: Picture,    // Solid color fill for border
@@ -50,6 +117,24 @@ pub struct Thumbnail<'a> {
     overlay_gc: Gcontext,    // Graphics context for text rendering
     overlay_pixmap: Pixmap,  // Backing pixmap for overlay compositing
     overlay_picture: Picture, // Picture wrapping overlay pixmap
+    /// (source, thumbnail) sizes the window content was last fully scaled for, None = stale
+    scaled_for: Cell<Option<(Dimensions, Dimensions)>>,
+    /// Colour correction of the monitor the thumbnail is on (monitor_colors) and night tint
+    color_adjust: Option<ColorAdjust>,
+    /// Night mode settings while night mode is active
+    night: Option<NightModeSettings>,
+    /// Opacity blended in by us instead of the compositor (opacity_mode)
+    opacity_blend: Option<OpacityBlend>,
+    /// Block buffer while privacy mode pixelates the preview
+    pixelate: Option<Pixelate>,
+    /// Black fill over the character's redacted client areas
+    redactor: Option<Redactor>,
+    #[cfg(feature = "gl")]
+    gl: Option<GlSurface<'a>>, // OpenGL drawing of the source (None = XRender only)
+    #[cfg(feature = "gl")]
+    gl_pending: Cell<bool>, // GL draw issued, overlay goes on once its frame is finished
+    /// Client redirected by us (redirect_fullscreen_clients; GL surfaces redirect it themselves)
+    redirected: bool,
     
     // === Borrowed Dependencies (private, references to app context) ===
     conn: &'a RustConnection,
@@ -80,12 +165,14 @@ impl<'a> Thumbnail<'a> {
             WindowClass::INPUT_OUTPUT,
             ctx.screen.root_visual,
             &CreateWindowAux::new()
-            .override_redirect(x11::OVERRIDE_REDIRECT)
+            .override_redirect(if ctx.wm.get().override_redirect { x11::OVERRIDE_REDIRECT } else { 0 })
             .event_mask(
                 EventMask::SUBSTRUCTURE_NOTIFY
                 | EventMask::BUTTON_PRESS
                 | EventMask::BUTTON_RELEASE
-                | EventMask::POINTER_MOTION,
+                | EventMask::POINTER_MOTION
+                | EventMask::ENTER_WINDOW
+                | EventMask::LEAVE_WINDOW,
             ),
         )
         .context(format!("Failed to create thumbnail window for '{}'", character_name))?;
@@ -94,10 +181,12 @@ impl<'a> Thumbnail<'a> {
     }
 
     /// Setup window properties (opacity, WM_CLASS, always-on-top, PID)
+    /// `blended`: opacity is drawn by OpacityBlend, so the compositor must not apply it again
     fn setup_window_properties(
         ctx: &AppContext,
         window: Window,
         character_name: &str,
+        blended: bool,
     ) -> Result<()> {
         // Set PID so we can identify our own thumbnail windows
         let pid = std::process::id();
@@ -111,20 +200,22 @@ impl<'a> Thumbnail<'a> {
         .context(format!("Failed to set _NET_WM_PID for '{}'", character_name))?;
 
         // Set opacity
-        let opacity_atom = ctx.conn
-            .intern_atom(false, b"_NET_WM_WINDOW_OPACITY")
-            .context("Failed to intern _NET_WM_WINDOW_OPACITY atom")?
-            .reply()
-            .context("Failed to get reply for _NET_WM_WINDOW_OPACITY atom")?
-            .atom;
-        ctx.conn.change_property32(
-            PropMode::REPLACE,
-            window,
-            opacity_atom,
-            AtomEnum::CARDINAL,
-            &[ctx.config.opacity],
-        )
-        .context(format!("Failed to set window opacity for '{}'", character_name))?;
+        if !blended {
+            let opacity_atom = ctx.conn
+                .intern_atom(false, b"_NET_WM_WINDOW_OPACITY")
+                .context("Failed to intern _NET_WM_WINDOW_OPACITY atom")?
+                .reply()
+                .context("Failed to get reply for _NET_WM_WINDOW_OPACITY atom")?
+                .atom;
+            ctx.conn.change_property32(
+                PropMode::REPLACE,
+                window,
+                opacity_atom,
+                AtomEnum::CARDINAL,
+                &[ctx.config.opacity],
+            )
+            .context(format!("Failed to set window opacity for '{}'", character_name))?;
+        }
 
         // Set WM_CLASS
         let wm_class = ctx.conn.intern_atom(false, b"WM_CLASS")
@@ -137,7 +228,7 @@ impl<'a> Thumbnail<'a> {
             window,
             wm_class,
             AtomEnum::STRING,
-            b"eve-l-preview\0eve-l-preview\0",
+            format!("{0}\0{0}\0", x11::THUMBNAIL_WM_CLASS).as_bytes(),
         )
         .context(format!("Failed to set WM_CLASS for '{}'", character_name))?;
 
@@ -152,12 +243,16 @@ impl<'a> Thumbnail<'a> {
             .reply()
             .context("Failed to get reply for _NET_WM_STATE_ABOVE atom")?
             .atom;
+        let mut states = vec![above_atom];
+        if !ctx.wm.get().override_redirect {
+            states.extend(Self::setup_managed_window(ctx, window, character_name)?);
+        }
         ctx.conn.change_property32(
             PropMode::REPLACE,
             window,
             net_wm_state,
             AtomEnum::ATOM,
-            &[above_atom],
+            &states,
         )
         .context(format!("Failed to set window always-on-top for '{}'", character_name))?;
 
@@ -175,6 +270,51 @@ impl<'a> Thumbnail<'a> {
     }
 
     /// Create render pictures and resources
+    /// Hints for a thumbnail the WM manages (wm_preset without override-redirect):
+    /// undecorated utility window at the position we asked for, on every desktop
+    /// Returns the extra _NET_WM_STATE atoms (skip taskbar/pager, sticky)
+    fn setup_managed_window(ctx: &AppContext, window: Window, character_name: &str) -> Result<Vec<Atom>> {
+        let intern = |name: &str| -> Result<Atom> {
+            Ok(ctx.conn.intern_atom(false, name.as_bytes())
+                .context(format!("Failed to intern {} atom", name))?
+                .reply()
+                .context(format!("Failed to get reply for {} atom", name))?
+                .atom)
+        };
+        ctx.conn.change_property32(
+            PropMode::REPLACE,
+            window,
+            intern("_NET_WM_WINDOW_TYPE")?,
+            AtomEnum::ATOM,
+            &[intern("_NET_WM_WINDOW_TYPE_UTILITY")?],
+        )
+        .context(format!("Failed to set window type for '{}'", character_name))?;
+        ctx.conn.change_property32(
+            PropMode::REPLACE,
+            window,
+            ctx.atoms.motif_wm_hints,
+            ctx.atoms.motif_wm_hints,
+            &[x11::MWM_HINTS_DECORATIONS, 0, 0, 0, 0],
+        )
+        .context(format!("Failed to clear decorations for '{}'", character_name))?;
+
+        let geom = ctx.conn.get_geometry(window)
+            .context("Failed to send geometry query for new thumbnail")?
+            .reply()
+            .context(format!("Failed to get geometry of new thumbnail for '{}'", character_name))?;
+        let mut hints = WmSizeHints::new();
+        hints.position = Some((WmSizeHintsSpecification::UserSpecified, geom.x as i32, geom.y as i32));
+        hints.size = Some((WmSizeHintsSpecification::UserSpecified, geom.width as i32, geom.height as i32));
+        hints.set_normal_hints(ctx.conn, window)
+            .context(format!("Failed to set WM_NORMAL_HINTS for '{}'", character_name))?;
+
+        Ok(vec![
+            intern("_NET_WM_STATE_SKIP_TASKBAR")?,
+            intern("_NET_WM_STATE_SKIP_PAGER")?,
+            intern("_NET_WM_STATE_STICKY")?,
+        ])
+    }
+
     fn create_render_resources(
         ctx: &AppContext,
         window: Window,
@@ -197,6 +337,8 @@ impl<'a> Thumbnail<'a> {
             .context("Failed to generate ID for destination picture")?;
         ctx.conn.render_create_picture(src_picture, src, pict_format, &CreatePictureAux::new())
             .context(format!("Failed to create source picture for '{}'", character_name))?;
+        x11_trace::request(src, "RenderCreatePicture", format_args!("picture={:#x} drawable={:#x} format={:#x} (depth {})", src_picture, src, pict_format, ctx.screen.root_depth));
+        Self::set_scale_filter(ctx.conn, src_picture, ctx.config.scale_filter, character_name)?;
         ctx.conn.render_create_picture(dst_picture, window, pict_format, &CreatePictureAux::new())
             .context(format!("Failed to create destination picture for '{}'", character_name))?;
 
@@ -234,11 +376,13 @@ impl<'a> Thumbnail<'a> {
         ctx: &AppContext,
         src: Window,
         character_name: &str,
+        level: DamageReportLevel,
     ) -> Result<Damage> {
         let damage = ctx.conn.generate_id()
             .context("Failed to generate ID for damage tracking")?;
-        ctx.conn.damage_create(damage, src, DamageReportLevel::RAW_RECTANGLES)
+        ctx.conn.damage_create(damage, src, level)
             .context(format!("Failed to create damage tracking for '{}' (check DAMAGE extension)", character_name))?;
+        x11_trace::request(src, "DamageCreate", format_args!("damage={:#x} drawable={:#x} level={:?}", damage, src, level));
         Ok(damage)
     }
 
@@ -249,6 +393,7 @@ impl<'a> Thumbnail<'a> {
         font_renderer: &'a FontRenderer,
         position: Option<Position>,
         dimensions: Dimensions,
+        low_bandwidth: bool,
     ) -> Result<Self> {
         // Validate dimensions are non-zero
         if dimensions.width == 0 || dimensions.height == 0 {
@@ -263,6 +408,21 @@ impl<'a> Thumbnail<'a> {
             .context("Failed to send geometry query for source EVE window")?
             .reply()
             .context(format!("Failed to get geometry for source window {} (character: '{}')", src, character_name))?;
+        x11_trace::reply(src, "GetGeometry", format_args!("{}x{}+{}+{} depth={} root={:#x}", src_geom.width, src_geom.height, src_geom.x, src_geom.y, src_geom.depth, src_geom.root));
+        
+        // The requested size stays authoritative (and saved); low bandwidth mode only draws smaller
+        let requested = dimensions;
+        let dimensions = if low_bandwidth {
+            dimensions.capped(low_bandwidth::MAX_WIDTH, low_bandwidth::MAX_HEIGHT)
+        } else {
+            dimensions
+        };
+        // Only the first change after each redraw is reported in low bandwidth mode
+        let damage_level = if low_bandwidth {
+            DamageReportLevel::NON_EMPTY
+        } else {
+            DamageReportLevel::RAW_RECTANGLES
+        };
         
         // Use saved position OR top-left of EVE window with 20px padding
         let Position { x, y } = position.unwrap_or_else(|| {
@@ -282,6 +442,7 @@ impl<'a> Thumbnail<'a> {
 
         // Create window and setup properties
         let window = Self::create_window(ctx, &character_name, x, y, dimensions)?;
+        x11_trace::request(src, "CreateWindow", format_args!("thumbnail={:#x} {}x{}+{}+{} depth={} override_redirect", window, dimensions.width, dimensions.height, x, y, ctx.screen.root_depth));
         
         // Setup a cleanup guard that destroys the window if we fail during initialization
         // This prevents leaking the window if later steps fail
@@ -316,29 +477,50 @@ impl<'a> Thumbnail<'a> {
             should_cleanup: true,
         };
         
-        Self::setup_window_properties(ctx, window, &character_name)?;
+        let opacity_blend = OpacityBlend::new(ctx)
+            .context(format!("Failed to set up opacity blending for '{}'", character_name))?;
+        Self::setup_window_properties(ctx, window, &character_name, opacity_blend.is_some())?;
 
         // Create rendering resources
         let (border_fill, src_picture, dst_picture, overlay_pixmap, overlay_picture, overlay_gc) = 
             Self::create_render_resources(ctx, window, src, dimensions, &character_name)?;
 
         // Setup damage tracking
-        let damage = Self::create_damage_tracking(ctx, src, &character_name)?;
+        let damage = Self::create_damage_tracking(ctx, src, &character_name, damage_level)?;
 
-        let thumbnail = Self {
+        let identity = WindowIdentity::new(src, &character_name, Position::new(x, y), requested);
+        let mut thumbnail = Self {
             // Application State
             character_name,
             state: ThumbnailState::default(), // Start in unfocused normal state
             input_state: InputState::default(),
+            borderless_restore: None,
+            preview_hidden: false,
+            duplicate_of: None,
+            panel_detached: false,
+            label_color: ctx.config.text_color,
+            alias: None,
+            pid: None,
+            stats: None,
+            stalled: false,
+            label: None,
+            frames: FrameMeter::default(),
+            tooltip: None,
+            identity,
+            lifecycle: ClientLifecycle::default(),
             
             // Geometry
             dimensions,
+            low_bandwidth,
+            layout_cap: None,
             
             // X11 Window Handles
             window,
             src,
             damage,
-            root: ctx.screen.root,
+            damage_level,
+            screen: ctx.screen,
+            panel: None,
             
             // X11 Render Resources
             border_fill,
@@ -347,6 +529,17 @@ impl<'a> Thumbnail<'a> {
             overlay_gc,
             overlay_pixmap,
             overlay_picture,
+            scaled_for: Cell::new(None),
+            color_adjust: None,
+            night: None,
+            opacity_blend,
+            pixelate: None,
+            redactor: None,
+            #[cfg(feature = "gl")]
+            gl: None,
+            #[cfg(feature = "gl")]
+            gl_pending: Cell::new(false),
+            redirected: false,
             
             // Borrowed Dependencies
             conn: ctx.conn,
@@ -354,6 +547,9 @@ impl<'a> Thumbnail<'a> {
             font_renderer,
         };
         
+        thumbnail.update_color_adjust()
+            .context(format!("Failed to set up colour correction for '{}'", thumbnail.character_name))?;
+        
         // Render initial name overlay
         thumbnail.update_name()
             .context(format!("Failed to render initial name overlay for '{}'", thumbnail.character_name))?;
@@ -373,13 +569,18 @@ impl<'a> Thumbnail<'a> {
         if visible {
             // Restore from Hidden state to Normal (unfocused)
             self.state = ThumbnailState::Normal { focused: false };
-            self.conn.map_window(self.window)
-                .context(format!("Failed to map window for '{}'", self.character_name))?;
+            if !self.preview_hidden {
+                self.conn.map_window(self.window)
+                    .context(format!("Failed to map window for '{}'", self.character_name))?;
+                x11_trace::request(self.src, "MapWindow", format_args!("thumbnail={:#x}", self.window));
+            }
         } else {
             // Hide the window
             self.state = ThumbnailState::Hidden;
+            self.scaled_for.set(None);
             self.conn.unmap_window(self.window)
                 .context(format!("Failed to unmap window for '{}'", self.character_name))?;
+            x11_trace::request(self.src, "UnmapWindow", format_args!("thumbnail={:#x}", self.window));
         }
         Ok(())
     }
@@ -389,33 +590,156 @@ impl<'a> Thumbnail<'a> {
             .context("Failed to send geometry query for source window")?
             .reply()
             .context(format!("Failed to get geometry for source window (character: '{}')", self.character_name))?;
-        let transform = Transform {
-            matrix11: to_fixed(geom.width as f32 / self.dimensions.width as f32),
-            matrix22: to_fixed(geom.height as f32 / self.dimensions.height as f32),
-            matrix33: to_fixed(1.0),
-            ..Default::default()
+        x11_trace::reply(self.src, "GetGeometry", format_args!("{}x{}+{}+{} depth={}", geom.width, geom.height, geom.x, geom.y, geom.depth));
+        #[cfg(feature = "gl")]
+        if let Some(surface) = self.gl.as_ref().filter(|_| self.pixelate.is_none() && self.redactor.is_none()) {
+            match surface.draw(self.src, &geom, self.dimensions) {
+                Ok(()) => {
+                    self.scaled_for.set(None);
+                    self.gl_pending.set(true);
+                    return Ok(());
+                }
+                Err(e) => tracing::debug!(character = %self.character_name, error = %e, "OpenGL draw failed, using XRender"),
+            }
+        }
+        #[cfg(feature = "gl")]
+        self.gl_pending.set(false);
+        self.capture_xrender(Dimensions::new(geom.width, geom.height))
+    }
+
+    /// Scale the whole `source`-sized client with XRender
+    fn capture_xrender(&self, source: Dimensions) -> Result<()> {
+        self.scale_source(source, self.full_region())?;
+        // Blocks don't line up with damage rectangles, pixelated previews are always redrawn whole
+        let scaled_for = self.pixelate.is_none().then_some((source, self.dimensions));
+        self.scaled_for.set(scaled_for);
+        Ok(())
+    }
+
+    /// Scale `region` of the thumbnail from a `source`-sized client
+    fn scale_source(&self, source: Dimensions, region: Rect) -> Result<()> {
+        if let Some(pixelate) = &self.pixelate {
+            pixelate.draw(self.conn, self.src_picture, self.dst_picture, source, self.dimensions)
+                .context(format!("Failed to pixelate '{}'", self.character_name))?;
+        } else {
+            let transform = Transform {
+                matrix11: to_fixed(source.width as f32 / self.dimensions.width as f32),
+                matrix22: to_fixed(source.height as f32 / self.dimensions.height as f32),
+                matrix33: to_fixed(1.0),
+                ..Default::default()
+            };
+            self.conn
+                .render_set_picture_transform(self.src_picture, transform)
+                .context(format!("Failed to set transform for '{}'", self.character_name))?;
+            self.conn.render_composite(
+                PictOp::SRC,
+                self.src_picture,
+                0u32,
+                self.dst_picture,
+                region.x,
+                region.y,
+                0,
+                0,
+                region.x,
+                region.y,
+                region.width,
+                region.height,
+            )
+            .context(format!("Failed to composite source window for '{}'", self.character_name))?;
+            x11_trace::request(self.src, "RenderComposite", format_args!(
+                "src={:#x} dst={:#x} region={}x{}+{}+{} scale_from={}x{}",
+                self.src_picture, self.dst_picture, region.width, region.height, region.x, region.y, source.width, source.height));
+        }
+        if let Some(redactor) = &self.redactor {
+            redactor.apply(self.conn, self.dst_picture, source, self.dimensions, region)
+                .context(format!("Failed to redact '{}'", self.character_name))?;
+        }
+        if let Some(adjust) = &self.color_adjust {
+            adjust.apply(self.conn, self.dst_picture, region)
+                .context(format!("Failed to colour correct '{}'", self.character_name))?;
+        }
+        if let Some(blend) = &self.opacity_blend {
+            let origin = self.panel.is_none().then(|| self.identity.position());
+            blend.apply(self.conn, self.dst_picture, region, origin)
+                .context(format!("Failed to blend opacity for '{}'", self.character_name))?;
+        }
+        Ok(())
+    }
+
+    /// Pick up the colour correction of the monitor the thumbnail's center is on,
+    /// plus the night tint while night mode is active
+    fn update_color_adjust(&mut self) -> Result<()> {
+        if self.config.monitor_colors.is_empty() && self.night.is_none() && self.color_adjust.is_none() {
+            return Ok(());
+        }
+        let monitor = if self.config.monitor_colors.is_empty() {
+            0
+        } else {
+            let Position { x, y } = self.identity.position();
+            let center_x = x.saturating_add((self.dimensions.width / 2) as i16);
+            let center_y = y.saturating_add((self.dimensions.height / 2) as i16);
+            monitor_index_at(&list_monitors(self.conn, self.screen), center_x, center_y)
         };
-        self.conn
-            .render_set_picture_transform(self.src_picture, transform)
-            .context(format!("Failed to set transform for '{}'", self.character_name))?;
-        self.conn.render_composite(
-            PictOp::SRC,
-            self.src_picture,
-            0u32,
-            self.dst_picture,
-            0,
-            0,
-            0,
-            0,
-            0,
-            0,
-            self.dimensions.width,
-            self.dimensions.height,
-        )
-        .context(format!("Failed to composite source window for '{}'", self.character_name))?;
+        if self.color_adjust.as_ref().is_some_and(|adjust| adjust.matches(monitor, self.night)) {
+            return Ok(());
+        }
+        if let Some(previous) = self.color_adjust.take() {
+            previous.free(self.conn)?;
+        }
+        self.color_adjust = ColorAdjust::new(self.conn, &self.config.monitor_colors, monitor, self.night)?;
+        self.scaled_for.set(None);
         Ok(())
     }
 
+    /// Pixelate the preview (privacy mode) or go back to the normal image
+    pub fn set_privacy(&mut self, enabled: bool) -> Result<()> {
+        if self.pixelate.is_some() == enabled {
+            return Ok(());
+        }
+        if let Some(pixelate) = self.pixelate.take() {
+            pixelate.free(self.conn)?;
+        } else {
+            self.pixelate = Some(Pixelate::new(self.conn, self.screen, self.dimensions)
+                .context(format!("Failed to set up pixelation for '{}'", self.character_name))?);
+        }
+        self.scaled_for.set(None);
+        self.refresh_source()
+    }
+
+    /// Black out these client areas in the preview (per-character redactions)
+    pub fn set_redactions(&mut self, rects: &[RedactRect]) -> Result<()> {
+        if self.redactions() == rects {
+            return Ok(());
+        }
+        if let Some(redactor) = self.redactor.take() {
+            redactor.free(self.conn)?;
+        }
+        self.redactor = Redactor::new(self.conn, rects)
+            .context(format!("Failed to set up redactions for '{}'", self.character_name))?;
+        self.scaled_for.set(None);
+        self.refresh_source()
+    }
+
+    /// Client areas currently blacked out
+    pub fn redactions(&self) -> &[RedactRect] {
+        self.redactor.as_ref().map_or(&[], Redactor::rects)
+    }
+
+    /// Tint the thumbnail for night mode (Some) or stop tinting it (None)
+    pub fn set_night(&mut self, night: Option<NightModeSettings>) -> Result<()> {
+        if self.night == night {
+            return Ok(());
+        }
+        self.night = night;
+        self.update_color_adjust()
+            .context(format!("Failed to update night tint for '{}'", self.character_name))?;
+        self.refresh_source()
+    }
+
+    fn full_region(&self) -> Rect {
+        Rect { x: 0, y: 0, width: self.dimensions.width, height: self.dimensions.height }
+    }
+
     pub fn border(&self, focused: bool) -> Result<()> {
         if focused {
             self.conn.render_composite(
@@ -504,16 +828,34 @@ impl<'a> Thumbnail<'a> {
         )
         .context(format!("Failed to clear overlay area for '{}'", self.character_name))?;
         
+        self.draw_text(self.label_text(), self.config.text_offset.x, self.config.text_offset.y, true)?;
+        if self.stalled {
+            let y = self.config.text_offset.y + self.font_renderer.size() as i16 * 5 / 4;
+            self.draw_text(stall::LABEL, self.config.text_offset.x, y, false)
+                .context(format!("Failed to draw stalled badge for '{}'", self.character_name))?;
+        }
+        if let Some(stats) = &self.stats {
+            // Mirror the name label's offset from the bottom edge
+            let y = self.dimensions.height as i16 - self.config.text_offset.y - self.font_renderer.size() as i16;
+            self.draw_text(stats, self.config.text_offset.x, y, false)
+                .context(format!("Failed to draw process stats for '{}'", self.character_name))?;
+        }
+        Ok(())
+    }
+
+    /// Draw `text` in the label color onto the overlay, top-left corner at (x, y)
+    /// `background`: put the label box (text_background) behind it
+    fn draw_text(&self, text: &str, x: i16, y: i16, background: bool) -> Result<()> {
         // Render text based on font renderer type
         if self.font_renderer.requires_direct_rendering() {
-            // X11 fallback: direct rendering using ImageText8
+            // X11 fallback: direct rendering using ImageText16 (UCS-2, so non-ASCII names work)
             if let Some(font_id) = self.font_renderer.x11_font_id() {
                 // Create GC with font
                 let gc = self.conn.generate_id()
                     .context("Failed to generate GC ID for X11 text")?;
                 
                 // Convert ARGB color to X11 pixel value (strip alpha)
-                let fg_pixel = self.config.text_color & 0x00FFFFFF;
+                let fg_pixel = self.label_color & 0x00FFFFFF;
                 
                 self.conn.create_gc(gc, self.overlay_pixmap, &CreateGCAux::new()
                     .font(font_id)
@@ -521,13 +863,24 @@ impl<'a> Thumbnail<'a> {
                 )
                 .context(format!("Failed to create GC for X11 text rendering for '{}'", self.character_name))?;
                 
-                // ImageText8 renders directly to drawable
-                self.conn.image_text8(
+                let chars = core_font_text(text);
+                let baseline = y + self.font_renderer.size() as i16;
+                if background && self.config.text_background.is_some() && !chars.is_empty() {
+                    let extents = self.conn.query_text_extents(font_id, &chars)
+                        .context("Failed to send text extents query for label box")?
+                        .reply()
+                        .context(format!("Failed to get text extents for label box of '{}'", self.character_name))?;
+                    let height = (extents.font_ascent + extents.font_descent).max(0) as u16;
+                    self.draw_label_box(x, baseline - extents.font_ascent, extents.overall_width.max(0) as u16, height)?;
+                }
+                
+                // ImageText16 renders directly to drawable
+                self.conn.image_text16(
                     self.overlay_pixmap,
                     gc,
-                    self.config.text_offset.x,
-                    self.config.text_offset.y + self.font_renderer.size() as i16, // Baseline adjustment
-                    self.character_name.as_bytes()
+                    x,
+                    baseline,
+                    &chars
                 )
                 .context(format!("Failed to render X11 text for '{}'", self.character_name))?;
                 
@@ -537,12 +890,16 @@ impl<'a> Thumbnail<'a> {
         } else {
             // Fontdue: pre-rendered bitmap
             let rendered = self.font_renderer.render_text(
-                &self.character_name,
-                self.config.text_color,
+                text,
+                self.label_color,
             )
-            .context(format!("Failed to render text '{}' with font renderer", self.character_name))?;
+            .context(format!("Failed to render text '{}' with font renderer", text))?;
             
             if rendered.width > 0 && rendered.height > 0 {
+                if background {
+                    self.draw_label_box(x, y, rendered.width as u16, rendered.height as u16)?;
+                }
+                
                 // Upload rendered text bitmap to X11
                 let text_pixmap = self.conn.generate_id()
                     .context("Failed to generate ID for text pixmap")?;
@@ -600,8 +957,8 @@ impl<'a> Thumbnail<'a> {
                     0,
                     0,
                     0,
-                    self.config.text_offset.x,
-                    self.config.text_offset.y,
+                    x,
+                    y,
                     rendered.width as u16,
                     rendered.height as u16,
                 )
@@ -618,10 +975,39 @@ impl<'a> Thumbnail<'a> {
         Ok(())
     }
 
-    fn overlay(&self) -> Result<()> {
-        self.conn.render_composite(
+    /// Fill the label box (text_background) around a `width`×`height` label at (x, y), inside the border
+    fn draw_label_box(&self, x: i16, y: i16, width: u16, height: u16) -> Result<()> {
+        let Some(color) = self.config.text_background else {
+            return Ok(());
+        };
+        let padding = self.config.text_background_padding as i16;
+        let inset = self.config.border_size as i16;
+        let left = (x - padding).max(inset);
+        let top = (y - padding).max(inset);
+        let right = (x + width as i16 + padding).min(self.dimensions.width as i16 - inset);
+        let bottom = (y + height as i16 + padding).min(self.dimensions.height as i16 - inset);
+        if right <= left || bottom <= top {
+            return Ok(());
+        }
+        self.conn.render_fill_rectangles(
             PictOp::OVER,
             self.overlay_picture,
+            color,
+            &[Rectangle { x: left, y: top, width: (right - left) as u16, height: (bottom - top) as u16 }],
+        )
+        .context(format!("Failed to draw label box for '{}'", self.character_name))?;
+        Ok(())
+    }
+
+    /// Drop the colour from the drawn frame (HSL saturation of a grey fill is zero)
+    fn desaturate(&self) -> Result<()> {
+        let grey = self.conn.generate_id()
+            .context("Failed to generate ID for desaturation fill")?;
+        self.conn.render_create_solid_fill(grey, Color { red: 0x8000, green: 0x8000, blue: 0x8000, alpha: 0xFFFF })
+            .context(format!("Failed to create desaturation fill for '{}'", self.character_name))?;
+        self.conn.render_composite(
+            PictOp::HSL_SATURATION,
+            grey,
             0u32,
             self.dst_picture,
             0,
@@ -633,6 +1019,31 @@ impl<'a> Thumbnail<'a> {
             self.dimensions.width,
             self.dimensions.height,
         )
+        .context(format!("Failed to desaturate '{}'", self.character_name))?;
+        self.conn.render_free_picture(grey)
+            .context("Failed to free desaturation fill")?;
+        Ok(())
+    }
+
+    fn overlay(&self) -> Result<()> {
+        self.overlay_region(self.full_region())
+    }
+
+    fn overlay_region(&self, region: Rect) -> Result<()> {
+        self.conn.render_composite(
+            PictOp::OVER,
+            self.overlay_picture,
+            0u32,
+            self.dst_picture,
+            region.x,
+            region.y,
+            0,
+            0,
+            region.x,
+            region.y,
+            region.width,
+            region.height,
+        )
         .context(format!("Failed to composite overlay onto destination for '{}'", self.character_name))?;
         Ok(())
     }
@@ -640,11 +1051,64 @@ impl<'a> Thumbnail<'a> {
     pub fn update(&self) -> Result<()> {
         self.capture()
             .context(format!("Failed to capture source window for '{}'", self.character_name))?;
+        // A GL draw gets its overlay once the frame is finished (finish_gl_frame)
+        #[cfg(feature = "gl")]
+        if self.gl_pending.get() {
+            return Ok(());
+        }
+        self.decorate()
+    }
+
+    /// The OpenGL frame with this thumbnail's draw is done: put the overlay on
+    /// If the frame failed, the source is scaled with XRender instead
+    #[cfg(feature = "gl")]
+    pub fn finish_gl_frame(&self, drawn: bool) -> Result<()> {
+        if !self.gl_pending.replace(false) {
+            return Ok(());
+        }
+        if !drawn {
+            let geom = self.conn.get_geometry(self.src)
+                .context("Failed to send geometry query for source window")?
+                .reply()
+                .context(format!("Failed to get geometry for source window (character: '{}')", self.character_name))?;
+            self.capture_xrender(Dimensions::new(geom.width, geom.height))
+                .context(format!("Failed to capture source window for '{}'", self.character_name))?;
+        }
+        self.decorate()
+    }
+
+    /// Grey out a stalled client and draw the border/name overlay over a fresh frame
+    fn decorate(&self) -> Result<()> {
+        if self.stalled {
+            self.desaturate()
+                .context(format!("Failed to grey out stalled thumbnail for '{}'", self.character_name))?;
+        }
         self.overlay()
             .context(format!("Failed to apply overlay for '{}'", self.character_name))?;
         Ok(())
     }
 
+    /// Redraw only the part of the thumbnail showing a raw damage rectangle of the client
+    /// The rest of the window still holds the last scaled frame; anything that changed
+    /// the scaling since (client or thumbnail resized, window unmapped) gets a full update
+    pub fn update_damaged(&self, area: Rectangle, geometry: Rectangle) -> Result<()> {
+        let source = Dimensions::new(geometry.width, geometry.height);
+        let cached = self.damage_level == DamageReportLevel::RAW_RECTANGLES
+            && !self.stalled
+            && self.scaled_for.get() == Some((source, self.dimensions));
+        if !cached {
+            return self.update();
+        }
+        let Some(region) = damage_region::scaled_region(area, source, self.dimensions) else {
+            return Ok(());
+        };
+        self.scale_source(source, region)
+            .context(format!("Failed to rescale damaged region for '{}'", self.character_name))?;
+        self.overlay_region(region)
+            .context(format!("Failed to apply overlay for '{}'", self.character_name))?;
+        Ok(())
+    }
+
     pub fn focus(&self) -> Result<()> {
         let net_active = self
             .conn
@@ -665,7 +1129,7 @@ impl<'a> Thumbnail<'a> {
 
         self.conn.send_event(
             false,
-            self.root,
+            self.screen.root,
             EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
             ev,
         )
@@ -677,20 +1141,549 @@ impl<'a> Thumbnail<'a> {
     }
 
     pub fn reposition(&mut self, x: i16, y: i16) -> Result<()> {
+        // Inside the panel only the saved position changes; the panel decides where it's drawn
+        if self.panel.is_some() {
+            self.identity.set_position(Position::new(x, y));
+            return Ok(());
+        }
         self.conn.configure_window(
             self.window,
             &ConfigureWindowAux::new().x(x as i32).y(y as i32),
         )
         .context(format!("Failed to reposition window for '{}' to ({}, {})", self.character_name, x, y))?;
+        x11_trace::request(self.src, "ConfigureWindow", format_args!("thumbnail={:#x} x={} y={}", self.window, x, y));
+        self.identity.set_position(Position::new(x, y));
+        self.update_color_adjust()
+            .context(format!("Failed to update colour correction for '{}'", self.character_name))?;
+        // The wallpaper behind the thumbnail changed, partial redraws would leave seams
+        if self.opacity_blend.is_some() {
+            self.scaled_for.set(None);
+        }
+        if let Some(selection) = &mut self.input_state.selection {
+            selection.move_to(self.conn, x, y)
+                .context(format!("Failed to move selection outline for '{}'", self.character_name))?;
+        }
         self.conn.flush()
             .context("Failed to flush X11 connection after reposition")?;
         Ok(())
     }
 
+    /// Hide or show this character's preview independently of focus/minimize state
+    pub fn set_preview_hidden(&mut self, hidden: bool) -> Result<()> {
+        if self.preview_hidden == hidden {
+            return Ok(());
+        }
+        self.preview_hidden = hidden;
+        if hidden {
+            self.scaled_for.set(None);
+            self.conn.unmap_window(self.window)
+                .context(format!("Failed to unmap hidden preview for '{}'", self.character_name))?;
+        } else if self.state.is_visible() {
+            self.conn.map_window(self.window)
+                .context(format!("Failed to map preview for '{}'", self.character_name))?;
+        }
+        self.conn.flush()
+            .context("Failed to flush X11 connection after changing preview visibility")?;
+        info!(character = %self.character_name, hidden = hidden, "Changed preview visibility");
+        Ok(())
+    }
+
+    /// Text drawn on the name label (label_template, else the alias if configured, else the character name)
+    fn label_text(&self) -> &str {
+        self.label.as_deref().unwrap_or_else(|| self.alias.as_deref().unwrap_or(&self.character_name))
+    }
+
+    /// Apply this character's configured overrides (visibility, label, border color) and redraw
+    /// `None` restores the profile defaults, e.g. after switching to an unconfigured character
+    pub fn apply_character_settings(&mut self, settings: Option<&CharacterSettings>) -> Result<()> {
+        self.set_preview_hidden(settings.is_some_and(|s| s.preview_hidden))?;
+        self.set_panel_detached(settings.is_some_and(|s| s.panel_detached))?;
+        self.set_redactions(settings.map(|s| s.redactions.as_slice()).unwrap_or_default())?;
+        self.label_color = settings.map_or(self.config.text_color, |s| s.label_argb(self.config.text_color));
+        self.alias = settings
+            .map(|s| s.label_text(&self.character_name))
+            .filter(|text| *text != self.character_name)
+            .map(str::to_string);
+
+        let border_color = settings
+            .and_then(|s| s.border_color.as_deref())
+            .and_then(HexColor::parse)
+            .map_or(self.config.border_color, HexColor::to_x11_color);
+        self.conn.render_free_picture(self.border_fill)
+            .context(format!("Failed to free border fill for '{}'", self.character_name))?;
+        self.conn.render_create_solid_fill(self.border_fill, border_color)
+            .context(format!("Failed to create border fill for '{}'", self.character_name))?;
+
+        // Redraws border and name label
+        self.border(self.state.is_focused())
+            .context(format!("Failed to redraw overlay for '{}'", self.character_name))?;
+        self.conn.flush()
+            .context("Failed to flush X11 connection after applying character settings")?;
+        Ok(())
+    }
+
+    /// Resize the thumbnail window and recreate the overlay at the new size
+    pub fn resize(&mut self, dimensions: Dimensions) -> Result<()> {
+        self.identity.set_dimensions(dimensions);
+        let mut dimensions = if self.low_bandwidth {
+            dimensions.capped(low_bandwidth::MAX_WIDTH, low_bandwidth::MAX_HEIGHT)
+        } else {
+            dimensions
+        };
+        if let Some(cap) = self.layout_cap {
+            dimensions = dimensions.capped(cap.width, cap.height);
+        }
+        if dimensions == self.dimensions {
+            return Ok(());
+        }
+        self.conn.configure_window(
+            self.window,
+            &ConfigureWindowAux::new()
+                .width(dimensions.width as u32)
+                .height(dimensions.height as u32),
+        )
+        .context(format!("Failed to resize thumbnail for '{}'", self.character_name))?;
+        x11_trace::request(self.src, "ConfigureWindow", format_args!("thumbnail={:#x} width={} height={}", self.window, dimensions.width, dimensions.height));
+
+        self.conn.render_free_picture(self.overlay_picture)
+            .context(format!("Failed to free overlay picture for '{}'", self.character_name))?;
+        self.conn.free_pixmap(self.overlay_pixmap)
+            .context(format!("Failed to free overlay pixmap for '{}'", self.character_name))?;
+        self.conn.create_pixmap(x11::ARGB_DEPTH, self.overlay_pixmap, self.screen.root, dimensions.width, dimensions.height)
+            .context(format!("Failed to create overlay pixmap for '{}'", self.character_name))?;
+        self.conn.render_create_picture(
+            self.overlay_picture,
+            self.overlay_pixmap,
+            get_pictformat(self.conn, x11::ARGB_DEPTH, true)
+                .context("Failed to get ARGB picture format for overlay")?,
+            &CreatePictureAux::new(),
+        )
+        .context(format!("Failed to create overlay picture for '{}'", self.character_name))?;
+        self.dimensions = dimensions;
+        if let Some(pixelate) = self.pixelate.take() {
+            pixelate.free(self.conn)?;
+            self.pixelate = Some(Pixelate::new(self.conn, self.screen, dimensions)
+                .context(format!("Failed to resize pixelation for '{}'", self.character_name))?);
+        }
+
+        self.border(self.state.is_focused())
+            .context(format!("Failed to redraw overlay after resize for '{}'", self.character_name))?;
+        self.update()
+            .context(format!("Failed to redraw thumbnail after resize for '{}'", self.character_name))?;
+        self.conn.flush()
+            .context("Failed to flush X11 connection after resize")?;
+        info!(character = %self.character_name, width = dimensions.width, height = dimensions.height, "Resized thumbnail");
+        Ok(())
+    }
+
+    /// Recreate the damage tracking and source picture after the client was reparented or remapped
+    /// Some WMs do this when toggling decorations, which leaves the old binding rendering black
+    /// Filter the source picture is sampled with when scaled (scale_filter)
+    fn set_scale_filter(conn: &RustConnection, picture: Picture, filter: ScaleFilter, character_name: &str) -> Result<()> {
+        let name: &[u8] = match filter {
+            // New pictures already use it
+            ScaleFilter::Nearest => return Ok(()),
+            ScaleFilter::Bilinear => b"bilinear",
+            ScaleFilter::Best => b"best",
+        };
+        conn.render_set_picture_filter(picture, name, &[])
+            .context(format!("Failed to set scale filter for '{}'", character_name))?;
+        Ok(())
+    }
+
+    pub fn rebind_source(&mut self) -> Result<()> {
+        let geom = self.conn.get_geometry(self.src)
+            .context("Failed to send geometry query for source window")?
+            .reply()
+            .context(format!("Failed to get geometry for source window (character: '{}')", self.character_name))?;
+
+        self.conn.damage_destroy(self.damage)
+            .context(format!("Failed to destroy damage tracking for '{}'", self.character_name))?;
+        self.conn.damage_create(self.damage, self.src, self.damage_level)
+            .context(format!("Failed to recreate damage tracking for '{}'", self.character_name))?;
+        x11_trace::request(self.src, "DamageCreate", format_args!("damage={:#x} drawable={:#x} level={:?} (rebind)", self.damage, self.src, self.damage_level));
+
+        self.conn.render_free_picture(self.src_picture)
+            .context(format!("Failed to free source picture for '{}'", self.character_name))?;
+        self.conn.render_create_picture(
+            self.src_picture,
+            self.src,
+            get_pictformat(self.conn, geom.depth, false)
+                .context("Failed to get picture format for source window")?,
+            &CreatePictureAux::new(),
+        )
+        .context(format!("Failed to recreate source picture for '{}'", self.character_name))?;
+        x11_trace::request(self.src, "RenderCreatePicture", format_args!("picture={:#x} drawable={:#x} (depth {}, rebind)", self.src_picture, self.src, geom.depth));
+        Self::set_scale_filter(self.conn, self.src_picture, self.config.scale_filter, &self.character_name)?;
+        #[cfg(feature = "gl")]
+        if let Some(surface) = &self.gl {
+            surface.release_source();
+        }
+
+        if self.is_displayed() && !self.state.is_minimized() {
+            self.update()
+                .context(format!("Failed to redraw thumbnail after rebinding '{}'", self.character_name))?;
+        }
+        self.conn.flush()
+            .context("Failed to flush X11 connection after rebinding source")?;
+        info!(window = self.src, character = %self.character_name, "Rebound thumbnail to source window");
+        Ok(())
+    }
+
+    /// Redirect the client ourselves so a compositor unredirecting it can't leave
+    /// the source reading the screen; automatic redirection ends with our connection
+    pub fn keep_redirected(&mut self) -> Result<()> {
+        if self.redirected {
+            return Ok(());
+        }
+        #[cfg(feature = "gl")]
+        if self.gl.is_some() {
+            self.redirected = true;
+            return Ok(());
+        }
+        self.conn.composite_redirect_window(self.src, Redirect::AUTOMATIC)
+            .context(format!("Failed to send redirect request for '{}'", self.character_name))?
+            .check()
+            .context(format!("Failed to redirect client of '{}'", self.character_name))?;
+        x11_trace::request(self.src, "CompositeRedirectWindow", format_args!("automatic"));
+        self.redirected = true;
+        Ok(())
+    }
+
+    pub fn is_redirected(&self) -> bool {
+        self.redirected
+    }
+
+    /// Check the X resources against what the thumbnail believes (watchdog), None = consistent
+    pub fn health(&self) -> Result<Option<Problem>> {
+        let source = match self.conn.get_geometry(self.src)
+            .context("Failed to send geometry query for source window")?
+            .reply()
+        {
+            Ok(geometry) => geometry,
+            Err(ReplyError::X11Error(_)) => return Ok(Some(Problem::SourceGone)),
+            Err(e) => return Err(e).context(format!("Failed to get source geometry for '{}'", self.character_name)),
+        };
+        let window = match self.conn.get_geometry(self.window)
+            .context("Failed to send geometry query for thumbnail window")?
+            .reply()
+        {
+            Ok(geometry) => geometry,
+            Err(ReplyError::X11Error(_)) => return Ok(Some(Problem::Broken)),
+            Err(e) => return Err(e).context(format!("Failed to get thumbnail window geometry for '{}'", self.character_name)),
+        };
+        if let Err(ReplyError::X11Error(_)) = self.conn.get_geometry(self.overlay_pixmap)
+            .context("Failed to send geometry query for overlay pixmap")?
+            .reply()
+        {
+            return Ok(Some(Problem::Broken));
+        }
+        if Dimensions::new(window.width, window.height) != self.dimensions {
+            return Ok(Some(Problem::WrongSize));
+        }
+        let source = Dimensions::new(source.width, source.height);
+        if self.scaled_for.get().is_some_and(|(scaled, _)| scaled != source) {
+            return Ok(Some(Problem::StaleSource));
+        }
+        Ok(None)
+    }
+
+    /// Whether `resource` (from an X error's bad value) is one this thumbnail created
+    /// The client window itself isn't included; errors on it mean the client went away
+    pub fn owns_resource(&self, resource: u32) -> bool {
+        [
+            self.window,
+            self.damage,
+            self.border_fill,
+            self.src_picture,
+            self.dst_picture,
+            self.overlay_gc,
+            self.overlay_pixmap,
+            self.overlay_picture,
+        ]
+        .contains(&resource)
+    }
+
+    /// Redraw after the source window was moved/resized (a resize replaces its pixmap)
+    pub fn refresh_source(&self) -> Result<()> {
+        #[cfg(feature = "gl")]
+        if let Some(surface) = &self.gl {
+            surface.release_source();
+        }
+        if self.is_displayed() && !self.state.is_minimized() {
+            self.update()
+                .context(format!("Failed to redraw thumbnail after '{}' was configured", self.character_name))?;
+        }
+        self.conn.flush()
+            .context("Failed to flush X11 connection after refreshing source")?;
+        Ok(())
+    }
+
+    /// Draw the source through the OpenGL backend from now on (XRender stays the fallback)
+    #[cfg(feature = "gl")]
+    pub fn use_gl(&mut self, backend: &'a GlBackend) -> Result<()> {
+        self.gl = Some(GlSurface::new(backend, self.conn, self.window, self.src)
+            .context(format!("Failed to set up OpenGL drawing for '{}'", self.character_name))?);
+        info!(character = %self.character_name, "Thumbnail uses OpenGL backend");
+        Ok(())
+    }
+
+    /// Make the thumbnail window a child of the panel (panel mode); the panel places it
+    pub fn attach_to_panel(&mut self, panel: Window) -> Result<()> {
+        // Selection outlines live on the root window and mean nothing inside the panel
+        if let Some(mut outline) = self.input_state.selection.take() {
+            drag_guides::hide(self.conn, &mut outline.windows);
+        }
+        self.conn.reparent_window(self.window, panel, 0, 0)
+            .context(format!("Failed to reparent '{}' into panel {}", self.character_name, panel))?;
+        self.panel = Some(panel);
+        Ok(())
+    }
+
+    /// Limit the drawn size for the panel layout (None = draw the requested size)
+    pub fn set_layout_cap(&mut self, cap: Option<Dimensions>) -> Result<()> {
+        if self.layout_cap == cap {
+            return Ok(());
+        }
+        self.layout_cap = cap;
+        self.resize(self.identity.dimensions())
+    }
+
+    /// Make the thumbnail a free-floating top-level again, at its saved position
+    pub fn detach_from_panel(&mut self) -> Result<()> {
+        if self.panel.take().is_none() {
+            return Ok(());
+        }
+        self.set_layout_cap(None)?;
+        let Position { x, y } = self.identity.position();
+        self.conn.reparent_window(self.window, self.screen.root, x, y)
+            .context(format!("Failed to reparent '{}' back to the root window", self.character_name))?;
+        Ok(())
+    }
+
+    /// Mark the client as stalled (no frames) or drawing again; redrawn right away unless minimized
+    pub fn set_stalled(&mut self, stalled: bool) -> Result<()> {
+        if self.stalled == stalled {
+            return Ok(());
+        }
+        self.stalled = stalled;
+        if self.state.is_minimized() {
+            return Ok(());
+        }
+        self.border(self.state.is_focused())
+            .context(format!("Failed to redraw overlay for stalled state of '{}'", self.character_name))?;
+        if self.is_displayed() {
+            self.update()?;
+        }
+        self.conn.flush()
+            .context("Failed to flush X11 connection after marking stalled client")?;
+        Ok(())
+    }
+
+    /// Show the hover tooltip with the client's full details (no-op if already shown)
+    pub fn show_tooltip(&mut self) -> Result<()> {
+        if self.tooltip.is_some() {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let geom = self.conn.get_geometry(self.src)
+            .context("Failed to send geometry query for source window")?
+            .reply()
+            .context(format!("Failed to get geometry for source window (character: '{}')", self.character_name))?;
+        let client = self.conn.translate_coordinates(self.src, self.screen.root, 0, 0)
+            .context("Failed to send coordinate translation for source window")?
+            .reply()
+            .context(format!("Failed to get root position of source window (character: '{}')", self.character_name))?;
+        // Panel children report geometry relative to the panel, so ask for the root position
+        let origin = self.conn.translate_coordinates(self.window, self.screen.root, 0, 0)
+            .context("Failed to send coordinate translation for thumbnail")?
+            .reply()
+            .context(format!("Failed to get root position of thumbnail for '{}'", self.character_name))?;
+
+        let name = if self.character_name.is_empty() { "Logged out" } else { &self.character_name };
+        let lines = [
+            name.to_string(),
+            format!("Window: {}x{} at {},{}", geom.width, geom.height, client.dst_x, client.dst_y),
+            format!("FPS: {:.0}", self.frames.fps(now)),
+            format!("Last update: {}", tooltip::format_age(self.frames.last(), now)),
+        ];
+        let anchor = Rect {
+            x: origin.dst_x,
+            y: origin.dst_y,
+            width: self.dimensions.width,
+            height: self.dimensions.height,
+        };
+        self.tooltip = Some(Tooltip::show(self.conn, self.screen, self.font_renderer, &lines, Placement::Near(anchor))
+            .context(format!("Failed to show tooltip for '{}'", self.character_name))?);
+        Ok(())
+    }
+
+    pub fn hide_tooltip(&mut self) -> Result<()> {
+        if let Some(tooltip) = self.tooltip.take() {
+            tooltip.close(self.conn)
+                .context(format!("Failed to close tooltip for '{}'", self.character_name))?;
+        }
+        Ok(())
+    }
+
+    /// Show (Some) or remove the CPU/RAM readout; redrawn right away unless minimized
+    pub fn set_stats(&mut self, stats: Option<String>) -> Result<()> {
+        if self.stats == stats {
+            return Ok(());
+        }
+        self.stats = stats;
+        // Redrawing would wipe the MINIMIZED text, the readout shows up once restored
+        if self.state.is_minimized() {
+            return Ok(());
+        }
+        self.border(self.state.is_focused())
+            .context(format!("Failed to redraw overlay with process stats for '{}'", self.character_name))?;
+        if self.is_displayed() {
+            self.update()?;
+        }
+        self.conn.flush()
+            .context("Failed to flush X11 connection after updating process stats")?;
+        Ok(())
+    }
+
+    /// Expand the profile's label_template for this thumbnail, `index` being its cycle number
+    /// Redrawn when the text changes, unless minimized
+    pub fn set_label_template(&mut self, template: &str, index: Option<usize>) -> Result<()> {
+        let status = if self.character_name.is_empty() {
+            label_template::STATUS_LOGGED_OUT
+        } else if self.stalled {
+            label_template::STATUS_STALLED
+        } else {
+            ""
+        };
+        let fields = LabelFields {
+            character: self.alias.as_deref().unwrap_or(&self.character_name),
+            name: &self.character_name,
+            index,
+            status,
+        };
+        let label = (!template.is_empty()).then(|| label_template::expand(template, &fields));
+        if self.label == label {
+            return Ok(());
+        }
+        self.label = label;
+        if self.state.is_minimized() {
+            return Ok(());
+        }
+        self.border(self.state.is_focused())
+            .context(format!("Failed to redraw overlay with new label for '{}'", self.character_name))?;
+        if self.is_displayed() {
+            self.update()?;
+        }
+        self.conn.flush()
+            .context("Failed to flush X11 connection after updating label")?;
+        Ok(())
+    }
+
+    /// Keep the thumbnail out of the panel (true) or let the panel adopt it again on its next layout
+    pub fn set_panel_detached(&mut self, detached: bool) -> Result<()> {
+        self.panel_detached = detached;
+        if detached {
+            self.detach_from_panel()?;
+        }
+        Ok(())
+    }
+
+    /// Start a drag out of the panel: the thumbnail becomes a top-level where it is drawn now
+    /// Its saved free-floating position is kept in `input_state.from_panel` until the drop
+    pub fn float_out_of_panel(&mut self) -> Result<()> {
+        let Some(panel) = self.panel else {
+            return Ok(());
+        };
+        let origin = self.conn.translate_coordinates(self.window, self.screen.root, 0, 0)
+            .context("Failed to send coordinate translation for panel drag")?
+            .reply()
+            .context(format!("Failed to get root position of '{}' in panel {}", self.character_name, panel))?;
+        self.input_state.from_panel = Some(self.identity.position());
+        self.panel = None;
+        self.panel_detached = true;
+        self.set_layout_cap(None)?;
+        self.conn.reparent_window(self.window, self.screen.root, origin.dst_x, origin.dst_y)
+            .context(format!("Failed to reparent '{}' out of the panel", self.character_name))?;
+        self.identity.set_position(Position::new(origin.dst_x, origin.dst_y));
+        self.conn.flush()
+            .context("Failed to flush X11 connection after leaving the panel")?;
+        debug!(character = %self.character_name, x = origin.dst_x, y = origin.dst_y, "Dragging thumbnail out of the panel");
+        Ok(())
+    }
+
+    /// Move the thumbnail within its panel (layout only, nothing is saved; caller flushes)
+    pub fn place_in_panel(&self, position: Position) -> Result<()> {
+        self.conn.configure_window(
+            self.window,
+            &ConfigureWindowAux::new().x(position.x as i32).y(position.y as i32),
+        )
+        .context(format!("Failed to place '{}' in panel at ({}, {})", self.character_name, position.x, position.y))?;
+        Ok(())
+    }
+
+    /// Whether the thumbnail is actually on screen (state allows it and not hidden by config)
+    pub fn is_displayed(&self) -> bool {
+        !self.preview_hidden && self.state.is_visible()
+    }
+
+    /// Follow a drag to (x, y): moves the ghost outline if there is one, else the thumbnail
+    pub fn drag_to(&mut self, x: i16, y: i16) -> Result<()> {
+        match &mut self.input_state.ghost {
+            Some(ghost) => ghost.move_to(self.conn, x, y)
+                .context(format!("Failed to move drag outline for '{}'", self.character_name)),
+            None => self.reposition(x, y),
+        }
+    }
+
+    /// Drop the ghost outline (if any) and move the thumbnail to where it was left
+    pub fn land_ghost(&mut self) -> Result<()> {
+        let Some(mut ghost) = self.input_state.ghost.take() else {
+            return Ok(());
+        };
+        drag_guides::hide(self.conn, &mut ghost.windows);
+        self.reposition(ghost.position.x, ghost.position.y)
+    }
+
+    /// Enter or leave redaction edit mode, dropping an area still being drawn
+    pub fn set_redact_edit(&mut self, enabled: bool) {
+        self.input_state.redact_edit = enabled;
+        if !enabled
+            && let Some(mut draw) = self.input_state.redact_draw.take()
+        {
+            drag_guides::hide(self.conn, &mut draw.outline.windows);
+        }
+    }
+
+    /// Move the thumbnail to the top of the stacking order (caller flushes)
+    pub fn raise(&self) -> Result<()> {
+        self.conn.configure_window(
+            self.window,
+            &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
+        )
+        .context(format!("Failed to raise thumbnail for '{}'", self.character_name))?;
+        if let Some(selection) = &self.input_state.selection {
+            selection.raise(self.conn)
+                .context(format!("Failed to raise selection outline for '{}'", self.character_name))?;
+        }
+        Ok(())
+    }
+
+    /// Stack the thumbnail right under `sibling`, a top-level window (caller flushes)
+    pub fn lower_beneath(&self, sibling: Window) -> Result<()> {
+        self.conn.configure_window(
+            self.window,
+            &ConfigureWindowAux::new().sibling(sibling).stack_mode(StackMode::BELOW),
+        )
+        .context(format!("Failed to stack thumbnail for '{}' below window {}", self.character_name, sibling))?;
+        Ok(())
+    }
+
     /// Called when character name changes (login/logout)
     /// Updates name and optionally moves to new position
     pub fn set_character_name(&mut self, new_name: String, new_position: Option<Position>) -> Result<()> {
         self.character_name = new_name;
+        self.duplicate_of = None;
         self.update_name()
             .context(format!("Failed to update name overlay to '{}'", self.character_name))?;
         
@@ -701,11 +1694,45 @@ impl<'a> Thumbnail<'a> {
         Ok(())
     }
 
+    /// Give up the character name to a newer client of the same character
+    /// The thumbnail stays up, labelled with a suffix naming its window
+    pub fn mark_duplicate(&mut self) -> Result<()> {
+        let original = std::mem::take(&mut self.character_name);
+        self.character_name = format!("{} (duplicate {:#x})", original, self.src);
+        self.duplicate_of = Some(original);
+        self.alias = None;
+        self.update_name()
+            .context(format!("Failed to relabel duplicate thumbnail '{}'", self.character_name))
+    }
+
+    /// Take the real character name back after the newer client went away
+    pub fn restore_duplicate(&mut self) -> Result<()> {
+        if let Some(original) = self.duplicate_of.take() {
+            self.character_name = original;
+            self.update_name()
+                .context(format!("Failed to relabel restored thumbnail '{}'", self.character_name))?;
+        }
+        Ok(())
+    }
+
     pub fn is_hovered(&self, x: i16, y: i16) -> bool {
+        if self.preview_hidden {
+            return false;
+        }
+        // Panel children report geometry relative to the panel, so ask for the root position
+        if self.panel.is_some() {
+            if let Ok(req) = self.conn.translate_coordinates(self.window, self.screen.root, 0, 0)
+                && let Ok(origin) = req.reply()
+            {
+                return x >= origin.dst_x
+                    && x <= origin.dst_x + self.dimensions.width as i16
+                    && y >= origin.dst_y
+                    && y <= origin.dst_y + self.dimensions.height as i16;
+            }
+            return false;
+        }
         // Query actual window geometry to avoid desync when compositor moves window
-        if let Ok(req) = self.conn.get_geometry(self.window)
-            && let Ok(geom) = req.reply()
-        {
+        if let Ok(geom) = root_geometry(self.conn, self.window) {
             return x >= geom.x
                 && x <= geom.x + geom.width as i16
                 && y >= geom.y
@@ -720,6 +1747,27 @@ impl Drop for Thumbnail<'_> {
         // Clean up each resource independently to prevent cascade failures
         // If one cleanup fails, we still attempt to clean up the rest
         
+        // GLX drawable goes first, while the thumbnail window still exists
+        #[cfg(feature = "gl")]
+        drop(self.gl.take());
+        
+        if let Some(tooltip) = self.tooltip.take()
+            && let Err(e) = tooltip.close(self.conn)
+        {
+            error!(error = %e, "Failed to close tooltip");
+        }
+        
+        drag_guides::hide(self.conn, &mut self.input_state.guides);
+        let drawing = self.input_state.redact_draw.take().map(|draw| draw.outline);
+        for mut outline in [self.input_state.ghost.take(), self.input_state.selection.take(), drawing].into_iter().flatten() {
+            drag_guides::hide(self.conn, &mut outline.windows);
+        }
+        if let Some(keycode) = self.input_state.cancel_key.take()
+            && let Err(e) = self.conn.ungrab_key(keycode, self.screen.root, ModMask::ANY)
+        {
+            error!(keycode = keycode, error = %e, "Failed to release drag cancel key grab");
+        }
+        
         if let Err(e) = self.conn.damage_destroy(self.damage) {
             error!(damage = self.damage, error = %e, "Failed to destroy damage");
         }
@@ -740,6 +1788,30 @@ impl Drop for Thumbnail<'_> {
             error!(picture = self.dst_picture, error = %e, "Failed to free destination picture");
         }
         
+        if let Some(pixelate) = &self.pixelate
+            && let Err(e) = pixelate.free(self.conn)
+        {
+            error!(error = %e, "Failed to free pixelation buffer");
+        }
+        
+        if let Some(redactor) = &self.redactor
+            && let Err(e) = redactor.free(self.conn)
+        {
+            error!(error = %e, "Failed to free redaction fill");
+        }
+        
+        if let Some(adjust) = &self.color_adjust
+            && let Err(e) = adjust.free(self.conn)
+        {
+            error!(error = %e, "Failed to free colour correction fills");
+        }
+        
+        if let Some(blend) = &self.opacity_blend
+            && let Err(e) = blend.free(self.conn)
+        {
+            error!(error = %e, "Failed to free opacity blend pictures");
+        }
+        
         if let Err(e) = self.conn.render_free_picture(self.border_fill) {
             error!(picture = self.border_fill, error = %e, "Failed to free border fill picture");
         }
@@ -0,0 +1,333 @@
+//! Hover tooltip with the full details of a thumbnail's client
+//!
+//! Shown next to a hovered thumbnail (hover_tooltip setting): full character
+//! name, client window geometry, an estimate of the client's frame rate and
+//! when it last drew. It is a small override-redirect window, drawn once when
+//! it appears, with the label font on a dark background. The chord hint, the
+//! switch OSD and the cycle strip are the same kind of window.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+
+use crate::constants::tooltip;
+use crate::preview::font::{core_font_text, FontRenderer};
+
+use super::snapping::Rect;
+
+/// Client frame rate estimate from DamageNotify timestamps
+#[derive(Debug, Default)]
+pub struct FrameMeter {
+    /// Frames within the last FPS window, oldest first
+    recent: VecDeque<Instant>,
+}
+
+impl FrameMeter {
+    /// The client drew a frame
+    pub fn record(&mut self, now: Instant) {
+        self.recent.push_back(now);
+        self.trim(now);
+    }
+
+    fn trim(&mut self, now: Instant) {
+        let window = Duration::from_millis(tooltip::FPS_WINDOW_MS);
+        while self.recent.front().is_some_and(|frame| now.duration_since(*frame) > window) {
+            self.recent.pop_front();
+        }
+    }
+
+    /// Frames per second over the last FPS window
+    pub fn fps(&self, now: Instant) -> f32 {
+        let window = Duration::from_millis(tooltip::FPS_WINDOW_MS);
+        let frames = self.recent.iter().filter(|frame| now.duration_since(**frame) <= window).count();
+        frames as f32 / window.as_secs_f32()
+    }
+
+    /// When the client last drew (None = not since the thumbnail was created)
+    pub fn last(&self) -> Option<Instant> {
+        self.recent.back().copied()
+    }
+}
+
+/// "0.4 s ago" style age of the last frame
+pub fn format_age(last: Option<Instant>, now: Instant) -> String {
+    match last {
+        Some(last) => format!("{:.1} s ago", now.saturating_duration_since(last).as_secs_f32()),
+        None => "never".to_string(),
+    }
+}
+
+/// Where a tooltip window goes
+#[derive(Debug, Clone, Copy)]
+pub enum Placement {
+    /// Below the rect (above it if there's no room below), never overlapping it
+    Near(Rect),
+    /// Middle of the screen
+    Centered,
+}
+
+/// Top-left corner of a `width` x `height` tooltip on a `screen` sized screen
+fn place(placement: Placement, width: u16, height: u16, screen: (u16, u16)) -> (i16, i16) {
+    let (screen_width, screen_height) = (screen.0 as i16, screen.1 as i16);
+    match placement {
+        Placement::Near(anchor) => {
+            let padding = tooltip::PADDING as i16;
+            let x = anchor.x.min(screen_width - width as i16).max(0);
+            let below = anchor.y + anchor.height as i16 + padding;
+            let y = if below + height as i16 <= screen_height {
+                below
+            } else {
+                anchor.y - padding - height as i16
+            };
+            (x, y)
+        }
+        Placement::Centered => ((screen_width - width as i16) / 2, (screen_height - height as i16) / 2),
+    }
+}
+
+/// How the text items sit in the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Arrangement {
+    /// One item per line
+    Lines,
+    /// Side by side, `highlight` on a lighter background
+    Strip { highlight: usize },
+}
+
+/// Each item's cell inside the window, how far the text is inset in its cell,
+/// and the window size, for items `widths` wide
+fn layout(widths: &[u16], line_height: u16, arrangement: Arrangement) -> (Vec<Rect>, u16, u16, u16) {
+    let padding = tooltip::PADDING;
+    match arrangement {
+        Arrangement::Lines => {
+            let cells = widths.iter()
+                .enumerate()
+                .map(|(i, width)| Rect {
+                    x: padding as i16,
+                    y: (padding + line_height * i as u16) as i16,
+                    width: *width,
+                    height: line_height,
+                })
+                .collect();
+            let width = widths.iter().copied().max().unwrap_or(0) + padding * 2;
+            let height = line_height * widths.len() as u16 + padding * 2;
+            (cells, 0, width.max(1), height.max(1))
+        }
+        Arrangement::Strip { .. } => {
+            let mut x = padding;
+            let cells = widths.iter()
+                .map(|width| {
+                    let cell = Rect { x: x as i16, y: padding as i16, width: width + padding * 2, height: line_height + padding * 2 };
+                    x += cell.width;
+                    cell
+                })
+                .collect();
+            (cells, padding, (x + padding).max(1), line_height + padding * 4)
+        }
+    }
+}
+
+/// A tooltip window on screen (destroyed with `close`)
+#[derive(Debug)]
+pub struct Tooltip {
+    window: Window,
+}
+
+impl Tooltip {
+    /// Draw `lines` in a new tooltip window; next to a thumbnail it never
+    /// overlaps it, so the pointer stays on the thumbnail
+    pub fn show(
+        conn: &RustConnection,
+        screen: &Screen,
+        font_renderer: &FontRenderer,
+        lines: &[String],
+        placement: Placement,
+    ) -> Result<Self> {
+        Self::create(conn, screen, font_renderer, lines, Arrangement::Lines, placement)
+    }
+
+    /// Draw `items` side by side with `highlight` standing out
+    pub fn show_strip(
+        conn: &RustConnection,
+        screen: &Screen,
+        font_renderer: &FontRenderer,
+        items: &[String],
+        highlight: usize,
+        placement: Placement,
+    ) -> Result<Self> {
+        Self::create(conn, screen, font_renderer, items, Arrangement::Strip { highlight }, placement)
+    }
+
+    fn create(
+        conn: &RustConnection,
+        screen: &Screen,
+        font_renderer: &FontRenderer,
+        items: &[String],
+        arrangement: Arrangement,
+        placement: Placement,
+    ) -> Result<Self> {
+        let line_height = (font_renderer.size() * 5.0 / 4.0).ceil() as u16;
+        let highlight = match arrangement {
+            Arrangement::Strip { highlight } => Some(highlight),
+            Arrangement::Lines => None,
+        };
+        let background = |i: usize| if highlight == Some(i) { tooltip::HIGHLIGHT } else { tooltip::BACKGROUND };
+        let rendered = items.iter()
+            .map(|item| font_renderer.render_text(item, tooltip::TEXT_COLOR))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to render tooltip text")?;
+        let widths: Vec<u16> = if font_renderer.requires_direct_rendering() {
+            // Core font: rough width, the X server draws the text itself
+            items.iter().map(|item| item.chars().count() as u16 * (font_renderer.size() * 0.6).ceil() as u16).collect()
+        } else {
+            rendered.iter().map(|text| text.width as u16).collect()
+        };
+        let (cells, inset, width, height) = layout(&widths, line_height, arrangement);
+        let (x, y) = place(placement, width, height, (screen.width_in_pixels, screen.height_in_pixels));
+
+        let window = conn.generate_id()
+            .context("Failed to generate ID for tooltip window")?;
+        conn.create_window(
+            screen.root_depth,
+            window,
+            screen.root,
+            x,
+            y,
+            width,
+            height,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            screen.root_visual,
+            &CreateWindowAux::new()
+                .background_pixel(tooltip::BACKGROUND)
+                .override_redirect(1),
+        )
+        .context("Failed to create tooltip window")?;
+        conn.map_window(window)
+            .context("Failed to map tooltip window")?;
+
+        let gc = conn.generate_id()
+            .context("Failed to generate GC ID for tooltip")?;
+        let mut gc_aux = CreateGCAux::new()
+            .foreground(tooltip::TEXT_COLOR & 0x00FFFFFF)
+            .background(tooltip::BACKGROUND);
+        if let Some(font_id) = font_renderer.x11_font_id() {
+            gc_aux = gc_aux.font(font_id);
+        }
+        conn.create_gc(gc, window, &gc_aux)
+            .context("Failed to create GC for tooltip")?;
+
+        for (i, ((item, text), cell)) in items.iter().zip(&rendered).zip(&cells).enumerate() {
+            if highlight == Some(i) {
+                conn.change_gc(gc, &ChangeGCAux::new().foreground(tooltip::HIGHLIGHT))
+                    .context("Failed to set tooltip highlight color")?;
+                conn.poly_fill_rectangle(window, gc, &[Rectangle { x: cell.x, y: cell.y, width: cell.width, height: cell.height }])
+                    .context("Failed to draw tooltip highlight")?;
+                conn.change_gc(gc, &ChangeGCAux::new().foreground(tooltip::TEXT_COLOR & 0x00FFFFFF))
+                    .context("Failed to set tooltip text color")?;
+            }
+            let (text_x, text_y) = (cell.x + inset as i16, cell.y + inset as i16);
+            if font_renderer.requires_direct_rendering() {
+                conn.change_gc(gc, &ChangeGCAux::new().background(background(i)))
+                    .context("Failed to set tooltip text background")?;
+                conn.image_text16(window, gc, text_x, text_y + font_renderer.size() as i16, &core_font_text(item))
+                    .context("Failed to draw tooltip text")?;
+            } else if text.width > 0 && text.height > 0 {
+                conn.put_image(
+                    ImageFormat::Z_PIXMAP,
+                    window,
+                    gc,
+                    text.width as u16,
+                    text.height as u16,
+                    text_x,
+                    text_y,
+                    0,
+                    screen.root_depth,
+                    &blend_onto_background(&text.data, background(i)),
+                )
+                .context("Failed to draw tooltip text")?;
+            }
+        }
+
+        conn.free_gc(gc)
+            .context("Failed to free tooltip GC")?;
+        conn.flush()
+            .context("Failed to flush X11 connection after showing tooltip")?;
+        Ok(Self { window })
+    }
+
+    pub fn close(self, conn: &RustConnection) -> Result<()> {
+        conn.destroy_window(self.window)
+            .context("Failed to destroy tooltip window")?;
+        conn.flush()
+            .context("Failed to flush X11 connection after closing tooltip")?;
+        Ok(())
+    }
+}
+
+/// Premultiplied ARGB text over an opaque background pixel, as BGRX bytes
+fn blend_onto_background(pixels: &[u32], background: u32) -> Vec<u8> {
+    let channel = |color: u32, shift: u32| (color >> shift) & 0xFF;
+    let mut data = Vec::with_capacity(pixels.len() * 4);
+    for &pixel in pixels {
+        let inverse = 255 - channel(pixel, 24);
+        for shift in [0, 8, 16] {
+            data.push((channel(pixel, shift) + channel(background, shift) * inverse / 255) as u8);
+        }
+        data.push(0);
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_meter_counts_recent_frames() {
+        let mut meter = FrameMeter::default();
+        let start = Instant::now();
+        assert_eq!(meter.fps(start), 0.0);
+        for i in 0..60 {
+            meter.record(start + Duration::from_millis(i * 50));
+        }
+        let now = start + Duration::from_millis(59 * 50);
+        // 41 frames 50 ms apart fit in the 2 s window
+        assert_eq!(meter.fps(now), 20.5);
+        assert_eq!(meter.last(), Some(now));
+        assert_eq!(meter.fps(now + Duration::from_secs(10)), 0.0);
+    }
+
+    #[test]
+    fn test_place_near_and_centered() {
+        let anchor = Rect { x: 100, y: 900, width: 240, height: 135 };
+        // No room below the thumbnail: above it
+        assert_eq!(place(Placement::Near(anchor), 200, 80, (1920, 1080)), (100, 900 - tooltip::PADDING as i16 - 80));
+        assert_eq!(place(Placement::Centered, 200, 80, (1920, 1080)), (860, 500));
+    }
+
+    #[test]
+    fn test_strip_layout_puts_items_side_by_side() {
+        let padding = tooltip::PADDING;
+        let (cells, inset, width, height) = layout(&[40, 60], 20, Arrangement::Strip { highlight: 1 });
+        assert_eq!(inset, padding);
+        assert_eq!(cells[1].x, (padding * 3 + 40) as i16);
+        assert_eq!(width, padding * 6 + 100);
+        assert_eq!(height, 20 + padding * 4);
+        let (cells, _, width, _) = layout(&[40, 60], 20, Arrangement::Lines);
+        assert_eq!((cells[1].x, cells[1].y), (padding as i16, (padding + 20) as i16));
+        assert_eq!(width, 60 + padding * 2);
+    }
+
+    #[test]
+    fn test_blend_onto_background() {
+        // Opaque white text replaces the background, transparent keeps it
+        assert_eq!(blend_onto_background(&[0xFFFFFFFF], tooltip::BACKGROUND), vec![0xFF, 0xFF, 0xFF, 0]);
+        let bg = tooltip::BACKGROUND;
+        assert_eq!(blend_onto_background(&[0], bg), vec![bg as u8, (bg >> 8) as u8, (bg >> 16) as u8, 0]);
+    }
+}
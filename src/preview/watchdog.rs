@@ -0,0 +1,89 @@
+//! Periodic sanity pass over the thumbnails
+//!
+//! Thumbnails are kept up to date by X events, and the odd missed event
+//! (a DestroyNotify lost while the server was busy, a resize that never
+//! reached us) leaves a thumbnail showing the wrong thing until restart.
+//! Every so often each thumbnail is checked against the server: its source
+//! window must still exist, its own window and overlay pixmap must be valid
+//! and its window must have the size it is drawn at. Anything inconsistent is
+//! repaired in the main loop (see `Problem`).
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+use x11rb::protocol::xproto::Window;
+
+use crate::constants::watchdog::INTERVAL_SECS;
+
+use super::thumbnail::Thumbnail;
+
+/// Something a check found wrong, and what the main loop does about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    /// Source window is gone: handled like its DestroyNotify
+    SourceGone,
+    /// Thumbnail window or overlay pixmap is invalid: the thumbnail is rebuilt
+    Broken,
+    /// Thumbnail window has another size than the one drawn: resized back
+    WrongSize,
+    /// Client was resized since the last full scale: redrawn from the source
+    StaleSource,
+}
+
+#[derive(Debug)]
+pub struct Watchdog {
+    next: Instant,
+}
+
+impl Watchdog {
+    pub fn new(now: Instant) -> Self {
+        Self { next: now + Duration::from_secs(INTERVAL_SECS) }
+    }
+
+    /// When the next pass is due (None without thumbnails to check, so an idle daemon can block)
+    pub fn deadline(&self, thumbnails: bool) -> Option<Instant> {
+        thumbnails.then_some(self.next)
+    }
+
+    /// Whether a pass is due; starts the next interval if so
+    pub fn due(&mut self, now: Instant) -> bool {
+        if now < self.next {
+            return false;
+        }
+        self.next = now + Duration::from_secs(INTERVAL_SECS);
+        true
+    }
+}
+
+/// Check every thumbnail, by EVE window
+pub fn inspect(eves: &HashMap<Window, Thumbnail>) -> Vec<(Window, Problem)> {
+    let mut problems = Vec::new();
+    for (window, thumbnail) in eves {
+        match thumbnail.health() {
+            Ok(Some(problem)) => {
+                warn!(window = window, character = %thumbnail.character_name, problem = ?problem, "Watchdog found an inconsistent thumbnail");
+                problems.push((*window, problem));
+            }
+            Ok(None) => {}
+            Err(e) => debug!(window = window, error = ?e, "Watchdog couldn't check thumbnail"),
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_due_once_per_interval() {
+        let start = Instant::now();
+        let mut watchdog = Watchdog::new(start);
+        assert!(!watchdog.due(start));
+        let interval = Duration::from_secs(INTERVAL_SECS);
+        assert!(watchdog.due(start + interval));
+        assert!(!watchdog.due(start + interval));
+        assert_eq!(watchdog.deadline(true), Some(start + interval * 2));
+        assert_eq!(watchdog.deadline(false), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Per-window identity that survives character changes
+//!
+//! An EVE client window keeps its X11 ID across logins, logouts and relogs
+//! while the character name in its title changes. `WindowIdentity` follows
+//! one window for the whole session: it remembers the names the window has
+//! carried and owns the thumbnail's authoritative position and size. The
+//! per-character entries in the config are only a projection of it, taken
+//! whenever the window stops showing a character.
+
+use std::collections::HashMap;
+
+use x11rb::protocol::xproto::Window;
+
+use crate::types::{CharacterSettings, Dimensions, Position};
+
+/// Names kept per window; older entries are only interesting for logs
+const MAX_NAME_HISTORY: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowIdentity {
+    /// Source EVE client window
+    window: Window,
+    /// Character names carried by this window, oldest first ("" = logged out)
+    names: Vec<String>,
+    /// Thumbnail top-left corner (authoritative, updated on every move)
+    position: Position,
+    /// Thumbnail size (authoritative, updated on every resize)
+    dimensions: Dimensions,
+}
+
+/// Result of switching a window to another character
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharacterChange {
+    /// Character the window showed until now, with its final geometry (None if it was logged out)
+    pub left: Option<(String, CharacterSettings)>,
+    /// Saved geometry of the new character that the window took over (None = keep current)
+    pub restored: Option<CharacterSettings>,
+}
+
+impl WindowIdentity {
+    pub fn new(window: Window, character_name: &str, position: Position, dimensions: Dimensions) -> Self {
+        Self {
+            window,
+            names: vec![character_name.to_string()],
+            position,
+            dimensions,
+        }
+    }
+
+    pub fn window(&self) -> Window {
+        self.window
+    }
+
+    /// Character currently shown by the window ("" while logged out)
+    pub fn character_name(&self) -> &str {
+        self.names.last().map_or("", String::as_str)
+    }
+
+    /// Names this window has carried, oldest first, current last
+    pub fn name_history(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    pub fn set_position(&mut self, position: Position) {
+        self.position = position;
+    }
+
+    pub fn set_dimensions(&mut self, dimensions: Dimensions) {
+        self.dimensions = dimensions;
+    }
+
+    /// What gets persisted for this window: the current character's geometry (None while logged out)
+    pub fn projection(&self) -> Option<(String, CharacterSettings)> {
+        let name = self.character_name();
+        (!name.is_empty()).then(|| (
+            name.to_string(),
+            CharacterSettings::new(self.position.x, self.position.y, self.dimensions.width, self.dimensions.height),
+        ))
+    }
+
+    /// Switch the window to another character (login, logout or relog)
+    /// The outgoing character's projection is returned so the caller can persist it; if the new
+    /// character has saved geometry, the window takes it over (zero dimensions keep the current size)
+    pub fn change_character(&mut self, new_name: &str, saved: &HashMap<String, CharacterSettings>) -> CharacterChange {
+        if new_name == self.character_name() {
+            return CharacterChange { left: None, restored: None };
+        }
+
+        let left = self.projection();
+        if self.names.len() >= MAX_NAME_HISTORY {
+            self.names.remove(0);
+        }
+        self.names.push(new_name.to_string());
+
+        let restored = if new_name.is_empty() {
+            None
+        } else {
+            saved.get(new_name).cloned()
+        };
+        if let Some(settings) = &restored {
+            self.position = settings.position();
+            if settings.dimensions.width > 0 && settings.dimensions.height > 0 {
+                self.dimensions = settings.dimensions;
+            }
+        }
+
+        CharacterChange { left, restored }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply a change the way the event handler does: persist the outgoing character first
+    fn relog(
+        identity: &mut WindowIdentity,
+        new_name: &str,
+        saved: &mut HashMap<String, CharacterSettings>,
+    ) -> CharacterChange {
+        let change = identity.change_character(new_name, saved);
+        if let Some((name, settings)) = &change.left {
+            saved.insert(name.clone(), settings.clone());
+        }
+        change
+    }
+
+    #[test]
+    fn test_login_restores_saved_geometry() {
+        let mut saved = HashMap::from([("Alice".to_string(), CharacterSettings::new(100, 200, 320, 180))]);
+        let mut identity = WindowIdentity::new(1, "", Position::new(10, 10), Dimensions::new(240, 135));
+
+        let change = relog(&mut identity, "Alice", &mut saved);
+        assert_eq!(change.left, None);
+        assert_eq!(identity.position(), Position::new(100, 200));
+        assert_eq!(identity.dimensions(), Dimensions::new(320, 180));
+    }
+
+    #[test]
+    fn test_login_new_character_keeps_geometry() {
+        let mut saved = HashMap::new();
+        let mut identity = WindowIdentity::new(1, "", Position::new(10, 10), Dimensions::new(240, 135));
+
+        let change = relog(&mut identity, "Bob", &mut saved);
+        assert_eq!(change.restored, None);
+        assert_eq!(identity.position(), Position::new(10, 10));
+        assert_eq!(identity.projection().map(|(name, _)| name), Some("Bob".to_string()));
+    }
+
+    #[test]
+    fn test_logout_persists_final_position() {
+        let mut saved = HashMap::new();
+        let mut identity = WindowIdentity::new(1, "Alice", Position::new(0, 0), Dimensions::new(240, 135));
+        identity.set_position(Position::new(500, 600));
+
+        let change = relog(&mut identity, "", &mut saved);
+        assert_eq!(change.left.map(|(name, _)| name), Some("Alice".to_string()));
+        assert_eq!(saved["Alice"].position(), Position::new(500, 600));
+        assert_eq!(identity.projection(), None);
+    }
+
+    #[test]
+    fn test_rapid_relog_keeps_each_characters_dimensions() {
+        let mut saved = HashMap::from([
+            ("Alice".to_string(), CharacterSettings::new(0, 0, 320, 180)),
+            ("Bob".to_string(), CharacterSettings::new(400, 0, 200, 112)),
+        ]);
+        let mut identity = WindowIdentity::new(1, "Alice", Position::new(0, 0), Dimensions::new(320, 180));
+
+        relog(&mut identity, "", &mut saved);
+        relog(&mut identity, "Bob", &mut saved);
+        assert_eq!(identity.dimensions(), Dimensions::new(200, 112));
+        relog(&mut identity, "", &mut saved);
+        relog(&mut identity, "Alice", &mut saved);
+
+        assert_eq!(identity.position(), Position::new(0, 0));
+        assert_eq!(identity.dimensions(), Dimensions::new(320, 180));
+        assert_eq!(saved["Bob"].dimensions, Dimensions::new(200, 112));
+        assert_eq!(saved["Bob"].position(), Position::new(400, 0));
+    }
+
+    #[test]
+    fn test_switch_without_logout_moves_between_characters() {
+        let mut saved = HashMap::from([("Bob".to_string(), CharacterSettings::new(400, 0, 240, 135))]);
+        let mut identity = WindowIdentity::new(1, "Alice", Position::new(50, 60), Dimensions::new(240, 135));
+
+        let change = relog(&mut identity, "Bob", &mut saved);
+        assert_eq!(change.left.map(|(name, settings)| (name, settings.position())), Some(("Alice".to_string(), Position::new(50, 60))));
+        assert_eq!(identity.position(), Position::new(400, 0));
+
+        relog(&mut identity, "Alice", &mut saved);
+        assert_eq!(identity.position(), Position::new(50, 60));
+        assert_eq!(saved["Bob"].position(), Position::new(400, 0));
+    }
+
+    #[test]
+    fn test_same_name_is_not_a_change() {
+        let mut saved = HashMap::from([("Alice".to_string(), CharacterSettings::new(0, 0, 240, 135))]);
+        let mut identity = WindowIdentity::new(1, "Alice", Position::new(700, 700), Dimensions::new(240, 135));
+
+        let change = relog(&mut identity, "Alice", &mut saved);
+        assert_eq!(change, CharacterChange { left: None, restored: None });
+        assert_eq!(identity.position(), Position::new(700, 700));
+        assert_eq!(identity.name_history().len(), 1);
+    }
+
+    #[test]
+    fn test_name_history_is_bounded() {
+        let mut saved = HashMap::new();
+        let mut identity = WindowIdentity::new(1, "", Position::new(0, 0), Dimensions::new(240, 135));
+        for i in 0..(MAX_NAME_HISTORY * 2) {
+            relog(&mut identity, &format!("Char{}", i), &mut saved);
+        }
+        assert_eq!(identity.name_history().len(), MAX_NAME_HISTORY);
+        assert_eq!(identity.character_name(), format!("Char{}", MAX_NAME_HISTORY * 2 - 1));
+    }
+}
@@ -0,0 +1,183 @@
+//! Window rules engine for EVE client windows
+//!
+//! Rules come from `GlobalSettings::window_rules` and are evaluated whenever a
+//! client is mapped (or a character logs in on it) and when it gains focus.
+//! Matching rules are applied in config order, so later rules win on conflicts.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use tracing::{debug, info, warn};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+
+use crate::config::profile::{RuleActions, RuleTrigger, WindowRule};
+use crate::x11_utils::{list_monitors, move_window_to_desktop, set_window_above, window_title, AppContext};
+
+/// The configured rules with their title_regex compiled, rebuilt when settings arrive
+#[derive(Debug, Default)]
+pub struct WindowRules {
+    rules: Vec<(WindowRule, Option<Regex>)>,
+}
+
+impl WindowRules {
+    /// Compile every title_regex once; a rule with an invalid one is skipped (warned about here)
+    pub fn compile(rules: &[WindowRule]) -> Self {
+        let rules = rules.iter()
+            .filter_map(|rule| {
+                let Some(pattern) = &rule.title_regex else {
+                    return Some((rule.clone(), None));
+                };
+                match Regex::new(pattern) {
+                    Ok(regex) => Some((rule.clone(), Some(regex))),
+                    Err(e) => {
+                        warn!(rule = %rule.name, pattern = %pattern, error = %e, "Invalid title_regex in window rule, skipping the rule");
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+}
+
+/// Check whether a rule matches the client (empty character = logged out)
+/// `title` is the client's window title, None if it couldn't be read
+fn rule_matches(rule: &WindowRule, title_regex: Option<&Regex>, character_name: &str, title: Option<&str>) -> bool {
+    if let Some(character) = &rule.character
+        && character != character_name
+    {
+        return false;
+    }
+    match title_regex {
+        Some(regex) => title.is_some_and(|title| regex.is_match(title)),
+        None => true,
+    }
+}
+
+/// Apply every rule for `trigger` that matches the client's character and window title
+pub fn apply_matching(
+    ctx: &AppContext,
+    rules: &WindowRules,
+    trigger: RuleTrigger,
+    window: Window,
+    character_name: &str,
+) -> Result<()> {
+    let candidates: Vec<_> = rules.rules.iter().filter(|(rule, _)| rule.trigger == trigger).collect();
+    if candidates.is_empty() {
+        return Ok(());
+    }
+    // The title the client actually has (localized or custom titles differ from "EVE - name")
+    let title = if candidates.iter().any(|(_, regex)| regex.is_some()) {
+        window_title(ctx.conn, window, ctx.atoms)
+            .inspect_err(|e| debug!(window = window, error = ?e, "Failed to read client title, title_regex rules won't match"))
+            .ok()
+            .flatten()
+    } else {
+        None
+    };
+    for (rule, _) in candidates.into_iter().filter(|(rule, regex)| rule_matches(rule, regex.as_ref(), character_name, title.as_deref())) {
+        info!(rule = %rule.name, window = window, character = %character_name, trigger = ?trigger, "Applying window rule");
+        apply_actions(ctx, window, &rule.actions)
+            .context(format!("Failed to apply window rule '{}' to window {}", rule.name, window))?;
+    }
+    Ok(())
+}
+
+fn apply_actions(ctx: &AppContext, window: Window, actions: &RuleActions) -> Result<()> {
+    let mut aux = ConfigureWindowAux::new();
+    let mut reconfigure = false;
+
+    if let Some(index) = actions.monitor {
+        let monitors = list_monitors(ctx.conn, ctx.screen);
+        if let Some((monitor_pos, _)) = monitors.get(index) {
+            aux = aux.x(monitor_pos.x as i32).y(monitor_pos.y as i32);
+            reconfigure = true;
+        } else {
+            warn!(monitor = index, available = monitors.len(), "Window rule targets a monitor that doesn't exist");
+        }
+    }
+
+    if actions.width.is_some() || actions.height.is_some() {
+        let geom = ctx.conn.get_geometry(window)
+            .context(format!("Failed to query geometry for window {}", window))?
+            .reply()
+            .context(format!("Failed to get geometry reply for window {}", window))?;
+        aux = aux
+            .width(actions.width.unwrap_or(geom.width) as u32)
+            .height(actions.height.unwrap_or(geom.height) as u32);
+        reconfigure = true;
+    }
+
+    if reconfigure {
+        debug!(window = window, config = ?aux, "Configuring client window");
+        ctx.conn.configure_window(window, &aux)
+            .context(format!("Failed to move/resize window {}", window))?;
+    }
+
+    if let Some(above) = actions.always_on_top {
+        set_window_above(ctx.conn, ctx.screen, ctx.atoms, window, above)
+            .context(format!("Failed to set always-on-top for window {}", window))?;
+    }
+
+    if let Some(desktop) = actions.desktop {
+        move_window_to_desktop(ctx.conn, ctx.screen, ctx.atoms, window, desktop)
+            .context(format!("Failed to move window {} to desktop {}", window, desktop))?;
+    }
+
+    ctx.conn.flush()
+        .context("Failed to flush X11 connection after applying window rule")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(character: Option<&str>, title_regex: Option<&str>) -> WindowRule {
+        WindowRule {
+            name: "test".to_string(),
+            character: character.map(str::to_string),
+            title_regex: title_regex.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    /// Whether the single compiled rule matches
+    fn matches(rule: WindowRule, character_name: &str, title: Option<&str>) -> bool {
+        let compiled = WindowRules::compile(&[rule]);
+        compiled.rules.iter().any(|(rule, regex)| rule_matches(rule, regex.as_ref(), character_name, title))
+    }
+
+    #[test]
+    fn test_rule_without_matchers_matches_everything() {
+        assert!(matches(rule(None, None), "Main", Some("EVE - Main")));
+        assert!(matches(rule(None, None), "", None));
+    }
+
+    #[test]
+    fn test_rule_matches_character() {
+        assert!(matches(rule(Some("Main"), None), "Main", None));
+        assert!(!matches(rule(Some("Main"), None), "Scout", None));
+    }
+
+    #[test]
+    fn test_rule_matches_title_regex() {
+        let r = rule(None, Some("^EVE - .* Alt$"));
+        assert!(matches(r.clone(), "Hauler Alt", Some("EVE - Hauler Alt")));
+        assert!(!matches(r.clone(), "Main", Some("EVE - Main")));
+        // The real title is matched, not one rebuilt from the character name
+        assert!(matches(rule(None, Some("^星战前夜")), "Main", Some("星战前夜: Main")));
+        // Unreadable title: only rules without title_regex apply
+        assert!(!matches(r, "Hauler Alt", None));
+    }
+
+    #[test]
+    fn test_rule_requires_all_matchers() {
+        assert!(!matches(rule(Some("Main"), Some("Alt$")), "Main", Some("EVE - Main")));
+    }
+
+    #[test]
+    fn test_invalid_regex_drops_rule() {
+        assert!(WindowRules::compile(&[rule(None, Some("(unclosed"))]).rules.is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+//! Window manager compatibility presets (wm_preset)
+//!
+//! Tiling WMs (i3, awesome) would tile managed thumbnails, so there the
+//! thumbnails bypass the WM with override-redirect. KWin and Mutter get managed,
+//! undecorated keep-above windows instead, which stay in the compositor's own
+//! stacking (panels, notifications, lock screen), and are left to raise clients
+//! themselves: raising a client before _NET_ACTIVE_WINDOW trips their focus
+//! stealing prevention.
+
+use crate::config::profile::WmPreset;
+
+/// Hints the daemon follows for the running WM
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WmCompat {
+    /// Thumbnails bypass the WM (false: managed, undecorated keep-above windows)
+    pub override_redirect: bool,
+    /// Raise a client ourselves before asking the WM to activate it
+    pub raise_before_activate: bool,
+}
+
+impl Default for WmCompat {
+    fn default() -> Self {
+        compat(WmPreset::Generic)
+    }
+}
+
+/// Preset for a WM by the name on its _NET_SUPPORTING_WM_CHECK window
+pub fn detect(wm_name: Option<&str>) -> WmPreset {
+    let Some(name) = wm_name.map(str::to_lowercase) else {
+        return WmPreset::Generic;
+    };
+    if name == "i3" || name.starts_with("i3 ") {
+        WmPreset::I3
+    } else if name.starts_with("awesome") {
+        WmPreset::Awesome
+    } else if name.starts_with("kwin") {
+        WmPreset::Kwin
+    } else if name.contains("mutter") || name.contains("muffin") || name.starts_with("gnome shell") {
+        WmPreset::Mutter
+    } else {
+        WmPreset::Generic
+    }
+}
+
+/// The configured preset, or the detected one for `auto`
+pub fn resolve(configured: WmPreset, detected: WmPreset) -> WmPreset {
+    match configured {
+        WmPreset::Auto => detected,
+        preset => preset,
+    }
+}
+
+pub fn compat(preset: WmPreset) -> WmCompat {
+    match preset {
+        WmPreset::Auto | WmPreset::Generic | WmPreset::Awesome => WmCompat { override_redirect: true, raise_before_activate: true },
+        WmPreset::I3 => WmCompat { override_redirect: true, raise_before_activate: false },
+        WmPreset::Kwin | WmPreset::Mutter => WmCompat { override_redirect: false, raise_before_activate: false },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_by_wm_name() {
+        assert_eq!(detect(Some("i3")), WmPreset::I3);
+        assert_eq!(detect(Some("awesome v4.3")), WmPreset::Awesome);
+        assert_eq!(detect(Some("KWin")), WmPreset::Kwin);
+        assert_eq!(detect(Some("GNOME Shell")), WmPreset::Mutter);
+        assert_eq!(detect(Some("Mutter (Muffin)")), WmPreset::Mutter);
+        assert_eq!(detect(Some("Openbox")), WmPreset::Generic);
+        assert_eq!(detect(None), WmPreset::Generic);
+    }
+
+    #[test]
+    fn test_manual_preset_overrides_detection() {
+        assert_eq!(resolve(WmPreset::Auto, WmPreset::Kwin), WmPreset::Kwin);
+        assert_eq!(resolve(WmPreset::Generic, WmPreset::Kwin), WmPreset::Generic);
+        assert!(!compat(resolve(WmPreset::Auto, WmPreset::Mutter)).override_redirect);
+        assert!(compat(WmPreset::I3).override_redirect);
+    }
+}
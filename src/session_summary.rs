@@ -0,0 +1,290 @@
+//! Session summary written when the daemon shuts down cleanly
+//!
+//! The daemon tracks which characters were seen, how often each one was
+//! focused and for how long, how many hotkeys were pressed and how many
+//! clients were running; on a clean shutdown the summary goes to
+//! `$XDG_STATE_HOME/eve-l-preview/last-session.json` (replacing the previous
+//! one) and the GUI's "Session" tab shows it.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::constants::{config, session};
+use crate::permissions;
+
+/// What happened during one daemon session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    /// Session start (Unix seconds)
+    pub started_at: u64,
+    /// Session length in seconds
+    pub duration_secs: u64,
+    /// Every character seen during the session, by name
+    pub characters: Vec<CharacterSession>,
+    /// Hotkey commands acted on (cycling and per-character focus)
+    #[serde(default)]
+    pub hotkey_presses: u32,
+    /// Running clients integrated over the session (client-seconds)
+    #[serde(default)]
+    pub client_secs: u64,
+}
+
+impl SessionSummary {
+    /// Focus switches over all characters
+    pub fn focus_switches(&self) -> u32 {
+        self.characters.iter().map(|c| c.focus_switches).sum()
+    }
+
+    /// Average number of clients running during the session
+    pub fn average_clients(&self) -> f32 {
+        if self.duration_secs == 0 {
+            return 0.0;
+        }
+        self.client_secs as f32 / self.duration_secs as f32
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CharacterSession {
+    pub name: String,
+    /// Times focus moved to this character from another window
+    pub focus_switches: u32,
+    /// Total time this character's client held focus, in seconds
+    #[serde(default)]
+    pub focus_secs: u64,
+}
+
+/// Per-character tally kept while the session runs
+#[derive(Debug, Clone, Copy, Default)]
+struct Activity {
+    focus_switches: u32,
+    focus_time: Duration,
+}
+
+/// Running tally for the current session (daemon side)
+#[derive(Debug)]
+pub struct SessionTracker {
+    started_at: SystemTime,
+    started: Instant,
+    /// Activity per character seen
+    characters: BTreeMap<String, Activity>,
+    /// Character holding focus right now, and since when
+    focused: Option<(String, Instant)>,
+    hotkey_presses: u32,
+    /// Client-time booked so far, and the count running since the last update
+    client_time: Duration,
+    clients: (usize, Instant),
+}
+
+impl Default for SessionTracker {
+    fn default() -> Self {
+        Self {
+            started_at: SystemTime::now(),
+            started: Instant::now(),
+            characters: BTreeMap::new(),
+            focused: None,
+            hotkey_presses: 0,
+            client_time: Duration::ZERO,
+            clients: (0, Instant::now()),
+        }
+    }
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Character has a client running (logged-out windows have no name and don't count)
+    pub fn seen(&mut self, character: &str) {
+        if !character.is_empty() && !self.characters.contains_key(character) {
+            self.characters.insert(character.to_string(), Activity::default());
+        }
+    }
+
+    /// Focus moved to this character's client
+    pub fn focused(&mut self, character: &str, now: Instant) {
+        if character.is_empty() {
+            return;
+        }
+        self.end_focus(now);
+        self.characters.entry(character.to_string()).or_default().focus_switches += 1;
+        self.focused = Some((character.to_string(), now));
+    }
+
+    /// This character's client lost focus (ignored if another one already took it)
+    pub fn unfocused(&mut self, character: &str, now: Instant) {
+        if self.focused.as_ref().is_some_and(|(name, _)| name == character) {
+            self.end_focus(now);
+        }
+    }
+
+    /// A hotkey command was acted on
+    pub fn hotkey_pressed(&mut self) {
+        self.hotkey_presses = self.hotkey_presses.saturating_add(1);
+    }
+
+    /// Number of clients running from `now` on (cheap, fine to call every loop)
+    pub fn clients(&mut self, count: usize, now: Instant) {
+        self.client_time = self.client_time_at(now);
+        self.clients = (count, now);
+    }
+
+    /// Client-time up to `now`, counting the running span
+    fn client_time_at(&self, now: Instant) -> Duration {
+        let (count, since) = self.clients;
+        self.client_time + now.saturating_duration_since(since) * count as u32
+    }
+
+    /// Book the running focus span to its character
+    fn end_focus(&mut self, now: Instant) {
+        if let Some((name, since)) = self.focused.take() {
+            self.characters.entry(name).or_default().focus_time += now.saturating_duration_since(since);
+        }
+    }
+
+    /// Summary up to `now`, counting the running focus span
+    pub fn summary(&self, now: Instant) -> SessionSummary {
+        SessionSummary {
+            started_at: self.started_at
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs()),
+            duration_secs: now.saturating_duration_since(self.started).as_secs(),
+            characters: self.characters.iter()
+                .map(|(name, activity)| {
+                    let running = match &self.focused {
+                        Some((focused, since)) if focused == name => now.saturating_duration_since(*since),
+                        _ => Duration::ZERO,
+                    };
+                    CharacterSession {
+                        name: name.clone(),
+                        focus_switches: activity.focus_switches,
+                        focus_secs: (activity.focus_time + running).as_secs(),
+                    }
+                })
+                .collect(),
+            hotkey_presses: self.hotkey_presses,
+            client_secs: self.client_time_at(now).as_secs(),
+        }
+    }
+}
+
+/// Where the last session's summary is kept (XDG state dir, local data dir as fallback)
+fn path() -> PathBuf {
+    let mut path = dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."));
+    path.push(config::APP_DIR);
+    path.push(session::SUMMARY_FILENAME);
+    path
+}
+
+/// Replace the stored summary with this session's
+pub fn save(summary: &SessionSummary) -> Result<()> {
+    let path = path();
+    if let Some(parent) = path.parent() {
+        permissions::create_private_dir(parent)
+            .with_context(|| format!("Failed to create state directory {:?}", parent))?;
+    }
+    let json = serde_json::to_string_pretty(summary)
+        .context("Failed to serialize session summary")?;
+    permissions::write_private(&path, json.as_bytes())
+        .with_context(|| format!("Failed to write session summary to {:?}", path))?;
+    info!(path = ?path, characters = summary.characters.len(), duration_secs = summary.duration_secs, "Saved session summary");
+    Ok(())
+}
+
+/// The summary of the last cleanly ended session, None if there is none yet
+pub fn load_last() -> Result<Option<SessionSummary>> {
+    let path = path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read session summary {:?}", path))?;
+    let summary = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse session summary {:?}", path))?;
+    Ok(Some(summary))
+}
+
+/// "2h 05m" style duration for the GUI
+pub fn format_duration(secs: u64) -> String {
+    let (hours, minutes) = (secs / 3600, secs % 3600 / 60);
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_counts_focus_and_ignores_logged_out() {
+        let mut tracker = SessionTracker::new();
+        let now = Instant::now();
+        tracker.seen("Scout");
+        tracker.seen("");
+        tracker.focused("Main", now);
+        tracker.focused("Main", now);
+        tracker.focused("", now);
+        tracker.seen("Main");
+        let summary = tracker.summary(now);
+        assert_eq!(summary.characters, vec![
+            CharacterSession { name: "Main".to_string(), focus_switches: 2, focus_secs: 0 },
+            CharacterSession { name: "Scout".to_string(), focus_switches: 0, focus_secs: 0 },
+        ]);
+    }
+
+    #[test]
+    fn test_tracker_sums_focus_time() {
+        let mut tracker = SessionTracker::new();
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        tracker.focused("Main", at(0));
+        // Focus moves straight to the booster; Main's late FocusOut changes nothing
+        tracker.focused("Booster", at(10));
+        tracker.unfocused("Main", at(11));
+        tracker.unfocused("Booster", at(40));
+        tracker.focused("Main", at(100));
+
+        let focus_secs = |summary: &SessionSummary, name: &str| summary.characters.iter()
+            .find(|c| c.name == name)
+            .map(|c| c.focus_secs);
+        let summary = tracker.summary(at(105));
+        assert_eq!(focus_secs(&summary, "Main"), Some(15));
+        assert_eq!(focus_secs(&summary, "Booster"), Some(30));
+    }
+
+    #[test]
+    fn test_tracker_averages_clients_over_time() {
+        let mut tracker = SessionTracker::new();
+        let start = tracker.started;
+        let at = |secs| start + Duration::from_secs(secs);
+        tracker.clients(2, at(0));
+        tracker.clients(4, at(60));
+        tracker.clients(4, at(70));
+        tracker.hotkey_pressed();
+        let summary = tracker.summary(at(120));
+        assert_eq!(summary.client_secs, 2 * 60 + 4 * 60);
+        assert_eq!(summary.average_clients(), 3.0);
+        assert_eq!(summary.hotkey_presses, 1);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(125), "2m 05s");
+        assert_eq!(format_duration(2 * 3600 + 5 * 60 + 9), "2h 05m");
+    }
+}
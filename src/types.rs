@@ -1,7 +1,13 @@
 //! Domain types for type safety and clarity
 
+use anyhow::{Context, Result};
+use regex::Regex;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::color::{HexColor, Opacity};
+use crate::constants::eve;
+
 /// EVE Online window type classification
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EveWindowType {
@@ -22,6 +28,46 @@ impl EveWindowType {
     
 }
 
+/// Recognizes EVE client window titles and extracts the character name
+/// A title matching the whole pattern is an EVE client; the `character` group
+/// holds the name, and a match without it is a logged-out client
+#[derive(Debug, Clone)]
+pub struct TitlePattern {
+    regex: Regex,
+}
+
+impl TitlePattern {
+    /// Compile a title pattern (must contain a `character` named group)
+    pub fn new(pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .context(format!("Invalid title pattern '{}'", pattern))?;
+        if !regex.capture_names().any(|name| name == Some(eve::TITLE_PATTERN_GROUP)) {
+            anyhow::bail!("Title pattern '{}' has no (?P<{}>...) group", pattern, eve::TITLE_PATTERN_GROUP);
+        }
+        Ok(Self { regex })
+    }
+
+    /// The pattern's source text
+    pub fn as_str(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    /// Classify a window title (None = not an EVE client)
+    pub fn parse(&self, title: &str) -> Option<EveWindowType> {
+        let captures = self.regex.captures(title)?;
+        match captures.name(eve::TITLE_PATTERN_GROUP) {
+            Some(name) if !name.as_str().is_empty() => Some(EveWindowType::LoggedIn(name.as_str().to_string())),
+            _ => Some(EveWindowType::LoggedOut),
+        }
+    }
+}
+
+impl Default for TitlePattern {
+    fn default() -> Self {
+        Self::new(eve::DEFAULT_TITLE_PATTERN).expect("default title pattern is valid")
+    }
+}
+
 /// A position in 2D space (X11 coordinates)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub struct Position {
@@ -60,7 +106,7 @@ impl From<Position> for (i16, i16) {
 
 /// Thumbnail dimensions (width × height)
 /// Using a newtype prevents accidentally swapping width and height
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, Default)]
 pub struct Dimensions {
     pub width: u16,
     pub height: u16,
@@ -86,6 +132,18 @@ impl Dimensions {
         self.width as u32 * self.height as u32
     }
 
+    /// Shrink to fit within max_width x max_height, keeping the aspect ratio (never grows)
+    pub fn capped(self, max_width: u16, max_height: u16) -> Self {
+        if self.width <= max_width && self.height <= max_height {
+            return self;
+        }
+        let scale = (max_width as f32 / self.width as f32).min(max_height as f32 / self.height as f32);
+        Self {
+            width: ((self.width as f32 * scale) as u16).max(1),
+            height: ((self.height as f32 * scale) as u16).max(1),
+        }
+    }
+
     /// Convert to tuple for compatibility
     pub fn as_tuple(self) -> (u16, u16) {
         (self.width, self.height)
@@ -109,6 +167,21 @@ impl From<Dimensions> for (u16, u16) {
     }
 }
 
+/// Thumbnail position and size, as remembered for one monitor setup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ThumbnailGeometry {
+    pub x: i16,
+    pub y: i16,
+    #[serde(flatten)]
+    pub dimensions: Dimensions,
+}
+
+impl ThumbnailGeometry {
+    pub fn of(settings: &CharacterSettings) -> Self {
+        Self { x: settings.x, y: settings.y, dimensions: settings.dimensions }
+    }
+}
+
 /// Text offset from border edge
 /// Using a newtype makes the coordinate context clear (not absolute window coordinates)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
@@ -161,14 +234,71 @@ impl Default for ThumbnailState {
     }
 }
 
-/// Per-character settings: position and thumbnail dimensions
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Area of a client blacked out in its preview, in client pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, Default)]
+pub struct RedactRect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl RedactRect {
+    /// Whether the client pixel (x, y) is inside the area
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        (self.x..self.x.saturating_add(self.width)).contains(&x)
+            && (self.y..self.y.saturating_add(self.height)).contains(&y)
+    }
+}
+
+/// Per-character settings: position, thumbnail dimensions and client options
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct CharacterSettings {
     pub x: i16,
     pub y: i16,
     /// Thumbnail dimensions (0 = use auto-detect)
     #[serde(flatten)]
     pub dimensions: Dimensions,
+    /// Strip decorations and fill the monitor while this character's client is focused
+    #[serde(default)]
+    pub borderless_fullscreen: bool,
+    /// Stacking order among overlapping thumbnails (higher = on top)
+    #[serde(default)]
+    pub z_order: i32,
+    /// Don't show a thumbnail for this character (the client is still tracked for cycling)
+    #[serde(default)]
+    pub preview_hidden: bool,
+    /// Name label color override (hex, #RRGGBB or #AARRGGBB); profile text_color when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label_color: Option<String>,
+    /// Name label opacity override in percent, replaces the color's alpha
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(max = 100))]
+    pub label_opacity: Option<u8>,
+    /// Name shown on the thumbnail label instead of the character name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    /// Focus border color override (hex); profile border_color when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border_color: Option<String>,
+    /// Key that focuses this character's client directly (evdev name, e.g. "F1" or "KEY_F1")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotkey: Option<String>,
+    /// Skip this character when cycling with Tab/Shift+Tab
+    #[serde(default)]
+    pub exclude_from_cycle: bool,
+    /// Float this character's preview on its own while panel mode is on
+    #[serde(default)]
+    pub panel_detached: bool,
+    /// Shell command that starts this character's client (relaunch after terminating it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_command: Option<String>,
+    /// When the GUI last saw this character's client running (Unix seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen: Option<u64>,
+    /// Areas of the client blacked out in the preview only (e.g. the chat panel)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redactions: Vec<RedactRect>,
 }
 
 impl CharacterSettings {
@@ -177,6 +307,19 @@ impl CharacterSettings {
             x, 
             y, 
             dimensions: Dimensions::new(width, height),
+            borderless_fullscreen: false,
+            z_order: 0,
+            preview_hidden: false,
+            label_color: None,
+            label_opacity: None,
+            alias: None,
+            border_color: None,
+            hotkey: None,
+            exclude_from_cycle: false,
+            panel_detached: false,
+            launch_command: None,
+            last_seen: None,
+            redactions: Vec::new(),
         }
     }
     
@@ -184,12 +327,59 @@ impl CharacterSettings {
         Position::new(self.x, self.y)
     }
     
+    /// Update position and dimensions, keeping per-character options intact
+    pub fn set_geometry(&mut self, x: i16, y: i16, width: u16, height: u16) {
+        self.x = x;
+        self.y = y;
+        self.dimensions = Dimensions::new(width, height);
+    }
+    
+    /// Text for the thumbnail label: the alias if one is set, else the character name
+    pub fn label_text<'a>(&'a self, character_name: &'a str) -> &'a str {
+        self.alias.as_deref().filter(|alias| !alias.is_empty()).unwrap_or(character_name)
+    }
+    
+    /// Name label color (ARGB) with this character's overrides applied over `default_argb`
+    pub fn label_argb(&self, default_argb: u32) -> u32 {
+        let color = self.label_color.as_deref()
+            .and_then(HexColor::parse)
+            .map_or(default_argb, HexColor::argb32);
+        match self.label_opacity {
+            Some(percent) => (color & 0x00FFFFFF) | Opacity::from_percent(percent).to_argb32(),
+            None => color,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_dimensions_capped_keeps_aspect() {
+        assert_eq!(Dimensions::new(640, 360).capped(320, 180), Dimensions::new(320, 180));
+        assert_eq!(Dimensions::new(400, 100).capped(320, 180), Dimensions::new(320, 80));
+        assert_eq!(Dimensions::new(200, 100).capped(320, 180), Dimensions::new(200, 100));
+    }
+
+    #[test]
+    fn test_default_title_pattern() {
+        let pattern = TitlePattern::default();
+        assert_eq!(pattern.parse("EVE - Some Pilot"), Some(EveWindowType::LoggedIn("Some Pilot".to_string())));
+        assert_eq!(pattern.parse("EVE"), Some(EveWindowType::LoggedOut));
+        assert_eq!(pattern.parse("EVE Launcher"), None);
+        assert_eq!(pattern.parse("Firefox - EVE - Some Pilot"), None);
+    }
+
+    #[test]
+    fn test_custom_title_pattern() {
+        let pattern = TitlePattern::new(r"^(?:EVE|星战前夜)(?:(?: -|:) (?P<character>.+))?$").unwrap();
+        assert_eq!(pattern.parse("星战前夜 - 飞行员"), Some(EveWindowType::LoggedIn("飞行员".to_string())));
+        assert_eq!(pattern.parse("EVE: Pilot"), Some(EveWindowType::LoggedIn("Pilot".to_string())));
+        assert!(TitlePattern::new("^EVE - (.+)$").is_err());
+        assert!(TitlePattern::new("^EVE - (?P<character>.+$").is_err());
+    }
+
     #[test]
     fn test_position_creation() {
         let pos = Position::new(100, 200);
@@ -316,4 +506,28 @@ mod tests {
         assert!(state.is_visible());
         assert!(!state.is_focused());
     }
+
+    #[test]
+    fn test_label_argb_overrides() {
+        let mut settings = CharacterSettings::new(0, 0, 240, 135);
+        assert_eq!(settings.label_argb(0xFFFFFFFF), 0xFFFFFFFF);
+
+        settings.label_color = Some("#FF0000".to_string());
+        assert_eq!(settings.label_argb(0xFFFFFFFF), 0xFFFF0000);
+
+        settings.label_opacity = Some(0);
+        assert_eq!(settings.label_argb(0xFFFFFFFF), 0x00FF0000);
+
+        settings.label_color = Some("not a color".to_string());
+        assert_eq!(settings.label_argb(0xFF00FF00), 0x0000FF00);
+    }
+
+    #[test]
+    fn test_redact_rect_contains() {
+        let rect = RedactRect { x: 10, y: 20, width: 30, height: 40 };
+        assert!(rect.contains(10, 20));
+        assert!(rect.contains(39, 59));
+        assert!(!rect.contains(40, 20));
+        assert!(!rect.contains(10, 60));
+    }
 }
@@ -0,0 +1,163 @@
+//! Local usage statistics (opt-in, never leaves the machine)
+//!
+//! With `usage_stats` enabled, the daemon folds each cleanly ended session's
+//! counters into `$XDG_STATE_HOME/eve-l-preview/usage-stats.json`, one entry
+//! per day, and the GUI's "Session" tab graphs them. Nothing is sent anywhere;
+//! deleting the file resets the history.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::constants::{config, usage_stats};
+use crate::permissions;
+use crate::session_summary::SessionSummary;
+
+/// Counters of every session that ended on one day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayStats {
+    /// Local date, YYYY-MM-DD
+    pub date: String,
+    pub sessions: u32,
+    pub duration_secs: u64,
+    pub hotkey_presses: u32,
+    pub focus_switches: u32,
+    /// Running clients integrated over the day's sessions (client-seconds)
+    pub client_secs: u64,
+}
+
+impl DayStats {
+    /// Average number of clients while the daemon was running
+    pub fn average_clients(&self) -> f32 {
+        if self.duration_secs == 0 {
+            return 0.0;
+        }
+        self.client_secs as f32 / self.duration_secs as f32
+    }
+}
+
+/// Daily history, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageStats {
+    pub days: Vec<DayStats>,
+}
+
+impl UsageStats {
+    /// Add a session's counters to the entry for `date`, dropping the oldest days past the cap
+    pub fn record(&mut self, summary: &SessionSummary, date: &str) {
+        let day = match self.days.iter().position(|day| day.date == date) {
+            Some(index) => &mut self.days[index],
+            None => {
+                self.days.push(DayStats { date: date.to_string(), ..Default::default() });
+                self.days.sort_by(|a, b| a.date.cmp(&b.date));
+                let index = self.days.iter().position(|day| day.date == date).unwrap();
+                &mut self.days[index]
+            }
+        };
+        day.sessions += 1;
+        day.duration_secs += summary.duration_secs;
+        day.hotkey_presses = day.hotkey_presses.saturating_add(summary.hotkey_presses);
+        day.focus_switches = day.focus_switches.saturating_add(summary.focus_switches());
+        day.client_secs += summary.client_secs;
+
+        let excess = self.days.len().saturating_sub(usage_stats::MAX_DAYS);
+        self.days.drain(..excess);
+    }
+
+    /// All recorded days added up (date left empty)
+    pub fn total(&self) -> DayStats {
+        self.days.iter().fold(DayStats::default(), |mut total, day| {
+            total.sessions += day.sessions;
+            total.duration_secs += day.duration_secs;
+            total.hotkey_presses = total.hotkey_presses.saturating_add(day.hotkey_presses);
+            total.focus_switches = total.focus_switches.saturating_add(day.focus_switches);
+            total.client_secs += day.client_secs;
+            total
+        })
+    }
+}
+
+/// Where the history is kept (XDG state dir, local data dir as fallback)
+fn path() -> PathBuf {
+    let mut path = dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."));
+    path.push(config::APP_DIR);
+    path.push(usage_stats::FILENAME);
+    path
+}
+
+/// The recorded history, empty if there is none yet
+pub fn load() -> Result<UsageStats> {
+    let path = path();
+    if !path.exists() {
+        return Ok(UsageStats::default());
+    }
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read usage stats {:?}", path))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse usage stats {:?}", path))
+}
+
+/// Fold a finished session into the history on disk (dated by the local day it ended)
+pub fn record_session(summary: &SessionSummary) -> Result<()> {
+    let mut stats = load()?;
+    let today = chrono::Local::now();
+    let date = format!("{:04}-{:02}-{:02}", today.year(), today.month(), today.day());
+    stats.record(summary, &date);
+
+    let path = path();
+    if let Some(parent) = path.parent() {
+        permissions::create_private_dir(parent)
+            .with_context(|| format!("Failed to create state directory {:?}", parent))?;
+    }
+    let json = serde_json::to_string_pretty(&stats)
+        .context("Failed to serialize usage stats")?;
+    permissions::write_private(&path, json.as_bytes())
+        .with_context(|| format!("Failed to write usage stats to {:?}", path))?;
+    info!(path = ?path, days = stats.days.len(), "Recorded session in usage stats");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_summary::CharacterSession;
+
+    fn session(duration_secs: u64, switches: u32) -> SessionSummary {
+        SessionSummary {
+            duration_secs,
+            characters: vec![CharacterSession { name: "Main".to_string(), focus_switches: switches, focus_secs: 0 }],
+            hotkey_presses: switches,
+            client_secs: duration_secs * 2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_record_merges_sessions_of_a_day() {
+        let mut stats = UsageStats::default();
+        stats.record(&session(100, 3), "2026-01-02");
+        stats.record(&session(50, 2), "2026-01-01");
+        stats.record(&session(300, 5), "2026-01-02");
+        assert_eq!(stats.days.iter().map(|d| d.date.as_str()).collect::<Vec<_>>(), ["2026-01-01", "2026-01-02"]);
+        let day = &stats.days[1];
+        assert_eq!((day.sessions, day.duration_secs, day.focus_switches, day.hotkey_presses), (2, 400, 8, 8));
+        assert_eq!(day.average_clients(), 2.0);
+        assert_eq!(stats.total().sessions, 3);
+    }
+
+    #[test]
+    fn test_record_keeps_at_most_max_days() {
+        let mut stats = UsageStats::default();
+        for day in 0..usage_stats::MAX_DAYS + 5 {
+            stats.record(&session(10, 1), &format!("day-{:04}", day));
+        }
+        assert_eq!(stats.days.len(), usage_stats::MAX_DAYS);
+        assert_eq!(stats.days[0].date, "day-0005");
+    }
+}
@@ -0,0 +1,154 @@
+//! X protocol tracing (--trace-x11)
+//!
+//! Logs the requests, replies and events that decide what a thumbnail shows:
+//! source geometry/depth, the pictures and damage bound to the client, every
+//! composite from it, map/unmap/configure of the thumbnail, and the events
+//! and errors about either window. Meant for "my thumbnail is black on WM X"
+//! reports without asking users to install xtrace. With a window id only that
+//! client (and its thumbnail) is traced.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use tracing::info;
+use x11rb::protocol::xproto::Window;
+use x11rb::protocol::Event;
+
+/// Which windows get traced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFilter {
+    All,
+    /// One client window (its thumbnail is traced under the client's id)
+    Window(Window),
+}
+
+impl TraceFilter {
+    /// "all", a decimal window id or a hex one ("0x3c00007", as xwininfo prints it)
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        if value.is_empty() || value.eq_ignore_ascii_case("all") {
+            return Ok(Self::All);
+        }
+        let parsed = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+            Some(hex) => Window::from_str_radix(hex, 16),
+            None => value.parse(),
+        };
+        parsed
+            .map(Self::Window)
+            .map_err(|_| format!("'{}' is not a window id (use e.g. 0x3c00007 or \"all\")", value))
+    }
+
+    fn matches(&self, window: Option<Window>) -> bool {
+        match self {
+            Self::All => true,
+            Self::Window(wanted) => window == Some(*wanted),
+        }
+    }
+}
+
+impl fmt::Display for TraceFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => write!(f, "all"),
+            Self::Window(window) => write!(f, "{:#x}", window),
+        }
+    }
+}
+
+static FILTER: OnceLock<TraceFilter> = OnceLock::new();
+
+/// Turn tracing on for this process (once, at startup)
+pub fn enable(filter: TraceFilter) {
+    if FILTER.set(filter).is_ok() {
+        info!(target: "x11", filter = %filter, "X protocol tracing enabled");
+    }
+}
+
+pub fn active() -> bool {
+    FILTER.get().is_some()
+}
+
+fn traced(window: Option<Window>) -> bool {
+    FILTER.get().is_some_and(|filter| filter.matches(window))
+}
+
+/// Arguments that pass the tracing setting on to a spawned preview daemon
+pub fn daemon_args() -> Vec<String> {
+    match FILTER.get() {
+        Some(filter) => vec!["--trace-x11".to_string(), filter.to_string()],
+        None => Vec::new(),
+    }
+}
+
+/// A request sent on behalf of `client`
+pub fn request(client: Window, request: &str, details: fmt::Arguments) {
+    if traced(Some(client)) {
+        info!(target: "x11", client = format_args!("{:#x}", client), "-> {} {}", request, details);
+    }
+}
+
+/// The reply to a request sent on behalf of `client`
+pub fn reply(client: Window, request: &str, details: fmt::Arguments) {
+    if traced(Some(client)) {
+        info!(target: "x11", client = format_args!("{:#x}", client), "<- {} {}", request, details);
+    }
+}
+
+/// An event, traced under `client` when it concerns one (see `event_window`)
+pub fn event(client: Option<Window>, event: &Event) {
+    if traced(client) {
+        match client {
+            Some(client) => info!(target: "x11", client = format_args!("{:#x}", client), "<- event {:?}", event),
+            None => info!(target: "x11", "<- event {:?}", event),
+        }
+    }
+}
+
+/// The window an event is about (errors report their bad resource id)
+pub fn event_window(event: &Event) -> Option<Window> {
+    Some(match event {
+        Event::DamageNotify(e) => e.drawable,
+        Event::ConfigureNotify(e) => e.window,
+        Event::PropertyNotify(e) => e.window,
+        Event::MapNotify(e) => e.window,
+        Event::UnmapNotify(e) => e.window,
+        Event::DestroyNotify(e) => e.window,
+        Event::ReparentNotify(e) => e.window,
+        Event::CreateNotify(e) => e.window,
+        Event::VisibilityNotify(e) => e.window,
+        Event::Expose(e) => e.window,
+        Event::FocusIn(e) => e.event,
+        Event::FocusOut(e) => e.event,
+        Event::ButtonPress(e) => e.event,
+        Event::ButtonRelease(e) => e.event,
+        Event::MotionNotify(e) => e.event,
+        Event::EnterNotify(e) => e.event,
+        Event::LeaveNotify(e) => e.event,
+        Event::Error(e) => e.bad_value,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(TraceFilter::parse("all"), Ok(TraceFilter::All));
+        assert_eq!(TraceFilter::parse(""), Ok(TraceFilter::All));
+        assert_eq!(TraceFilter::parse("0x3c00007"), Ok(TraceFilter::Window(0x3c00007)));
+        assert_eq!(TraceFilter::parse("62914567"), Ok(TraceFilter::Window(62914567)));
+        assert!(TraceFilter::parse("0xzz").is_err());
+        // Round-trips through the daemon's command line
+        assert_eq!(TraceFilter::parse(&TraceFilter::Window(0x3c00007).to_string()), Ok(TraceFilter::Window(0x3c00007)));
+    }
+
+    #[test]
+    fn test_filter_matches_only_its_window() {
+        assert!(TraceFilter::All.matches(None));
+        assert!(TraceFilter::Window(7).matches(Some(7)));
+        assert!(!TraceFilter::Window(7).matches(Some(8)));
+        assert!(!TraceFilter::Window(7).matches(None));
+    }
+}
@@ -2,33 +2,80 @@ use anyhow::{Context, Result};
 use tracing::debug;
 use x11rb::errors::ReplyError;
 use x11rb::connection::Connection;
+use x11rb::protocol::randr::ConnectionExt as RandrExt;
 use x11rb::protocol::render::{ConnectionExt as RenderExt, Fixed, Pictformat};
 use x11rb::protocol::xproto::*;
 use x11rb::rust_connection::RustConnection;
 
 use crate::config::DisplayConfig;
-use crate::constants::{eve, fixed_point, x11};
+use crate::config::profile::{ClientSwitchAction, LowBandwidth};
+use crate::constants::{fixed_point, x11};
+use crate::mqtt::MqttPublisher;
 use crate::preview::font::FontRenderer;
-use crate::types::EveWindowType;
+use crate::types::{Dimensions, EveWindowType, Position, TitlePattern};
 
 /// Application context holding immutable shared state
+#[derive(Clone, Copy)]
 pub struct AppContext<'a> {
     pub conn: &'a RustConnection,
     pub screen: &'a Screen,
     pub config: &'a DisplayConfig,
     pub atoms: &'a CachedAtoms,
     pub font_renderer: &'a FontRenderer,
+    pub mqtt: &'a MqttPublisher,
+    /// Compositor's toplevel list on wlroots Wayland sessions (None = focus through EWMH)
+    pub toplevels: Option<&'a crate::preview::foreign_toplevel::ForeignToplevels>,
+    /// Hints for the running window manager (wm_preset, re-resolved when settings change)
+    pub wm: &'a std::cell::Cell<crate::preview::wm_compat::WmCompat>,
+    /// Root and frame of the active window the thumbnails stay under (stacking_rules, None = on top)
+    pub stack_below: &'a std::cell::Cell<Option<(Window, Window)>>,
+    /// OpenGL backend, set up the first time a thumbnail asks for it (None = unavailable)
+    #[cfg(feature = "gl")]
+    pub gl: &'a std::cell::OnceCell<Option<crate::preview::gl_backend::GlBackend>>,
+}
+
+impl<'a> AppContext<'a> {
+    /// Same context targeting another X screen (multi-screen / Zaphod setups)
+    pub fn on_screen(&self, screen: &'a Screen) -> Self {
+        Self { screen, ..*self }
+    }
+
+    /// Context for the screen whose root window is `root` (default screen if unknown)
+    pub fn on_root(&self, root: Window) -> Self {
+        let conn: &'a RustConnection = self.conn;
+        match conn.setup().roots.iter().find(|s| s.root == root) {
+            Some(screen) => self.on_screen(screen),
+            None => *self,
+        }
+    }
+}
+
+/// X screen a window lives on, found through its root window
+pub fn screen_of_window(conn: &RustConnection, window: Window) -> Result<&Screen> {
+    let root = conn.get_geometry(window)
+        .context(format!("Failed to send geometry query for window {}", window))?
+        .reply()
+        .context(format!("Failed to get geometry for window {}", window))?
+        .root;
+    conn.setup().roots.iter()
+        .find(|s| s.root == root)
+        .ok_or_else(|| anyhow::anyhow!("Window {} has unknown root {}", window, root))
 }
 
 /// Pre-cached X11 atoms to avoid repeated roundtrips
 pub struct CachedAtoms {
     pub wm_name: Atom,
+    pub net_wm_name: Atom,
+    pub utf8_string: Atom,
     pub net_wm_pid: Atom,
     pub net_wm_state: Atom,
     pub net_wm_state_hidden: Atom,
     pub net_active_window: Atom,
     pub wm_change_state: Atom,
     pub wm_state: Atom,
+    pub motif_wm_hints: Atom,
+    pub net_wm_state_above: Atom,
+    pub net_wm_desktop: Atom,
 }
 
 impl CachedAtoms {
@@ -40,6 +87,16 @@ impl CachedAtoms {
                 .reply()
                 .context("Failed to get reply for WM_NAME atom")?
                 .atom,
+            net_wm_name: conn.intern_atom(false, b"_NET_WM_NAME")
+                .context("Failed to intern _NET_WM_NAME atom")?
+                .reply()
+                .context("Failed to get reply for _NET_WM_NAME atom")?
+                .atom,
+            utf8_string: conn.intern_atom(false, b"UTF8_STRING")
+                .context("Failed to intern UTF8_STRING atom")?
+                .reply()
+                .context("Failed to get reply for UTF8_STRING atom")?
+                .atom,
             net_wm_pid: conn.intern_atom(false, b"_NET_WM_PID")
                 .context("Failed to intern _NET_WM_PID atom")?
                 .reply()
@@ -70,6 +127,21 @@ impl CachedAtoms {
                 .reply()
                 .context("Failed to get reply for WM_STATE atom")?
                 .atom,
+            motif_wm_hints: conn.intern_atom(false, b"_MOTIF_WM_HINTS")
+                .context("Failed to intern _MOTIF_WM_HINTS atom")?
+                .reply()
+                .context("Failed to get reply for _MOTIF_WM_HINTS atom")?
+                .atom,
+            net_wm_state_above: conn.intern_atom(false, b"_NET_WM_STATE_ABOVE")
+                .context("Failed to intern _NET_WM_STATE_ABOVE atom")?
+                .reply()
+                .context("Failed to get reply for _NET_WM_STATE_ABOVE atom")?
+                .atom,
+            net_wm_desktop: conn.intern_atom(false, b"_NET_WM_DESKTOP")
+                .context("Failed to intern _NET_WM_DESKTOP atom")?
+                .reply()
+                .context("Failed to get reply for _NET_WM_DESKTOP atom")?
+                .atom,
         })
     }
 }
@@ -104,30 +176,105 @@ pub fn get_pictformat(conn: &RustConnection, depth: u8, alpha: bool) -> Result<P
     }
 }
 
-pub fn is_window_eve(conn: &RustConnection, window: Window, atoms: &CachedAtoms) -> Result<Option<EveWindowType>> {
-    let cookie = conn
-        .get_property(false, window, atoms.wm_name, AtomEnum::STRING, 0, 1024)
-        .context(format!("Failed to query WM_NAME property for window {}", window))?;
-    let name_prop = match cookie.reply() {
-        Ok(reply) => reply,
-        Err(ReplyError::X11Error(err))
-            if err.error_kind == x11rb::protocol::ErrorKind::Window =>
-        {
-            debug!(window = window, "Window destroyed before WM_NAME reply, skipping");
-            return Ok(None);
+/// Window title, preferring the UTF-8 _NET_WM_NAME over the legacy WM_NAME
+/// (some wine/locale combinations only get _NET_WM_NAME right)
+/// Returns None if the window was destroyed before the reply
+pub fn window_title(conn: &RustConnection, window: Window, atoms: &CachedAtoms) -> Result<Option<String>> {
+    let properties = [
+        (atoms.net_wm_name, atoms.utf8_string, "_NET_WM_NAME"),
+        (atoms.wm_name, AtomEnum::STRING.into(), "WM_NAME"),
+    ];
+    for (property, type_, name) in properties {
+        let cookie = conn
+            .get_property(false, window, property, type_, 0, 1024)
+            .context(format!("Failed to query {} property for window {}", name, window))?;
+        let prop = match cookie.reply() {
+            Ok(reply) => reply,
+            Err(ReplyError::X11Error(err))
+                if err.error_kind == x11rb::protocol::ErrorKind::Window =>
+            {
+                debug!(window = window, property = name, "Window destroyed before title reply, skipping");
+                return Ok(None);
+            }
+            Err(err) => {
+                return Err(err).context(format!("Failed to get {} reply for window {}", name, window));
+            }
+        };
+        if !prop.value.is_empty() {
+            return Ok(Some(String::from_utf8_lossy(&prop.value).into_owned()));
         }
-        Err(err) => {
-            return Err(err).context(format!("Failed to get WM_NAME reply for window {}", window));
+    }
+    Ok(Some(String::new()))
+}
+
+pub fn is_window_eve(conn: &RustConnection, window: Window, atoms: &CachedAtoms, pattern: &TitlePattern) -> Result<Option<EveWindowType>> {
+    let Some(title) = window_title(conn, window, atoms)? else {
+        return Ok(None);
+    };
+    Ok(pattern.parse(&title))
+}
+
+/// Client windows under `root` found by walking the window tree, for window managers
+/// that don't maintain _NET_CLIENT_LIST (minimal WMs, no WM at all)
+/// A window carrying WM_STATE is a client; reparenting WMs keep it inside their frame
+/// windows. Without any WM nothing has WM_STATE, so mapped top-level windows count.
+/// Returned bottom to top in stacking order
+pub fn client_windows_from_tree(
+    conn: &RustConnection,
+    root: Window,
+    atoms: &CachedAtoms,
+) -> Result<Vec<Window>> {
+    use x11rb::protocol::xproto::MapState;
+
+    let children = conn.query_tree(root)
+        .context(format!("Failed to query window tree of root {}", root))?
+        .reply()
+        .context(format!("Failed to get window tree of root {}", root))?
+        .children;
+
+    let mut clients = Vec::new();
+    for child in children {
+        // Windows can disappear while we walk the tree; they just aren't clients
+        let Ok(attributes) = conn.get_window_attributes(child)
+            .context("Failed to query window attributes")?
+            .reply()
+        else {
+            continue;
+        };
+        if attributes.override_redirect {
+            continue;
         }
+        if let Some(client) = find_client_window(conn, child, atoms)? {
+            clients.push(client);
+        } else if attributes.map_state == MapState::VIEWABLE {
+            clients.push(child);
+        }
+    }
+    Ok(clients)
+}
+
+/// `window` or the first window below it that carries WM_STATE
+fn find_client_window(conn: &RustConnection, window: Window, atoms: &CachedAtoms) -> Result<Option<Window>> {
+    let has_wm_state = conn
+        .get_property(false, window, atoms.wm_state, AtomEnum::ANY, 0, 0)
+        .context(format!("Failed to query WM_STATE for window {}", window))?
+        .reply()
+        .is_ok_and(|reply| reply.type_ != x11rb::NONE);
+    if has_wm_state {
+        return Ok(Some(window));
+    }
+    let Ok(tree) = conn.query_tree(window)
+        .context(format!("Failed to query window tree of {}", window))?
+        .reply()
+    else {
+        return Ok(None);
     };
-    let title = String::from_utf8_lossy(&name_prop.value).into_owned();
-    Ok(if let Some(name) = title.strip_prefix(eve::WINDOW_TITLE_PREFIX) {
-        Some(EveWindowType::LoggedIn(name.to_string()))
-    } else if title == eve::LOGGED_OUT_TITLE {
-        Some(EveWindowType::LoggedOut)
-    } else {
-        None
-    })
+    for child in tree.children {
+        if let Some(client) = find_client_window(conn, child, atoms)? {
+            return Ok(Some(client));
+        }
+    }
+    Ok(None)
 }
 
 /// Check whether the given EVE client window is currently minimized/iconified
@@ -196,7 +343,7 @@ pub fn is_window_minimized(
 }
 
 /// Check if the currently focused window is an EVE client
-pub fn is_eve_window_focused(conn: &RustConnection, screen: &Screen, atoms: &CachedAtoms) -> Result<bool> {
+pub fn is_eve_window_focused(conn: &RustConnection, screen: &Screen, atoms: &CachedAtoms, pattern: &TitlePattern) -> Result<bool> {
     // Get the currently active window
     let active_window_prop = conn
         .get_property(
@@ -211,32 +358,130 @@ pub fn is_eve_window_focused(conn: &RustConnection, screen: &Screen, atoms: &Cac
         .reply()
         .context("Failed to get reply for _NET_ACTIVE_WINDOW query")?;
     
-    if active_window_prop.value.len() >= 4 {
-        let active_window = u32::from_ne_bytes(active_window_prop.value[0..4].try_into()
-            .context("Invalid _NET_ACTIVE_WINDOW property format")?);
-        // Check if this window is an EVE client
-        Ok(is_window_eve(conn, active_window, atoms)
-            .context(format!("Failed to check if active window {} is EVE client", active_window))?.is_some())
+    let active_window = if active_window_prop.value.len() >= 4 {
+        u32::from_ne_bytes(active_window_prop.value[0..4].try_into()
+            .context("Invalid _NET_ACTIVE_WINDOW property format")?)
     } else {
-        Ok(false)
+        // No EWMH window manager: ask the server who has the keyboard
+        let focus = conn.get_input_focus()
+            .context("Failed to query input focus")?
+            .reply()
+            .context("Failed to get input focus reply")?
+            .focus;
+        // 0 = None, 1 = PointerRoot
+        if focus <= 1 {
+            return Ok(false);
+        }
+        focus
+    };
+    // Check if this window is an EVE client
+    Ok(is_window_eve(conn, active_window, atoms, pattern)
+        .context(format!("Failed to check if active window {} is EVE client", active_window))?.is_some())
+}
+
+/// Whether a window manager is running on `screen` (the one client allowed to select
+/// SubstructureRedirect on the root). Assumes one is if the root can't be queried
+pub fn window_manager_running(conn: &RustConnection, screen: &Screen) -> bool {
+    use x11rb::protocol::xproto::*;
+
+    conn.get_window_attributes(screen.root)
+        .map_err(anyhow::Error::from)
+        .and_then(|cookie| cookie.reply().map_err(anyhow::Error::from))
+        .map(|attributes| u32::from(attributes.all_event_masks) & u32::from(EventMask::SUBSTRUCTURE_REDIRECT) != 0)
+        .inspect_err(|e| debug!(root = screen.root, error = ?e, "Failed to check for a window manager"))
+        .unwrap_or(true)
+}
+
+/// Geometry of `window` with its position in root coordinates
+/// (differs from get_geometry once a WM reparents it into a frame, see wm_preset)
+pub fn root_geometry(conn: &RustConnection, window: Window) -> Result<GetGeometryReply> {
+    let mut geom = conn.get_geometry(window)
+        .context(format!("Failed to send geometry query for window {}", window))?
+        .reply()
+        .context(format!("Failed to get geometry for window {}", window))?;
+    let origin = conn.translate_coordinates(window, geom.root, 0, 0)
+        .context(format!("Failed to send coordinate translation for window {}", window))?
+        .reply()
+        .context(format!("Failed to translate coordinates of window {}", window))?;
+    geom.x = origin.dst_x - geom.border_width as i16;
+    geom.y = origin.dst_y - geom.border_width as i16;
+    Ok(geom)
+}
+
+/// Name of the EWMH window manager on `screen`, from the _NET_SUPPORTING_WM_CHECK window
+/// None if no EWMH-compliant WM is running (or its check window is stale)
+pub fn window_manager_name(conn: &RustConnection, screen: &Screen, atoms: &CachedAtoms) -> Result<Option<String>> {
+    let check = conn.intern_atom(false, b"_NET_SUPPORTING_WM_CHECK")
+        .context("Failed to intern _NET_SUPPORTING_WM_CHECK atom")?
+        .reply()
+        .context("Failed to get reply for _NET_SUPPORTING_WM_CHECK atom")?
+        .atom;
+    let supporting = |window: Window| -> Result<Option<Window>> {
+        let Ok(prop) = conn.get_property(false, window, check, AtomEnum::WINDOW, 0, 1)
+            .context(format!("Failed to query _NET_SUPPORTING_WM_CHECK on window {}", window))?
+            .reply()
+        else {
+            return Ok(None);
+        };
+        Ok(prop.value32().and_then(|mut values| values.next()))
+    };
+    // The check window points at itself; anything else is left over from a WM that exited
+    let Some(wm_window) = supporting(screen.root)? else {
+        return Ok(None);
+    };
+    if supporting(wm_window)? != Some(wm_window) {
+        return Ok(None);
     }
+    Ok(window_title(conn, wm_window, atoms)?.filter(|name| !name.is_empty()))
+}
+
+/// Whether a compositing manager is running on `screen` (owner of _NET_WM_CM_Sn)
+/// Assumes one is if the selection can't be queried
+pub fn compositor_running(conn: &RustConnection, screen: &Screen) -> bool {
+    let index = conn.setup().roots.iter().position(|s| s.root == screen.root).unwrap_or(0);
+    let selection = format!("_NET_WM_CM_S{}", index);
+    conn.intern_atom(false, selection.as_bytes())
+        .map_err(anyhow::Error::from)
+        .and_then(|cookie| cookie.reply().map_err(anyhow::Error::from))
+        .and_then(|reply| Ok(conn.get_selection_owner(reply.atom)?.reply()?))
+        .map(|owner| owner.owner != x11rb::NONE)
+        .inspect_err(|e| debug!(selection = %selection, error = ?e, "Failed to check for a compositing manager"))
+        .unwrap_or(true)
+}
+
+/// Raise and focus a window ourselves, for X servers without a window manager
+/// (nothing would act on _NET_ACTIVE_WINDOW there)
+pub fn focus_window_directly(conn: &RustConnection, window: Window) -> Result<()> {
+    use x11rb::protocol::xproto::*;
+
+    conn.configure_window(window, &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE))
+        .context(format!("Failed to raise window {}", window))?;
+    conn.set_input_focus(InputFocus::PARENT, window, x11rb::CURRENT_TIME)
+        .context(format!("Failed to focus window {}", window))?;
+    conn.flush()
+        .context("Failed to flush X11 connection after focusing window")?;
+    Ok(())
 }
 
 /// Activate (focus) an X11 window using _NET_ACTIVE_WINDOW
+/// `raise`: restack it on top first (WMs with focus stealing prevention raise it themselves)
 pub fn activate_window(
     conn: &RustConnection,
     screen: &Screen,
     atoms: &CachedAtoms,
     window: Window,
+    raise: bool,
 ) -> Result<()> {
     use x11rb::protocol::xproto::*;
 
     // First, raise the window to top of stack
-    conn.configure_window(
-        window,
-        &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
-    )
-    .context(format!("Failed to raise window {} to top of stack", window))?;
+    if raise {
+        conn.configure_window(
+            window,
+            &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
+        )
+        .context(format!("Failed to raise window {} to top of stack", window))?;
+    }
 
     // Send _NET_ACTIVE_WINDOW client message to root window
     let event = ClientMessageEvent {
@@ -328,3 +573,272 @@ pub fn minimize_window(
         .context("Failed to flush X11 connection after window minimize")?;
     Ok(())
 }
+
+/// Find the top-level frame window the WM reparented a client into
+/// Returns the client itself when it is a direct child of root (no reparenting WM)
+pub fn frame_window(conn: &RustConnection, window: Window) -> Result<Window> {
+    let mut current = window;
+    loop {
+        let tree = conn.query_tree(current)
+            .context(format!("Failed to query window tree for window {}", current))?
+            .reply()
+            .context(format!("Failed to get window tree reply for window {}", current))?;
+        if tree.parent == tree.root || tree.parent == x11rb::NONE {
+            return Ok(current);
+        }
+        current = tree.parent;
+    }
+}
+
+/// List monitor geometries, sorted left to right (then top to bottom)
+/// Falls back to a single monitor covering the whole screen when RandR is unavailable
+pub fn list_monitors(conn: &RustConnection, screen: &Screen) -> Vec<(Position, Dimensions)> {
+    let screen_geometry = vec![(
+        Position::new(0, 0),
+        Dimensions::new(screen.width_in_pixels, screen.height_in_pixels),
+    )];
+    
+    let reply = match conn.randr_get_monitors(screen.root, true).map(|cookie| cookie.reply()) {
+        Ok(Ok(reply)) => reply,
+        Ok(Err(e)) => {
+            debug!(error = %e, "RandR monitor query failed, using full screen geometry");
+            return screen_geometry;
+        }
+        Err(e) => {
+            debug!(error = %e, "RandR unavailable, using full screen geometry");
+            return screen_geometry;
+        }
+    };
+    
+    let mut monitors: Vec<(Position, Dimensions)> = reply.monitors
+        .iter()
+        .map(|m| (Position::new(m.x, m.y), Dimensions::new(m.width, m.height)))
+        .collect();
+    if monitors.is_empty() {
+        return screen_geometry;
+    }
+    monitors.sort_by_key(|(pos, _)| (pos.x, pos.y));
+    monitors
+}
+
+/// Get the geometry of the monitor containing the given root coordinates
+/// Falls back to the first monitor (or the whole screen) when the point is off-screen
+pub fn monitor_geometry_at(conn: &RustConnection, screen: &Screen, x: i16, y: i16) -> (Position, Dimensions) {
+    let monitors = list_monitors(conn, screen);
+    monitors[monitor_index_at(&monitors, x, y)]
+}
+
+/// Index into `monitors` (as from list_monitors) of the one containing the given root coordinates
+/// Falls back to the first monitor when the point is off-screen
+pub fn monitor_index_at(monitors: &[(Position, Dimensions)], x: i16, y: i16) -> usize {
+    monitors
+        .iter()
+        .position(|(pos, size)| {
+            let (mx, my) = (pos.x as i32, pos.y as i32);
+            let (px, py) = (x as i32, y as i32);
+            px >= mx && px < mx + size.width as i32 && py >= my && py < my + size.height as i32
+        })
+        .unwrap_or(0)
+}
+
+/// Set or clear always-on-top via _NET_WM_STATE_ABOVE
+pub fn set_window_above(
+    conn: &RustConnection,
+    screen: &Screen,
+    atoms: &CachedAtoms,
+    window: Window,
+    above: bool,
+) -> Result<()> {
+    let event = ClientMessageEvent {
+        response_type: CLIENT_MESSAGE_EVENT,
+        format: 32,
+        sequence: 0,
+        window,
+        type_: atoms.net_wm_state,
+        data: ClientMessageData::from([
+            if above { x11::NET_WM_STATE_ADD } else { x11::NET_WM_STATE_REMOVE },
+            atoms.net_wm_state_above,
+            0,
+            x11::ACTIVE_WINDOW_SOURCE_PAGER,
+            0,
+        ]),
+    };
+
+    conn.send_event(
+        false,
+        screen.root,
+        EventMask::SUBSTRUCTURE_NOTIFY | EventMask::SUBSTRUCTURE_REDIRECT,
+        event,
+    )
+    .context(format!("Failed to send _NET_WM_STATE_ABOVE event for window {}", window))?;
+    Ok(())
+}
+
+/// Move a window to another virtual desktop via _NET_WM_DESKTOP
+pub fn move_window_to_desktop(
+    conn: &RustConnection,
+    screen: &Screen,
+    atoms: &CachedAtoms,
+    window: Window,
+    desktop: u32,
+) -> Result<()> {
+    let event = ClientMessageEvent {
+        response_type: CLIENT_MESSAGE_EVENT,
+        format: 32,
+        sequence: 0,
+        window,
+        type_: atoms.net_wm_desktop,
+        data: ClientMessageData::from([
+            desktop,
+            x11::ACTIVE_WINDOW_SOURCE_PAGER,
+            0,
+            0,
+            0,
+        ]),
+    };
+
+    conn.send_event(
+        false,
+        screen.root,
+        EventMask::SUBSTRUCTURE_NOTIFY | EventMask::SUBSTRUCTURE_REDIRECT,
+        event,
+    )
+    .context(format!("Failed to send _NET_WM_DESKTOP event for window {}", window))?;
+    Ok(())
+}
+
+/// Push a window to the bottom of the stacking order
+pub fn lower_window(conn: &RustConnection, window: Window) -> Result<()> {
+    conn.configure_window(
+        window,
+        &ConfigureWindowAux::new().stack_mode(StackMode::BELOW),
+    )
+    .context(format!("Failed to lower window {}", window))?;
+    Ok(())
+}
+
+/// Undo a minimize: clear _NET_WM_STATE_HIDDEN and re-map the client (ICCCM de-iconify)
+pub fn unminimize_window(
+    conn: &RustConnection,
+    screen: &Screen,
+    atoms: &CachedAtoms,
+    window: Window,
+) -> Result<()> {
+    let event = ClientMessageEvent {
+        response_type: CLIENT_MESSAGE_EVENT,
+        format: 32,
+        sequence: 0,
+        window,
+        type_: atoms.net_wm_state,
+        data: ClientMessageData::from([
+            x11::NET_WM_STATE_REMOVE,
+            atoms.net_wm_state_hidden,
+            0,
+            x11::ACTIVE_WINDOW_SOURCE_PAGER,
+            0,
+        ]),
+    };
+
+    conn.send_event(
+        false,
+        screen.root,
+        EventMask::SUBSTRUCTURE_NOTIFY | EventMask::SUBSTRUCTURE_REDIRECT,
+        event,
+    )
+    .context(format!("Failed to send _NET_WM_STATE restore event for window {}", window))?;
+
+    conn.map_window(window)
+        .context(format!("Failed to map window {} for de-iconify", window))?;
+    Ok(())
+}
+
+/// Minimize or lower a non-active client after switching to another character
+pub fn apply_switch_action(
+    conn: &RustConnection,
+    screen: &Screen,
+    atoms: &CachedAtoms,
+    window: Window,
+    action: ClientSwitchAction,
+) -> Result<()> {
+    match action {
+        ClientSwitchAction::Minimize => minimize_window(conn, screen, atoms, window),
+        ClientSwitchAction::Lower => {
+            lower_window(conn, window)?;
+            conn.flush()
+                .context("Failed to flush X11 connection after lowering window")?;
+            Ok(())
+        }
+    }
+}
+
+/// Find the keycode that produces `keysym` in the current keyboard mapping
+pub fn keycode_for_keysym(conn: &RustConnection, keysym: Keysym) -> Result<Option<Keycode>> {
+    let setup = conn.setup();
+    let count = setup.max_keycode - setup.min_keycode + 1;
+    let mapping = conn.get_keyboard_mapping(setup.min_keycode, count)
+        .context("Failed to query keyboard mapping")?
+        .reply()
+        .context("Failed to get keyboard mapping reply")?;
+    let per_keycode = mapping.keysyms_per_keycode as usize;
+    if per_keycode == 0 {
+        return Ok(None);
+    }
+    Ok(mapping.keysyms
+        .chunks(per_keycode)
+        .position(|syms| syms.contains(&keysym))
+        .map(|index| setup.min_keycode + index as u8))
+}
+
+/// Whether a DISPLAY string (`[protocol/][host]:display[.screen]`) points at another host
+/// Local sockets (`:0`, `unix:0`, `unix/:0`, launchd paths) are local; any hostname, including
+/// `localhost:10.0` as used by ssh -X forwarding, goes over TCP
+pub fn is_remote_display(display: &str) -> bool {
+    let Some((host, _)) = display.rsplit_once(':') else {
+        return false;
+    };
+    let (protocol, host) = host.split_once('/').map_or(("", host), |(p, h)| (p, h));
+    if host.starts_with('/') || display.starts_with('/') || protocol == "unix" {
+        return false;
+    }
+    !(host.is_empty() || host == "unix")
+}
+
+/// Whether low bandwidth mode is in effect for this daemon's display
+pub fn low_bandwidth_active(mode: LowBandwidth) -> bool {
+    match mode {
+        LowBandwidth::On => true,
+        LowBandwidth::Off => false,
+        LowBandwidth::Auto => std::env::var("DISPLAY").is_ok_and(|display| is_remote_display(&display)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_displays() {
+        for display in [":0", ":1.0", "unix:0", "unix/:0", "/private/tmp/com.apple.launchd.abc/org.xquartz:0"] {
+            assert!(!is_remote_display(display), "{} should be local", display);
+        }
+    }
+
+    #[test]
+    fn test_remote_displays() {
+        for display in ["localhost:10.0", "otherhost:0", "tcp/otherhost:0", "192.168.1.5:0.0"] {
+            assert!(is_remote_display(display), "{} should be remote", display);
+        }
+    }
+
+    #[test]
+    fn test_monitor_index_at() {
+        let monitors = [
+            (Position::new(0, 0), Dimensions::new(1920, 1080)),
+            (Position::new(1920, 0), Dimensions::new(2560, 1440)),
+        ];
+        assert_eq!(monitor_index_at(&monitors, 100, 100), 0);
+        assert_eq!(monitor_index_at(&monitors, 1920, 1200), 1);
+        // Below the smaller monitor, off-screen
+        assert_eq!(monitor_index_at(&monitors, 100, 1200), 0);
+    }
+}